@@ -0,0 +1,214 @@
+//! Hot Path Benchmarks
+//!
+//! Criterion benchmarks for the operations users wait on: token counting,
+//! composing a large persona, bulk JSON import, and full-text search. They
+//! exist so that performance work (connection pooling, caching) can be
+//! measured before and after instead of guessed.
+//!
+//! # Running
+//!
+//! ```text
+//! cargo bench --bench hot_paths                         # full measurement
+//! cargo bench --bench hot_paths -- --save-baseline main # record a baseline
+//! cargo bench --bench hot_paths -- --baseline main      # compare against it
+//! cargo bench --bench hot_paths -- --test               # run each once (CI)
+//! ```
+//!
+//! Every database benchmark runs on a fresh in-memory database with all
+//! migrations applied. Token counting uses the real tokenizer when it is
+//! cached or can be downloaded, and the estimate otherwise.
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+
+use persona_prompt_manager_lib::domain::export::{
+    BulkExport, ExportedToken, PersonaExport, PersonaImportOptions,
+};
+use persona_prompt_manager_lib::domain::persona::Persona;
+use persona_prompt_manager_lib::domain::prompt::{
+    CompositionOptions, PromptComposer, TokenOrder, WeightSyntax,
+};
+use persona_prompt_manager_lib::domain::token::{GranularityLevel, Token, TokenPolarity};
+use persona_prompt_manager_lib::infrastructure::database::repositories::{
+    GranularityRepository, SearchRepository,
+};
+use persona_prompt_manager_lib::infrastructure::{count_tokens, count_tokens_batch, Database};
+use persona_prompt_manager_lib::services::ImportService;
+
+/// Tokens in the large persona used for composition.
+const LARGE_PERSONA_TOKENS: usize = 1_000;
+
+/// Personas in the bulk import document.
+const IMPORT_PERSONAS: usize = 50;
+
+/// Tokens per imported persona.
+const IMPORT_TOKENS_PER_PERSONA: usize = 40;
+
+/// Words combined into token contents, so that search has real matches.
+const WORDS: [&str; 12] = [
+    "red",
+    "long",
+    "hair",
+    "blue",
+    "eyes",
+    "freckles",
+    "silver",
+    "armor",
+    "soft",
+    "lighting",
+    "watercolor",
+    "portrait",
+];
+
+/// Returns a short descriptive content for the `i`-th token.
+fn token_content(i: usize) -> String {
+    format!(
+        "{} {} {i}",
+        WORDS[i % WORDS.len()],
+        WORDS[(i / WORDS.len() + 1) % WORDS.len()]
+    )
+}
+
+/// Returns the seeded granularity levels of a fresh database.
+fn granularity_levels() -> Vec<GranularityLevel> {
+    let db = Database::in_memory().expect("in-memory database");
+    GranularityRepository::find_all(db.connection()).expect("granularity levels")
+}
+
+/// Builds a persona's tokens spread over every level, one in five negative.
+fn large_persona_tokens(levels: &[GranularityLevel]) -> Vec<Token> {
+    (0..LARGE_PERSONA_TOKENS)
+        .map(|i| {
+            let polarity = if i % 5 == 0 {
+                TokenPolarity::Negative
+            } else {
+                TokenPolarity::Positive
+            };
+            Token::new(
+                "persona".to_string(),
+                levels[i % levels.len()].id.clone(),
+                polarity,
+                token_content(i),
+                if i % 3 == 0 { 1.2 } else { 1.0 },
+                i as i32,
+            )
+        })
+        .collect()
+}
+
+/// Builds an export document of `IMPORT_PERSONAS` distinct personas.
+fn bulk_export(levels: &[GranularityLevel]) -> BulkExport {
+    BulkExport::new(
+        (0..IMPORT_PERSONAS)
+            .map(|p| PersonaExport {
+                persona: Persona::new(
+                    format!("Persona {p}"),
+                    Some(format!("A {} character", WORDS[p % WORDS.len()])),
+                    vec!["bench".to_string()],
+                ),
+                generation_params: None,
+                composition_defaults: None,
+                tokens: (0..IMPORT_TOKENS_PER_PERSONA)
+                    .map(|i| ExportedToken {
+                        granularity_id: levels[i % levels.len()].id.clone(),
+                        polarity: TokenPolarity::Positive,
+                        content: token_content(p * IMPORT_TOKENS_PER_PERSONA + i),
+                        weight: 1.0,
+                        display_order: i as i32,
+                    })
+                    .collect(),
+            })
+            .collect(),
+    )
+}
+
+fn bench_token_counting(c: &mut Criterion) {
+    let prompt = (0..75).map(token_content).collect::<Vec<_>>().join(", ");
+    let contents: Vec<String> = (0..100).map(token_content).collect();
+    let texts: Vec<&str> = contents.iter().map(String::as_str).collect();
+
+    let mut group = c.benchmark_group("token_counting");
+    group.bench_function("prompt", |b| {
+        b.iter(|| count_tokens(black_box(&prompt), None))
+    });
+    group.bench_function("batch_100", |b| {
+        b.iter(|| count_tokens_batch(black_box(&texts), None));
+    });
+    group.finish();
+}
+
+fn bench_composition(c: &mut Criterion) {
+    let levels = granularity_levels();
+    let tokens = large_persona_tokens(&levels);
+
+    let mut group = c.benchmark_group("compose_1k_tokens");
+    for (name, options) in [
+        ("global_a1111", CompositionOptions::default()),
+        (
+            "by_granularity_compel",
+            CompositionOptions {
+                order_by: TokenOrder::GranularityThenOrder,
+                weight_syntax: WeightSyntax::Compel,
+                adhoc_positive: Some("masterpiece, best quality".to_string()),
+                ..CompositionOptions::default()
+            },
+        ),
+    ] {
+        group.bench_function(name, |b| {
+            b.iter(|| PromptComposer::compose(black_box(&tokens), &levels, &options, true));
+        });
+    }
+    group.finish();
+}
+
+fn bench_import(c: &mut Criterion) {
+    let levels = granularity_levels();
+    let data = bulk_export(&levels);
+
+    c.bench_function("bulk_import_50_personas", |b| {
+        b.iter_batched(
+            || {
+                (
+                    Database::in_memory().expect("in-memory database"),
+                    data.clone(),
+                )
+            },
+            |(db, data)| {
+                ImportService::import_personas(
+                    db.connection(),
+                    data,
+                    &PersonaImportOptions::default(),
+                )
+                .expect("import")
+            },
+            BatchSize::PerIteration,
+        );
+    });
+}
+
+fn bench_search(c: &mut Criterion) {
+    let levels = granularity_levels();
+    let db = Database::in_memory().expect("in-memory database");
+    ImportService::import_personas(
+        db.connection(),
+        bulk_export(&levels),
+        &PersonaImportOptions::default(),
+    )
+    .expect("import");
+
+    let mut group = c.benchmark_group("search");
+    for query in ["hair", "red ha", "watercolor portrait"] {
+        group.bench_function(query, |b| {
+            b.iter(|| SearchRepository::search(db.connection(), black_box(query), 50, false));
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_token_counting,
+    bench_composition,
+    bench_import,
+    bench_search
+);
+criterion_main!(benches);
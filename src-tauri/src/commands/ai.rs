@@ -10,13 +10,111 @@
 //! - **Google**: gemini-3-flash-preview, gemini-3-pro-preview
 //! - **xAI**: grok-4-1-fast-non-reasoning, grok-4-1-fast-reasoning
 //! - **Ollama**: Local models (Llama 3.2, etc.) - no API key required
+//!
+//! # Rate Limiting
+//!
+//! Generation requests pass through a per-provider [`AiRateLimiter`] so bursts
+//! queue locally instead of triggering HTTP 429 responses. While a request
+//! waits, its queue position is emitted as an `ai-queue-status` event.
+//!
+//! # Debug Logging
+//!
+//! When enabled via [`set_ai_logging_enabled`], prompts and raw responses are
+//! recorded (with API keys redacted) to a rotating log under the app data
+//! directory, readable via [`get_recent_ai_logs`].
+//!
+//! # Banned Terms
+//!
+//! Generated tokens containing a banned term are dropped before they reach
+//! the frontend or the database, and listed in the response's `filtered` field.
+
+use rusqlite::Connection;
+use tauri::{AppHandle, Emitter, State, Window};
 
+use super::emit_persona_changed;
+use super::suggestion::stage_token_response;
 use crate::domain::ai::{
+    normalize_output_language, resolve_output_language, AiCreatedPersona, AiLogEntry,
     AiPersonaGenerationRequest, AiPersonaGenerationResponse, AiProvider, AiProviderConfig,
-    AiProviderMetadata, TokenGenerationRequest, TokenGenerationResponse,
+    AiProviderMetadata, AiQueueStatus, ImageConsistencyReport, StyleTransferProposal,
+    StyleTransferRequest, TokenGenerationRequest, TokenGenerationResponse, AI_QUEUE_STATUS_EVENT,
 };
+use crate::domain::banned_term::BannedTerm;
+use crate::domain::blend::{BlendMode, BlendParent, PersonaBlendDraft, PersonaBlendRequest};
+use crate::domain::constants::DEFAULT_IMAGE_MODEL_ID;
+use crate::domain::events::ChangeKind;
+use crate::domain::naming;
+use crate::domain::persona::{
+    ContentRating, CreatePersonaRequest, GenerationParams, UpdatePersonaRequest,
+};
+use crate::domain::suggestion::SuggestionSource;
+use crate::domain::telemetry::Feature;
+use crate::domain::token::{GeneratedTokenSelection, TokenPolarity};
 use crate::error::AppError;
 use crate::infrastructure::ai;
+use crate::infrastructure::ai::rate_limit::{AiRateLimiter, RateLimitPermit};
+use crate::infrastructure::ai::request_log::AiRequestLog;
+use crate::infrastructure::ai::style_transfer::PersonaTokens;
+use crate::infrastructure::database::repositories::{
+    BannedTermRepository, PersonaRepository, SettingsRepository, TokenRepository,
+};
+use crate::infrastructure::telemetry;
+use crate::AppState;
+
+/// Number of log entries returned by `get_recent_ai_logs` when no limit is given.
+const DEFAULT_AI_LOG_LIMIT: usize = 50;
+
+/// Waits for a rate limit slot, emitting queue position updates to the frontend.
+pub(super) async fn acquire_rate_limit(
+    app: &AppHandle,
+    limiter: &AiRateLimiter,
+    provider: AiProvider,
+) -> Result<RateLimitPermit, AppError> {
+    limiter
+        .acquire(provider, |position| {
+            let _ = app.emit(AI_QUEUE_STATUS_EVENT, AiQueueStatus { provider, position });
+        })
+        .await
+}
+
+/// Returns the language AI-written prose should use.
+///
+/// `preferred` lists the request's and persona's languages, most specific
+/// first; the global setting applies when none is set.
+pub(super) fn load_output_language(
+    conn: &Connection,
+    preferred: &[Option<&str>],
+) -> Result<String, AppError> {
+    let settings = SettingsRepository::load(conn)?;
+    let mut candidates = preferred.to_vec();
+    candidates.push(settings.ai_output_language.as_deref());
+    Ok(resolve_output_language(&candidates))
+}
+
+/// Reads the banned terms and output language before an AI call, releasing
+/// the database lock.
+fn load_generation_inputs(
+    state: &AppState,
+    requested_language: Option<&str>,
+) -> Result<(Vec<BannedTerm>, String), AppError> {
+    let requested_language = normalize_output_language(requested_language)?;
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+    let conn = db.connection();
+    Ok((
+        BannedTermRepository::find_all(conn)?,
+        load_output_language(conn, &[requested_language.as_deref()])?,
+    ))
+}
+
+/// Counts a use of an AI feature once its request has succeeded.
+pub(super) fn record_usage(state: &AppState, feature: Feature) {
+    if let Ok(db) = state.db.lock() {
+        telemetry::record(db.connection(), feature);
+    }
+}
 
 // ============================================================================
 // Persona Generation
@@ -32,7 +130,9 @@ use crate::infrastructure::ai;
 ///
 /// # Arguments
 ///
-/// * `config` - AI provider configuration including provider type, model, and API key
+/// * `state` - Application state containing the database connection (banned terms)
+/// * `config` - AI provider configuration (provider type and model); the API key is
+///   read from the keyring by the backend
 /// * `request` - Generation parameters including:
 ///   - `name`: Persona name (required)
 ///   - `style`: Desired visual style (e.g., "anime", "realistic")
@@ -46,17 +146,433 @@ use crate::infrastructure::ai;
 /// - `description`: Elaborated persona description
 /// - `tags`: Inferred tags from style and description
 /// - `tokens`: Generated tokens organized by granularity
+/// - `filtered`: Generated tokens dropped for containing a banned term
 /// - Provider and model used for attribution
 ///
 /// # Errors
 ///
 /// Returns `AppError::Internal` if the AI request fails or response parsing fails.
 #[tauri::command]
+#[tracing::instrument(skip_all, fields(provider = ?config.provider), err)]
 pub async fn generate_persona_with_ai(
+    app: AppHandle,
+    limiter: State<'_, AiRateLimiter>,
+    log: State<'_, AiRequestLog>,
+    state: State<'_, AppState>,
     config: AiProviderConfig,
     request: AiPersonaGenerationRequest,
 ) -> Result<AiPersonaGenerationResponse, AppError> {
-    ai::generate_persona(&config, &request).await
+    let (banned_terms, output_language) =
+        load_generation_inputs(&state, request.output_language.as_deref())?;
+    let _permit = acquire_rate_limit(&app, &limiter, config.provider).await?;
+    let response =
+        ai::generate_persona(&config, &request, &banned_terms, &output_language, &log).await?;
+    record_usage(&state, Feature::AiGeneratePersona);
+    Ok(response)
+}
+
+/// Generates a persona with AI and saves it, with its tokens, in one step.
+///
+/// The name is checked for uniqueness before the AI call so a conflict does
+/// not waste a request. The persona, its AI configuration, generation
+/// parameters, and tokens are then written in a single transaction: if any
+/// write fails, nothing is saved.
+///
+/// # Arguments
+///
+/// * `window` - Window issuing the command, named in the change event
+/// * `state` - Application state containing the database connection
+/// * `config` - AI provider configuration (provider type and model); the API key is
+///   read from the keyring by the backend
+/// * `request` - Same generation parameters as `generate_persona_with_ai`
+///
+/// # Returns
+///
+/// `AiCreatedPersona` containing the saved persona, its tokens in
+/// AI-recommended order, the per-granularity budgets, and the generated
+/// tokens dropped for containing a banned term.
+///
+/// # Errors
+///
+/// Returns `AppError::Validation` if the name breaks the naming rules or is taken,
+/// `AppError::Internal` if the AI request fails, or `AppError::Database`
+/// if saving fails.
+#[tauri::command]
+#[tracing::instrument(skip_all, fields(provider = ?config.provider), err)]
+pub async fn create_persona_from_ai(
+    app: AppHandle,
+    window: Window,
+    limiter: State<'_, AiRateLimiter>,
+    log: State<'_, AiRequestLog>,
+    state: State<'_, AppState>,
+    config: AiProviderConfig,
+    request: AiPersonaGenerationRequest,
+) -> Result<AiCreatedPersona, AppError> {
+    let name = naming::validate_name(&request.name)?;
+
+    let requested_language = normalize_output_language(request.output_language.as_deref())?;
+
+    let (banned_terms, output_language) = {
+        let db = state
+            .db
+            .lock()
+            .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+        let conn = db.connection();
+        if PersonaRepository::name_exists(conn, &name, None)? {
+            return Err(AppError::Validation(format!(
+                "A persona with name '{name}' already exists"
+            )));
+        }
+        (
+            BannedTermRepository::find_all(conn)?,
+            load_output_language(conn, &[requested_language.as_deref()])?,
+        )
+    };
+
+    let response = {
+        let _permit = acquire_rate_limit(&app, &limiter, config.provider).await?;
+        ai::generate_persona(&config, &request, &banned_terms, &output_language, &log).await?
+    };
+
+    let selections: Vec<GeneratedTokenSelection> = response
+        .tokens
+        .iter()
+        .filter_map(|token| {
+            Some(GeneratedTokenSelection {
+                granularity_id: token.granularity_id.clone()?,
+                polarity: TokenPolarity::Positive,
+                content: token.content.clone(),
+                weight: token.suggested_weight,
+            })
+        })
+        .collect();
+
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+    let created = db.unit_of_work(|conn| {
+        let created = PersonaRepository::create(
+            conn,
+            &CreatePersonaRequest {
+                name,
+                description: Some(request.final_description(&response)),
+                tags: response.tags.clone(),
+                content_rating: ContentRating::General,
+            },
+        )?;
+
+        let persona = PersonaRepository::update(
+            conn,
+            &created.id,
+            &UpdatePersonaRequest {
+                name: None,
+                description: None,
+                tags: None,
+                ai_provider_id: Some(Some(config.provider.id().to_string())),
+                ai_model_id: Some(Some(config.model.clone())),
+                ai_instructions: Some(request.final_instructions(&response)),
+                ai_output_language: requested_language.clone().map(Some),
+                archived: None,
+                color: None,
+                content_rating: None,
+                expected_updated_at: None,
+            },
+        )?;
+
+        PersonaRepository::update_generation_params(
+            conn,
+            &GenerationParams {
+                model_id: request
+                    .image_model_id
+                    .clone()
+                    .unwrap_or_else(|| DEFAULT_IMAGE_MODEL_ID.to_string()),
+                ..GenerationParams::default_for_persona(&persona.id)
+            },
+        )?;
+
+        let tokens = TokenRepository::create_from_selections(conn, &persona.id, &selections)?;
+
+        Ok(AiCreatedPersona {
+            persona,
+            tokens,
+            granularity_budgets: response.granularity_budgets,
+            filtered: response.filtered,
+        })
+    })?;
+    telemetry::record(db.connection(), Feature::AiGeneratePersona);
+
+    emit_persona_changed(&window, &created.persona.id, ChangeKind::Created);
+    Ok(created)
+}
+
+// ============================================================================
+// Style Transfer
+// ============================================================================
+//
+// Adapts one persona's tokens to another persona's visual style.
+
+/// Proposes changes adapting a persona's tokens to another persona's style.
+///
+/// The source persona's Style tokens (and optionally its description) define
+/// the style; the target persona's tokens are rewritten to match it while
+/// keeping the character recognizable. Nothing is saved: the returned
+/// proposal lists per-token changes for the user to review and apply.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection
+/// * `config` - AI provider configuration (provider type and model); the API key is
+///   read from the keyring by the backend
+/// * `request` - Source and target persona IDs, and whether to include the
+///   source description as style context
+///
+/// # Returns
+///
+/// `StyleTransferProposal` with one entry per token the AI would change.
+///
+/// # Errors
+///
+/// Returns `AppError::NotFound` if either persona does not exist,
+/// `AppError::Validation` if both IDs are the same, the source has no style
+/// to transfer, or the target has no tokens, and `AppError::Internal` if the
+/// AI request fails.
+#[tauri::command]
+#[tracing::instrument(skip_all, fields(provider = ?config.provider), err)]
+pub async fn transfer_persona_style(
+    app: AppHandle,
+    limiter: State<'_, AiRateLimiter>,
+    log: State<'_, AiRequestLog>,
+    state: State<'_, AppState>,
+    config: AiProviderConfig,
+    request: StyleTransferRequest,
+) -> Result<StyleTransferProposal, AppError> {
+    if request.source_persona_id == request.target_persona_id {
+        return Err(AppError::Validation(
+            "Source and target personas must be different".to_string(),
+        ));
+    }
+
+    let (source, source_tokens, target, target_tokens, image_model_id, output_language) = {
+        let db = state
+            .db
+            .lock()
+            .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+        let conn = db.connection();
+
+        let source = PersonaRepository::find_by_id(conn, &request.source_persona_id)?;
+        let target = PersonaRepository::find_by_id(conn, &request.target_persona_id)?;
+        let image_model_id = PersonaRepository::find_resolved_generation_params(conn, &target.id)
+            .map_or_else(
+                |_| DEFAULT_IMAGE_MODEL_ID.to_string(),
+                |params| params.model_id,
+            );
+        let output_language = load_output_language(conn, &[target.ai_output_language.as_deref()])?;
+        (
+            source,
+            TokenRepository::find_by_persona(conn, &request.source_persona_id)?,
+            target,
+            TokenRepository::find_by_persona(conn, &request.target_persona_id)?,
+            image_model_id,
+            output_language,
+        )
+    };
+
+    let _permit = acquire_rate_limit(&app, &limiter, config.provider).await?;
+    let proposal = ai::style_transfer::transfer_style(
+        &config,
+        &request,
+        PersonaTokens {
+            persona: &source,
+            tokens: &source_tokens,
+        },
+        PersonaTokens {
+            persona: &target,
+            tokens: &target_tokens,
+        },
+        &image_model_id,
+        &output_language,
+        &log,
+    )
+    .await?;
+    record_usage(&state, Feature::AiStyleTransfer);
+    Ok(proposal)
+}
+
+// ============================================================================
+// Image Consistency
+// ============================================================================
+//
+// Checks a generated image against the persona it was made from.
+
+/// Checks which of a persona's tokens a generated image shows.
+///
+/// The image and the persona's tokens (including inherited ones) are sent to
+/// a vision model, which reports per token whether the image shows it.
+/// Positive tokens should be present and negative tokens absent; the share of
+/// tokens that agree is the consistency score, overall and per granularity.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection
+/// * `config` - AI provider configuration; the model must accept images
+/// * `image_path` - Absolute path of a PNG, JPEG, WebP, or GIF image
+/// * `persona_id` - UUID of the persona to check against
+///
+/// # Returns
+///
+/// `ImageConsistencyReport` with per-token results grouped by granularity.
+///
+/// # Errors
+///
+/// Returns `AppError::NotFound` if the persona does not exist,
+/// `AppError::Validation` if it has no tokens or the image cannot be used,
+/// and `AppError::Internal` if the AI request fails.
+#[tauri::command]
+#[tracing::instrument(skip_all, fields(provider = ?config.provider, persona_id = %persona_id), err)]
+pub async fn verify_image_against_persona(
+    app: AppHandle,
+    limiter: State<'_, AiRateLimiter>,
+    log: State<'_, AiRequestLog>,
+    state: State<'_, AppState>,
+    config: AiProviderConfig,
+    image_path: String,
+    persona_id: String,
+) -> Result<ImageConsistencyReport, AppError> {
+    let (persona, tokens, output_language) = {
+        let db = state
+            .db
+            .lock()
+            .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+        let conn = db.connection();
+
+        let persona = PersonaRepository::find_by_id(conn, &persona_id)?;
+        let output_language = load_output_language(conn, &[persona.ai_output_language.as_deref()])?;
+        (
+            persona,
+            TokenRepository::find_resolved_by_persona(conn, &persona_id)?,
+            output_language,
+        )
+    };
+
+    let _permit = acquire_rate_limit(&app, &limiter, config.provider).await?;
+    let report = ai::consistency::verify_image(
+        &config,
+        PersonaTokens {
+            persona: &persona,
+            tokens: &tokens,
+        },
+        image_path.trim(),
+        &output_language,
+        &log,
+    )
+    .await?;
+    record_usage(&state, Feature::AiVerifyImage);
+    Ok(report)
+}
+
+// ============================================================================
+// Persona Blending
+// ============================================================================
+//
+// Combines several parent personas into a new persona draft.
+
+/// Blends several personas into a new, unsaved persona draft.
+///
+/// In `interleave` mode the parents' tokens are merged deterministically by
+/// weight and no AI request is made. In `ai` mode the configured provider
+/// synthesizes a child persona inheriting traits from each parent.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection
+/// * `config` - AI provider configuration; required for `ai` mode only
+/// * `request` - Parent persona IDs, their weights, the blend mode, and an
+///   optional name and inheritance guidance
+///
+/// # Returns
+///
+/// `PersonaBlendDraft` to review and save with `create_persona` and
+/// `apply_generated_tokens`.
+///
+/// # Errors
+///
+/// Returns `AppError::NotFound` if a parent does not exist,
+/// `AppError::Validation` if the parents or weights are invalid or `ai` mode
+/// has no config, and `AppError::Internal` if the AI request fails.
+#[tauri::command]
+#[tracing::instrument(skip_all, fields(provider = ?config.provider), err)]
+pub async fn blend_personas(
+    app: AppHandle,
+    limiter: State<'_, AiRateLimiter>,
+    log: State<'_, AiRequestLog>,
+    state: State<'_, AppState>,
+    config: Option<AiProviderConfig>,
+    request: PersonaBlendRequest,
+) -> Result<PersonaBlendDraft, AppError> {
+    let weights = request.normalized_weights()?;
+
+    let (loaded, image_model_id, output_language) = {
+        let db = state
+            .db
+            .lock()
+            .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+        let conn = db.connection();
+
+        let mut loaded = Vec::with_capacity(request.persona_ids.len());
+        for id in &request.persona_ids {
+            let persona = PersonaRepository::find_by_id(conn, id)?;
+            let tokens = TokenRepository::find_by_persona(conn, id)?;
+            loaded.push((persona, tokens));
+        }
+
+        // The heaviest parent decides which image model the draft targets
+        let heaviest = weights
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .map_or(0, |(i, _)| i);
+        let image_model_id =
+            PersonaRepository::find_resolved_generation_params(conn, &loaded[heaviest].0.id)
+                .map_or_else(
+                    |_| DEFAULT_IMAGE_MODEL_ID.to_string(),
+                    |params| params.model_id,
+                );
+        let output_language = load_output_language(conn, &[])?;
+
+        (loaded, image_model_id, output_language)
+    };
+
+    let parents: Vec<BlendParent<'_>> = loaded
+        .iter()
+        .zip(&weights)
+        .map(|((persona, tokens), &weight)| BlendParent {
+            persona,
+            tokens,
+            weight,
+        })
+        .collect();
+
+    let draft = match request.mode {
+        BlendMode::Interleave => PersonaBlendDraft::interleave(&request, &parents),
+        BlendMode::Ai => {
+            let config = config.ok_or_else(|| {
+                AppError::Validation("AI blending requires an AI provider".to_string())
+            })?;
+            let _permit = acquire_rate_limit(&app, &limiter, config.provider).await?;
+            ai::blend::synthesize_blend(
+                &config,
+                &request,
+                &parents,
+                &image_model_id,
+                &output_language,
+                &log,
+            )
+            .await?
+        }
+    };
+    record_usage(&state, Feature::AiBlendPersonas);
+    Ok(draft)
 }
 
 // ============================================================================
@@ -71,9 +587,15 @@ pub async fn generate_persona_with_ai(
 /// structured token suggestions. The prompt is optimized for each provider's
 /// strengths (e.g., XML formatting for Claude, JSON mode for GPT).
 ///
+/// When the request names a persona and granularity, the new suggestions are
+/// also staged in that persona's pending suggestions, so they survive a
+/// window reload until accepted or rejected.
+///
 /// # Arguments
 ///
-/// * `config` - AI provider configuration including provider type, model, and API key
+/// * `state` - Application state containing the database connection (banned terms)
+/// * `config` - AI provider configuration (provider type and model); the API key is
+///   read from the keyring by the backend
 /// * `request` - Generation parameters including:
 ///   - Persona name and description for context
 ///   - Target granularity level (e.g., "Hair", "Face")
@@ -81,23 +603,49 @@ pub async fn generate_persona_with_ai(
 ///   - Existing tokens to avoid duplicates
 ///   - Current prompt state for budget awareness
 ///   - Optional custom AI instructions
+///   - Optional persona and granularity IDs to stage the suggestions under
 ///
 /// # Returns
 ///
 /// `TokenGenerationResponse` containing:
 /// - `positive_tokens`: Suggested tokens with weights and rationales
 /// - `negative_tokens`: Suggested exclusion tokens
+/// - `filtered`: Suggestions dropped for containing a banned term
 /// - Provider and model used for attribution
 ///
 /// # Errors
 ///
-/// Returns `AppError::Internal` if the AI request fails or response parsing fails.
+/// Returns `AppError::Internal` if the AI request fails or response parsing fails,
+/// and `AppError::NotFound` if the persona to stage the suggestions for doesn't exist.
 #[tauri::command]
+#[tracing::instrument(skip_all, fields(provider = ?config.provider), err)]
 pub async fn generate_ai_token_suggestions(
+    app: AppHandle,
+    limiter: State<'_, AiRateLimiter>,
+    log: State<'_, AiRequestLog>,
+    state: State<'_, AppState>,
     config: AiProviderConfig,
     request: TokenGenerationRequest,
 ) -> Result<TokenGenerationResponse, AppError> {
-    ai::generate_tokens(&config, &request).await
+    let (banned_terms, output_language) =
+        load_generation_inputs(&state, request.output_language.as_deref())?;
+    let _permit = acquire_rate_limit(&app, &limiter, config.provider).await?;
+    let response =
+        ai::generate_tokens(&config, &request, &banned_terms, &output_language, &log).await?;
+    if let (Some(persona_id), Some(granularity_id)) = (&request.persona_id, &request.granularity_id)
+    {
+        stage_token_response(
+            &app,
+            &state,
+            SuggestionSource::TokenSuggestions,
+            None,
+            persona_id,
+            granularity_id,
+            response.clone(),
+        )?;
+    }
+    record_usage(&state, Feature::AiTokenSuggestions);
+    Ok(response)
 }
 
 // ============================================================================
@@ -108,8 +656,8 @@ pub async fn generate_ai_token_suggestions(
 
 /// Returns the default configuration for an AI provider.
 ///
-/// Creates a new configuration with the provider's default model and no API key.
-/// The frontend uses this as a starting point before adding the user's API key.
+/// Creates a new configuration with the provider's default model. The frontend
+/// uses this as a starting point; API keys are resolved by the backend at call time.
 ///
 /// # Arguments
 ///
@@ -142,3 +690,56 @@ pub fn get_ai_provider_config(provider: AiProvider) -> AiProviderConfig {
 pub fn get_ai_provider_metadata() -> Vec<AiProviderMetadata> {
     AiProvider::all_metadata()
 }
+
+// ============================================================================
+// Debug Logging
+// ============================================================================
+//
+// Opt-in recording of AI exchanges for diagnosing failed requests.
+
+/// Enables or disables the AI request/response debug log.
+///
+/// The setting is held in memory; the frontend persists the preference and
+/// re-applies it on startup.
+///
+/// # Arguments
+///
+/// * `enabled` - Whether AI exchanges should be recorded
+#[tauri::command]
+pub fn set_ai_logging_enabled(log: State<AiRequestLog>, enabled: bool) {
+    log.set_enabled(enabled);
+}
+
+/// Returns the most recent AI debug log entries, newest first.
+///
+/// # Arguments
+///
+/// * `limit` - Maximum number of entries to return (defaults to 50)
+///
+/// # Returns
+///
+/// Recorded exchanges with prompts, raw responses, and errors. API keys are
+/// redacted before entries are written, so none appear here.
+///
+/// # Errors
+///
+/// Returns `AppError::Io` if the log files cannot be read.
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn get_recent_ai_logs(
+    log: State<AiRequestLog>,
+    limit: Option<usize>,
+) -> Result<Vec<AiLogEntry>, AppError> {
+    log.recent(limit.unwrap_or(DEFAULT_AI_LOG_LIMIT))
+}
+
+/// Deletes all AI debug log files.
+///
+/// # Errors
+///
+/// Returns `AppError::Io` if a log file cannot be removed.
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn clear_ai_logs(log: State<AiRequestLog>) -> Result<(), AppError> {
+    log.clear()
+}
@@ -0,0 +1,90 @@
+//! Token Alias Commands
+//!
+//! This module provides Tauri IPC commands for the user's token aliases:
+//! variants of a token and the phrasing they stand for (see `domain::alias`).
+//!
+//! Editing the aliases clears every cached prompt, so the next composition
+//! with `apply_aliases` uses them.
+
+use tauri::State;
+
+use crate::domain::alias::TokenAlias;
+use crate::error::AppError;
+use crate::infrastructure::database::repositories::TokenAliasRepository;
+use crate::AppState;
+
+/// Lists all token aliases, ordered alphabetically by alias.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection
+///
+/// # Returns
+///
+/// Vector of aliases, which may be empty.
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn list_token_aliases(state: State<AppState>) -> Result<Vec<TokenAlias>, AppError> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+    TokenAliasRepository::find_all(db.connection())
+}
+
+/// Adds a token variant and the phrasing it stands for.
+///
+/// Stored tokens are not changed; the alias applies when prompts are composed
+/// with `apply_aliases`, and when prompts are linted or personas compared.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection
+/// * `alias` - The variant to replace (e.g., "ginger hair")
+/// * `canonical` - The phrasing to use instead (e.g., "red hair")
+///
+/// # Returns
+///
+/// The newly created alias.
+///
+/// # Errors
+///
+/// Returns `AppError::Validation` if either text is empty, the alias matches
+/// its phrasing, the alias already exists or is the phrasing of another
+/// alias, or the phrasing is itself an alias.
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn add_token_alias(
+    state: State<AppState>,
+    alias: String,
+    canonical: String,
+) -> Result<TokenAlias, AppError> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+    db.unit_of_work(|conn| TokenAliasRepository::create(conn, &alias, &canonical))
+}
+
+/// Removes a token alias.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection
+/// * `id` - UUID of the alias
+///
+/// # Errors
+///
+/// Returns `AppError::NotFound` if the alias doesn't exist.
+#[tauri::command]
+#[tracing::instrument(skip_all, fields(id = %id), err)]
+pub fn remove_token_alias(state: State<AppState>, id: String) -> Result<(), AppError> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+    db.unit_of_work(|conn| TokenAliasRepository::delete(conn, &id))
+}
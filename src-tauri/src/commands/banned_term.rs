@@ -0,0 +1,84 @@
+//! Banned Term Commands
+//!
+//! This module provides Tauri IPC commands for the user's banned terms: words
+//! and phrases that tokens must not contain (see `domain::banned_term`).
+//!
+//! Editing the list clears every cached prompt, so the next composition
+//! applies it.
+
+use tauri::State;
+
+use crate::domain::banned_term::BannedTerm;
+use crate::error::AppError;
+use crate::infrastructure::database::repositories::BannedTermRepository;
+use crate::AppState;
+
+/// Lists all banned terms, ordered alphabetically.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection
+///
+/// # Returns
+///
+/// Vector of banned terms, which may be empty.
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn list_banned_terms(state: State<AppState>) -> Result<Vec<BannedTerm>, AppError> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+    BannedTermRepository::find_all(db.connection())
+}
+
+/// Adds a word or phrase to the banned terms.
+///
+/// Existing tokens are kept, but are left out of composed prompts while they
+/// contain the term.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection
+/// * `term` - The word or phrase to ban
+///
+/// # Returns
+///
+/// The newly created banned term.
+///
+/// # Errors
+///
+/// Returns `AppError::Validation` if the term has no letter or digit, or is
+/// already banned.
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn add_banned_term(state: State<AppState>, term: String) -> Result<BannedTerm, AppError> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+    db.unit_of_work(|conn| BannedTermRepository::create(conn, &term))
+}
+
+/// Removes a word or phrase from the banned terms.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection
+/// * `id` - UUID of the banned term
+///
+/// # Errors
+///
+/// Returns `AppError::NotFound` if the term doesn't exist.
+#[tauri::command]
+#[tracing::instrument(skip_all, fields(id = %id), err)]
+pub fn remove_banned_term(state: State<AppState>, id: String) -> Result<(), AppError> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+    db.unit_of_work(|conn| BannedTermRepository::delete(conn, &id))
+}
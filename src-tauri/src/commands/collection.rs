@@ -0,0 +1,196 @@
+//! Smart Collection Commands
+//!
+//! This module provides Tauri IPC commands for smart collections: saved persona
+//! queries whose members are computed on demand by [`query_personas`].
+//!
+//! # Recently Used
+//!
+//! Personas have no usage log, so a persona counts as used when it was last
+//! edited or when its prompt was last composed (the prompt cache timestamp),
+//! whichever is later.
+
+use rusqlite::Connection;
+use tauri::State;
+
+use crate::domain::collection::{
+    CreateSmartCollectionRequest, PersonaQuery, SmartCollection, UpdateSmartCollectionRequest,
+};
+use crate::domain::persona::Persona;
+use crate::domain::telemetry::Feature;
+use crate::error::AppError;
+use crate::infrastructure::database::repositories::{
+    PersonaRepository, PromptCacheRepository, SmartCollectionRepository,
+};
+use crate::infrastructure::{safe_mode, telemetry, tokenizer};
+use crate::AppState;
+
+/// Lists all saved smart collections, ordered by name.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection
+///
+/// # Returns
+///
+/// Vector of collection definitions, which may be empty.
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn list_smart_collections(state: State<AppState>) -> Result<Vec<SmartCollection>, AppError> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+    SmartCollectionRepository::find_all(db.connection())
+}
+
+/// Saves a new smart collection.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection
+/// * `request` - Collection name and query
+///
+/// # Returns
+///
+/// The newly created collection.
+///
+/// # Errors
+///
+/// Returns `AppError::Validation` if the name is empty or taken, or the query is invalid.
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn create_smart_collection(
+    state: State<AppState>,
+    request: CreateSmartCollectionRequest,
+) -> Result<SmartCollection, AppError> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+    SmartCollectionRepository::create(db.connection(), &request)
+}
+
+/// Renames a smart collection or replaces its query.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection
+/// * `id` - UUID of the collection to update
+/// * `request` - Fields to change; omitted fields are kept
+///
+/// # Returns
+///
+/// The updated collection.
+///
+/// # Errors
+///
+/// Returns `AppError::NotFound` if the collection doesn't exist.
+/// Returns `AppError::Validation` if the new name is empty or taken, or the query is invalid.
+#[tauri::command]
+#[tracing::instrument(skip_all, fields(id = %id), err)]
+pub fn update_smart_collection(
+    state: State<AppState>,
+    id: String,
+    request: UpdateSmartCollectionRequest,
+) -> Result<SmartCollection, AppError> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+    SmartCollectionRepository::update(db.connection(), &id, &request)
+}
+
+/// Deletes a smart collection. The personas it matched are not affected.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection
+/// * `id` - UUID of the collection to delete
+///
+/// # Errors
+///
+/// Returns `AppError::NotFound` if the collection doesn't exist.
+#[tauri::command]
+#[tracing::instrument(skip_all, fields(id = %id), err)]
+pub fn delete_smart_collection(state: State<AppState>, id: String) -> Result<(), AppError> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+    SmartCollectionRepository::delete(db.connection(), &id)
+}
+
+/// Evaluates a persona query, such as a smart collection's saved query.
+///
+/// The model family is derived from each persona's image model ID using the
+/// same detection as the tokenizer; personas without generation parameters
+/// fall back to the default model.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection
+/// * `query` - The filter to evaluate
+///
+/// # Returns
+///
+/// Matching personas, ordered by creation date (newest first). In safe mode,
+/// mature-rated personas never match.
+///
+/// # Errors
+///
+/// Returns `AppError::Validation` if the query is invalid.
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn query_personas(
+    state: State<AppState>,
+    query: PersonaQuery,
+) -> Result<Vec<Persona>, AppError> {
+    query.validate()?;
+
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+    let personas = matching_personas(db.connection(), &query)?;
+    telemetry::record(db.connection(), Feature::SmartCollection);
+    Ok(personas)
+}
+
+/// Returns the personas matching a query, newest first; in safe mode,
+/// mature-rated personas never match.
+pub(crate) fn matching_personas(
+    conn: &Connection,
+    query: &PersonaQuery,
+) -> Result<Vec<Persona>, AppError> {
+    let now = chrono::Utc::now();
+
+    let mut matching = Vec::new();
+    for persona in PersonaRepository::find_all(conn)?
+        .into_iter()
+        .filter(safe_mode::is_visible)
+    {
+        let model_id = match PersonaRepository::find_resolved_generation_params(conn, &persona.id) {
+            Ok(params) => Some(params.model_id),
+            Err(AppError::NotFound(_)) => None,
+            Err(e) => return Err(e),
+        };
+        let family = tokenizer::get_prompt_context_for_model(model_id.as_deref()).family;
+
+        let last_composed =
+            PromptCacheRepository::find(conn, &persona.id)?.map(|cached| cached.created_at);
+        let last_used_at = last_composed.map_or(persona.updated_at, |composed| {
+            composed.max(persona.updated_at)
+        });
+
+        if query.matches(&persona, &family, last_used_at, now) {
+            matching.push(persona);
+        }
+    }
+
+    Ok(matching)
+}
@@ -16,7 +16,10 @@
 //! # Available Commands
 //!
 //! - [`get_default_image_model_id`] - Default model for image generation
+//! - [`get_api_version`] - Version of the IPC command interface
+//! - [`api_handshake`] - Compatibility check of the frontend's API version
 
+use crate::domain::api_version::{ApiHandshake, ApiVersionInfo};
 use crate::domain::DEFAULT_IMAGE_MODEL_ID;
 
 // ============================================================================
@@ -47,3 +50,50 @@ use crate::domain::DEFAULT_IMAGE_MODEL_ID;
 pub const fn get_default_image_model_id() -> &'static str {
     DEFAULT_IMAGE_MODEL_ID
 }
+
+// ============================================================================
+// IPC API Versioning
+// ============================================================================
+
+/// Returns the version of the IPC command interface this backend serves.
+///
+/// # Example (TypeScript)
+///
+/// ```typescript
+/// const info = await invoke<ApiVersionInfo>('get_api_version');
+/// // Returns: { api_version: 1, min_supported_api_version: 1, app_version: "0.2.1" }
+/// ```
+///
+/// # See Also
+///
+/// - [`crate::domain::api_version`] - The compatibility policy
+#[tauri::command]
+#[must_use]
+pub fn get_api_version() -> ApiVersionInfo {
+    ApiVersionInfo::current()
+}
+
+/// Checks whether a frontend built against `frontend_api_version` can use
+/// this backend.
+///
+/// Called once at startup; mismatches are logged for bug reports.
+///
+/// # Example (TypeScript)
+///
+/// ```typescript
+/// const handshake = await invoke<ApiHandshake>('api_handshake', { frontendApiVersion: 1 });
+/// if (!handshake.usable) showError(handshake.message);
+/// ```
+#[tauri::command]
+#[must_use]
+pub fn api_handshake(frontend_api_version: u32) -> ApiHandshake {
+    let handshake = ApiHandshake::new(frontend_api_version);
+    if let Some(message) = &handshake.message {
+        tracing::warn!(
+            frontend_api_version,
+            compatibility = ?handshake.compatibility,
+            "{message}"
+        );
+    }
+    handshake
+}
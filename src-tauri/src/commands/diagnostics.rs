@@ -0,0 +1,263 @@
+//! Diagnostics Commands
+//!
+//! This module provides Tauri IPC commands for support requests:
+//! [`get_system_diagnostics`] gathers the state of every subsystem into a single
+//! [`SystemDiagnostics`] report, [`get_migration_history`] lists the schema
+//! migrations that ran, [`get_update_report`] tells how the last application
+//! update went, [`repair_token_order`] renumbers token orders the report
+//! flagged, [`set_log_level`] controls how much is logged, and
+//! [`collect_logs_zip`] packs the log files for a bug report.
+//!
+//! # Failure Handling
+//!
+//! Only a database lock failure aborts the report. Every other check that
+//! fails (keyring unavailable, missing database file, etc.) is reported as
+//! unavailable so the rest of the report still comes through.
+
+use std::fs;
+use std::path::Path;
+
+use rusqlite::Connection;
+use tauri::State;
+use tauri_plugin_dialog::DialogExt;
+
+use crate::domain::ai::AiProvider;
+use crate::domain::diagnostics::{
+    DatabaseDiagnostics, MigrationRecord, ProviderDiagnostics, SystemDiagnostics,
+    TokenizerDiagnostics,
+};
+use crate::domain::export::ExportResult;
+use crate::domain::settings::LogLevel;
+use crate::domain::update::UpdateReport;
+use crate::error::AppError;
+use crate::infrastructure::database::migrations::{
+    current_schema_version, read_migration_history, read_schema_version,
+};
+use crate::infrastructure::database::repositories::{
+    SettingsRepository, TokenCountCacheRepository, TokenRepository,
+};
+use crate::infrastructure::logging::AppLogging;
+use crate::infrastructure::{keyring, offline, proxy, tokenizer, update};
+use crate::AppState;
+
+/// Returns the status of every subsystem in one report.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection and path
+///
+/// # Returns
+///
+/// `SystemDiagnostics` covering the database, credential store, tokenizers,
+/// AI provider keys, network mode, and last backup time.
+///
+/// Read-only: personas whose token order has gaps or duplicate display
+/// orders are counted, not repaired (see [`repair_token_order`]).
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn get_system_diagnostics(state: State<AppState>) -> Result<SystemDiagnostics, AppError> {
+    let (database, cached_counts, settings, last_backup_at) = {
+        let db = state
+            .db
+            .lock()
+            .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+        let conn = db.connection();
+        let denormalized_token_orders = TokenRepository::find_denormalized_personas(conn)
+            .map(|persona_ids| persona_ids.len())
+            .unwrap_or_default();
+
+        (
+            database_diagnostics(conn, &state.db_path, denormalized_token_orders),
+            TokenCountCacheRepository::count(conn).unwrap_or_default(),
+            SettingsRepository::load(conn).unwrap_or_default(),
+            SettingsRepository::last_backup_at(conn).unwrap_or_default(),
+        )
+    };
+
+    Ok(SystemDiagnostics {
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        database,
+        credential_store_available: keyring::check_credential_store_available().unwrap_or_default(),
+        tokenizers: TokenizerDiagnostics {
+            loaded: tokenizer::loaded_tokenizer_ids(),
+            loading: tokenizer::loading_tokenizer_ids(),
+            cached_counts,
+        },
+        ai_providers: provider_diagnostics(),
+        offline: offline::is_offline(),
+        proxy_source: proxy::proxy_source(&settings.proxy),
+        last_backup_at,
+    })
+}
+
+/// Renumbers the tokens of every persona whose display orders have gaps or
+/// duplicates.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection
+///
+/// # Returns
+///
+/// The number of personas whose tokens were renumbered.
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn repair_token_order(state: State<AppState>) -> Result<usize, AppError> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+    let repaired = db.unit_of_work(TokenRepository::normalize_all_token_orders)?;
+    tracing::info!(repaired, "Repaired token order");
+    Ok(repaired)
+}
+
+/// Lists the schema migrations that ran on this database, most recent first.
+///
+/// Failed runs are included with their error, and every run made during an
+/// upgrade names the backup taken before it, so a failed upgrade can be
+/// diagnosed and the backup restored.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn get_migration_history(state: State<AppState>) -> Result<Vec<MigrationRecord>, AppError> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+    read_migration_history(db.connection())
+}
+
+/// Returns the record of the last application update, if any.
+///
+/// A `rolled_back` outcome means the new version could not migrate the
+/// database and restored the snapshot taken before the update; the record
+/// names the snapshot and the error.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database path
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn get_update_report(state: State<AppState>) -> Result<Option<UpdateReport>, AppError> {
+    update::read_report(&state.db_path)
+}
+
+/// Changes the log level and stores it for future launches.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection
+/// * `logging` - The installed logging subscriber
+/// * `level` - Minimum level of events to record
+#[tauri::command]
+#[tracing::instrument(skip_all, fields(level = ?level), err)]
+pub fn set_log_level(
+    state: State<AppState>,
+    logging: State<AppLogging>,
+    level: LogLevel,
+) -> Result<(), AppError> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+    let conn = db.connection();
+
+    let mut settings = SettingsRepository::load(conn)?;
+    settings.log_level = level;
+    SettingsRepository::save(conn, &settings)?;
+
+    logging.set_level(level)
+}
+
+/// Saves all log files as a zip archive at a user-selected location.
+///
+/// Opens a native save dialog for the user to choose the destination.
+///
+/// # Arguments
+///
+/// * `app` - Tauri application handle for dialog access
+/// * `logging` - The installed logging subscriber
+///
+/// # Returns
+///
+/// `ExportResult` indicating success with the archive path, or cancellation
+/// (success=false, error=None).
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn collect_logs_zip(
+    app: tauri::AppHandle,
+    logging: State<'_, AppLogging>,
+) -> Result<ExportResult, AppError> {
+    let file_path = app
+        .dialog()
+        .file()
+        .set_title("Save Logs")
+        .set_file_name(format!(
+            "ppm-logs-{}.zip",
+            chrono::Utc::now().format("%Y-%m-%d")
+        ))
+        .add_filter("Zip Archive", &["zip"])
+        .blocking_save_file();
+
+    let Some(file_path) = file_path else {
+        return Ok(ExportResult::cancelled());
+    };
+
+    let dest_path = file_path.as_path().ok_or_else(|| {
+        AppError::Validation("Invalid file path: URL paths are not supported".to_string())
+    })?;
+
+    let archived = logging.write_archive(dest_path)?;
+    tracing::info!(archived, path = %dest_path.display(), "Log archive written");
+
+    Ok(ExportResult::success(
+        dest_path.to_string_lossy().to_string(),
+    ))
+}
+
+/// Collects the database file size and schema versions.
+fn database_diagnostics(
+    conn: &Connection,
+    db_path: &Path,
+    denormalized_token_orders: usize,
+) -> DatabaseDiagnostics {
+    let mut wal_path = db_path.as_os_str().to_owned();
+    wal_path.push("-wal");
+
+    let size_bytes = [db_path, Path::new(&wal_path)]
+        .iter()
+        .filter_map(|path| fs::metadata(path).ok())
+        .map(|metadata| metadata.len())
+        .sum();
+
+    DatabaseDiagnostics {
+        path: db_path.to_string_lossy().to_string(),
+        size_bytes,
+        schema_version: read_schema_version(conn).unwrap_or_default(),
+        expected_schema_version: current_schema_version(),
+        denormalized_token_orders,
+    }
+}
+
+/// Collects API key status for every provider, treating keyring errors as no key.
+fn provider_diagnostics() -> Vec<ProviderDiagnostics> {
+    let stored = keyring::get_providers_with_stored_keys().unwrap_or_default();
+
+    AiProvider::all()
+        .iter()
+        .map(|provider| ProviderDiagnostics {
+            provider: *provider,
+            requires_api_key: provider.requires_api_key(),
+            has_stored_key: stored
+                .iter()
+                .any(|(stored_provider, has_key)| stored_provider == provider && *has_key),
+            has_env_key: keyring::has_env_api_key(provider),
+        })
+        .collect()
+}
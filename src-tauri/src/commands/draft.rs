@@ -0,0 +1,144 @@
+//! Persona Draft Commands
+//!
+//! This module provides Tauri IPC commands for autosaving the create form as
+//! a draft (see `domain::draft`) and turning drafts into personas.
+//!
+//! Drafts are not personas: saving one emits no change event, and its name
+//! is only checked when [`promote_draft`] creates the persona.
+
+use tauri::{State, Window};
+
+use super::emit_persona_changed;
+use crate::domain::draft::{PersonaDraft, SaveDraftRequest};
+use crate::domain::events::ChangeKind;
+use crate::domain::persona::Persona;
+use crate::domain::telemetry::Feature;
+use crate::error::AppError;
+use crate::infrastructure::database::repositories::{DraftRepository, PersonaRepository};
+use crate::infrastructure::telemetry;
+use crate::services::{PersonaService, TokenService};
+use crate::AppState;
+
+/// Autosaves the create form, creating a draft or updating it by ID.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection
+/// * `request` - The form's current contents, with the draft ID after the first save
+///
+/// # Returns
+///
+/// The saved draft; later saves pass its ID.
+///
+/// # Errors
+///
+/// Returns `AppError::NotFound` if the draft to update was promoted or deleted.
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn save_draft(
+    state: State<AppState>,
+    request: SaveDraftRequest,
+) -> Result<PersonaDraft, AppError> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+    let draft = match request.id.clone() {
+        Some(id) => {
+            let mut draft = DraftRepository::find_by_id(db.connection(), &id)?;
+            draft.apply(request);
+            draft
+        }
+        None => PersonaDraft::new(request),
+    };
+    DraftRepository::save(db.connection(), &draft)?;
+    Ok(draft)
+}
+
+/// Lists all drafts, most recently saved first.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection
+///
+/// # Returns
+///
+/// Vector of drafts to resume, which may be empty.
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn list_drafts(state: State<AppState>) -> Result<Vec<PersonaDraft>, AppError> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+    DraftRepository::find_all(db.connection())
+}
+
+/// Creates a persona from a draft and deletes the draft.
+///
+/// The persona and its tokens are saved in one transaction; if the name is
+/// invalid or taken, or a token is rejected, nothing changes and the draft is kept.
+///
+/// # Arguments
+///
+/// * `window` - Window issuing the command, named in the change event
+/// * `state` - Application state containing the database connection
+/// * `id` - UUID of the draft
+///
+/// # Returns
+///
+/// The new persona.
+///
+/// # Errors
+///
+/// Returns `AppError::NotFound` if the draft doesn't exist, or
+/// `AppError::Validation` if the name breaks the naming rules or is taken,
+/// or a token has an unknown granularity or contains a banned term.
+#[tauri::command]
+#[tracing::instrument(skip_all, fields(id = %id), err)]
+pub fn promote_draft(
+    window: Window,
+    state: State<AppState>,
+    id: String,
+) -> Result<Persona, AppError> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+    let persona = db.unit_of_work(|conn| {
+        let draft = DraftRepository::find_by_id(conn, &id)?;
+        let persona = PersonaService::create(conn, draft.to_create_request())?;
+        TokenService::append_selections(conn, &persona.id, &draft.tokens)?;
+        DraftRepository::delete(conn, &draft.id)?;
+        // Re-read for the content hash and timestamps the tokens changed
+        PersonaRepository::find_by_id(conn, &persona.id)
+    })?;
+    telemetry::record(db.connection(), Feature::CreatePersona);
+
+    emit_persona_changed(&window, &persona.id, ChangeKind::Created);
+    Ok(persona)
+}
+
+/// Deletes a draft without creating its persona.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection
+/// * `id` - UUID of the draft
+///
+/// # Errors
+///
+/// Returns `AppError::NotFound` if the draft doesn't exist.
+#[tauri::command]
+#[tracing::instrument(skip_all, fields(id = %id), err)]
+pub fn delete_draft(state: State<AppState>, id: String) -> Result<(), AppError> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+    DraftRepository::delete(db.connection(), &id)
+}
@@ -6,7 +6,8 @@
 //! # Export Behavior
 //!
 //! Export performs a WAL checkpoint to ensure all data is written to the
-//! main database file, then copies it to the user-selected location.
+//! main database file, then copies it to the user-selected location. The
+//! export time is recorded and shown in the system diagnostics.
 //!
 //! # Import Behavior
 //!
@@ -19,18 +20,70 @@
 //! Before importing, the schema version is validated:
 //! - Missing `schema_version` table: Not a valid PPM database
 //! - Schema version > current: Incompatible future version (requires app update)
+//!
+//! # JSON Persona Exchange
+//!
+//! [`export_personas`] and [`import_personas`] exchange selected personas as JSON.
+//! Unlike database import, JSON import merges into the current library.
+//! [`preview_import`] lists the granularity levels a file uses but the library
+//! lacks, so the user can map them before importing. [`get_export_schema`]
+//! publishes the JSON Schema of the format and [`validate_export_json`] checks
+//! a document against it.
+//!
+//! # Section Snippets
+//!
+//! [`export_section_snippet`] copies the tokens of one granularity section of a
+//! persona; [`import_section_snippet`] adds them to another persona, so a hair
+//! style (for example) can be reused between characters.
+//!
+//! # Share-Codes
+//!
+//! [`encode_persona_share_code`] packs a single persona into a compact string
+//! for pasting into chat; [`decode_persona_share_code`] unpacks it into a
+//! `PersonaExport` that can be previewed and then passed to [`import_personas`].
+//! Share-codes opened via `ppm://import?code=…` links arrive the same way; see
+//! [`take_pending_persona_import`] for links that launched the app.
+//!
+//! # Persona Sheets
+//!
+//! [`export_persona_pdf`] prints a persona as a PDF character sheet for
+//! people who don't use the app, such as commission clients. Templates choose
+//! how much of the technical setup the sheet shows (see `domain::sheet`).
+//! [`export_library_html`] publishes selected personas as a static HTML site
+//! with client-side search, for a character wiki.
 
 use std::fs;
 use std::path::Path;
 
 use rusqlite::Connection;
-use tauri::State;
+use tauri::{State, Window};
 use tauri_plugin_dialog::DialogExt;
 
-use crate::domain::export::{ExportResult, ImportResult};
+use super::collection::matching_personas;
+use super::prompt::{allowed_tokens, composition_options};
+use super::{emit_persona_changed, emit_tokens_changed};
+use crate::domain::collation;
+use crate::domain::collection::PersonaQuery;
+use crate::domain::events::ChangeKind;
+use crate::domain::export::{
+    export_json_schema, BulkExport, ExportResult, ExportValidationError, ExportedToken,
+    ImportPreview, ImportResult, PersonaExport, PersonaImportOptions, PersonaImportResult,
+    SectionImportOptions, SectionSnippet,
+};
+use crate::domain::persona::Persona;
+use crate::domain::prompt::PromptComposer;
+use crate::domain::sheet::{CharacterSheet, SheetTemplate};
+use crate::domain::site::{self, SitePersona};
+use crate::domain::telemetry::Feature;
+use crate::domain::token::{Granularity, GranularityLevel, Token};
 use crate::error::AppError;
 use crate::infrastructure::database::migrations::{current_schema_version, read_schema_version};
-use crate::infrastructure::Database;
+use crate::infrastructure::database::repositories::{
+    GeneratedImageRepository, PersonaRepository, SettingsRepository, TokenRepository,
+};
+use crate::infrastructure::deep_link::PendingPersonaImport;
+use crate::infrastructure::{keyring, pdf, safe_mode, telemetry, tokenizer, Database};
+use crate::services::ImportService;
 use crate::AppState;
 
 /// Exports the database to a user-selected location.
@@ -48,6 +101,7 @@ use crate::AppState;
 /// `ExportResult` indicating success with file path, failure with error,
 /// or cancellation (success=false, error=None).
 #[tauri::command]
+#[tracing::instrument(skip_all, err)]
 pub async fn export_database(
     app: tauri::AppHandle,
     state: State<'_, AppState>,
@@ -88,6 +142,14 @@ pub async fn export_database(
     // Copy database file to destination
     fs::copy(&state.db_path, dest_path)?;
 
+    {
+        let db = state
+            .db
+            .lock()
+            .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+        SettingsRepository::record_backup(db.connection())?;
+    }
+
     Ok(ExportResult::success(dest_path.to_string_lossy().to_string()))
 }
 
@@ -97,7 +159,9 @@ pub async fn export_database(
 /// - No `schema_version` table (not a PPM database)
 /// - Schema version higher than current (incompatible future version)
 ///
-/// Replaces the current database and reopens the connection.
+/// Replaces the current database and reopens the connection. An encrypted file
+/// must use the library's passphrase; a plaintext file imported into an
+/// encrypted library is encrypted with it.
 ///
 /// # Arguments
 ///
@@ -108,6 +172,7 @@ pub async fn export_database(
 ///
 /// `ImportResult` indicating success with persona count, or failure with error.
 #[tauri::command]
+#[tracing::instrument(skip_all, err)]
 pub async fn import_database(
     app: tauri::AppHandle,
     state: State<'_, AppState>,
@@ -128,8 +193,24 @@ pub async fn import_database(
         AppError::Validation("Invalid file path: URL paths are not supported".to_string())
     })?;
 
+    // An encrypted file can only be opened with the library's own passphrase
+    let library_passphrase = if Database::is_encrypted_file(&state.db_path)? {
+        keyring::get_database_passphrase()?
+    } else {
+        None
+    };
+    let source_passphrase = if Database::is_encrypted_file(source_path)? {
+        Some(library_passphrase.clone().ok_or_else(|| {
+            AppError::Validation(
+                "The database is encrypted; remove its passphrase before importing it".to_string(),
+            )
+        })?)
+    } else {
+        None
+    };
+
     // Validate the imported database
-    let personas_count = validate_and_count_personas(source_path)?;
+    let personas_count = validate_and_count_personas(source_path, source_passphrase.as_deref())?;
 
     // Close current database connection and replace the file
     {
@@ -140,6 +221,7 @@ pub async fn import_database(
 
         // Copy the imported database over the current one
         fs::copy(source_path, &state.db_path)?;
+        state.metadata.invalidate();
 
         // Remove any WAL/SHM files from the old database
         let wal_path = state.db_path.with_extension("db-wal");
@@ -147,8 +229,18 @@ pub async fn import_database(
         let _ = fs::remove_file(wal_path); // Ignore errors if files don't exist
         let _ = fs::remove_file(shm_path);
 
-        // Reopen the database connection
-        *db = Database::new(&state.db_path)?;
+        // Reopen the database connection, keeping the library encrypted if it was
+        *db = Database::new(&state.db_path, source_passphrase.as_deref())?;
+        if let (None, Some(passphrase)) = (&source_passphrase, &library_passphrase) {
+            db.rekey(&state.db_path, None, Some(passphrase))?;
+        }
+
+        // The file is already in place; a failed repair is left to the health check
+        match db.unit_of_work(TokenRepository::normalize_all_token_orders) {
+            Ok(0) => {}
+            Ok(repaired) => tracing::info!(repaired, "Normalized token order after import"),
+            Err(e) => tracing::warn!(error = %e, "Failed to normalize token order after import"),
+        }
     }
 
     Ok(ImportResult::success(personas_count))
@@ -163,9 +255,12 @@ pub async fn import_database(
 /// 4. `personas` table exists
 ///
 /// Returns the count of personas in the database.
-fn validate_and_count_personas(path: &Path) -> Result<usize, AppError> {
+fn validate_and_count_personas(path: &Path, passphrase: Option<&str>) -> Result<usize, AppError> {
     // Open the imported database read-only
     let conn = Connection::open_with_flags(path, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    if let Some(passphrase) = passphrase {
+        conn.pragma_update(None, "key", passphrase)?;
+    }
 
     // Check schema version
     let schema_version = read_schema_version(&conn)?;
@@ -198,3 +293,630 @@ fn validate_and_count_personas(path: &Path) -> Result<usize, AppError> {
     // Safe conversion: COUNT(*) is always non-negative
     Ok(usize::try_from(count).unwrap_or(0))
 }
+
+/// Exports personas with their generation parameters and tokens as JSON data.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection
+/// * `persona_ids` - Personas to export; an empty list exports the whole library
+///
+/// # Returns
+///
+/// A `BulkExport` document the frontend can write to disk.
+///
+/// # Errors
+///
+/// Returns `AppError::NotFound` if any requested persona does not exist.
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn export_personas(
+    state: State<AppState>,
+    persona_ids: Vec<String>,
+) -> Result<BulkExport, AppError> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+    let conn = db.connection();
+
+    let personas = if persona_ids.is_empty() {
+        PersonaRepository::find_all(conn)?
+    } else {
+        persona_ids
+            .iter()
+            .map(|id| PersonaRepository::find_by_id(conn, id))
+            .collect::<Result<Vec<_>, _>>()?
+    };
+
+    let exports = personas
+        .into_iter()
+        .map(|persona| build_persona_export(conn, persona))
+        .collect::<Result<Vec<_>, _>>()?;
+    telemetry::record(conn, Feature::ExportPersonas);
+
+    Ok(BulkExport::new(exports))
+}
+
+/// Collects a persona's generation parameters, composition defaults, and tokens
+/// into an export entry.
+///
+/// Variants are exported with what they inherit resolved, so the entry stands
+/// on its own without the base persona.
+pub(crate) fn build_persona_export(
+    conn: &Connection,
+    mut persona: Persona,
+) -> Result<PersonaExport, AppError> {
+    let generation_params =
+        PersonaRepository::find_resolved_generation_params(conn, &persona.id).ok();
+    let composition_defaults = PersonaRepository::find_composition_defaults(conn, &persona.id)?;
+    let tokens = TokenRepository::find_resolved_by_persona(conn, &persona.id)?
+        .into_iter()
+        .map(|t| ExportedToken {
+            granularity_id: t.granularity_id,
+            polarity: t.polarity,
+            content: t.content,
+            weight: t.weight,
+            display_order: t.display_order,
+        })
+        .collect();
+
+    persona.base_persona_id = None;
+    persona.inherited_granularities.clear();
+    persona.param_overrides.clear();
+
+    Ok(PersonaExport {
+        persona,
+        generation_params,
+        composition_defaults,
+        tokens,
+    })
+}
+
+/// Returns the JSON Schema of the JSON persona export format.
+///
+/// The schema is generated from the export types, so it always matches what
+/// [`import_personas`] accepts.
+#[tauri::command]
+#[must_use]
+pub fn get_export_schema() -> serde_json::Value {
+    export_json_schema()
+}
+
+/// Validates a JSON persona export without importing it.
+///
+/// # Arguments
+///
+/// * `json` - The export document as text
+///
+/// # Returns
+///
+/// Every problem found with the JSON path of the offending value; empty if
+/// the document is valid.
+///
+/// # Errors
+///
+/// Returns `AppError::Internal` if the export schema cannot be compiled.
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn validate_export_json(json: String) -> Result<Vec<ExportValidationError>, AppError> {
+    crate::domain::export::validate_export_json(&json)
+}
+
+/// Previews a JSON persona import without changing anything.
+///
+/// Lists the granularity levels the file uses but the library lacks; each
+/// must be given a target in `granularity_mapping` before [`import_personas`]
+/// accepts the file.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection
+/// * `data` - The export document to preview
+///
+/// # Returns
+///
+/// `ImportPreview` with counts, personas that already exist, and unknown levels.
+///
+/// # Errors
+///
+/// Returns `AppError::Validation` if the export format version is unsupported.
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn preview_import(state: State<AppState>, data: BulkExport) -> Result<ImportPreview, AppError> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+    let conn = db.connection();
+    let levels = state.metadata.granularity_levels(conn)?;
+    ImportService::preview(conn, &data, &levels)
+}
+
+/// Imports personas from a JSON export into the current library.
+///
+/// Each persona is created under a new ID. Name conflicts are resolved by
+/// appending "(Imported)" or "(Imported N)". When `skip_duplicates` is set,
+/// personas whose content hash (description + tokens) matches an existing
+/// persona are skipped even if they were renamed.
+///
+/// When `update_existing` is set, an entry whose UUID matches an existing
+/// persona replaces that persona's fields, generation parameters, composition
+/// defaults, and tokens instead; a rename in the export is followed when the
+/// new name is free.
+///
+/// Tokens of granularity levels the library lacks are moved or given a new
+/// custom level as set in `granularity_mapping` (see [`preview_import`]).
+///
+/// The whole import runs in a single transaction.
+///
+/// # Arguments
+///
+/// * `window` - Window issuing the command, named in the change events
+/// * `state` - Application state containing the database connection
+/// * `data` - The export document to import
+/// * `options` - Import options (defaults: import everything)
+///
+/// # Returns
+///
+/// `PersonaImportResult` listing created and updated personas and skipped entries.
+///
+/// # Errors
+///
+/// Returns `AppError::Validation` if the export format version is unsupported,
+/// or an unknown granularity level is unmapped or mapped to a missing level.
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn import_personas(
+    window: Window,
+    state: State<AppState>,
+    data: BulkExport,
+    options: Option<PersonaImportOptions>,
+) -> Result<PersonaImportResult, AppError> {
+    let options = options.unwrap_or_default();
+
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+    let result = ImportService::import_personas(db.connection(), data, &options)?;
+    // The mapping may have created custom levels
+    state.metadata.invalidate();
+    telemetry::record(db.connection(), Feature::ImportPersonas);
+
+    for persona in &result.imported {
+        emit_persona_changed(&window, &persona.id, ChangeKind::Created);
+    }
+    for persona in &result.updated {
+        emit_persona_changed(&window, &persona.id, ChangeKind::Updated);
+    }
+    Ok(result)
+}
+
+/// Exports the tokens of one granularity section of a persona.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection
+/// * `persona_id` - UUID of the persona to copy from
+/// * `granularity_id` - Section to copy (e.g., "hair")
+///
+/// # Returns
+///
+/// A `SectionSnippet` the frontend can copy to the clipboard or write to disk.
+///
+/// # Errors
+///
+/// Returns `AppError::NotFound` if the persona does not exist.
+/// Returns `AppError::Validation` if the granularity is unknown.
+#[tauri::command]
+#[tracing::instrument(skip_all, fields(persona_id = %persona_id), err)]
+pub fn export_section_snippet(
+    state: State<AppState>,
+    persona_id: String,
+    granularity_id: String,
+) -> Result<SectionSnippet, AppError> {
+    if Granularity::parse(&granularity_id).is_none() {
+        return Err(AppError::Validation(format!(
+            "Unknown granularity '{granularity_id}'"
+        )));
+    }
+
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+    let conn = db.connection();
+
+    let persona = PersonaRepository::find_by_id(conn, &persona_id)?;
+    let tokens = TokenRepository::find_resolved_by_persona(conn, &persona_id)?
+        .into_iter()
+        .filter(|t| t.granularity_id == granularity_id)
+        .map(|t| ExportedToken {
+            granularity_id: t.granularity_id,
+            polarity: t.polarity,
+            content: t.content,
+            weight: t.weight,
+            display_order: t.display_order,
+        })
+        .collect();
+
+    Ok(SectionSnippet::new(persona.name, granularity_id, tokens))
+}
+
+/// Imports a section snippet into a persona.
+///
+/// The snippet's tokens are appended to the persona in the snippet's section,
+/// keeping their relative order. Tokens the section already contains (same
+/// polarity and content) are skipped. With `replace`, the section is emptied
+/// first, so the result matches the source persona's section.
+///
+/// # Arguments
+///
+/// * `window` - Window issuing the command, named in the change event
+/// * `state` - Application state containing the database connection
+/// * `persona_id` - UUID of the persona to import into
+/// * `snippet` - Snippet produced by `export_section_snippet`
+/// * `options` - Import options (default: keep existing tokens)
+///
+/// # Returns
+///
+/// The tokens created by the import.
+///
+/// # Errors
+///
+/// Returns `AppError::NotFound` if the persona does not exist.
+/// Returns `AppError::Validation` if the snippet version or granularity is unsupported.
+#[tauri::command]
+#[tracing::instrument(skip_all, fields(persona_id = %persona_id), err)]
+pub fn import_section_snippet(
+    window: Window,
+    state: State<AppState>,
+    persona_id: String,
+    snippet: SectionSnippet,
+    options: Option<SectionImportOptions>,
+) -> Result<Vec<Token>, AppError> {
+    let options = options.unwrap_or_default();
+
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+    let created = ImportService::import_section(db.connection(), &persona_id, snippet, &options)?;
+
+    emit_tokens_changed(&window, &persona_id);
+    Ok(created)
+}
+
+/// Encodes a single persona as a compact share-code.
+///
+/// The code contains the persona's metadata, generation parameters, and tokens,
+/// compressed and base64url-encoded so it can be pasted into chat.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection
+/// * `persona_id` - UUID of the persona to share
+///
+/// # Returns
+///
+/// A share-code string starting with `ppm1.`.
+///
+/// # Errors
+///
+/// Returns `AppError::NotFound` if the persona does not exist.
+#[tauri::command]
+#[tracing::instrument(skip_all, fields(persona_id = %persona_id), err)]
+pub fn encode_persona_share_code(
+    state: State<AppState>,
+    persona_id: String,
+) -> Result<String, AppError> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+    let conn = db.connection();
+
+    let persona = PersonaRepository::find_by_id(conn, &persona_id)?;
+    let code = build_persona_export(conn, persona)?.to_share_code()?;
+    telemetry::record(conn, Feature::ShareCode);
+    Ok(code)
+}
+
+/// Decodes a share-code into a persona export for preview.
+///
+/// Nothing is written to the database; pass the result to `import_personas`
+/// to add it to the library.
+///
+/// # Arguments
+///
+/// * `code` - Share-code produced by `encode_persona_share_code`
+///
+/// # Errors
+///
+/// Returns `AppError::Validation` if the code is malformed.
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn decode_persona_share_code(code: String) -> Result<PersonaExport, AppError> {
+    PersonaExport::from_share_code(&code)
+}
+
+/// Returns the persona import requested by the deep link that launched the app.
+///
+/// The frontend calls this once on startup; later `ppm://import` links are
+/// delivered through the `persona-import-requested` event instead. The pending
+/// import is cleared after it has been returned.
+///
+/// # Returns
+///
+/// The decoded persona export, or `None` if the app was not opened via a link.
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn take_pending_persona_import(
+    pending: State<PendingPersonaImport>,
+) -> Result<Option<PersonaExport>, AppError> {
+    let mut pending = pending
+        .0
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire pending import lock".to_string()))?;
+
+    Ok(pending.take())
+}
+
+/// Exports a persona as a printable PDF character sheet.
+///
+/// The sheet shows the persona's metadata, reference images from its
+/// gallery (favorites first), token tables, and composed prompts, as the
+/// template allows. Prompts are composed with the persona's composition
+/// defaults. Gallery images other than PNG and JPEG are left out.
+///
+/// # Arguments
+///
+/// * `app` - Tauri app handle for the save dialog
+/// * `state` - Application state containing the database connection
+/// * `persona_id` - UUID of the persona to print
+/// * `template` - Which parts of the persona to show (default: full)
+///
+/// # Returns
+///
+/// `ExportResult` with the path of the written file, or cancellation if the
+/// user closed the save dialog.
+///
+/// # Errors
+///
+/// Returns `AppError::NotFound` if the persona does not exist or is hidden by
+/// safe mode, or `AppError::Io` if the file cannot be written.
+#[tauri::command]
+#[tracing::instrument(skip_all, fields(persona_id = %persona_id), err)]
+pub async fn export_persona_pdf(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    persona_id: String,
+    template: Option<SheetTemplate>,
+) -> Result<ExportResult, AppError> {
+    let sheet = {
+        let db = state
+            .db
+            .lock()
+            .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+        let conn = db.connection();
+
+        let persona = PersonaRepository::find_by_id(conn, &persona_id)?;
+        safe_mode::ensure_visible(&persona)?;
+        let granularity_levels = state.metadata.granularity_levels(conn)?;
+        persona_sheet(
+            conn,
+            &persona,
+            &granularity_levels,
+            template.unwrap_or_default(),
+        )?
+    };
+
+    let file_path = app
+        .dialog()
+        .file()
+        .set_title("Export Persona Sheet")
+        .set_file_name(sheet.file_name())
+        .add_filter("PDF Document", &["pdf"])
+        .blocking_save_file();
+
+    let Some(file_path) = file_path else {
+        return Ok(ExportResult::cancelled());
+    };
+
+    let dest_path = file_path.as_path().ok_or_else(|| {
+        AppError::Validation("Invalid file path: URL paths are not supported".to_string())
+    })?;
+
+    fs::write(dest_path, pdf::render_sheet(&sheet))?;
+
+    {
+        let db = state
+            .db
+            .lock()
+            .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+        telemetry::record(db.connection(), Feature::PersonaSheet);
+    }
+
+    Ok(ExportResult::success(
+        dest_path.to_string_lossy().to_string(),
+    ))
+}
+
+/// Exports selected personas as a static HTML site.
+///
+/// The site has an index page with a search box and one page per persona
+/// with its reference images, token tables, and composed prompts; it can be
+/// published on any web host (see `domain::site`). Generation parameters,
+/// file paths, and AI settings are not written.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection
+/// * `path` - Folder to write the site to; created if missing. It must be
+///   empty or hold an earlier export, whose pages and images are replaced
+/// * `filter` - Which personas to publish (default: all unarchived)
+///
+/// # Returns
+///
+/// `ExportResult` with the path of the site's `index.html`.
+///
+/// # Errors
+///
+/// Returns `AppError::Validation` if the folder holds other files, the
+/// filter is invalid, or no persona matches it, or `AppError::Io` if the
+/// site cannot be written.
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn export_library_html(
+    state: State<AppState>,
+    path: String,
+    filter: Option<PersonaQuery>,
+) -> Result<ExportResult, AppError> {
+    let filter = filter.unwrap_or_default();
+    filter.validate()?;
+
+    if path.trim().is_empty() {
+        return Err(AppError::Validation(
+            "Choose a folder to export the site to".to_string(),
+        ));
+    }
+    let root = Path::new(path.trim());
+    let index_path = root.join("index.html");
+    if root.exists() {
+        if !root.is_dir() {
+            return Err(AppError::Validation(format!(
+                "'{}' is not a folder",
+                root.display()
+            )));
+        }
+        let earlier_export =
+            fs::read_to_string(&index_path).is_ok_and(|html| site::is_site_index(&html));
+        if earlier_export {
+            for dir in [site::PAGES_DIR, site::IMAGES_DIR] {
+                if root.join(dir).is_dir() {
+                    fs::remove_dir_all(root.join(dir))?;
+                }
+            }
+        } else if fs::read_dir(root)?.next().is_some() {
+            return Err(AppError::Validation(
+                "Choose an empty folder or the folder of an earlier site export".to_string(),
+            ));
+        }
+    }
+
+    let mut personas = {
+        let db = state
+            .db
+            .lock()
+            .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+        let conn = db.connection();
+
+        let granularity_levels = state.metadata.granularity_levels(conn)?;
+        let matching = matching_personas(conn, &filter)?;
+        site_personas(conn, &granularity_levels, matching)?
+    };
+    if personas.is_empty() {
+        return Err(AppError::Validation(
+            "No persona matches the filter".to_string(),
+        ));
+    }
+
+    fs::create_dir_all(root.join(site::PAGES_DIR))?;
+    fs::create_dir_all(root.join(site::IMAGES_DIR))?;
+    for persona in &mut personas {
+        for (image, source) in persona.image_sources() {
+            match fs::copy(&source, root.join(&image)) {
+                Ok(_) => persona.images.push(image),
+                Err(e) => {
+                    tracing::warn!(path = %source, error = %e, "Leaving image off library site");
+                }
+            }
+        }
+    }
+
+    let generated_at = chrono::Utc::now();
+    for persona in &personas {
+        fs::write(
+            root.join(&persona.page),
+            site::render_page(persona, generated_at),
+        )?;
+    }
+    fs::write(root.join("style.css"), site::STYLESHEET)?;
+    fs::write(&index_path, site::render_index(&personas, generated_at))?;
+
+    {
+        let db = state
+            .db
+            .lock()
+            .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+        telemetry::record(db.connection(), Feature::LibrarySite);
+    }
+    tracing::info!(personas = personas.len(), "Exported library site");
+
+    Ok(ExportResult::success(
+        index_path.to_string_lossy().to_string(),
+    ))
+}
+
+/// Prepares personas for a library site, sorted by name, without their
+/// generation parameters. Their images are not attached yet.
+pub(crate) fn site_personas(
+    conn: &Connection,
+    granularity_levels: &[GranularityLevel],
+    mut personas: Vec<Persona>,
+) -> Result<Vec<SitePersona>, AppError> {
+    personas.sort_by(|a, b| collation::compare(&a.name, &b.name));
+    personas
+        .iter()
+        .map(|persona| {
+            let mut sheet = persona_sheet(conn, persona, granularity_levels, SheetTemplate::Full)?;
+            sheet.parameters.clear();
+            Ok(SitePersona::new(&persona.id, sheet))
+        })
+        .collect()
+}
+
+/// Collects the content of a persona's sheet: tokens and prompt as composed
+/// with its composition defaults, and its gallery.
+fn persona_sheet(
+    conn: &Connection,
+    persona: &Persona,
+    granularity_levels: &[GranularityLevel],
+    template: SheetTemplate,
+) -> Result<CharacterSheet, AppError> {
+    let params = match PersonaRepository::find_resolved_generation_params(conn, &persona.id) {
+        Ok(params) => Some(params),
+        Err(AppError::NotFound(_)) => None,
+        Err(e) => return Err(e),
+    };
+    let options = composition_options(conn, &persona.id, None)?;
+    let (tokens, _) = allowed_tokens(conn, &persona.id, &options)?;
+    let context =
+        tokenizer::get_prompt_context_for_model(params.as_ref().map(|p| p.model_id.as_str()));
+    let prompt = PromptComposer::compose(
+        &tokens,
+        granularity_levels,
+        &options,
+        context.supports_negative_prompt,
+    );
+    let images = GeneratedImageRepository::find_by_persona(conn, &persona.id, false)?;
+
+    Ok(CharacterSheet::new(
+        template,
+        persona,
+        &tokens,
+        granularity_levels,
+        &prompt,
+        params.as_ref(),
+        &images,
+    ))
+}
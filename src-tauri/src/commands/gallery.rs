@@ -0,0 +1,308 @@
+//! Result Gallery Commands
+//!
+//! This module provides Tauri IPC commands for the result gallery: images
+//! generated from a persona, with the prompt hash, seed, and parameters that
+//! produced them (see `domain::gallery`).
+//!
+//! Only gallery entries are managed here; image files are never modified or
+//! deleted.
+//!
+//! [`export_reproduction_bundle`] packs an entry with its prompts and the
+//! persona snapshot taken when it was attached.
+//!
+//! [`analyze_gallery_consistency`] groups a persona's images by face with the
+//! optional local face embedder, to detect visual drift between generations.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use chrono::{Duration, Utc};
+use tauri::State;
+
+use super::export::build_persona_export;
+use crate::domain::face::{
+    validate_face_similarity, GalleryConsistencyReport, DEFAULT_FACE_SIMILARITY,
+};
+use crate::domain::gallery::{AttachImageRequest, GeneratedImage, ReproductionBundle};
+use crate::error::AppError;
+use crate::infrastructure::database::repositories::{
+    GeneratedImageRepository, PersonaRepository, SettingsRepository,
+};
+use crate::infrastructure::face_embedder;
+use crate::AppState;
+
+/// Adds an existing image file to a persona's gallery.
+///
+/// Without explicit parameters, the persona's current generation parameters
+/// (including inherited ones) are recorded. The prompts and a snapshot of the
+/// persona are kept for `export_reproduction_bundle`.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection
+/// * `request` - The persona, image path, prompts, and optional seed and
+///   parameters
+///
+/// # Returns
+///
+/// The new gallery entry.
+///
+/// # Errors
+///
+/// Returns `AppError::NotFound` if the persona doesn't exist, and
+/// `AppError::Validation` if the path is not absolute or no file exists there.
+#[tauri::command]
+#[tracing::instrument(skip_all, fields(persona_id = %request.persona_id), err)]
+pub fn attach_generated_image(
+    state: State<AppState>,
+    mut request: AttachImageRequest,
+) -> Result<GeneratedImage, AppError> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+    let conn = db.connection();
+
+    let persona = PersonaRepository::find_by_id(conn, &request.persona_id)?;
+    if request.params.is_none() {
+        request.params =
+            PersonaRepository::find_resolved_generation_params(conn, &request.persona_id).ok();
+    }
+
+    let image = GeneratedImage::from_request(request)?;
+    if !Path::new(&image.path).is_file() {
+        return Err(AppError::Validation(format!(
+            "Image file '{}' not found",
+            image.path
+        )));
+    }
+
+    let snapshot = build_persona_export(conn, persona)?;
+    GeneratedImageRepository::create(conn, &image, Some(&snapshot))?;
+    Ok(image)
+}
+
+/// Exports everything needed to reproduce a gallery image as one document.
+///
+/// The bundle holds the exact prompts, seed, parameters, and the persona as
+/// it was when the image was attached, so later edits to the persona do not
+/// affect it.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection
+/// * `image_id` - UUID of the gallery entry
+///
+/// # Returns
+///
+/// A `ReproductionBundle` the frontend can write to disk.
+///
+/// # Errors
+///
+/// Returns `AppError::NotFound` if the entry doesn't exist.
+#[tauri::command]
+#[tracing::instrument(skip_all, fields(image_id = %image_id), err)]
+pub fn export_reproduction_bundle(
+    state: State<AppState>,
+    image_id: String,
+) -> Result<ReproductionBundle, AppError> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+    let conn = db.connection();
+
+    let image = GeneratedImageRepository::find_by_id(conn, &image_id)?;
+    let snapshot = GeneratedImageRepository::find_persona_snapshot(conn, &image_id)?;
+    Ok(ReproductionBundle::new(image, snapshot))
+}
+
+/// Groups a persona's gallery images by face to detect visual drift.
+///
+/// Images without a cached face embedding are first embedded with the face
+/// embedder set in the app settings, on a blocking worker thread; without
+/// one, only cached embeddings are used. Images the embedder fails on (e.g.,
+/// because the file was moved) are reported as not analyzed.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection
+/// * `persona_id` - UUID of the persona
+/// * `threshold` - Similarity at which faces are grouped together (default:
+///   0.5)
+/// * `recompute` - Whether to embed every image again, e.g. after changing
+///   the embedder (default: false)
+///
+/// # Returns
+///
+/// `GalleryConsistencyReport` with the face clusters, each image's similarity
+/// to the persona's main face, and whether the persona is drifting.
+///
+/// # Errors
+///
+/// Returns `AppError::NotFound` if the persona doesn't exist, and
+/// `AppError::Validation` if the threshold is outside 0–1 or the face
+/// embedder program does not exist.
+#[tauri::command]
+#[tracing::instrument(skip_all, fields(persona_id = %persona_id), err)]
+pub async fn analyze_gallery_consistency(
+    state: State<'_, AppState>,
+    persona_id: String,
+    threshold: Option<f32>,
+    recompute: Option<bool>,
+) -> Result<GalleryConsistencyReport, AppError> {
+    let threshold = validate_face_similarity(threshold.unwrap_or(DEFAULT_FACE_SIMILARITY))?;
+
+    let (images, mut embeddings, embedder) = {
+        let db = state
+            .db
+            .lock()
+            .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+        let conn = db.connection();
+
+        PersonaRepository::find_by_id(conn, &persona_id)?;
+        let embeddings = if recompute.unwrap_or(false) {
+            HashMap::new()
+        } else {
+            GeneratedImageRepository::find_face_embeddings(conn, &persona_id)?
+        };
+        (
+            GeneratedImageRepository::find_by_persona(conn, &persona_id, false)?,
+            embeddings,
+            SettingsRepository::load(conn)?.face_embedder,
+        )
+    };
+
+    let missing: Vec<(String, String)> = images
+        .iter()
+        .filter(|image| !embeddings.contains_key(&image.id))
+        .map(|image| (image.id.clone(), image.path.clone()))
+        .collect();
+    if let Some(program) = embedder.filter(|_| !missing.is_empty()) {
+        let computed = tauri::async_runtime::spawn_blocking(move || {
+            let mut computed = Vec::new();
+            for (id, path) in missing {
+                match face_embedder::embed_face(program.trim(), &path) {
+                    Ok(embedding) => computed.push((id, embedding)),
+                    // A missing embedder fails every image; report it once
+                    Err(e @ AppError::Validation(_)) => return Err(e),
+                    Err(e) => tracing::warn!(image_id = %id, error = %e, "Face embedding failed"),
+                }
+            }
+            Ok(computed)
+        })
+        .await
+        .map_err(|e| AppError::Internal(format!("Face embedding task failed: {e}")))??;
+
+        let db = state
+            .db
+            .lock()
+            .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+        for (id, embedding) in computed {
+            GeneratedImageRepository::set_face_embedding(db.connection(), &id, &embedding)?;
+            embeddings.insert(id, embedding);
+        }
+    }
+
+    Ok(GalleryConsistencyReport::analyze(
+        persona_id,
+        &images,
+        &embeddings,
+        threshold,
+    ))
+}
+
+/// Lists a persona's gallery, newest first.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection
+/// * `persona_id` - UUID of the persona
+/// * `starred_only` - Whether to return favorites only (default: false)
+///
+/// # Returns
+///
+/// Vector of gallery entries, which may be empty.
+#[tauri::command]
+#[tracing::instrument(skip_all, fields(persona_id = %persona_id), err)]
+pub fn get_persona_gallery(
+    state: State<AppState>,
+    persona_id: String,
+    starred_only: Option<bool>,
+) -> Result<Vec<GeneratedImage>, AppError> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+    GeneratedImageRepository::find_by_persona(
+        db.connection(),
+        &persona_id,
+        starred_only.unwrap_or(false),
+    )
+}
+
+/// Marks a gallery image as a favorite, or unmarks it.
+///
+/// Favorites are kept by `purge_generated_images` unless it is told otherwise.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection
+/// * `id` - UUID of the gallery entry
+/// * `starred` - Whether the image is a favorite
+///
+/// # Returns
+///
+/// The updated gallery entry.
+///
+/// # Errors
+///
+/// Returns `AppError::NotFound` if the entry doesn't exist.
+#[tauri::command]
+#[tracing::instrument(skip_all, fields(id = %id), err)]
+pub fn set_image_starred(
+    state: State<AppState>,
+    id: String,
+    starred: bool,
+) -> Result<GeneratedImage, AppError> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+    GeneratedImageRepository::set_starred(db.connection(), &id, starred)
+}
+
+/// Removes gallery entries older than a number of days, across all personas.
+///
+/// The image files are left in place.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection
+/// * `older_than_days` - Entries added more than this many days ago are removed
+/// * `include_starred` - Whether favorites are removed too (default: false)
+///
+/// # Returns
+///
+/// The number of entries removed.
+#[tauri::command]
+#[tracing::instrument(skip_all, fields(older_than_days), err)]
+pub fn purge_generated_images(
+    state: State<AppState>,
+    older_than_days: u32,
+    include_starred: Option<bool>,
+) -> Result<usize, AppError> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+    let before = Utc::now() - Duration::days(i64::from(older_than_days));
+    GeneratedImageRepository::purge_before(
+        db.connection(),
+        before,
+        include_starred.unwrap_or(false),
+    )
+}
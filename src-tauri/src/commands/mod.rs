@@ -18,6 +18,34 @@
 //! - [`ai`]: AI-powered token generation using LLM providers
 //! - [`export`]: Persona import/export for backup and sharing
 //! - [`settings`]: API key management via secure OS credential storage
+//! - [`diagnostics`]: Aggregated subsystem status for support
+//! - [`draft`]: Autosaved persona drafts and promoting them to personas
+//! - [`window`]: Secondary windows such as the compose popout
+//! - [`wizard`]: Step-by-step persona creation with resumable drafts
+//! - [`collection`]: Smart collections and persona queries
+//! - [`share`]: Temporary read-only web view of a smart collection for collaborators
+//! - [`search`]: Quick search across personas, tokens, collections, and templates
+//! - [`alias`]: User-maintained token variants and their preferred phrasing
+//! - [`banned_term`]: User-managed banned terms enforced on tokens and prompts
+//! - [`gallery`]: Generated images attached to personas, with favorites and purging
+//! - [`stats`]: Library-wide statistics for the dashboard
+//! - [`suggestion`]: Inbox of AI-suggested tokens with accept and reject
+//! - [`schedule`]: Scheduled background tasks such as AI style refreshes
+//! - [`telemetry`]: Viewing and clearing opt-in feature usage counters
+//! - [`update`]: Checking for and installing application updates
+//!
+//! # Transactions
+//!
+//! Commands that perform more than one write (including repository methods that
+//! refresh content hashes or invalidate caches) run them through
+//! `Database::unit_of_work`, so a failure never leaves partial data behind.
+//!
+//! # Change Events
+//!
+//! Commands that modify personas or tokens publish a `DomainEvent` on the
+//! backend event bus once their transaction succeeds (see `domain::events`).
+//! Its subscribers forward it to every open window as `persona-changed` or
+//! `token-changed`, so commands never wire those side effects themselves.
 //!
 //! # Error Handling
 //!
@@ -25,10 +53,61 @@
 //! for Tauri IPC compatibility. Errors are propagated to the frontend for user feedback.
 
 pub mod ai;
+pub mod alias;
+pub mod banned_term;
+pub mod collection;
 pub mod config;
+pub mod diagnostics;
+pub mod draft;
 pub mod export;
+pub mod gallery;
 pub mod persona;
 pub mod prompt;
+pub mod schedule;
+pub mod search;
 pub mod settings;
+pub mod share;
+pub mod stats;
+pub mod suggestion;
+pub mod telemetry;
 pub mod token;
 pub mod tokenizer;
+#[cfg(desktop)]
+pub mod update;
+pub mod window;
+pub mod wizard;
+
+use tauri::{Manager, Window};
+
+use crate::domain::events::{ChangeKind, DomainEvent, PersonaChanged, TokenChanged};
+use crate::infrastructure::event_bus::EventBus;
+
+/// Publishes that a persona changed.
+pub(crate) fn emit_persona_changed(window: &Window, persona_id: &str, kind: ChangeKind) {
+    publish(
+        window,
+        &DomainEvent::PersonaChanged(PersonaChanged {
+            persona_id: persona_id.to_string(),
+            kind,
+            source_window: window.label().to_string(),
+        }),
+    );
+}
+
+/// Publishes that a persona's tokens changed.
+pub(crate) fn emit_tokens_changed(window: &Window, persona_id: &str) {
+    publish(
+        window,
+        &DomainEvent::TokensChanged(TokenChanged {
+            persona_id: persona_id.to_string(),
+            source_window: window.label().to_string(),
+        }),
+    );
+}
+
+/// Publishes an event on the app's event bus.
+fn publish(window: &Window, event: &DomainEvent) {
+    if let Some(bus) = window.try_state::<EventBus>() {
+        bus.publish(event);
+    }
+}
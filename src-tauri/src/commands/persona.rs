@@ -7,26 +7,64 @@
 //! # Operations
 //!
 //! - **CRUD**: Create, read, update, and delete personas
+//! - **Bulk Operations**: Retag, reconfigure, archive, or delete many personas at once
 //! - **Duplication**: Clone personas with automatic name deduplication
-//! - **Generation Params**: Configure image generation settings per persona
+//! - **Generation Params**: Configure image generation settings per persona and check
+//!   them against the target model
+//! - **Inheritance**: Link variants to a base persona whose parameters and tokens they share
+//! - **Templates**: Create personas from built-in archetypes
+//! - **Recent**: Record opened personas and list recently opened, modified, or composed ones
+//! - **Presentation**: Load everything a read-only view displays in one call
+//! - **Comparison**: Diff two personas to reconcile variants of a character
+//! - **Similarity**: Rank personas by shared tokens and tags to find near-duplicates
+//! - **Tags**: Browse namespaced tags (e.g., `project/clientA`) as a tree
 
-use tauri::State;
+use std::collections::{HashMap, HashSet};
 
+use tauri::{State, Window};
+
+use super::prompt::{allowed_tokens, composition_options};
+use super::{emit_persona_changed, emit_tokens_changed};
+use crate::domain::activity::{ActivityKind, RecentPersona};
+use crate::domain::alias::AliasMap;
+use crate::domain::compare::{ComparedPersona, PersonaComparison};
+use crate::domain::events::ChangeKind;
+use crate::domain::naming::NameCheck;
 use crate::domain::persona::{
-    CreatePersonaRequest, GenerationParams, Persona, UpdatePersonaRequest,
+    BulkPersonaPatch, CreatePersonaRequest, GenerationParams, ParamWarning, Persona, PersonaFull,
+    UpdatePersonaRequest,
 };
+use crate::domain::prompt::{CompositionOptions, PromptComposer};
+use crate::domain::resolution::ResolutionPresets;
+use crate::domain::similarity::{sort_by_similarity, SimilarPersona, SimilarityProfile};
+use crate::domain::tag::{build_tag_tree, TagNode};
+use crate::domain::telemetry::Feature;
+use crate::domain::template::PersonaTemplate;
+use crate::domain::token::Token;
 use crate::error::AppError;
-use crate::infrastructure::database::repositories::PersonaRepository;
+use crate::infrastructure::database::repositories::{
+    ActivityRepository, PersonaRepository, TokenAliasRepository, TokenRepository,
+};
+use crate::infrastructure::{locale, safe_mode, telemetry, tokenizer};
+use crate::services::PersonaService;
 use crate::AppState;
 
+/// Number of personas returned by `list_recent_personas` when no limit is given.
+const DEFAULT_RECENT_LIMIT: usize = 10;
+
+/// Number of personas returned by `find_similar_personas` when no limit is given.
+const DEFAULT_SIMILAR_LIMIT: usize = 10;
+
 /// Creates a new persona with the given name, description, and tags.
 ///
-/// This command validates that the persona name is unique before creating. A new UUID
+/// This command validates the persona name against the naming rules (see
+/// `domain::naming`) and checks that it is unique before creating. A new UUID
 /// is generated for the persona, and default generation parameters are automatically
 /// created (linked via foreign key).
 ///
 /// # Arguments
 ///
+/// * `window` - Window issuing the command, named in the change event
 /// * `state` - Application state containing the database connection
 /// * `request` - Persona creation data (name required, description and tags optional)
 ///
@@ -36,9 +74,12 @@ use crate::AppState;
 ///
 /// # Errors
 ///
-/// Returns `AppError::Validation` if a persona with the same name already exists.
+/// Returns `AppError::Validation` if the name breaks the naming rules or a persona
+/// with the same name already exists.
 #[tauri::command]
+#[tracing::instrument(skip_all, err)]
 pub fn create_persona(
+    window: Window,
     state: State<AppState>,
     request: CreatePersonaRequest,
 ) -> Result<Persona, AppError> {
@@ -47,7 +88,10 @@ pub fn create_persona(
         .lock()
         .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
 
-    PersonaRepository::create(db.connection(), &request)
+    let persona = PersonaService::create(db.connection(), request)?;
+    telemetry::record(db.connection(), Feature::CreatePersona);
+    emit_persona_changed(&window, &persona.id, ChangeKind::Created);
+    Ok(persona)
 }
 
 /// Retrieves a single persona by its unique identifier.
@@ -65,6 +109,7 @@ pub fn create_persona(
 ///
 /// Returns `AppError::NotFound` if no persona exists with the given ID.
 #[tauri::command]
+#[tracing::instrument(skip_all, fields(id = %id), err)]
 pub fn get_persona_by_id(state: State<AppState>, id: String) -> Result<Persona, AppError> {
     let db = state
         .db
@@ -74,10 +119,79 @@ pub fn get_persona_by_id(state: State<AppState>, id: String) -> Result<Persona,
     PersonaRepository::find_by_id(db.connection(), &id)
 }
 
+/// Retrieves a persona for editing and records it as recently opened.
+///
+/// Use this when the user opens a persona; use `get_persona_by_id` for
+/// background refreshes, which should not affect the recent list.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection
+/// * `id` - UUID of the persona to open
+///
+/// # Returns
+///
+/// The complete persona entity.
+///
+/// # Errors
+///
+/// Returns `AppError::NotFound` if no persona exists with the given ID.
+#[tauri::command]
+#[tracing::instrument(skip_all, fields(id = %id), err)]
+pub fn open_persona(state: State<AppState>, id: String) -> Result<Persona, AppError> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+    db.unit_of_work(|conn| {
+        let persona = PersonaRepository::find_by_id(conn, &id)?;
+        ActivityRepository::record(conn, &id, ActivityKind::Opened)?;
+        Ok(persona)
+    })
+}
+
+/// Lists the personas most recently opened, modified, or composed.
+///
+/// Timestamps are recorded by the commands themselves: `open_persona` for
+/// opened, any change to a persona, its tokens, or its generation parameters
+/// for modified, and `compose_prompt` for composed. Archived personas, and
+/// mature-rated ones in safe mode, are left out.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection
+/// * `kind` - Which activity to list by
+/// * `limit` - Maximum number of personas (default: 10)
+///
+/// # Returns
+///
+/// Personas with the time of that activity, most recent first.
+#[tauri::command]
+#[tracing::instrument(skip_all, fields(kind = ?kind), err)]
+pub fn list_recent_personas(
+    state: State<AppState>,
+    kind: ActivityKind,
+    limit: Option<usize>,
+) -> Result<Vec<RecentPersona>, AppError> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+    ActivityRepository::find_recent(
+        db.connection(),
+        kind,
+        limit.unwrap_or(DEFAULT_RECENT_LIMIT),
+        safe_mode::is_safe_mode(),
+    )
+}
+
 /// Lists all personas in the database, ordered by creation date (newest first).
 ///
 /// This command returns all personas without pagination. For large datasets,
-/// consider using `search_personas` with specific criteria.
+/// consider using `search_personas` with specific criteria. In safe mode,
+/// mature-rated personas are left out.
 ///
 /// # Arguments
 ///
@@ -87,13 +201,251 @@ pub fn get_persona_by_id(state: State<AppState>, id: String) -> Result<Persona,
 ///
 /// Vector of all personas, which may be empty if none exist.
 #[tauri::command]
+#[tracing::instrument(skip_all, err)]
 pub fn list_personas(state: State<AppState>) -> Result<Vec<Persona>, AppError> {
     let db = state
         .db
         .lock()
         .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
 
-    PersonaRepository::find_all(db.connection())
+    Ok(PersonaRepository::find_all(db.connection())?
+        .into_iter()
+        .filter(safe_mode::is_visible)
+        .collect())
+}
+
+/// Retrieves a persona with everything needed to present it read-only.
+///
+/// Returns the persona, its generation parameters, tokens, the granularity
+/// levels, and the composed prompt with its copy formats, so a presenter
+/// window needs a single round trip. Nothing is written, not even the prompt
+/// cache. All tokens are returned, but those containing a banned term are
+/// left out of the prompt.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection
+/// * `persona_id` - UUID of the persona to present
+/// * `options` - Optional composition settings (see `compose_prompt`)
+///
+/// # Returns
+///
+/// A `PersonaFull` with all the persona's data.
+///
+/// # Errors
+///
+/// Returns `AppError::NotFound` if no persona exists with the given ID, or
+/// safe mode hides it.
+#[tauri::command]
+#[tracing::instrument(skip_all, fields(persona_id = %persona_id), err)]
+pub fn get_persona_full(
+    state: State<AppState>,
+    persona_id: String,
+    options: Option<CompositionOptions>,
+) -> Result<PersonaFull, AppError> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+    let conn = db.connection();
+
+    let persona = PersonaRepository::find_by_id(conn, &persona_id)?;
+    safe_mode::ensure_visible(&persona)?;
+    let generation_params =
+        match PersonaRepository::find_resolved_generation_params(conn, &persona_id) {
+            Ok(params) => Some(params),
+            Err(AppError::NotFound(_)) => None,
+            Err(e) => return Err(e),
+        };
+    let tokens = TokenRepository::find_resolved_by_persona(conn, &persona_id)?;
+    let options = composition_options(conn, &persona_id, options)?;
+    let (allowed, filtered_tokens) = allowed_tokens(conn, &persona_id, &options)?;
+    let granularity_levels = state.metadata.granularity_levels(conn)?;
+
+    let model_id = generation_params.as_ref().map(|p| p.model_id.as_str());
+    let context = tokenizer::get_prompt_context_for_model(model_id);
+    let mut prompt = PromptComposer::preview(
+        &allowed,
+        &granularity_levels,
+        &options,
+        context.supports_negative_prompt,
+        &context.family,
+        generation_params.as_ref(),
+    );
+    prompt.composed.filtered_tokens = filtered_tokens;
+
+    Ok(PersonaFull {
+        persona,
+        generation_params,
+        tokens,
+        granularity_levels,
+        prompt,
+    })
+}
+
+/// Lists persona tags as a tree of namespaces.
+///
+/// Tags are split on `/`, so `project/clientA` and `project/clientB` appear
+/// as children of `project`. Filter by a namespace with the `namespaces`
+/// criterion of `query_personas`. Mature-rated personas are not counted in
+/// safe mode.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection
+/// * `include_archived` - Whether archived personas are counted (default: false)
+///
+/// # Returns
+///
+/// Top-level `TagNode`s sorted by name, each with the number of personas
+/// tagged with it or a tag below it.
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn list_tag_tree(
+    state: State<AppState>,
+    include_archived: Option<bool>,
+) -> Result<Vec<TagNode>, AppError> {
+    let include_archived = include_archived.unwrap_or(false);
+
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+    let personas: Vec<Persona> = PersonaRepository::find_all(db.connection())?
+        .into_iter()
+        .filter(|persona| (include_archived || !persona.archived) && safe_mode::is_visible(persona))
+        .collect();
+
+    Ok(build_tag_tree(&personas))
+}
+
+/// Compares two personas field by field.
+///
+/// Tokens are matched on granularity, polarity, and content (ignoring case),
+/// so the result lists shared tokens, tokens unique to each persona, and
+/// shared tokens whose weights differ.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection
+/// * `a` - UUID of the first persona
+/// * `b` - UUID of the second persona
+///
+/// # Returns
+///
+/// A `PersonaComparison` with the differing metadata and generation
+/// parameters, and the tag and token overlap.
+///
+/// # Errors
+///
+/// Returns `AppError::NotFound` if either persona does not exist.
+#[tauri::command]
+#[tracing::instrument(skip_all, fields(a = %a, b = %b), err)]
+pub fn compare_personas(
+    state: State<AppState>,
+    a: String,
+    b: String,
+) -> Result<PersonaComparison, AppError> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+    let conn = db.connection();
+
+    let load = |id: &str| -> Result<_, AppError> {
+        let persona = PersonaRepository::find_by_id(conn, id)?;
+        let params = match PersonaRepository::find_resolved_generation_params(conn, id) {
+            Ok(params) => Some(params),
+            Err(AppError::NotFound(_)) => None,
+            Err(e) => return Err(e),
+        };
+        let tokens = TokenRepository::find_resolved_by_persona(conn, id)?;
+        Ok((persona, params, tokens))
+    };
+
+    let (persona_a, params_a, tokens_a) = load(&a)?;
+    let (persona_b, params_b, tokens_b) = load(&b)?;
+    telemetry::record(conn, Feature::ComparePersonas);
+
+    Ok(PersonaComparison::compare(
+        ComparedPersona {
+            persona: &persona_a,
+            params: params_a.as_ref(),
+            tokens: &tokens_a,
+        },
+        ComparedPersona {
+            persona: &persona_b,
+            params: params_b.as_ref(),
+            tokens: &tokens_b,
+        },
+    ))
+}
+
+/// Finds the personas most similar to a persona, to spot near-duplicates.
+///
+/// Personas are ranked by token overlap and shared tags (see
+/// `domain::similarity`), with aliased tokens counted as their phrasing;
+/// personas sharing neither are left out, as are mature-rated personas in
+/// safe mode. Archived personas are included.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection
+/// * `persona_id` - UUID of the persona to compare against
+/// * `limit` - Maximum number of personas to return (default: 10)
+///
+/// # Returns
+///
+/// Similar personas with their scores, most similar first.
+///
+/// # Errors
+///
+/// Returns `AppError::NotFound` if the persona does not exist or is hidden in
+/// safe mode.
+#[tauri::command]
+#[tracing::instrument(skip_all, fields(persona_id = %persona_id), err)]
+pub fn find_similar_personas(
+    state: State<AppState>,
+    persona_id: String,
+    limit: Option<usize>,
+) -> Result<Vec<SimilarPersona>, AppError> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+    let conn = db.connection();
+    let target = PersonaRepository::find_by_id(conn, &persona_id)?;
+    safe_mode::ensure_visible(&target)?;
+
+    let mut tokens_by_persona: HashMap<String, Vec<Token>> = HashMap::new();
+    for token in TokenRepository::find_all(conn)? {
+        tokens_by_persona
+            .entry(token.persona_id.clone())
+            .or_default()
+            .push(token);
+    }
+    let tokens_of = |id: &str| tokens_by_persona.get(id).map_or(&[][..], Vec::as_slice);
+
+    let aliases = AliasMap::new(&TokenAliasRepository::find_all(conn)?);
+
+    let profile = SimilarityProfile::new(&target, tokens_of(&target.id), &aliases);
+    let mut similar: Vec<SimilarPersona> = PersonaRepository::find_all(conn)?
+        .into_iter()
+        .filter(|persona| persona.id != target.id && safe_mode::is_visible(persona))
+        .filter_map(|persona| {
+            let other = SimilarityProfile::new(&persona, tokens_of(&persona.id), &aliases);
+            profile.compare(persona, &other)
+        })
+        .collect();
+
+    sort_by_similarity(&mut similar);
+    similar.truncate(limit.unwrap_or(DEFAULT_SIMILAR_LIMIT));
+
+    Ok(similar)
 }
 
 /// Updates an existing persona with the provided field values.
@@ -103,6 +455,7 @@ pub fn list_personas(state: State<AppState>) -> Result<Vec<Persona>, AppError> {
 ///
 /// # Arguments
 ///
+/// * `window` - Window issuing the command, named in the change event
 /// * `state` - Application state containing the database connection
 /// * `id` - UUID of the persona to update
 /// * `request` - Partial update data (all fields optional)
@@ -115,7 +468,9 @@ pub fn list_personas(state: State<AppState>) -> Result<Vec<Persona>, AppError> {
 ///
 /// Returns `AppError::NotFound` if no persona exists with the given ID.
 #[tauri::command]
+#[tracing::instrument(skip_all, fields(id = %id), err)]
 pub fn update_persona(
+    window: Window,
     state: State<AppState>,
     id: String,
     request: UpdatePersonaRequest,
@@ -125,16 +480,71 @@ pub fn update_persona(
         .lock()
         .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
 
-    PersonaRepository::update(db.connection(), &id, &request)
+    let persona = db.unit_of_work(|conn| PersonaRepository::update(conn, &id, &request))?;
+    emit_persona_changed(&window, &id, ChangeKind::Updated);
+    Ok(persona)
+}
+
+/// Links a variant persona to a base persona it inherits from, or unlinks it.
+///
+/// The variant inherits the base's generation parameters, except those it
+/// overrides, and the tokens of the selected granularity levels, except levels
+/// it has tokens of itself (see `domain::inheritance`). Unlinking copies the
+/// inherited parameters and tokens onto the persona, so it looks the same.
+///
+/// # Arguments
+///
+/// * `window` - Window issuing the command, named in the change events
+/// * `state` - Application state containing the database connection
+/// * `persona_id` - UUID of the variant
+/// * `base_persona_id` - UUID of the base persona, or `None` to unlink
+/// * `inherited_granularities` - Granularity level IDs whose tokens are inherited
+///
+/// # Returns
+///
+/// The updated persona.
+///
+/// # Errors
+///
+/// Returns `AppError::NotFound` if either persona does not exist, or
+/// `AppError::Validation` if the link would form a cycle or names an unknown
+/// granularity level.
+#[tauri::command]
+#[tracing::instrument(skip_all, fields(persona_id = %persona_id), err)]
+pub fn set_base_persona(
+    window: Window,
+    state: State<AppState>,
+    persona_id: String,
+    base_persona_id: Option<String>,
+    inherited_granularities: Vec<String>,
+) -> Result<Persona, AppError> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+    let persona = db.unit_of_work(|conn| {
+        PersonaRepository::set_base_persona(
+            conn,
+            &persona_id,
+            base_persona_id.as_deref(),
+            &inherited_granularities,
+        )
+    })?;
+    emit_persona_changed(&window, &persona_id, ChangeKind::Updated);
+    emit_tokens_changed(&window, &persona_id);
+    Ok(persona)
 }
 
 /// Deletes a persona and all associated data.
 ///
 /// This operation cascades to delete related generation parameters and tokens
-/// via foreign key constraints. This action is irreversible.
+/// via foreign key constraints. This action is irreversible. Variants of the
+/// persona are unlinked and keep what they inherited.
 ///
 /// # Arguments
 ///
+/// * `window` - Window issuing the command, named in the change event
 /// * `state` - Application state containing the database connection
 /// * `id` - UUID of the persona to delete
 ///
@@ -142,13 +552,121 @@ pub fn update_persona(
 ///
 /// Returns `AppError::NotFound` if no persona exists with the given ID.
 #[tauri::command]
-pub fn delete_persona(state: State<AppState>, id: String) -> Result<(), AppError> {
+#[tracing::instrument(skip_all, fields(id = %id), err)]
+pub fn delete_persona(window: Window, state: State<AppState>, id: String) -> Result<(), AppError> {
     let db = state
         .db
         .lock()
         .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
 
-    PersonaRepository::delete(db.connection(), &id)
+    db.unit_of_work(|conn| PersonaRepository::delete(conn, &id))?;
+    emit_persona_changed(&window, &id, ChangeKind::Deleted);
+    Ok(())
+}
+
+/// Applies the same changes to several personas in one transaction.
+///
+/// Tags are added to and removed from each persona's existing tags; the AI
+/// provider, model, and archive flag are set as given. If any persona does
+/// not exist, nothing is changed.
+///
+/// # Arguments
+///
+/// * `window` - Window issuing the command, named in the change events
+/// * `state` - Application state containing the database connection
+/// * `ids` - UUIDs of the personas to update (duplicates are ignored)
+/// * `patch` - Changes to apply to every persona
+///
+/// # Returns
+///
+/// The updated personas, in the order of `ids`.
+///
+/// # Errors
+///
+/// Returns `AppError::Validation` if the patch changes nothing, or
+/// `AppError::NotFound` if any persona does not exist.
+#[tauri::command]
+#[tracing::instrument(skip_all, fields(count = ids.len()), err)]
+pub fn bulk_update_personas(
+    window: Window,
+    state: State<AppState>,
+    ids: Vec<String>,
+    patch: BulkPersonaPatch,
+) -> Result<Vec<Persona>, AppError> {
+    if patch.is_empty() {
+        return Err(AppError::Validation("Nothing to update".to_string()));
+    }
+
+    let ids = dedup_ids(ids);
+
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+    let personas = db.unit_of_work(|conn| {
+        ids.iter()
+            .map(|id| {
+                let persona = PersonaRepository::find_by_id(conn, id)?;
+                PersonaRepository::update(conn, id, &patch.to_update_request(&persona))
+            })
+            .collect::<Result<Vec<_>, _>>()
+    })?;
+
+    for persona in &personas {
+        emit_persona_changed(&window, &persona.id, ChangeKind::Updated);
+    }
+    Ok(personas)
+}
+
+/// Deletes several personas and all their data in one transaction.
+///
+/// If any persona does not exist, nothing is deleted.
+///
+/// # Arguments
+///
+/// * `window` - Window issuing the command, named in the change events
+/// * `state` - Application state containing the database connection
+/// * `ids` - UUIDs of the personas to delete (duplicates are ignored)
+///
+/// # Returns
+///
+/// The number of personas deleted.
+///
+/// # Errors
+///
+/// Returns `AppError::NotFound` if any persona does not exist.
+#[tauri::command]
+#[tracing::instrument(skip_all, fields(count = ids.len()), err)]
+pub fn bulk_delete_personas(
+    window: Window,
+    state: State<AppState>,
+    ids: Vec<String>,
+) -> Result<usize, AppError> {
+    let ids = dedup_ids(ids);
+
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+    db.unit_of_work(|conn| {
+        ids.iter()
+            .try_for_each(|id| PersonaRepository::delete(conn, id))
+    })?;
+
+    for id in &ids {
+        emit_persona_changed(&window, id, ChangeKind::Deleted);
+    }
+    Ok(ids.len())
+}
+
+/// Removes duplicate IDs, keeping the first occurrence of each.
+fn dedup_ids(ids: Vec<String>) -> Vec<String> {
+    let mut seen = HashSet::new();
+    ids.into_iter()
+        .filter(|id| seen.insert(id.clone()))
+        .collect()
 }
 
 /// Retrieves the image generation parameters for a persona.
@@ -163,12 +681,14 @@ pub fn delete_persona(state: State<AppState>, id: String) -> Result<(), AppError
 ///
 /// # Returns
 ///
-/// The generation parameters associated with the persona.
+/// The generation parameters associated with the persona; for a variant,
+/// with the parameters it inherits from its base persona resolved.
 ///
 /// # Errors
 ///
 /// Returns `AppError::NotFound` if no persona exists with the given ID.
 #[tauri::command]
+#[tracing::instrument(skip_all, fields(persona_id = %persona_id), err)]
 pub fn get_persona_generation_params(
     state: State<AppState>,
     persona_id: String,
@@ -178,45 +698,105 @@ pub fn get_persona_generation_params(
         .lock()
         .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
 
-    PersonaRepository::find_generation_params(db.connection(), &persona_id)
+    PersonaRepository::find_resolved_generation_params(db.connection(), &persona_id)
 }
 
 /// Updates the image generation parameters for a persona.
 ///
-/// All parameter fields are replaced with the provided values.
+/// All parameter fields are replaced with the provided values. For a variant,
+/// the fields that differ from its base persona become overrides; the others
+/// keep following the base.
 ///
 /// # Arguments
 ///
+/// * `window` - Window issuing the command, named in the change event
 /// * `state` - Application state containing the database connection
 /// * `params` - Complete generation parameters (`persona_id` must match existing persona)
+///
+/// # Errors
+///
+/// Returns `AppError::Validation` if the parameters are unusable (see
+/// `GenerationParams::validate`). Settings merely unsuited to the model are
+/// saved; check them first with [`validate_generation_params`].
 #[tauri::command]
+#[tracing::instrument(skip_all, err)]
 pub fn update_generation_params(
+    window: Window,
     state: State<AppState>,
     params: GenerationParams,
 ) -> Result<(), AppError> {
+    params.validate()?;
+
     let db = state
         .db
         .lock()
         .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
 
-    PersonaRepository::update_generation_params(db.connection(), &params)
+    db.unit_of_work(|conn| PersonaRepository::update_generation_params(conn, &params))?;
+    emit_persona_changed(&window, &params.persona_id, ChangeKind::Updated);
+    Ok(())
+}
+
+/// Checks generation parameters against the model they target.
+///
+/// Flags settings that work but are likely to give poor results: a CFG scale
+/// or step count outside the model's recommended range (e.g., CFG 7 or 30
+/// steps on SDXL-Turbo, which wants CFG 0–1 and 1–4 steps), and an off-bucket
+/// resolution. Nothing is saved.
+///
+/// # Arguments
+///
+/// * `params` - Generation parameters to check
+///
+/// # Returns
+///
+/// Warnings in field order (CFG scale, steps, resolution); empty if the
+/// settings suit the model.
+///
+/// # Errors
+///
+/// Returns `AppError::Validation` if the parameters are unusable, as
+/// [`update_generation_params`] would.
+#[tauri::command]
+#[tracing::instrument(skip_all, fields(model_id = %params.model_id), err)]
+pub fn validate_generation_params(params: GenerationParams) -> Result<Vec<ParamWarning>, AppError> {
+    params.validate()?;
+
+    let context = tokenizer::get_prompt_context_for_model(Some(params.model_id.as_str()));
+    let locale = locale::current();
+    let mut warnings = params.sampling_warnings(&context.sampling, locale);
+
+    if let (Some(width), Some(height)) = (params.width, params.height) {
+        let check = ResolutionPresets::for_family(&context.family).check(width, height, locale);
+        if let (Some(code), Some(message)) = (check.warning_code, check.warning) {
+            warnings.push(ParamWarning {
+                field: "resolution".to_string(),
+                code,
+                message,
+            });
+        }
+    }
+
+    Ok(warnings)
 }
 
 /// Creates a duplicate of an existing persona with a unique name.
 ///
 /// The duplication process:
 /// 1. Copies all persona metadata (name, description, tags)
-/// 2. Copies generation parameters
-/// 3. Generates a unique name by appending "(Copy)" or "(Copy N)" if needed
+/// 2. Copies generation parameters and composition defaults
+/// 3. Generates a unique name by appending "(Copy)" or "(Copy N)" to the original
+///    name (without any suffix it already has)
 ///
 /// Note: Tokens are intentionally NOT copied. This allows users to create
 /// variations of a persona without inheriting potentially unwanted tokens.
 ///
 /// # Arguments
 ///
+/// * `window` - Window issuing the command, named in the change event
 /// * `state` - Application state containing the database connection
 /// * `id` - UUID of the persona to duplicate
-/// * `new_name` - Optional custom name for the copy (auto-deduplicated if taken)
+/// * `new_name` - Optional custom name for the copy (suffixed with "(Copy)" if taken)
 ///
 /// # Returns
 ///
@@ -224,9 +804,12 @@ pub fn update_generation_params(
 ///
 /// # Errors
 ///
-/// Returns `AppError::NotFound` if the source persona does not exist.
+/// Returns `AppError::NotFound` if the source persona does not exist, or
+/// `AppError::Validation` if the custom name breaks the naming rules.
 #[tauri::command]
+#[tracing::instrument(skip_all, fields(id = %id), err)]
 pub fn duplicate_persona(
+    window: Window,
     state: State<AppState>,
     id: String,
     new_name: Option<String>,
@@ -236,32 +819,95 @@ pub fn duplicate_persona(
         .lock()
         .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
 
-    let conn = db.connection();
+    let new_persona = PersonaService::duplicate(db.connection(), &id, new_name.as_deref())?;
+    telemetry::record(db.connection(), Feature::DuplicatePersona);
 
-    let original = PersonaRepository::find_by_id(conn, &id)?;
+    emit_persona_changed(&window, &new_persona.id, ChangeKind::Created);
+    Ok(new_persona)
+}
 
-    // Generate a unique name by incrementing a counter if necessary
-    let base_name = new_name.unwrap_or_else(|| format!("{} (Copy)", original.name));
-    let mut name = base_name.clone();
-    let mut counter = 1;
+/// Checks a persona name before saving it and suggests available alternatives.
+///
+/// Applies the same rules as creating or renaming a persona (see
+/// `domain::naming`), so forms can warn while the user types. Suggestions drop
+/// any reserved suffix and append a counter ("Alice (2)") until a free name is
+/// found.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection
+/// * `name` - The name to check
+/// * `exclude_id` - UUID of the persona being renamed; its current name is always accepted
+///
+/// # Returns
+///
+/// The normalized name, whether it can be used, the broken rule if any, and
+/// suggestions when it cannot be used.
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn check_persona_name(
+    state: State<AppState>,
+    name: String,
+    exclude_id: Option<String>,
+) -> Result<NameCheck, AppError> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
 
-    while PersonaRepository::name_exists(conn, &name, None)? {
-        counter += 1;
-        name = format!("{base_name} ({counter})");
-    }
+    PersonaService::check_name(db.connection(), &name, exclude_id.as_deref())
+}
 
-    let request = CreatePersonaRequest {
-        name,
-        description: original.description,
-        tags: original.tags,
-    };
+/// Lists the built-in persona archetype templates.
+///
+/// Templates are embedded in the application and need no AI provider.
+///
+/// # Returns
+///
+/// All templates, each with its description, tags, and tokens.
+#[tauri::command]
+pub fn list_persona_templates() -> Vec<PersonaTemplate> {
+    PersonaTemplate::all().to_vec()
+}
 
-    let new_persona = PersonaRepository::create(conn, &request)?;
+/// Creates a new persona from a built-in template.
+///
+/// The persona receives the template's description, tags, and tokens. The
+/// persona and its tokens are created in a single transaction.
+///
+/// # Arguments
+///
+/// * `window` - Window issuing the command, named in the change event
+/// * `state` - Application state containing the database connection
+/// * `template_id` - ID of the template to use (e.g., "`fantasy_knight`")
+/// * `name` - Unique name for the new persona
+///
+/// # Returns
+///
+/// The newly created persona.
+///
+/// # Errors
+///
+/// Returns `AppError::NotFound` if the template does not exist, or
+/// `AppError::Validation` if the name breaks the naming rules or is already taken.
+#[tauri::command]
+#[tracing::instrument(skip_all, fields(template_id = %template_id), err)]
+pub fn create_persona_from_template(
+    window: Window,
+    state: State<AppState>,
+    template_id: String,
+    name: String,
+) -> Result<Persona, AppError> {
+    let template = PersonaTemplate::find(&template_id)
+        .ok_or_else(|| AppError::NotFound(format!("Persona template '{template_id}' not found")))?;
 
-    // Copy generation params to the new persona
-    let mut params = PersonaRepository::find_generation_params(conn, &id)?;
-    params.persona_id = new_persona.id.clone();
-    PersonaRepository::update_generation_params(conn, &params)?;
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
 
-    Ok(new_persona)
+    let persona = PersonaService::create_from_template(db.connection(), template, &name)?;
+    telemetry::record(db.connection(), Feature::CreateFromTemplate);
+    emit_persona_changed(&window, &persona.id, ChangeKind::Created);
+    Ok(persona)
 }
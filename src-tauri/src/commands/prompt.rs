@@ -12,13 +12,55 @@
 //! 4. Applies weight formatting if enabled (e.g., "(token:1.2)")
 //! 5. Joins tokens with the configured separator
 //! 6. Optionally inserts ad-hoc tokens at the beginning or end
+//!
+//! Prompts composed without ad-hoc tokens are cached per persona, so list views
+//! can read them back with [`get_cached_prompt`]. Every [`compose_prompt`] call
+//! records the persona as recently composed.
+//!
+//! [`compose_prompt_preview`] runs the same composition and also returns every
+//! copy format, so the UI does not recompose per format. Composed (or
+//! hand-edited) prompts can then be checked with [`lint_prompt`].
+//! [`generate_prompt_matrix`] composes a batch of prompts, one per combination
+//! of alternative tokens, for testing variations side by side.
+//!
+//! When no options are passed, the persona's composition defaults apply (see
+//! [`set_composition_defaults`]), falling back to the global defaults.
+//! [`get_default_regions`] returns the face/body/background layout the UI
+//! starts regional composition from.
+//!
+//! In safe mode, mature-rated personas cannot be composed; the commands fail
+//! with `AppError::NotFound` as if the persona did not exist.
+//!
+//! Stored tokens containing a banned term are left out of every composed
+//! prompt and listed in `ComposedPrompt::filtered_tokens`. Ad-hoc tokens are
+//! typed for one composition and are not filtered.
+//!
+//! With the `apply_aliases` option, stored tokens the user aliased are
+//! composed with their preferred phrasing (see `domain::alias`); ad-hoc
+//! tokens are kept as typed.
 
-use tauri::State;
+use rusqlite::Connection;
+use tauri::{State, Window};
 
-use crate::domain::prompt::{ComposedPrompt, CompositionOptions, PromptComposer};
-use crate::domain::token::GranularityLevel;
+use super::emit_persona_changed;
+use crate::domain::activity::ActivityKind;
+use crate::domain::alias::AliasMap;
+use crate::domain::banned_term::{self, FilteredToken};
+use crate::domain::events::ChangeKind;
+use crate::domain::lint::{LintInput, LintIssue, PromptLength, PromptLinter};
+use crate::domain::matrix::{MatrixAxis, PromptMatrix};
+use crate::domain::prompt::{
+    CachedPrompt, ComposedPrompt, CompositionDefaults, CompositionOptions, PromptComposer,
+    PromptPreview, RegionalOptions,
+};
+use crate::domain::telemetry::Feature;
+use crate::domain::token::Token;
 use crate::error::AppError;
-use crate::infrastructure::database::repositories::TokenRepository;
+use crate::infrastructure::database::repositories::{
+    ActivityRepository, BannedTermRepository, PersonaRepository, PromptCacheRepository,
+    TokenAliasRepository, TokenRepository,
+};
+use crate::infrastructure::{safe_mode, telemetry, tokenizer};
 use crate::AppState;
 
 /// Composes a prompt from a persona's tokens with configurable options.
@@ -30,20 +72,32 @@ use crate::AppState;
 ///
 /// * `state` - Application state containing the database connection
 /// * `persona_id` - UUID of the persona whose tokens to compose
-/// * `options` - Optional composition settings:
+/// * `options` - Optional composition settings (default: the persona's composition
+///   defaults, if set):
 ///   - `include_weights`: Whether to format tokens with weight modifiers (default: true)
 ///   - `separator`: String to join tokens (default: ", ")
 ///   - `granularity_ids`: Which levels to include (default: all, in display order)
 ///   - `adhoc_positive/negative`: Additional tokens to inject
 ///   - `adhoc_position`: Where to place ad-hoc tokens (beginning or end)
+///   - `unsupported_negative`: Whether to keep (and flag) or drop the negative
+///     prompt when the persona's image model ignores it (default: warn)
+///   - `dual_prompt`: Also compose `prompt_2` for the second text encoder of
+///     SDXL and FLUX, with per-granularity routing (default: off)
+///   - `regional`: Split the positive prompt into image regions by granularity,
+///     in Regional Prompter or attention couple syntax (default: off)
+///   - `apply_aliases`: Replace aliased tokens with their preferred phrasing
+///     (default: off)
 ///
 /// # Returns
 ///
 /// A `ComposedPrompt` containing:
 /// - `positive_prompt`: Ready-to-use positive prompt string
+/// - `positive_prompt_2`: Prompt for the second text encoder, if requested
 /// - `negative_prompt`: Ready-to-use negative prompt string
 /// - Token counts for both prompts
 /// - Breakdown showing which tokens came from which granularity levels
+/// - `negative_prompt_unsupported`: Whether the image model ignores the negative prompt
+/// - `filtered_tokens`: Stored tokens left out for containing a banned term
 ///
 /// # Example Output
 ///
@@ -52,6 +106,7 @@ use crate::AppState;
 /// positive_prompt: "masterpiece, 1girl, (red hair:1.1)"
 /// ```
 #[tauri::command]
+#[tracing::instrument(skip_all, fields(persona_id = %persona_id), err)]
 pub fn compose_prompt(
     state: State<AppState>,
     persona_id: String,
@@ -62,13 +117,378 @@ pub fn compose_prompt(
         .lock()
         .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
 
-    let conn = db.connection();
+    let composed = db.unit_of_work(|conn| {
+        safe_mode::ensure_visible(&PersonaRepository::find_by_id(conn, &persona_id)?)?;
+        let opts = composition_options(conn, &persona_id, options)?;
+        let (tokens, filtered_tokens) = allowed_tokens(conn, &persona_id, &opts)?;
+        let granularity_levels = state.metadata.granularity_levels(conn)?;
+
+        let mut composed = PromptComposer::compose(
+            &tokens,
+            &granularity_levels,
+            &opts,
+            supports_negative_prompt(conn, &persona_id)?,
+        );
+        composed.filtered_tokens = filtered_tokens;
 
-    let tokens = TokenRepository::find_by_persona(conn, &persona_id)?;
-    let granularity_levels = GranularityLevel::all();
+        // Ad-hoc tokens are one-off additions and never persisted
+        if !opts.has_adhoc() {
+            PromptCacheRepository::store(conn, &persona_id, &opts.cache_key(), &composed)?;
+        }
+        ActivityRepository::record(conn, &persona_id, ActivityKind::Composed)?;
 
-    let opts = options.unwrap_or_default();
-    let composed = PromptComposer::compose(&tokens, &granularity_levels, &opts);
+        Ok(composed)
+    })?;
 
+    telemetry::record(db.connection(), Feature::ComposePrompt);
     Ok(composed)
 }
+
+/// Returns the persona's cached prompt, composing it on a cache miss.
+///
+/// The cache holds the last prompt composed without ad-hoc tokens and is
+/// cleared whenever the persona's tokens, generation parameters, or
+/// composition defaults change, or the banned terms are edited. On a miss,
+/// the prompt is composed with the persona's composition defaults (or the
+/// global defaults) and cached.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection
+/// * `persona_id` - UUID of the persona
+///
+/// # Returns
+///
+/// The `CachedPrompt`; compare `options_hash` with
+/// `CompositionOptions::cache_key` to know which options produced it.
+#[tauri::command]
+#[tracing::instrument(skip_all, fields(persona_id = %persona_id), err)]
+pub fn get_cached_prompt(
+    state: State<AppState>,
+    persona_id: String,
+) -> Result<CachedPrompt, AppError> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+    let conn = db.connection();
+
+    // Fail with NotFound for unknown personas rather than caching an empty prompt
+    safe_mode::ensure_visible(&PersonaRepository::find_by_id(conn, &persona_id)?)?;
+
+    if let Some(cached) = PromptCacheRepository::find(conn, &persona_id)? {
+        return Ok(cached);
+    }
+
+    let opts = composition_options(conn, &persona_id, None)?;
+    let (tokens, _) = allowed_tokens(conn, &persona_id, &opts)?;
+    let granularity_levels = state.metadata.granularity_levels(conn)?;
+
+    let composed = PromptComposer::compose(
+        &tokens,
+        &granularity_levels,
+        &opts,
+        supports_negative_prompt(conn, &persona_id)?,
+    );
+
+    PromptCacheRepository::store(conn, &persona_id, &opts.cache_key(), &composed)
+}
+
+/// Composes a prompt and renders it in every copy format.
+///
+/// Read-only: ad-hoc tokens in `options` only affect the returned strings.
+/// The persona's generation parameters, if saved, are included in the A1111
+/// settings line.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection
+/// * `persona_id` - UUID of the persona whose tokens to compose
+/// * `options` - Optional composition settings (see [`compose_prompt`])
+///
+/// # Returns
+///
+/// A `PromptPreview` with the `ComposedPrompt` and its `plain`, `a1111`,
+/// `comfyui`, and `json` encodings.
+#[tauri::command]
+#[tracing::instrument(skip_all, fields(persona_id = %persona_id), err)]
+pub fn compose_prompt_preview(
+    state: State<AppState>,
+    persona_id: String,
+    options: Option<CompositionOptions>,
+) -> Result<PromptPreview, AppError> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+    let conn = db.connection();
+
+    safe_mode::ensure_visible(&PersonaRepository::find_by_id(conn, &persona_id)?)?;
+    let opts = composition_options(conn, &persona_id, options)?;
+    let (tokens, filtered_tokens) = allowed_tokens(conn, &persona_id, &opts)?;
+    let granularity_levels = state.metadata.granularity_levels(conn)?;
+    let params = match PersonaRepository::find_resolved_generation_params(conn, &persona_id) {
+        Ok(params) => Some(params),
+        Err(AppError::NotFound(_)) => None,
+        Err(e) => return Err(e),
+    };
+
+    let model_id = params.as_ref().map(|p| p.model_id.as_str());
+    let context = tokenizer::get_prompt_context_for_model(model_id);
+
+    let mut preview = PromptComposer::preview(
+        &tokens,
+        &granularity_levels,
+        &opts,
+        context.supports_negative_prompt,
+        &context.family,
+        params.as_ref(),
+    );
+    preview.composed.filtered_tokens = filtered_tokens;
+    Ok(preview)
+}
+
+/// Composes one prompt per combination of alternative tokens.
+///
+/// Read-only, like [`compose_prompt_preview`]: the alternatives are only
+/// added to the returned prompts. The matrix includes plain and A1111
+/// script text lists for batch generation.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection
+/// * `persona_id` - UUID of the persona whose tokens to compose
+/// * `axes` - Varying aspects (e.g., hairstyle, outfit), each with its
+///   alternatives and optional granularity level
+/// * `options` - Optional composition settings (see [`compose_prompt`])
+///
+/// # Returns
+///
+/// A `PromptMatrix` with one labelled prompt per combination, the last axis
+/// varying fastest.
+///
+/// # Errors
+///
+/// Returns `AppError::Validation` if an axis is invalid or the matrix would
+/// exceed `MAX_MATRIX_PROMPTS` prompts.
+#[tauri::command]
+#[tracing::instrument(skip_all, fields(persona_id = %persona_id, axes = axes.len()), err)]
+pub fn generate_prompt_matrix(
+    state: State<AppState>,
+    persona_id: String,
+    axes: Vec<MatrixAxis>,
+    options: Option<CompositionOptions>,
+) -> Result<PromptMatrix, AppError> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+    let conn = db.connection();
+
+    safe_mode::ensure_visible(&PersonaRepository::find_by_id(conn, &persona_id)?)?;
+    let opts = composition_options(conn, &persona_id, options)?;
+    let (tokens, _) = allowed_tokens(conn, &persona_id, &opts)?;
+    let granularity_levels = state.metadata.granularity_levels(conn)?;
+
+    let matrix = PromptMatrix::generate(
+        persona_id.clone(),
+        &tokens,
+        &granularity_levels,
+        axes,
+        &opts,
+        supports_negative_prompt(conn, &persona_id)?,
+    )?;
+
+    telemetry::record(conn, Feature::PromptMatrix);
+    Ok(matrix)
+}
+
+/// Returns the default region layout for regional composition.
+///
+/// Hair and face tokens form the face region, body tokens the body region,
+/// and tokens of a custom `background` granularity the background region;
+/// style and general tokens are common to all regions. The layout is a
+/// starting point for the `regional` composition option.
+#[tauri::command]
+#[must_use]
+pub fn get_default_regions() -> RegionalOptions {
+    RegionalOptions::default()
+}
+
+/// Returns the persona's composition defaults.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection
+/// * `persona_id` - UUID of the persona
+///
+/// # Returns
+///
+/// The defaults, or `None` if the persona composes with the global defaults.
+#[tauri::command]
+#[tracing::instrument(skip_all, fields(persona_id = %persona_id), err)]
+pub fn get_composition_defaults(
+    state: State<AppState>,
+    persona_id: String,
+) -> Result<Option<CompositionDefaults>, AppError> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+    PersonaRepository::find_composition_defaults(db.connection(), &persona_id)
+}
+
+/// Sets the separator, weight toggle, weight syntax, and dual prompt settings
+/// a persona composes with when no options are passed.
+///
+/// Useful for personas targeting natural-language models, which read ". "
+/// separated sentences better than ", " separated tags.
+///
+/// # Arguments
+///
+/// * `window` - Window issuing the command, named in the change event
+/// * `state` - Application state containing the database connection
+/// * `persona_id` - UUID of the persona
+/// * `defaults` - New defaults, or `None` to return to the global defaults
+///
+/// # Errors
+///
+/// Returns `AppError::NotFound` if the persona does not exist, or
+/// `AppError::Validation` if a separator is empty.
+#[tauri::command]
+#[tracing::instrument(skip_all, fields(persona_id = %persona_id), err)]
+pub fn set_composition_defaults(
+    window: Window,
+    state: State<AppState>,
+    persona_id: String,
+    defaults: Option<CompositionDefaults>,
+) -> Result<(), AppError> {
+    if let Some(defaults) = &defaults {
+        defaults.validate()?;
+    }
+
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+    db.unit_of_work(|conn| {
+        PersonaRepository::set_composition_defaults(conn, &persona_id, defaults.as_ref())
+    })?;
+    emit_persona_changed(&window, &persona_id, ChangeKind::Updated);
+    Ok(())
+}
+
+/// Returns the given options, or the persona's composition defaults if none
+/// were given.
+pub(crate) fn composition_options(
+    conn: &Connection,
+    persona_id: &str,
+    options: Option<CompositionOptions>,
+) -> Result<CompositionOptions, AppError> {
+    if let Some(options) = options {
+        return Ok(options);
+    }
+
+    let defaults = PersonaRepository::find_composition_defaults(conn, persona_id)?;
+    Ok(defaults
+        .as_ref()
+        .map_or_else(CompositionOptions::default, CompositionDefaults::to_options))
+}
+
+/// Returns a persona's tokens without those containing a banned term, and
+/// the tokens left out.
+///
+/// With `apply_aliases` set in the options, aliased tokens are replaced by
+/// their phrasing first, so a phrasing containing a banned term is left out too.
+pub(crate) fn allowed_tokens(
+    conn: &Connection,
+    persona_id: &str,
+    options: &CompositionOptions,
+) -> Result<(Vec<Token>, Vec<FilteredToken>), AppError> {
+    let mut tokens = TokenRepository::find_resolved_by_persona(conn, persona_id)?;
+    if options.apply_aliases {
+        tokens = AliasMap::new(&TokenAliasRepository::find_all(conn)?).apply(tokens);
+    }
+    let banned_terms = BannedTermRepository::find_all(conn)?;
+    let allowed = banned_term::strip_banned(tokens, &banned_terms, |t| &t.content);
+    Ok(allowed)
+}
+
+/// Returns whether the image model in the persona's generation parameters
+/// uses a negative prompt. Personas without parameters use the default model.
+fn supports_negative_prompt(conn: &Connection, persona_id: &str) -> Result<bool, AppError> {
+    let model_id = match PersonaRepository::find_resolved_generation_params(conn, persona_id) {
+        Ok(params) => Some(params.model_id),
+        Err(AppError::NotFound(_)) => None,
+        Err(e) => return Err(e),
+    };
+    Ok(tokenizer::get_prompt_context_for_model(model_id.as_deref()).supports_negative_prompt)
+}
+
+/// Checks a prompt pair for common quality issues.
+///
+/// The linter is deterministic and needs no AI provider. It flags duplicate
+/// concepts, conflicting weights on the same concept, prompts longer than the
+/// model's limit, positive tokens that belong in the negative prompt, stray
+/// separators, malformed weight syntax, and negative prompts for models that
+/// ignore them. Token aliases count as their phrasing, so a variant next to
+/// its phrasing is flagged as a duplicate.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection
+/// * `positive_prompt` - The positive prompt text
+/// * `negative_prompt` - The negative prompt text (optional)
+/// * `model_id` - Image model used to measure prompt length.
+///   Defaults to SDXL-compatible CLIP tokenizer if not specified.
+///
+/// # Returns
+///
+/// Vector of `LintIssue`, positive prompt first; empty if the prompts are clean.
+#[tauri::command]
+#[must_use]
+pub fn lint_prompt(
+    state: State<AppState>,
+    positive_prompt: String,
+    negative_prompt: Option<String>,
+    model_id: Option<String>,
+) -> Vec<LintIssue> {
+    let negative_prompt = negative_prompt.unwrap_or_default();
+    let context = tokenizer::get_prompt_context_for_model(model_id.as_deref());
+    let model_name = context.display_name;
+
+    // Lint without aliases rather than fail if they cannot be read
+    let aliases = match state.db.lock() {
+        Ok(db) => {
+            telemetry::record(db.connection(), Feature::LintPrompt);
+            TokenAliasRepository::find_all(db.connection()).unwrap_or_default()
+        }
+        Err(_) => Vec::new(),
+    };
+    let aliases = AliasMap::new(&aliases);
+
+    let measure = |text: &str| {
+        (!text.trim().is_empty()).then(|| {
+            let count = tokenizer::count_tokens(text, model_id.as_deref());
+            PromptLength {
+                token_count: count.count,
+                usable_tokens: count.usable_tokens,
+                model_name: model_name.clone(),
+            }
+        })
+    };
+
+    PromptLinter::lint(&LintInput {
+        positive_prompt: &positive_prompt,
+        negative_prompt: &negative_prompt,
+        positive_length: measure(&positive_prompt),
+        negative_length: measure(&negative_prompt),
+        negative_ignored_by: (!context.supports_negative_prompt).then(|| model_name.clone()),
+        aliases: Some(&aliases),
+    })
+}
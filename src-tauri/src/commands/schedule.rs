@@ -0,0 +1,315 @@
+//! Scheduled Task Commands
+//!
+//! This module provides Tauri IPC commands for scheduled tasks, and the job
+//! the background scheduler runs (see `infrastructure::scheduler`).
+//!
+//! # Running
+//!
+//! Enabled tasks run on their schedule while the scheduler is turned on in the
+//! app settings; [`run_scheduled_task`] runs one immediately either way. A
+//! style refresh asks the task's AI provider for style tokens per persona and
+//! stages the new ones in the pending suggestions inbox (see
+//! `commands::suggestion`), where they wait for review. Failures are recorded
+//! per persona in the task's `last_error`; in offline mode, due runs of remote
+//! providers wait until the app is online.
+
+use chrono::Utc;
+use tauri::{AppHandle, Manager, State};
+
+use super::ai::{acquire_rate_limit, load_output_language, record_usage};
+use super::suggestion::stage_token_response;
+use crate::domain::schedule::{
+    CreateScheduledTaskRequest, ScheduledTask, ScheduledTaskKind, UpdateScheduledTaskRequest,
+};
+use crate::domain::suggestion::SuggestionSource;
+use crate::domain::telemetry::Feature;
+use crate::domain::token::Granularity;
+use crate::error::AppError;
+use crate::infrastructure::ai;
+use crate::infrastructure::ai::rate_limit::AiRateLimiter;
+use crate::infrastructure::ai::request_log::AiRequestLog;
+use crate::infrastructure::database::repositories::{
+    BannedTermRepository, PendingSuggestionRepository, PersonaRepository, ScheduledTaskRepository,
+    TokenRepository,
+};
+use crate::infrastructure::scheduler;
+use crate::AppState;
+
+/// Lists all scheduled tasks, oldest first.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection
+///
+/// # Returns
+///
+/// Vector of tasks with their last and next run, which may be empty.
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn list_scheduled_tasks(state: State<AppState>) -> Result<Vec<ScheduledTask>, AppError> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+    ScheduledTaskRepository::find_all(db.connection())
+}
+
+/// Creates a scheduled task, first due one interval from now.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection
+/// * `request` - Task name, personas, frequency, and AI provider
+///
+/// # Returns
+///
+/// The newly created task.
+///
+/// # Errors
+///
+/// Returns `AppError::NotFound` if one of the personas doesn't exist.
+/// Returns `AppError::Validation` if the request is invalid.
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn create_scheduled_task(
+    state: State<AppState>,
+    request: CreateScheduledTaskRequest,
+) -> Result<ScheduledTask, AppError> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+    db.unit_of_work(|conn| {
+        for persona_id in &request.persona_ids {
+            PersonaRepository::find_by_id(conn, persona_id)?;
+        }
+        ScheduledTaskRepository::create(conn, request)
+    })
+}
+
+/// Changes a scheduled task's settings or turns its schedule on or off.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection
+/// * `id` - UUID of the task to update
+/// * `request` - Fields to change; omitted fields are kept
+///
+/// # Returns
+///
+/// The updated task.
+///
+/// # Errors
+///
+/// Returns `AppError::NotFound` if the task or one of the new personas doesn't exist.
+/// Returns `AppError::Validation` if a new value is invalid.
+#[tauri::command]
+#[tracing::instrument(skip_all, fields(id = %id), err)]
+pub fn update_scheduled_task(
+    state: State<AppState>,
+    id: String,
+    request: UpdateScheduledTaskRequest,
+) -> Result<ScheduledTask, AppError> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+    db.unit_of_work(|conn| {
+        for persona_id in request.persona_ids.iter().flatten() {
+            PersonaRepository::find_by_id(conn, persona_id)?;
+        }
+        ScheduledTaskRepository::update(conn, &id, &request)
+    })
+}
+
+/// Deletes a scheduled task. Suggestions it staged stay pending.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection
+/// * `id` - UUID of the task to delete
+///
+/// # Errors
+///
+/// Returns `AppError::NotFound` if the task doesn't exist.
+#[tauri::command]
+#[tracing::instrument(skip_all, fields(id = %id), err)]
+pub fn delete_scheduled_task(state: State<AppState>, id: String) -> Result<(), AppError> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+    ScheduledTaskRepository::delete(db.connection(), &id)
+}
+
+/// Runs a scheduled task now, whether or not it is due or enabled.
+///
+/// The next run is rescheduled one interval from now.
+///
+/// # Arguments
+///
+/// * `app` - Application handle, used to emit queue and staging events
+/// * `limiter` - Per-provider AI rate limiter
+/// * `log` - Opt-in AI request log
+/// * `state` - Application state containing the database connection
+/// * `id` - UUID of the task to run
+///
+/// # Returns
+///
+/// The task with its run recorded; `last_error` lists the personas whose
+/// refresh failed.
+///
+/// # Errors
+///
+/// Returns `AppError::NotFound` if the task doesn't exist,
+/// `AppError::Validation` if it is already running, and `AppError::Offline`
+/// if offline mode blocks its provider.
+#[tauri::command]
+#[tracing::instrument(skip_all, fields(id = %id), err)]
+pub async fn run_scheduled_task(
+    app: AppHandle,
+    limiter: State<'_, AiRateLimiter>,
+    log: State<'_, AiRequestLog>,
+    state: State<'_, AppState>,
+    id: String,
+) -> Result<ScheduledTask, AppError> {
+    let task = {
+        let db = state
+            .db
+            .lock()
+            .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+        ScheduledTaskRepository::find_by_id(db.connection(), &id)?
+    };
+
+    run_task(&app, &limiter, &log, &state, task).await
+}
+
+/// Runs every due task; called by the scheduler's timer.
+///
+/// Errors are logged, and recorded in the task where they concern a persona.
+pub(crate) async fn run_due_tasks(app: AppHandle) {
+    let state = app.state::<AppState>();
+    let due = match load_due_tasks(&state) {
+        Ok(due) => due,
+        Err(error) => {
+            tracing::error!(%error, "Failed to load due scheduled tasks");
+            return;
+        }
+    };
+
+    let limiter = app.state::<AiRateLimiter>();
+    let log = app.state::<AiRequestLog>();
+    for task in due {
+        let task_id = task.id.clone();
+        if let Err(error) = run_task(&app, &limiter, &log, &state, task).await {
+            // Offline runs stay due and are retried on a later check
+            tracing::warn!(%error, task_id = %task_id, "Scheduled task did not run");
+        }
+    }
+}
+
+/// Loads the tasks due now (internal helper).
+fn load_due_tasks(state: &AppState) -> Result<Vec<ScheduledTask>, AppError> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+    ScheduledTaskRepository::find_due(db.connection(), Utc::now())
+}
+
+/// Runs a task and records the run.
+async fn run_task(
+    app: &AppHandle,
+    limiter: &AiRateLimiter,
+    log: &AiRequestLog,
+    state: &AppState,
+    mut task: ScheduledTask,
+) -> Result<ScheduledTask, AppError> {
+    let Some(_guard) = scheduler::begin_run(&task.id) else {
+        return Err(AppError::Validation(format!(
+            "Scheduled task '{}' is already running",
+            task.name
+        )));
+    };
+    ai::ensure_provider_online(&task.config)?;
+
+    let mut errors = Vec::new();
+    let mut remaining = Vec::new();
+    for persona_id in &task.persona_ids {
+        let result = match task.kind {
+            ScheduledTaskKind::StyleRefresh => {
+                refresh_style(app, limiter, log, state, &task, persona_id).await
+            }
+        };
+        match result {
+            Ok(()) => remaining.push(persona_id.clone()),
+            // Deleted since the task was set up
+            Err(AppError::NotFound(_)) => {}
+            Err(error) => {
+                errors.push(format!("{persona_id}: {error}"));
+                remaining.push(persona_id.clone());
+            }
+        }
+    }
+    task.persona_ids = remaining;
+    task.record_run(Utc::now(), &errors);
+
+    {
+        let db = state
+            .db
+            .lock()
+            .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+        ScheduledTaskRepository::save_run(db.connection(), &task)?;
+    }
+    record_usage(state, Feature::AiScheduledRefresh);
+
+    Ok(task)
+}
+
+/// Asks for style tokens for one persona and stages the new ones.
+async fn refresh_style(
+    app: &AppHandle,
+    limiter: &AiRateLimiter,
+    log: &AiRequestLog,
+    state: &AppState,
+    task: &ScheduledTask,
+    persona_id: &str,
+) -> Result<(), AppError> {
+    let (request, banned_terms, output_language) = {
+        let db = state
+            .db
+            .lock()
+            .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+        let conn = db.connection();
+        let persona = PersonaRepository::find_by_id(conn, persona_id)?;
+        let tokens = TokenRepository::find_resolved_by_persona(conn, persona_id)?;
+        let pending = PendingSuggestionRepository::find_by_persona(conn, persona_id)?;
+        let image_model_id = PersonaRepository::find_resolved_generation_params(conn, persona_id)
+            .ok()
+            .map(|params| params.model_id);
+        let request = task.style_refresh_request(&persona, &tokens, &pending, image_model_id);
+        (
+            request,
+            BannedTermRepository::find_all(conn)?,
+            load_output_language(conn, &[persona.ai_output_language.as_deref()])?,
+        )
+    };
+
+    let _permit = acquire_rate_limit(app, limiter, task.config.provider).await?;
+    let response =
+        ai::generate_tokens(&task.config, &request, &banned_terms, &output_language, log).await?;
+    stage_token_response(
+        app,
+        state,
+        SuggestionSource::ScheduledRefresh,
+        Some(&task.id),
+        persona_id,
+        Granularity::Style.as_str(),
+        response,
+    )?;
+    Ok(())
+}
@@ -0,0 +1,143 @@
+//! Quick Search Commands
+//!
+//! This module provides the Tauri IPC command behind the quick switcher
+//! (Cmd/Ctrl+K), which finds personas, tokens, smart collections, and
+//! persona templates from a single text box, and the advanced token search
+//! ([`search_tokens`]), which finds tokens by substring or regular expression.
+
+use tauri::State;
+
+use crate::domain::collation;
+use crate::domain::search::{match_templates, SearchResult, TokenPattern, TokenSearchMatch};
+use crate::domain::telemetry::Feature;
+use crate::domain::token::TokenPolarity;
+use crate::error::AppError;
+use crate::infrastructure::database::repositories::{
+    PersonaRepository, SearchRepository, TokenRepository,
+};
+use crate::infrastructure::{safe_mode, telemetry};
+use crate::AppState;
+
+/// Number of results returned when no limit is given.
+const DEFAULT_SEARCH_LIMIT: usize = 20;
+
+/// Upper bound on the number of results, to keep the switcher responsive.
+const MAX_SEARCH_LIMIT: usize = 100;
+
+/// Searches the library and the built-in templates.
+///
+/// Every word of the query must prefix-match a word of the entity (case and
+/// diacritics are ignored). Library results come from the full-text index
+/// ordered by relevance; matching templates follow them. In safe mode,
+/// mature-rated personas and their tokens are left out.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection
+/// * `query` - Search text
+/// * `limit` - Maximum number of results (default: 20, at most 100)
+///
+/// # Returns
+///
+/// Mixed results tagged with their kind, best first. Empty if the query has
+/// no searchable words.
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn quick_search(
+    state: State<AppState>,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<SearchResult>, AppError> {
+    let limit = limit.unwrap_or(DEFAULT_SEARCH_LIMIT).min(MAX_SEARCH_LIMIT);
+
+    let mut results = {
+        let db = state
+            .db
+            .lock()
+            .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+        telemetry::record(db.connection(), Feature::QuickSearch);
+        SearchRepository::search(db.connection(), &query, limit, safe_mode::is_safe_mode())?
+    };
+
+    results.extend(match_templates(&query));
+    results.truncate(limit);
+
+    Ok(results)
+}
+
+/// Finds tokens whose content matches a substring or regular expression.
+///
+/// Unlike [`quick_search`], this matches anywhere in the content (not only
+/// word prefixes) and returns every match. Substring queries ignore case and
+/// diacritics; regular expressions are case-sensitive unless they start with
+/// `(?i)`. In safe mode, mature-rated personas are not searched.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection
+/// * `query` - Text to find, or a regular expression if `regex` is set
+/// * `regex` - Whether `query` is a regular expression
+/// * `persona_ids` - Only search these personas (default: all, archived included)
+/// * `granularity` - Only match tokens of this granularity level ID
+/// * `polarity` - Only match tokens of this polarity
+///
+/// # Returns
+///
+/// Matching tokens with their persona, ordered by persona name, then by the
+/// persona's token order.
+///
+/// # Errors
+///
+/// Returns `AppError::Validation` if the query is blank or an invalid regular
+/// expression.
+#[tauri::command]
+#[tracing::instrument(skip_all, fields(regex), err)]
+pub fn search_tokens(
+    state: State<AppState>,
+    query: String,
+    regex: bool,
+    persona_ids: Option<Vec<String>>,
+    granularity: Option<String>,
+    polarity: Option<TokenPolarity>,
+) -> Result<Vec<TokenSearchMatch>, AppError> {
+    let pattern = TokenPattern::new(&query, regex)?;
+
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+    let conn = db.connection();
+
+    let mut personas: Vec<_> = PersonaRepository::find_all(conn)?
+        .into_iter()
+        .filter(safe_mode::is_visible)
+        .filter(|persona| {
+            persona_ids
+                .as_ref()
+                .map_or(true, |ids| ids.contains(&persona.id))
+        })
+        .collect();
+    personas.sort_by(|a, b| collation::compare(&a.name, &b.name));
+
+    let mut matches = Vec::new();
+    for persona in personas {
+        matches.extend(
+            TokenRepository::find_by_persona(conn, &persona.id)?
+                .into_iter()
+                .filter(|token| {
+                    granularity
+                        .as_ref()
+                        .map_or(true, |id| token.granularity_id == *id)
+                        && polarity.map_or(true, |p| token.polarity == p)
+                        && pattern.matches(&token.content)
+                })
+                .map(|token| TokenSearchMatch {
+                    token,
+                    persona_name: persona.name.clone(),
+                    persona_archived: persona.archived,
+                }),
+        );
+    }
+
+    Ok(matches)
+}
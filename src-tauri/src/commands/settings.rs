@@ -9,21 +9,55 @@
 //!
 //! # Security Model
 //!
-//! API keys are never stored in the application database or configuration files.
-//! The OS keyring provides:
+//! API keys are never stored in the application database or configuration files,
+//! and are never returned to the frontend: AI commands read them from the
+//! keyring inside the backend, so plaintext keys never cross the IPC boundary.
+//! Alongside each key, only the time it was stored is recorded so stale keys can
+//! be spotted. The OS keyring provides:
 //! - Encryption at rest
 //! - Access control tied to user session
 //! - Protection from unauthorized process access
 //!
+//! # Environment Passthrough
+//!
+//! On machines where credentials are injected via environment variables, the
+//! AI layer can read `OPENAI_API_KEY` etc. at call time instead (see
+//! `AiProviderConfig::use_env_credentials`). [`get_env_credential_status`]
+//! reports which variables were found.
+//!
+//! # Network Proxy
+//!
+//! [`get_app_settings`] and [`update_app_settings`] manage the explicit proxy
+//! used for tokenizer downloads and AI requests (see `infrastructure::proxy`).
+//! [`check_connectivity`] reports whether each endpoint is reachable.
+//!
+//! # Database Encryption
+//!
+//! [`set_database_passphrase`] encrypts the library with `SQLCipher` (or changes
+//! its passphrase) and [`remove_database_passphrase`] turns encryption off. The
+//! passphrase is kept in the OS keyring so the database opens at startup.
+//!
 //! # Linux Requirements
 //!
 //! Linux requires a Secret Service daemon (gnome-keyring or kwallet) to be running.
 //! The `check_credential_store` command allows the application to detect this
 //! and show appropriate guidance to users.
 
-use crate::domain::ai::AiProvider;
+use chrono::{DateTime, Utc};
+use tauri::State;
+
+use crate::domain::ai::{normalize_output_language, AiProvider, AiProviderConfig};
+use crate::domain::i18n::{Locale, MessageCatalog};
+use crate::domain::settings::{AppSettings, ConnectivityReport, DatabaseEncryptionStatus};
 use crate::error::AppError;
-use crate::infrastructure::keyring;
+use crate::infrastructure::database::repositories::{FeatureUsageRepository, SettingsRepository};
+use crate::infrastructure::{
+    ai, keyring, locale, offline, proxy, safe_mode, scheduler, telemetry, Database,
+};
+use crate::AppState;
+
+/// Minimum length of a database passphrase, in characters.
+const MIN_PASSPHRASE_LENGTH: usize = 8;
 
 /// Stores an API key securely in the OS credential store.
 ///
@@ -40,27 +74,54 @@ use crate::infrastructure::keyring;
 /// Returns `AppError::Internal` if the credential store is unavailable or
 /// the storage operation fails.
 #[tauri::command]
+#[tracing::instrument(skip_all, fields(provider = ?provider), err)]
 pub fn store_api_key(provider: AiProvider, api_key: String) -> Result<(), AppError> {
     keyring::store_api_key(&provider, &api_key)
 }
 
-/// Retrieves an API key from the OS credential store for a specific provider.
+/// Replaces a provider's API key after verifying the new key works.
+///
+/// A minimal test request is sent to the provider's default model using the
+/// new key. The stored key is only replaced if that request succeeds, so a
+/// mistyped or revoked key never overwrites a working one.
 ///
 /// # Arguments
 ///
-/// * `provider` - The AI provider whose key to retrieve
+/// * `provider` - The AI provider this key authenticates to
+/// * `new_key` - The replacement API key
 ///
 /// # Returns
 ///
-/// The API key if one is stored, or `None` if no key exists for this provider.
+/// The updated `ApiKeyStatus`, including the new storage timestamp.
 ///
 /// # Errors
 ///
-/// Returns `AppError::Internal` if the credential store is unavailable or
-/// the retrieval operation fails.
+/// Returns `AppError::Validation` if the key is empty or rejected by the provider,
+/// or `AppError::Internal` if the credential store operation fails.
 #[tauri::command]
-pub fn get_api_key_for_provider(provider: AiProvider) -> Result<Option<String>, AppError> {
-    keyring::get_api_key(&provider)
+#[tracing::instrument(skip_all, fields(provider = ?provider), err)]
+pub async fn rotate_api_key(
+    provider: AiProvider,
+    new_key: String,
+) -> Result<ApiKeyStatus, AppError> {
+    let new_key = new_key.trim().to_string();
+    if new_key.is_empty() {
+        return Err(AppError::Validation("API key cannot be empty".to_string()));
+    }
+
+    let config = AiProviderConfig {
+        api_key: Some(new_key.clone()),
+        ..AiProviderConfig::new(provider)
+    };
+    ai::verify_api_key(&config).await?;
+
+    keyring::store_api_key(&provider, &new_key)?;
+
+    Ok(ApiKeyStatus {
+        provider,
+        has_key: true,
+        stored_at: keyring::get_api_key_stored_at(&provider)?,
+    })
 }
 
 /// Deletes an API key from the OS credential store.
@@ -76,6 +137,7 @@ pub fn get_api_key_for_provider(provider: AiProvider) -> Result<Option<String>,
 /// Returns `AppError::Internal` if the credential store is unavailable or
 /// the deletion operation fails.
 #[tauri::command]
+#[tracing::instrument(skip_all, fields(provider = ?provider), err)]
 pub fn delete_api_key(provider: AiProvider) -> Result<(), AppError> {
     keyring::delete_api_key(&provider)
 }
@@ -90,6 +152,8 @@ pub struct ApiKeyStatus {
     pub provider: AiProvider,
     /// Whether an API key is stored for this provider
     pub has_key: bool,
+    /// When the key was stored, if recorded (keys saved by older versions have none)
+    pub stored_at: Option<DateTime<Utc>>,
 }
 
 /// Returns the API key status for all supported providers.
@@ -101,13 +165,61 @@ pub struct ApiKeyStatus {
 ///
 /// Vector of `ApiKeyStatus` for all providers (`OpenAI`, Anthropic, Google, xAI, Ollama).
 #[tauri::command]
+#[tracing::instrument(skip_all, err)]
 pub fn get_api_key_status() -> Result<Vec<ApiKeyStatus>, AppError> {
     let stored = keyring::get_providers_with_stored_keys()?;
 
-    Ok(stored
+    stored
         .into_iter()
-        .map(|(provider, has_key)| ApiKeyStatus { provider, has_key })
-        .collect())
+        .map(|(provider, has_key)| {
+            let stored_at = if has_key {
+                keyring::get_api_key_stored_at(&provider)?
+            } else {
+                None
+            };
+
+            Ok(ApiKeyStatus {
+                provider,
+                has_key,
+                stored_at,
+            })
+        })
+        .collect()
+}
+
+/// Environment credential status for a provider (passthrough mode).
+///
+/// Reports which environment variable is read and whether it is set,
+/// never its value.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct EnvCredentialStatus {
+    /// The AI provider this status applies to
+    pub provider: AiProvider,
+    /// Environment variable read for this provider (`None` if no key is needed)
+    pub env_var: Option<String>,
+    /// Whether the variable is set to a non-empty value
+    pub found: bool,
+}
+
+/// Returns which provider API key environment variables are set.
+///
+/// Used by the settings page when credential passthrough mode is enabled,
+/// so users can confirm their injected variables are visible to the app.
+///
+/// # Returns
+///
+/// Vector of `EnvCredentialStatus` for all providers.
+#[tauri::command]
+#[must_use]
+pub fn get_env_credential_status() -> Vec<EnvCredentialStatus> {
+    AiProvider::all()
+        .iter()
+        .map(|provider| EnvCredentialStatus {
+            provider: *provider,
+            env_var: provider.api_key_env_var().map(String::from),
+            found: keyring::has_env_api_key(provider),
+        })
+        .collect()
 }
 
 /// Checks if the system credential store is available and functional.
@@ -125,6 +237,287 @@ pub fn get_api_key_status() -> Result<Vec<ApiKeyStatus>, AppError> {
 /// The application calls this at startup on Linux to detect missing keyring
 /// services and display setup instructions to the user.
 #[tauri::command]
+#[tracing::instrument(skip_all, err)]
 pub fn check_credential_store() -> Result<bool, AppError> {
     keyring::check_credential_store_available()
 }
+
+/// Returns the backend application settings.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection
+///
+/// # Returns
+///
+/// The stored `AppSettings`, or defaults if none have been saved.
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn get_app_settings(state: State<AppState>) -> Result<AppSettings, AppError> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+    SettingsRepository::load(db.connection())
+}
+
+/// Saves the backend application settings and applies them immediately.
+///
+/// Proxy and offline mode changes affect new AI requests right away and
+/// tokenizer downloads that have not happened yet. Safe mode applies from the
+/// next list, search, or compose call, and the locale from the next message.
+/// Turning the scheduler on lets due tasks run from its next check.
+/// Turning telemetry off deletes the feature usage counters collected so far.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection
+/// * `settings` - The complete settings to store
+///
+/// # Errors
+///
+/// Returns `AppError::Validation` if the proxy settings, the AI output
+/// language, the resource folders, the tag dictionary paths, or the face
+/// embedder path are invalid.
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn update_app_settings(
+    state: State<AppState>,
+    mut settings: AppSettings,
+) -> Result<AppSettings, AppError> {
+    settings.proxy.validate()?;
+    settings.resource_folders.validate()?;
+    settings.validate_tag_dictionaries()?;
+    settings.validate_face_embedder()?;
+    settings.ai_output_language =
+        normalize_output_language(settings.ai_output_language.as_deref())?;
+
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+    SettingsRepository::save(db.connection(), &settings)?;
+    proxy::apply_proxy_settings(&settings.proxy);
+    offline::set_offline(settings.offline);
+    safe_mode::set_safe_mode(settings.safe_mode);
+    if !settings.telemetry {
+        FeatureUsageRepository::clear(db.connection())?;
+    }
+    telemetry::set_enabled(settings.telemetry);
+    locale::set_locale(settings.locale);
+    scheduler::set_enabled(settings.scheduler);
+
+    Ok(settings)
+}
+
+/// Changes the language of backend display text and stores it for future launches.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection
+/// * `locale` - The new display locale
+///
+/// # Returns
+///
+/// The message catalog of the new locale, so the frontend can render codes
+/// it receives without another call.
+#[tauri::command]
+#[tracing::instrument(skip_all, fields(locale = locale.code()), err)]
+pub fn set_locale(state: State<AppState>, locale: Locale) -> Result<MessageCatalog, AppError> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+    let conn = db.connection();
+
+    let mut settings = SettingsRepository::load(conn)?;
+    settings.locale = locale;
+    SettingsRepository::save(conn, &settings)?;
+
+    locale::set_locale(locale);
+    Ok(MessageCatalog::for_locale(locale))
+}
+
+/// Returns the message catalog of a locale.
+///
+/// Messages are keyed by the same codes the backend uses for errors
+/// (`error.<code>`), built-in granularity level names, and parameter warnings.
+///
+/// # Arguments
+///
+/// * `locale` - Locale to return (default: the current display locale)
+#[tauri::command]
+#[must_use]
+pub fn get_message_catalog(locale: Option<Locale>) -> MessageCatalog {
+    MessageCatalog::for_locale(locale.unwrap_or_else(locale::current))
+}
+
+/// Checks that `HuggingFace` and each AI provider API can be reached.
+///
+/// Uses the proxy currently in effect, tunnelling through it with HTTP
+/// `CONNECT` to verify the proxy lets the traffic through.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection
+///
+/// # Returns
+///
+/// A `ConnectivityReport` with the proxy source and one check per endpoint.
+///
+/// # Errors
+///
+/// Returns `AppError::Offline` if offline mode is enabled.
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn check_connectivity(
+    state: State<'_, AppState>,
+) -> Result<ConnectivityReport, AppError> {
+    offline::ensure_online("Checking connectivity")?;
+
+    let settings = {
+        let db = state
+            .db
+            .lock()
+            .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+        SettingsRepository::load(db.connection())?
+    };
+
+    Ok(proxy::check_connectivity(&settings.proxy).await)
+}
+
+/// Reports whether the database is encrypted and whether encryption is supported.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection and path
+///
+/// # Errors
+///
+/// Returns `AppError::Io` if the database file cannot be read.
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn get_database_encryption_status(
+    state: State<AppState>,
+) -> Result<DatabaseEncryptionStatus, AppError> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+    Ok(DatabaseEncryptionStatus {
+        available: db.encryption_available(),
+        encrypted: Database::is_encrypted_file(&state.db_path)?,
+    })
+}
+
+/// Encrypts the database with a passphrase, or changes its passphrase.
+///
+/// A plaintext database is rewritten encrypted. An encrypted one is rewritten
+/// with the new passphrase once `current_passphrase` is confirmed.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection and path
+/// * `current_passphrase` - The passphrase in use, required if already encrypted
+/// * `new_passphrase` - The passphrase to encrypt with
+///
+/// # Errors
+///
+/// Returns `AppError::Validation` if the new passphrase is too short or the
+/// current one does not match.
+/// Returns `AppError::Encryption` if this build does not support encryption.
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn set_database_passphrase(
+    state: State<AppState>,
+    current_passphrase: Option<String>,
+    new_passphrase: String,
+) -> Result<DatabaseEncryptionStatus, AppError> {
+    if new_passphrase.chars().count() < MIN_PASSPHRASE_LENGTH {
+        return Err(AppError::Validation(format!(
+            "Passphrase must be at least {MIN_PASSPHRASE_LENGTH} characters"
+        )));
+    }
+
+    rekey_database(&state, current_passphrase.as_deref(), Some(&new_passphrase))
+}
+
+/// Decrypts the database back to a plain `SQLite` file.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection and path
+/// * `current_passphrase` - The passphrase in use
+///
+/// # Errors
+///
+/// Returns `AppError::Validation` if the passphrase does not match.
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn remove_database_passphrase(
+    state: State<AppState>,
+    current_passphrase: String,
+) -> Result<DatabaseEncryptionStatus, AppError> {
+    rekey_database(&state, Some(&current_passphrase), None)
+}
+
+/// Rewrites the database with a new passphrase (or none) after confirming the current one.
+///
+/// The keyring is updated before the file is swapped and restored if the swap
+/// fails, so the stored passphrase always opens the file.
+fn rekey_database(
+    state: &AppState,
+    current: Option<&str>,
+    new: Option<&str>,
+) -> Result<DatabaseEncryptionStatus, AppError> {
+    let mut db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+    let stored = if Database::is_encrypted_file(&state.db_path)? {
+        let stored = keyring::get_database_passphrase()?.ok_or_else(|| {
+            AppError::Encryption("The database passphrase is missing from the keyring".to_string())
+        })?;
+        if current != Some(stored.as_str()) {
+            return Err(AppError::Validation(
+                "Current passphrase is incorrect".to_string(),
+            ));
+        }
+        Some(stored)
+    } else if new.is_none() {
+        return Err(AppError::Validation(
+            "The database is not encrypted".to_string(),
+        ));
+    } else {
+        None
+    };
+
+    if let Some(new) = new {
+        keyring::store_database_passphrase(new)?;
+    }
+
+    if let Err(e) = db.rekey(&state.db_path, stored.as_deref(), new) {
+        let _ = match stored.as_deref() {
+            Some(stored) => keyring::store_database_passphrase(stored),
+            None => keyring::delete_database_passphrase(),
+        };
+        return Err(e);
+    }
+
+    // A stale entry is harmless: the keyring is only read for encrypted files
+    if new.is_none() {
+        if let Err(e) = keyring::delete_database_passphrase() {
+            tracing::warn!(error = %e, "Failed to delete database passphrase from keyring");
+        }
+    }
+
+    tracing::info!(encrypted = new.is_some(), "Re-keyed database");
+    Ok(DatabaseEncryptionStatus {
+        available: true,
+        encrypted: new.is_some(),
+    })
+}
@@ -0,0 +1,191 @@
+//! Share Session Commands
+//!
+//! This module provides Tauri IPC commands for share sessions: a temporary,
+//! read-only web view of one smart collection for a collaborator on the same
+//! computer or network (see `domain::share`).
+//!
+//! # Serving
+//!
+//! Pages are rendered on each request from the current library, like the
+//! static site of `export_library_html`: the collection's query is run again,
+//! so edits show up on reload and personas leaving the collection disappear.
+//! Safe mode applies as in the app. Images are read from the gallery files
+//! and never copied.
+
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+use chrono::Utc;
+use tauri::{AppHandle, Manager, State};
+
+use super::collection::matching_personas;
+use super::export::site_personas;
+use crate::domain::share::{self, ShareRoute, ShareSession, StartShareRequest};
+use crate::domain::site::{self, SitePersona};
+use crate::domain::telemetry::Feature;
+use crate::error::AppError;
+use crate::infrastructure::database::repositories::SmartCollectionRepository;
+use crate::infrastructure::share_server::{self, ShareHandler, ShareResponse};
+use crate::infrastructure::telemetry;
+use crate::AppState;
+
+/// Starts sharing a smart collection, replacing any running session.
+///
+/// # Arguments
+///
+/// * `app` - Tauri app handle, used by the server to reach the database
+/// * `state` - Application state containing the database connection
+/// * `request` - The collection, whether to open the server to the local
+///   network, and the session length
+///
+/// # Returns
+///
+/// The session, with the URL to send to the collaborator.
+///
+/// # Errors
+///
+/// Returns `AppError::NotFound` if the collection does not exist,
+/// `AppError::Validation` if the duration is out of range or no local network
+/// address is found, or `AppError::Io` if the server cannot listen.
+#[tauri::command]
+#[tracing::instrument(skip_all, fields(collection_id = %request.collection_id), err)]
+pub fn start_share_session(
+    app: AppHandle,
+    state: State<AppState>,
+    request: StartShareRequest,
+) -> Result<ShareSession, AppError> {
+    let duration = request.duration()?;
+
+    let collection = {
+        let db = state
+            .db
+            .lock()
+            .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+        SmartCollectionRepository::find_by_id(db.connection(), &request.collection_id)?
+    };
+
+    let session = ShareSession::new(collection.id, collection.name, request.lan, duration);
+    let routing = session.clone();
+    let handler: ShareHandler = Arc::new(move |path| {
+        let state = app.state::<AppState>();
+        match respond(&state, &routing, path) {
+            Ok(response) => response,
+            Err(AppError::NotFound(_)) => ShareResponse::error(404, "Not found"),
+            Err(error) => {
+                tracing::error!(%error, "Failed to answer share request");
+                ShareResponse::error(500, "Something went wrong")
+            }
+        }
+    });
+    let session = share_server::start(session, handler)?;
+
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+    telemetry::record(db.connection(), Feature::ShareSession);
+    Ok(session)
+}
+
+/// Stops the running share session; its links stop working immediately.
+///
+/// # Returns
+///
+/// Whether a session was running.
+#[tauri::command]
+#[must_use]
+pub fn stop_share_session() -> bool {
+    share_server::stop()
+}
+
+/// Returns the running share session, or `None` if there is none or it
+/// expired.
+#[tauri::command]
+#[must_use]
+pub fn get_share_session() -> Option<ShareSession> {
+    share_server::current()
+}
+
+/// Answers a request to a share session (internal helper).
+///
+/// Unknown paths, wrong tokens, and personas outside the collection yield
+/// `AppError::NotFound`.
+fn respond(
+    state: &AppState,
+    session: &ShareSession,
+    path: &str,
+) -> Result<ShareResponse, AppError> {
+    let not_found = || AppError::NotFound("Shared resource".to_string());
+    let html =
+        |page: String| ShareResponse::ok(share::content_type("index.html"), page.into_bytes());
+
+    match session.route(path) {
+        ShareRoute::NotFound => Err(not_found()),
+        ShareRoute::Redirect(location) => Ok(ShareResponse::redirect(location)),
+        ShareRoute::Stylesheet => Ok(ShareResponse::ok(
+            share::content_type("style.css"),
+            site::STYLESHEET.as_bytes().to_vec(),
+        )),
+        ShareRoute::Index => {
+            let personas = shared_personas(state, session, None)?;
+            Ok(html(site::render_index(&personas, Utc::now())))
+        }
+        ShareRoute::Page(page) => {
+            let prefix = site::persona_id_prefix(&page).ok_or_else(not_found)?;
+            let persona = shared_personas(state, session, Some(prefix))?
+                .into_iter()
+                .find(|persona| persona.page == page)
+                .ok_or_else(not_found)?;
+            Ok(html(site::render_page(&persona, Utc::now())))
+        }
+        ShareRoute::Image(image) => {
+            let prefix = site::persona_id_prefix(&image).ok_or_else(not_found)?;
+            let source = shared_personas(state, session, Some(prefix))?
+                .iter()
+                .flat_map(SitePersona::image_sources)
+                .find(|(served, _)| *served == image)
+                .map(|(_, source)| source)
+                .ok_or_else(not_found)?;
+            let bytes = fs::read(source).map_err(|_| not_found())?;
+            Ok(ShareResponse::ok(share::content_type(&image), bytes))
+        }
+    }
+}
+
+/// Returns the shared collection's personas, with the images that exist on
+/// disk attached (internal helper).
+///
+/// # Arguments
+///
+/// * `id_prefix` - Only personas whose UUID starts with it, when given
+fn shared_personas(
+    state: &AppState,
+    session: &ShareSession,
+    id_prefix: Option<&str>,
+) -> Result<Vec<SitePersona>, AppError> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+    let conn = db.connection();
+
+    let collection = SmartCollectionRepository::find_by_id(conn, &session.collection_id)?;
+    let granularity_levels = state.metadata.granularity_levels(conn)?;
+    let mut matching = matching_personas(conn, &collection.query)?;
+    if let Some(prefix) = id_prefix {
+        matching.retain(|persona| persona.id.starts_with(prefix));
+    }
+
+    let mut personas = site_personas(conn, &granularity_levels, matching)?;
+    for persona in &mut personas {
+        persona.images = persona
+            .image_sources()
+            .into_iter()
+            .filter(|(_, source)| Path::new(source).is_file())
+            .map(|(image, _)| image)
+            .collect();
+    }
+    Ok(personas)
+}
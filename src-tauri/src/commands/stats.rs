@@ -0,0 +1,49 @@
+//! Library Statistics Commands
+//!
+//! This module provides the Tauri IPC command behind the library dashboard,
+//! which summarizes personas, tokens, tags, and target model families.
+
+use tauri::State;
+
+use crate::domain::stats::{model_family_counts, LibraryStats};
+use crate::error::AppError;
+use crate::infrastructure::database::repositories::StatsRepository;
+use crate::infrastructure::safe_mode;
+use crate::infrastructure::tokenizer;
+use crate::AppState;
+
+/// Gets aggregate statistics over the whole library.
+///
+/// In safe mode, mature-rated personas and their tokens are left out.
+/// Personas without generation parameters count toward the family of the
+/// default image model.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection
+///
+/// # Returns
+///
+/// Persona and token counts, tokens per granularity level, tag use, and
+/// personas per model family.
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn get_library_stats(state: State<AppState>) -> Result<LibraryStats, AppError> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+    let hide_mature = safe_mode::is_safe_mode();
+
+    let mut stats = StatsRepository::library_stats(db.connection(), hide_mature)?;
+    let by_model = StatsRepository::count_personas_by_model(db.connection(), hide_mature)?;
+    stats.personas_per_model_family =
+        model_family_counts(by_model.into_iter().map(|(model_id, count)| {
+            (
+                tokenizer::get_prompt_context_for_model(model_id.as_deref()).family,
+                count,
+            )
+        }));
+
+    Ok(stats)
+}
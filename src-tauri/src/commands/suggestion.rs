@@ -0,0 +1,212 @@
+//! Pending Suggestion Commands
+//!
+//! This module provides Tauri IPC commands for the inbox of AI-suggested
+//! tokens (see `domain::suggestion`).
+//!
+//! # Staging
+//!
+//! AI token suggestions requested for a persona and scheduled style refreshes
+//! stage their results here, emitting `suggestions-staged` so open windows
+//! can show them. Staged suggestions outlive the request: a reloaded window
+//! lists them again with [`list_pending_suggestions`].
+//!
+//! # Review
+//!
+//! Nothing in the inbox is applied automatically: the user accepts
+//! suggestions with [`accept_pending_suggestions`], which appends them as
+//! tokens, or drops them with [`reject_pending_suggestions`].
+
+use std::collections::HashSet;
+
+use tauri::{AppHandle, Emitter, State, Window};
+
+use super::emit_tokens_changed;
+use crate::domain::ai::TokenGenerationResponse;
+use crate::domain::suggestion::{
+    PendingSuggestion, SuggestionSource, SuggestionsStaged, SUGGESTIONS_STAGED_EVENT,
+};
+use crate::domain::token::{GeneratedTokenSelection, Token};
+use crate::error::AppError;
+use crate::infrastructure::database::repositories::{
+    PendingSuggestionRepository, PersonaRepository, TokenRepository,
+};
+use crate::services::TokenService;
+use crate::AppState;
+
+/// Lists pending suggestions, newest first.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection
+/// * `persona_id` - Only list this persona's suggestions (default: all)
+///
+/// # Returns
+///
+/// Vector of suggestions awaiting review, which may be empty.
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn list_pending_suggestions(
+    state: State<AppState>,
+    persona_id: Option<String>,
+) -> Result<Vec<PendingSuggestion>, AppError> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+    match persona_id {
+        Some(persona_id) => {
+            PendingSuggestionRepository::find_by_persona(db.connection(), &persona_id)
+        }
+        None => PendingSuggestionRepository::find_all(db.connection()),
+    }
+}
+
+/// Appends pending suggestions to their personas as tokens, in one transaction.
+///
+/// Accepted suggestions are removed from the inbox. If any suggestion cannot
+/// be accepted, nothing changes.
+///
+/// # Arguments
+///
+/// * `window` - Window issuing the command, named in the change events
+/// * `state` - Application state containing the database connection
+/// * `ids` - UUIDs of the suggestions to accept, in the order to append them
+///
+/// # Returns
+///
+/// Vector of all newly created tokens, in acceptance order.
+///
+/// # Errors
+///
+/// Returns `AppError::NotFound` if a suggestion doesn't exist, or
+/// `AppError::Validation` if one contains a banned term.
+#[tauri::command]
+#[tracing::instrument(skip_all, fields(count = ids.len()), err)]
+pub fn accept_pending_suggestions(
+    window: Window,
+    state: State<AppState>,
+    ids: Vec<String>,
+) -> Result<Vec<Token>, AppError> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+    let (tokens, persona_ids) = db.unit_of_work(|conn| {
+        let suggestions = PendingSuggestionRepository::find_by_ids(conn, &ids)?;
+
+        // One append per persona, keeping the acceptance order within each
+        let mut persona_ids: Vec<String> = Vec::new();
+        for suggestion in &suggestions {
+            if !persona_ids.contains(&suggestion.persona_id) {
+                persona_ids.push(suggestion.persona_id.clone());
+            }
+        }
+
+        let mut tokens = Vec::new();
+        for persona_id in &persona_ids {
+            let selections: Vec<GeneratedTokenSelection> = suggestions
+                .iter()
+                .filter(|suggestion| &suggestion.persona_id == persona_id)
+                .map(PendingSuggestion::to_selection)
+                .collect();
+            tokens.extend(TokenService::append_selections(
+                conn,
+                persona_id,
+                &selections,
+            )?);
+        }
+        for suggestion in &suggestions {
+            PendingSuggestionRepository::delete(conn, &suggestion.id)?;
+        }
+        Ok((tokens, persona_ids))
+    })?;
+
+    for persona_id in &persona_ids {
+        emit_tokens_changed(&window, persona_id);
+    }
+    Ok(tokens)
+}
+
+/// Discards pending suggestions without adding them.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection
+/// * `ids` - UUIDs of the suggestions to reject (duplicates are ignored)
+///
+/// # Returns
+///
+/// The number of suggestions rejected.
+///
+/// # Errors
+///
+/// Returns `AppError::NotFound` if a suggestion doesn't exist; nothing is rejected.
+#[tauri::command]
+#[tracing::instrument(skip_all, fields(count = ids.len()), err)]
+pub fn reject_pending_suggestions(
+    state: State<AppState>,
+    ids: Vec<String>,
+) -> Result<usize, AppError> {
+    let mut seen = HashSet::new();
+    let ids: Vec<String> = ids
+        .into_iter()
+        .filter(|id| seen.insert(id.clone()))
+        .collect();
+
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+    db.unit_of_work(|conn| {
+        ids.iter()
+            .try_for_each(|id| PendingSuggestionRepository::delete(conn, id))
+    })?;
+    Ok(ids.len())
+}
+
+/// Stages the new suggestions of a token response for a persona, and
+/// announces them (internal helper).
+///
+/// # Returns
+///
+/// The suggestions actually staged.
+///
+/// # Errors
+///
+/// Returns `AppError::NotFound` if the persona doesn't exist.
+pub(super) fn stage_token_response(
+    app: &AppHandle,
+    state: &AppState,
+    source: SuggestionSource,
+    task_id: Option<&str>,
+    persona_id: &str,
+    granularity_id: &str,
+    response: TokenGenerationResponse,
+) -> Result<Vec<PendingSuggestion>, AppError> {
+    let staged = {
+        let db = state
+            .db
+            .lock()
+            .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+        let conn = db.connection();
+        PersonaRepository::find_by_id(conn, persona_id)?;
+        let tokens = TokenRepository::find_resolved_by_persona(conn, persona_id)?;
+        let suggestions = PendingSuggestion::from_response(
+            source,
+            task_id,
+            persona_id,
+            granularity_id,
+            response,
+            &tokens,
+        );
+        PendingSuggestionRepository::stage(conn, suggestions)?
+    };
+
+    if let Some(payload) = SuggestionsStaged::from_staged(source, task_id, &staged) {
+        let _ = app.emit(SUGGESTIONS_STAGED_EVENT, payload);
+    }
+    Ok(staged)
+}
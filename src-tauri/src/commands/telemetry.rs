@@ -0,0 +1,57 @@
+//! Telemetry Commands
+//!
+//! This module provides Tauri IPC commands for the opt-in feature usage
+//! counters: viewing everything collected so far, and deleting it.
+
+use tauri::State;
+
+use crate::domain::telemetry::UsageReport;
+use crate::error::AppError;
+use crate::infrastructure::database::repositories::FeatureUsageRepository;
+use crate::infrastructure::telemetry;
+use crate::AppState;
+
+/// Gets the feature usage counters collected so far.
+///
+/// This is exactly what telemetry holds; it can be reviewed before anything
+/// is shared.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection
+///
+/// # Returns
+///
+/// Whether telemetry is enabled, the app version, and the counters, most
+/// used first.
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn get_usage_report(state: State<AppState>) -> Result<UsageReport, AppError> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+    Ok(UsageReport {
+        enabled: telemetry::is_enabled(),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        features: FeatureUsageRepository::find_all(db.connection())?,
+    })
+}
+
+/// Deletes all feature usage counters.
+///
+/// Counting continues afterwards if telemetry is still enabled.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn clear_usage_data(state: State<AppState>) -> Result<(), AppError> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+    FeatureUsageRepository::clear(db.connection())
+}
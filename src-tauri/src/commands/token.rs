@@ -11,16 +11,39 @@
 //!
 //! Tokens are grouped by granularity level to enable selective prompt composition.
 //! Users can choose which levels to include when composing prompts, allowing for
-//! flexible reuse of persona definitions.
+//! flexible reuse of persona definitions. [`optimize_token_order`] proposes a
+//! heuristic global order that can be applied with [`reorder_tokens`].
+//! [`find_replace_tokens`] edits token content across many personas at once.
+//! [`autocomplete_tokens`] completes a token as it is typed (see
+//! `domain::autocomplete`).
+//!
+//! # Banned Terms
+//!
+//! Creating tokens, applying AI suggestions, and editing or reverting token
+//! content fail with `AppError::Validation` if the content contains a banned
+//! term (see `domain::banned_term`).
 
-use tauri::State;
+use tauri::{State, Window};
 
+use super::collection::matching_personas;
+use super::emit_tokens_changed;
+use crate::domain::autocomplete::{self, TokenCompletion};
+use crate::domain::find_replace::{FindReplaceResult, ReplaceScope, TokenReplacer};
+use crate::domain::ordering;
+use crate::domain::telemetry::Feature;
 use crate::domain::token::{
-    BatchCreateTokenRequest, CreateTokenRequest, GranularityLevel, ReorderTokensRequest, Token,
-    UpdateTokenRequest,
+    BatchCreateTokenRequest, CreateTokenRequest, GeneratedTokenSelection, GranularityLevel,
+    ReorderTokensRequest, Token, TokenRevision, UpdateTokenRequest,
 };
+use crate::domain::token_pack::TokenPack;
+use crate::domain::DEFAULT_IMAGE_MODEL_ID;
 use crate::error::AppError;
-use crate::infrastructure::database::repositories::TokenRepository;
+use crate::infrastructure::database::repositories::{
+    BannedTermRepository, PersonaRepository, SettingsRepository, SmartCollectionRepository,
+    TokenRepository, TokenRevisionRepository,
+};
+use crate::infrastructure::{locale, safe_mode, tag_dictionary, telemetry, tokenizer};
+use crate::services::TokenService;
 use crate::AppState;
 
 /// Creates a single token for a persona.
@@ -30,14 +53,21 @@ use crate::AppState;
 ///
 /// # Arguments
 ///
+/// * `window` - Window issuing the command, named in the change event
 /// * `state` - Application state containing the database connection
 /// * `request` - Token creation data including `persona_id`, `granularity_id`, polarity, content, and weight
 ///
 /// # Returns
 ///
 /// The newly created token with generated ID and timestamps.
+///
+/// # Errors
+///
+/// Returns `AppError::Validation` if the content contains a banned term.
 #[tauri::command]
+#[tracing::instrument(skip_all, err)]
 pub fn create_token(
+    window: Window,
     state: State<AppState>,
     request: CreateTokenRequest,
 ) -> Result<Token, AppError> {
@@ -46,7 +76,9 @@ pub fn create_token(
         .lock()
         .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
 
-    TokenRepository::create(db.connection(), &request)
+    let token = TokenService::create(db.connection(), &request)?;
+    emit_tokens_changed(&window, &token.persona_id);
+    Ok(token)
 }
 
 /// Creates multiple tokens at once from comma-separated input.
@@ -57,6 +89,7 @@ pub fn create_token(
 ///
 /// # Arguments
 ///
+/// * `window` - Window issuing the command, named in the change event
 /// * `state` - Application state containing the database connection
 /// * `request` - Batch creation data with comma-separated contents string
 ///
@@ -64,11 +97,18 @@ pub fn create_token(
 ///
 /// Vector of all newly created tokens, in creation order.
 ///
+/// # Errors
+///
+/// Returns `AppError::Validation` if any of the contents contains a banned
+/// term; no token is created.
+///
 /// # Example
 ///
 /// A request with contents "red hair, long hair, flowing" creates three tokens.
 #[tauri::command]
+#[tracing::instrument(skip_all, err)]
 pub fn create_tokens_batch(
+    window: Window,
     state: State<AppState>,
     request: BatchCreateTokenRequest,
 ) -> Result<Vec<Token>, AppError> {
@@ -77,16 +117,48 @@ pub fn create_tokens_batch(
         .lock()
         .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
 
-    let contents = request.parse_contents();
+    let tokens = TokenService::create_batch(db.connection(), &request)?;
+    emit_tokens_changed(&window, &request.persona_id);
+    Ok(tokens)
+}
 
-    TokenRepository::create_batch(
-        db.connection(),
-        &request.persona_id,
-        &request.granularity_id,
-        request.polarity,
-        &contents,
-        request.weight,
-    )
+/// Saves accepted AI suggestions as tokens in a single transaction.
+///
+/// Replaces one `create_token` call per suggestion: each selection keeps its own
+/// granularity, polarity, and weight, and either all are saved or none are.
+///
+/// # Arguments
+///
+/// * `window` - Window issuing the command, named in the change event
+/// * `state` - Application state containing the database connection
+/// * `persona_id` - UUID of the persona receiving the tokens
+/// * `selections` - The suggestions the user accepted, in the order to append them
+///
+/// # Returns
+///
+/// Vector of all newly created tokens, in selection order.
+///
+/// # Errors
+///
+/// Returns `AppError::NotFound` if the persona does not exist, or
+/// `AppError::Validation` if a selection has an unknown granularity or
+/// contains a banned term.
+#[tauri::command]
+#[tracing::instrument(skip_all, fields(persona_id = %persona_id), err)]
+pub fn apply_generated_tokens(
+    window: Window,
+    state: State<AppState>,
+    persona_id: String,
+    selections: Vec<GeneratedTokenSelection>,
+) -> Result<Vec<Token>, AppError> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+    let tokens = TokenService::append_selections(db.connection(), &persona_id, &selections)?;
+    emit_tokens_changed(&window, &persona_id);
+    Ok(tokens)
 }
 
 /// Retrieves all tokens for a persona in user-defined order.
@@ -103,6 +175,7 @@ pub fn create_tokens_batch(
 ///
 /// Vector of all tokens belonging to the persona, which may be empty.
 #[tauri::command]
+#[tracing::instrument(skip_all, fields(persona_id = %persona_id), err)]
 pub fn get_tokens_by_persona(
     state: State<AppState>,
     persona_id: String,
@@ -115,6 +188,61 @@ pub fn get_tokens_by_persona(
     TokenRepository::find_by_persona(db.connection(), &persona_id)
 }
 
+/// Completes a token being typed from the library and the tag dictionaries.
+///
+/// Library completions come first, ranked by how many tokens use them; tag
+/// dictionaries set in the app settings fill the remaining places. In safe
+/// mode, tokens of mature-rated personas are not offered.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection
+/// * `prefix` - Text typed so far; leading spaces and case are ignored
+/// * `granularity_id` - Only offer library tokens of this granularity level (default: all)
+/// * `limit` - Maximum number of completions (default 10, at most 50)
+///
+/// # Returns
+///
+/// Completions, best first; empty if the prefix is blank.
+///
+/// # Errors
+///
+/// Returns `AppError::Io` if a tag dictionary cannot be read.
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn autocomplete_tokens(
+    state: State<AppState>,
+    prefix: String,
+    granularity_id: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<TokenCompletion>, AppError> {
+    let prefix_key = autocomplete::prefix_key(&prefix);
+    let limit = autocomplete::clamp_limit(limit);
+    if prefix_key.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let (library, dictionaries) = {
+        let db = state
+            .db
+            .lock()
+            .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+        let conn = db.connection();
+        let library = TokenRepository::complete_prefix(
+            conn,
+            &prefix_key,
+            granularity_id.as_deref(),
+            limit,
+            safe_mode::is_safe_mode(),
+        )?;
+        (library, SettingsRepository::load(conn)?.tag_dictionaries)
+    };
+
+    // Dictionaries may need loading; the database is unlocked by now
+    let dictionary = tag_dictionary::complete(&dictionaries, &prefix_key, limit)?;
+    Ok(autocomplete::merge(library, dictionary, limit))
+}
+
 /// Updates a token's content, weight, granularity, or polarity.
 ///
 /// Only fields present in the request are updated. The `updated_at` timestamp
@@ -122,6 +250,7 @@ pub fn get_tokens_by_persona(
 ///
 /// # Arguments
 ///
+/// * `window` - Window issuing the command, named in the change event
 /// * `state` - Application state containing the database connection
 /// * `id` - UUID of the token to update
 /// * `request` - Partial update data (all fields optional)
@@ -132,9 +261,12 @@ pub fn get_tokens_by_persona(
 ///
 /// # Errors
 ///
-/// Returns `AppError::NotFound` if no token exists with the given ID.
+/// Returns `AppError::NotFound` if no token exists with the given ID, or
+/// `AppError::Validation` if the new content contains a banned term.
 #[tauri::command]
+#[tracing::instrument(skip_all, fields(id = %id), err)]
 pub fn update_token(
+    window: Window,
     state: State<AppState>,
     id: String,
     request: UpdateTokenRequest,
@@ -144,13 +276,189 @@ pub fn update_token(
         .lock()
         .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
 
-    TokenRepository::update(db.connection(), &id, &request)
+    let token = TokenService::update(db.connection(), &id, &request)?;
+    emit_tokens_changed(&window, &token.persona_id);
+    Ok(token)
+}
+
+/// Returns the edit trail of a token.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection
+/// * `token_id` - UUID of the token
+///
+/// # Returns
+///
+/// Previous content and weight values, most recent first. Only the latest
+/// 50 edits are kept.
+#[tauri::command]
+#[tracing::instrument(skip_all, fields(token_id = %token_id), err)]
+pub fn get_token_history(
+    state: State<AppState>,
+    token_id: String,
+) -> Result<Vec<TokenRevision>, AppError> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+    TokenRevisionRepository::find_by_token(db.connection(), &token_id)
+}
+
+/// Restores a token's content and weight from one of its revisions.
+///
+/// The revert is itself an edit: the values it replaces are recorded as a new
+/// revision, so it can be undone the same way.
+///
+/// # Arguments
+///
+/// * `window` - Window issuing the command, named in the change event
+/// * `state` - Application state containing the database connection
+/// * `token_id` - UUID of the token
+/// * `revision` - Revision number from `get_token_history`
+///
+/// # Returns
+///
+/// The token with the restored values.
+///
+/// # Errors
+///
+/// Returns `AppError::NotFound` if the token or revision doesn't exist, or
+/// `AppError::Validation` if the restored content contains a banned term.
+#[tauri::command]
+#[tracing::instrument(skip_all, fields(token_id = %token_id, revision), err)]
+pub fn revert_token(
+    window: Window,
+    state: State<AppState>,
+    token_id: String,
+    revision: i32,
+) -> Result<Token, AppError> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+    let token = TokenService::revert(db.connection(), &token_id, revision)?;
+    telemetry::record(db.connection(), Feature::RevertToken);
+    emit_tokens_changed(&window, &token.persona_id);
+    Ok(token)
+}
+
+/// Replaces text in token content across a persona, smart collection, or the
+/// whole library.
+///
+/// Every changed token is returned with its content before and after. With
+/// `dry_run`, or if any replacement is rejected (empty content, a duplicate
+/// token, or a banned term), nothing is written; otherwise all replacements
+/// are applied in one transaction and recorded in each token's history.
+///
+/// # Arguments
+///
+/// * `window` - Window issuing the command, named in the change events
+/// * `state` - Application state containing the database connection
+/// * `scope` - The persona, collection, or library to search
+/// * `find` - Text to find, or a regular expression if `regex` is set
+/// * `replace` - Replacement text; with `regex`, `$1` and `${name}` insert groups
+/// * `regex` - Whether `find` is a regular expression
+/// * `dry_run` - Only preview the replacements
+///
+/// # Returns
+///
+/// A `FindReplaceResult` listing the replacements and whether they were applied.
+///
+/// # Errors
+///
+/// Returns `AppError::Validation` if `find` is empty or an invalid regular
+/// expression, or `AppError::NotFound` if the scope's persona or collection
+/// does not exist (or safe mode hides the persona).
+#[tauri::command]
+#[tracing::instrument(skip_all, fields(scope = ?scope, regex, dry_run), err)]
+pub fn find_replace_tokens(
+    window: Window,
+    state: State<AppState>,
+    scope: ReplaceScope,
+    find: String,
+    replace: String,
+    regex: bool,
+    dry_run: bool,
+) -> Result<FindReplaceResult, AppError> {
+    let replacer = TokenReplacer::new(&find, &replace, regex)?;
+
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+    let result = db.unit_of_work(|conn| {
+        let personas = match &scope {
+            ReplaceScope::Persona { persona_id } => {
+                let persona = PersonaRepository::find_by_id(conn, persona_id)?;
+                safe_mode::ensure_visible(&persona)?;
+                vec![persona]
+            }
+            ReplaceScope::Collection { collection_id } => {
+                let collection = SmartCollectionRepository::find_by_id(conn, collection_id)?;
+                matching_personas(conn, &collection.query)?
+            }
+            ReplaceScope::Library => PersonaRepository::find_all(conn)?
+                .into_iter()
+                .filter(safe_mode::is_visible)
+                .collect(),
+        };
+
+        let banned_terms = BannedTermRepository::find_all(conn)?;
+        let mut replacements = Vec::new();
+        for persona in &personas {
+            let tokens = TokenRepository::find_by_persona(conn, &persona.id)?;
+            replacements.extend(replacer.plan(persona, &tokens, &banned_terms));
+        }
+
+        let applied = !dry_run
+            && !replacements.is_empty()
+            && replacements.iter().all(|r| r.rejected.is_none());
+        if applied {
+            for replacement in &replacements {
+                TokenRepository::update(
+                    conn,
+                    &replacement.token_id,
+                    &UpdateTokenRequest {
+                        content: Some(replacement.after.clone()),
+                        weight: None,
+                        granularity_id: None,
+                        polarity: None,
+                        expected_updated_at: None,
+                    },
+                )?;
+            }
+        }
+
+        Ok(FindReplaceResult {
+            replacements,
+            applied,
+        })
+    })?;
+
+    if result.applied {
+        let mut persona_ids: Vec<&str> = result
+            .replacements
+            .iter()
+            .map(|r| r.persona_id.as_str())
+            .collect();
+        persona_ids.dedup();
+        for persona_id in persona_ids {
+            emit_tokens_changed(&window, persona_id);
+        }
+    }
+    telemetry::record(db.connection(), Feature::FindReplace);
+    Ok(result)
 }
 
 /// Deletes a token permanently.
 ///
 /// # Arguments
 ///
+/// * `window` - Window issuing the command, named in the change event
 /// * `state` - Application state containing the database connection
 /// * `id` - UUID of the token to delete
 ///
@@ -158,30 +466,59 @@ pub fn update_token(
 ///
 /// Returns `AppError::NotFound` if no token exists with the given ID.
 #[tauri::command]
-pub fn delete_token(state: State<AppState>, id: String) -> Result<(), AppError> {
+#[tracing::instrument(skip_all, fields(id = %id), err)]
+pub fn delete_token(window: Window, state: State<AppState>, id: String) -> Result<(), AppError> {
     let db = state
         .db
         .lock()
         .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
 
-    TokenRepository::delete(db.connection(), &id)
+    let persona_id = db.unit_of_work(|conn| {
+        let token = TokenRepository::find_by_id(conn, &id)?;
+        TokenRepository::delete(conn, &id)?;
+        Ok(token.persona_id)
+    })?;
+
+    emit_tokens_changed(&window, &persona_id);
+    Ok(())
 }
 
 /// Returns all available granularity levels.
 ///
-/// Granularity levels are hardcoded constants representing the hierarchical
-/// categories for organizing tokens: Style, General, Hair, Face, Upper Body, Midsection, Lower Body.
+/// Granularity levels are the hierarchical categories for organizing tokens:
+/// Style, General, Hair, Face, Upper Body, Midsection, Lower Body. They are
+/// stored in the database, including each level's color and display order,
+/// and served from the metadata cache, so unchanged levels compare equal
+/// across calls.
 ///
 /// This endpoint provides the frontend with the canonical list for UI rendering
 /// and validation.
 ///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection
+///
 /// # Returns
 ///
-/// Vector of all granularity levels in display order.
+/// Vector of all granularity levels in display order, with built-in level
+/// names in the current display locale.
 #[tauri::command]
-#[must_use]
-pub fn get_all_granularity_levels() -> Vec<GranularityLevel> {
-    GranularityLevel::all()
+#[tracing::instrument(skip_all, err)]
+pub fn get_all_granularity_levels(
+    state: State<AppState>,
+) -> Result<Vec<GranularityLevel>, AppError> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+    let locale = locale::current();
+    Ok(state
+        .metadata
+        .granularity_levels(db.connection())?
+        .into_iter()
+        .map(|level| level.localized(locale))
+        .collect())
 }
 
 /// Reorders tokens within a persona.
@@ -192,6 +529,7 @@ pub fn get_all_granularity_levels() -> Vec<GranularityLevel> {
 ///
 /// # Arguments
 ///
+/// * `window` - Window issuing the command, named in the change event
 /// * `state` - Application state containing the database connection
 /// * `request` - Reorder request with `persona_id` and `token_orders` array
 ///
@@ -200,7 +538,9 @@ pub fn get_all_granularity_levels() -> Vec<GranularityLevel> {
 /// Returns `AppError::Validation` if any token doesn't belong to the specified persona.
 /// Returns `AppError::NotFound` if any token ID doesn't exist.
 #[tauri::command]
+#[tracing::instrument(skip_all, err)]
 pub fn reorder_tokens(
+    window: Window,
     state: State<AppState>,
     request: ReorderTokensRequest,
 ) -> Result<(), AppError> {
@@ -209,5 +549,164 @@ pub fn reorder_tokens(
         .lock()
         .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
 
-    TokenRepository::reorder_tokens(db.connection(), &request)
+    db.unit_of_work(|conn| TokenRepository::reorder_tokens(conn, &request))?;
+    emit_tokens_changed(&window, &request.persona_id);
+    Ok(())
+}
+
+/// Reorders the tokens of one granularity level.
+///
+/// The backend recomputes the global display order: the level keeps the
+/// positions its tokens occupy, filled in the new order, and every other
+/// token stays where it is. The frontend only sends the order of the level
+/// that was dragged.
+///
+/// # Arguments
+///
+/// * `window` - Window issuing the command, named in the change event
+/// * `state` - Application state containing the database connection
+/// * `persona_id` - UUID of the persona
+/// * `granularity_id` - Level whose tokens are reordered
+/// * `ordered_token_ids` - Every token ID of that level, in the new order
+///
+/// # Returns
+///
+/// All of the persona's tokens in their new order.
+///
+/// # Errors
+///
+/// Returns `AppError::Validation` if `ordered_token_ids` does not list each
+/// token of the level exactly once.
+#[tauri::command]
+#[tracing::instrument(skip_all, fields(persona_id = %persona_id), err)]
+pub fn reorder_within_granularity(
+    window: Window,
+    state: State<AppState>,
+    persona_id: String,
+    granularity_id: String,
+    ordered_token_ids: Vec<String>,
+) -> Result<Vec<Token>, AppError> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+    let tokens = db.unit_of_work(|conn| {
+        TokenRepository::reorder_within_granularity(
+            conn,
+            &persona_id,
+            &granularity_id,
+            &ordered_token_ids,
+        )
+    })?;
+    emit_tokens_changed(&window, &persona_id);
+    Ok(tokens)
+}
+
+/// Lists the built-in starter token packs.
+///
+/// Packs are embedded in the application and need no AI provider.
+///
+/// # Returns
+///
+/// All packs, each with the model families it targets and its tokens.
+#[tauri::command]
+pub fn list_token_packs() -> Vec<TokenPack> {
+    TokenPack::all().to_vec()
+}
+
+/// Adds the tokens of a built-in pack to a persona.
+///
+/// Tokens are appended after the persona's existing ones. Tokens the persona
+/// already has (same granularity, polarity, and content) are skipped, so
+/// applying a pack twice changes nothing.
+///
+/// # Arguments
+///
+/// * `window` - Window issuing the command, named in the change event
+/// * `state` - Application state containing the database connection
+/// * `persona_id` - UUID of the persona receiving the tokens
+/// * `pack_id` - ID of the pack to apply (e.g., "`sdxl_negatives`")
+///
+/// # Returns
+///
+/// The tokens created for the persona.
+///
+/// # Errors
+///
+/// Returns `AppError::NotFound` if the persona or pack does not exist.
+#[tauri::command]
+#[tracing::instrument(skip_all, fields(persona_id = %persona_id, pack_id = %pack_id), err)]
+pub fn apply_token_pack(
+    window: Window,
+    state: State<AppState>,
+    persona_id: String,
+    pack_id: String,
+) -> Result<Vec<Token>, AppError> {
+    let pack = TokenPack::find(&pack_id)
+        .ok_or_else(|| AppError::NotFound(format!("Token pack '{pack_id}' not found")))?;
+
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+    let tokens = TokenService::apply_pack(db.connection(), &persona_id, pack)?;
+    telemetry::record(db.connection(), Feature::ApplyTokenPack);
+    emit_tokens_changed(&window, &persona_id);
+    Ok(tokens)
+}
+
+/// Proposes a better token order for a persona using prompt heuristics.
+///
+/// Subject tokens come first, Style early, detail modifiers last, and
+/// emphasized tokens are kept within the first chunk of the persona's image
+/// model (see [`crate::domain::ordering`]). Nothing is saved; pass the result
+/// to `reorder_tokens` to apply it.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection
+/// * `persona_id` - UUID of the persona
+///
+/// # Returns
+///
+/// A reorder request covering every token of the persona.
+///
+/// # Errors
+///
+/// Returns `AppError::NotFound` if the persona does not exist.
+#[tauri::command]
+#[tracing::instrument(skip_all, fields(persona_id = %persona_id), err)]
+pub fn optimize_token_order(
+    state: State<AppState>,
+    persona_id: String,
+) -> Result<ReorderTokensRequest, AppError> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+    let conn = db.connection();
+
+    PersonaRepository::find_by_id(conn, &persona_id)?;
+    let model_id = match PersonaRepository::find_resolved_generation_params(conn, &persona_id) {
+        Ok(params) => Some(params.model_id),
+        Err(AppError::NotFound(_)) => None,
+        Err(e) => return Err(e),
+    };
+    let tokens = TokenRepository::find_by_persona(conn, &persona_id)?;
+    let levels = state.metadata.granularity_levels(conn)?;
+
+    let model_id = model_id.as_deref();
+    let chunk_budget =
+        tokenizer::get_config_for_model(model_id.unwrap_or(DEFAULT_IMAGE_MODEL_ID)).usable_tokens;
+
+    Ok(ordering::propose_token_order(
+        &persona_id,
+        &tokens,
+        &levels,
+        chunk_budget,
+        // One extra token for the separator
+        |token| tokenizer::estimate_tokens(&token.format_for_prompt(true), model_id).count + 1,
+    ))
 }
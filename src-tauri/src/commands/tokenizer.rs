@@ -18,17 +18,66 @@
 //! - Exact match against known model configurations
 //! - Family-based fallback (e.g., any "pixart" model uses T5)
 //! - Default to CLIP tokenizer for unknown models
+//!
+//! # Caching and Warmup
+//!
+//! Exact counts are cached in the database by (text hash, tokenizer), so repeated
+//! texts never need the tokenizer again. When a count needs a tokenizer that is
+//! not loaded yet, an estimate is returned immediately and the tokenizer loads in
+//! the background; a `tokenizer-loaded` event signals the frontend to recount.
+//! [`warmup_tokenizers`] starts those loads ahead of time.
+//!
+//! [`count_tokens_batch`] counts several labeled texts in one IPC round trip.
+//! [`tokenize_text`] returns the token pieces themselves, for highlighting
+//! token boundaries in the prompt editor.
+//!
+//! [`suggest_quality_tokens`] recommends model-appropriate quality tags.
+//! [`list_resolution_presets`] and [`check_resolution`] cover the model's
+//! native output sizes.
+//!
+//! [`lookup_civitai_model_by_hash`] and [`search_civitai_models`] fetch the
+//! family and trigger words of a checkpoint or `LoRA` from Civitai, so a
+//! model the user brings can be set up with the right tokenizer and tokens.
+//! [`search_hf_models`] finds the exact repo ID of a `HuggingFace` model and
+//! shows which tokenizer it would get. [`scan_local_resources`] lists the
+//! embeddings and `LoRA` models in the user's folders with their triggers.
 
-use crate::infrastructure::tokenizer::{self, TokenCount, TokenizerInfo};
+use std::collections::HashSet;
+
+use rusqlite::Connection;
+use tauri::{AppHandle, State};
+
+use crate::domain::civitai::{
+    CivitaiModel, DEFAULT_CIVITAI_SEARCH_LIMIT, MAX_CIVITAI_SEARCH_LIMIT,
+};
+use crate::domain::resolution::{ResolutionCheck, ResolutionPresets};
+use crate::domain::resource::LocalResource;
+use crate::domain::token::{GeneratedTokenSelection, Granularity, TokenPolarity};
+use crate::domain::DEFAULT_IMAGE_MODEL_ID;
+use crate::error::AppError;
+use crate::infrastructure::database::repositories::token_count_cache::MAX_CACHED_COUNTS;
+use crate::infrastructure::database::repositories::{
+    SettingsRepository, TokenCountCacheRepository,
+};
+use crate::infrastructure::huggingface::{HubModel, DEFAULT_PIPELINE_TAG};
+use crate::infrastructure::tokenizer::{
+    self, LabeledText, LabeledTokenCount, TokenCount, TokenizedText, TokenizerInfo,
+};
+use crate::infrastructure::{civitai, huggingface, locale, resource_scan};
+use crate::AppState;
 
 /// Counts tokens in text for a specific image generation model.
 ///
 /// Uses the `HuggingFace` tokenizers library for accurate counting with the same
-/// tokenizer used by the target model. Falls back to word-based estimation
-/// if the tokenizer cannot be loaded.
+/// tokenizer used by the target model. Cached counts are returned without
+/// touching the tokenizer. If the tokenizer is not loaded yet, a word-based
+/// estimate is returned (`estimated: true`) while the tokenizer loads in the
+/// background; a `tokenizer-loaded` event is emitted when it is ready.
 ///
 /// # Arguments
 ///
+/// * `app` - Tauri app handle used to emit the load event
+/// * `state` - Application state containing the database connection
 /// * `text` - The prompt text to count tokens for
 /// * `model_id` - Optional model identifier (e.g., "stabilityai/stable-diffusion-xl-base-1.0").
 ///               Defaults to SDXL-compatible CLIP tokenizer if not specified.
@@ -41,10 +90,163 @@ use crate::infrastructure::tokenizer::{self, TokenCount, TokenizerInfo};
 /// - `usable_tokens`: Tokens available after accounting for special tokens
 /// - `exceeds_limit`: Whether the prompt is too long
 /// - `usage_percent`: Percentage of limit used (can exceed 100%)
+/// - `estimated`: Whether the count is a word-based estimate
 #[tauri::command]
-#[must_use]
-pub fn count_tokens_for_model(text: String, model_id: Option<String>) -> TokenCount {
-    tokenizer::count_tokens(&text, model_id.as_deref())
+#[tracing::instrument(skip_all, fields(model_id = ?model_id), err)]
+pub async fn count_tokens_for_model(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    text: String,
+    model_id: Option<String>,
+) -> Result<TokenCount, AppError> {
+    let model = model_id.as_deref().unwrap_or(DEFAULT_IMAGE_MODEL_ID);
+
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+    count_with_cache(&app, db.connection(), &text, model)
+}
+
+/// Counts tokens in several labeled texts in one call.
+///
+/// Lets the UI count the positive and negative prompts (or per-granularity
+/// strings) together instead of one IPC round trip per text. Each text is
+/// counted exactly like [`count_tokens_for_model`], including caching and
+/// estimates while the tokenizer loads.
+///
+/// # Arguments
+///
+/// * `app` - Tauri app handle used to emit the load event
+/// * `state` - Application state containing the database connection
+/// * `texts` - Texts to count, each with a caller-chosen label
+/// * `model_id` - Optional model identifier; defaults to SDXL-compatible CLIP tokenizer
+///
+/// # Returns
+///
+/// One `LabeledTokenCount` per input text, in the same order.
+#[tauri::command]
+#[tracing::instrument(skip_all, fields(model_id = ?model_id), err)]
+pub async fn count_tokens_batch(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    texts: Vec<LabeledText>,
+    model_id: Option<String>,
+) -> Result<Vec<LabeledTokenCount>, AppError> {
+    let model = model_id.as_deref().unwrap_or(DEFAULT_IMAGE_MODEL_ID);
+
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+    texts
+        .into_iter()
+        .map(|labeled| {
+            let count = count_with_cache(&app, db.connection(), &labeled.text, model)?;
+            Ok(LabeledTokenCount {
+                label: labeled.label,
+                count,
+            })
+        })
+        .collect()
+}
+
+/// Counts tokens using the persistent cache.
+///
+/// Returns a cached count if present. Otherwise counts with the tokenizer and
+/// caches the result, or returns an estimate and starts a background load if
+/// the tokenizer is not loaded yet.
+fn count_with_cache(
+    app: &AppHandle,
+    conn: &Connection,
+    text: &str,
+    model: &str,
+) -> Result<TokenCount, AppError> {
+    let tokenizer_id = tokenizer::get_config_for_model(model).tokenizer_id;
+    let hash = tokenizer::text_hash(text);
+
+    if let Some(count) = TokenCountCacheRepository::find(conn, &hash, &tokenizer_id)? {
+        return Ok(TokenCount::for_model(count, Some(model)));
+    }
+
+    if !tokenizer::is_tokenizer_loaded(&tokenizer_id) {
+        tokenizer::load_in_background(app, &tokenizer_id);
+        return Ok(tokenizer::estimate_tokens(text, Some(model)));
+    }
+
+    let count = tokenizer::count_tokens(text, Some(model));
+    if !count.estimated {
+        TokenCountCacheRepository::store(conn, &hash, &tokenizer_id, count.count)?;
+    }
+
+    Ok(count)
+}
+
+/// Splits text into the model's actual tokens with byte offsets.
+///
+/// Lets the prompt editor render colored token boundaries. Runs on a blocking
+/// worker thread, since the tokenizer may need to be loaded first.
+///
+/// # Arguments
+///
+/// * `text` - The prompt text to tokenize
+/// * `model_id` - Optional model identifier; defaults to SDXL-compatible CLIP tokenizer
+///
+/// # Returns
+///
+/// `TokenizedText` with one `TokenPiece` per token (ID, vocabulary entry,
+/// covered text, and byte offsets) plus the overall `TokenCount`.
+#[tauri::command]
+#[tracing::instrument(skip_all, fields(model_id = ?model_id), err)]
+pub async fn tokenize_text(
+    text: String,
+    model_id: Option<String>,
+) -> Result<TokenizedText, AppError> {
+    tauri::async_runtime::spawn_blocking(move || tokenizer::tokenize(&text, model_id.as_deref()))
+        .await
+        .map_err(|e| AppError::Internal(format!("Tokenization task failed: {e}")))?
+}
+
+/// Starts loading tokenizers in the background so the first count is fast.
+///
+/// Called by the frontend at startup unless warmup is disabled in preferences.
+/// Each distinct tokenizer is loaded once; a `tokenizer-loaded` event is
+/// emitted as each finishes. Also trims the token count cache to its most
+/// recent entries.
+///
+/// # Arguments
+///
+/// * `app` - Tauri app handle used to emit load events
+/// * `state` - Application state containing the database connection
+/// * `model_ids` - Models whose tokenizers to load (default: the default image model)
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn warmup_tokenizers(
+    app: AppHandle,
+    state: State<AppState>,
+    model_ids: Option<Vec<String>>,
+) -> Result<(), AppError> {
+    let model_ids = model_ids
+        .filter(|ids| !ids.is_empty())
+        .unwrap_or_else(|| vec![DEFAULT_IMAGE_MODEL_ID.to_string()]);
+
+    let tokenizer_ids: HashSet<String> = model_ids
+        .iter()
+        .map(|model| tokenizer::get_config_for_model(model).tokenizer_id)
+        .collect();
+    for tokenizer_id in &tokenizer_ids {
+        tokenizer::load_in_background(&app, tokenizer_id);
+    }
+
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+    TokenCountCacheRepository::prune(db.connection(), MAX_CACHED_COUNTS)?;
+
+    Ok(())
 }
 
 /// Returns configuration information for all known image generation models.
@@ -63,3 +265,202 @@ pub fn count_tokens_for_model(text: String, model_id: Option<String>) -> TokenCo
 pub fn get_known_image_models() -> Vec<TokenizerInfo> {
     tokenizer::get_known_models()
 }
+
+/// Recommends Style-level quality tokens for an image generation model.
+///
+/// The recommendation is deterministic and comes from the model's prompt
+/// context: tag-trained models get the quality tags they respond to (e.g.,
+/// "masterpiece, best quality" for anime SD 1.5 checkpoints), while
+/// natural-language models such as FLUX or PixArt get none, since such tags
+/// hurt their output.
+///
+/// # Arguments
+///
+/// * `model_id` - Optional model identifier. Defaults to the default image model.
+///
+/// # Returns
+///
+/// Positive Style-level tokens with weight 1.0, in prompt order; may be empty.
+#[tauri::command]
+#[must_use]
+pub fn suggest_quality_tokens(model_id: Option<String>) -> Vec<GeneratedTokenSelection> {
+    tokenizer::get_prompt_context_for_model(model_id.as_deref())
+        .quality_tokens
+        .into_iter()
+        .map(|content| GeneratedTokenSelection {
+            granularity_id: Granularity::Style.as_str().to_string(),
+            polarity: TokenPolarity::Positive,
+            content,
+            weight: 1.0,
+        })
+        .collect()
+}
+
+/// Lists the native output resolutions for an image generation model.
+///
+/// # Arguments
+///
+/// * `model_id` - Optional model identifier. Defaults to the default image model.
+///
+/// # Returns
+///
+/// `ResolutionPresets` for the model's family: fixed buckets for SD 1.5,
+/// SD 2.x, and SDXL; recommended sizes and the accepted range for flexible
+/// families such as FLUX.
+#[tauri::command]
+#[must_use]
+pub fn list_resolution_presets(model_id: Option<String>) -> ResolutionPresets {
+    let family = tokenizer::get_prompt_context_for_model(model_id.as_deref()).family;
+    ResolutionPresets::for_family(&family)
+}
+
+/// Checks whether an output resolution is native for an image generation model.
+///
+/// Intended for the generation parameters form, to warn before an off-bucket
+/// size is saved; saving is never blocked.
+///
+/// # Arguments
+///
+/// * `model_id` - Optional model identifier. Defaults to the default image model.
+/// * `width` - Output width in pixels
+/// * `height` - Output height in pixels
+///
+/// # Returns
+///
+/// A `ResolutionCheck` with a warning in the current display locale and the
+/// preset with the closest aspect ratio if the size is off-bucket.
+#[tauri::command]
+#[must_use]
+pub fn check_resolution(model_id: Option<String>, width: u32, height: u32) -> ResolutionCheck {
+    list_resolution_presets(model_id).check(width, height, locale::current())
+}
+
+/// Looks up a checkpoint or `LoRA` on Civitai by the hash of its file.
+///
+/// The result's `family` tells which tokenizer and prompt conventions apply,
+/// and its `suggested_tokens` are the trigger words, ready for
+/// `apply_generated_tokens`. Runs on a blocking worker thread.
+///
+/// # Arguments
+///
+/// * `hash` - SHA-256, BLAKE3, `AutoV1`, or `AutoV2` hash of the model file
+///
+/// # Returns
+///
+/// The matching model version, or `None` if Civitai doesn't know the hash.
+///
+/// # Errors
+///
+/// Returns `AppError::Validation` for a malformed hash, `AppError::Offline` in
+/// offline mode, and `AppError::Internal` if Civitai cannot be reached.
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn lookup_civitai_model_by_hash(hash: String) -> Result<Option<CivitaiModel>, AppError> {
+    tauri::async_runtime::spawn_blocking(move || civitai::lookup_by_hash(&hash))
+        .await
+        .map_err(|e| AppError::Internal(format!("Civitai lookup task failed: {e}")))?
+}
+
+/// Searches Civitai checkpoints and `LoRA` models by name.
+///
+/// Runs on a blocking worker thread.
+///
+/// # Arguments
+///
+/// * `query` - Name or part of a name
+/// * `limit` - Maximum number of results (default: 10, at most 50)
+///
+/// # Returns
+///
+/// Matching models, each with its latest version; may be empty.
+///
+/// # Errors
+///
+/// Returns `AppError::Validation` for an empty query, `AppError::Offline` in
+/// offline mode, and `AppError::Internal` if Civitai cannot be reached.
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn search_civitai_models(
+    query: String,
+    limit: Option<u32>,
+) -> Result<Vec<CivitaiModel>, AppError> {
+    let limit = limit
+        .unwrap_or(DEFAULT_CIVITAI_SEARCH_LIMIT)
+        .clamp(1, MAX_CIVITAI_SEARCH_LIMIT);
+    tauri::async_runtime::spawn_blocking(move || civitai::search_by_name(&query, limit))
+        .await
+        .map_err(|e| AppError::Internal(format!("Civitai lookup task failed: {e}")))?
+}
+
+/// Lists the embeddings and `LoRA` models in the configured resource folders.
+///
+/// Each resource comes with its prompt trigger and a suggested token, so
+/// triggers can be picked instead of typed, and with its file hashes for
+/// `lookup_civitai_model_by_hash`. Every file is hashed, so this can take a
+/// while for large `LoRA` folders; it runs on a blocking worker thread.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection
+///
+/// # Returns
+///
+/// Embeddings, then `LoRA` models, each sorted by name; empty if no folders
+/// are set.
+///
+/// # Errors
+///
+/// Returns `AppError::Io` if a folder or file cannot be read.
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn scan_local_resources(
+    state: State<'_, AppState>,
+) -> Result<Vec<LocalResource>, AppError> {
+    let folders = {
+        let db = state
+            .db
+            .lock()
+            .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+        SettingsRepository::load(db.connection())?.resource_folders
+    };
+
+    tauri::async_runtime::spawn_blocking(move || resource_scan::scan_resources(&folders))
+        .await
+        .map_err(|e| AppError::Internal(format!("Resource scan task failed: {e}")))?
+}
+
+/// Searches the `HuggingFace` Hub for model repositories, most downloaded first.
+///
+/// Lets the user pick the exact repo ID of a custom model instead of typing
+/// it; each result carries the tokenizer and family the app would use, and
+/// whether the model was recognized at all. Runs on a blocking worker thread.
+///
+/// # Arguments
+///
+/// * `query` - Repo ID or part of one
+/// * `pipeline_tag` - Task to filter on (default: "text-to-image")
+///
+/// # Returns
+///
+/// Up to 20 matching repositories; may be empty.
+///
+/// # Errors
+///
+/// Returns `AppError::Validation` for an empty query or a malformed pipeline
+/// tag, `AppError::Offline` in offline mode, and `AppError::Internal` if the
+/// Hub cannot be reached.
+#[tauri::command]
+#[tracing::instrument(skip_all, fields(pipeline_tag = ?pipeline_tag), err)]
+pub async fn search_hf_models(
+    query: String,
+    pipeline_tag: Option<String>,
+) -> Result<Vec<HubModel>, AppError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        huggingface::search_models(
+            &query,
+            pipeline_tag.as_deref().unwrap_or(DEFAULT_PIPELINE_TAG),
+        )
+    })
+    .await
+    .map_err(|e| AppError::Internal(format!("HuggingFace search task failed: {e}")))?
+}
@@ -0,0 +1,153 @@
+//! Update Commands
+//!
+//! This module provides Tauri IPC commands for application updates through the
+//! Tauri updater: [`check_for_update`] asks the release server for a newer
+//! version, and [`install_update`] downloads it, snapshots the database, installs
+//! it, and restarts the app.
+//!
+//! The new version checks the database at startup and restores the snapshot if
+//! its migrations failed (see `infrastructure::update`); the outcome is returned
+//! by `get_update_report` in the diagnostics commands.
+//!
+//! # Configuration
+//!
+//! The updater is only built with the `updater` Cargo feature, by release builds
+//! that also merge the updater config holding the signing public key, e.g.
+//! `tauri build --features updater --config updater.conf.json` with
+//! `plugins.updater.pubkey`, `plugins.updater.endpoints`, and
+//! `bundle.createUpdaterArtifacts` set. Other builds report that updates are
+//! unavailable.
+
+#[cfg(feature = "updater")]
+use tauri::{AppHandle, State};
+#[cfg(feature = "updater")]
+use tauri_plugin_updater::{Update, UpdaterExt};
+
+use crate::domain::update::AvailableUpdate;
+use crate::error::AppError;
+#[cfg(feature = "updater")]
+use crate::infrastructure::{offline, update};
+#[cfg(feature = "updater")]
+use crate::AppState;
+
+/// Returns the update offered by the release server, if any.
+///
+/// # Arguments
+///
+/// * `app` - Tauri application handle for updater access
+///
+/// # Errors
+///
+/// Returns `AppError::Offline` in offline mode, `AppError::Validation` if this
+/// build has no updater key, or `AppError::Internal` if the server cannot be
+/// reached.
+#[cfg(feature = "updater")]
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn check_for_update(app: AppHandle) -> Result<Option<AvailableUpdate>, AppError> {
+    Ok(find_update(&app).await?.map(|update| AvailableUpdate {
+        version: update.version,
+        current_version: update.current_version,
+        date: update.date.map(|date| date.to_string()),
+        notes: update.body,
+    }))
+}
+
+/// Installs the update offered by the release server and restarts the app.
+///
+/// The database is snapshotted after the download, right before installing, so
+/// the new version can restore it if its migrations fail.
+///
+/// # Arguments
+///
+/// * `app` - Tauri application handle for updater access
+/// * `state` - Application state containing the database connection and path
+///
+/// # Errors
+///
+/// Returns `AppError::NotFound` if no update is available, or an error if the
+/// download, snapshot, or installation fails; the running version is kept.
+#[cfg(feature = "updater")]
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn install_update(app: AppHandle, state: State<'_, AppState>) -> Result<(), AppError> {
+    let update = find_update(&app)
+        .await?
+        .ok_or_else(|| AppError::NotFound("No update is available".to_string()))?;
+
+    let bytes = update
+        .download(|_, _| {}, || {})
+        .await
+        .map_err(|e| AppError::Internal(format!("Failed to download update: {e}")))?;
+
+    {
+        let db = state
+            .db
+            .lock()
+            .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+        update::prepare_update(db.connection(), &state.db_path, &update.version)?;
+    }
+
+    update
+        .install(bytes)
+        .map_err(|e| AppError::Internal(format!("Failed to install update: {e}")))?;
+
+    tracing::info!(version = %update.version, "Update installed, restarting");
+    app.restart()
+}
+
+/// Reports that this build has no updater (built without the `updater` feature).
+///
+/// # Errors
+///
+/// Always returns `AppError::Validation`.
+#[cfg(not(feature = "updater"))]
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn check_for_update() -> Result<Option<AvailableUpdate>, AppError> {
+    Err(updates_unavailable())
+}
+
+/// Reports that this build has no updater (built without the `updater` feature).
+///
+/// # Errors
+///
+/// Always returns `AppError::Validation`.
+#[cfg(not(feature = "updater"))]
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn install_update() -> Result<(), AppError> {
+    Err(updates_unavailable())
+}
+
+/// Asks the release server for a newer version.
+#[cfg(feature = "updater")]
+async fn find_update(app: &AppHandle) -> Result<Option<Update>, AppError> {
+    offline::ensure_online("Checking for updates")?;
+    if !updates_configured(app) {
+        return Err(updates_unavailable());
+    }
+
+    app.updater()
+        .map_err(|e| AppError::Internal(format!("Failed to initialize updater: {e}")))?
+        .check()
+        .await
+        .map_err(|e| AppError::Internal(format!("Failed to check for updates: {e}")))
+}
+
+/// Returns whether the updater has a public key to verify updates with.
+#[cfg(feature = "updater")]
+fn updates_configured(app: &AppHandle) -> bool {
+    app.config()
+        .plugins
+        .0
+        .get("updater")
+        .and_then(|updater| updater.get("pubkey"))
+        .and_then(serde_json::Value::as_str)
+        .is_some_and(|pubkey| !pubkey.is_empty())
+}
+
+/// The error returned when this build cannot update itself.
+fn updates_unavailable() -> AppError {
+    AppError::Validation("Updates are not available in this build".to_string())
+}
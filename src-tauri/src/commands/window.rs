@@ -0,0 +1,75 @@
+//! Window Commands
+//!
+//! This module provides Tauri IPC commands for opening secondary windows.
+//! Secondary windows load the same frontend as the main window and stay in
+//! sync with it through the change events emitted by mutating commands.
+//!
+//! # Compose Window
+//!
+//! A compose window shows the prompt composer for a single persona, so the
+//! prompt can stay visible next to an image generation tool while tokens are
+//! edited in the main window. Each persona has at most one compose window,
+//! labeled `compose-<persona id>`.
+
+use tauri::{AppHandle, Manager, State, WebviewUrl, WebviewWindowBuilder};
+
+use crate::error::AppError;
+use crate::infrastructure::database::repositories::PersonaRepository;
+use crate::infrastructure::safe_mode;
+use crate::AppState;
+
+/// Prefix of compose window labels; the persona ID is appended.
+pub const COMPOSE_WINDOW_PREFIX: &str = "compose-";
+
+/// Opens the compose window for a persona, or focuses it if already open.
+///
+/// The window loads the compose page with the persona preselected and the
+/// navigation hidden.
+///
+/// This command is async because creating a window from a synchronous
+/// command deadlocks on Windows.
+///
+/// # Arguments
+///
+/// * `app` - Tauri application handle used to create the window
+/// * `state` - Application state containing the database connection
+/// * `persona_id` - UUID of the persona to compose
+///
+/// # Errors
+///
+/// Returns `AppError::NotFound` if the persona does not exist or is hidden by
+/// safe mode, or `AppError::Internal` if the window cannot be created.
+#[tauri::command]
+#[tracing::instrument(skip_all, fields(persona_id = %persona_id), err)]
+pub async fn open_compose_window(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    persona_id: String,
+) -> Result<(), AppError> {
+    let persona = {
+        let db = state
+            .db
+            .lock()
+            .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+        PersonaRepository::find_by_id(db.connection(), &persona_id)?
+    };
+    safe_mode::ensure_visible(&persona)?;
+
+    let label = format!("{COMPOSE_WINDOW_PREFIX}{}", persona.id);
+    if let Some(window) = app.get_webview_window(&label) {
+        let _ = window.unminimize();
+        return window
+            .set_focus()
+            .map_err(|e| AppError::Internal(format!("Failed to focus compose window: {e}")));
+    }
+
+    let url = format!("compose?persona={}&popout=1", persona.id);
+    WebviewWindowBuilder::new(&app, label, WebviewUrl::App(url.into()))
+        .title(format!("{} - Compose", persona.name))
+        .inner_size(640.0, 720.0)
+        .min_inner_size(420.0, 480.0)
+        .build()
+        .map_err(|e| AppError::Internal(format!("Failed to open compose window: {e}")))?;
+
+    Ok(())
+}
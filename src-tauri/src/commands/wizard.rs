@@ -0,0 +1,278 @@
+//! Persona Wizard Commands
+//!
+//! This module provides Tauri IPC commands for creating a persona step by
+//! step (see `domain::wizard`).
+//!
+//! # Flow
+//!
+//! [`start_persona_wizard`] stores a draft, [`submit_wizard_step`] saves each
+//! step (style, physique, face, hair) with optional AI fill-in, and
+//! [`finish_wizard`] creates the persona. Drafts are stored after every step,
+//! so [`list_persona_wizards`] can resume them after a restart;
+//! [`discard_persona_wizard`] drops one.
+
+use tauri::{AppHandle, State, Window};
+
+use super::ai::{acquire_rate_limit, load_output_language};
+use super::emit_persona_changed;
+use crate::domain::ai::AiProviderConfig;
+use crate::domain::banned_term;
+use crate::domain::events::ChangeKind;
+use crate::domain::naming;
+use crate::domain::persona::{CreatePersonaRequest, GenerationParams, Persona};
+use crate::domain::telemetry::Feature;
+use crate::domain::token::{GeneratedTokenSelection, TokenPolarity};
+use crate::domain::wizard::{PersonaWizard, StartPersonaWizardRequest, WizardStepRequest};
+use crate::error::AppError;
+use crate::infrastructure::ai;
+use crate::infrastructure::ai::rate_limit::AiRateLimiter;
+use crate::infrastructure::ai::request_log::AiRequestLog;
+use crate::infrastructure::database::repositories::{
+    BannedTermRepository, PersonaRepository, PersonaWizardRepository,
+};
+use crate::infrastructure::telemetry;
+use crate::services::{PersonaService, TokenService};
+use crate::AppState;
+
+/// Starts the persona creation wizard.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection
+/// * `request` - Name, description, tags, rating, and image model of the persona
+///
+/// # Returns
+///
+/// The new draft, at the style step.
+///
+/// # Errors
+///
+/// Returns `AppError::Validation` if the name breaks the naming rules or is taken.
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn start_persona_wizard(
+    state: State<AppState>,
+    request: StartPersonaWizardRequest,
+) -> Result<PersonaWizard, AppError> {
+    let name = naming::validate_name(&request.name)?;
+
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+    if PersonaRepository::name_exists(db.connection(), &name, None)? {
+        return Err(AppError::Validation(format!(
+            "A persona with name '{name}' already exists"
+        )));
+    }
+
+    let wizard = PersonaWizard::new(name, request);
+    PersonaWizardRepository::create(db.connection(), &wizard)?;
+    Ok(wizard)
+}
+
+/// Lists the unfinished wizard drafts, most recently changed first.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection
+///
+/// # Returns
+///
+/// Vector of drafts to resume, which may be empty.
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn list_persona_wizards(state: State<AppState>) -> Result<Vec<PersonaWizard>, AppError> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+    PersonaWizardRepository::find_all(db.connection())
+}
+
+/// Submits a wizard step, optionally letting the AI add tokens to it.
+///
+/// The step's tokens replace those submitted with it before. With
+/// `ai_fill_count`, the AI suggests that many tokens for each of the step's
+/// granularity levels, added after the user's tokens; the result is saved
+/// like any other submission and can be changed by submitting the step again.
+///
+/// # Arguments
+///
+/// * `app` - Application handle, used to emit queue status events
+/// * `limiter` - Per-provider AI rate limiter
+/// * `log` - Opt-in AI request log
+/// * `state` - Application state containing the database connection
+/// * `id` - UUID of the wizard draft
+/// * `request` - The step, its tokens, and the optional AI fill count
+/// * `config` - AI provider configuration; required for AI fill-in
+///
+/// # Returns
+///
+/// The saved draft, with the next step to submit as its current step.
+///
+/// # Errors
+///
+/// Returns `AppError::NotFound` if the draft doesn't exist,
+/// `AppError::Validation` if an earlier step is missing, a token is invalid
+/// or contains a banned term, or the step has no positive token, and
+/// `AppError::Internal` if the AI request fails.
+#[tauri::command]
+#[tracing::instrument(skip_all, fields(id = %id, step = ?request.step), err)]
+pub async fn submit_wizard_step(
+    app: AppHandle,
+    limiter: State<'_, AiRateLimiter>,
+    log: State<'_, AiRequestLog>,
+    state: State<'_, AppState>,
+    id: String,
+    request: WizardStepRequest,
+    config: Option<AiProviderConfig>,
+) -> Result<PersonaWizard, AppError> {
+    request.validate()?;
+    let fill = match (request.ai_fill_count, config) {
+        (Some(count), Some(config)) => Some((count, config)),
+        (Some(_), None) => {
+            return Err(AppError::Validation(
+                "AI fill-in requires an AI provider".to_string(),
+            ))
+        }
+        (None, _) => None,
+    };
+
+    let (mut wizard, banned_terms, output_language) = {
+        let db = state
+            .db
+            .lock()
+            .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+        let conn = db.connection();
+        let wizard = PersonaWizardRepository::find_by_id(conn, &id)?;
+        wizard.check_step(request.step, &request.tokens)?;
+        let banned_terms = BannedTermRepository::find_all(conn)?;
+        for token in &request.tokens {
+            banned_term::ensure_allowed(&token.content, &banned_terms)?;
+        }
+        (wizard, banned_terms, load_output_language(conn, &[])?)
+    };
+
+    let mut tokens = request.tokens;
+    if let Some((count, config)) = fill {
+        for granularity in request.step.granularities() {
+            let fill_request = wizard.fill_request(*granularity, &tokens, count);
+            let response = {
+                let _permit = acquire_rate_limit(&app, &limiter, config.provider).await?;
+                ai::generate_tokens(
+                    &config,
+                    &fill_request,
+                    &banned_terms,
+                    &output_language,
+                    &log,
+                )
+                .await?
+            };
+            tokens.extend(response.positive_tokens.into_iter().map(|token| {
+                GeneratedTokenSelection {
+                    granularity_id: granularity.as_str().to_string(),
+                    polarity: TokenPolarity::Positive,
+                    content: token.content,
+                    weight: token.suggested_weight,
+                }
+            }));
+        }
+    }
+    wizard.submit(request.step, tokens)?;
+
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+    PersonaWizardRepository::save_steps(db.connection(), &wizard)?;
+    Ok(wizard)
+}
+
+/// Creates the persona of a completed wizard and deletes the draft.
+///
+/// The persona, its generation parameters, and its tokens are saved in one
+/// transaction; if any part fails, the draft is kept.
+///
+/// # Arguments
+///
+/// * `window` - Window issuing the command, named in the change event
+/// * `state` - Application state containing the database connection
+/// * `id` - UUID of the wizard draft
+///
+/// # Returns
+///
+/// The new persona.
+///
+/// # Errors
+///
+/// Returns `AppError::NotFound` if the draft doesn't exist, or
+/// `AppError::Validation` if a step is missing, the name was taken
+/// meanwhile, or a token contains a banned term.
+#[tauri::command]
+#[tracing::instrument(skip_all, fields(id = %id), err)]
+pub fn finish_wizard(
+    window: Window,
+    state: State<AppState>,
+    id: String,
+) -> Result<Persona, AppError> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+    let persona = db.unit_of_work(|conn| {
+        let wizard = PersonaWizardRepository::find_by_id(conn, &id)?;
+        wizard.ensure_complete()?;
+
+        let persona = PersonaService::create(
+            conn,
+            CreatePersonaRequest {
+                name: wizard.name.clone(),
+                description: wizard.description.clone(),
+                tags: wizard.tags.clone(),
+                content_rating: wizard.content_rating,
+            },
+        )?;
+        if let Some(model_id) = wizard.image_model_id.clone() {
+            PersonaRepository::update_generation_params(
+                conn,
+                &GenerationParams {
+                    model_id,
+                    ..GenerationParams::default_for_persona(&persona.id)
+                },
+            )?;
+        }
+        TokenService::append_selections(conn, &persona.id, &wizard.tokens)?;
+        PersonaWizardRepository::delete(conn, &wizard.id)?;
+        // Re-read for the content hash and timestamps the tokens changed
+        PersonaRepository::find_by_id(conn, &persona.id)
+    })?;
+    telemetry::record(db.connection(), Feature::PersonaWizard);
+
+    emit_persona_changed(&window, &persona.id, ChangeKind::Created);
+    Ok(persona)
+}
+
+/// Deletes a wizard draft without creating its persona.
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the database connection
+/// * `id` - UUID of the wizard draft
+///
+/// # Errors
+///
+/// Returns `AppError::NotFound` if the draft doesn't exist.
+#[tauri::command]
+#[tracing::instrument(skip_all, fields(id = %id), err)]
+pub fn discard_persona_wizard(state: State<AppState>, id: String) -> Result<(), AppError> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire database lock".to_string()))?;
+
+    PersonaWizardRepository::delete(db.connection(), &id)
+}
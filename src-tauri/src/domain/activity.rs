@@ -0,0 +1,43 @@
+//! Persona Activity
+//!
+//! Commands record when a persona was last opened, modified, or composed, so
+//! the frontend can list recent personas without inferring activity from
+//! `updated_at` (which only covers metadata edits, not token changes).
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::persona::Persona;
+
+/// Kind of activity recorded for a persona.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ActivityKind {
+    /// The persona was opened in the editor
+    Opened,
+    /// The persona, its tokens, or its generation parameters changed
+    Modified,
+    /// A prompt was composed from the persona
+    Composed,
+}
+
+impl ActivityKind {
+    /// Returns the string representation for database storage.
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Opened => "opened",
+            Self::Modified => "modified",
+            Self::Composed => "composed",
+        }
+    }
+}
+
+/// A persona with the time of its latest activity of the requested kind.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecentPersona {
+    /// The persona
+    pub persona: Persona,
+    /// When the activity last happened
+    pub at: DateTime<Utc>,
+}
@@ -21,8 +21,14 @@
 //! The frontend fetches this information via `get_ai_provider_metadata()`,
 //! ensuring consistency and making it easy to add new providers.
 
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use super::banned_term::FilteredToken;
+use super::persona::Persona;
+use super::token::{Token, TokenPolarity};
+use crate::error::AppError;
+
 // ============================================================================
 // Provider Configuration
 // ============================================================================
@@ -33,7 +39,7 @@ use serde::{Deserialize, Serialize};
 ///
 /// Each provider has specific characteristics regarding API access,
 /// default models, and authentication requirements.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum AiProvider {
     /// `OpenAI` (GPT models)
@@ -84,6 +90,34 @@ impl AiProvider {
         }
     }
 
+    /// Returns the environment variable read in credential passthrough mode.
+    ///
+    /// Names follow each provider's own SDK convention; Ollama needs no key.
+    #[must_use]
+    pub const fn api_key_env_var(&self) -> Option<&'static str> {
+        match self {
+            Self::OpenAI => Some("OPENAI_API_KEY"),
+            Self::Anthropic => Some("ANTHROPIC_API_KEY"),
+            Self::Google => Some("GEMINI_API_KEY"),
+            Self::XAi => Some("XAI_API_KEY"),
+            Self::Ollama => None,
+        }
+    }
+
+    /// Returns the public API host, used by the connectivity diagnostic.
+    ///
+    /// Ollama runs locally and has no public host.
+    #[must_use]
+    pub const fn api_host(&self) -> Option<&'static str> {
+        match self {
+            Self::OpenAI => Some("api.openai.com"),
+            Self::Anthropic => Some("api.anthropic.com"),
+            Self::Google => Some("generativelanguage.googleapis.com"),
+            Self::XAi => Some("api.x.ai"),
+            Self::Ollama => None,
+        }
+    }
+
     /// Returns the default base URL if the provider supports custom endpoints.
     #[must_use]
     pub const fn default_base_url(&self) -> Option<&'static str> {
@@ -154,17 +188,25 @@ pub struct AiProviderMetadata {
 /// Configuration for connecting to an AI provider.
 ///
 /// This struct is populated by the frontend and passed to the backend
-/// for token generation requests.
+/// for token generation requests. It never carries the API key over IPC;
+/// the backend resolves credentials itself when the request is made.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AiProviderConfig {
     /// Target provider
     pub provider: AiProvider,
     /// Model to use for generation
     pub model: String,
-    /// API key (retrieved from keyring, optional for Ollama)
+    /// Explicit API key override, set only inside the backend (e.g., to verify a
+    /// key before storing it). Skipped by serde so keys never cross IPC;
+    /// when `None`, the key is read from the keyring at call time.
+    #[serde(skip)]
     pub api_key: Option<String>,
     /// Custom base URL (optional)
     pub base_url: Option<String>,
+    /// Read the API key from the provider's environment variable at call time
+    /// instead of the keyring (credential passthrough mode)
+    #[serde(default)]
+    pub use_env_credentials: bool,
 }
 
 impl AiProviderConfig {
@@ -174,6 +216,7 @@ impl AiProviderConfig {
             model: provider.default_model().to_string(),
             api_key: None,
             base_url: provider.default_base_url().map(String::from),
+            use_env_credentials: false,
             provider,
         }
     }
@@ -185,6 +228,112 @@ impl AiProviderConfig {
 //
 // Types used by both Persona Generation and Token Generation.
 
+/// Event name for AI request queue updates.
+pub const AI_QUEUE_STATUS_EVENT: &str = "ai-queue-status";
+
+/// Queue position of a rate-limited AI request, emitted while it waits.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AiQueueStatus {
+    /// Provider whose queue the request is waiting in
+    pub provider: AiProvider,
+    /// Requests still ahead in the queue (0 once the request starts)
+    pub position: usize,
+}
+
+/// A recorded AI request/response exchange from the opt-in debug log.
+///
+/// Secrets are redacted before an entry is written to disk.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AiLogEntry {
+    /// When the request was sent
+    pub timestamp: DateTime<Utc>,
+    /// Provider the request was sent to
+    pub provider: AiProvider,
+    /// Model used for the request
+    pub model: String,
+    /// Operation that issued the request (e.g., `persona_generation`)
+    pub operation: String,
+    /// System prompt sent to the model
+    pub system_prompt: String,
+    /// User prompt sent to the model
+    pub user_prompt: String,
+    /// Raw response text, if any was received
+    pub response: Option<String>,
+    /// Request or parse error, if the exchange failed
+    pub error: Option<String>,
+}
+
+impl AiLogEntry {
+    /// Creates an entry for a request that is about to be sent.
+    #[must_use]
+    pub fn new(
+        config: &AiProviderConfig,
+        operation: &str,
+        system_prompt: &str,
+        user_prompt: &str,
+    ) -> Self {
+        Self {
+            timestamp: Utc::now(),
+            provider: config.provider,
+            model: config.model.clone(),
+            operation: operation.to_string(),
+            system_prompt: system_prompt.to_string(),
+            user_prompt: user_prompt.to_string(),
+            response: None,
+            error: None,
+        }
+    }
+}
+
+/// Language the AI writes descriptions and rationales in when none is configured.
+pub const DEFAULT_AI_OUTPUT_LANGUAGE: &str = "English";
+
+/// Maximum length of an AI output language name.
+pub const MAX_AI_OUTPUT_LANGUAGE_LENGTH: usize = 40;
+
+/// Validates an AI output language name and trims it.
+///
+/// The language is written into the system prompt as-is, so only names made of
+/// letters, spaces, hyphens, and parentheses are accepted (e.g., "Brazilian
+/// Portuguese", "Chinese (Simplified)"). Blank means "not set".
+///
+/// # Errors
+///
+/// Returns `AppError::Validation` if the name is too long or contains other
+/// characters.
+pub fn normalize_output_language(language: Option<&str>) -> Result<Option<String>, AppError> {
+    let Some(language) = language.map(str::trim).filter(|l| !l.is_empty()) else {
+        return Ok(None);
+    };
+
+    let valid = language.chars().count() <= MAX_AI_OUTPUT_LANGUAGE_LENGTH
+        && language
+            .chars()
+            .all(|c| c.is_alphabetic() || matches!(c, ' ' | '-' | '(' | ')'));
+    if !valid {
+        return Err(AppError::Validation(format!(
+            "Invalid AI output language '{language}': use a language name like \"German\""
+        )));
+    }
+    Ok(Some(language.to_string()))
+}
+
+/// Returns the language AI-written prose should use.
+///
+/// The first language set wins: the one given for this request, then the
+/// persona's, then the global setting, then [`DEFAULT_AI_OUTPUT_LANGUAGE`].
+/// Tokens are always generated in English regardless.
+#[must_use]
+pub fn resolve_output_language(candidates: &[Option<&str>]) -> String {
+    candidates
+        .iter()
+        .flatten()
+        .map(|language| language.trim())
+        .find(|language| !language.is_empty())
+        .unwrap_or(DEFAULT_AI_OUTPUT_LANGUAGE)
+        .to_string()
+}
+
 /// A single token suggestion from AI generation.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GeneratedToken {
@@ -197,6 +346,15 @@ pub struct GeneratedToken {
     /// Granularity category (only set for persona generation, not ad-hoc token generation)
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub granularity_id: Option<String>,
+    /// Encoder tokens this token costs in the target model's prompt (weight syntax included)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_cost: Option<usize>,
+    /// Encoder tokens used by the prompt once this and all preceding suggestions are added
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cumulative_tokens: Option<usize>,
+    /// Whether accepting this token (with those before it) would exceed the model's budget
+    #[serde(default)]
+    pub exceeds_budget: bool,
 }
 
 // ============================================================================
@@ -377,8 +535,53 @@ pub struct AiPersonaGenerationRequest {
     /// Whether to skip AI description generation entirely when no description provided (default: false)
     #[serde(default)]
     pub skip_ai_description: bool,
+    /// Language of the generated description and rationales; the global setting applies when unset
+    #[serde(default)]
+    pub output_language: Option<String>,
 }
 
+impl AiPersonaGenerationRequest {
+    /// Returns the description to save, given the AI's response.
+    ///
+    /// Empty if the user skipped description generation, the AI's elaboration
+    /// if improvement was requested, otherwise the user's original text.
+    #[must_use]
+    pub fn final_description(&self, response: &AiPersonaGenerationResponse) -> String {
+        let original = self
+            .character_description
+            .as_deref()
+            .map(str::trim)
+            .unwrap_or_default();
+
+        if self.skip_ai_description && original.is_empty() {
+            String::new()
+        } else if self.improve_description_via_ai {
+            response.description.clone()
+        } else {
+            original.to_string()
+        }
+    }
+
+    /// Returns the AI instructions to save, given the AI's response.
+    ///
+    /// Uses the AI-refined instructions when improvement was requested and
+    /// returned, otherwise the user's original instructions.
+    #[must_use]
+    pub fn final_instructions(&self, response: &AiPersonaGenerationResponse) -> Option<String> {
+        let original = self
+            .ai_instructions
+            .as_deref()
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(String::from);
+
+        if self.improve_instructions_via_ai {
+            response.ai_instructions.clone().or(original)
+        } else {
+            original
+        }
+    }
+}
 
 /// Response from AI persona generation.
 ///
@@ -396,10 +599,42 @@ pub struct AiPersonaGenerationResponse {
     /// Generated tokens in AI-recommended optimal order for prompt composition.
     /// Each token includes `granularity_id` for UI categorization.
     pub tokens: Vec<GeneratedToken>,
+    /// Planned and tokenizer-verified budget for each granularity
+    pub granularity_budgets: Vec<GranularityBudget>,
     /// Provider that handled the request
     pub provider: AiProvider,
     /// Model used for generation
     pub model: String,
+    /// Tokens left out because they contain a banned term
+    #[serde(default)]
+    pub filtered: Vec<FilteredToken>,
+}
+
+/// A persona created end-to-end from AI generation, as saved to the database.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AiCreatedPersona {
+    /// The saved persona, including its AI configuration
+    pub persona: Persona,
+    /// The saved tokens, in AI-recommended order
+    pub tokens: Vec<Token>,
+    /// Planned and tokenizer-verified budget for each granularity
+    pub granularity_budgets: Vec<GranularityBudget>,
+    /// Generated tokens left out because they contain a banned term
+    #[serde(default)]
+    pub filtered: Vec<FilteredToken>,
+}
+
+/// Token budget planned for a granularity and the verified usage after trimming.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GranularityBudget {
+    /// Granularity level ID (e.g., "hair", "face")
+    pub granularity_id: String,
+    /// Encoder tokens allocated by the planning pass
+    pub allocated_tokens: usize,
+    /// Encoder tokens used by the kept tokens, measured with the model's tokenizer
+    pub used_tokens: usize,
 }
 
 // ============================================================================
@@ -411,7 +646,9 @@ pub struct AiPersonaGenerationResponse {
 /// Request payload for AI token generation.
 ///
 /// Contains all context needed for the AI to generate relevant tokens,
-/// including persona information, existing tokens, and prompt state.
+/// including persona information, existing tokens, and prompt state. With a
+/// persona and granularity ID, the suggestions are also kept as pending
+/// suggestions for that persona (see `domain::suggestion`).
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TokenGenerationRequest {
     /// Persona name for context
@@ -451,6 +688,15 @@ pub struct TokenGenerationRequest {
     /// Maximum tokens allowed for the target model
     #[serde(default)]
     pub max_usable_tokens: Option<usize>,
+    /// Language of the rationales; the persona's or global setting applies when unset
+    #[serde(default)]
+    pub output_language: Option<String>,
+    /// Persona whose inbox the suggestions are staged in (with `granularity_id`)
+    #[serde(default)]
+    pub persona_id: Option<String>,
+    /// Granularity level ID the suggestions are staged under (with `persona_id`)
+    #[serde(default)]
+    pub granularity_id: Option<String>,
 }
 
 /// Response from AI token generation.
@@ -464,4 +710,204 @@ pub struct TokenGenerationResponse {
     pub provider: AiProvider,
     /// Model used for generation
     pub model: String,
+    /// Suggestions left out because they contain a banned term
+    #[serde(default)]
+    pub filtered: Vec<FilteredToken>,
+}
+
+// ============================================================================
+// Style Transfer Types
+// ============================================================================
+//
+// Types for adapting one persona's tokens to another persona's visual style.
+
+/// Request payload for AI style transfer between two personas.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StyleTransferRequest {
+    /// Persona whose Style tokens define the style to apply
+    pub source_persona_id: String,
+    /// Persona whose tokens are adapted
+    pub target_persona_id: String,
+    /// Whether to also give the AI the source persona's description as style context
+    #[serde(default)]
+    pub include_source_description: bool,
+}
+
+/// A proposed change to one of the target persona's tokens.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenStyleChange {
+    /// UUID of the token to change
+    pub token_id: String,
+    /// Granularity level ID of the token
+    pub granularity_id: String,
+    /// Polarity of the token
+    pub polarity: TokenPolarity,
+    /// Current token text
+    pub original_content: String,
+    /// Current token weight
+    pub original_weight: f64,
+    /// Proposed token text
+    pub proposed_content: String,
+    /// Proposed token weight
+    pub proposed_weight: f64,
+    /// AI's explanation for the change
+    pub rationale: Option<String>,
+}
+
+/// Proposal returned by style transfer, to be reviewed before applying.
+///
+/// Tokens the AI left unchanged are omitted; nothing is written to the database.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StyleTransferProposal {
+    /// Persona the style was taken from
+    pub source_persona_id: String,
+    /// Persona whose tokens would change
+    pub target_persona_id: String,
+    /// Proposed per-token changes, in the target's token order
+    pub changes: Vec<TokenStyleChange>,
+    /// Provider that handled the request
+    pub provider: AiProvider,
+    /// Model used for generation
+    pub model: String,
+}
+
+// ============================================================================
+// Image Consistency Types
+// ============================================================================
+//
+// Types for checking a generated image against the persona it was made from.
+
+/// What a vision model saw of one persona token in an image.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenPresence {
+    /// UUID of the token
+    pub token_id: String,
+    /// Token text
+    pub content: String,
+    /// Polarity of the token
+    pub polarity: TokenPolarity,
+    /// Whether the token's content is visible in the image
+    pub present: bool,
+    /// Whether the image agrees with the token: positive tokens present,
+    /// negative tokens absent
+    pub consistent: bool,
+    /// AI's remark on what it saw, if any
+    pub note: Option<String>,
+}
+
+impl TokenPresence {
+    /// Creates a token's result from what the model saw.
+    #[must_use]
+    pub fn new(token: &Token, present: bool, note: Option<String>) -> Self {
+        Self {
+            token_id: token.id.clone(),
+            content: token.content.clone(),
+            polarity: token.polarity,
+            present,
+            consistent: present == (token.polarity == TokenPolarity::Positive),
+            note,
+        }
+    }
+}
+
+/// Consistency of one granularity level's tokens with an image.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GranularityConsistency {
+    /// Granularity level ID
+    pub granularity_id: String,
+    /// Share of the level's tokens the image agrees with (0–1)
+    pub score: f64,
+    /// Per-token results, in the persona's token order
+    pub tokens: Vec<TokenPresence>,
+}
+
+/// Result of checking a generated image against a persona.
+///
+/// Tokens the model gave no verdict on are left out, and so are levels with
+/// no token left.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageConsistencyReport {
+    /// Persona the image was checked against
+    pub persona_id: String,
+    /// Path of the checked image
+    pub image_path: String,
+    /// Share of all checked tokens the image agrees with (0–1)
+    pub score: f64,
+    /// Results per granularity level, in the order levels first appear in the
+    /// persona's tokens
+    pub granularities: Vec<GranularityConsistency>,
+    /// AI's overall impression of the image, if given
+    pub summary: Option<String>,
+    /// Provider that handled the request
+    pub provider: AiProvider,
+    /// Model used for the check
+    pub model: String,
+}
+
+impl ImageConsistencyReport {
+    /// Groups token results by granularity level and scores them.
+    ///
+    /// # Arguments
+    ///
+    /// * `persona_id` - Persona the image was checked against
+    /// * `image_path` - Path of the checked image
+    /// * `results` - Each checked token's level ID and result, in token order
+    /// * `summary` - AI's overall impression, if any
+    /// * `config` - Provider configuration used for the check
+    #[must_use]
+    pub fn new(
+        persona_id: String,
+        image_path: String,
+        results: Vec<(String, TokenPresence)>,
+        summary: Option<String>,
+        config: &AiProviderConfig,
+    ) -> Self {
+        let score = consistency_score(results.iter().map(|(_, presence)| presence));
+
+        let mut granularities: Vec<GranularityConsistency> = Vec::new();
+        for (granularity_id, presence) in results {
+            match granularities
+                .iter_mut()
+                .find(|level| level.granularity_id == granularity_id)
+            {
+                Some(level) => level.tokens.push(presence),
+                None => granularities.push(GranularityConsistency {
+                    granularity_id,
+                    score: 0.0,
+                    tokens: vec![presence],
+                }),
+            }
+        }
+        for level in &mut granularities {
+            level.score = consistency_score(level.tokens.iter());
+        }
+
+        Self {
+            persona_id,
+            image_path,
+            score,
+            granularities,
+            summary,
+            provider: config.provider,
+            model: config.model.clone(),
+        }
+    }
+}
+
+/// Share of results the image agrees with; 0 when there are none.
+fn consistency_score<'a>(results: impl Iterator<Item = &'a TokenPresence>) -> f64 {
+    let (consistent, total) = results.fold((0_usize, 0_usize), |(consistent, total), result| {
+        (consistent + usize::from(result.consistent), total + 1)
+    });
+    if total == 0 {
+        0.0
+    } else {
+        consistent as f64 / total as f64
+    }
 }
@@ -0,0 +1,204 @@
+//! Token Aliases
+//!
+//! A user-maintained table mapping stylistic variants of a token to the
+//! phrasing that works best for the user's checkpoint, like "ginger hair" to
+//! "red hair".
+//!
+//! # Matching
+//!
+//! Aliases match whole tokens, ignoring case, accents, and repeated
+//! whitespace: `ginger hair` matches "Ginger  Hair" but not "long ginger
+//! hair". A token keeps its weight when it is replaced.
+//!
+//! # Use
+//!
+//! - Composition replaces aliased tokens with their phrasing when
+//!   `CompositionOptions::apply_aliases` is set (see [`AliasMap::apply`])
+//! - The prompt linter and persona similarity compare tokens by their
+//!   phrasing, so variants count as duplicates
+//! - Applying a token pack or importing a section skips tokens the persona
+//!   already has under another variant (see `TokenRepo::create_missing`)
+//!
+//! # Chains
+//!
+//! A phrasing cannot itself be an alias, and an alias cannot be the phrasing
+//! of another alias, so a single lookup always resolves a token.
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::collation;
+use super::token::{Token, TokenPolarity};
+use crate::error::AppError;
+
+/// A variant of a token and the phrasing it stands for.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenAlias {
+    /// Unique identifier (UUID v4)
+    pub id: String,
+    /// The variant to replace (e.g., "ginger hair"), unique ignoring case
+    pub alias: String,
+    /// The phrasing to use instead (e.g., "red hair")
+    pub canonical: String,
+    /// Creation timestamp
+    pub created_at: DateTime<Utc>,
+}
+
+impl TokenAlias {
+    /// Creates a new alias with auto-generated UUID and current timestamp.
+    #[must_use]
+    pub fn new(alias: String, canonical: String) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            alias,
+            canonical,
+            created_at: Utc::now(),
+        }
+    }
+}
+
+/// Normalizes an alias or phrasing: trims it and collapses inner whitespace.
+///
+/// # Errors
+///
+/// Returns `AppError::Validation` if the text is empty.
+pub fn normalize_phrase(text: &str) -> Result<String, AppError> {
+    let phrase = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if phrase.is_empty() {
+        return Err(AppError::Validation(
+            "Alias and phrasing cannot be empty".to_string(),
+        ));
+    }
+    Ok(phrase)
+}
+
+/// Returns the matching key of a token: folded, with whitespace collapsed.
+#[must_use]
+pub fn alias_key(content: &str) -> String {
+    collation::fold(content)
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Checks that a new alias neither repeats nor chains existing ones.
+///
+/// # Arguments
+///
+/// * `alias` - The normalized variant
+/// * `canonical` - The normalized phrasing
+/// * `existing` - The aliases stored so far
+///
+/// # Errors
+///
+/// Returns `AppError::Validation` if the alias matches its phrasing, is
+/// already an alias or the phrasing of one, or the phrasing is an alias.
+pub fn validate_new(alias: &str, canonical: &str, existing: &[TokenAlias]) -> Result<(), AppError> {
+    let new_alias = alias_key(alias);
+    let new_canonical = alias_key(canonical);
+    if new_alias == new_canonical {
+        return Err(AppError::Validation(format!(
+            "'{alias}' cannot be an alias of itself"
+        )));
+    }
+
+    for other in existing {
+        let other_alias = alias_key(&other.alias);
+        if other_alias == new_alias {
+            return Err(AppError::Validation(format!(
+                "'{}' is already an alias of '{}'",
+                other.alias, other.canonical
+            )));
+        }
+        if alias_key(&other.canonical) == new_alias {
+            return Err(AppError::Validation(format!(
+                "'{alias}' is the phrasing of the alias '{}'; it cannot be an alias itself",
+                other.alias
+            )));
+        }
+        if other_alias == new_canonical {
+            return Err(AppError::Validation(format!(
+                "'{canonical}' is an alias of '{}'; use that phrasing instead",
+                other.canonical
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Aliases indexed by matching key, for resolving tokens.
+#[derive(Debug, Clone, Default)]
+pub struct AliasMap {
+    canonical_by_key: HashMap<String, String>,
+}
+
+impl AliasMap {
+    /// Indexes the given aliases.
+    #[must_use]
+    pub fn new(aliases: &[TokenAlias]) -> Self {
+        Self {
+            canonical_by_key: aliases
+                .iter()
+                .map(|alias| (alias_key(&alias.alias), alias.canonical.clone()))
+                .collect(),
+        }
+    }
+
+    /// Returns the phrasing a token stands for, or `None` if it is no alias.
+    #[must_use]
+    pub fn canonical(&self, content: &str) -> Option<&str> {
+        self.canonical_by_key
+            .get(&alias_key(content))
+            .map(String::as_str)
+    }
+
+    /// Returns the matching key of a token's phrasing, so variants share a key.
+    #[must_use]
+    pub fn key(&self, content: &str) -> String {
+        alias_key(self.canonical(content).unwrap_or(content))
+    }
+
+    /// Replaces aliased tokens with their phrasing, keeping their weight.
+    ///
+    /// When a replaced token repeats another token of the same polarity
+    /// (e.g., "ginger hair" next to "red hair"), only the first in order is
+    /// kept. Other repeats are left alone.
+    ///
+    /// # Arguments
+    ///
+    /// * `tokens` - Tokens in display order
+    ///
+    /// # Returns
+    ///
+    /// The tokens in the same order, with the replaced contents.
+    #[must_use]
+    pub fn apply(&self, tokens: Vec<Token>) -> Vec<Token> {
+        if self.canonical_by_key.is_empty() {
+            return tokens;
+        }
+
+        let mut replaced_keys: HashSet<(TokenPolarity, String)> = HashSet::new();
+        let tokens: Vec<Token> = tokens
+            .into_iter()
+            .map(|mut token| {
+                if let Some(canonical) = self.canonical(&token.content) {
+                    token.content = canonical.to_string();
+                    replaced_keys.insert((token.polarity, alias_key(canonical)));
+                }
+                token
+            })
+            .collect();
+
+        let mut seen: HashSet<(TokenPolarity, String)> = HashSet::new();
+        tokens
+            .into_iter()
+            .filter(|token| {
+                let key = (token.polarity, alias_key(&token.content));
+                !replaced_keys.contains(&key) || seen.insert(key)
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,175 @@
+//! IPC API Versioning
+//!
+//! This module versions the command interface between the TypeScript frontend
+//! and the Rust backend, so that a frontend and backend from different builds
+//! (e.g., after a partial update) detect the mismatch at startup instead of
+//! failing on the first changed command.
+//!
+//! [`API_VERSION`] is bumped whenever a command is removed or renamed, or its
+//! arguments or result change incompatibly; new commands and new optional
+//! fields do not bump it.
+//!
+//! # Handshake
+//!
+//! At startup the frontend sends the API version it was built against to the
+//! `api_handshake` command and receives an [`ApiHandshake`] telling it whether
+//! it can proceed.
+
+use serde::{Deserialize, Serialize};
+
+/// Current version of the IPC command interface.
+pub const API_VERSION: u32 = 1;
+
+/// Oldest frontend API version the handshake accepts.
+pub const MIN_SUPPORTED_API_VERSION: u32 = if API_VERSION > 1 {
+    API_VERSION - 1
+} else {
+    API_VERSION
+};
+
+/// Version information about the running backend.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiVersionInfo {
+    /// Version of the IPC command interface
+    pub api_version: u32,
+    /// Oldest frontend API version still served
+    pub min_supported_api_version: u32,
+    /// Application version of the backend build (e.g., "0.2.1")
+    pub app_version: String,
+}
+
+impl ApiVersionInfo {
+    /// Returns the version information of this build.
+    #[must_use]
+    pub fn current() -> Self {
+        Self {
+            api_version: API_VERSION,
+            min_supported_api_version: MIN_SUPPORTED_API_VERSION,
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+        }
+    }
+}
+
+/// How a frontend's API version relates to the backend's.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiCompatibility {
+    /// Same version; everything works
+    Compatible,
+    /// Older than the backend, but still accepted
+    Deprecated,
+    /// Older than the backend still serves
+    FrontendTooOld,
+    /// Newer than the backend; commands it relies on may be missing
+    FrontendTooNew,
+}
+
+impl ApiCompatibility {
+    /// Compares a frontend API version against this backend.
+    #[must_use]
+    pub const fn check(frontend_api_version: u32) -> Self {
+        if frontend_api_version == API_VERSION {
+            Self::Compatible
+        } else if frontend_api_version > API_VERSION {
+            Self::FrontendTooNew
+        } else if frontend_api_version >= MIN_SUPPORTED_API_VERSION {
+            Self::Deprecated
+        } else {
+            Self::FrontendTooOld
+        }
+    }
+
+    /// Returns true if the frontend can use the backend.
+    #[must_use]
+    pub const fn is_usable(self) -> bool {
+        matches!(self, Self::Compatible | Self::Deprecated)
+    }
+}
+
+/// Result of the startup handshake between frontend and backend.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiHandshake {
+    /// How the frontend's version relates to the backend's
+    pub compatibility: ApiCompatibility,
+    /// Whether the frontend can use the backend
+    pub usable: bool,
+    /// The backend's version information
+    pub backend: ApiVersionInfo,
+    /// Explanation for the user when the versions differ
+    pub message: Option<String>,
+}
+
+impl ApiHandshake {
+    /// Performs the handshake for a frontend built against the given version.
+    #[must_use]
+    pub fn new(frontend_api_version: u32) -> Self {
+        let compatibility = ApiCompatibility::check(frontend_api_version);
+        let message = match compatibility {
+            ApiCompatibility::Compatible => None,
+            ApiCompatibility::Deprecated => Some(format!(
+                "The interface uses API version {frontend_api_version}, which is deprecated \
+                 (current: {API_VERSION}). Reinstall the application to update it."
+            )),
+            ApiCompatibility::FrontendTooOld => Some(format!(
+                "The interface uses API version {frontend_api_version}, which this backend no \
+                 longer supports (oldest supported: {MIN_SUPPORTED_API_VERSION}). Reinstall the \
+                 application."
+            )),
+            ApiCompatibility::FrontendTooNew => Some(format!(
+                "The interface uses API version {frontend_api_version}, which is newer than this \
+                 backend (current: {API_VERSION}). Reinstall the application."
+            )),
+        };
+
+        Self {
+            compatibility,
+            usable: compatibility.is_usable(),
+            backend: ApiVersionInfo::current(),
+            message,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_version_is_compatible() {
+        assert_eq!(
+            ApiCompatibility::check(API_VERSION),
+            ApiCompatibility::Compatible
+        );
+        assert!(ApiHandshake::new(API_VERSION).message.is_none());
+    }
+
+    #[test]
+    fn newer_frontend_is_rejected() {
+        let handshake = ApiHandshake::new(API_VERSION + 1);
+
+        assert_eq!(handshake.compatibility, ApiCompatibility::FrontendTooNew);
+        assert!(!handshake.usable);
+        assert!(handshake.message.is_some());
+    }
+
+    #[test]
+    fn minimum_supported_version_is_usable() {
+        let compatibility = ApiCompatibility::check(MIN_SUPPORTED_API_VERSION);
+
+        assert!(compatibility.is_usable());
+        if MIN_SUPPORTED_API_VERSION < API_VERSION {
+            assert_eq!(compatibility, ApiCompatibility::Deprecated);
+        } else {
+            assert_eq!(compatibility, ApiCompatibility::Compatible);
+        }
+    }
+
+    #[test]
+    fn older_than_minimum_is_rejected() {
+        let handshake = ApiHandshake::new(MIN_SUPPORTED_API_VERSION - 1);
+
+        assert_eq!(handshake.compatibility, ApiCompatibility::FrontendTooOld);
+        assert!(!handshake.usable);
+        assert!(handshake.message.is_some());
+    }
+}
@@ -0,0 +1,164 @@
+//! Token Autocompletion
+//!
+//! Completions for a token being typed, matched by prefix against the
+//! content of the user's own tokens and, optionally, tag dictionaries set in
+//! [`AppSettings::tag_dictionaries`](super::settings::AppSettings::tag_dictionaries).
+//!
+//! # Matching
+//!
+//! Prefixes and contents are compared by their completion key: trimmed and
+//! lowercased ("Blue Eyes" and " blue eyes" share the key "blue eyes").
+//! Tokens are always English, so only ASCII letters are folded; this matches
+//! the `content_key` column indexed in the tokens table.
+//!
+//! # Ranking
+//!
+//! Library completions come first, most used first. Dictionary tags fill the
+//! remaining places, most popular first, skipping tags the library already
+//! offered.
+//!
+//! # Dictionary Format
+//!
+//! Dictionaries are the CSV files of the A1111 tag autocomplete extension
+//! (`tag,category,post_count,aliases`), or plain lists with one tag per line.
+//! Underscores in tags are written as spaces, as in prompts (`long_hair`
+//! completes as "long hair"); tags without letters or digits, like `^_^`,
+//! are kept as they are.
+
+use serde::{Deserialize, Serialize};
+
+/// Number of completions returned when no limit is given.
+pub const DEFAULT_AUTOCOMPLETE_LIMIT: usize = 10;
+
+/// Largest number of completions returned at once.
+pub const MAX_AUTOCOMPLETE_LIMIT: usize = 50;
+
+/// Where a completion comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CompletionSource {
+    /// Content of the user's own tokens
+    Library,
+    /// A tag of a tag dictionary
+    Dictionary,
+}
+
+/// A completion for the token being typed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenCompletion {
+    /// Token content to insert
+    pub content: String,
+    /// Library tokens or a tag dictionary
+    pub source: CompletionSource,
+    /// Number of tokens with this content (library), or the tag's post count
+    /// (dictionary)
+    pub count: u64,
+}
+
+/// A tag read from a tag dictionary.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DictionaryTag {
+    /// Completion key of the tag (see [`completion_key`])
+    pub key: String,
+    /// Tag as written in prompts
+    pub content: String,
+    /// Post count from the dictionary; 0 when it has none
+    pub count: u64,
+}
+
+impl DictionaryTag {
+    /// Parses a dictionary line.
+    ///
+    /// Returns `None` for blank lines and `#` comments.
+    #[must_use]
+    pub fn parse(line: &str) -> Option<Self> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+
+        let mut fields = line.split(',');
+        let tag = fields.next()?.trim().trim_matches('"').trim();
+        if tag.is_empty() {
+            return None;
+        }
+        let count = fields
+            .nth(1)
+            .and_then(|count| count.trim().trim_matches('"').parse().ok())
+            .unwrap_or(0);
+
+        let content = if tag.chars().any(char::is_alphanumeric) {
+            tag.replace('_', " ")
+        } else {
+            tag.to_string()
+        };
+        Some(Self {
+            key: completion_key(&content),
+            content,
+            count,
+        })
+    }
+
+    /// Converts the tag into a completion.
+    #[must_use]
+    pub fn to_completion(&self) -> TokenCompletion {
+        TokenCompletion {
+            content: self.content.clone(),
+            source: CompletionSource::Dictionary,
+            count: self.count,
+        }
+    }
+}
+
+/// Returns the completion key of a token or tag: trimmed, ASCII lowercase.
+#[must_use]
+pub fn completion_key(text: &str) -> String {
+    text.trim().to_ascii_lowercase()
+}
+
+/// Returns the completion key of a typed prefix.
+///
+/// Unlike [`completion_key`], trailing spaces are kept: "blue " completes
+/// "blue eyes" but not "bluebell".
+#[must_use]
+pub fn prefix_key(prefix: &str) -> String {
+    prefix.trim_start().to_ascii_lowercase()
+}
+
+/// Clamps a requested completion limit to 1 to [`MAX_AUTOCOMPLETE_LIMIT`].
+#[must_use]
+pub fn clamp_limit(limit: Option<usize>) -> usize {
+    limit
+        .unwrap_or(DEFAULT_AUTOCOMPLETE_LIMIT)
+        .clamp(1, MAX_AUTOCOMPLETE_LIMIT)
+}
+
+/// Ranks library completions before dictionary ones, dropping dictionary
+/// tags the library already offers.
+///
+/// # Arguments
+///
+/// * `library` - Library completions, most used first
+/// * `dictionary` - Dictionary completions, most popular first
+/// * `limit` - Number of completions to return at most
+#[must_use]
+pub fn merge(
+    mut library: Vec<TokenCompletion>,
+    dictionary: Vec<TokenCompletion>,
+    limit: usize,
+) -> Vec<TokenCompletion> {
+    library.truncate(limit);
+    let keys: Vec<String> = library
+        .iter()
+        .map(|completion| completion_key(&completion.content))
+        .collect();
+
+    let remaining = limit - library.len();
+    library.extend(
+        dictionary
+            .into_iter()
+            .filter(|completion| !keys.contains(&completion_key(&completion.content)))
+            .take(remaining),
+    );
+    library
+}
@@ -0,0 +1,145 @@
+//! Banned Terms
+//!
+//! A user-managed blacklist of words and phrases that tokens must not contain.
+//!
+//! # Matching
+//!
+//! Terms match whole words, ignoring case, accents, and punctuation: `gore`
+//! matches "gore" and "(Gore:1.2)" but not "gorey". A multi-word term matches
+//! the same words in sequence, so `full body` matches "full body shot" but
+//! not "full upper body".
+//!
+//! # Enforcement
+//!
+//! - Creating or editing tokens, and applying AI suggestions, fails with
+//!   `AppError::Validation`
+//! - AI suggestions and composed prompts drop matching tokens and report them
+//!   as [`FilteredToken`]s
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::collation;
+use crate::error::AppError;
+
+/// A word or phrase that tokens must not contain.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BannedTerm {
+    /// Unique identifier (UUID v4)
+    pub id: String,
+    /// The banned word or phrase, unique ignoring case
+    pub term: String,
+    /// Creation timestamp
+    pub created_at: DateTime<Utc>,
+}
+
+impl BannedTerm {
+    /// Creates a new banned term with auto-generated UUID and current timestamp.
+    #[must_use]
+    pub fn new(term: String) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            term,
+            created_at: Utc::now(),
+        }
+    }
+}
+
+/// A token left out because it contains a banned term.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FilteredToken {
+    /// The token text
+    pub content: String,
+    /// The banned term it contains, as stored
+    pub term: String,
+}
+
+/// Normalizes a term: trims it and collapses inner whitespace.
+///
+/// # Errors
+///
+/// Returns `AppError::Validation` if the term has no letter or digit, since
+/// it could never match a word.
+pub fn normalize_term(term: &str) -> Result<String, AppError> {
+    let term = term.split_whitespace().collect::<Vec<_>>().join(" ");
+    if words(&term).is_empty() {
+        return Err(AppError::Validation(
+            "Banned term must contain a letter or digit".to_string(),
+        ));
+    }
+    Ok(term)
+}
+
+/// Returns the first banned term contained in `content`, if any.
+#[must_use]
+pub fn find_banned_term<'a>(content: &str, terms: &'a [BannedTerm]) -> Option<&'a str> {
+    let content_words = words(content);
+    terms
+        .iter()
+        .find(|banned| {
+            let term_words = words(&banned.term);
+            !term_words.is_empty()
+                && content_words
+                    .windows(term_words.len())
+                    .any(|window| window == term_words.as_slice())
+        })
+        .map(|banned| banned.term.as_str())
+}
+
+/// Checks that a token does not contain a banned term.
+///
+/// # Errors
+///
+/// Returns `AppError::Validation` naming the token and the term it contains.
+pub fn ensure_allowed(content: &str, terms: &[BannedTerm]) -> Result<(), AppError> {
+    find_banned_term(content, terms).map_or(Ok(()), |term| {
+        Err(AppError::Validation(format!(
+            "Token '{content}' contains the banned term '{term}'"
+        )))
+    })
+}
+
+/// Splits items into those without banned terms and a report of those left out.
+///
+/// # Arguments
+///
+/// * `items` - Tokens or suggestions, in order
+/// * `terms` - The banned terms
+/// * `content` - Text of an item
+///
+/// # Returns
+///
+/// The kept items in their original order, and one [`FilteredToken`] per
+/// item left out.
+pub fn strip_banned<T>(
+    items: Vec<T>,
+    terms: &[BannedTerm],
+    content: impl Fn(&T) -> &str,
+) -> (Vec<T>, Vec<FilteredToken>) {
+    if terms.is_empty() {
+        return (items, Vec::new());
+    }
+
+    let mut kept = Vec::with_capacity(items.len());
+    let mut filtered = Vec::new();
+    for item in items {
+        match find_banned_term(content(&item), terms) {
+            Some(term) => filtered.push(FilteredToken {
+                content: content(&item).to_string(),
+                term: term.to_string(),
+            }),
+            None => kept.push(item),
+        }
+    }
+    (kept, filtered)
+}
+
+/// Folded alphanumeric words of a text.
+fn words(text: &str) -> Vec<String> {
+    collation::fold(text)
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_string)
+        .collect()
+}
@@ -0,0 +1,257 @@
+//! Persona Blending
+//!
+//! This module defines how several parent personas are combined into a new
+//! persona draft, for creating families or variants of a character.
+//!
+//! # Blend Modes
+//!
+//! - **Interleave**: Deterministic. For each granularity and polarity, parent
+//!   tokens are merged with a smooth weighted round-robin, so a parent with
+//!   twice the weight contributes roughly twice as many tokens.
+//! - **AI**: The LLM synthesizes a child persona that inherits traits from
+//!   each parent in proportion to its weight (see `infrastructure::ai::blend`).
+//!
+//! Either way the result is a [`PersonaBlendDraft`]; nothing is saved until the
+//! user creates the persona from it.
+
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use super::persona::Persona;
+use super::token::{GeneratedTokenSelection, Granularity, Token, TokenPolarity};
+use crate::error::AppError;
+
+/// How parent personas are combined.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BlendMode {
+    /// Deterministically interleave parent tokens by weight
+    #[default]
+    Interleave,
+    /// Ask the AI provider to synthesize a child persona
+    Ai,
+}
+
+/// Request payload for blending personas into a new draft.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PersonaBlendRequest {
+    /// UUIDs of the parent personas (at least two, no duplicates)
+    pub persona_ids: Vec<String>,
+    /// Relative weight per parent, in the same order; empty means equal weights
+    #[serde(default)]
+    pub weights: Vec<f64>,
+    /// How the parents are combined
+    #[serde(default)]
+    pub mode: BlendMode,
+    /// Name for the draft; derived from the parents' names if omitted
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Guidance on which traits to inherit from which parent (AI mode only)
+    #[serde(default)]
+    pub inherit_traits: Option<String>,
+}
+
+/// A parent persona with its tokens and normalized weight.
+#[derive(Debug, Clone, Copy)]
+pub struct BlendParent<'a> {
+    /// The parent persona
+    pub persona: &'a Persona,
+    /// The parent's tokens, in display order
+    pub tokens: &'a [Token],
+    /// Normalized weight (all parents sum to 1.0)
+    pub weight: f64,
+}
+
+/// Unsaved persona produced by blending.
+///
+/// Create it with `create_persona` followed by `apply_generated_tokens`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PersonaBlendDraft {
+    /// Proposed name
+    pub name: String,
+    /// Proposed description
+    pub description: Option<String>,
+    /// Tags inherited from the parents, heaviest parent first
+    pub tags: Vec<String>,
+    /// Tokens in the order they should be saved
+    pub tokens: Vec<GeneratedTokenSelection>,
+    /// UUIDs of the parents, in request order
+    pub parent_ids: Vec<String>,
+    /// Mode that produced the draft
+    pub mode: BlendMode,
+}
+
+impl PersonaBlendRequest {
+    /// Validates the parent list and returns weights normalized to sum to 1.0.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Validation` if fewer than two distinct personas are
+    /// given, the weight count does not match, or a weight is not positive.
+    pub fn normalized_weights(&self) -> Result<Vec<f64>, AppError> {
+        if self.persona_ids.len() < 2 {
+            return Err(AppError::Validation(
+                "Blending requires at least two personas".to_string(),
+            ));
+        }
+
+        let unique: HashSet<&str> = self.persona_ids.iter().map(String::as_str).collect();
+        if unique.len() != self.persona_ids.len() {
+            return Err(AppError::Validation(
+                "Each persona can only be blended once".to_string(),
+            ));
+        }
+
+        if self.weights.is_empty() {
+            let equal = 1.0 / self.persona_ids.len() as f64;
+            return Ok(vec![equal; self.persona_ids.len()]);
+        }
+
+        if self.weights.len() != self.persona_ids.len() {
+            return Err(AppError::Validation(format!(
+                "Expected {} weights, got {}",
+                self.persona_ids.len(),
+                self.weights.len()
+            )));
+        }
+
+        if self.weights.iter().any(|w| !w.is_finite() || *w <= 0.0) {
+            return Err(AppError::Validation(
+                "Blend weights must be positive numbers".to_string(),
+            ));
+        }
+
+        let total: f64 = self.weights.iter().sum();
+        Ok(self.weights.iter().map(|w| w / total).collect())
+    }
+
+    /// Returns the requested name, or the parents' names joined with " × ".
+    #[must_use]
+    pub fn draft_name(&self, parents: &[BlendParent<'_>]) -> String {
+        self.name
+            .as_deref()
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map_or_else(
+                || {
+                    parents
+                        .iter()
+                        .map(|p| p.persona.name.as_str())
+                        .collect::<Vec<_>>()
+                        .join(" × ")
+                },
+                String::from,
+            )
+    }
+}
+
+impl PersonaBlendDraft {
+    /// Builds a draft by interleaving the parents' tokens by weight.
+    #[must_use]
+    pub fn interleave(request: &PersonaBlendRequest, parents: &[BlendParent<'_>]) -> Self {
+        let mut tokens = Vec::new();
+        for &granularity in Granularity::all() {
+            for polarity in [TokenPolarity::Positive, TokenPolarity::Negative] {
+                tokens.extend(interleave_bucket(parents, granularity, polarity));
+            }
+        }
+
+        let description = parents
+            .iter()
+            .map(|p| format!("{} ({:.0}%)", p.persona.name, p.weight * 100.0))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        Self {
+            name: request.draft_name(parents),
+            description: Some(format!("Blend of {description}")),
+            tags: blend_tags(parents),
+            tokens,
+            parent_ids: parents.iter().map(|p| p.persona.id.clone()).collect(),
+            mode: BlendMode::Interleave,
+        }
+    }
+}
+
+/// Returns the union of the parents' tags, heaviest parent first.
+#[must_use]
+pub fn blend_tags(parents: &[BlendParent<'_>]) -> Vec<String> {
+    let mut ordered: Vec<&BlendParent<'_>> = parents.iter().collect();
+    ordered.sort_by(|a, b| b.weight.total_cmp(&a.weight));
+
+    let mut seen = HashSet::new();
+    ordered
+        .iter()
+        .flat_map(|p| p.persona.tags.iter())
+        .filter(|tag| seen.insert(tag.to_lowercase()))
+        .cloned()
+        .collect()
+}
+
+/// Merges one granularity/polarity bucket from all parents.
+///
+/// The output length is the weighted average of the parents' bucket sizes.
+/// Tokens are picked with a smooth weighted round-robin, skipping content
+/// already taken from another parent (case-insensitive).
+fn interleave_bucket(
+    parents: &[BlendParent<'_>],
+    granularity: Granularity,
+    polarity: TokenPolarity,
+) -> Vec<GeneratedTokenSelection> {
+    let queues: Vec<Vec<&Token>> = parents
+        .iter()
+        .map(|p| {
+            p.tokens
+                .iter()
+                .filter(|t| t.granularity_id == granularity.as_str() && t.polarity == polarity)
+                .collect()
+        })
+        .collect();
+
+    let weighted_len: f64 = parents
+        .iter()
+        .zip(&queues)
+        .map(|(p, q)| p.weight * q.len() as f64)
+        .sum();
+    #[allow(clippy::cast_sign_loss)] // weights and counts are non-negative
+    let target = weighted_len.round() as usize;
+
+    let mut cursors = vec![0usize; parents.len()];
+    let mut credits = vec![0.0f64; parents.len()];
+    let mut seen = HashSet::new();
+    let mut merged = Vec::with_capacity(target);
+
+    while merged.len() < target {
+        let active: Vec<usize> = (0..parents.len())
+            .filter(|&i| cursors[i] < queues[i].len())
+            .collect();
+        let Some(&first) = active.first() else {
+            break;
+        };
+
+        let mut pick = first;
+        for &i in &active {
+            credits[i] += parents[i].weight;
+            if credits[i] > credits[pick] {
+                pick = i;
+            }
+        }
+        credits[pick] -= active.iter().map(|&i| parents[i].weight).sum::<f64>();
+
+        while let Some(token) = queues[pick].get(cursors[pick]) {
+            cursors[pick] += 1;
+            if seen.insert(token.content.trim().to_lowercase()) {
+                merged.push(GeneratedTokenSelection {
+                    granularity_id: token.granularity_id.clone(),
+                    polarity: token.polarity,
+                    content: token.content.clone(),
+                    weight: token.weight,
+                });
+                break;
+            }
+        }
+    }
+
+    merged
+}
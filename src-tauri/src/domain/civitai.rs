@@ -0,0 +1,138 @@
+//! Civitai Model Metadata
+//!
+//! Checkpoint and `LoRA` metadata looked up on Civitai (see
+//! `infrastructure::civitai`), reduced to what the app can use: the trigger
+//! words a `LoRA` was trained with, and the base model family, which selects the
+//! tokenizer and prompt conventions.
+//!
+//! Models are looked up by file hash (the SHA-256 or `AutoV2` hash shown by most
+//! image generators) or by name.
+
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use super::token::{GeneratedTokenSelection, Granularity, TokenPolarity};
+use crate::error::AppError;
+
+/// Default number of results of a lookup by name.
+pub const DEFAULT_CIVITAI_SEARCH_LIMIT: u32 = 10;
+
+/// Maximum number of results of a lookup by name.
+pub const MAX_CIVITAI_SEARCH_LIMIT: u32 = 50;
+
+/// A model version published on Civitai.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CivitaiModel {
+    /// Civitai model ID
+    pub model_id: u64,
+    /// Civitai ID of the matched version
+    pub version_id: u64,
+    /// Model name (e.g., "Juggernaut XL")
+    pub name: String,
+    /// Version name (e.g., "v9")
+    pub version_name: String,
+    /// Model type as reported by Civitai (e.g., "Checkpoint", "LORA")
+    pub model_type: String,
+    /// Base model as reported by Civitai (e.g., "SDXL 1.0", "Pony")
+    pub base_model: String,
+    /// Model family the base model belongs to (sd15, sd2, sdxl, flux), if known
+    pub family: Option<String>,
+    /// Words the version was trained with, deduplicated, in published order
+    pub trigger_words: Vec<String>,
+    /// Trigger words as positive Style-level tokens, ready to add to a persona
+    pub suggested_tokens: Vec<GeneratedTokenSelection>,
+}
+
+impl CivitaiModel {
+    /// Creates a model from the fields Civitai reports for a version.
+    ///
+    /// Trained words may hold several comma-separated triggers; they are split,
+    /// trimmed, and deduplicated case-insensitively.
+    #[must_use]
+    pub fn new(
+        model_id: u64,
+        version_id: u64,
+        name: String,
+        version_name: String,
+        model_type: String,
+        base_model: String,
+        trained_words: &[String],
+    ) -> Self {
+        let mut seen = HashSet::new();
+        let trigger_words: Vec<String> = trained_words
+            .iter()
+            .flat_map(|words| words.split(','))
+            .map(str::trim)
+            .filter(|word| !word.is_empty() && seen.insert(word.to_lowercase()))
+            .map(ToString::to_string)
+            .collect();
+
+        let suggested_tokens = trigger_words
+            .iter()
+            .map(|word| GeneratedTokenSelection {
+                granularity_id: Granularity::Style.as_str().to_string(),
+                polarity: TokenPolarity::Positive,
+                content: word.clone(),
+                weight: 1.0,
+            })
+            .collect();
+
+        Self {
+            model_id,
+            version_id,
+            family: family_for_base_model(&base_model).map(ToString::to_string),
+            name,
+            version_name,
+            model_type,
+            base_model,
+            trigger_words,
+            suggested_tokens,
+        }
+    }
+}
+
+/// Maps a Civitai base model name to a model family identifier.
+///
+/// Pony, Illustrious, and `NoobAI` are SDXL fine-tunes and share its
+/// tokenizer. Families the app has no tokenizer for (e.g., SD 3, Hunyuan
+/// Video) return `None`.
+#[must_use]
+pub fn family_for_base_model(base_model: &str) -> Option<&'static str> {
+    let base = base_model.to_lowercase();
+
+    if base.starts_with("flux") {
+        Some("flux")
+    } else if base.starts_with("sdxl")
+        || base.starts_with("pony")
+        || base.starts_with("illustrious")
+        || base.starts_with("noobai")
+    {
+        Some("sdxl")
+    } else if base.starts_with("sd 1.") {
+        Some("sd15")
+    } else if base.starts_with("sd 2.") {
+        Some("sd2")
+    } else {
+        None
+    }
+}
+
+/// Validates a model file hash and normalizes it to uppercase.
+///
+/// Accepts the short hashes image generators display (`AutoV1` and `AutoV2`, 8
+/// to 10 characters) as well as full SHA-256 and BLAKE3 hashes.
+///
+/// # Errors
+///
+/// Returns `AppError::Validation` if the hash is not 8 to 64 hexadecimal
+/// characters.
+pub fn normalize_model_hash(hash: &str) -> Result<String, AppError> {
+    let hash = hash.trim();
+    if !(8..=64).contains(&hash.len()) || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(AppError::Validation(format!(
+            "Invalid model hash '{hash}': expected 8 to 64 hexadecimal characters"
+        )));
+    }
+    Ok(hash.to_ascii_uppercase())
+}
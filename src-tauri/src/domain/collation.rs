@@ -0,0 +1,53 @@
+//! Text Folding and Collation
+//!
+//! Names, tags, and search text are compared the way people read them,
+//! ignoring case and accents: "Élise" matches "elise" and sorts next to
+//! "Elise" rather than after "Zoe".
+//!
+//! # Folding
+//!
+//! [`fold`] applies Unicode compatibility decomposition, drops combining
+//! marks, and lowercases the result, so "Élise", "ELISE", and "ｅｌｉｓｅ" all
+//! fold to "elise". This matches the `remove_diacritics` folding of the quick
+//! search index, so in-memory and indexed matching agree.
+//!
+//! # Ordering
+//!
+//! [`compare`] orders by folded text first; texts that fold the same are
+//! ordered by their original spelling, so sorting is deterministic.
+
+use std::cmp::Ordering;
+
+use unicode_normalization::char::{decompose_compatible, is_combining_mark};
+
+/// Folds text for matching: decomposes it, drops accents, and lowercases it.
+#[must_use]
+pub fn fold(text: &str) -> String {
+    let mut folded = String::with_capacity(text.len());
+    for c in text.chars() {
+        decompose_compatible(c, |d| {
+            if !is_combining_mark(d) {
+                folded.extend(d.to_lowercase());
+            }
+        });
+    }
+    folded
+}
+
+/// Returns true if `needle` occurs in `haystack`, ignoring case and accents.
+#[must_use]
+pub fn contains(haystack: &str, needle: &str) -> bool {
+    fold(haystack).contains(&fold(needle))
+}
+
+/// Returns true if both texts fold to the same text.
+#[must_use]
+pub fn eq(a: &str, b: &str) -> bool {
+    fold(a) == fold(b)
+}
+
+/// Orders texts ignoring case and accents, then by original spelling.
+#[must_use]
+pub fn compare(a: &str, b: &str) -> Ordering {
+    fold(a).cmp(&fold(b)).then_with(|| a.cmp(b))
+}
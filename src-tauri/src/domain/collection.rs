@@ -0,0 +1,176 @@
+//! Smart Collections
+//!
+//! A smart collection is a saved [`PersonaQuery`]: its members are not stored
+//! but computed whenever the collection is opened, so new or edited personas
+//! show up without maintenance.
+//!
+//! # Query Criteria
+//!
+//! All set criteria must match:
+//! - **Tags**: every term is contained in one of the persona's tags
+//! - **Tag namespaces**: every namespace (e.g., `project/clientA`) holds one of
+//!   the persona's tags (see [`super::tag`])
+//! - **Model family**: the family of the persona's image model (e.g., "sdxl")
+//! - **Recently used**: the persona was edited or its prompt composed within N days
+//!
+//! Archived personas are excluded unless the query includes them.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use serde_with::rust::double_option;
+use uuid::Uuid;
+
+use super::collation;
+use super::persona::Persona;
+use super::tag::{in_namespace, normalize_tag};
+use crate::error::AppError;
+
+/// Filter selecting personas; unset criteria match everything.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PersonaQuery {
+    /// Terms that must each be contained in one of the persona's tags (case- and
+    /// accent-insensitive)
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Namespaces that must each hold one of the persona's tags (e.g., "project"
+    /// matches "project/clientA")
+    #[serde(default)]
+    pub namespaces: Vec<String>,
+    /// Model family of the persona's image model (e.g., "sdxl", "sd15")
+    #[serde(default)]
+    pub model_family: Option<String>,
+    /// Only personas used within this many days
+    #[serde(default)]
+    pub used_within_days: Option<u32>,
+    /// Whether archived personas can match
+    #[serde(default)]
+    pub include_archived: bool,
+}
+
+impl PersonaQuery {
+    /// Validates the query.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Validation` if `used_within_days` is zero.
+    pub fn validate(&self) -> Result<(), AppError> {
+        if self.used_within_days == Some(0) {
+            return Err(AppError::Validation(
+                "Recently used period must be at least one day".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Returns true if the persona matches every set criterion.
+    ///
+    /// # Arguments
+    ///
+    /// * `persona` - The persona to test
+    /// * `model_family` - Family of the persona's image model
+    /// * `last_used_at` - When the persona was last edited or composed
+    /// * `now` - Reference time for `used_within_days`
+    #[must_use]
+    pub fn matches(
+        &self,
+        persona: &Persona,
+        model_family: &str,
+        last_used_at: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> bool {
+        if persona.archived && !self.include_archived {
+            return false;
+        }
+
+        let tags: Vec<String> = persona.tags.iter().map(|t| collation::fold(t)).collect();
+        let tags_match = self
+            .tags
+            .iter()
+            .map(|term| collation::fold(term.trim()))
+            .filter(|term| !term.is_empty())
+            .all(|term| tags.iter().any(|tag| tag.contains(&term)));
+        if !tags_match {
+            return false;
+        }
+
+        let namespaces_match = self
+            .namespaces
+            .iter()
+            .filter(|namespace| !normalize_tag(namespace).is_empty())
+            .all(|namespace| persona.tags.iter().any(|tag| in_namespace(tag, namespace)));
+        if !namespaces_match {
+            return false;
+        }
+
+        if let Some(family) = &self.model_family {
+            if !family.trim().eq_ignore_ascii_case(model_family) {
+                return false;
+            }
+        }
+
+        self.used_within_days.map_or(true, |days| {
+            last_used_at >= now - Duration::days(i64::from(days))
+        })
+    }
+}
+
+/// A named, saved persona query.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SmartCollection {
+    /// Unique identifier (UUID v4)
+    pub id: String,
+    /// Display name, unique across smart collections
+    pub name: String,
+    /// Filter defining the members
+    pub query: PersonaQuery,
+    /// Label color as `#rrggbb` (e.g., "#e11d48")
+    #[serde(default)]
+    pub color: Option<String>,
+    /// Creation timestamp
+    pub created_at: DateTime<Utc>,
+    /// Last modification timestamp
+    pub updated_at: DateTime<Utc>,
+}
+
+impl SmartCollection {
+    /// Creates a new smart collection with auto-generated UUID and current timestamps.
+    #[must_use]
+    pub fn new(name: String, query: PersonaQuery) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4().to_string(),
+            name,
+            query,
+            color: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+}
+
+/// Request payload for creating a smart collection.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateSmartCollectionRequest {
+    /// Unique name (required)
+    pub name: String,
+    /// Filter defining the members
+    #[serde(default)]
+    pub query: PersonaQuery,
+    /// Optional label color as `#rrggbb`
+    #[serde(default)]
+    pub color: Option<String>,
+}
+
+/// Request payload for updating a smart collection; omitted fields are kept.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateSmartCollectionRequest {
+    /// New name (must be unique if provided)
+    #[serde(default)]
+    pub name: Option<String>,
+    /// New filter
+    #[serde(default)]
+    pub query: Option<PersonaQuery>,
+    /// New label color: None = not provided, Some(None) = clear, Some(Some(hex)) = set
+    #[serde(default, with = "double_option")]
+    pub color: Option<Option<String>>,
+}
@@ -0,0 +1,246 @@
+//! Persona Comparison
+//!
+//! This module computes a structured diff between two personas, for
+//! reconciling two variants of the same character.
+//!
+//! # Token Matching
+//!
+//! Tokens match when they have the same granularity, polarity, and content
+//! (trimmed, case-insensitive). Each token matches at most one token of the
+//! other persona, so duplicates are compared one-to-one. Matched tokens land
+//! in [`TokenComparison::shared`] or, if their weights differ,
+//! [`TokenComparison::weight_differences`]; the rest are unique to one side.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use super::persona::{GenerationParams, Persona};
+use super::token::{Token, TokenPolarity};
+
+/// Weights closer than this are considered equal.
+const WEIGHT_EPSILON: f64 = 0.005;
+
+/// One side of a comparison: a persona with its parameters and tokens.
+#[derive(Debug, Clone, Copy)]
+pub struct ComparedPersona<'a> {
+    /// The persona
+    pub persona: &'a Persona,
+    /// Generation parameters, if saved
+    pub params: Option<&'a GenerationParams>,
+    /// Tokens in display order
+    pub tokens: &'a [Token],
+}
+
+/// Structured diff between persona A and persona B.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PersonaComparison {
+    /// UUID of persona A
+    pub persona_a_id: String,
+    /// UUID of persona B
+    pub persona_b_id: String,
+    /// Metadata fields that differ (name, description, AI settings)
+    pub metadata: Vec<FieldDifference>,
+    /// Generation parameters that differ
+    pub params: Vec<FieldDifference>,
+    /// Tag overlap
+    pub tags: TagComparison,
+    /// Token overlap
+    pub tokens: TokenComparison,
+}
+
+/// A field whose value differs between the two personas.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldDifference {
+    /// Field name (e.g., "description", "`cfg_scale`")
+    pub field: String,
+    /// Value in persona A (`null` if unset)
+    pub a: Value,
+    /// Value in persona B (`null` if unset)
+    pub b: Value,
+}
+
+/// Tags split by which persona has them (compared case-insensitively).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagComparison {
+    /// Tags on both personas, as spelled on A
+    pub shared: Vec<String>,
+    /// Tags only on persona A
+    pub only_a: Vec<String>,
+    /// Tags only on persona B
+    pub only_b: Vec<String>,
+}
+
+/// Tokens split by which persona has them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenComparison {
+    /// Tokens on both personas with the same weight
+    pub shared: Vec<TokenMatch>,
+    /// Tokens only on persona A, in A's display order
+    pub only_a: Vec<Token>,
+    /// Tokens only on persona B, in B's display order
+    pub only_b: Vec<Token>,
+    /// Tokens on both personas with different weights
+    pub weight_differences: Vec<TokenMatch>,
+}
+
+/// A token present on both personas.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenMatch {
+    /// Granularity level ID
+    pub granularity_id: String,
+    /// Positive or negative
+    pub polarity: TokenPolarity,
+    /// Token content, as written on A
+    pub content: String,
+    /// UUID of the token on persona A
+    pub token_a_id: String,
+    /// UUID of the token on persona B
+    pub token_b_id: String,
+    /// Weight on persona A
+    pub weight_a: f64,
+    /// Weight on persona B
+    pub weight_b: f64,
+}
+
+impl PersonaComparison {
+    /// Compares persona A with persona B.
+    #[must_use]
+    pub fn compare(a: ComparedPersona<'_>, b: ComparedPersona<'_>) -> Self {
+        Self {
+            persona_a_id: a.persona.id.clone(),
+            persona_b_id: b.persona.id.clone(),
+            metadata: diff_fields(&metadata_fields(a.persona), &metadata_fields(b.persona)),
+            params: diff_fields(&params_fields(a.params), &params_fields(b.params)),
+            tags: compare_tags(&a.persona.tags, &b.persona.tags),
+            tokens: compare_tokens(a.tokens, b.tokens),
+        }
+    }
+}
+
+/// Compared metadata fields of a persona, in display order.
+fn metadata_fields(persona: &Persona) -> Vec<(&'static str, Value)> {
+    vec![
+        ("name", json!(persona.name)),
+        ("description", json!(persona.description)),
+        ("ai_provider_id", json!(persona.ai_provider_id)),
+        ("ai_model_id", json!(persona.ai_model_id)),
+        ("ai_instructions", json!(persona.ai_instructions)),
+        ("ai_output_language", json!(persona.ai_output_language)),
+    ]
+}
+
+/// Compared generation parameters, in display order; all `null` if unsaved.
+fn params_fields(params: Option<&GenerationParams>) -> Vec<(&'static str, Value)> {
+    vec![
+        ("model_id", json!(params.map(|p| &p.model_id))),
+        ("seed", json!(params.map(|p| p.seed))),
+        ("steps", json!(params.map(|p| p.steps))),
+        ("cfg_scale", json!(params.map(|p| p.cfg_scale))),
+        ("sampler", json!(params.and_then(|p| p.sampler.as_ref()))),
+        (
+            "scheduler",
+            json!(params.and_then(|p| p.scheduler.as_ref())),
+        ),
+        ("width", json!(params.and_then(|p| p.width))),
+        ("height", json!(params.and_then(|p| p.height))),
+    ]
+}
+
+/// Lists the fields whose values differ; both lists have the same fields in the same order.
+fn diff_fields(a: &[(&'static str, Value)], b: &[(&'static str, Value)]) -> Vec<FieldDifference> {
+    a.iter()
+        .zip(b)
+        .filter(|((_, value_a), (_, value_b))| value_a != value_b)
+        .map(|((field, value_a), (_, value_b))| FieldDifference {
+            field: (*field).to_string(),
+            a: value_a.clone(),
+            b: value_b.clone(),
+        })
+        .collect()
+}
+
+/// Splits tags into shared and unique ones, comparing case-insensitively.
+fn compare_tags(a: &[String], b: &[String]) -> TagComparison {
+    let keys_a: HashSet<String> = a.iter().map(|tag| tag.trim().to_lowercase()).collect();
+    let keys_b: HashSet<String> = b.iter().map(|tag| tag.trim().to_lowercase()).collect();
+
+    let (shared, only_a) = a
+        .iter()
+        .cloned()
+        .partition(|tag| keys_b.contains(&tag.trim().to_lowercase()));
+    let only_b = b
+        .iter()
+        .filter(|tag| !keys_a.contains(&tag.trim().to_lowercase()))
+        .cloned()
+        .collect();
+
+    TagComparison {
+        shared,
+        only_a,
+        only_b,
+    }
+}
+
+/// Identity of a token for matching: granularity, polarity, normalized content.
+fn token_key(token: &Token) -> (&str, TokenPolarity, String) {
+    (
+        token.granularity_id.as_str(),
+        token.polarity,
+        token.content.trim().to_lowercase(),
+    )
+}
+
+/// Matches tokens one-to-one and splits them into shared, unique, and reweighted.
+fn compare_tokens(a: &[Token], b: &[Token]) -> TokenComparison {
+    let mut unmatched_b: HashMap<_, VecDeque<&Token>> = HashMap::new();
+    for token in b {
+        unmatched_b
+            .entry(token_key(token))
+            .or_default()
+            .push_back(token);
+    }
+
+    let mut comparison = TokenComparison {
+        shared: Vec::new(),
+        only_a: Vec::new(),
+        only_b: Vec::new(),
+        weight_differences: Vec::new(),
+    };
+    let mut matched_b: HashSet<&str> = HashSet::new();
+
+    for token_a in a {
+        let Some(token_b) = unmatched_b
+            .get_mut(&token_key(token_a))
+            .and_then(VecDeque::pop_front)
+        else {
+            comparison.only_a.push(token_a.clone());
+            continue;
+        };
+        matched_b.insert(token_b.id.as_str());
+
+        let token_match = TokenMatch {
+            granularity_id: token_a.granularity_id.clone(),
+            polarity: token_a.polarity,
+            content: token_a.content.clone(),
+            token_a_id: token_a.id.clone(),
+            token_b_id: token_b.id.clone(),
+            weight_a: token_a.weight,
+            weight_b: token_b.weight,
+        };
+        if (token_a.weight - token_b.weight).abs() < WEIGHT_EPSILON {
+            comparison.shared.push(token_match);
+        } else {
+            comparison.weight_differences.push(token_match);
+        }
+    }
+
+    comparison.only_b = b
+        .iter()
+        .filter(|token| !matched_b.contains(token.id.as_str()))
+        .cloned()
+        .collect();
+
+    comparison
+}
@@ -0,0 +1,93 @@
+//! System Diagnostics
+//!
+//! A snapshot of every subsystem's state, gathered in one call so a single
+//! screenshot of the diagnostics panel answers the usual support questions:
+//! which database is in use, whether keys are configured, whether tokenizers
+//! loaded, and when the last backup was made.
+//!
+//! Collecting diagnostics never fails because one subsystem is broken; a
+//! failing check is reported as unavailable instead.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::ai::AiProvider;
+use crate::domain::settings::ProxySource;
+
+/// Aggregated status of all subsystems.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SystemDiagnostics {
+    /// Application version
+    pub app_version: String,
+    /// Database file and schema state
+    pub database: DatabaseDiagnostics,
+    /// Whether the OS credential store can be used
+    pub credential_store_available: bool,
+    /// Tokenizer loading and count cache state
+    pub tokenizers: TokenizerDiagnostics,
+    /// API key status for each AI provider
+    pub ai_providers: Vec<ProviderDiagnostics>,
+    /// Whether offline mode is enabled
+    pub offline: bool,
+    /// Where the proxy in effect comes from
+    pub proxy_source: ProxySource,
+    /// When the database was last exported, if ever
+    pub last_backup_at: Option<DateTime<Utc>>,
+}
+
+/// Database file and schema state.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatabaseDiagnostics {
+    /// Path of the database file
+    pub path: String,
+    /// Size on disk in bytes, including the write-ahead log
+    pub size_bytes: u64,
+    /// Schema version stored in the database
+    pub schema_version: Option<i32>,
+    /// Schema version this build of the app expects
+    pub expected_schema_version: i32,
+    /// Personas whose token order has gaps or duplicates (repaired by the
+    /// `repair_token_order` command)
+    pub denormalized_token_orders: usize,
+}
+
+/// Tokenizer loading and count cache state.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenizerDiagnostics {
+    /// Tokenizer IDs loaded in memory
+    pub loaded: Vec<String>,
+    /// Tokenizer IDs currently loading in the background
+    pub loading: Vec<String>,
+    /// Number of token counts in the persistent cache
+    pub cached_counts: usize,
+}
+
+/// API key status for one AI provider.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderDiagnostics {
+    /// The AI provider
+    pub provider: AiProvider,
+    /// Whether the provider needs an API key at all
+    pub requires_api_key: bool,
+    /// Whether a key is stored in the OS credential store
+    pub has_stored_key: bool,
+    /// Whether the provider's API key environment variable is set
+    pub has_env_key: bool,
+}
+
+/// A schema migration run recorded in the migration history.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MigrationRecord {
+    /// Schema version the migration upgrades to
+    pub version: i32,
+    /// What the migration changes
+    pub description: String,
+    /// When the migration ran
+    pub applied_at: DateTime<Utc>,
+    /// How long the migration took, in milliseconds
+    pub duration_ms: u64,
+    /// Backup of the database taken before the upgrade, if any
+    pub backup_path: Option<String>,
+    /// Why the migration failed; `None` if it succeeded
+    pub error: Option<String>,
+}
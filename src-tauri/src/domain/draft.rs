@@ -0,0 +1,111 @@
+//! Persona Drafts
+//!
+//! Incomplete personas autosaved by the create flow. Drafts live apart from
+//! personas: they are not listed, searched, or composed, and their names need
+//! not be valid or unique until the draft is promoted to a persona.
+//!
+//! # Lifecycle
+//!
+//! The create form saves its state as a [`PersonaDraft`] every few seconds,
+//! updating the same draft by ID. Promoting a draft creates the persona with
+//! its tokens, applying the usual naming and banned-term rules, and deletes
+//! the draft.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::persona::{ContentRating, CreatePersonaRequest};
+use super::token::GeneratedTokenSelection;
+
+/// An autosaved, possibly incomplete persona.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PersonaDraft {
+    /// Unique identifier (UUID v4)
+    pub id: String,
+    /// Name as typed so far; may be empty or taken
+    pub name: String,
+    /// Description as typed so far
+    pub description: Option<String>,
+    /// Tags chosen so far
+    pub tags: Vec<String>,
+    /// Audience rating chosen so far
+    pub content_rating: ContentRating,
+    /// Tokens added so far, in order
+    pub tokens: Vec<GeneratedTokenSelection>,
+    /// When the draft was first saved
+    pub created_at: DateTime<Utc>,
+    /// When the draft was last saved
+    pub updated_at: DateTime<Utc>,
+}
+
+impl PersonaDraft {
+    /// Creates a draft from the first save of the create form.
+    #[must_use]
+    pub fn new(request: SaveDraftRequest) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4().to_string(),
+            name: request.name,
+            description: request.description,
+            tags: request.tags,
+            content_rating: request.content_rating,
+            tokens: request.tokens,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// Replaces the draft's contents with a later save.
+    pub fn apply(&mut self, request: SaveDraftRequest) {
+        self.name = request.name;
+        self.description = request.description;
+        self.tags = request.tags;
+        self.content_rating = request.content_rating;
+        self.tokens = request.tokens;
+        self.updated_at = Utc::now();
+    }
+
+    /// Returns the request creating the draft's persona.
+    ///
+    /// The name is passed as is; the persona service validates it.
+    #[must_use]
+    pub fn to_create_request(&self) -> CreatePersonaRequest {
+        CreatePersonaRequest {
+            name: self.name.clone(),
+            description: self
+                .description
+                .as_deref()
+                .map(str::trim)
+                .filter(|description| !description.is_empty())
+                .map(str::to_string),
+            tags: self.tags.clone(),
+            content_rating: self.content_rating,
+        }
+    }
+}
+
+/// Request payload for autosaving the create form.
+///
+/// Every field is optional, as the form may be saved while still empty.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SaveDraftRequest {
+    /// Draft to update; a new draft is created when unset
+    #[serde(default)]
+    pub id: Option<String>,
+    /// Name as typed so far
+    #[serde(default)]
+    pub name: String,
+    /// Description as typed so far
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Tags chosen so far
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Audience rating chosen so far
+    #[serde(default)]
+    pub content_rating: ContentRating,
+    /// Tokens added so far, in order
+    #[serde(default)]
+    pub tokens: Vec<GeneratedTokenSelection>,
+}
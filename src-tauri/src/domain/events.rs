@@ -0,0 +1,86 @@
+//! Change Events
+//!
+//! Mutating commands emit these events to every window after their transaction
+//! commits, so secondary windows (e.g., a compose popout) can refresh the data
+//! they display. Payloads name what changed rather than carrying it; listeners
+//! re-fetch what they need.
+//!
+//! Each payload records the label of the window that issued the command, so a
+//! window can skip reloading after its own changes.
+//!
+//! # Event Bus
+//!
+//! Commands do not emit these events to the windows themselves: they publish a
+//! [`DomainEvent`] on the backend event bus (see `infrastructure::event_bus`),
+//! and forwarding to the windows is its subscriber.
+
+use serde::{Deserialize, Serialize};
+
+/// Event name for persona changes (metadata, generation parameters, creation, deletion).
+pub const PERSONA_CHANGED_EVENT: &str = "persona-changed";
+
+/// Event name for changes to a persona's tokens.
+pub const TOKEN_CHANGED_EVENT: &str = "token-changed";
+
+/// What happened to a persona.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    /// The persona was created
+    Created,
+    /// The persona's fields or generation parameters changed
+    Updated,
+    /// The persona was deleted
+    Deleted,
+}
+
+/// Payload of [`PERSONA_CHANGED_EVENT`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PersonaChanged {
+    /// Persona that changed
+    pub persona_id: String,
+    /// What happened to it
+    pub kind: ChangeKind,
+    /// Label of the window that made the change
+    pub source_window: String,
+}
+
+/// Payload of [`TOKEN_CHANGED_EVENT`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenChanged {
+    /// Persona whose tokens changed
+    pub persona_id: String,
+    /// Label of the window that made the change
+    pub source_window: String,
+}
+
+/// A persona or token mutation, published on the backend event bus after
+/// the command's transaction commits.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DomainEvent {
+    /// A persona was created, updated, or deleted
+    PersonaChanged(PersonaChanged),
+    /// A persona's tokens changed
+    TokensChanged(TokenChanged),
+}
+
+impl DomainEvent {
+    /// Returns the ID of the persona the event is about.
+    #[must_use]
+    pub fn persona_id(&self) -> &str {
+        match self {
+            Self::PersonaChanged(e) => &e.persona_id,
+            Self::TokensChanged(e) => &e.persona_id,
+        }
+    }
+
+    /// Returns the name the event is emitted under to the windows.
+    #[must_use]
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::PersonaChanged(_) => PERSONA_CHANGED_EVENT,
+            Self::TokensChanged(_) => TOKEN_CHANGED_EVENT,
+        }
+    }
+}
@@ -14,9 +14,70 @@
 //!
 //! Before importing, the schema version is validated to prevent importing
 //! databases from incompatible versions of the application.
+//!
+//! # JSON Persona Exchange
+//!
+//! Individual personas can also be exchanged as JSON ([`BulkExport`]), which
+//! merges them into the existing library instead of replacing it. Name conflicts
+//! are resolved with an "(Imported)" suffix, and personas whose content hash
+//! matches an existing one can optionally be skipped.
+//!
+//! Exports keep each persona's UUID. When the import option `update_existing`
+//! is set, an entry whose UUID matches a persona in the library updates that
+//! persona in place, so re-importing an edited export of one's own persona
+//! does not create an "(Imported)" copy.
+//!
+//! Tokens may use granularity levels the library does not have. An import
+//! preview lists them ([`ImportPreview`]), and the import requires each to be
+//! mapped to an existing level or created as a custom level
+//! ([`GranularityMappingTarget`]), so no tokens are dropped.
+//!
+//! The format is described by a JSON Schema generated from these types
+//! ([`export_json_schema`]); [`validate_export_json`] checks a document against
+//! it and reports each problem with its JSON path, so third-party tools can
+//! produce compatible files.
+//!
+//! # Section Snippets
+//!
+//! The tokens of a single granularity section (e.g., all Hair tokens) can be
+//! exported as a [`SectionSnippet`] and imported into another persona, either
+//! alongside or in place of that persona's tokens in the same section.
+//!
+//! # Share-Codes
+//!
+//! A single [`PersonaExport`] can be packed into a share-code: its JSON is
+//! deflate-compressed and base64url-encoded behind a `ppm1.` prefix, producing
+//! a string short enough to paste into chat.
 
+use std::collections::HashMap;
+use std::io::{Read, Write};
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine as _;
+use chrono::{DateTime, Utc};
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use super::persona::{GenerationParams, Persona};
+use super::prompt::CompositionDefaults;
+use super::token::{GranularityLevel, TokenPolarity};
+use crate::error::AppError;
+
+/// Current version of the JSON persona exchange format.
+pub const PERSONA_EXPORT_VERSION: u32 = 1;
+
+/// Current version of the section snippet format.
+pub const SECTION_SNIPPET_VERSION: u32 = 1;
+
+/// Prefix identifying share-codes (and their format version).
+pub const SHARE_CODE_PREFIX: &str = "ppm1.";
+
+/// Upper bound on the decompressed size of a share-code payload.
+const MAX_SHARE_CODE_PAYLOAD_BYTES: u64 = 1024 * 1024;
+
 /// Result of a database export operation.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExportResult {
@@ -94,3 +155,329 @@ impl ImportResult {
         }
     }
 }
+
+/// A token as stored in a JSON persona export.
+///
+/// Identifiers and timestamps are omitted; they are regenerated on import.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ExportedToken {
+    /// Granularity level ID (e.g., "hair", "face")
+    pub granularity_id: String,
+    /// Token polarity
+    pub polarity: TokenPolarity,
+    /// Descriptive content
+    pub content: String,
+    /// Weight modifier
+    pub weight: f64,
+    /// Global sort order within the persona
+    pub display_order: i32,
+}
+
+/// A single persona with its generation parameters, composition defaults, and tokens.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PersonaExport {
+    /// Persona metadata as it existed at export time
+    pub persona: Persona,
+    /// Generation parameters (absent if the source had none)
+    #[serde(default)]
+    pub generation_params: Option<GenerationParams>,
+    /// Composition defaults (absent if the source used the global defaults)
+    #[serde(default)]
+    pub composition_defaults: Option<CompositionDefaults>,
+    /// Tokens in display order
+    #[serde(default)]
+    pub tokens: Vec<ExportedToken>,
+}
+
+impl PersonaExport {
+    /// Encodes this persona as a compact share-code.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Serialization` or `AppError::Io` if encoding fails.
+    pub fn to_share_code(&self) -> Result<String, AppError> {
+        let json = serde_json::to_vec(self)?;
+
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::best());
+        encoder.write_all(&json)?;
+        let compressed = encoder.finish()?;
+
+        Ok(format!(
+            "{SHARE_CODE_PREFIX}{}",
+            URL_SAFE_NO_PAD.encode(compressed)
+        ))
+    }
+
+    /// Decodes a share-code produced by [`PersonaExport::to_share_code`].
+    ///
+    /// Surrounding whitespace is ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Validation` if the code is malformed, uses an unknown
+    /// prefix, or expands beyond the maximum payload size.
+    pub fn from_share_code(code: &str) -> Result<Self, AppError> {
+        let invalid = |detail: &str| AppError::Validation(format!("Invalid share-code: {detail}"));
+
+        let payload = code
+            .trim()
+            .strip_prefix(SHARE_CODE_PREFIX)
+            .ok_or_else(|| invalid("unrecognized prefix"))?;
+
+        let compressed = URL_SAFE_NO_PAD
+            .decode(payload)
+            .map_err(|_| invalid("not valid base64"))?;
+
+        let mut json = Vec::new();
+        DeflateDecoder::new(compressed.as_slice())
+            .take(MAX_SHARE_CODE_PAYLOAD_BYTES + 1)
+            .read_to_end(&mut json)
+            .map_err(|_| invalid("corrupted data"))?;
+
+        if json.len() as u64 > MAX_SHARE_CODE_PAYLOAD_BYTES {
+            return Err(invalid("payload too large"));
+        }
+
+        serde_json::from_slice(&json).map_err(|_| invalid("unexpected content"))
+    }
+}
+
+/// A JSON export document containing one or more personas.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct BulkExport {
+    /// Format version (see [`PERSONA_EXPORT_VERSION`])
+    pub version: u32,
+    /// When the export was produced
+    pub exported_at: DateTime<Utc>,
+    /// Exported personas
+    pub personas: Vec<PersonaExport>,
+}
+
+impl BulkExport {
+    /// Creates an export document stamped with the current format version and time.
+    #[must_use]
+    pub fn new(personas: Vec<PersonaExport>) -> Self {
+        Self {
+            version: PERSONA_EXPORT_VERSION,
+            exported_at: Utc::now(),
+            personas,
+        }
+    }
+}
+
+/// A problem found while validating a JSON persona export.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportValidationError {
+    /// JSON Pointer to the offending value (e.g., "/personas/0/tokens/2/weight");
+    /// empty for the document itself
+    pub path: String,
+    /// Human-readable description of the problem
+    pub message: String,
+}
+
+/// Returns the JSON Schema of the JSON persona export format ([`BulkExport`]).
+#[must_use]
+pub fn export_json_schema() -> serde_json::Value {
+    schemars::schema_for!(BulkExport).to_value()
+}
+
+/// Validates a JSON persona export against [`export_json_schema`].
+///
+/// # Returns
+///
+/// Every problem found, empty if the document can be imported. A document
+/// that is not JSON, or whose format version is newer than
+/// [`PERSONA_EXPORT_VERSION`], yields a single error.
+///
+/// # Errors
+///
+/// Returns `AppError::Internal` if the generated schema cannot be compiled.
+pub fn validate_export_json(json: &str) -> Result<Vec<ExportValidationError>, AppError> {
+    let document: serde_json::Value = match serde_json::from_str(json) {
+        Ok(document) => document,
+        Err(e) => {
+            return Ok(vec![ExportValidationError {
+                path: String::new(),
+                message: format!("Not valid JSON: {e}"),
+            }])
+        }
+    };
+
+    let validator = jsonschema::validator_for(&export_json_schema())
+        .map_err(|e| AppError::Internal(format!("Invalid export schema: {e}")))?;
+    let errors: Vec<ExportValidationError> = validator
+        .iter_errors(&document)
+        .map(|e| ExportValidationError {
+            path: e.instance_path.to_string(),
+            message: e.to_string(),
+        })
+        .collect();
+    if !errors.is_empty() {
+        return Ok(errors);
+    }
+
+    let version = document["version"].as_u64().unwrap_or_default();
+    if version > u64::from(PERSONA_EXPORT_VERSION) {
+        return Ok(vec![ExportValidationError {
+            path: "/version".to_string(),
+            message: format!(
+                "Unsupported export version {version} (latest supported is {PERSONA_EXPORT_VERSION})"
+            ),
+        }]);
+    }
+
+    Ok(Vec::new())
+}
+
+/// Options controlling how a JSON persona import is merged into the library.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PersonaImportOptions {
+    /// Skip personas whose content hash matches an existing persona, regardless of name
+    #[serde(default)]
+    pub skip_duplicates: bool,
+    /// Update the existing persona with the same UUID in place instead of
+    /// importing a copy (takes precedence over `skip_duplicates`)
+    #[serde(default)]
+    pub update_existing: bool,
+    /// Target level for each granularity ID the library does not have
+    #[serde(default)]
+    pub granularity_mapping: HashMap<String, GranularityMappingTarget>,
+}
+
+/// Where tokens of an unknown granularity level go on import.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum GranularityMappingTarget {
+    /// Move the tokens to an existing level
+    Existing {
+        /// ID of the existing level (e.g., "hair")
+        granularity_id: String,
+    },
+    /// Create a custom level with the unknown ID
+    CreateNew {
+        /// Display name of the new level (defaults to the ID)
+        #[serde(default)]
+        name: Option<String>,
+    },
+}
+
+/// A granularity level used in an import file but missing from the library.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnknownGranularity {
+    /// The unknown granularity ID
+    pub granularity_id: String,
+    /// Number of tokens using it
+    pub token_count: usize,
+    /// Names of the personas whose tokens use it
+    pub persona_names: Vec<String>,
+}
+
+/// What a JSON persona import would do, computed without changing anything.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportPreview {
+    /// Number of personas in the file
+    pub persona_count: usize,
+    /// Number of tokens in the file
+    pub token_count: usize,
+    /// IDs of personas that already exist (updated in place with `update_existing`)
+    pub existing_ids: Vec<String>,
+    /// Granularity levels that must be mapped before importing
+    pub unknown_granularities: Vec<UnknownGranularity>,
+}
+
+/// Lists the granularity levels used by an export but missing from `levels`.
+///
+/// # Returns
+///
+/// Unknown levels in order of first use.
+#[must_use]
+pub fn find_unknown_granularities(
+    data: &BulkExport,
+    levels: &[GranularityLevel],
+) -> Vec<UnknownGranularity> {
+    let mut unknown: Vec<UnknownGranularity> = Vec::new();
+
+    for entry in &data.personas {
+        for token in &entry.tokens {
+            if levels.iter().any(|level| level.id == token.granularity_id) {
+                continue;
+            }
+
+            let index = unknown
+                .iter()
+                .position(|u| u.granularity_id == token.granularity_id)
+                .unwrap_or_else(|| {
+                    unknown.push(UnknownGranularity {
+                        granularity_id: token.granularity_id.clone(),
+                        token_count: 0,
+                        persona_names: Vec::new(),
+                    });
+                    unknown.len() - 1
+                });
+            let item = &mut unknown[index];
+            item.token_count += 1;
+            if !item.persona_names.contains(&entry.persona.name) {
+                item.persona_names.push(entry.persona.name.clone());
+            }
+        }
+    }
+
+    unknown
+}
+
+/// A persona that was not imported, with the reason why.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkippedPersona {
+    /// Name of the persona in the import file
+    pub name: String,
+    /// Human-readable reason for skipping
+    pub reason: String,
+}
+
+/// Result of a JSON persona import.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PersonaImportResult {
+    /// Personas created by the import
+    pub imported: Vec<Persona>,
+    /// Existing personas updated in place (see `update_existing`)
+    pub updated: Vec<Persona>,
+    /// Personas left out of the import
+    pub skipped: Vec<SkippedPersona>,
+}
+
+/// The tokens of one granularity section of a persona.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SectionSnippet {
+    /// Format version (see [`SECTION_SNIPPET_VERSION`])
+    pub version: u32,
+    /// When the snippet was produced
+    pub exported_at: DateTime<Utc>,
+    /// Name of the persona the section was copied from
+    pub source_persona: String,
+    /// Granularity level ID of the section (e.g., "hair")
+    pub granularity_id: String,
+    /// Tokens of the section in display order
+    pub tokens: Vec<ExportedToken>,
+}
+
+impl SectionSnippet {
+    /// Creates a snippet stamped with the current format version and time.
+    #[must_use]
+    pub fn new(source_persona: String, granularity_id: String, tokens: Vec<ExportedToken>) -> Self {
+        Self {
+            version: SECTION_SNIPPET_VERSION,
+            exported_at: Utc::now(),
+            source_persona,
+            granularity_id,
+            tokens,
+        }
+    }
+}
+
+/// Options controlling how a section snippet is imported into a persona.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SectionImportOptions {
+    /// Delete the persona's existing tokens in the section before importing
+    #[serde(default)]
+    pub replace: bool,
+}
@@ -0,0 +1,244 @@
+//! Face Consistency
+//!
+//! Groups a persona's gallery images by face similarity, to tell when
+//! generations of a character drift away from its usual face.
+//!
+//! Face embeddings come from an optional local program set in the app
+//! settings (see `infrastructure::face_embedder`), such as an `InsightFace`
+//! script; no image leaves the machine. Each gallery image's embedding is
+//! cached, so only new images are embedded on the next analysis.
+//!
+//! # Clustering
+//!
+//! Images are taken in the order they were added. Each joins the cluster whose
+//! mean face is most similar to its own (cosine similarity), if that similarity
+//! reaches the threshold; otherwise it starts a new cluster. The largest
+//! cluster is taken as the persona's face, and the persona is drifting when
+//! fewer than [`DRIFT_SHARE`] of its analyzed images belong to it.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::gallery::GeneratedImage;
+use crate::error::AppError;
+
+/// Cosine similarity at which two faces count as the same person, suited to
+/// `ArcFace`-style embeddings.
+pub const DEFAULT_FACE_SIMILARITY: f32 = 0.5;
+
+/// Share of analyzed images the largest cluster must hold for the persona not
+/// to be drifting.
+pub const DRIFT_SHARE: f64 = 0.8;
+
+/// A group of images showing the same face.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FaceCluster {
+    /// Gallery images in the cluster, oldest first
+    pub image_ids: Vec<String>,
+    /// Mean similarity of the cluster's faces to its mean face (0–1)
+    pub cohesion: f32,
+}
+
+/// How one image's face compares to the persona's face.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageFaceSimilarity {
+    /// UUID of the gallery image
+    pub image_id: String,
+    /// When the image was added to the gallery
+    pub created_at: DateTime<Utc>,
+    /// Index of the image's cluster in the report's clusters
+    pub cluster: usize,
+    /// Cosine similarity to the mean face of the largest cluster
+    pub similarity: f32,
+}
+
+/// Result of grouping a persona's gallery images by face.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GalleryConsistencyReport {
+    /// UUID of the persona
+    pub persona_id: String,
+    /// Similarity at which faces were grouped together
+    pub threshold: f32,
+    /// Clusters, largest first; the first is taken as the persona's face
+    pub clusters: Vec<FaceCluster>,
+    /// Analyzed images, oldest first
+    pub images: Vec<ImageFaceSimilarity>,
+    /// Share of analyzed images in the largest cluster (1 when there are none)
+    pub main_share: f64,
+    /// Whether the largest cluster holds less than [`DRIFT_SHARE`] of the
+    /// analyzed images
+    pub drifting: bool,
+    /// Images in which no face was found
+    pub without_face: Vec<String>,
+    /// Images without an embedding, because no face embedder is set, the
+    /// embedder failed on them, or their embedding has another dimension
+    pub not_analyzed: Vec<String>,
+}
+
+/// Validates a face similarity threshold.
+///
+/// # Errors
+///
+/// Returns `AppError::Validation` if the threshold is outside 0–1.
+pub fn validate_face_similarity(threshold: f32) -> Result<f32, AppError> {
+    if (0.0..=1.0).contains(&threshold) {
+        Ok(threshold)
+    } else {
+        Err(AppError::Validation(
+            "Face similarity threshold must be between 0 and 1".to_string(),
+        ))
+    }
+}
+
+/// A cluster being built, with the sum of its normalized embeddings.
+struct ClusterBuilder {
+    members: Vec<usize>,
+    sum: Vec<f32>,
+}
+
+impl ClusterBuilder {
+    fn centroid(&self) -> Vec<f32> {
+        normalize(&self.sum)
+    }
+}
+
+impl GalleryConsistencyReport {
+    /// Groups a persona's gallery images by face.
+    ///
+    /// # Arguments
+    ///
+    /// * `persona_id` - UUID of the persona
+    /// * `images` - The persona's gallery images, in any order
+    /// * `embeddings` - Face embedding per image ID; empty for images without
+    ///   a face, missing for images not embedded
+    /// * `threshold` - Similarity at which faces are grouped together (0–1)
+    #[must_use]
+    pub fn analyze(
+        persona_id: String,
+        images: &[GeneratedImage],
+        embeddings: &HashMap<String, Vec<f32>>,
+        threshold: f32,
+    ) -> Self {
+        let mut images: Vec<&GeneratedImage> = images.iter().collect();
+        images.sort_by_key(|image| image.created_at);
+
+        // Embeddings from an earlier embedder may have another dimension
+        let mut dimensions: HashMap<usize, usize> = HashMap::new();
+        for embedding in embeddings.values().filter(|e| !e.is_empty()) {
+            *dimensions.entry(embedding.len()).or_default() += 1;
+        }
+        let dimension = dimensions
+            .into_iter()
+            .max_by_key(|&(len, count)| (count, len))
+            .map(|(len, _)| len);
+
+        let mut faces: Vec<(&GeneratedImage, Vec<f32>)> = Vec::new();
+        let mut without_face = Vec::new();
+        let mut not_analyzed = Vec::new();
+        for image in images {
+            match embeddings.get(&image.id) {
+                Some(embedding) if embedding.is_empty() => without_face.push(image.id.clone()),
+                Some(embedding) if Some(embedding.len()) == dimension => {
+                    faces.push((image, normalize(embedding)));
+                }
+                _ => not_analyzed.push(image.id.clone()),
+            }
+        }
+
+        let mut builders: Vec<ClusterBuilder> = Vec::new();
+        for (index, (_, face)) in faces.iter().enumerate() {
+            let best = builders
+                .iter()
+                .enumerate()
+                .map(|(i, cluster)| (i, cosine(face, &cluster.centroid())))
+                .filter(|&(_, similarity)| similarity >= threshold)
+                .max_by(|a, b| a.1.total_cmp(&b.1));
+            match best {
+                Some((i, _)) => {
+                    let cluster = &mut builders[i];
+                    cluster.members.push(index);
+                    for (sum, value) in cluster.sum.iter_mut().zip(face) {
+                        *sum += value;
+                    }
+                }
+                None => builders.push(ClusterBuilder {
+                    members: vec![index],
+                    sum: face.clone(),
+                }),
+            }
+        }
+        // Largest first; among equals, the one started first
+        builders.sort_by_key(|cluster| std::cmp::Reverse(cluster.members.len()));
+
+        let mut cluster_of = vec![0; faces.len()];
+        let clusters: Vec<FaceCluster> = builders
+            .iter()
+            .enumerate()
+            .map(|(i, cluster)| {
+                let centroid = cluster.centroid();
+                let total: f32 = cluster
+                    .members
+                    .iter()
+                    .map(|&member| {
+                        cluster_of[member] = i;
+                        cosine(&faces[member].1, &centroid)
+                    })
+                    .sum();
+                FaceCluster {
+                    image_ids: cluster
+                        .members
+                        .iter()
+                        .map(|&m| faces[m].0.id.clone())
+                        .collect(),
+                    cohesion: total / cluster.members.len() as f32,
+                }
+            })
+            .collect();
+
+        let main_centroid = builders.first().map(ClusterBuilder::centroid);
+        let images = faces
+            .iter()
+            .zip(cluster_of)
+            .map(|((image, face), cluster)| ImageFaceSimilarity {
+                image_id: image.id.clone(),
+                created_at: image.created_at,
+                cluster,
+                similarity: main_centroid
+                    .as_ref()
+                    .map_or(1.0, |centroid| cosine(face, centroid)),
+            })
+            .collect();
+
+        let main_share = clusters
+            .first()
+            .map_or(1.0, |main| main.image_ids.len() as f64 / faces.len() as f64);
+
+        Self {
+            persona_id,
+            threshold,
+            clusters,
+            images,
+            main_share,
+            drifting: main_share < DRIFT_SHARE,
+            without_face,
+            not_analyzed,
+        }
+    }
+}
+
+/// Scales a vector to unit length; a zero vector is returned unchanged.
+fn normalize(vector: &[f32]) -> Vec<f32> {
+    let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        vector.iter().map(|v| v / norm).collect()
+    } else {
+        vector.to_vec()
+    }
+}
+
+/// Cosine similarity of two unit vectors.
+fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
@@ -0,0 +1,183 @@
+//! Bulk Find and Replace
+//!
+//! Replaces text in token content across a persona, a smart collection, or
+//! the whole library. A dry run returns the same preview without writing, so
+//! the user can review every change before applying it.
+//!
+//! # Matching
+//!
+//! The find text is matched literally unless `regex` is set; regex
+//! replacements may refer to capture groups (`$1`, `${name}`). Matching is
+//! case-sensitive. Replaced content is trimmed.
+//!
+//! # Rejected Replacements
+//!
+//! A replacement is rejected if it would leave the token empty, duplicate
+//! another token of the persona (same level, polarity, and content), or
+//! contain a banned term. Rejected replacements are reported with the reason;
+//! if any is rejected, nothing is applied.
+
+use regex::{NoExpand, Regex};
+use serde::{Deserialize, Serialize};
+
+use super::banned_term::{find_banned_term, BannedTerm};
+use super::persona::Persona;
+use super::search::compile_regex;
+use super::token::Token;
+use crate::error::AppError;
+
+/// Tokens a find-and-replace operates on.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ReplaceScope {
+    /// One persona's tokens
+    Persona {
+        /// The persona's UUID
+        persona_id: String,
+    },
+    /// Tokens of every persona in a smart collection
+    Collection {
+        /// The smart collection's UUID
+        collection_id: String,
+    },
+    /// Tokens of every persona
+    Library,
+}
+
+/// A token whose content the replacement changes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenReplacement {
+    /// The token's UUID
+    pub token_id: String,
+    /// Persona the token belongs to
+    pub persona_id: String,
+    /// Persona name, for display
+    pub persona_name: String,
+    /// Current content
+    pub before: String,
+    /// Content after the replacement
+    pub after: String,
+    /// Why the replacement cannot be applied, if it cannot
+    pub rejected: Option<String>,
+}
+
+/// Result of a find-and-replace.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FindReplaceResult {
+    /// Every changed token, in persona then display order
+    pub replacements: Vec<TokenReplacement>,
+    /// Whether the replacements were written (false for dry runs and when
+    /// any replacement is rejected)
+    pub applied: bool,
+}
+
+/// Compiled find pattern with its replacement.
+#[derive(Debug)]
+pub struct TokenReplacer {
+    pattern: Regex,
+    replace: String,
+    regex: bool,
+}
+
+impl TokenReplacer {
+    /// Compiles a find pattern.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Validation` if `find` is empty or, with `regex`,
+    /// not a valid regular expression.
+    pub fn new(find: &str, replace: &str, regex: bool) -> Result<Self, AppError> {
+        if find.is_empty() {
+            return Err(AppError::Validation("Find text is required".to_string()));
+        }
+
+        let pattern = if regex {
+            compile_regex(find)?
+        } else {
+            Regex::new(&regex::escape(find))
+                .map_err(|e| AppError::Internal(format!("Failed to compile find text: {e}")))?
+        };
+
+        Ok(Self {
+            pattern,
+            replace: replace.to_string(),
+            regex,
+        })
+    }
+
+    /// Returns the token's content after the replacement, or `None` if the
+    /// token does not match or its content would not change.
+    #[must_use]
+    pub fn apply(&self, token: &Token) -> Option<String> {
+        if !self.pattern.is_match(&token.content) {
+            return None;
+        }
+
+        let replaced = if self.regex {
+            self.pattern
+                .replace_all(&token.content, self.replace.as_str())
+        } else {
+            self.pattern
+                .replace_all(&token.content, NoExpand(&self.replace))
+        };
+        let after = replaced.trim();
+
+        (after != token.content).then(|| after.to_string())
+    }
+
+    /// Previews the replacement across one persona's tokens.
+    ///
+    /// # Arguments
+    ///
+    /// * `persona` - The persona the tokens belong to
+    /// * `tokens` - All of the persona's tokens, in display order
+    /// * `banned_terms` - Terms replaced content must not contain
+    ///
+    /// # Returns
+    ///
+    /// One entry per token whose content changes, with the reason if the
+    /// change is rejected.
+    #[must_use]
+    pub fn plan(
+        &self,
+        persona: &Persona,
+        tokens: &[Token],
+        banned_terms: &[BannedTerm],
+    ) -> Vec<TokenReplacement> {
+        // Content of every token once all replacements are applied
+        let finals: Vec<(&Token, Option<String>)> = tokens
+            .iter()
+            .map(|token| (token, self.apply(token)))
+            .collect();
+
+        finals
+            .iter()
+            .filter_map(|(token, after)| after.as_ref().map(|after| (*token, after)))
+            .map(|(token, after)| {
+                let duplicate = finals.iter().any(|(other, other_after)| {
+                    other.id != token.id
+                        && other.granularity_id == token.granularity_id
+                        && other.polarity == token.polarity
+                        && other_after.as_deref().unwrap_or(&other.content) == after
+                });
+                let rejected = if after.is_empty() {
+                    Some("The token would be empty".to_string())
+                } else if duplicate {
+                    Some(format!("Another token of the level is already '{after}'"))
+                } else {
+                    find_banned_term(after, banned_terms)
+                        .map(|term| format!("Contains the banned term '{term}'"))
+                };
+
+                TokenReplacement {
+                    token_id: token.id.clone(),
+                    persona_id: persona.id.clone(),
+                    persona_name: persona.name.clone(),
+                    before: token.content.clone(),
+                    after: after.clone(),
+                    rejected,
+                }
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,193 @@
+//! Result Gallery
+//!
+//! Images generated from a persona's prompt, kept with what produced them so a
+//! good result can be reproduced: the prompt hash, the seed, and a snapshot of
+//! the generation parameters.
+//!
+//! Images are attached by path; the files stay where the image generator wrote
+//! them. Removing an image from the gallery (including a purge by age) never
+//! deletes the file.
+//!
+//! # Reproduction Bundles
+//!
+//! Attaching an image also records the exact prompts and a snapshot of the
+//! persona as it was. A [`ReproductionBundle`] packs these with the seed and
+//! parameters into one JSON document, so the image can be reproduced long
+//! after the persona has been edited.
+
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use super::export::PersonaExport;
+use super::persona::GenerationParams;
+use crate::error::AppError;
+
+/// An image generated for a persona.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeneratedImage {
+    /// Unique identifier (UUID v4)
+    pub id: String,
+    /// UUID of the persona the image was generated for
+    pub persona_id: String,
+    /// Absolute path of the image file
+    pub path: String,
+    /// Hash of the prompt the image was generated from (see [`prompt_hash`])
+    pub prompt_hash: String,
+    /// Positive prompt as sent to the image generator, if recorded
+    pub positive_prompt: Option<String>,
+    /// Negative prompt as sent to the image generator, if recorded
+    pub negative_prompt: Option<String>,
+    /// Seed the image was generated with, if known
+    pub seed: Option<i64>,
+    /// Generation parameters at the time, if known
+    pub params: Option<GenerationParams>,
+    /// Whether the user marked the image as a favorite
+    pub starred: bool,
+    /// When the image was added to the gallery
+    pub created_at: DateTime<Utc>,
+}
+
+/// Request to add an image to a persona's gallery.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttachImageRequest {
+    /// UUID of the persona the image belongs to
+    pub persona_id: String,
+    /// Absolute path of the image file
+    pub path: String,
+    /// Positive prompt the image was generated from
+    pub positive_prompt: String,
+    /// Negative prompt the image was generated from
+    #[serde(default)]
+    pub negative_prompt: String,
+    /// Seed actually used; defaults to the parameters' seed unless it is random
+    #[serde(default)]
+    pub seed: Option<i64>,
+    /// Generation parameters used; defaults to the persona's current ones
+    #[serde(default)]
+    pub params: Option<GenerationParams>,
+}
+
+impl GeneratedImage {
+    /// Creates a gallery entry from an attach request.
+    ///
+    /// The seed falls back to the parameters' seed, unless that is -1 (random).
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Validation` if the path is empty or not absolute.
+    pub fn from_request(request: AttachImageRequest) -> Result<Self, AppError> {
+        let path = request.path.trim();
+        if path.is_empty() || !Path::new(path).is_absolute() {
+            return Err(AppError::Validation(format!(
+                "Invalid image path '{path}': expected an absolute file path"
+            )));
+        }
+
+        let seed = request.seed.or_else(|| {
+            request
+                .params
+                .as_ref()
+                .map(|params| params.seed)
+                .filter(|&seed| seed != -1)
+        });
+
+        Ok(Self {
+            id: Uuid::new_v4().to_string(),
+            persona_id: request.persona_id,
+            path: path.to_string(),
+            prompt_hash: prompt_hash(&request.positive_prompt, &request.negative_prompt),
+            positive_prompt: Some(request.positive_prompt),
+            negative_prompt: Some(request.negative_prompt),
+            seed,
+            params: request.params,
+            starred: false,
+            created_at: Utc::now(),
+        })
+    }
+}
+
+/// Computes the hash identifying a prompt, to group images generated from the
+/// same prompt.
+///
+/// Covers the trimmed positive and negative prompts; whitespace around them
+/// does not change the result.
+///
+/// # Returns
+///
+/// Lowercase hexadecimal SHA-256 digest.
+#[must_use]
+pub fn prompt_hash(positive_prompt: &str, negative_prompt: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(positive_prompt.trim().as_bytes());
+    hasher.update(b"\n");
+    hasher.update(negative_prompt.trim().as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// Version of the reproduction bundle format.
+pub const REPRODUCTION_BUNDLE_VERSION: u32 = 1;
+
+/// Everything needed to reproduce a gallery image, as one JSON document.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReproductionBundle {
+    /// Format version (see [`REPRODUCTION_BUNDLE_VERSION`])
+    pub version: u32,
+    /// When the bundle was produced
+    pub exported_at: DateTime<Utc>,
+    /// UUID of the gallery entry
+    pub image_id: String,
+    /// Path of the image file when it was attached
+    pub image_path: String,
+    /// When the image was added to the gallery
+    pub generated_at: DateTime<Utc>,
+    /// Positive prompt as sent to the image generator, if recorded
+    pub positive_prompt: Option<String>,
+    /// Negative prompt as sent to the image generator, if recorded
+    pub negative_prompt: Option<String>,
+    /// Hash of the prompts (see [`prompt_hash`])
+    pub prompt_hash: String,
+    /// Image model the prompt was written for, if known
+    pub model_id: Option<String>,
+    /// Seed the image was generated with, if known
+    pub seed: Option<i64>,
+    /// Generation parameters at the time, if known
+    pub params: Option<GenerationParams>,
+    /// The persona as it was when the image was attached, with inherited
+    /// tokens and parameters resolved; absent if no snapshot was recorded
+    pub persona: Option<PersonaExport>,
+}
+
+impl ReproductionBundle {
+    /// Creates a bundle for a gallery image, stamped with the current format
+    /// version and time.
+    ///
+    /// The model comes from the image's parameters, or else from the
+    /// snapshot's.
+    #[must_use]
+    pub fn new(image: GeneratedImage, persona: Option<PersonaExport>) -> Self {
+        let model_id = image
+            .params
+            .as_ref()
+            .or_else(|| persona.as_ref()?.generation_params.as_ref())
+            .map(|params| params.model_id.clone());
+
+        Self {
+            version: REPRODUCTION_BUNDLE_VERSION,
+            exported_at: Utc::now(),
+            image_id: image.id,
+            image_path: image.path,
+            generated_at: image.created_at,
+            positive_prompt: image.positive_prompt,
+            negative_prompt: image.negative_prompt,
+            prompt_hash: image.prompt_hash,
+            model_id,
+            seed: image.seed,
+            params: image.params,
+            persona,
+        }
+    }
+}
@@ -0,0 +1,180 @@
+//! Localization
+//!
+//! Message catalogs for the display text the backend sends to the frontend:
+//! error messages, the names of the built-in granularity levels, and warnings
+//! about generation parameters. Each message has a stable code (e.g.,
+//! "`error.not_found`") so the frontend can recognize it without parsing the
+//! text, and a template per locale with `{name}` placeholders.
+//!
+//! Catalogs are embedded in the binary from `locales/<code>.json` and parsed
+//! once on first use. English is complete; a code missing from another locale
+//! falls back to English.
+//!
+//! Details inserted into a message (persona names, IDs, `SQLite` errors) are
+//! not translated.
+
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+
+/// Catalogs parsed from the embedded JSON on first use, in [`Locale::ALL`] order.
+static CATALOGS: OnceLock<Vec<BTreeMap<String, String>>> = OnceLock::new();
+
+/// A language the backend's display text is available in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    /// English
+    #[default]
+    En,
+    /// German
+    De,
+    /// French
+    Fr,
+    /// Spanish
+    Es,
+}
+
+impl Locale {
+    /// Every supported locale.
+    pub const ALL: [Self; 4] = [Self::En, Self::De, Self::Fr, Self::Es];
+
+    /// Returns the language code (e.g., "de").
+    #[must_use]
+    pub const fn code(&self) -> &'static str {
+        match self {
+            Self::En => "en",
+            Self::De => "de",
+            Self::Fr => "fr",
+            Self::Es => "es",
+        }
+    }
+
+    /// Returns the locale's position in [`Self::ALL`].
+    #[must_use]
+    pub const fn index(self) -> usize {
+        self as usize
+    }
+
+    /// Returns the locale at a position in [`Self::ALL`], English if out of range.
+    #[must_use]
+    pub fn from_index(index: usize) -> Self {
+        Self::ALL.get(index).copied().unwrap_or_default()
+    }
+
+    /// Returns the embedded catalog JSON.
+    const fn catalog_json(self) -> &'static str {
+        match self {
+            Self::En => include_str!("locales/en.json"),
+            Self::De => include_str!("locales/de.json"),
+            Self::Fr => include_str!("locales/fr.json"),
+            Self::Es => include_str!("locales/es.json"),
+        }
+    }
+}
+
+/// All messages of a locale, for the frontend to look codes up itself.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageCatalog {
+    /// Locale of the messages
+    pub locale: Locale,
+    /// Message templates by code, English where the locale has none
+    pub messages: BTreeMap<String, String>,
+}
+
+impl MessageCatalog {
+    /// Returns the catalog of a locale, completed with English.
+    #[must_use]
+    pub fn for_locale(locale: Locale) -> Self {
+        let mut messages = catalog(Locale::En).clone();
+        messages.extend(
+            catalog(locale)
+                .iter()
+                .map(|(code, template)| (code.clone(), template.clone())),
+        );
+        Self { locale, messages }
+    }
+}
+
+/// Returns the parsed catalog of a locale.
+///
+/// # Panics
+///
+/// Panics if an embedded catalog is malformed, which is a build defect.
+fn catalog(locale: Locale) -> &'static BTreeMap<String, String> {
+    &CATALOGS.get_or_init(|| {
+        Locale::ALL
+            .iter()
+            .map(|locale| {
+                serde_json::from_str(locale.catalog_json())
+                    .expect("embedded message catalogs must be valid JSON")
+            })
+            .collect()
+    })[locale.index()]
+}
+
+/// Renders a message in a locale.
+///
+/// Falls back to English if the locale lacks the code, and to the code itself
+/// if English does too. Placeholders without a matching argument are kept.
+///
+/// # Arguments
+///
+/// * `locale` - Language to render in
+/// * `code` - Stable message code (e.g., "`error.not_found`")
+/// * `args` - Values for the template's `{name}` placeholders
+#[must_use]
+pub fn message(locale: Locale, code: &str, args: &[(&str, &dyn Display)]) -> String {
+    let Some(template) = catalog(locale)
+        .get(code)
+        .or_else(|| catalog(Locale::En).get(code))
+    else {
+        return code.to_string();
+    };
+
+    args.iter().fold(template.clone(), |text, (name, value)| {
+        text.replace(&format!("{{{name}}}"), &value.to_string())
+    })
+}
+
+/// A message code with its arguments, rendered on demand.
+///
+/// Useful where the locale is not known yet when the message is produced.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message {
+    /// Stable message code
+    pub code: &'static str,
+    /// Placeholder values, already formatted
+    pub args: Vec<(&'static str, String)>,
+}
+
+impl Message {
+    /// Creates a message without arguments.
+    #[must_use]
+    pub const fn new(code: &'static str) -> Self {
+        Self {
+            code,
+            args: Vec::new(),
+        }
+    }
+
+    /// Adds a placeholder value.
+    #[must_use]
+    pub fn arg(mut self, name: &'static str, value: impl Display) -> Self {
+        self.args.push((name, value.to_string()));
+        self
+    }
+
+    /// Renders the message in a locale.
+    #[must_use]
+    pub fn render(&self, locale: Locale) -> String {
+        let args: Vec<(&str, &dyn Display)> = self
+            .args
+            .iter()
+            .map(|(name, value)| (*name, value as &dyn Display))
+            .collect();
+        message(locale, self.code, &args)
+    }
+}
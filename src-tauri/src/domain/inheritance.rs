@@ -0,0 +1,170 @@
+//! Persona Inheritance
+//!
+//! A variant persona can name a base persona and inherit from it, so shared
+//! settings are edited once on the base.
+//!
+//! # Generation Parameters
+//!
+//! A variant inherits every generation parameter except those it overrides.
+//! Overrides are stored on the variant as a list of [`ParamField`]s; the
+//! variant's own parameter row holds their values. Saving a variant's
+//! parameters overrides exactly the fields that differ from the base.
+//!
+//! # Tokens
+//!
+//! A variant inherits the tokens of the granularity levels it selects (e.g.,
+//! Style). A selected level the variant has tokens of itself is overridden:
+//! only the variant's own tokens are used there. Inherited tokens keep the
+//! base persona's `persona_id`, so they can be told apart.
+//!
+//! # Chains
+//!
+//! A base can itself be a variant; resolution follows the chain up to
+//! [`MAX_INHERITANCE_DEPTH`] levels. Links that would form a cycle are
+//! rejected.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::persona::GenerationParams;
+use super::token::Token;
+
+/// Longest chain of base personas followed when resolving a variant.
+pub const MAX_INHERITANCE_DEPTH: usize = 8;
+
+/// A generation parameter a variant can override.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ParamField {
+    /// Image model
+    ModelId,
+    /// Random seed
+    Seed,
+    /// Diffusion steps
+    Steps,
+    /// CFG scale
+    CfgScale,
+    /// Sampler
+    Sampler,
+    /// Scheduler
+    Scheduler,
+    /// Width and height, overridden together
+    Resolution,
+    /// `ControlNet` and IP-Adapter units, overridden as a whole
+    Conditioning,
+}
+
+impl ParamField {
+    /// Every parameter field, in form order.
+    pub const ALL: [Self; 8] = [
+        Self::ModelId,
+        Self::Seed,
+        Self::Steps,
+        Self::CfgScale,
+        Self::Sampler,
+        Self::Scheduler,
+        Self::Resolution,
+        Self::Conditioning,
+    ];
+
+    /// Returns true if the field has different values in `a` and `b`.
+    #[must_use]
+    pub fn differs(self, a: &GenerationParams, b: &GenerationParams) -> bool {
+        match self {
+            Self::ModelId => a.model_id != b.model_id,
+            Self::Seed => a.seed != b.seed,
+            Self::Steps => a.steps != b.steps,
+            Self::CfgScale => (a.cfg_scale - b.cfg_scale).abs() > f32::EPSILON,
+            Self::Sampler => a.sampler != b.sampler,
+            Self::Scheduler => a.scheduler != b.scheduler,
+            Self::Resolution => (a.width, a.height) != (b.width, b.height),
+            Self::Conditioning => a.conditioning != b.conditioning,
+        }
+    }
+
+    /// Copies the field's value from `from` into `to`.
+    fn copy(self, from: &GenerationParams, to: &mut GenerationParams) {
+        match self {
+            Self::ModelId => to.model_id.clone_from(&from.model_id),
+            Self::Seed => to.seed = from.seed,
+            Self::Steps => to.steps = from.steps,
+            Self::CfgScale => to.cfg_scale = from.cfg_scale,
+            Self::Sampler => to.sampler.clone_from(&from.sampler),
+            Self::Scheduler => to.scheduler.clone_from(&from.scheduler),
+            Self::Resolution => {
+                to.width = from.width;
+                to.height = from.height;
+            }
+            Self::Conditioning => to.conditioning.clone_from(&from.conditioning),
+        }
+    }
+}
+
+/// Lists the fields a variant's parameters override, given its base's
+/// resolved parameters.
+#[must_use]
+pub fn overridden_fields(local: &GenerationParams, base: &GenerationParams) -> Vec<ParamField> {
+    ParamField::ALL
+        .into_iter()
+        .filter(|field| field.differs(local, base))
+        .collect()
+}
+
+/// Resolves a variant's generation parameters.
+///
+/// # Arguments
+///
+/// * `local` - The variant's stored parameters
+/// * `base` - The base persona's resolved parameters
+/// * `overrides` - Fields taken from `local`; the rest come from `base`
+#[must_use]
+pub fn resolve_params(
+    local: &GenerationParams,
+    base: &GenerationParams,
+    overrides: &[ParamField],
+) -> GenerationParams {
+    let mut resolved = GenerationParams {
+        persona_id: local.persona_id.clone(),
+        ..base.clone()
+    };
+    for field in overrides {
+        field.copy(local, &mut resolved);
+    }
+    resolved
+}
+
+/// Resolves a variant's tokens.
+///
+/// # Arguments
+///
+/// * `local` - The variant's own tokens, in display order
+/// * `base` - The base persona's resolved tokens, in display order
+/// * `inherited` - Granularity level IDs inherited from the base
+///
+/// # Returns
+///
+/// The variant's own tokens followed by the base's tokens of every inherited
+/// level the variant has no tokens of. Inherited tokens are renumbered to
+/// follow the variant's own in display order.
+#[must_use]
+pub fn resolve_tokens(local: Vec<Token>, base: Vec<Token>, inherited: &[String]) -> Vec<Token> {
+    let next_order = local.iter().map(|t| t.display_order + 1).max().unwrap_or(0);
+    let inherited_tokens: Vec<Token> = base
+        .into_iter()
+        .filter(|token| {
+            inherited.contains(&token.granularity_id)
+                && !local
+                    .iter()
+                    .any(|own| own.granularity_id == token.granularity_id)
+        })
+        .zip(next_order..)
+        .map(|(token, display_order)| Token {
+            display_order,
+            ..token
+        })
+        .collect();
+
+    let mut resolved = local;
+    resolved.extend(inherited_tokens);
+    resolved
+}
@@ -0,0 +1,493 @@
+//! Prompt Quality Linting
+//!
+//! This module implements a deterministic linter for composed prompts. It works
+//! on prompt text, so it applies equally to composed persona prompts, prompts
+//! with ad-hoc tokens, and prompts edited by hand.
+//!
+//! # Checks
+//!
+//! - **Duplicate concepts**: The same concept appears more than once in a prompt
+//! - **Conflicting weights**: The same concept appears with different weights
+//! - **Prompt length**: The prompt exceeds the image model's usable token limit
+//! - **Negative in positive**: A positive token is also excluded in the negative
+//!   prompt, or is typical negative-prompt vocabulary (e.g., "worst quality")
+//! - **Separators**: Leading/trailing separators and empty segments
+//! - **Weight syntax**: Unbalanced brackets and malformed `(token:weight)` syntax
+//! - **Unsupported negative**: A negative prompt is given for a model that
+//!   ignores it (e.g., FLUX or Turbo models)
+//!
+//! # Concept Matching
+//!
+//! Segments are compared by concept: emphasis brackets and weights are removed,
+//! underscores become spaces, and text is lowercased with whitespace collapsed,
+//! so `(Red_Hair:1.2)` and `red hair` are the same concept. With token aliases
+//! (see `domain::alias`), a variant is the same concept as its phrasing, so
+//! `ginger hair` repeats `red hair` when one is an alias of the other.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::alias::AliasMap;
+
+/// Multiplier applied per level of `( )` emphasis (and divided per `[ ]` level).
+const EMPHASIS_FACTOR: f64 = 1.1;
+
+/// Weights closer than this are considered equal (prompts round to one decimal).
+const WEIGHT_TOLERANCE: f64 = 0.01;
+
+/// Vocabulary that almost always belongs in a negative prompt.
+const NEGATIVE_VOCABULARY: &[&str] = &[
+    "worst quality",
+    "low quality",
+    "normal quality",
+    "lowres",
+    "bad anatomy",
+    "bad hands",
+    "bad proportions",
+    "extra fingers",
+    "extra limbs",
+    "missing fingers",
+    "fused fingers",
+    "deformed",
+    "disfigured",
+    "mutated",
+    "mutation",
+    "jpeg artifacts",
+    "watermark",
+    "signature",
+    "username",
+    "blurry",
+    "ugly",
+];
+
+/// Kind of problem reported by the linter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LintCode {
+    /// Same concept repeated with the same weight
+    DuplicateConcept,
+    /// Same concept repeated with different weights
+    ConflictingWeights,
+    /// Prompt exceeds the model's usable token limit
+    PromptTooLong,
+    /// Positive token that belongs in the negative prompt
+    NegativeInPositive,
+    /// Separator at the start or end of the prompt
+    TrailingSeparator,
+    /// Empty segment between two separators
+    EmptySegment,
+    /// Unbalanced brackets or invalid weight syntax
+    MalformedWeight,
+    /// Negative prompt for a model that ignores negative prompts
+    UnsupportedNegativePrompt,
+}
+
+/// How serious a lint issue is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LintSeverity {
+    /// Cosmetic; the prompt works as intended
+    Info,
+    /// Likely to weaken or change the result
+    Warning,
+    /// The prompt will not be interpreted as written
+    Error,
+}
+
+/// Which prompt an issue was found in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PromptTarget {
+    /// The positive prompt
+    Positive,
+    /// The negative prompt
+    Negative,
+}
+
+/// A single problem found by the linter.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LintIssue {
+    /// Kind of problem
+    pub code: LintCode,
+    /// How serious the problem is
+    pub severity: LintSeverity,
+    /// Prompt the problem was found in
+    pub target: PromptTarget,
+    /// Human-readable explanation
+    pub message: String,
+    /// The offending prompt segment, if the issue concerns one
+    pub segment: Option<String>,
+}
+
+/// Measured length of a prompt against the target model's limit.
+#[derive(Debug, Clone)]
+pub struct PromptLength {
+    /// Encoder tokens used by the prompt
+    pub token_count: usize,
+    /// Usable encoder tokens for the model
+    pub usable_tokens: usize,
+    /// Human-readable model name for messages (e.g., "Stable Diffusion XL")
+    pub model_name: String,
+}
+
+/// Input to the linter.
+#[derive(Debug, Clone, Default)]
+pub struct LintInput<'a> {
+    /// Positive prompt text
+    pub positive_prompt: &'a str,
+    /// Negative prompt text
+    pub negative_prompt: &'a str,
+    /// Positive prompt length, if measured
+    pub positive_length: Option<PromptLength>,
+    /// Negative prompt length, if measured
+    pub negative_length: Option<PromptLength>,
+    /// Display name of the image model if it ignores negative prompts
+    pub negative_ignored_by: Option<String>,
+    /// The user's token aliases, if concepts should be compared by phrasing
+    pub aliases: Option<&'a AliasMap>,
+}
+
+/// A prompt segment with its concept and effective weight.
+#[derive(Debug, Clone)]
+struct Segment<'a> {
+    /// Segment text as written
+    raw: &'a str,
+    /// Normalized concept for comparison
+    concept: String,
+    /// Effective weight, or `None` if the syntax is malformed
+    weight: Option<f64>,
+}
+
+/// Stateless prompt linter.
+pub struct PromptLinter;
+
+impl PromptLinter {
+    /// Lints a positive/negative prompt pair.
+    ///
+    /// Issues are returned positive prompt first, in the order the checks are
+    /// listed in the module documentation.
+    #[must_use]
+    pub fn lint(input: &LintInput<'_>) -> Vec<LintIssue> {
+        let mut positive = split_segments(input.positive_prompt);
+        let mut negative = split_segments(input.negative_prompt);
+        if let Some(aliases) = input.aliases {
+            for segment in positive.iter_mut().chain(negative.iter_mut()) {
+                if let Some(canonical) = aliases.canonical(&segment.concept) {
+                    segment.concept = normalize_concept(canonical);
+                }
+            }
+        }
+        let mut issues = Vec::new();
+
+        for (target, text, segments, length) in [
+            (
+                PromptTarget::Positive,
+                input.positive_prompt,
+                &positive,
+                input.positive_length.as_ref(),
+            ),
+            (
+                PromptTarget::Negative,
+                input.negative_prompt,
+                &negative,
+                input.negative_length.as_ref(),
+            ),
+        ] {
+            check_repeated_concepts(target, segments, &mut issues);
+            if let Some(length) = length {
+                check_length(target, length, &mut issues);
+            }
+            if target == PromptTarget::Positive {
+                check_negative_in_positive(&positive, &negative, &mut issues);
+            }
+            check_separators(target, text, &mut issues);
+            check_weight_syntax(target, segments, &mut issues);
+        }
+
+        if let Some(model_name) = &input.negative_ignored_by {
+            check_unsupported_negative(input.negative_prompt, model_name, &mut issues);
+        }
+
+        issues
+    }
+}
+
+/// Splits a prompt on commas outside brackets, keeping empty segments.
+fn split_segments(prompt: &str) -> Vec<Segment<'_>> {
+    let mut segments = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+
+    for (i, c) in prompt.char_indices() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                segments.push(parse_segment(prompt[start..i].trim()));
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    segments.push(parse_segment(prompt[start..].trim()));
+
+    segments
+}
+
+/// Parses a segment's concept and effective weight.
+fn parse_segment(raw: &str) -> Segment<'_> {
+    let mut inner = raw;
+    let mut weight = 1.0;
+    let mut valid = brackets_balanced(raw);
+
+    // Peel matching outer emphasis brackets: ((x)) = 1.1², [x] = 1/1.1
+    loop {
+        if let Some(rest) = strip_brackets(inner, '(', ')') {
+            if let Some((text, explicit)) = rest.rsplit_once(':') {
+                match explicit.trim().parse::<f64>() {
+                    Ok(w) if w.is_finite() && w > 0.0 => weight *= w,
+                    _ => valid = false,
+                }
+                inner = text;
+                break;
+            }
+            weight *= EMPHASIS_FACTOR;
+            inner = rest;
+        } else if let Some(rest) = strip_brackets(inner, '[', ']') {
+            weight /= EMPHASIS_FACTOR;
+            inner = rest;
+        } else {
+            break;
+        }
+    }
+
+    // A weight outside brackets (`token:1.2`) is read as literal text
+    let is_extra_network = raw.starts_with('<');
+    if !is_extra_network
+        && inner
+            .rsplit_once(':')
+            .is_some_and(|(_, w)| w.trim().parse::<f64>().is_ok())
+    {
+        valid = false;
+    }
+
+    let concept = normalize_concept(inner);
+    if concept.is_empty() && !raw.is_empty() {
+        valid = false;
+    }
+
+    Segment {
+        raw,
+        concept,
+        weight: valid.then_some(weight),
+    }
+}
+
+/// Strips one pair of brackets enclosing the whole text, e.g. `(x)` but not `(a) (b)`.
+fn strip_brackets(text: &str, open: char, close: char) -> Option<&str> {
+    text.strip_prefix(open)
+        .and_then(|s| s.strip_suffix(close))
+        .filter(|inner| brackets_balanced(inner))
+}
+
+/// Returns whether parentheses and square brackets are balanced and properly nested.
+fn brackets_balanced(text: &str) -> bool {
+    let mut stack = Vec::new();
+    for c in text.chars() {
+        match c {
+            '(' | '[' => stack.push(c),
+            ')' if stack.pop() != Some('(') => return false,
+            ']' if stack.pop() != Some('[') => return false,
+            _ => {}
+        }
+    }
+    stack.is_empty()
+}
+
+/// Normalizes segment text for concept comparison.
+fn normalize_concept(text: &str) -> String {
+    text.chars()
+        .filter(|c| !matches!(c, '(' | ')' | '[' | ']'))
+        .map(|c| if c == '_' { ' ' } else { c })
+        .collect::<String>()
+        .to_lowercase()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Reports concepts that appear more than once, with the same or different weights.
+fn check_repeated_concepts(
+    target: PromptTarget,
+    segments: &[Segment<'_>],
+    issues: &mut Vec<LintIssue>,
+) {
+    let mut groups: Vec<(&str, Vec<&Segment<'_>>)> = Vec::new();
+    let mut index: HashMap<&str, usize> = HashMap::new();
+    for segment in segments.iter().filter(|s| !s.concept.is_empty()) {
+        let i = *index.entry(segment.concept.as_str()).or_insert_with(|| {
+            groups.push((segment.concept.as_str(), Vec::new()));
+            groups.len() - 1
+        });
+        groups[i].1.push(segment);
+    }
+
+    for (concept, group) in groups.into_iter().filter(|(_, g)| g.len() > 1) {
+        let weights: Vec<f64> = group.iter().filter_map(|s| s.weight).collect();
+        let conflicting = weights
+            .iter()
+            .any(|w| (w - weights[0]).abs() > WEIGHT_TOLERANCE);
+
+        let written = group.iter().map(|s| s.raw).collect::<Vec<_>>().join(", ");
+        issues.push(if conflicting {
+            LintIssue {
+                code: LintCode::ConflictingWeights,
+                severity: LintSeverity::Warning,
+                target,
+                message: format!(
+                    "\"{concept}\" appears with different weights ({written}); keep one"
+                ),
+                segment: Some(group[0].raw.to_string()),
+            }
+        } else {
+            LintIssue {
+                code: LintCode::DuplicateConcept,
+                severity: LintSeverity::Info,
+                target,
+                message: if group.iter().all(|s| s.raw == group[0].raw) {
+                    format!(
+                        "\"{concept}\" appears {} times; repeats waste tokens",
+                        group.len()
+                    )
+                } else {
+                    format!(
+                        "\"{concept}\" appears {} times ({written}); repeats waste tokens",
+                        group.len()
+                    )
+                },
+                segment: Some(group[0].raw.to_string()),
+            }
+        });
+    }
+}
+
+/// Reports a prompt that exceeds the model's usable token limit.
+fn check_length(target: PromptTarget, length: &PromptLength, issues: &mut Vec<LintIssue>) {
+    if length.token_count <= length.usable_tokens {
+        return;
+    }
+
+    issues.push(LintIssue {
+        code: LintCode::PromptTooLong,
+        severity: LintSeverity::Error,
+        target,
+        message: format!(
+            "Prompt uses {} of {} tokens for {}; tokens past the limit are truncated",
+            length.token_count, length.usable_tokens, length.model_name
+        ),
+        segment: None,
+    });
+}
+
+/// Reports a non-empty negative prompt written for a model that ignores it.
+fn check_unsupported_negative(
+    negative_prompt: &str,
+    model_name: &str,
+    issues: &mut Vec<LintIssue>,
+) {
+    if negative_prompt.trim().is_empty() {
+        return;
+    }
+
+    issues.push(LintIssue {
+        code: LintCode::UnsupportedNegativePrompt,
+        severity: LintSeverity::Warning,
+        target: PromptTarget::Negative,
+        message: format!(
+            "{model_name} does not use a negative prompt; describe what to avoid in the positive prompt instead"
+        ),
+        segment: None,
+    });
+}
+
+/// Reports positive segments that are excluded in the negative prompt or read as exclusions.
+fn check_negative_in_positive(
+    positive: &[Segment<'_>],
+    negative: &[Segment<'_>],
+    issues: &mut Vec<LintIssue>,
+) {
+    for segment in positive.iter().filter(|s| !s.concept.is_empty()) {
+        let concept = segment.concept.as_str();
+        let message = if negative.iter().any(|n| n.concept == concept) {
+            format!("\"{concept}\" is in both prompts; the two cancel out")
+        } else if NEGATIVE_VOCABULARY.contains(&concept) {
+            format!("\"{concept}\" is negative-prompt vocabulary; move it to the negative prompt")
+        } else {
+            continue;
+        };
+
+        issues.push(LintIssue {
+            code: LintCode::NegativeInPositive,
+            severity: LintSeverity::Warning,
+            target: PromptTarget::Positive,
+            message,
+            segment: Some(segment.raw.to_string()),
+        });
+    }
+}
+
+/// Reports leading/trailing separators and empty segments.
+fn check_separators(target: PromptTarget, text: &str, issues: &mut Vec<LintIssue>) {
+    let trimmed = text.trim();
+    if trimmed.is_empty() {
+        return;
+    }
+
+    if trimmed.starts_with(',') || trimmed.ends_with(',') {
+        issues.push(LintIssue {
+            code: LintCode::TrailingSeparator,
+            severity: LintSeverity::Info,
+            target,
+            message: "Prompt starts or ends with a separator".to_string(),
+            segment: None,
+        });
+    }
+
+    let inner = trimmed.trim_matches(|c: char| c == ',' || c.is_whitespace());
+    let empty_segments = split_segments(inner)
+        .iter()
+        .filter(|s| s.raw.is_empty())
+        .count();
+    if empty_segments > 0 {
+        issues.push(LintIssue {
+            code: LintCode::EmptySegment,
+            severity: LintSeverity::Info,
+            target,
+            message: format!(
+                "Prompt contains {empty_segments} empty segment(s) between separators"
+            ),
+            segment: None,
+        });
+    }
+}
+
+/// Reports segments with unbalanced brackets or invalid weights.
+fn check_weight_syntax(
+    target: PromptTarget,
+    segments: &[Segment<'_>],
+    issues: &mut Vec<LintIssue>,
+) {
+    for segment in segments.iter().filter(|s| s.weight.is_none()) {
+        issues.push(LintIssue {
+            code: LintCode::MalformedWeight,
+            severity: LintSeverity::Error,
+            target,
+            message: format!(
+                "\"{}\" has malformed weight syntax; use (token:1.2)",
+                segment.raw
+            ),
+            segment: Some(segment.raw.to_string()),
+        });
+    }
+}
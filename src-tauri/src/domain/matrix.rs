@@ -0,0 +1,319 @@
+//! Prompt Matrix
+//!
+//! Expands a persona into a batch of prompts for systematic testing: each
+//! [`MatrixAxis`] lists alternatives for one aspect of the image (e.g., three
+//! hairstyles, four outfits), and the matrix holds one composed prompt per
+//! combination, labelled with the alternatives it uses.
+//!
+//! # Placement
+//!
+//! An axis with a granularity level places its alternative among that
+//! level's tokens, after the persona's own ones, so a hairstyle lands next to
+//! the hair color. An axis without one adds its alternative to the ad-hoc
+//! positive tokens, at the ad-hoc position of the composition options.
+//!
+//! # Order
+//!
+//! Combinations are ordered like nested loops over the axes, the last axis
+//! varying fastest, so prompts sharing their first alternatives stay
+//! together in the exported list.
+
+use serde::{Deserialize, Serialize};
+
+use super::prompt::{ComposedPrompt, CompositionOptions, PromptComposer};
+use super::token::{GranularityLevel, Token, TokenPolarity};
+use crate::error::AppError;
+
+/// Largest number of prompts a matrix may hold.
+pub const MAX_MATRIX_PROMPTS: usize = 1000;
+
+/// One varying aspect of the prompts, with its alternatives.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MatrixAxis {
+    /// Name used in the labels (e.g., "hairstyle")
+    pub name: String,
+    /// Granularity level the alternatives belong to; unset adds them as
+    /// ad-hoc tokens
+    #[serde(default)]
+    pub granularity_id: Option<String>,
+    /// Token contents to alternate between (e.g., "ponytail", "braids")
+    pub alternatives: Vec<String>,
+}
+
+/// One combination of alternatives and its composed prompt.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptMatrixEntry {
+    /// The chosen alternatives (e.g., "hairstyle: ponytail / outfit: sundress")
+    pub label: String,
+    /// Index of the chosen alternative on each axis, in axis order
+    pub choices: Vec<usize>,
+    /// The composed prompt
+    pub prompt: ComposedPrompt,
+}
+
+/// Copy-ready encodings of a prompt matrix.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptMatrixFormats {
+    /// One positive prompt per line
+    pub lines: String,
+    /// One `--prompt "…" --negative_prompt "…"` line per prompt, for the
+    /// A1111 "Prompts from file or textbox" script
+    pub a1111_script: String,
+}
+
+/// Every combination of a persona's matrix axes, composed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptMatrix {
+    /// UUID of the persona
+    pub persona_id: String,
+    /// The axes, with blank alternatives removed and the rest trimmed
+    pub axes: Vec<MatrixAxis>,
+    /// One entry per combination, last axis varying fastest
+    pub entries: Vec<PromptMatrixEntry>,
+    /// The prompts as text lists
+    pub formats: PromptMatrixFormats,
+}
+
+impl MatrixAxis {
+    /// Trims the axis and checks it against the granularity levels.
+    fn normalized(self, granularity_levels: &[GranularityLevel]) -> Result<Self, AppError> {
+        let name = self.name.trim().to_string();
+        if name.is_empty() {
+            return Err(AppError::Validation(
+                "Matrix axis name cannot be empty".to_string(),
+            ));
+        }
+
+        let granularity_id = self
+            .granularity_id
+            .map(|id| id.trim().to_string())
+            .filter(|id| !id.is_empty());
+        if let Some(id) = &granularity_id {
+            if !granularity_levels.iter().any(|level| &level.id == id) {
+                return Err(AppError::Validation(format!(
+                    "Unknown granularity level '{id}' on matrix axis '{name}'"
+                )));
+            }
+        }
+
+        let alternatives: Vec<String> = self
+            .alternatives
+            .iter()
+            .map(|alternative| alternative.trim().to_string())
+            .filter(|alternative| !alternative.is_empty())
+            .collect();
+        if alternatives.is_empty() {
+            return Err(AppError::Validation(format!(
+                "Matrix axis '{name}' needs at least one alternative"
+            )));
+        }
+
+        Ok(Self {
+            name,
+            granularity_id,
+            alternatives,
+        })
+    }
+}
+
+impl PromptMatrix {
+    /// Composes one prompt per combination of the axes' alternatives.
+    ///
+    /// # Arguments
+    ///
+    /// * `persona_id` - UUID of the persona
+    /// * `tokens` - The persona's tokens
+    /// * `granularity_levels` - Available granularity level definitions
+    /// * `axes` - The varying aspects, each with its alternatives
+    /// * `options` - Composition configuration shared by every prompt
+    /// * `supports_negative_prompt` - Whether the target image model uses a
+    ///   negative prompt
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Validation` if there are no axes, an axis has no
+    /// name, no alternatives, or an unknown granularity level, or the matrix
+    /// would hold more than [`MAX_MATRIX_PROMPTS`] prompts.
+    pub fn generate(
+        persona_id: String,
+        tokens: &[Token],
+        granularity_levels: &[GranularityLevel],
+        axes: Vec<MatrixAxis>,
+        options: &CompositionOptions,
+        supports_negative_prompt: bool,
+    ) -> Result<Self, AppError> {
+        if axes.is_empty() {
+            return Err(AppError::Validation(
+                "A prompt matrix needs at least one axis".to_string(),
+            ));
+        }
+        let axes = axes
+            .into_iter()
+            .map(|axis| axis.normalized(granularity_levels))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let count = axes
+            .iter()
+            .try_fold(1_usize, |count, axis| {
+                count
+                    .checked_mul(axis.alternatives.len())
+                    .filter(|&count| count <= MAX_MATRIX_PROMPTS)
+            })
+            .ok_or_else(|| {
+                AppError::Validation(format!(
+                    "A prompt matrix cannot hold more than {MAX_MATRIX_PROMPTS} prompts"
+                ))
+            })?;
+
+        let mut entries = Vec::with_capacity(count);
+        let mut choices = vec![0; axes.len()];
+        for _ in 0..count {
+            entries.push(Self::compose_entry(
+                &persona_id,
+                tokens,
+                granularity_levels,
+                &axes,
+                &choices,
+                options,
+                supports_negative_prompt,
+            ));
+
+            // Advance like an odometer, last axis fastest
+            for (choice, axis) in choices.iter_mut().zip(&axes).rev() {
+                *choice += 1;
+                if *choice < axis.alternatives.len() {
+                    break;
+                }
+                *choice = 0;
+            }
+        }
+
+        let formats = PromptMatrixFormats {
+            lines: entries
+                .iter()
+                .map(|entry| single_line(&entry.prompt.positive_prompt))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            a1111_script: entries
+                .iter()
+                .map(|entry| a1111_script_line(&entry.prompt))
+                .collect::<Vec<_>>()
+                .join("\n"),
+        };
+
+        Ok(Self {
+            persona_id,
+            axes,
+            entries,
+            formats,
+        })
+    }
+
+    /// Composes the prompt for one combination of alternatives.
+    fn compose_entry(
+        persona_id: &str,
+        tokens: &[Token],
+        granularity_levels: &[GranularityLevel],
+        axes: &[MatrixAxis],
+        choices: &[usize],
+        options: &CompositionOptions,
+        supports_negative_prompt: bool,
+    ) -> PromptMatrixEntry {
+        let mut tokens = tokens.to_vec();
+        let mut adhoc: Vec<String> = options
+            .adhoc_positive
+            .iter()
+            .map(|adhoc| adhoc.trim().to_string())
+            .filter(|adhoc| !adhoc.is_empty())
+            .collect();
+        let mut label = Vec::with_capacity(axes.len());
+
+        for (axis, &choice) in axes.iter().zip(choices) {
+            let alternative = &axis.alternatives[choice];
+            label.push(format!("{}: {alternative}", axis.name));
+            match &axis.granularity_id {
+                Some(granularity_id) => {
+                    insert_alternative(&mut tokens, persona_id, granularity_id, alternative);
+                }
+                None => adhoc.push(alternative.clone()),
+            }
+        }
+
+        let options = CompositionOptions {
+            adhoc_positive: (!adhoc.is_empty()).then(|| adhoc.join(&options.separator)),
+            ..options.clone()
+        };
+        PromptMatrixEntry {
+            label: label.join(" / "),
+            choices: choices.to_vec(),
+            prompt: PromptComposer::compose(
+                &tokens,
+                granularity_levels,
+                &options,
+                supports_negative_prompt,
+            ),
+        }
+    }
+}
+
+/// Adds an alternative as a positive token after the last token of its
+/// granularity level, or after all tokens if the level has none.
+///
+/// Composition sorts tokens stably by display order, so sharing the display
+/// order of the token before it keeps the alternative right behind it.
+fn insert_alternative(
+    tokens: &mut Vec<Token>,
+    persona_id: &str,
+    granularity_id: &str,
+    alternative: &str,
+) {
+    let (index, display_order) = tokens
+        .iter()
+        .enumerate()
+        .filter(|(_, token)| token.granularity_id == granularity_id)
+        .max_by_key(|(i, token)| (token.display_order, *i))
+        .map_or((tokens.len(), i32::MAX), |(i, token)| {
+            (i + 1, token.display_order)
+        });
+
+    let now = chrono::Utc::now();
+    tokens.insert(
+        index,
+        Token {
+            id: String::new(),
+            persona_id: persona_id.to_string(),
+            granularity_id: granularity_id.to_string(),
+            polarity: TokenPolarity::Positive,
+            content: alternative.to_string(),
+            weight: 1.0,
+            display_order,
+            created_at: now,
+            updated_at: now,
+        },
+    );
+}
+
+/// Joins the lines of a prompt, so each prompt takes one line in a list.
+fn single_line(prompt: &str) -> String {
+    prompt.lines().map(str::trim).collect::<Vec<_>>().join(" ")
+}
+
+/// Formats a prompt as a line of the A1111 "Prompts from file or textbox"
+/// script, which splits lines like a shell.
+fn a1111_script_line(prompt: &ComposedPrompt) -> String {
+    let quote = |text: &str| {
+        format!(
+            "\"{}\"",
+            single_line(text).replace('\\', "\\\\").replace('"', "\\\"")
+        )
+    };
+
+    let mut line = format!("--prompt {}", quote(&prompt.positive_prompt));
+    if !prompt.negative_prompt.is_empty() {
+        line.push_str(&format!(
+            " --negative_prompt {}",
+            quote(&prompt.negative_prompt)
+        ));
+    }
+    line
+}
@@ -19,7 +19,47 @@
 //! - [`token`]: Token entities, granularity levels, and polarity
 //! - [`prompt`]: Prompt composition logic and output formatting
 //! - [`ai`]: AI provider configuration and token generation types
+//! - [`activity`]: Recently opened, modified, and composed personas
+//! - [`alias`]: User-maintained token variants and the phrasing they stand for
+//! - [`api_version`]: IPC API version and the frontend compatibility handshake
+//! - [`autocomplete`]: Prefix completions for tokens from the library and tag dictionaries
+//! - [`banned_term`]: User-managed blacklist of words tokens must not contain
+//! - [`blend`]: Combining parent personas into a new persona draft
+//! - [`civitai`]: Trigger words and base model family of Civitai models
+//! - [`collation`]: Case- and accent-insensitive matching and sorting of text
+//! - [`collection`]: Smart collections defined by saved persona queries
+//! - [`compare`]: Structured diff between two personas
+//! - [`diagnostics`]: Aggregated subsystem status for support
+//! - [`draft`]: Autosaved incomplete personas from the create flow
+//! - [`events`]: Change notifications keeping multiple windows in sync
 //! - [`export`]: Import/export data structures for backup and sharing
+//! - [`face`]: Grouping gallery images by face to detect visual drift
+//! - [`find_replace`]: Bulk find-and-replace across token content
+//! - [`gallery`]: Generated images linked to the persona, prompt, and seed used
+//! - [`i18n`]: Localized message catalogs keyed by stable codes
+//! - [`inheritance`]: Variant personas inheriting parameters and tokens from a base
+//! - [`lint`]: Deterministic prompt quality checks
+//! - [`matrix`]: Batches of prompts combining alternatives for several aspects
+//! - [`naming`]: Persona name normalization, comparison, and reserved suffixes
+//! - [`ordering`]: Heuristic token order proposals
+//! - [`repository`]: Storage-independent persona and token repository traits
+//! - [`resolution`]: Native output resolutions per model family
+//! - [`resource`]: Local embeddings and `LoRA` models with their prompt triggers
+//! - [`schedule`]: Recurring background tasks such as AI style refreshes
+//! - [`search`]: Quick search results across personas, tokens, collections, and templates
+//! - [`settings`]: Backend application settings (network proxy, offline mode, logging)
+//! - [`share`]: Temporary read-only sharing of a smart collection over HTTP
+//! - [`sheet`]: Printable persona character sheets and their page layout
+//! - [`site`]: Static HTML export of selected personas as a browsable library
+//! - [`similarity`]: Ranking personas by shared tokens and tags
+//! - [`stats`]: Library-wide statistics for the dashboard
+//! - [`suggestion`]: Inbox of AI-suggested tokens awaiting review
+//! - [`tag`]: Namespaced persona tags and the tag tree
+//! - [`telemetry`]: Opt-in, local-only feature usage counters
+//! - [`template`]: Built-in persona archetype templates
+//! - [`token_pack`]: Built-in negative token packs per model family
+//! - [`update`]: Available application updates and the record of the last one
+//! - [`wizard`]: Step-by-step persona creation drafts
 //!
 //! # Design Principles
 //!
@@ -27,18 +67,61 @@
 //! - **Immutable by Default**: Updates are explicit via `update()` methods
 //! - **Validation at Boundaries**: Domain types trust their invariants internally
 
+pub mod activity;
 pub mod ai;
+pub mod alias;
+pub mod api_version;
+pub mod autocomplete;
+pub mod banned_term;
+pub mod blend;
+pub mod civitai;
+pub mod collation;
+pub mod collection;
+pub mod compare;
 pub mod constants;
+pub mod diagnostics;
+pub mod draft;
+pub mod events;
 pub mod export;
+pub mod face;
+pub mod find_replace;
+pub mod gallery;
+pub mod i18n;
+pub mod inheritance;
+pub mod lint;
+pub mod matrix;
+pub mod naming;
+pub mod ordering;
 pub mod persona;
 pub mod prompt;
+pub mod repository;
+pub mod resolution;
+pub mod resource;
+pub mod schedule;
+pub mod search;
+pub mod settings;
+pub mod share;
+pub mod sheet;
+pub mod similarity;
+pub mod site;
+pub mod stats;
+pub mod suggestion;
+pub mod tag;
+pub mod telemetry;
+pub mod template;
 pub mod token;
+pub mod token_pack;
+pub mod update;
+pub mod wizard;
 
 // Re-export commonly used types for ergonomic imports
 pub use ai::{
     AiProvider, AiProviderConfig, GeneratedToken, TokenGenerationRequest, TokenGenerationResponse,
 };
-pub use export::{ExportResult, ImportResult};
+pub use export::{
+    BulkExport, ExportResult, ImportResult, PersonaExport, PersonaImportOptions,
+    PersonaImportResult,
+};
 pub use persona::{CreatePersonaRequest, GenerationParams, Persona, UpdatePersonaRequest};
 pub use prompt::{ComposedPrompt, CompositionOptions, PromptComposer};
 pub use token::{
@@ -0,0 +1,188 @@
+//! Persona Name Rules
+//!
+//! This module defines how persona names are normalized, compared, and
+//! deduplicated.
+//!
+//! # Normalization
+//!
+//! Names are trimmed and runs of whitespace (including control characters)
+//! collapse to a single space before they are stored.
+//!
+//! # Uniqueness
+//!
+//! Two names collide when their [`name_key`] is equal: the key ignores case,
+//! diacritics, and compatibility forms, so "Zoë", "zoe", and "ZOE" are the
+//! same persona name.
+//!
+//! # Reserved Suffixes
+//!
+//! Duplicating and importing resolve collisions by appending "(Copy)",
+//! "(Copy N)", "(Imported)", or "(Imported N)". Names typed by the user may not
+//! end with these suffixes, so an automatic name never lands next to a
+//! hand-made look-alike. Conflicts in suggestions use a plain "(N)" counter,
+//! which is not reserved.
+
+use serde::{Deserialize, Serialize};
+use tokenizers::normalizers::StripAccents;
+use tokenizers::{NormalizedString, Normalizer};
+
+use crate::error::AppError;
+
+/// Maximum length of a persona name, in characters.
+pub const MAX_NAME_LENGTH: usize = 100;
+
+/// Scheme for making a name unique by appending a suffix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NameSuffix {
+    /// "(Copy)", "(Copy 2)", ... for duplicated personas (reserved)
+    Copy,
+    /// "(Imported)", "(Imported 2)", ... for imported personas (reserved)
+    Imported,
+    /// "(2)", "(3)", ... for suggested names
+    Counter,
+}
+
+impl NameSuffix {
+    /// Reserved suffix schemes, which user-entered names may not end with.
+    const RESERVED: [Self; 2] = [Self::Copy, Self::Imported];
+
+    /// Returns the word in the suffix, if any.
+    const fn label(self) -> Option<&'static str> {
+        match self {
+            Self::Copy => Some("Copy"),
+            Self::Imported => Some("Imported"),
+            Self::Counter => None,
+        }
+    }
+
+    /// Appends the suffix for the given attempt (starting at 1) to a base name.
+    ///
+    /// The base is shortened if needed so the result fits [`MAX_NAME_LENGTH`].
+    #[must_use]
+    pub fn apply(self, base: &str, attempt: u32) -> String {
+        let suffix = match (self.label(), attempt) {
+            (Some(label), 1) => format!(" ({label})"),
+            (Some(label), n) => format!(" ({label} {n})"),
+            (None, n) => format!(" ({})", n + 1),
+        };
+        let room = MAX_NAME_LENGTH.saturating_sub(suffix.chars().count());
+        let base: String = base.chars().take(room).collect();
+        format!("{}{suffix}", base.trim_end())
+    }
+
+    /// Returns true if the name ends with this suffix scheme (case-insensitive).
+    fn matches(self, name: &str) -> bool {
+        let Some(inner) = name
+            .strip_suffix(')')
+            .and_then(|rest| rest.rsplit_once(" ("))
+            .map(|(_, inner)| inner)
+        else {
+            return false;
+        };
+
+        let (word, number) = match inner.split_once(' ') {
+            Some((word, number)) => (word, Some(number)),
+            None => (inner, None),
+        };
+        let is_number = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_digit());
+
+        match self.label() {
+            Some(label) => word.eq_ignore_ascii_case(label) && number.map_or(true, is_number),
+            None => number.is_none() && is_number(word),
+        }
+    }
+}
+
+/// Trims a name and collapses whitespace and control characters to single spaces.
+#[must_use]
+pub fn normalize_name(name: &str) -> String {
+    name.split(|c: char| c.is_whitespace() || c.is_control())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Returns the key two names are compared by: normalized, without diacritics,
+/// compatibility characters decomposed, and lowercased.
+#[must_use]
+pub fn name_key(name: &str) -> String {
+    let mut key = NormalizedString::from(normalize_name(name).as_str());
+    key.nfkd();
+    // Only removes combining marks, which cannot fail
+    let _ = StripAccents.normalize(&mut key);
+    key.lowercase();
+    key.get().to_string()
+}
+
+/// Returns the reserved suffix scheme the name ends with, if any.
+#[must_use]
+pub fn reserved_suffix(name: &str) -> Option<NameSuffix> {
+    let name = normalize_name(name);
+    NameSuffix::RESERVED
+        .into_iter()
+        .find(|suffix| suffix.matches(&name))
+}
+
+/// Removes a reserved suffix ("(Copy 2)", "(Imported)") from a name, if present.
+///
+/// Used to find the base of an automatically named persona, so duplicating
+/// "Alice (Copy)" yields "Alice (Copy 2)" rather than "Alice (Copy) (Copy)".
+#[must_use]
+pub fn strip_reserved_suffix(name: &str) -> String {
+    let name = normalize_name(name);
+    match name.rsplit_once(" (") {
+        Some((base, _)) if reserved_suffix(&name).is_some() && !base.is_empty() => base.to_string(),
+        _ => name,
+    }
+}
+
+/// Yields the base name followed by its suffixed variants, in order of preference.
+pub fn name_candidates(base: String, suffix: NameSuffix) -> impl Iterator<Item = String> {
+    let variants = (1..).map({
+        let base = base.clone();
+        move |attempt| suffix.apply(&base, attempt)
+    });
+    std::iter::once(base).chain(variants)
+}
+
+/// Validates and normalizes a name entered by the user.
+///
+/// # Returns
+///
+/// The normalized name.
+///
+/// # Errors
+///
+/// Returns `AppError::Validation` if the name is empty, longer than
+/// [`MAX_NAME_LENGTH`], or ends with a reserved suffix.
+pub fn validate_name(name: &str) -> Result<String, AppError> {
+    let name = normalize_name(name);
+    if name.is_empty() {
+        return Err(AppError::Validation("Persona name is required".to_string()));
+    }
+    if name.chars().count() > MAX_NAME_LENGTH {
+        return Err(AppError::Validation(format!(
+            "Persona name must be at most {MAX_NAME_LENGTH} characters"
+        )));
+    }
+    if let Some(suffix) = reserved_suffix(&name) {
+        let label = suffix.label().unwrap_or_default();
+        return Err(AppError::Validation(format!(
+            "Names ending in \"({label})\" are reserved for automatically named personas"
+        )));
+    }
+    Ok(name)
+}
+
+/// Result of checking a persona name before saving it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NameCheck {
+    /// The name as it would be stored
+    pub normalized: String,
+    /// Whether the name is valid and unused
+    pub available: bool,
+    /// Why the name cannot be used, if it breaks a naming rule
+    pub error: Option<String>,
+    /// Available alternatives, empty if the name is available
+    pub suggestions: Vec<String>,
+}
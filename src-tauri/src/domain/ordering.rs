@@ -0,0 +1,183 @@
+//! Token Order Optimization
+//!
+//! Heuristics that propose a better global token order for a persona. The
+//! proposal is a [`ReorderTokensRequest`] the user can review and then apply
+//! with `reorder_tokens`; nothing is changed by computing it.
+//!
+//! # Heuristics
+//!
+//! CLIP-based models weigh early tokens more and split long prompts into
+//! chunks of about 75 tokens, so the proposal:
+//!
+//! 1. **Subject first**: tokens naming the subject ("1girl", "woman", "solo")
+//!    lead the prompt
+//! 2. **Style early**: Style-level tokens follow the subject
+//! 3. **Appearance by level**: remaining tokens follow in granularity level
+//!    order (General, Hair, Face, ...)
+//! 4. **Detail modifiers later**: tokens that refine rendering rather than
+//!    content ("intricate details", "soft lighting", "8k") close the prompt
+//! 5. **Emphasis in the first chunk**: emphasized tokens (weight above 1.0)
+//!    that would start after the first chunk are moved to its end, most
+//!    weighted first
+//!
+//! Positive and negative tokens are ordered separately (they form separate
+//! prompts), positives first. Within a tier, tokens keep their current order.
+
+use super::token::{
+    Granularity, GranularityLevel, ReorderTokensRequest, Token, TokenOrderUpdate, TokenPolarity,
+};
+
+/// Words that name the subject of the image.
+const SUBJECT_WORDS: &[&str] = &[
+    "1girl", "1boy", "2girls", "2boys", "solo", "girl", "boy", "woman", "man", "person", "female",
+    "male", "couple", "child",
+];
+
+/// Words and phrases that refine rendering rather than describe content.
+const DETAIL_MODIFIERS: &[&str] = &[
+    "detailed",
+    "details",
+    "intricate",
+    "texture",
+    "textured",
+    "lighting",
+    "bokeh",
+    "depth of field",
+    "sharp focus",
+    "4k",
+    "8k",
+    "hdr",
+    "highres",
+];
+
+/// Position class of a token in the proposed order; lower comes first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Tier {
+    Subject,
+    Style,
+    Appearance,
+    DetailModifier,
+}
+
+impl Tier {
+    fn of(token: &Token) -> Self {
+        let content = token.content.to_lowercase();
+        let words: Vec<&str> = content
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .collect();
+
+        if words.iter().any(|word| SUBJECT_WORDS.contains(word)) {
+            Self::Subject
+        } else if DETAIL_MODIFIERS.iter().any(|modifier| {
+            if modifier.contains(' ') {
+                content.contains(modifier)
+            } else {
+                words.contains(modifier)
+            }
+        }) {
+            Self::DetailModifier
+        } else if token.granularity_id == Granularity::Style.as_str() {
+            Self::Style
+        } else {
+            Self::Appearance
+        }
+    }
+}
+
+/// Proposes a new global order for a persona's tokens.
+///
+/// # Arguments
+///
+/// * `persona_id` - The persona the tokens belong to
+/// * `tokens` - The persona's tokens in their current order
+/// * `granularity_levels` - Levels in display order, for ordering appearance tokens
+/// * `chunk_budget` - Size of the first prompt chunk in model tokens
+/// * `cost` - Model tokens a token takes in the prompt, including its separator
+///
+/// # Returns
+///
+/// A reorder request assigning every token a new display order from 0.
+#[must_use]
+pub fn propose_token_order(
+    persona_id: &str,
+    tokens: &[Token],
+    granularity_levels: &[GranularityLevel],
+    chunk_budget: usize,
+    cost: impl Fn(&Token) -> usize,
+) -> ReorderTokensRequest {
+    let level_rank = |token: &Token| {
+        granularity_levels
+            .iter()
+            .position(|level| level.id == token.granularity_id)
+            .unwrap_or(granularity_levels.len())
+    };
+
+    let ordered = [TokenPolarity::Positive, TokenPolarity::Negative]
+        .into_iter()
+        .flat_map(|polarity| {
+            let mut group: Vec<&Token> = tokens.iter().filter(|t| t.polarity == polarity).collect();
+            group.sort_by_key(|t| {
+                let tier = Tier::of(t);
+                let rank = if tier == Tier::Appearance {
+                    level_rank(t)
+                } else {
+                    0
+                };
+                (tier, rank)
+            });
+            emphasize_first_chunk(group, chunk_budget, &cost)
+        });
+
+    ReorderTokensRequest {
+        persona_id: persona_id.to_string(),
+        token_orders: ordered
+            .enumerate()
+            .map(|(position, token)| TokenOrderUpdate {
+                token_id: token.id.clone(),
+                display_order: position as i32,
+            })
+            .collect(),
+    }
+}
+
+/// Moves emphasized tokens that start after the first chunk to its end.
+fn emphasize_first_chunk<'a>(
+    tokens: Vec<&'a Token>,
+    chunk_budget: usize,
+    cost: &impl Fn(&Token) -> usize,
+) -> Vec<&'a Token> {
+    let mut late = Vec::new();
+    let mut remaining = Vec::new();
+    let mut used = 0;
+    for token in tokens {
+        if used >= chunk_budget && token.weight > 1.0 {
+            late.push(token);
+        } else {
+            remaining.push(token);
+        }
+        used += cost(token);
+    }
+
+    if late.is_empty() {
+        return remaining;
+    }
+
+    // Most weighted first; the sort is stable, so ties keep their order
+    late.sort_by(|a, b| b.weight.total_cmp(&a.weight));
+
+    // Insert where the moved tokens still end within the first chunk
+    let moved_cost: usize = late.iter().map(|t| cost(t)).sum();
+    let mut used = 0;
+    let mut index = 0;
+    for token in &remaining {
+        if used + cost(token) + moved_cost > chunk_budget {
+            break;
+        }
+        used += cost(token);
+        index += 1;
+    }
+
+    remaining.splice(index..index, late);
+    remaining
+}
@@ -12,12 +12,31 @@
 //! - **Generation Params**: Image generation settings (model, seed, steps, etc.)
 //! - **AI Configuration**: Optional LLM provider settings for token generation
 
+use std::path::Path;
+
 use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_with::rust::double_option;
+use sha2::{Digest, Sha256};
 use uuid::Uuid;
 
+use super::i18n::{Locale, Message};
+use super::inheritance::ParamField;
+use super::prompt::PromptPreview;
+use super::tag::{normalize_tag, normalize_tags};
+use super::token::{GranularityLevel, Token};
 use super::DEFAULT_IMAGE_MODEL_ID;
+use crate::error::AppError;
+
+/// Highest step count accepted for generation parameters.
+const MAX_STEPS: u32 = 150;
+
+/// Highest CFG scale accepted for generation parameters.
+const MAX_CFG_SCALE: f32 = 30.0;
+
+/// Highest weight accepted for a conditioning entry.
+const MAX_CONDITIONING_WEIGHT: f32 = 2.0;
 
 /// A Persona represents a complete fictional character profile for AI image generation.
 ///
@@ -33,7 +52,12 @@ use super::DEFAULT_IMAGE_MODEL_ID;
 /// - `tags`: Organizational labels for filtering and grouping
 /// - `ai_*`: Optional configuration for AI-powered token generation
 /// - `created_at`/`updated_at`: Timestamps for auditing and sorting
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// - `archived`: Set aside without being deleted
+/// - `color`: Optional label color for color-coded cards
+/// - `content_rating`: Audience rating; mature personas are hidden in safe mode
+/// - `base_persona_id`, `inherited_granularities`, `param_overrides`: Inheritance
+///   from a base persona (see `domain::inheritance`)
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Persona {
     /// Unique identifier (UUID v4)
     pub id: String,
@@ -49,10 +73,67 @@ pub struct Persona {
     pub ai_model_id: Option<String>,
     /// Custom instructions passed to AI during token generation
     pub ai_instructions: Option<String>,
+    /// Language of AI-written descriptions and rationales (e.g., "German");
+    /// the global setting applies when unset
+    #[serde(default)]
+    pub ai_output_language: Option<String>,
     /// Creation timestamp
     pub created_at: DateTime<Utc>,
     /// Last modification timestamp
     pub updated_at: DateTime<Utc>,
+    /// Whether the persona is archived (kept, but hidden from everyday lists)
+    #[serde(default)]
+    pub archived: bool,
+    /// Label color as `#rrggbb` (e.g., "#e11d48")
+    #[serde(default)]
+    pub color: Option<String>,
+    /// Audience rating
+    #[serde(default)]
+    pub content_rating: ContentRating,
+    /// Persona this variant inherits from, if any
+    #[serde(default)]
+    pub base_persona_id: Option<String>,
+    /// Granularity level IDs whose tokens are inherited from the base
+    #[serde(default)]
+    pub inherited_granularities: Vec<String>,
+    /// Generation parameters set on this persona instead of inherited
+    #[serde(default)]
+    pub param_overrides: Vec<ParamField>,
+}
+
+/// Audience rating of a persona.
+///
+/// Safe mode hides personas rated `Mature` from lists, search, and
+/// composition (see `infrastructure::safe_mode`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ContentRating {
+    /// Suitable for any audience
+    #[default]
+    General,
+    /// Mature content, hidden in safe mode
+    Mature,
+}
+
+impl ContentRating {
+    /// Returns the lowercase string representation for database storage.
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::General => "general",
+            Self::Mature => "mature",
+        }
+    }
+
+    /// Parses from database string representation.
+    #[must_use]
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "general" => Some(Self::General),
+            "mature" => Some(Self::Mature),
+            _ => None,
+        }
+    }
 }
 
 /// Image generation parameters associated with a persona.
@@ -66,7 +147,9 @@ pub struct Persona {
 /// - `seed`: -1 (random)
 /// - `steps`: 30
 /// - `cfg_scale`: 7.0
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// - `width`/`height`: None (the model's default resolution)
+/// - `conditioning`: empty
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct GenerationParams {
     /// UUID of the parent persona (foreign key)
     pub persona_id: String,
@@ -82,6 +165,96 @@ pub struct GenerationParams {
     pub sampler: Option<String>,
     /// Scheduler algorithm (e.g., "karras", "exponential", "normal")
     pub scheduler: Option<String>,
+    /// Output width in pixels (`None` for the model's default)
+    #[serde(default)]
+    pub width: Option<u32>,
+    /// Output height in pixels (`None` for the model's default)
+    #[serde(default)]
+    pub height: Option<u32>,
+    /// `ControlNet` and IP-Adapter units applied on top of the prompt, in order
+    #[serde(default)]
+    pub conditioning: Vec<ConditioningEntry>,
+}
+
+/// Kind of image conditioning a [`ConditioningEntry`] applies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ConditioningKind {
+    /// Guides composition or pose with a `ControlNet` model
+    #[serde(rename = "controlnet")]
+    ControlNet,
+    /// Carries the look of a reference image with an IP-Adapter model
+    IpAdapter,
+}
+
+/// A reference image applied through `ControlNet` or IP-Adapter.
+///
+/// Stored with the generation parameters, so the reference images of a
+/// character are reproduced along with its prompt.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ConditioningEntry {
+    /// `ControlNet` or IP-Adapter
+    pub kind: ConditioningKind,
+    /// Model file name (e.g., `control_v11p_sd15_openpose.pth`)
+    pub model: String,
+    /// Preprocessor run on the reference image (e.g., "openpose"), if any
+    #[serde(default)]
+    pub preprocessor: Option<String>,
+    /// Absolute path of the reference image
+    pub image_path: String,
+    /// Strength of the conditioning (0–2)
+    pub weight: f32,
+}
+
+/// Sampling settings an image model works well with.
+///
+/// Guidance- and step-distilled models (Turbo, Lightning, LCM, FLUX schnell)
+/// need far lower CFG scales and step counts than regular diffusion models.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SamplingRange {
+    /// Lowest recommended CFG scale
+    pub min_cfg_scale: f32,
+    /// Highest recommended CFG scale
+    pub max_cfg_scale: f32,
+    /// Lowest recommended step count
+    pub min_steps: u32,
+    /// Highest recommended step count
+    pub max_steps: u32,
+}
+
+impl Default for SamplingRange {
+    /// Range for regular (not distilled) diffusion models.
+    fn default() -> Self {
+        Self {
+            min_cfg_scale: 3.0,
+            max_cfg_scale: 12.0,
+            min_steps: 15,
+            max_steps: 60,
+        }
+    }
+}
+
+/// A generation setting that is valid but likely to give poor results.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParamWarning {
+    /// Setting the warning is about ("`cfg_scale`", "steps", or "resolution")
+    pub field: String,
+    /// Stable message code (e.g., "`param.steps_out_of_range`")
+    pub code: String,
+    /// Human-readable explanation, in the requested locale
+    pub message: String,
+}
+
+impl ParamWarning {
+    /// Creates a warning about a setting, rendering its message in a locale.
+    #[must_use]
+    pub fn new(field: &str, message: &Message, locale: Locale) -> Self {
+        Self {
+            field: field.to_string(),
+            code: message.code.to_string(),
+            message: message.render(locale),
+        }
+    }
 }
 
 /// Request payload for creating a new persona.
@@ -96,6 +269,9 @@ pub struct CreatePersonaRequest {
     /// Optional tags (defaults to empty vector)
     #[serde(default)]
     pub tags: Vec<String>,
+    /// Audience rating (defaults to general)
+    #[serde(default)]
+    pub content_rating: ContentRating,
 }
 
 /// Request payload for updating an existing persona.
@@ -103,11 +279,16 @@ pub struct CreatePersonaRequest {
 /// All fields are optional; only provided fields are updated.
 /// Omitted fields retain their current values.
 ///
-/// For AI fields (`ai_provider_id`, `ai_model_id`, `ai_instructions`), the double
+/// For AI fields (`ai_provider_id`, `ai_model_id`, `ai_instructions`,
+/// `ai_output_language`), the double
 /// option pattern is used to distinguish between:
 /// - `None`: Field not provided in JSON, retain current value
 /// - `Some(None)`: Field explicitly set to `null` in JSON, clear the value
 /// - `Some(Some(value))`: Field has a value in JSON, update to that value
+///
+/// Set `expected_updated_at` to the `updated_at` the edit was based on to
+/// reject it with `AppError::Conflict` if the persona changed in the meantime
+/// (e.g., in another window).
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UpdatePersonaRequest {
     /// New name (must be unique if provided)
@@ -125,6 +306,118 @@ pub struct UpdatePersonaRequest {
     /// New AI instructions: None = not provided, Some(None) = clear, Some(Some(text)) = set
     #[serde(default, with = "double_option")]
     pub ai_instructions: Option<Option<String>>,
+    /// New AI output language: None = not provided, Some(None) = clear, Some(Some(name)) = set
+    #[serde(default, with = "double_option")]
+    pub ai_output_language: Option<Option<String>>,
+    /// Archive or unarchive the persona
+    #[serde(default)]
+    pub archived: Option<bool>,
+    /// New label color: None = not provided, Some(None) = clear, Some(Some(hex)) = set
+    #[serde(default, with = "double_option")]
+    pub color: Option<Option<String>>,
+    /// New audience rating
+    #[serde(default)]
+    pub content_rating: Option<ContentRating>,
+    /// Last modification time the edit was based on; `None` skips the check
+    #[serde(default)]
+    pub expected_updated_at: Option<DateTime<Utc>>,
+}
+
+/// Changes applied to every persona of a bulk update.
+///
+/// Unlike [`UpdatePersonaRequest`], tags are added and removed rather than
+/// replaced, so each persona keeps its other tags. AI fields use the same
+/// double option pattern.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BulkPersonaPatch {
+    /// Tags to add (skipped where already present, ignoring case)
+    #[serde(default)]
+    pub add_tags: Vec<String>,
+    /// Tags to remove (matched ignoring case)
+    #[serde(default)]
+    pub remove_tags: Vec<String>,
+    /// New AI provider ID: None = unchanged, Some(None) = clear, Some(Some(id)) = set
+    #[serde(default, with = "double_option")]
+    pub ai_provider_id: Option<Option<String>>,
+    /// New AI model ID: None = unchanged, Some(None) = clear, Some(Some(id)) = set
+    #[serde(default, with = "double_option")]
+    pub ai_model_id: Option<Option<String>>,
+    /// Archive or unarchive the personas
+    #[serde(default)]
+    pub archived: Option<bool>,
+    /// New audience rating
+    #[serde(default)]
+    pub content_rating: Option<ContentRating>,
+}
+
+impl BulkPersonaPatch {
+    /// Returns true if the patch changes nothing.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.add_tags.iter().all(|tag| tag.trim().is_empty())
+            && self.remove_tags.iter().all(|tag| tag.trim().is_empty())
+            && self.ai_provider_id.is_none()
+            && self.ai_model_id.is_none()
+            && self.archived.is_none()
+            && self.content_rating.is_none()
+    }
+
+    /// Builds the update request applying this patch to `persona`.
+    #[must_use]
+    pub fn to_update_request(&self, persona: &Persona) -> UpdatePersonaRequest {
+        let removed: Vec<String> = self
+            .remove_tags
+            .iter()
+            .map(|tag| normalize_tag(tag).to_lowercase())
+            .collect();
+
+        let mut tags: Vec<String> = persona
+            .tags
+            .iter()
+            .filter(|tag| !removed.contains(&normalize_tag(tag).to_lowercase()))
+            .cloned()
+            .collect();
+        for tag in self.add_tags.iter().map(|tag| normalize_tag(tag)) {
+            let present = tags
+                .iter()
+                .any(|t| normalize_tag(t).to_lowercase() == tag.to_lowercase());
+            if !tag.is_empty() && !present {
+                tags.push(tag);
+            }
+        }
+
+        UpdatePersonaRequest {
+            name: None,
+            description: None,
+            tags: (tags != persona.tags).then_some(tags),
+            ai_provider_id: self.ai_provider_id.clone(),
+            ai_model_id: self.ai_model_id.clone(),
+            ai_instructions: None,
+            ai_output_language: None,
+            archived: self.archived,
+            color: None,
+            content_rating: self.content_rating,
+            expected_updated_at: None,
+        }
+    }
+}
+
+/// Everything a read-only presentation of a persona displays, in one payload.
+///
+/// Lets a presenter window render a persona with a single IPC round trip
+/// instead of fetching the persona, parameters, tokens, and prompt separately.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PersonaFull {
+    /// The persona itself
+    pub persona: Persona,
+    /// Image generation parameters, if saved
+    pub generation_params: Option<GenerationParams>,
+    /// All tokens in display order
+    pub tokens: Vec<Token>,
+    /// Granularity levels in display order, for grouping and labeling tokens
+    pub granularity_levels: Vec<GranularityLevel>,
+    /// The composed prompt and its copy formats
+    pub prompt: PromptPreview,
 }
 
 impl Persona {
@@ -144,12 +437,19 @@ impl Persona {
             id: Uuid::new_v4().to_string(),
             name,
             description,
-            tags,
+            tags: normalize_tags(&tags),
             ai_provider_id: None,
             ai_model_id: None,
             ai_instructions: None,
+            ai_output_language: None,
             created_at: now,
             updated_at: now,
+            archived: false,
+            color: None,
+            content_rating: ContentRating::General,
+            base_persona_id: None,
+            inherited_granularities: Vec::new(),
+            param_overrides: Vec::new(),
         }
     }
 
@@ -166,7 +466,7 @@ impl Persona {
             self.description = Some(description.clone());
         }
         if let Some(tags) = &request.tags {
-            self.tags = tags.clone();
+            self.tags = normalize_tags(tags);
         }
         // AI fields use double option: Some(None) clears, Some(Some(v)) sets, None = no change
         if let Some(ai_provider_id) = &request.ai_provider_id {
@@ -178,10 +478,77 @@ impl Persona {
         if let Some(ai_instructions) = &request.ai_instructions {
             self.ai_instructions = ai_instructions.clone();
         }
+        if let Some(ai_output_language) = &request.ai_output_language {
+            self.ai_output_language = ai_output_language.clone();
+        }
+        if let Some(archived) = request.archived {
+            self.archived = archived;
+        }
+        if let Some(color) = &request.color {
+            self.color = color.clone();
+        }
+        if let Some(content_rating) = request.content_rating {
+            self.content_rating = content_rating;
+        }
         self.updated_at = Utc::now();
     }
 }
 
+/// Validates a label color and normalizes it to lowercase `#rrggbb`.
+///
+/// # Errors
+///
+/// Returns `AppError::Validation` if the color is not a `#` followed by six
+/// hex digits.
+pub fn validate_color(color: &str) -> Result<String, AppError> {
+    let color = color.trim();
+    let valid = color
+        .strip_prefix('#')
+        .is_some_and(|hex| hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit()));
+    if !valid {
+        return Err(AppError::Validation(format!(
+            "Invalid color '{color}': expected a hex color like #e11d48"
+        )));
+    }
+    Ok(color.to_ascii_lowercase())
+}
+
+/// Computes a content hash identifying a persona by what it describes rather than its name.
+///
+/// The hash covers the trimmed description and every token's granularity, polarity,
+/// content (case-insensitive) and weight. Tokens are sorted before hashing so that
+/// reordering does not change the result. Two personas with the same hash describe
+/// the same character, which lets imports detect renamed duplicates.
+///
+/// # Returns
+///
+/// Lowercase hexadecimal SHA-256 digest.
+#[must_use]
+pub fn compute_content_hash(description: Option<&str>, tokens: &[Token]) -> String {
+    let mut entries: Vec<String> = tokens
+        .iter()
+        .map(|t| {
+            format!(
+                "{}\u{1f}{}\u{1f}{}\u{1f}{:.2}",
+                t.granularity_id,
+                t.polarity.as_str(),
+                t.content.trim().to_lowercase(),
+                t.weight
+            )
+        })
+        .collect();
+    entries.sort();
+
+    let mut hasher = Sha256::new();
+    hasher.update(description.unwrap_or("").trim().as_bytes());
+    for entry in &entries {
+        hasher.update(b"\n");
+        hasher.update(entry.as_bytes());
+    }
+
+    format!("{:x}", hasher.finalize())
+}
+
 impl GenerationParams {
     /// Creates default generation parameters linked to a specific persona.
     #[must_use]
@@ -191,6 +558,102 @@ impl GenerationParams {
             ..Default::default()
         }
     }
+
+    /// Validates that the parameters can be used for generation at all.
+    ///
+    /// Settings that are usable but unsuited to the model are reported by
+    /// [`Self::sampling_warnings`] instead.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Validation` if the model ID is empty, the seed is
+    /// below -1, steps are outside 1–150, the CFG scale is outside 0–30, or
+    /// only one of width and height is set (or either is zero), or a
+    /// conditioning entry has no model, a relative image path, or a weight
+    /// outside 0–2.
+    pub fn validate(&self) -> Result<(), AppError> {
+        if self.model_id.trim().is_empty() {
+            return Err(AppError::Validation("Model ID is required".to_string()));
+        }
+        if self.seed < -1 {
+            return Err(AppError::Validation(
+                "Seed must be -1 (random) or a non-negative number".to_string(),
+            ));
+        }
+        if !(1..=MAX_STEPS).contains(&self.steps) {
+            return Err(AppError::Validation(format!(
+                "Steps must be between 1 and {MAX_STEPS}"
+            )));
+        }
+        if !(0.0..=MAX_CFG_SCALE).contains(&self.cfg_scale) {
+            return Err(AppError::Validation(format!(
+                "CFG scale must be between 0 and {MAX_CFG_SCALE}"
+            )));
+        }
+        match (self.width, self.height) {
+            (None, None) => {}
+            (Some(width), Some(height)) if width > 0 && height > 0 => {}
+            (Some(_), Some(_)) => {
+                return Err(AppError::Validation(
+                    "Width and height must be positive".to_string(),
+                ));
+            }
+            _ => {
+                return Err(AppError::Validation(
+                    "Set both width and height, or neither".to_string(),
+                ));
+            }
+        }
+        for entry in &self.conditioning {
+            if entry.model.trim().is_empty() {
+                return Err(AppError::Validation(
+                    "Conditioning model is required".to_string(),
+                ));
+            }
+            if !Path::new(entry.image_path.trim()).is_absolute() {
+                return Err(AppError::Validation(format!(
+                    "Invalid reference image path '{}': expected an absolute file path",
+                    entry.image_path
+                )));
+            }
+            if !(0.0..=MAX_CONDITIONING_WEIGHT).contains(&entry.weight) {
+                return Err(AppError::Validation(format!(
+                    "Conditioning weight must be between 0 and {MAX_CONDITIONING_WEIGHT}"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Lists CFG scale and step settings outside the model's recommended range.
+    ///
+    /// # Arguments
+    ///
+    /// * `range` - Recommended sampling settings for `model_id`
+    /// * `locale` - Language of the warning messages
+    #[must_use]
+    pub fn sampling_warnings(&self, range: &SamplingRange, locale: Locale) -> Vec<ParamWarning> {
+        let mut warnings = Vec::new();
+
+        if self.cfg_scale < range.min_cfg_scale || self.cfg_scale > range.max_cfg_scale {
+            let message = Message::new("param.cfg_scale_out_of_range")
+                .arg("value", self.cfg_scale)
+                .arg("min", range.min_cfg_scale)
+                .arg("max", range.max_cfg_scale)
+                .arg("model", &self.model_id);
+            warnings.push(ParamWarning::new("cfg_scale", &message, locale));
+        }
+        if self.steps < range.min_steps || self.steps > range.max_steps {
+            let message = Message::new("param.steps_out_of_range")
+                .arg("value", self.steps)
+                .arg("min", range.min_steps)
+                .arg("max", range.max_steps)
+                .arg("model", &self.model_id);
+            warnings.push(ParamWarning::new("steps", &message, locale));
+        }
+
+        warnings
+    }
 }
 
 impl Default for GenerationParams {
@@ -203,6 +666,9 @@ impl Default for GenerationParams {
             cfg_scale: 7.0,
             sampler: None,
             scheduler: None,
+            width: None,
+            height: None,
+            conditioning: Vec::new(),
         }
     }
 }
@@ -9,9 +9,13 @@
 //! The `PromptComposer` processes tokens through these stages:
 //!
 //! 1. **Granularity Selection**: Filter to specified levels or use all
-//! 2. **Ordering**: Sort by global `display_order` (user-defined sequence)
-//! 3. **Polarity Separation**: Route tokens to positive or negative output
-//! 4. **Weight Formatting**: Apply `(token:weight)` syntax if enabled
+//! 2. **Ordering**: Sort by global `display_order` (user-defined sequence), then
+//!    optionally group by granularity (see [`TokenOrder`])
+//! 3. **Polarity Separation**: Route tokens to positive or negative output;
+//!    negative tokens can be dropped for models that ignore negative prompts
+//!    (see [`UnsupportedNegativeMode`])
+//! 4. **Weight Formatting**: Apply `(token:weight)` syntax (or another
+//!    [`WeightSyntax`]) if enabled
 //! 5. **Ad-hoc Injection**: Insert additional tokens at beginning or end
 //! 6. **Assembly**: Join with separator and create breakdown
 //!
@@ -21,10 +25,51 @@
 //! - Tokens joined by commas: `token1, token2, token3`
 //! - Weighted tokens: `(emphasized token:1.2)`
 //! - Separate positive and negative prompt strings
+//!
+//! [`PromptComposer::preview`] additionally renders the result as plain text,
+//! A1111 infotext, `ComfyUI` node JSON, and a JSON list of parts.
+//!
+//! # Persona Defaults
+//!
+//! A persona can store [`CompositionDefaults`] (separator, weights, weight
+//! syntax), used whenever a prompt is composed without explicit options, so
+//! personas for natural-language models can default to ". " separators.
+//!
+//! # Dual Prompts
+//!
+//! SDXL and FLUX have two text encoders, each of which can take its own
+//! prompt: `prompt` for CLIP ViT-L, and `prompt_2` for `OpenCLIP` bigG (SDXL)
+//! or T5 (FLUX). With [`DualPromptOptions`], positive tokens are routed per
+//! granularity to either prompt or both, and `prompt_2` gets its own
+//! separator and weight setting, so CLIP can read tags while T5 reads
+//! sentences. The negative prompt is shared by both encoders.
+//!
+//! # Regional Prompts
+//!
+//! With [`RegionalOptions`], granularities are grouped into image regions
+//! (e.g., face, body, background) and the positive prompt is written in
+//! Regional Prompter (`ADDCOMM` / `BREAK`) or attention couple (`AND`)
+//! syntax, so each region only describes its own area.
+//!
+//! # Conditioning
+//!
+//! The `ControlNet` and IP-Adapter entries of the generation parameters are
+//! written into the A1111 infotext as `ControlNet N` units and into the
+//! `ComfyUI` snippet as loader, image, and apply nodes.
 
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::{Digest, Sha256};
 
-use super::token::{GranularityLevel, Token, TokenPolarity};
+use super::banned_term::FilteredToken;
+use super::persona::{ConditioningEntry, ConditioningKind, GenerationParams};
+use super::token::{Granularity, GranularityLevel, Token, TokenPolarity};
+use crate::error::AppError;
 
 /// The final assembled prompt ready for image generation.
 ///
@@ -34,6 +79,10 @@ use super::token::{GranularityLevel, Token, TokenPolarity};
 pub struct ComposedPrompt {
     /// The positive prompt string (desired characteristics)
     pub positive_prompt: String,
+    /// The positive prompt for the second text encoder (`prompt_2`), if dual
+    /// prompts were requested
+    #[serde(default)]
+    pub positive_prompt_2: Option<String>,
     /// The negative prompt string (undesired characteristics)
     pub negative_prompt: String,
     /// Count of positive token parts (including ad-hoc)
@@ -42,6 +91,15 @@ pub struct ComposedPrompt {
     pub negative_token_count: usize,
     /// Detailed breakdown by granularity level
     pub breakdown: PromptBreakdown,
+    /// Whether the target image model ignores negative prompts. The negative
+    /// prompt is still composed unless [`UnsupportedNegativeMode::Suppress`]
+    /// was requested, so the UI can warn about it.
+    #[serde(default)]
+    pub negative_prompt_unsupported: bool,
+    /// Stored tokens left out because they contain a banned term (see
+    /// [`super::banned_term`])
+    #[serde(default)]
+    pub filtered_tokens: Vec<FilteredToken>,
 }
 
 /// Breakdown showing which tokens contributed from each granularity level.
@@ -69,6 +127,53 @@ pub struct GranularitySection {
     pub negative_tokens: Vec<String>,
 }
 
+/// A composed prompt together with every copy format.
+///
+/// Lets the UI offer one copy button per format without recomposing.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptPreview {
+    /// The composed prompt, as returned by `compose_prompt`
+    pub composed: ComposedPrompt,
+    /// The prompt rendered in each copy format
+    pub formats: PromptFormats,
+}
+
+/// Copy-ready encodings of a composed prompt.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptFormats {
+    /// "Positive:" and "Negative:" blocks separated by a blank line
+    pub plain: String,
+    /// AUTOMATIC1111 infotext (prompt, `Negative prompt:` line, settings line)
+    pub a1111: String,
+    /// `ComfyUI` API-format JSON with a text encoding node per prompt
+    pub comfyui: String,
+    /// Pretty-printed JSON of the individual parts and the breakdown
+    pub json: String,
+}
+
+/// The last composed prompt stored for a persona.
+///
+/// Lets list views show a prompt preview and length without recomposing.
+/// The entry is dropped whenever the persona's tokens or generation
+/// parameters change.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedPrompt {
+    /// UUID of the persona the prompt was composed for
+    pub persona_id: String,
+    /// [`CompositionOptions::cache_key`] of the options used
+    pub options_hash: String,
+    /// The positive prompt string
+    pub positive_prompt: String,
+    /// The negative prompt string
+    pub negative_prompt: String,
+    /// Count of positive token parts
+    pub positive_token_count: usize,
+    /// Count of negative token parts
+    pub negative_token_count: usize,
+    /// When the prompt was composed
+    pub created_at: DateTime<Utc>,
+}
+
 /// Configuration options for prompt composition.
 ///
 /// All fields have sensible defaults via `Default` implementation.
@@ -92,6 +197,247 @@ pub struct CompositionOptions {
     /// Placement of ad-hoc tokens (default: End)
     #[serde(default)]
     pub adhoc_position: AdhocPosition,
+    /// Token ordering strategy (default: Global)
+    #[serde(default)]
+    pub order_by: TokenOrder,
+    /// Handling of negative tokens when the image model ignores negative
+    /// prompts (default: Warn)
+    #[serde(default)]
+    pub unsupported_negative: UnsupportedNegativeMode,
+    /// How weights are written when `include_weights` is set (default: A1111)
+    #[serde(default)]
+    pub weight_syntax: WeightSyntax,
+    /// Compose a separate `prompt_2` for the second text encoder (default:
+    /// single prompt)
+    #[serde(default)]
+    pub dual_prompt: Option<DualPromptOptions>,
+    /// Split the positive prompt into regions (default: one prompt for the
+    /// whole image)
+    #[serde(default)]
+    pub regional: Option<RegionalOptions>,
+    /// Replace aliased tokens with their preferred phrasing (default: off;
+    /// see `domain::alias`)
+    #[serde(default)]
+    pub apply_aliases: bool,
+}
+
+/// A persona's own composition settings, used when no options are given.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct CompositionDefaults {
+    /// String used to join tokens (e.g., ", " for tags, ". " for sentences)
+    pub separator: String,
+    /// Whether to apply weight formatting to tokens
+    pub include_weights: bool,
+    /// How weights are written
+    #[serde(default)]
+    pub weight_syntax: WeightSyntax,
+    /// Dual prompt settings, if the persona composes a separate `prompt_2`
+    #[serde(default)]
+    pub dual_prompt: Option<DualPromptOptions>,
+}
+
+impl CompositionDefaults {
+    /// Validates that the separator is usable.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Validation` if the separator, or the dual prompt
+    /// separator, is empty.
+    pub fn validate(&self) -> Result<(), AppError> {
+        if self.separator.is_empty()
+            || self
+                .dual_prompt
+                .as_ref()
+                .is_some_and(|dual| dual.separator.is_empty())
+        {
+            return Err(AppError::Validation(
+                "Separator must not be empty".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Returns composition options with these settings and defaults otherwise.
+    #[must_use]
+    pub fn to_options(&self) -> CompositionOptions {
+        CompositionOptions {
+            separator: self.separator.clone(),
+            include_weights: self.include_weights,
+            weight_syntax: self.weight_syntax,
+            dual_prompt: self.dual_prompt.clone(),
+            ..CompositionOptions::default()
+        }
+    }
+}
+
+/// Which prompt a granularity's positive tokens go to when composing dual
+/// prompts.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum PromptTarget {
+    /// `prompt` only, read by CLIP ViT-L
+    Primary,
+    /// `prompt_2` only, read by `OpenCLIP` bigG (SDXL) or T5 (FLUX)
+    Secondary,
+    /// Both prompts
+    #[default]
+    Both,
+}
+
+/// Settings for composing a separate `prompt_2` for the second text encoder.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct DualPromptOptions {
+    /// Target prompt by granularity ID; unlisted granularities go to both
+    #[serde(default)]
+    pub routing: BTreeMap<String, PromptTarget>,
+    /// String used to join tokens in `prompt_2` (default: ". ")
+    #[serde(default = "default_dual_prompt_separator")]
+    pub separator: String,
+    /// Whether weights are written in `prompt_2` (default: false, since T5
+    /// reads weight syntax as literal text)
+    #[serde(default)]
+    pub include_weights: bool,
+}
+
+impl DualPromptOptions {
+    /// Returns the prompt a granularity's positive tokens go to.
+    #[must_use]
+    pub fn target(&self, granularity_id: &str) -> PromptTarget {
+        self.routing
+            .get(granularity_id)
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
+impl Default for DualPromptOptions {
+    fn default() -> Self {
+        Self {
+            routing: BTreeMap::new(),
+            separator: default_dual_prompt_separator(),
+            include_weights: false,
+        }
+    }
+}
+
+fn default_dual_prompt_separator() -> String {
+    ". ".to_string()
+}
+
+/// Syntax used to split a prompt into regions.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RegionalSyntax {
+    /// Regional Prompter (A1111, Forge): common prompt followed by `ADDCOMM`,
+    /// regions separated by `BREAK`
+    #[default]
+    RegionalPrompter,
+    /// Attention couple and Latent Couple: base prompt, then one `AND`
+    /// subprompt per region
+    AttentionCouple,
+}
+
+/// An area of the image and the granularities described in it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PromptRegion {
+    /// Region name, for display (e.g., "Face")
+    pub name: String,
+    /// Granularity IDs whose tokens go to this region
+    pub granularity_ids: Vec<String>,
+}
+
+/// Settings for splitting the positive prompt into regions.
+///
+/// Regions are listed in the order the extension assigns areas (e.g., top
+/// to bottom). Granularities in no region form the common prompt, shared by
+/// every region. The negative prompt is never split.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RegionalOptions {
+    /// Regions in area order
+    pub regions: Vec<PromptRegion>,
+    /// Syntax to emit (default: Regional Prompter)
+    #[serde(default)]
+    pub syntax: RegionalSyntax,
+}
+
+impl Default for RegionalOptions {
+    /// Face, body, and background regions for character work; style and
+    /// general traits stay common. The background region takes tokens of a
+    /// custom `background` granularity, if the user created one.
+    fn default() -> Self {
+        let region = |name: &str, granularities: &[Granularity]| PromptRegion {
+            name: name.to_string(),
+            granularity_ids: granularities
+                .iter()
+                .map(|g| g.as_str().to_string())
+                .collect(),
+        };
+
+        Self {
+            regions: vec![
+                region("Face", &[Granularity::Hair, Granularity::Face]),
+                region(
+                    "Body",
+                    &[
+                        Granularity::UpperBody,
+                        Granularity::Midsection,
+                        Granularity::LowerBody,
+                    ],
+                ),
+                PromptRegion {
+                    name: "Background".to_string(),
+                    granularity_ids: vec!["background".to_string()],
+                },
+            ],
+            syntax: RegionalSyntax::default(),
+        }
+    }
+}
+
+/// Determines how token weights are written in the composed prompt.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum WeightSyntax {
+    /// `(token:1.2)`, read by A1111, Forge, and `ComfyUI`
+    #[default]
+    A1111,
+    /// `(token)1.2`, read by Compel (diffusers, `InvokeAI`)
+    Compel,
+}
+
+impl WeightSyntax {
+    /// Returns the string representation for database storage.
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::A1111 => "a1111",
+            Self::Compel => "compel",
+        }
+    }
+
+    /// Parses a syntax from its database representation.
+    #[must_use]
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "a1111" => Some(Self::A1111),
+            "compel" => Some(Self::Compel),
+            _ => None,
+        }
+    }
+
+    /// Formats a token with its weight in this syntax.
+    ///
+    /// Tokens weighted 1.0 are returned as-is.
+    #[must_use]
+    pub fn format_token(&self, token: &Token) -> String {
+        match self {
+            Self::A1111 => token.format_for_prompt(true),
+            Self::Compel if (token.weight - 1.0).abs() > f64::EPSILON => {
+                format!("({}){:.1}", token.content, token.weight)
+            }
+            Self::Compel => token.content.clone(),
+        }
+    }
 }
 
 const fn default_prompt_include_weights() -> bool {
@@ -113,6 +459,70 @@ pub enum AdhocPosition {
     End,
 }
 
+/// Determines what happens to negative tokens when the target image model
+/// does not use a negative prompt (e.g., FLUX or Turbo models).
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum UnsupportedNegativeMode {
+    /// Compose the negative prompt anyway and flag it as unsupported
+    #[default]
+    Warn,
+    /// Leave the negative prompt empty
+    Suppress,
+}
+
+/// Determines the order of tokens in the composed prompt.
+///
+/// Within a granularity, tokens always keep their global `display_order`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenOrder {
+    /// Follow the global `display_order` (drag-and-drop order)
+    #[default]
+    Global,
+    /// Group by granularity in level order (Style first), then `display_order`
+    GranularityThenOrder,
+    /// Group by the listed granularity IDs first, in the given order; tokens of
+    /// unlisted granularities follow in global order
+    Custom(Vec<String>),
+}
+
+impl TokenOrder {
+    /// Returns the sort rank of a granularity; tokens are stably sorted by it.
+    fn rank(&self, granularity_id: &str, granularity_levels: &[GranularityLevel]) -> usize {
+        match self {
+            Self::Global => 0,
+            Self::GranularityThenOrder => granularity_levels
+                .iter()
+                .position(|l| l.id == granularity_id)
+                .unwrap_or(granularity_levels.len()),
+            Self::Custom(ids) => ids
+                .iter()
+                .position(|id| id == granularity_id)
+                .unwrap_or(ids.len()),
+        }
+    }
+}
+
+impl CompositionOptions {
+    /// Returns a stable hash of these options, used to tell cached prompts
+    /// composed with different settings apart.
+    #[must_use]
+    pub fn cache_key(&self) -> String {
+        // Serializing plain strings, booleans, and lists cannot fail
+        let serialized = serde_json::to_string(self).unwrap_or_default();
+        format!("{:x}", Sha256::digest(serialized.as_bytes()))
+    }
+
+    /// Returns true if ad-hoc tokens would be injected into the prompt.
+    #[must_use]
+    pub fn has_adhoc(&self) -> bool {
+        [&self.adhoc_positive, &self.adhoc_negative]
+            .iter()
+            .any(|adhoc| adhoc.as_deref().is_some_and(|s| !s.trim().is_empty()))
+    }
+}
+
 impl Default for CompositionOptions {
     fn default() -> Self {
         Self {
@@ -122,6 +532,12 @@ impl Default for CompositionOptions {
             adhoc_positive: None,
             adhoc_negative: None,
             adhoc_position: AdhocPosition::End,
+            order_by: TokenOrder::Global,
+            unsupported_negative: UnsupportedNegativeMode::Warn,
+            weight_syntax: WeightSyntax::A1111,
+            dual_prompt: None,
+            regional: None,
+            apply_aliases: false,
         }
     }
 }
@@ -139,11 +555,15 @@ impl PromptComposer {
     /// * `tokens` - All tokens for the persona
     /// * `granularity_levels` - Available granularity level definitions
     /// * `options` - Composition configuration
+    /// * `supports_negative_prompt` - Whether the target image model uses a
+    ///   negative prompt (see [`UnsupportedNegativeMode`])
     ///
     /// # Algorithm
     ///
-    /// 1. Filter tokens by selected granularity levels (or use all)
-    /// 2. Sort tokens by global `display_order` (user-defined sequence)
+    /// 1. Filter tokens by selected granularity levels (or use all), dropping
+    ///    negative tokens if the model ignores them and suppression is requested
+    /// 2. Sort tokens by global `display_order` (user-defined sequence), then
+    ///    stably by granularity if `order_by` requests it
     /// 3. Optionally inject ad-hoc tokens at the beginning
     /// 4. Process each token in order:
     ///    - Format token (apply weight if configured)
@@ -151,16 +571,107 @@ impl PromptComposer {
     ///    - Track breakdown by granularity for UI display
     /// 5. Optionally inject ad-hoc tokens at the end
     /// 6. Join parts with separator
+    ///
+    /// With `dual_prompt` set, positive tokens are also routed to `prompt_2`
+    /// by granularity (see [`DualPromptOptions`]); ad-hoc positive tokens go
+    /// to both prompts.
     #[must_use]
     pub fn compose(
         tokens: &[Token],
         granularity_levels: &[GranularityLevel],
         options: &CompositionOptions,
+        supports_negative_prompt: bool,
     ) -> ComposedPrompt {
+        Self::collect_parts(
+            tokens,
+            granularity_levels,
+            options,
+            supports_negative_prompt,
+        )
+        .into_composed(options)
+    }
+
+    /// Composes a prompt and renders it in every copy format at once.
+    ///
+    /// Uses the same algorithm as [`Self::compose`]. `params`, when given, adds
+    /// the generation settings line to the A1111 format. Nothing is persisted;
+    /// ad-hoc tokens only appear in the returned strings.
+    ///
+    /// `model_family` (e.g., "sdxl", "flux") selects the `ComfyUI` node that
+    /// takes both prompts when dual prompts are composed.
+    #[must_use]
+    pub fn preview(
+        tokens: &[Token],
+        granularity_levels: &[GranularityLevel],
+        options: &CompositionOptions,
+        supports_negative_prompt: bool,
+        model_family: &str,
+        params: Option<&GenerationParams>,
+    ) -> PromptPreview {
+        let parts = Self::collect_parts(
+            tokens,
+            granularity_levels,
+            options,
+            supports_negative_prompt,
+        );
+        let positive_parts = parts.positive.clone();
+        let positive_parts_2 = parts
+            .positive_2
+            .as_ref()
+            .map(|(parts_2, _)| parts_2.clone());
+        let negative_parts = parts.negative.clone();
+        let composed = parts.into_composed(options);
+
+        let plain = match &composed.positive_prompt_2 {
+            Some(positive_prompt_2) => format!(
+                "Positive:\n{}\n\nPositive (prompt_2):\n{}\n\nNegative:\n{}",
+                composed.positive_prompt, positive_prompt_2, composed.negative_prompt
+            ),
+            None => format!(
+                "Positive:\n{}\n\nNegative:\n{}",
+                composed.positive_prompt, composed.negative_prompt
+            ),
+        };
+        let mut parts_json = json!({
+            "positive": positive_parts,
+            "negative": negative_parts,
+            "sections": composed.breakdown.sections,
+        });
+        if let Some(positive_parts_2) = positive_parts_2 {
+            parts_json["positive_2"] = json!(positive_parts_2);
+        }
+
+        let formats = PromptFormats {
+            plain,
+            a1111: format_a1111(&composed, params),
+            comfyui: format_comfyui(&composed, model_family, params),
+            json: format!("{parts_json:#}"),
+        };
+
+        PromptPreview { composed, formats }
+    }
+
+    /// Filters, orders, and formats tokens into prompt parts.
+    fn collect_parts(
+        tokens: &[Token],
+        granularity_levels: &[GranularityLevel],
+        options: &CompositionOptions,
+        supports_negative_prompt: bool,
+    ) -> PromptParts {
         use std::collections::HashMap;
 
+        let include_negative = supports_negative_prompt
+            || options.unsupported_negative == UnsupportedNegativeMode::Warn;
+
         let mut positive_parts: Vec<String> = Vec::new();
         let mut negative_parts: Vec<String> = Vec::new();
+        // Granularity each positive part came from (None for ad-hoc tokens),
+        // used to split the prompt into regions
+        let mut positive_sources: Vec<Option<String>> = Vec::new();
+        // Only used when dual prompts are requested
+        let dual_prompt = options.dual_prompt.as_ref();
+        let mut positive_parts_2: Vec<String> = Vec::new();
+        let mut positive_sources_2: Vec<Option<String>> = Vec::new();
 
         // Determine which granularities to include
         let allowed_granularities: Option<std::collections::HashSet<&str>> =
@@ -179,6 +690,7 @@ impl PromptComposer {
         // Filter and sort tokens by global display_order
         let mut sorted_tokens: Vec<&Token> = tokens
             .iter()
+            .filter(|t| include_negative || t.polarity == TokenPolarity::Positive)
             .filter(|t| {
                 allowed_granularities
                     .as_ref()
@@ -186,6 +698,10 @@ impl PromptComposer {
             })
             .collect();
         sorted_tokens.sort_by_key(|t| t.display_order);
+        if options.order_by != TokenOrder::Global {
+            sorted_tokens
+                .sort_by_key(|t| options.order_by.rank(&t.granularity_id, granularity_levels));
+        }
 
         // Track breakdown by granularity (for informational purposes)
         let mut section_map: HashMap<String, GranularitySection> = HashMap::new();
@@ -195,9 +711,12 @@ impl PromptComposer {
             if let Some(adhoc) = &options.adhoc_positive {
                 if !adhoc.trim().is_empty() {
                     positive_parts.push(adhoc.trim().to_string());
+                    positive_sources.push(None);
+                    positive_parts_2.push(adhoc.trim().to_string());
+                    positive_sources_2.push(None);
                 }
             }
-            if let Some(adhoc) = &options.adhoc_negative {
+            if let Some(adhoc) = options.adhoc_negative.as_ref().filter(|_| include_negative) {
                 if !adhoc.trim().is_empty() {
                     negative_parts.push(adhoc.trim().to_string());
                 }
@@ -206,11 +725,30 @@ impl PromptComposer {
 
         // Process tokens in user-defined order
         for token in sorted_tokens {
-            let formatted = token.format_for_prompt(options.include_weights);
+            let formatted = if options.include_weights {
+                options.weight_syntax.format_token(token)
+            } else {
+                token.format_for_prompt(false)
+            };
 
             match token.polarity {
                 TokenPolarity::Positive => {
-                    positive_parts.push(formatted.clone());
+                    // Without dual prompts, everything goes to the one prompt
+                    let target = dual_prompt.map_or(PromptTarget::Primary, |dual| {
+                        dual.target(&token.granularity_id)
+                    });
+                    if target != PromptTarget::Secondary {
+                        positive_parts.push(formatted.clone());
+                        positive_sources.push(Some(token.granularity_id.clone()));
+                    }
+                    if let Some(dual) = dual_prompt.filter(|_| target != PromptTarget::Primary) {
+                        positive_parts_2.push(if dual.include_weights {
+                            options.weight_syntax.format_token(token)
+                        } else {
+                            token.format_for_prompt(false)
+                        });
+                        positive_sources_2.push(Some(token.granularity_id.clone()));
+                    }
                 }
                 TokenPolarity::Negative => {
                     negative_parts.push(formatted.clone());
@@ -221,15 +759,14 @@ impl PromptComposer {
             let section = section_map
                 .entry(token.granularity_id.clone())
                 .or_insert_with(|| {
-                    let level = granularity_levels
-                        .iter()
-                        .find(|l| l.id == token.granularity_id);
+                    let level = GranularityLevel::find_or_fallback(
+                        granularity_levels,
+                        &token.granularity_id,
+                    );
                     GranularitySection {
-                        granularity_id: token.granularity_id.clone(),
-                        granularity_name: level
-                            .map_or_else(|| "Unknown".to_string(), |l| l.name.clone()),
-                        granularity_color: level
-                            .map_or_else(|| "base".to_string(), |l| l.color.clone()),
+                        granularity_id: level.id,
+                        granularity_name: level.name,
+                        granularity_color: level.color,
                         positive_tokens: Vec::new(),
                         negative_tokens: Vec::new(),
                     }
@@ -246,9 +783,12 @@ impl PromptComposer {
             if let Some(adhoc) = &options.adhoc_positive {
                 if !adhoc.trim().is_empty() {
                     positive_parts.push(adhoc.trim().to_string());
+                    positive_sources.push(None);
+                    positive_parts_2.push(adhoc.trim().to_string());
+                    positive_sources_2.push(None);
                 }
             }
-            if let Some(adhoc) = &options.adhoc_negative {
+            if let Some(adhoc) = options.adhoc_negative.as_ref().filter(|_| include_negative) {
                 if !adhoc.trim().is_empty() {
                     negative_parts.push(adhoc.trim().to_string());
                 }
@@ -260,15 +800,1000 @@ impl PromptComposer {
             .iter()
             .filter_map(|l| section_map.remove(&l.id))
             .collect();
-        // Add any remaining sections (unknown granularities) at the end
-        sections.extend(section_map.into_values());
+        // Add any remaining sections (unknown granularities) at the end, sorted by ID
+        let mut unknown_sections: Vec<GranularitySection> = section_map.into_values().collect();
+        unknown_sections.sort_by(|a, b| a.granularity_id.cmp(&b.granularity_id));
+        sections.extend(unknown_sections);
+
+        PromptParts {
+            positive: positive_parts,
+            positive_sources,
+            positive_2: dual_prompt.map(|_| (positive_parts_2, positive_sources_2)),
+            negative: negative_parts,
+            sections,
+            negative_prompt_unsupported: !supports_negative_prompt,
+        }
+    }
+}
+
+/// Formatted prompt parts before joining.
+struct PromptParts {
+    positive: Vec<String>,
+    /// Granularity of each positive part, `None` for ad-hoc tokens
+    positive_sources: Vec<Option<String>>,
+    /// Parts of `prompt_2` and their granularities, if dual prompts were
+    /// requested
+    positive_2: Option<(Vec<String>, Vec<Option<String>>)>,
+    negative: Vec<String>,
+    sections: Vec<GranularitySection>,
+    negative_prompt_unsupported: bool,
+}
+
+impl PromptParts {
+    /// Joins the parts with the options' separators into the final prompt.
+    fn into_composed(self, options: &CompositionOptions) -> ComposedPrompt {
+        let separator = options.separator.as_str();
+        let separator_2 = options
+            .dual_prompt
+            .as_ref()
+            .map_or(separator, |dual| dual.separator.as_str());
+
+        let regional = options.regional.as_ref();
 
         ComposedPrompt {
-            positive_prompt: positive_parts.join(&options.separator),
-            negative_prompt: negative_parts.join(&options.separator),
-            positive_token_count: positive_parts.len(),
-            negative_token_count: negative_parts.len(),
-            breakdown: PromptBreakdown { sections },
+            positive_prompt: join_positive(
+                &self.positive,
+                &self.positive_sources,
+                separator,
+                regional,
+            ),
+            positive_prompt_2: self
+                .positive_2
+                .map(|(parts, sources)| join_positive(&parts, &sources, separator_2, regional)),
+            negative_prompt: self.negative.join(separator),
+            positive_token_count: self.positive.len(),
+            negative_token_count: self.negative.len(),
+            breakdown: PromptBreakdown {
+                sections: self.sections,
+            },
+            negative_prompt_unsupported: self.negative_prompt_unsupported,
+            filtered_tokens: Vec::new(),
+        }
+    }
+}
+
+/// Joins positive parts into a prompt, split into regions if requested.
+///
+/// Parts of granularities outside every region (and ad-hoc parts) form the
+/// common prompt; each region gets the parts of its granularities, in prompt
+/// order. Regions without parts are left out rather than written as empty
+/// segments. Attention couple always starts with the base segment (empty if
+/// there is no common prompt), so regions never shift into the base slot.
+fn join_positive(
+    parts: &[String],
+    sources: &[Option<String>],
+    separator: &str,
+    regional: Option<&RegionalOptions>,
+) -> String {
+    let Some(regional) = regional.filter(|regional| !regional.regions.is_empty()) else {
+        return parts.join(separator);
+    };
+
+    let in_region = |region: &PromptRegion, source: &Option<String>| {
+        source
+            .as_ref()
+            .is_some_and(|id| region.granularity_ids.contains(id))
+    };
+    let join_where = |include: &dyn Fn(&Option<String>) -> bool| {
+        parts
+            .iter()
+            .zip(sources)
+            .filter(|(_, source)| include(source))
+            .map(|(part, _)| part.as_str())
+            .collect::<Vec<_>>()
+            .join(separator)
+    };
+
+    let common = join_where(&|source| {
+        !regional
+            .regions
+            .iter()
+            .any(|region| in_region(region, source))
+    });
+    let regions: Vec<String> = regional
+        .regions
+        .iter()
+        .map(|region| join_where(&|source| in_region(region, source)))
+        .filter(|region| !region.is_empty())
+        .collect();
+
+    match regional.syntax {
+        RegionalSyntax::RegionalPrompter => {
+            let regions = regions.join(" BREAK\n");
+            if common.is_empty() {
+                regions
+            } else if regions.is_empty() {
+                common
+            } else {
+                format!("{common} ADDCOMM\n{regions}")
+            }
+        }
+        RegionalSyntax::AttentionCouple => {
+            let mut prompts = regions;
+            prompts.insert(0, common);
+            prompts.join("\nAND ")
+        }
+    }
+}
+
+/// Renders A1111 "infotext": the positive prompt, a `Negative prompt:` line,
+/// and the generation settings line if parameters are known.
+///
+/// Infotext has no field for a second prompt; `prompt_2` is left out.
+fn format_a1111(composed: &ComposedPrompt, params: Option<&GenerationParams>) -> String {
+    let mut text = composed.positive_prompt.clone();
+    if !composed.negative_prompt.is_empty() {
+        text.push_str(&format!("\nNegative prompt: {}", composed.negative_prompt));
+    }
+
+    if let Some(params) = params {
+        let mut settings = vec![format!("Steps: {}", params.steps)];
+        if let Some(sampler) = params.sampler.as_deref().filter(|s| !s.is_empty()) {
+            settings.push(format!("Sampler: {sampler}"));
+        }
+        if let Some(scheduler) = params.scheduler.as_deref().filter(|s| !s.is_empty()) {
+            settings.push(format!("Schedule type: {scheduler}"));
+        }
+        settings.push(format!("CFG scale: {}", params.cfg_scale));
+        if let (Some(width), Some(height)) = (params.width, params.height) {
+            settings.push(format!("Size: {width}x{height}"));
+        }
+        // -1 means a random seed, which A1111 expresses by omitting it
+        if params.seed >= 0 {
+            settings.push(format!("Seed: {}", params.seed));
+        }
+        // The ControlNet extension runs IP-Adapter models as ControlNet units too
+        for (index, entry) in params.conditioning.iter().enumerate() {
+            let module = entry
+                .preprocessor
+                .as_deref()
+                .filter(|p| !p.is_empty())
+                .unwrap_or("none");
+            settings.push(format!(
+                "ControlNet {index}: \"Module: {module}, Model: {}, Weight: {}\"",
+                entry.model, entry.weight
+            ));
+        }
+        text.push_str(&format!("\n{}", settings.join(", ")));
+    }
+
+    text
+}
+
+/// FLUX guidance written into `CLIPTextEncodeFlux` nodes (the FLUX dev default).
+const FLUX_GUIDANCE: f64 = 3.5;
+
+/// Output size assumed for `CLIPTextEncodeSDXL` nodes without saved parameters.
+const SDXL_DEFAULT_SIZE: u32 = 1024;
+
+/// Renders a `ComfyUI` API-format snippet with one text encoding node per
+/// prompt, ready to paste into a workflow.
+///
+/// Dual prompts use the node that takes both: `CLIPTextEncodeFlux` (CLIP-L
+/// and T5-XXL) for FLUX, `CLIPTextEncodeSDXL` (CLIP-L and CLIP-G) otherwise.
+/// Conditioning entries are added as described in [`conditioning_nodes`].
+fn format_comfyui(
+    composed: &ComposedPrompt,
+    model_family: &str,
+    params: Option<&GenerationParams>,
+) -> String {
+    let positive = match &composed.positive_prompt_2 {
+        Some(positive_prompt_2) if model_family == "flux" => json!({
+            "class_type": "CLIPTextEncodeFlux",
+            "inputs": {
+                "clip_l": composed.positive_prompt,
+                "t5xxl": positive_prompt_2,
+                "guidance": FLUX_GUIDANCE
+            },
+            "_meta": { "title": "Positive Prompt" }
+        }),
+        Some(positive_prompt_2) => {
+            let width = params.and_then(|p| p.width).unwrap_or(SDXL_DEFAULT_SIZE);
+            let height = params.and_then(|p| p.height).unwrap_or(SDXL_DEFAULT_SIZE);
+            json!({
+                "class_type": "CLIPTextEncodeSDXL",
+                "inputs": {
+                    "text_l": composed.positive_prompt,
+                    "text_g": positive_prompt_2,
+                    "width": width,
+                    "height": height,
+                    "crop_w": 0,
+                    "crop_h": 0,
+                    "target_width": width,
+                    "target_height": height
+                },
+                "_meta": { "title": "Positive Prompt" }
+            })
+        }
+        None => json!({
+            "class_type": "CLIPTextEncode",
+            "inputs": { "text": composed.positive_prompt },
+            "_meta": { "title": "Positive Prompt" }
+        }),
+    };
+
+    let mut nodes = json!({
+        "positive": positive,
+        "negative": {
+            "class_type": "CLIPTextEncode",
+            "inputs": { "text": composed.negative_prompt },
+            "_meta": { "title": "Negative Prompt" }
+        }
+    });
+    if let Some(params) = params {
+        for (id, node) in conditioning_nodes(&params.conditioning) {
+            nodes[id] = node;
+        }
+    }
+
+    format!("{nodes:#}")
+}
+
+/// Builds `ComfyUI` nodes for `ControlNet` and IP-Adapter entries.
+///
+/// `ControlNet` units are chained with `ControlNetApplyAdvanced` after the
+/// prompt nodes; IP-Adapter units (`ComfyUI_IPAdapter_plus`) are chained on
+/// the model, whose first input and CLIP vision input are left for the
+/// workflow to connect. Reference images are loaded by file name, so they
+/// must be copied into `ComfyUI`'s input folder; preprocessors are not added.
+fn conditioning_nodes(entries: &[ConditioningEntry]) -> Vec<(String, serde_json::Value)> {
+    let mut nodes = Vec::new();
+    // Links to the current positive and negative conditioning outputs
+    let mut conditioning = (json!(["positive", 0]), json!(["negative", 0]));
+    let mut model: Option<String> = None;
+
+    for (index, entry) in entries.iter().enumerate() {
+        let image_name = Path::new(&entry.image_path).file_name().map_or_else(
+            || entry.image_path.clone(),
+            |name| name.to_string_lossy().into_owned(),
+        );
+        // Widened from f32, which would print as e.g. 0.6000000238418579
+        let weight = (f64::from(entry.weight) * 100.0).round() / 100.0;
+        let prefix = match entry.kind {
+            ConditioningKind::ControlNet => format!("controlnet_{index}"),
+            ConditioningKind::IpAdapter => format!("ip_adapter_{index}"),
+        };
+        nodes.push((
+            format!("{prefix}_image"),
+            json!({
+                "class_type": "LoadImage",
+                "inputs": { "image": image_name },
+                "_meta": { "title": format!("Reference {index}") }
+            }),
+        ));
+
+        match entry.kind {
+            ConditioningKind::ControlNet => {
+                nodes.push((
+                    format!("{prefix}_model"),
+                    json!({
+                        "class_type": "ControlNetLoader",
+                        "inputs": { "control_net_name": entry.model }
+                    }),
+                ));
+                nodes.push((
+                    prefix.clone(),
+                    json!({
+                        "class_type": "ControlNetApplyAdvanced",
+                        "inputs": {
+                            "positive": conditioning.0,
+                            "negative": conditioning.1,
+                            "control_net": [format!("{prefix}_model"), 0],
+                            "image": [format!("{prefix}_image"), 0],
+                            "strength": weight,
+                            "start_percent": 0.0,
+                            "end_percent": 1.0
+                        }
+                    }),
+                ));
+                conditioning = (json!([prefix, 0]), json!([prefix, 1]));
+            }
+            ConditioningKind::IpAdapter => {
+                let mut inputs = json!({
+                    "ipadapter": [format!("{prefix}_model"), 0],
+                    "image": [format!("{prefix}_image"), 0],
+                    "weight": weight,
+                    "weight_type": "linear",
+                    "combine_embeds": "concat",
+                    "start_at": 0.0,
+                    "end_at": 1.0,
+                    "embeds_scaling": "V only"
+                });
+                if let Some(model) = &model {
+                    inputs["model"] = json!([model, 0]);
+                }
+                nodes.push((
+                    format!("{prefix}_model"),
+                    json!({
+                        "class_type": "IPAdapterModelLoader",
+                        "inputs": { "ipadapter_file": entry.model }
+                    }),
+                ));
+                nodes.push((
+                    prefix.clone(),
+                    json!({ "class_type": "IPAdapterAdvanced", "inputs": inputs }),
+                ));
+                model = Some(prefix);
+            }
+        }
+    }
+
+    nodes
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    fn token(granularity_id: &str, content: &str, display_order: i32) -> Token {
+        weighted_token(
+            granularity_id,
+            TokenPolarity::Positive,
+            content,
+            1.0,
+            display_order,
+        )
+    }
+
+    fn weighted_token(
+        granularity_id: &str,
+        polarity: TokenPolarity,
+        content: &str,
+        weight: f64,
+        display_order: i32,
+    ) -> Token {
+        Token::new(
+            "persona".to_string(),
+            granularity_id.to_string(),
+            polarity,
+            content.to_string(),
+            weight,
+            display_order,
+        )
+    }
+
+    /// Weighted positive and negative tokens across style, hair, and face.
+    fn persona_tokens() -> Vec<Token> {
+        vec![
+            weighted_token("style", TokenPolarity::Positive, "masterpiece", 1.0, 0),
+            weighted_token("hair", TokenPolarity::Positive, "blue hair", 1.2, 1),
+            weighted_token("face", TokenPolarity::Positive, "smile", 0.8, 2),
+            weighted_token("style", TokenPolarity::Negative, "lowres", 1.0, 3),
+            weighted_token("face", TokenPolarity::Negative, "extra fingers", 1.3, 4),
+        ]
+    }
+
+    fn compose(tokens: &[Token], options: &CompositionOptions) -> ComposedPrompt {
+        PromptComposer::compose(tokens, &GranularityLevel::all(), options, true)
+    }
+
+    fn compose_ordered(tokens: &[Token], order_by: TokenOrder) -> String {
+        let options = CompositionOptions {
+            order_by,
+            ..CompositionOptions::default()
+        };
+        PromptComposer::compose(tokens, &GranularityLevel::all(), &options, true).positive_prompt
+    }
+
+    #[test]
+    fn global_order_follows_display_order_across_granularities() {
+        let tokens = [
+            token("style", "masterpiece", 2),
+            token("hair", "blue hair", 0),
+            token("face", "smile", 1),
+        ];
+
+        assert_eq!(
+            compose_ordered(&tokens, TokenOrder::Global),
+            "blue hair, smile, masterpiece"
+        );
+    }
+
+    #[test]
+    fn granularity_then_order_groups_by_level_order() {
+        let tokens = [
+            token("face", "smile", 0),
+            token("background", "city street", 1),
+            token("hair", "blue hair", 2),
+            token("style", "best quality", 4),
+            token("style", "masterpiece", 3),
+        ];
+
+        assert_eq!(
+            compose_ordered(&tokens, TokenOrder::GranularityThenOrder),
+            "masterpiece, best quality, blue hair, smile, city street"
+        );
+    }
+
+    #[test]
+    fn custom_order_lists_given_ids_first_then_the_rest_in_display_order() {
+        let tokens = [
+            token("style", "masterpiece", 0),
+            token("hair", "blue hair", 1),
+            token("face", "freckles", 3),
+            token("face", "smile", 2),
+            token("background", "city street", 4),
+            token("general", "tall", 5),
+        ];
+        let order = TokenOrder::Custom(vec![
+            "face".to_string(),
+            "missing".to_string(),
+            "hair".to_string(),
+        ]);
+
+        assert_eq!(
+            compose_ordered(&tokens, order),
+            "smile, freckles, blue hair, masterpiece, city street, tall"
+        );
+    }
+
+    #[test]
+    fn a1111_weights_golden_output() {
+        let composed = compose(&persona_tokens(), &CompositionOptions::default());
+
+        assert_eq!(
+            composed.positive_prompt,
+            "masterpiece, (blue hair:1.2), (smile:0.8)"
+        );
+        assert_eq!(composed.negative_prompt, "lowres, (extra fingers:1.3)");
+        assert_eq!(composed.positive_token_count, 3);
+        assert_eq!(composed.negative_token_count, 2);
+    }
+
+    #[test]
+    fn compel_weights_golden_output() {
+        let options = CompositionOptions {
+            weight_syntax: WeightSyntax::Compel,
+            ..CompositionOptions::default()
+        };
+        let composed = compose(&persona_tokens(), &options);
+
+        assert_eq!(
+            composed.positive_prompt,
+            "masterpiece, (blue hair)1.2, (smile)0.8"
+        );
+        assert_eq!(composed.negative_prompt, "lowres, (extra fingers)1.3");
+    }
+
+    #[test]
+    fn weights_are_left_out_when_disabled() {
+        for weight_syntax in [WeightSyntax::A1111, WeightSyntax::Compel] {
+            let options = CompositionOptions {
+                include_weights: false,
+                weight_syntax,
+                ..CompositionOptions::default()
+            };
+            let composed = compose(&persona_tokens(), &options);
+
+            assert_eq!(composed.positive_prompt, "masterpiece, blue hair, smile");
+            assert_eq!(composed.negative_prompt, "lowres, extra fingers");
+        }
+    }
+
+    /// Model families with the composition settings their personas use, and
+    /// whether the family reads a negative prompt.
+    fn golden_families() -> Vec<(&'static str, CompositionOptions, bool)> {
+        let tags = CompositionOptions::default;
+        let dual = || CompositionOptions {
+            dual_prompt: Some(DualPromptOptions::default()),
+            ..CompositionOptions::default()
+        };
+        let sentences = || CompositionOptions {
+            separator: ". ".to_string(),
+            include_weights: false,
+            ..CompositionOptions::default()
+        };
+
+        vec![
+            ("sd15", tags(), true),
+            ("sd2", tags(), true),
+            ("sdxl", dual(), true),
+            ("cascade", tags(), true),
+            ("kandinsky", tags(), true),
+            ("stable-diffusion", tags(), true),
+            ("flux", dual(), false),
+            ("pixart", sentences(), true),
+            ("hunyuan", sentences(), true),
+            ("kolors", sentences(), true),
+            ("deepfloyd", sentences(), true),
+        ]
+    }
+
+    /// Renders every copy format, plus the Compel weight syntax, as one
+    /// golden file.
+    fn render_golden(
+        family: &str,
+        options: &CompositionOptions,
+        supports_negative: bool,
+    ) -> String {
+        let levels = GranularityLevel::all();
+        let params = GenerationParams::default();
+        let preview = PromptComposer::preview(
+            &persona_tokens(),
+            &levels,
+            options,
+            supports_negative,
+            family,
+            Some(&params),
+        );
+        let compel = PromptComposer::compose(
+            &persona_tokens(),
+            &levels,
+            &CompositionOptions {
+                weight_syntax: WeightSyntax::Compel,
+                ..options.clone()
+            },
+            supports_negative,
+        );
+
+        // Key order depends on serde_json's `preserve_order` feature
+        let sorted_json = |text: &str| {
+            let mut value: serde_json::Value = serde_json::from_str(text).unwrap();
+            value.sort_all_objects();
+            format!("{value:#}")
+        };
+
+        format!(
+            "== plain ==\n{}\n== a1111 ==\n{}\n== comfyui ==\n{}\n== json ==\n{}\n\
+             == compel ==\n{}\n{}\n",
+            preview.formats.plain,
+            preview.formats.a1111,
+            sorted_json(&preview.formats.comfyui),
+            sorted_json(&preview.formats.json),
+            compel.positive_prompt,
+            compel.negative_prompt,
+        )
+    }
+
+    /// Compares each model family's output with `tests/golden/prompt/<family>.txt`.
+    ///
+    /// Run with `UPDATE_GOLDEN=1` to rewrite the files after an intended change.
+    #[test]
+    fn preview_matches_golden_files_per_model_family() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden/prompt");
+        let update = std::env::var_os("UPDATE_GOLDEN").is_some();
+
+        for (family, options, supports_negative) in golden_families() {
+            let rendered = render_golden(family, &options, supports_negative);
+            let path = dir.join(format!("{family}.txt"));
+            if update {
+                std::fs::write(&path, &rendered).unwrap();
+                continue;
+            }
+            let expected = std::fs::read_to_string(&path)
+                .unwrap_or_else(|e| panic!("missing golden file {}: {e}", path.display()));
+            assert_eq!(
+                rendered,
+                expected,
+                "{family} output differs from {}; rerun with UPDATE_GOLDEN=1 if intended",
+                path.display()
+            );
+        }
+    }
+
+    #[test]
+    fn composition_is_stable_across_input_order_and_calls() {
+        let tokens = persona_tokens();
+        let mut reversed = tokens.clone();
+        reversed.reverse();
+        let options = CompositionOptions::default();
+
+        let first = compose(&tokens, &options);
+        let second = compose(&tokens, &options);
+        let from_reversed = compose(&reversed, &options);
+
+        assert_eq!(first.positive_prompt, second.positive_prompt);
+        assert_eq!(first.positive_prompt, from_reversed.positive_prompt);
+        assert_eq!(first.negative_prompt, from_reversed.negative_prompt);
+    }
+
+    #[test]
+    fn tokens_sharing_a_display_order_keep_their_input_order() {
+        let tokens = [
+            token("hair", "blue hair", 0),
+            token("face", "smile", 0),
+            token("style", "masterpiece", 0),
+        ];
+
+        for order_by in [TokenOrder::Global, TokenOrder::Custom(vec![])] {
+            assert_eq!(
+                compose_ordered(&tokens, order_by),
+                "blue hair, smile, masterpiece"
+            );
+        }
+    }
+
+    #[test]
+    fn adhoc_tokens_are_placed_at_the_requested_end() {
+        let options = |adhoc_position| CompositionOptions {
+            include_weights: false,
+            adhoc_positive: Some("  looking at viewer ".to_string()),
+            adhoc_negative: Some("blurry".to_string()),
+            adhoc_position,
+            ..CompositionOptions::default()
+        };
+
+        let beginning = compose(&persona_tokens(), &options(AdhocPosition::Beginning));
+        assert_eq!(
+            beginning.positive_prompt,
+            "looking at viewer, masterpiece, blue hair, smile"
+        );
+        assert_eq!(beginning.negative_prompt, "blurry, lowres, extra fingers");
+
+        let end = compose(&persona_tokens(), &options(AdhocPosition::End));
+        assert_eq!(
+            end.positive_prompt,
+            "masterpiece, blue hair, smile, looking at viewer"
+        );
+        assert_eq!(end.negative_prompt, "lowres, extra fingers, blurry");
+
+        // Ad-hoc parts are counted but never attributed to a granularity
+        assert_eq!(end.positive_token_count, 4);
+        assert_eq!(end.negative_token_count, 3);
+        assert!(end.breakdown.sections.iter().all(|section| {
+            !section
+                .positive_tokens
+                .contains(&"looking at viewer".to_string())
+                && !section.negative_tokens.contains(&"blurry".to_string())
+        }));
+    }
+
+    #[test]
+    fn adhoc_tokens_go_to_both_dual_prompts() {
+        let options = CompositionOptions {
+            adhoc_positive: Some("looking at viewer".to_string()),
+            adhoc_position: AdhocPosition::Beginning,
+            dual_prompt: Some(DualPromptOptions {
+                routing: BTreeMap::from([
+                    ("style".to_string(), PromptTarget::Primary),
+                    ("hair".to_string(), PromptTarget::Secondary),
+                    ("face".to_string(), PromptTarget::Secondary),
+                ]),
+                ..DualPromptOptions::default()
+            }),
+            ..CompositionOptions::default()
+        };
+        let composed = compose(&persona_tokens(), &options);
+
+        assert_eq!(composed.positive_prompt, "looking at viewer, masterpiece");
+        assert_eq!(
+            composed.positive_prompt_2.as_deref(),
+            Some("looking at viewer. blue hair. smile")
+        );
+    }
+
+    #[test]
+    fn blank_or_suppressed_adhoc_tokens_are_left_out() {
+        let blank = CompositionOptions {
+            include_weights: false,
+            adhoc_positive: Some("   ".to_string()),
+            adhoc_negative: Some(String::new()),
+            ..CompositionOptions::default()
+        };
+        let composed = compose(&persona_tokens(), &blank);
+        assert_eq!(composed.positive_prompt, "masterpiece, blue hair, smile");
+        assert_eq!(composed.negative_prompt, "lowres, extra fingers");
+
+        let suppressed = CompositionOptions {
+            adhoc_negative: Some("blurry".to_string()),
+            unsupported_negative: UnsupportedNegativeMode::Suppress,
+            ..CompositionOptions::default()
+        };
+        let composed = PromptComposer::compose(
+            &persona_tokens(),
+            &GranularityLevel::all(),
+            &suppressed,
+            false,
+        );
+        assert_eq!(composed.negative_prompt, "");
+        assert_eq!(composed.negative_token_count, 0);
+    }
+
+    #[test]
+    fn empty_negative_section_leaves_no_separator_or_infotext_line() {
+        let tokens = [
+            token("style", "masterpiece", 0),
+            token("hair", "blue hair", 1),
+        ];
+        let preview = PromptComposer::preview(
+            &tokens,
+            &GranularityLevel::all(),
+            &CompositionOptions::default(),
+            true,
+            "sd15",
+            None,
+        );
+
+        assert_eq!(preview.composed.positive_prompt, "masterpiece, blue hair");
+        assert_eq!(preview.composed.negative_prompt, "");
+        assert_eq!(preview.formats.a1111, "masterpiece, blue hair");
+    }
+
+    #[test]
+    fn filtered_out_sections_leave_no_stray_separators() {
+        let options = CompositionOptions {
+            granularity_ids: vec!["lower_body".to_string()],
+            adhoc_positive: Some("looking at viewer".to_string()),
+            ..CompositionOptions::default()
+        };
+        let composed = compose(&persona_tokens(), &options);
+
+        assert_eq!(composed.positive_prompt, "looking at viewer");
+        assert_eq!(composed.negative_prompt, "");
+        assert!(composed.breakdown.sections.is_empty());
+
+        let composed = compose(&[], &CompositionOptions::default());
+        assert_eq!(composed.positive_prompt, "");
+        assert_eq!(composed.negative_prompt, "");
+    }
+
+    #[test]
+    fn breakdown_lists_only_sections_with_tokens_in_level_order() {
+        let composed = compose(&persona_tokens(), &CompositionOptions::default());
+        let ids: Vec<&str> = composed
+            .breakdown
+            .sections
+            .iter()
+            .map(|section| section.granularity_id.as_str())
+            .collect();
+
+        assert_eq!(ids, ["style", "hair", "face"]);
+    }
+
+    #[test]
+    fn regional_prompt_skips_empty_regions() {
+        let tokens = [
+            token("hair", "blue hair", 0),
+            token("upper_body", "red jacket", 1),
+        ];
+        let regional = |syntax| CompositionOptions {
+            regional: Some(RegionalOptions {
+                syntax,
+                ..RegionalOptions::default()
+            }),
+            ..CompositionOptions::default()
+        };
+
+        // No common prompt, and the default background region stays empty
+        assert_eq!(
+            compose(&tokens, &regional(RegionalSyntax::RegionalPrompter)).positive_prompt,
+            "blue hair BREAK\nred jacket"
+        );
+        // The empty base segment keeps the face region out of the base slot
+        assert_eq!(
+            compose(&tokens, &regional(RegionalSyntax::AttentionCouple)).positive_prompt,
+            "\nAND blue hair\nAND red jacket"
+        );
+    }
+
+    #[test]
+    fn regional_prompt_keeps_the_common_prompt_first() {
+        let tokens = [token("style", "masterpiece", 0), token("face", "smile", 1)];
+        let regional = |syntax| CompositionOptions {
+            regional: Some(RegionalOptions {
+                syntax,
+                ..RegionalOptions::default()
+            }),
+            ..CompositionOptions::default()
+        };
+
+        assert_eq!(
+            compose(&tokens, &regional(RegionalSyntax::RegionalPrompter)).positive_prompt,
+            "masterpiece ADDCOMM\nsmile"
+        );
+        assert_eq!(
+            compose(&tokens, &regional(RegionalSyntax::AttentionCouple)).positive_prompt,
+            "masterpiece\nAND smile"
+        );
+
+        let common_only = [token("style", "masterpiece", 0)];
+        assert_eq!(
+            compose(&common_only, &regional(RegionalSyntax::RegionalPrompter)).positive_prompt,
+            "masterpiece"
+        );
+    }
+
+    /// Granularities generated tokens belong to, including a custom level
+    /// unknown to [`GranularityLevel::all`].
+    const PROPERTY_GRANULARITIES: [&str; 5] = ["style", "hair", "face", "lower_body", "background"];
+
+    /// Tokens with unique display orders, in a random input order.
+    fn arb_tokens() -> impl Strategy<Value = Vec<Token>> {
+        prop::collection::vec(
+            (
+                prop::sample::select(&PROPERTY_GRANULARITIES[..]),
+                prop::bool::ANY,
+                "[a-z]{1,8}( [a-z]{1,8})?",
+                5u8..=15,
+            ),
+            0..12,
+        )
+        .prop_map(|specs| {
+            specs
+                .into_iter()
+                .zip(0..)
+                .map(|((granularity_id, positive, content, tenths), order)| {
+                    let polarity = if positive {
+                        TokenPolarity::Positive
+                    } else {
+                        TokenPolarity::Negative
+                    };
+                    weighted_token(
+                        granularity_id,
+                        polarity,
+                        &content,
+                        f64::from(tenths) / 10.0,
+                        order,
+                    )
+                })
+                .collect::<Vec<_>>()
+        })
+        .prop_shuffle()
+    }
+
+    fn arb_order() -> impl Strategy<Value = TokenOrder> {
+        prop_oneof![
+            Just(TokenOrder::Global),
+            Just(TokenOrder::GranularityThenOrder),
+            prop::sample::subsequence(&PROPERTY_GRANULARITIES[..], 0..=5)
+                .prop_shuffle()
+                .prop_map(|ids| TokenOrder::Custom(ids.into_iter().map(String::from).collect())),
+        ]
+    }
+
+    /// Splits `(content:1.2)` back into its content and weight.
+    fn parse_a1111(formatted: &str) -> (String, f64) {
+        formatted
+            .strip_prefix('(')
+            .and_then(|rest| rest.strip_suffix(')'))
+            .and_then(|inner| inner.rsplit_once(':'))
+            .map_or_else(
+                || (formatted.to_string(), 1.0),
+                |(content, weight)| (content.to_string(), weight.parse().unwrap()),
+            )
+    }
+
+    /// Splits `(content)1.2` back into its content and weight.
+    fn parse_compel(formatted: &str) -> (String, f64) {
+        formatted
+            .strip_prefix('(')
+            .and_then(|rest| rest.rsplit_once(')'))
+            .map_or_else(
+                || (formatted.to_string(), 1.0),
+                |(content, weight)| (content.to_string(), weight.parse().unwrap()),
+            )
+    }
+
+    proptest! {
+        #[test]
+        fn prompts_never_contain_empty_segments(
+            tokens in arb_tokens(),
+            order_by in arb_order(),
+            adhoc in prop::option::of("( |[a-z]{1,8}){1,3}"),
+            beginning in prop::bool::ANY,
+        ) {
+            let options = CompositionOptions {
+                order_by,
+                adhoc_positive: adhoc.clone(),
+                adhoc_negative: adhoc,
+                adhoc_position: if beginning { AdhocPosition::Beginning } else { AdhocPosition::End },
+                ..CompositionOptions::default()
+            };
+            let composed = compose(&tokens, &options);
+
+            for (prompt, count) in [
+                (&composed.positive_prompt, composed.positive_token_count),
+                (&composed.negative_prompt, composed.negative_token_count),
+            ] {
+                let segments: Vec<&str> = if prompt.is_empty() {
+                    Vec::new()
+                } else {
+                    prompt.split(", ").collect()
+                };
+                prop_assert_eq!(segments.len(), count);
+                prop_assert!(segments.iter().all(|s| !s.trim().is_empty()));
+            }
+        }
+
+        #[test]
+        fn regional_prompts_never_contain_empty_regions(
+            tokens in arb_tokens(),
+            attention_couple in prop::bool::ANY,
+        ) {
+            let syntax = if attention_couple {
+                RegionalSyntax::AttentionCouple
+            } else {
+                RegionalSyntax::RegionalPrompter
+            };
+            let options = CompositionOptions {
+                regional: Some(RegionalOptions { syntax, ..RegionalOptions::default() }),
+                ..CompositionOptions::default()
+            };
+            let prompt = compose(&tokens, &options).positive_prompt;
+
+            match syntax {
+                RegionalSyntax::RegionalPrompter => {
+                    let regions = prompt.split_once(" ADDCOMM\n").map_or(prompt.as_str(), |(_, r)| r);
+                    if !prompt.is_empty() {
+                        prop_assert!(regions.split(" BREAK\n").all(|r| !r.is_empty()));
+                    }
+                }
+                RegionalSyntax::AttentionCouple => {
+                    // Only the base segment may be empty
+                    prop_assert!(prompt.split("\nAND ").skip(1).all(|r| !r.is_empty()));
+                }
+            }
+        }
+
+        #[test]
+        fn ordering_does_not_depend_on_input_order(
+            tokens in arb_tokens(),
+            order_by in arb_order(),
+        ) {
+            let mut sorted = tokens.clone();
+            sorted.sort_by_key(|t| t.display_order);
+            let options = CompositionOptions { order_by, ..CompositionOptions::default() };
+
+            let shuffled = compose(&tokens, &options);
+            let in_order = compose(&sorted, &options);
+            prop_assert_eq!(shuffled.positive_prompt, in_order.positive_prompt);
+            prop_assert_eq!(shuffled.negative_prompt, in_order.negative_prompt);
+        }
+
+        #[test]
+        fn tokens_keep_display_order_within_a_granularity(
+            tokens in arb_tokens(),
+            order_by in arb_order(),
+        ) {
+            let options = CompositionOptions {
+                order_by,
+                include_weights: false,
+                ..CompositionOptions::default()
+            };
+            let composed = compose(&tokens, &options);
+
+            for section in &composed.breakdown.sections {
+                let mut expected: Vec<&Token> = tokens
+                    .iter()
+                    .filter(|t| {
+                        t.granularity_id == section.granularity_id
+                            && t.polarity == TokenPolarity::Positive
+                    })
+                    .collect();
+                expected.sort_by_key(|t| t.display_order);
+                let expected: Vec<&str> = expected.iter().map(|t| t.content.as_str()).collect();
+                prop_assert_eq!(&section.positive_tokens, &expected);
+            }
+        }
+
+        #[test]
+        fn weight_syntax_round_trips(
+            content in "[a-z]{1,8}( [a-z]{1,8})?",
+            tenths in 1u8..=20,
+        ) {
+            let weight = f64::from(tenths) / 10.0;
+            let token = weighted_token("hair", TokenPolarity::Positive, &content, weight, 0);
+
+            for (syntax, parse) in [
+                (WeightSyntax::A1111, parse_a1111 as fn(&str) -> (String, f64)),
+                (WeightSyntax::Compel, parse_compel),
+            ] {
+                let (parsed_content, parsed_weight) = parse(&syntax.format_token(&token));
+                prop_assert_eq!(&parsed_content, &content);
+                prop_assert!((parsed_weight - weight).abs() < f64::EPSILON);
+            }
         }
     }
 }
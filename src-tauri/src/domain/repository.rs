@@ -0,0 +1,371 @@
+//! Repository Traits
+//!
+//! Storage-independent interfaces to persona, token, granularity level,
+//! banned term, and token alias data. Services are
+//! written against these traits rather than `SQLite`, so they can run on the
+//! in-memory fakes (see `infrastructure::memory`) without a database, and an
+//! alternative backend only has to implement them.
+//!
+//! # Implementations
+//!
+//! - `rusqlite::Connection`: the `SQLite` repositories, delegating to
+//!   `PersonaRepository`, `TokenRepository`, `GranularityRepository`,
+//!   `BannedTermRepository`, and `TokenAliasRepository`
+//! - `InMemoryStore`: a fake holding everything in memory
+//!
+//! Call trait methods with their trait path (e.g., `PersonaRepo::find_by_id`),
+//! since a store implements several traits with methods of the same name.
+
+use std::collections::HashSet;
+
+use super::alias::{AliasMap, TokenAlias};
+use super::banned_term::BannedTerm;
+use super::naming::name_key;
+use super::persona::{CreatePersonaRequest, GenerationParams, Persona, UpdatePersonaRequest};
+use super::prompt::CompositionDefaults;
+use super::token::{
+    CreateTokenRequest, GeneratedTokenSelection, GranularityLevel, Token, TokenPolarity,
+    TokenRevision, UpdateTokenRequest,
+};
+use crate::error::AppError;
+
+/// Runs several repository calls atomically.
+pub trait UnitOfWork {
+    /// Runs `work`, keeping its writes only if it succeeds.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error from `work`, after discarding its writes.
+    fn unit_of_work<T>(
+        &self,
+        work: impl FnOnce(&Self) -> Result<T, AppError>,
+    ) -> Result<T, AppError>;
+}
+
+/// Persona storage.
+pub trait PersonaRepo {
+    /// Returns the persona with the given ID.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::NotFound` if it does not exist.
+    fn find_by_id(&self, id: &str) -> Result<Persona, AppError>;
+
+    /// Returns every persona, newest first.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the storage fails.
+    fn find_all(&self) -> Result<Vec<Persona>, AppError>;
+
+    /// Creates a persona with default generation parameters.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Validation` if the name is empty or taken.
+    fn create(&self, request: &CreatePersonaRequest) -> Result<Persona, AppError>;
+
+    /// Applies a partial update to a persona.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::NotFound` if the persona does not exist, or
+    /// `AppError::Validation` if a new name is taken.
+    fn update(&self, id: &str, request: &UpdatePersonaRequest) -> Result<Persona, AppError>;
+
+    /// Deletes a persona with its tokens and generation parameters.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::NotFound` if the persona does not exist.
+    fn delete(&self, id: &str) -> Result<(), AppError>;
+
+    /// Returns the oldest persona whose content hash (see
+    /// `domain::persona::compute_content_hash`) is `hash`, if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the storage fails.
+    fn find_by_content_hash(&self, hash: &str) -> Result<Option<Persona>, AppError>;
+
+    /// Returns the names of all personas except `exclude_id`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the storage fails.
+    fn names(&self, exclude_id: Option<&str>) -> Result<Vec<String>, AppError>;
+
+    /// Returns a persona's generation parameters, with inherited ones resolved.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::NotFound` if the persona has none.
+    fn find_resolved_generation_params(
+        &self,
+        persona_id: &str,
+    ) -> Result<GenerationParams, AppError>;
+
+    /// Saves a persona's generation parameters.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the storage fails.
+    fn update_generation_params(&self, params: &GenerationParams) -> Result<(), AppError>;
+
+    /// Returns a persona's composition defaults, if it has any.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the storage fails.
+    fn find_composition_defaults(
+        &self,
+        persona_id: &str,
+    ) -> Result<Option<CompositionDefaults>, AppError>;
+
+    /// Sets or, with `None`, clears a persona's composition defaults.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::NotFound` if the persona does not exist.
+    fn set_composition_defaults(
+        &self,
+        persona_id: &str,
+        defaults: Option<&CompositionDefaults>,
+    ) -> Result<(), AppError>;
+
+    /// Returns true if another persona than `exclude_id` has the name.
+    ///
+    /// Names are compared by [`name_key`], ignoring case and diacritics.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the storage fails.
+    fn name_exists(&self, name: &str, exclude_id: Option<&str>) -> Result<bool, AppError> {
+        let key = name_key(name);
+        Ok(self
+            .names(exclude_id)?
+            .iter()
+            .any(|name| name_key(name) == key))
+    }
+
+    /// Returns up to `limit` candidate names no other persona has, in order.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the storage fails.
+    fn available_names(
+        &self,
+        candidates: impl IntoIterator<Item = String>,
+        exclude_id: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<String>, AppError> {
+        let keys: Vec<String> = self
+            .names(exclude_id)?
+            .iter()
+            .map(|name| name_key(name))
+            .collect();
+        Ok(candidates
+            .into_iter()
+            .filter(|candidate| !keys.contains(&name_key(candidate)))
+            .take(limit)
+            .collect())
+    }
+}
+
+/// Token storage.
+pub trait TokenRepo {
+    /// Returns the token with the given ID.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::NotFound` if it does not exist.
+    fn find_by_id(&self, id: &str) -> Result<Token, AppError>;
+
+    /// Returns a persona's own tokens in display order.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the storage fails.
+    fn find_by_persona(&self, persona_id: &str) -> Result<Vec<Token>, AppError>;
+
+    /// Creates a token after the persona's existing ones.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the storage fails.
+    fn create(&self, request: &CreateTokenRequest) -> Result<Token, AppError>;
+
+    /// Creates tokens after the persona's existing ones, in order, skipping
+    /// selections with empty content.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Validation` if a granularity is unknown.
+    fn create_from_selections(
+        &self,
+        persona_id: &str,
+        selections: &[GeneratedTokenSelection],
+    ) -> Result<Vec<Token>, AppError>;
+
+    /// Creates one token per non-empty content, sharing granularity,
+    /// polarity, and weight, after the persona's existing ones.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the storage fails.
+    fn create_batch(
+        &self,
+        persona_id: &str,
+        granularity_id: &str,
+        polarity: TokenPolarity,
+        contents: &[String],
+        weight: f64,
+    ) -> Result<Vec<Token>, AppError>;
+
+    /// Creates tokens from several requests, in order, each after its
+    /// persona's existing ones.
+    ///
+    /// Unlike [`Self::create_from_selections`], custom granularity levels are
+    /// accepted.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the storage fails.
+    fn create_all(&self, requests: &[CreateTokenRequest]) -> Result<Vec<Token>, AppError>;
+
+    /// Creates tokens from selections, skipping those the persona already has
+    /// (same granularity, polarity, and phrasing after resolving `aliases`)
+    /// and repeated selections.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Validation` if a granularity is unknown.
+    fn create_missing(
+        &self,
+        persona_id: &str,
+        selections: &[GeneratedTokenSelection],
+        aliases: &AliasMap,
+    ) -> Result<Vec<Token>, AppError> {
+        let mut existing: HashSet<_> = self
+            .find_by_persona(persona_id)?
+            .into_iter()
+            .map(|t| (t.granularity_id, t.polarity, aliases.key(&t.content)))
+            .collect();
+        let missing: Vec<_> = selections
+            .iter()
+            .filter(|s| {
+                existing.insert((
+                    s.granularity_id.clone(),
+                    s.polarity,
+                    aliases.key(&s.content),
+                ))
+            })
+            .cloned()
+            .collect();
+
+        self.create_from_selections(persona_id, &missing)
+    }
+
+    /// Applies a partial update to a token, recording its previous content and
+    /// weight as a revision when they change.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::NotFound` if the token does not exist.
+    fn update(&self, id: &str, request: &UpdateTokenRequest) -> Result<Token, AppError>;
+
+    /// Deletes a token.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::NotFound` if the token does not exist.
+    fn delete(&self, id: &str) -> Result<(), AppError>;
+
+    /// Deletes all of a persona's tokens, returning how many there were.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the storage fails.
+    fn delete_by_persona(&self, persona_id: &str) -> Result<usize, AppError>;
+
+    /// Deletes a persona's tokens in one granularity level, returning how many
+    /// there were.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the storage fails.
+    fn delete_by_granularity(
+        &self,
+        persona_id: &str,
+        granularity_id: &str,
+    ) -> Result<usize, AppError>;
+
+    /// Renumbers a persona's tokens from zero without gaps, keeping their
+    /// order; returns whether any changed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the storage fails.
+    fn normalize_token_order(&self, persona_id: &str) -> Result<bool, AppError>;
+
+    /// Returns a revision of a token.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::NotFound` if the token has no such revision.
+    fn find_revision(&self, token_id: &str, revision: i32) -> Result<TokenRevision, AppError>;
+}
+
+/// Granularity level storage.
+pub trait GranularityRepo {
+    /// Returns every level, built-in and custom, in display order.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the storage fails.
+    fn find_all(&self) -> Result<Vec<GranularityLevel>, AppError>;
+
+    /// Creates a custom level after every existing one.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Validation` if the ID or name is empty or the ID is
+    /// taken.
+    fn create(&self, id: &str, name: &str) -> Result<GranularityLevel, AppError>;
+}
+
+/// Banned term storage.
+pub trait BannedTermRepo {
+    /// Returns every banned term.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the storage fails.
+    fn find_all(&self) -> Result<Vec<BannedTerm>, AppError>;
+
+    /// Bans a term.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Validation` if the term has no letter or digit, or
+    /// is already banned.
+    fn create(&self, term: &str) -> Result<BannedTerm, AppError>;
+}
+
+/// Token alias storage.
+pub trait AliasRepo {
+    /// Returns every alias.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the storage fails.
+    fn find_all(&self) -> Result<Vec<TokenAlias>, AppError>;
+
+    /// Adds an alias.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Validation` if either text is empty, or the alias
+    /// would repeat or chain an existing one (see `alias::validate_new`).
+    fn create(&self, alias: &str, canonical: &str) -> Result<TokenAlias, AppError>;
+}
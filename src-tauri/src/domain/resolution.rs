@@ -0,0 +1,193 @@
+//! Resolution Presets
+//!
+//! Native output resolutions per image model family, and a check that warns
+//! when a persona's generation resolution is off-bucket for its model.
+//!
+//! # Families
+//!
+//! - **SD 1.5**: trained at 512×512; larger sizes duplicate subjects
+//! - **SD 2.x**: trained at 768×768
+//! - **SDXL**: trained on fixed ~1 megapixel aspect-ratio buckets; sizes
+//!   outside the buckets lose coherence
+//! - **FLUX**: flexible; any size in steps of 16 from 0.25 to 2 megapixels
+//! - **Other families**: around 1 megapixel in steps of 64
+//!
+//! The family of a model comes from its prompt context (see
+//! `infrastructure::tokenizer::get_prompt_context_for_model`).
+
+use serde::{Deserialize, Serialize};
+
+use super::i18n::{Locale, Message};
+
+/// SDXL training buckets, square first, then portrait/landscape pairs by elongation.
+const ONE_MEGAPIXEL_BUCKETS: &[(u32, u32)] = &[
+    (1024, 1024),
+    (896, 1152),
+    (1152, 896),
+    (832, 1216),
+    (1216, 832),
+    (768, 1344),
+    (1344, 768),
+    (640, 1536),
+    (1536, 640),
+];
+
+/// SD 1.5 sizes: the native square and the usual 2:3 and 3:2 variants.
+const SD15_SIZES: &[(u32, u32)] = &[(512, 512), (512, 768), (768, 512)];
+
+/// SD 2.x sizes: the native square and the 3:4 and 4:3 variants.
+const SD2_SIZES: &[(u32, u32)] = &[(768, 768), (576, 768), (768, 576)];
+
+/// A native output resolution.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResolutionPreset {
+    /// Width in pixels
+    pub width: u32,
+    /// Height in pixels
+    pub height: u32,
+    /// Aspect ratio in lowest terms (e.g., "3:2"); bucket sizes are only
+    /// approximately standard ratios (832×1216 is "13:19")
+    pub aspect_ratio: String,
+}
+
+impl ResolutionPreset {
+    fn new(width: u32, height: u32) -> Self {
+        let divisor = gcd(width, height).max(1);
+        Self {
+            width,
+            height,
+            aspect_ratio: format!("{}:{}", width / divisor, height / divisor),
+        }
+    }
+}
+
+/// Resolution options for an image model family.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResolutionPresets {
+    /// Model family identifier (e.g., "sdxl", "flux")
+    pub family: String,
+    /// Whether any size within the pixel range works, not only the presets
+    pub flexible: bool,
+    /// Width and height must be multiples of this
+    pub step: u32,
+    /// Smallest recommended pixel count (flexible families)
+    pub min_pixels: u32,
+    /// Largest recommended pixel count (flexible families)
+    pub max_pixels: u32,
+    /// Recommended sizes, default first
+    pub presets: Vec<ResolutionPreset>,
+}
+
+/// Result of checking a resolution against a model family.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResolutionCheck {
+    /// Whether the resolution is native for the model
+    pub supported: bool,
+    /// Stable code of the warning (e.g., "`resolution.not_native`"), if any
+    pub warning_code: Option<String>,
+    /// Why the resolution is off-bucket, if it is, in the requested locale
+    pub warning: Option<String>,
+    /// Preset with the closest aspect ratio, if the resolution is off-bucket
+    pub suggestion: Option<ResolutionPreset>,
+}
+
+impl ResolutionPresets {
+    /// Returns the resolution options of a model family.
+    ///
+    /// Unknown families get the generic ~1 megapixel options.
+    #[must_use]
+    pub fn for_family(family: &str) -> Self {
+        let (flexible, step, min_pixels, max_pixels, sizes) = match family {
+            "sd15" => (false, 64, 0, 0, SD15_SIZES),
+            "sd2" => (false, 64, 0, 0, SD2_SIZES),
+            "sdxl" => (false, 64, 0, 0, ONE_MEGAPIXEL_BUCKETS),
+            "flux" => (true, 16, 512 * 512, 2048 * 1024, ONE_MEGAPIXEL_BUCKETS),
+            _ => (true, 64, 1024 * 768, 1280 * 1024, ONE_MEGAPIXEL_BUCKETS),
+        };
+
+        Self {
+            family: family.to_string(),
+            flexible,
+            step,
+            min_pixels,
+            max_pixels,
+            presets: sizes
+                .iter()
+                .map(|&(width, height)| ResolutionPreset::new(width, height))
+                .collect(),
+        }
+    }
+
+    /// Checks whether a resolution is native for this family.
+    ///
+    /// Fixed families accept only their presets; flexible families accept any
+    /// size that is a multiple of `step` within the pixel range. The warning is
+    /// rendered in `locale`.
+    #[must_use]
+    pub fn check(&self, width: u32, height: u32, locale: Locale) -> ResolutionCheck {
+        let warning = if width == 0 || height == 0 {
+            Some(Message::new("resolution.not_positive"))
+        } else if !self.flexible {
+            let on_bucket = self
+                .presets
+                .iter()
+                .any(|preset| preset.width == width && preset.height == height);
+            (!on_bucket).then(|| {
+                Message::new("resolution.not_native")
+                    .arg("width", width)
+                    .arg("height", height)
+            })
+        } else if width % self.step != 0 || height % self.step != 0 {
+            Some(
+                Message::new("resolution.not_multiple")
+                    .arg("width", width)
+                    .arg("height", height)
+                    .arg("step", self.step),
+            )
+        } else {
+            let pixels = u64::from(width) * u64::from(height);
+            let megapixels = |pixels: u32| format!("{:.2}", f64::from(pixels) / 1_048_576.0);
+            (pixels < u64::from(self.min_pixels) || pixels > u64::from(self.max_pixels)).then(
+                || {
+                    Message::new("resolution.out_of_range")
+                        .arg("width", width)
+                        .arg("height", height)
+                        .arg("min", megapixels(self.min_pixels))
+                        .arg("max", megapixels(self.max_pixels))
+                },
+            )
+        };
+
+        let suggestion = warning.as_ref().and_then(|_| {
+            let requested = log_ratio(width.max(1), height.max(1));
+            let distance = |preset: &ResolutionPreset| {
+                (log_ratio(preset.width, preset.height) - requested).abs()
+            };
+            self.presets
+                .iter()
+                .min_by(|a, b| distance(a).total_cmp(&distance(b)))
+                .cloned()
+        });
+
+        ResolutionCheck {
+            supported: warning.is_none(),
+            warning_code: warning.as_ref().map(|message| message.code.to_string()),
+            warning: warning.map(|message| message.render(locale)),
+            suggestion,
+        }
+    }
+}
+
+/// Natural log of width over height; equal for equal aspect ratios.
+fn log_ratio(width: u32, height: u32) -> f64 {
+    (f64::from(width) / f64::from(height)).ln()
+}
+
+/// Greatest common divisor.
+const fn gcd(a: u32, b: u32) -> u32 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
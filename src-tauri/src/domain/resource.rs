@@ -0,0 +1,111 @@
+//! Local Model Resources
+//!
+//! Textual inversion embeddings and `LoRA` models found in the folders set in
+//! [`ResourceFolders`](super::settings::ResourceFolders) (see
+//! `infrastructure::resource_scan`). Each resource carries the trigger that
+//! activates it in a prompt, ready to add to a persona as a token, so trigger
+//! names never have to be typed by hand.
+//!
+//! The file hashes match the ones Civitai and image generators show, so a
+//! resource can be passed to `lookup_civitai_model_by_hash` for its trained
+//! words.
+
+use serde::{Deserialize, Serialize};
+
+use super::token::{GeneratedTokenSelection, Granularity, TokenPolarity};
+
+/// Number of hash characters in an `AutoV2` hash.
+const AUTO_V2_LENGTH: usize = 10;
+
+/// Kind of a local model resource.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResourceKind {
+    /// Textual inversion embedding, triggered by its name
+    Embedding,
+    /// `LoRA` model, triggered by a `<lora:name:weight>` tag
+    Lora,
+}
+
+impl ResourceKind {
+    /// File extensions of this kind of resource, lowercase.
+    #[must_use]
+    pub const fn extensions(self) -> &'static [&'static str] {
+        match self {
+            Self::Embedding => &["pt", "safetensors", "bin"],
+            Self::Lora => &["safetensors", "pt", "ckpt"],
+        }
+    }
+}
+
+/// An embedding or `LoRA` file found in a resource folder.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocalResource {
+    /// Embedding or `LoRA`
+    pub kind: ResourceKind,
+    /// File name without extension, as image generators refer to it
+    pub name: String,
+    /// Absolute path of the file
+    pub path: String,
+    /// File size in bytes
+    pub size: u64,
+    /// SHA-256 of the file, uppercase hexadecimal
+    pub sha256: String,
+    /// Short hash shown by A1111 and Civitai (first 10 characters of the
+    /// SHA-256)
+    pub auto_v2: String,
+    /// Text that activates the resource in A1111 prompt syntax
+    pub trigger: String,
+    /// The trigger as a Style-level token, ready to add to a persona
+    pub suggested_token: GeneratedTokenSelection,
+}
+
+impl LocalResource {
+    /// Creates a resource from a scanned file.
+    ///
+    /// Embeddings named like negative embeddings (e.g., `EasyNegative`,
+    /// "bad-hands-5") are suggested as negative tokens.
+    ///
+    /// # Arguments
+    ///
+    /// * `kind` - Embedding or `LoRA`
+    /// * `name` - File name without extension
+    /// * `path` - Absolute path of the file
+    /// * `size` - File size in bytes
+    /// * `sha256` - SHA-256 of the file, hexadecimal
+    #[must_use]
+    pub fn new(kind: ResourceKind, name: String, path: String, size: u64, sha256: &str) -> Self {
+        let sha256 = sha256.to_ascii_uppercase();
+        let trigger = match kind {
+            ResourceKind::Embedding => name.clone(),
+            ResourceKind::Lora => format!("<lora:{name}:1>"),
+        };
+        let polarity = if kind == ResourceKind::Embedding && is_negative_embedding(&name) {
+            TokenPolarity::Negative
+        } else {
+            TokenPolarity::Positive
+        };
+
+        Self {
+            kind,
+            auto_v2: sha256.chars().take(AUTO_V2_LENGTH).collect(),
+            suggested_token: GeneratedTokenSelection {
+                granularity_id: Granularity::Style.as_str().to_string(),
+                polarity,
+                content: trigger.clone(),
+                weight: 1.0,
+            },
+            name,
+            path,
+            size,
+            sha256,
+            trigger,
+        }
+    }
+}
+
+/// Returns true for embedding names conventionally used in negative prompts.
+fn is_negative_embedding(name: &str) -> bool {
+    let name = name.to_lowercase();
+    name.contains("negative") || name.starts_with("bad") || name.starts_with("ng_")
+}
@@ -0,0 +1,370 @@
+//! Scheduled Tasks
+//!
+//! Optional recurring jobs run in the background while the app is open (see
+//! `infrastructure::scheduler`). Schedules only run while the scheduler is
+//! turned on in the app settings.
+//!
+//! # Style Refresh
+//!
+//! The only kind of task so far asks the task's AI provider, e.g. weekly, for
+//! current style tokens for each of its personas. The answers are staged in
+//! the pending suggestions inbox (see `domain::suggestion`), where the user
+//! accepts or rejects them; nothing is added to a persona automatically.
+//! Suggestions already staged, or matching one of the persona's tokens, are
+//! not staged again.
+//!
+//! # Missed Runs
+//!
+//! A run that fell due while the app was closed happens shortly after the
+//! next launch, once; the next run is then due one interval after it.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::ai::{AiProviderConfig, TokenGenerationRequest};
+use super::persona::Persona;
+use super::suggestion::PendingSuggestion;
+use super::token::{Granularity, Token, TokenPolarity};
+use crate::error::AppError;
+
+/// Largest number of tokens a style refresh asks for per persona.
+pub const MAX_REFRESH_TOKEN_COUNT: usize = 20;
+
+/// Instructions sent with every style refresh request.
+const STYLE_REFRESH_INSTRUCTIONS: &str = "Suggest style tokens that are popular in current \
+     AI image generation (art styles, rendering and lighting keywords, aesthetic modifiers) and \
+     suit this character. Prefer fresh ideas over generic quality tags.";
+
+/// What a scheduled task does when it runs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScheduledTaskKind {
+    /// Stage AI suggestions for current style tokens
+    #[default]
+    StyleRefresh,
+}
+
+impl ScheduledTaskKind {
+    /// Returns the string representation for database storage.
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::StyleRefresh => "style_refresh",
+        }
+    }
+
+    /// Parses from database string representation.
+    #[must_use]
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "style_refresh" => Some(Self::StyleRefresh),
+            _ => None,
+        }
+    }
+}
+
+/// How often a scheduled task runs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScheduleFrequency {
+    /// Every day
+    Daily,
+    /// Every seven days
+    #[default]
+    Weekly,
+    /// Every thirty days
+    Monthly,
+}
+
+impl ScheduleFrequency {
+    /// Returns the lowercase string representation for database storage.
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Daily => "daily",
+            Self::Weekly => "weekly",
+            Self::Monthly => "monthly",
+        }
+    }
+
+    /// Parses from database string representation.
+    #[must_use]
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "daily" => Some(Self::Daily),
+            "weekly" => Some(Self::Weekly),
+            "monthly" => Some(Self::Monthly),
+            _ => None,
+        }
+    }
+
+    /// Time between two runs.
+    #[must_use]
+    pub const fn interval(self) -> Duration {
+        match self {
+            Self::Daily => Duration::days(1),
+            Self::Weekly => Duration::days(7),
+            Self::Monthly => Duration::days(30),
+        }
+    }
+}
+
+/// A recurring background job.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledTask {
+    /// Unique identifier (UUID v4)
+    pub id: String,
+    /// Display name
+    pub name: String,
+    /// What the task does
+    pub kind: ScheduledTaskKind,
+    /// Personas the task works on; deleted personas are dropped on the next run
+    pub persona_ids: Vec<String>,
+    /// How often the task runs
+    pub frequency: ScheduleFrequency,
+    /// AI provider and model asked for suggestions; the API key is read from
+    /// the keyring at run time
+    pub config: AiProviderConfig,
+    /// Number of tokens asked for per persona
+    pub token_count: usize,
+    /// Whether the task runs on its schedule (it can still be run by hand)
+    pub enabled: bool,
+    /// When the task last ran
+    pub last_run_at: Option<DateTime<Utc>>,
+    /// When the task is next due
+    pub next_run_at: DateTime<Utc>,
+    /// Why the last run failed, per persona; `None` if it succeeded
+    pub last_error: Option<String>,
+    /// Creation timestamp
+    pub created_at: DateTime<Utc>,
+    /// Last modification timestamp
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ScheduledTask {
+    /// Creates a task from a validated request, first due one interval from now.
+    #[must_use]
+    pub fn new(request: CreateScheduledTaskRequest) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4().to_string(),
+            name: request.name.trim().to_string(),
+            kind: request.kind,
+            persona_ids: request.persona_ids,
+            frequency: request.frequency,
+            config: request.config,
+            token_count: request.token_count,
+            enabled: request.enabled,
+            last_run_at: None,
+            next_run_at: now + request.frequency.interval(),
+            last_error: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// Returns true if the task is enabled and its next run is due.
+    #[must_use]
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        self.enabled && self.next_run_at <= now
+    }
+
+    /// Records a run finished at `now`, scheduling the next one.
+    pub fn record_run(&mut self, now: DateTime<Utc>, errors: &[String]) {
+        self.last_run_at = Some(now);
+        self.next_run_at = now + self.frequency.interval();
+        self.last_error = (!errors.is_empty()).then(|| errors.join("\n"));
+    }
+
+    /// Builds the token request of a style refresh for one persona.
+    ///
+    /// # Arguments
+    ///
+    /// * `persona` - The persona to refresh
+    /// * `tokens` - The persona's tokens, inherited ones included
+    /// * `pending` - Suggestions already staged for the persona
+    /// * `image_model_id` - Image model of the persona's generation parameters
+    #[must_use]
+    pub fn style_refresh_request(
+        &self,
+        persona: &Persona,
+        tokens: &[Token],
+        pending: &[PendingSuggestion],
+        image_model_id: Option<String>,
+    ) -> TokenGenerationRequest {
+        let style = Granularity::Style.as_str();
+        let existing = |polarity: TokenPolarity| -> Vec<String> {
+            tokens
+                .iter()
+                .filter(|t| t.granularity_id == style && t.polarity == polarity)
+                .map(|t| t.content.clone())
+                .chain(
+                    pending
+                        .iter()
+                        .filter(|s| s.granularity_id == style && s.polarity == polarity)
+                        .map(|s| s.content.clone()),
+                )
+                .collect()
+        };
+
+        let ai_instructions = match persona.ai_instructions.as_deref().map(str::trim) {
+            Some(instructions) if !instructions.is_empty() => {
+                format!("{STYLE_REFRESH_INSTRUCTIONS}\n\n{instructions}")
+            }
+            _ => STYLE_REFRESH_INSTRUCTIONS.to_string(),
+        };
+
+        TokenGenerationRequest {
+            persona_name: persona.name.clone(),
+            persona_description: persona.description.clone(),
+            granularity_name: style.to_string(),
+            positive_count: self.token_count,
+            negative_count: 0,
+            existing_positive_tokens: existing(TokenPolarity::Positive),
+            existing_negative_tokens: existing(TokenPolarity::Negative),
+            style_hints: None,
+            image_model_id,
+            ai_instructions: Some(ai_instructions),
+            current_positive_prompt: None,
+            current_negative_prompt: None,
+            positive_token_count: None,
+            negative_token_count: None,
+            max_usable_tokens: None,
+            output_language: None,
+            persona_id: Some(persona.id.clone()),
+            granularity_id: Some(style.to_string()),
+        }
+    }
+}
+
+/// Request payload for creating a scheduled task.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateScheduledTaskRequest {
+    /// Display name (required)
+    pub name: String,
+    /// What the task does (default: style refresh)
+    #[serde(default)]
+    pub kind: ScheduledTaskKind,
+    /// Personas the task works on (at least one)
+    pub persona_ids: Vec<String>,
+    /// How often the task runs (default: weekly)
+    #[serde(default)]
+    pub frequency: ScheduleFrequency,
+    /// AI provider and model asked for suggestions
+    pub config: AiProviderConfig,
+    /// Number of tokens asked for per persona (default: 5)
+    #[serde(default = "default_token_count")]
+    pub token_count: usize,
+    /// Whether the task runs on its schedule (default: true)
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+const fn default_token_count() -> usize {
+    5
+}
+
+const fn default_enabled() -> bool {
+    true
+}
+
+impl CreateScheduledTaskRequest {
+    /// Validates the request.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Validation` if the name is empty, no persona is
+    /// selected, or the token count is outside 1 to
+    /// [`MAX_REFRESH_TOKEN_COUNT`].
+    pub fn validate(&self) -> Result<(), AppError> {
+        validate_name(&self.name)?;
+        validate_persona_ids(&self.persona_ids)?;
+        validate_token_count(self.token_count)
+    }
+}
+
+/// Request payload for updating a scheduled task; omitted fields are kept.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpdateScheduledTaskRequest {
+    /// New display name
+    #[serde(default)]
+    pub name: Option<String>,
+    /// New personas
+    #[serde(default)]
+    pub persona_ids: Option<Vec<String>>,
+    /// New frequency; the next run is rescheduled from the last one
+    #[serde(default)]
+    pub frequency: Option<ScheduleFrequency>,
+    /// New AI provider and model
+    #[serde(default)]
+    pub config: Option<AiProviderConfig>,
+    /// New number of tokens per persona
+    #[serde(default)]
+    pub token_count: Option<usize>,
+    /// Turn the schedule on or off
+    #[serde(default)]
+    pub enabled: Option<bool>,
+}
+
+impl UpdateScheduledTaskRequest {
+    /// Applies the set fields to a task.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Validation` under the same rules as
+    /// [`CreateScheduledTaskRequest::validate`].
+    pub fn apply(&self, task: &mut ScheduledTask) -> Result<(), AppError> {
+        if let Some(name) = &self.name {
+            validate_name(name)?;
+            task.name = name.trim().to_string();
+        }
+        if let Some(persona_ids) = &self.persona_ids {
+            validate_persona_ids(persona_ids)?;
+            task.persona_ids = persona_ids.clone();
+        }
+        if let Some(frequency) = self.frequency {
+            task.frequency = frequency;
+            task.next_run_at = task.last_run_at.unwrap_or(task.created_at) + frequency.interval();
+        }
+        if let Some(config) = &self.config {
+            task.config = config.clone();
+        }
+        if let Some(token_count) = self.token_count {
+            validate_token_count(token_count)?;
+            task.token_count = token_count;
+        }
+        if let Some(enabled) = self.enabled {
+            task.enabled = enabled;
+        }
+        task.updated_at = Utc::now();
+        Ok(())
+    }
+}
+
+fn validate_name(name: &str) -> Result<(), AppError> {
+    if name.trim().is_empty() {
+        return Err(AppError::Validation(
+            "Scheduled task name is required".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_persona_ids(persona_ids: &[String]) -> Result<(), AppError> {
+    if persona_ids.is_empty() {
+        return Err(AppError::Validation(
+            "A scheduled task needs at least one persona".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_token_count(token_count: usize) -> Result<(), AppError> {
+    if !(1..=MAX_REFRESH_TOKEN_COUNT).contains(&token_count) {
+        return Err(AppError::Validation(format!(
+            "Token count must be between 1 and {MAX_REFRESH_TOKEN_COUNT}"
+        )));
+    }
+    Ok(())
+}
@@ -0,0 +1,202 @@
+//! Quick Search
+//!
+//! Types and query handling for the quick switcher, which searches personas,
+//! tokens, smart collections, and persona templates at once.
+//!
+//! Library entities are searched through the `search_index` full-text index
+//! (kept in sync by database triggers); the built-in templates are matched in
+//! memory. Matching is by word prefix, ignoring case and diacritics, and every
+//! word of the query must match.
+//!
+//! # Advanced Token Search
+//!
+//! [`TokenPattern`] finds tokens by substring (ignoring case and diacritics)
+//! or regular expression, for locating every persona that still uses a
+//! phrase such as "trending on artstation".
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use super::collation;
+use super::template::PersonaTemplate;
+use super::token::Token;
+use crate::error::AppError;
+
+/// Type of entity a search result points to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchResultKind {
+    /// A persona (matched on name, description, or tags)
+    Persona,
+    /// A token (matched on content)
+    Token,
+    /// A smart collection (matched on name)
+    Collection,
+    /// A built-in persona template (matched on name, description, or tags)
+    Template,
+}
+
+impl SearchResultKind {
+    /// Returns the string representation for database storage.
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Persona => "persona",
+            Self::Token => "token",
+            Self::Collection => "collection",
+            Self::Template => "template",
+        }
+    }
+
+    /// Parses a kind from its database representation.
+    #[must_use]
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "persona" => Some(Self::Persona),
+            "token" => Some(Self::Token),
+            "collection" => Some(Self::Collection),
+            "template" => Some(Self::Template),
+            _ => None,
+        }
+    }
+}
+
+/// A single quick search result.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchResult {
+    /// What the result points to
+    pub kind: SearchResultKind,
+    /// ID of the entity (persona, token, collection, or template ID)
+    pub id: String,
+    /// Persona the entity belongs to (personas and tokens only)
+    pub persona_id: Option<String>,
+    /// Primary display text (name or token content)
+    pub title: String,
+    /// Secondary display text (persona description, or the token's persona name)
+    pub subtitle: Option<String>,
+    /// Relevance; higher is better. Template matches score 0 and come last.
+    pub rank: f64,
+}
+
+/// Splits search text into folded words, the way the index tokenizes text.
+fn query_terms(text: &str) -> Vec<String> {
+    collation::fold(text)
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|term| !term.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Builds an FTS5 match expression requiring a prefix match for every word.
+///
+/// Returns `None` if the text contains no searchable words. Words are quoted,
+/// so operators typed by the user are matched literally.
+#[must_use]
+pub fn fts_query(text: &str) -> Option<String> {
+    let terms = query_terms(text);
+    if terms.is_empty() {
+        return None;
+    }
+    Some(
+        terms
+            .iter()
+            .map(|term| format!("\"{term}\"*"))
+            .collect::<Vec<_>>()
+            .join(" "),
+    )
+}
+
+/// Returns the templates where every word of the text prefixes a word of
+/// the template's name, description, or tags.
+#[must_use]
+pub fn match_templates(text: &str) -> Vec<SearchResult> {
+    let terms = query_terms(text);
+    if terms.is_empty() {
+        return Vec::new();
+    }
+
+    PersonaTemplate::all()
+        .iter()
+        .filter(|template| {
+            let searchable = format!(
+                "{} {} {}",
+                template.name,
+                template.description,
+                template.tags.join(" ")
+            );
+            let words = query_terms(&searchable);
+            terms
+                .iter()
+                .all(|term| words.iter().any(|word| word.starts_with(term.as_str())))
+        })
+        .map(|template| SearchResult {
+            kind: SearchResultKind::Template,
+            id: template.id.clone(),
+            persona_id: None,
+            title: template.name.clone(),
+            subtitle: Some(template.description.clone()),
+            rank: 0.0,
+        })
+        .collect()
+}
+
+/// A token found by advanced token search, with its persona.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenSearchMatch {
+    /// The matching token
+    pub token: Token,
+    /// Name of the persona the token belongs to
+    pub persona_name: String,
+    /// Whether the persona is archived
+    pub persona_archived: bool,
+}
+
+/// What advanced token search looks for in token content.
+#[derive(Debug)]
+pub enum TokenPattern {
+    /// Folded text contained in the content (see [`collation::fold`])
+    Text(String),
+    /// Regular expression found in the content (case-sensitive unless it
+    /// starts with `(?i)`)
+    Regex(Regex),
+}
+
+impl TokenPattern {
+    /// Builds a pattern from search text.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Validation` if the query is blank or, with `regex`,
+    /// not a valid regular expression.
+    pub fn new(query: &str, regex: bool) -> Result<Self, AppError> {
+        if query.trim().is_empty() {
+            return Err(AppError::Validation("Search text is required".to_string()));
+        }
+
+        if regex {
+            compile_regex(query).map(Self::Regex)
+        } else {
+            Ok(Self::Text(collation::fold(query.trim())))
+        }
+    }
+
+    /// Returns true if the token content matches.
+    #[must_use]
+    pub fn matches(&self, content: &str) -> bool {
+        match self {
+            Self::Text(folded) => collation::fold(content).contains(folded.as_str()),
+            Self::Regex(regex) => regex.is_match(content),
+        }
+    }
+}
+
+/// Compiles a user-entered regular expression.
+///
+/// # Errors
+///
+/// Returns `AppError::Validation` with the parser's message if the
+/// expression is invalid.
+pub fn compile_regex(pattern: &str) -> Result<Regex, AppError> {
+    Regex::new(pattern)
+        .map_err(|e| AppError::Validation(format!("Invalid regular expression: {e}")))
+}
@@ -0,0 +1,303 @@
+//! Application Settings
+//!
+//! Backend settings persisted in the database, as opposed to UI preferences,
+//! which the frontend keeps in its own store. They are loaded at startup
+//! because they must take effect before the frontend is ready (e.g., a
+//! tokenizer download during warmup).
+//!
+//! # Network Proxy
+//!
+//! [`ProxySettings`] route tokenizer downloads and AI requests through an
+//! HTTP(S) proxy. When disabled, the `HTTP_PROXY`/`HTTPS_PROXY`/`NO_PROXY`
+//! environment variables the app was started with still apply.
+//!
+//! # Offline Mode
+//!
+//! [`AppSettings::offline`] turns off every network feature so the app fails
+//! fast on air-gapped machines (see `infrastructure::offline`).
+//!
+//! # Safe Mode
+//!
+//! [`AppSettings::safe_mode`] hides mature-rated personas while streaming or
+//! demoing (see `infrastructure::safe_mode`).
+//!
+//! # Logging
+//!
+//! [`AppSettings::log_level`] sets how much is written to the log files (see
+//! `infrastructure::logging`).
+//!
+//! # Telemetry
+//!
+//! [`AppSettings::telemetry`] opts in to counting feature usage locally (see
+//! `domain::telemetry`). It is off unless the user turns it on.
+//!
+//! # Locale
+//!
+//! [`AppSettings::locale`] sets the language of error messages and other
+//! display text produced by the backend (see `domain::i18n`).
+//!
+//! # AI Output Language
+//!
+//! [`AppSettings::ai_output_language`] sets the language AI-written
+//! descriptions and rationales use, independently of the locale. A persona can
+//! override it (see `Persona::ai_output_language`). Tokens are always English.
+//!
+//! # Resource Folders
+//!
+//! [`ResourceFolders`] name the folders of textual inversion embeddings and
+//! `LoRA` models scanned for prompt triggers (see `domain::resource`).
+//!
+//! # Tag Dictionaries
+//!
+//! [`AppSettings::tag_dictionaries`] names tag lists, such as the CSV files of
+//! the A1111 tag autocomplete extension, offered as completions after the
+//! user's own tokens (see `domain::autocomplete`).
+//!
+//! # Face Embedder
+//!
+//! [`AppSettings::face_embedder`] names a local program computing face
+//! embeddings, used to group gallery images by face (see `domain::face`).
+//!
+//! # Scheduler
+//!
+//! [`AppSettings::scheduler`] lets scheduled tasks, such as a weekly AI
+//! refresh of style tokens, run in the background (see `domain::schedule`).
+//! It is off unless the user turns it on.
+//!
+//! # Database Encryption
+//!
+//! [`DatabaseEncryptionStatus`] reports whether the library is encrypted with a
+//! passphrase kept in the OS credential store (see `Database::rekey`).
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::i18n::Locale;
+use crate::error::AppError;
+
+/// Settings stored by the backend.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[allow(clippy::struct_excessive_bools)] // independent user toggles
+pub struct AppSettings {
+    /// Network proxy for outgoing HTTP requests
+    #[serde(default)]
+    pub proxy: ProxySettings,
+    /// Blocks tokenizer downloads and remote AI requests
+    #[serde(default)]
+    pub offline: bool,
+    /// Minimum level of events written to the log files
+    #[serde(default)]
+    pub log_level: LogLevel,
+    /// Hides mature-rated personas from lists, search, and composition
+    #[serde(default)]
+    pub safe_mode: bool,
+    /// Counts feature usage in the local database (opt-in)
+    #[serde(default)]
+    pub telemetry: bool,
+    /// Language of backend display text
+    #[serde(default)]
+    pub locale: Locale,
+    /// Language of AI-written prose (e.g., "German"); English when unset
+    #[serde(default)]
+    pub ai_output_language: Option<String>,
+    /// Folders scanned for embeddings and `LoRA` models
+    #[serde(default)]
+    pub resource_folders: ResourceFolders,
+    /// Absolute paths of tag dictionaries used for token autocompletion
+    #[serde(default)]
+    pub tag_dictionaries: Vec<String>,
+    /// Absolute path of a local program printing an image's face embedding
+    #[serde(default)]
+    pub face_embedder: Option<String>,
+    /// Runs scheduled tasks in the background while the app is open (opt-in)
+    #[serde(default)]
+    pub scheduler: bool,
+}
+
+impl AppSettings {
+    /// Validates that every tag dictionary is an absolute path.
+    ///
+    /// Files that do not exist yet are accepted; they are skipped when
+    /// completing.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Validation` if a path is relative.
+    pub fn validate_tag_dictionaries(&self) -> Result<(), AppError> {
+        for path in &self.tag_dictionaries {
+            if !Path::new(path.trim()).is_absolute() {
+                return Err(AppError::Validation(format!(
+                    "Invalid tag dictionary '{path}': expected an absolute path"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Validates the face embedder path, if set.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Validation` if the path is relative.
+    pub fn validate_face_embedder(&self) -> Result<(), AppError> {
+        match self.face_embedder.as_deref().map(str::trim) {
+            Some(program) if !Path::new(program).is_absolute() => Err(AppError::Validation(
+                format!("Invalid face embedder '{program}': expected an absolute path"),
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Verbosity of the application log.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    /// Failures only
+    Error,
+    /// Failures and recoverable problems
+    Warn,
+    /// Normal operation: commands, tokenizer loads, AI requests
+    #[default]
+    Info,
+    /// Repository calls and other internals
+    Debug,
+    /// Everything, including third-party crates' detail
+    Trace,
+}
+
+/// Explicit proxy configuration.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProxySettings {
+    /// Whether the explicit proxy overrides the environment
+    #[serde(default)]
+    pub enabled: bool,
+    /// Proxy URL for plain HTTP requests (e.g., `http://proxy.corp:8080`)
+    #[serde(default)]
+    pub http_proxy: Option<String>,
+    /// Proxy URL for HTTPS requests; most downloads and AI APIs use this one
+    #[serde(default)]
+    pub https_proxy: Option<String>,
+    /// Comma-separated hosts that bypass the proxy (e.g., "localhost,.corp")
+    #[serde(default)]
+    pub no_proxy: Option<String>,
+}
+
+impl ProxySettings {
+    /// Validates that enabled settings name at least one well-formed proxy.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Validation` if the proxy is enabled without any
+    /// proxy URL, or a URL has no `http://`, `https://`, or `socks5://` scheme.
+    pub fn validate(&self) -> Result<(), AppError> {
+        if !self.enabled {
+            return Ok(());
+        }
+
+        let urls: Vec<&str> = [&self.http_proxy, &self.https_proxy]
+            .into_iter()
+            .filter_map(|url| url.as_deref().map(str::trim))
+            .filter(|url| !url.is_empty())
+            .collect();
+
+        if urls.is_empty() {
+            return Err(AppError::Validation(
+                "Enter an HTTP or HTTPS proxy URL, or disable the proxy".to_string(),
+            ));
+        }
+
+        for url in urls {
+            let has_scheme = ["http://", "https://", "socks5://"]
+                .iter()
+                .any(|scheme| url.starts_with(scheme));
+            if !has_scheme || url.contains(char::is_whitespace) {
+                return Err(AppError::Validation(format!(
+                    "Invalid proxy URL '{url}': expected http://host:port"
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Local folders holding image generator add-ons.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResourceFolders {
+    /// Folder of textual inversion embeddings (e.g., A1111's `embeddings`)
+    #[serde(default)]
+    pub embeddings: Option<String>,
+    /// Folder of `LoRA` models (e.g., A1111's `models/Lora`)
+    #[serde(default)]
+    pub loras: Option<String>,
+}
+
+impl ResourceFolders {
+    /// Validates that every folder set is an absolute path.
+    ///
+    /// Folders that do not exist yet are accepted; they are skipped when
+    /// scanning.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Validation` if a folder is a relative path.
+    pub fn validate(&self) -> Result<(), AppError> {
+        for folder in [&self.embeddings, &self.loras].into_iter().flatten() {
+            if !Path::new(folder.trim()).is_absolute() {
+                return Err(AppError::Validation(format!(
+                    "Invalid folder '{folder}': expected an absolute path"
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Where the proxy in effect comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProxySource {
+    /// Explicit [`ProxySettings`]
+    Settings,
+    /// Environment variables present at launch
+    Environment,
+    /// Direct connection
+    None,
+}
+
+/// Result of checking that one endpoint can be reached.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConnectivityCheck {
+    /// What the endpoint is used for (e.g., "`HuggingFace` (tokenizers)")
+    pub name: String,
+    /// Host and port that was checked
+    pub target: String,
+    /// Proxy the connection went through, if any
+    pub proxy: Option<String>,
+    /// Whether a connection (or proxy tunnel) was established
+    pub reachable: bool,
+    /// Time until the connection succeeded or failed, in milliseconds
+    pub elapsed_ms: u64,
+    /// Failure reason, if unreachable
+    pub error: Option<String>,
+}
+
+/// Outcome of the connectivity diagnostic.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConnectivityReport {
+    /// Where the proxy in effect comes from
+    pub proxy_source: ProxySource,
+    /// One check per endpoint
+    pub checks: Vec<ConnectivityCheck>,
+}
+
+/// Encryption state of the library database.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatabaseEncryptionStatus {
+    /// Whether this build supports encrypted databases
+    pub available: bool,
+    /// Whether the database file is encrypted
+    pub encrypted: bool,
+}
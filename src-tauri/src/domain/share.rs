@@ -0,0 +1,203 @@
+//! Share Sessions
+//!
+//! A temporary, read-only view of one smart collection served over HTTP, so
+//! a collaborator on the same computer or network can browse its personas in
+//! a web browser without installing the app. The pages are those of the
+//! static library site (see `super::site`), rendered on request.
+//!
+//! # Access
+//!
+//! - Every URL carries the session's random token (`/s/<token>/`); requests
+//!   with another token get a plain 404, like unknown paths
+//! - The session expires after a chosen duration, at most
+//!   [`MAX_SHARE_MINUTES`]; later requests get a 410
+//! - Only `GET` and `HEAD` are served, and nothing can be modified
+//! - Only one session runs at a time; starting another replaces it
+//!
+//! By default the server listens on localhost only; the `lan` option opens it
+//! to the local network.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::site;
+use crate::error::AppError;
+
+/// Session length in minutes when none is given.
+pub const DEFAULT_SHARE_MINUTES: u32 = 60;
+
+/// Longest session, in minutes (one day).
+pub const MAX_SHARE_MINUTES: u32 = 24 * 60;
+
+/// Request to start sharing a smart collection.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StartShareRequest {
+    /// UUID of the smart collection to share
+    pub collection_id: String,
+    /// Whether other devices on the local network can connect (default: false,
+    /// localhost only)
+    #[serde(default)]
+    pub lan: bool,
+    /// Minutes until the session expires (default: [`DEFAULT_SHARE_MINUTES`])
+    #[serde(default)]
+    pub duration_minutes: Option<u32>,
+}
+
+impl StartShareRequest {
+    /// Returns the session length, validated.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Validation` if the duration is zero or longer than
+    /// [`MAX_SHARE_MINUTES`].
+    pub fn duration(&self) -> Result<Duration, AppError> {
+        let minutes = self.duration_minutes.unwrap_or(DEFAULT_SHARE_MINUTES);
+        if minutes == 0 || minutes > MAX_SHARE_MINUTES {
+            return Err(AppError::Validation(format!(
+                "Share duration must be between 1 and {MAX_SHARE_MINUTES} minutes"
+            )));
+        }
+        Ok(Duration::minutes(i64::from(minutes)))
+    }
+}
+
+/// A running share session.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShareSession {
+    /// Secret token in every URL of the session
+    pub token: String,
+    /// UUID of the shared smart collection
+    pub collection_id: String,
+    /// Name of the shared smart collection
+    pub collection_name: String,
+    /// Address of the shared view, to send to the collaborator
+    pub url: String,
+    /// Whether the server accepts connections from the local network
+    pub lan: bool,
+    /// When the session was started
+    pub started_at: DateTime<Utc>,
+    /// When the session stops
+    pub expires_at: DateTime<Utc>,
+}
+
+impl ShareSession {
+    /// Starts describing a session with a new random token; the URL is set
+    /// once the server is listening (see [`Self::with_address`]).
+    #[must_use]
+    pub fn new(
+        collection_id: String,
+        collection_name: String,
+        lan: bool,
+        duration: Duration,
+    ) -> Self {
+        let started_at = Utc::now();
+        Self {
+            token: Uuid::new_v4().simple().to_string(),
+            collection_id,
+            collection_name,
+            url: String::new(),
+            lan,
+            started_at,
+            expires_at: started_at + duration,
+        }
+    }
+
+    /// Sets the URL from the host and port the server listens on.
+    #[must_use]
+    pub fn with_address(mut self, host: &str, port: u16) -> Self {
+        self.url = format!("http://{host}:{port}/s/{}/", self.token);
+        self
+    }
+
+    /// Returns whether the session has expired.
+    #[must_use]
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        now >= self.expires_at
+    }
+
+    /// Resolves a request path (query string included) to what it asks for.
+    #[must_use]
+    pub fn route(&self, path: &str) -> ShareRoute {
+        let path = path.split(['?', '#']).next().unwrap_or_default();
+        let Some(rest) = path.strip_prefix("/s/") else {
+            return ShareRoute::NotFound;
+        };
+        let (token, resource) = rest.split_once('/').unwrap_or((rest, ""));
+        if !token_matches(token, &self.token) {
+            return ShareRoute::NotFound;
+        }
+        if !rest.contains('/') {
+            return ShareRoute::Redirect(format!("/s/{}/", self.token));
+        }
+
+        let is_plain_file =
+            |name: &str| !name.is_empty() && !name.contains(['/', '\\']) && !name.starts_with('.');
+        match resource {
+            "" | "index.html" => ShareRoute::Index,
+            "style.css" => ShareRoute::Stylesheet,
+            _ => {
+                if let Some(page) = resource
+                    .strip_prefix(site::PAGES_DIR)
+                    .and_then(|page| page.strip_prefix('/'))
+                    .filter(|page| is_plain_file(page))
+                {
+                    ShareRoute::Page(format!("{}/{page}", site::PAGES_DIR))
+                } else if let Some(image) = resource
+                    .strip_prefix(site::IMAGES_DIR)
+                    .and_then(|image| image.strip_prefix('/'))
+                    .filter(|image| is_plain_file(image))
+                {
+                    ShareRoute::Image(format!("{}/{image}", site::IMAGES_DIR))
+                } else {
+                    ShareRoute::NotFound
+                }
+            }
+        }
+    }
+}
+
+/// What a request to a share session asks for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShareRoute {
+    /// The collection's index page
+    Index,
+    /// The site stylesheet
+    Stylesheet,
+    /// A persona page, by its site path (e.g., `personas/aria-1a2b3c4d.html`)
+    Page(String),
+    /// A reference image, by its site path (e.g., `images/aria-1a2b3c4d-1.png`)
+    Image(String),
+    /// The same location with a trailing slash, so relative links resolve
+    Redirect(String),
+    /// Anything else, including a wrong token
+    NotFound,
+}
+
+/// Compares tokens in time independent of where they differ (internal
+/// helper).
+fn token_matches(given: &str, expected: &str) -> bool {
+    given.len() == expected.len()
+        && given
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// Returns the `Content-Type` of a served file from its extension.
+#[must_use]
+pub fn content_type(path: &str) -> &'static str {
+    let extension = path
+        .rsplit_once('.')
+        .map(|(_, ext)| ext.to_ascii_lowercase());
+    match extension.as_deref() {
+        Some("html") => "text/html; charset=utf-8",
+        Some("css") => "text/css; charset=utf-8",
+        Some("png") => "image/png",
+        Some("jpg" | "jpeg") => "image/jpeg",
+        Some("webp") => "image/webp",
+        Some("gif") => "image/gif",
+        _ => "application/octet-stream",
+    }
+}
@@ -0,0 +1,665 @@
+//! Persona Sheets
+//!
+//! A printable character sheet of a persona, exported as PDF to share with
+//! commission clients who don't use the app. This module picks what goes on
+//! the sheet and lays it out; `infrastructure::pdf` writes the pages.
+//!
+//! # Templates
+//!
+//! - [`SheetTemplate::Full`]: metadata, reference images, positive and
+//!   negative token tables, prompts, and generation parameters
+//! - [`SheetTemplate::Client`]: metadata, larger reference images, positive
+//!   tokens, and the positive prompt; nothing about the technical setup
+//! - [`SheetTemplate::Compact`]: metadata, a single reference image, token
+//!   tables, and prompts
+//!
+//! # Layout
+//!
+//! Pages are A4, measured in PDF points (1/72 inch) from the bottom-left
+//! corner. Text uses the standard Helvetica and Courier fonts, which every
+//! PDF reader provides, so no font is embedded; prompts are set in Courier
+//! so weights and separators read as typed. Sections flow from page to page,
+//! and token table headers repeat on each page a table spans.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::gallery::GeneratedImage;
+use super::persona::{ContentRating, GenerationParams, Persona};
+use super::prompt::ComposedPrompt;
+use super::token::{GranularityLevel, Token, TokenPolarity};
+
+/// Page width in points (A4).
+pub const PAGE_WIDTH: f32 = 595.0;
+
+/// Page height in points (A4).
+pub const PAGE_HEIGHT: f32 = 842.0;
+
+/// Blank border around the page content.
+const MARGIN: f32 = 48.0;
+
+/// Width available to the page content.
+const CONTENT_WIDTH: f32 = PAGE_WIDTH - 2.0 * MARGIN;
+
+/// Height kept free at the bottom of each page for the footer.
+const FOOTER_HEIGHT: f32 = 24.0;
+
+/// Line height, relative to the font size.
+const LEADING: f32 = 1.35;
+
+/// Space between images and between table columns.
+const GAP: f32 = 12.0;
+
+/// Width of the granularity column of token tables.
+const LEVEL_COLUMN_WIDTH: f32 = 96.0;
+
+/// Which parts of a persona a sheet shows.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SheetTemplate {
+    /// Everything, for the persona's owner
+    #[default]
+    Full,
+    /// What a commission client needs, without the technical setup
+    Client,
+    /// Tokens and prompts with a single image
+    Compact,
+}
+
+impl SheetTemplate {
+    /// Returns whether negative tokens and the negative prompt are shown.
+    #[must_use]
+    pub const fn shows_negative(self) -> bool {
+        !matches!(self, Self::Client)
+    }
+
+    /// Returns whether generation parameters are shown.
+    #[must_use]
+    pub const fn shows_parameters(self) -> bool {
+        matches!(self, Self::Full)
+    }
+
+    /// Returns the number of reference images shown at most.
+    #[must_use]
+    pub const fn max_images(self) -> usize {
+        match self {
+            Self::Full => 6,
+            Self::Client => 8,
+            Self::Compact => 1,
+        }
+    }
+
+    /// Returns the number of reference images per row.
+    const fn image_columns(self) -> usize {
+        match self {
+            Self::Full => 3,
+            Self::Client => 2,
+            Self::Compact => 3,
+        }
+    }
+}
+
+/// A row of a sheet's token table: the tokens of one granularity level.
+#[derive(Debug, Clone)]
+pub struct SheetTokenRow {
+    /// Name of the granularity level
+    pub granularity: String,
+    /// Positive tokens as written in prompts, weights included
+    pub positive: Vec<String>,
+    /// Negative tokens as written in prompts; empty when the template hides them
+    pub negative: Vec<String>,
+}
+
+/// The content of a persona sheet, ready for layout.
+#[derive(Debug, Clone)]
+pub struct CharacterSheet {
+    /// Template the content was chosen for
+    pub template: SheetTemplate,
+    /// Persona name
+    pub title: String,
+    /// Persona description, if any
+    pub description: Option<String>,
+    /// Persona tags
+    pub tags: Vec<String>,
+    /// Persona content rating
+    pub content_rating: ContentRating,
+    /// When the persona was last modified
+    pub updated_at: DateTime<Utc>,
+    /// Token table rows, in granularity display order
+    pub token_rows: Vec<SheetTokenRow>,
+    /// Composed positive prompt
+    pub positive_prompt: String,
+    /// Composed negative prompt; empty when the template hides it
+    pub negative_prompt: String,
+    /// Generation parameters as label and value; empty when hidden
+    pub parameters: Vec<(String, String)>,
+    /// Absolute paths of the reference images, favorites first
+    pub images: Vec<String>,
+}
+
+impl CharacterSheet {
+    /// Chooses the content of a persona's sheet.
+    ///
+    /// # Arguments
+    ///
+    /// * `template` - Which parts of the persona to show
+    /// * `persona` - The persona
+    /// * `tokens` - Tokens to list, as composed (banned terms left out)
+    /// * `granularity_levels` - Levels in display order
+    /// * `prompt` - The persona's composed prompt
+    /// * `params` - Resolved generation parameters, if any
+    /// * `images` - The persona's gallery, newest first
+    #[must_use]
+    pub fn new(
+        template: SheetTemplate,
+        persona: &Persona,
+        tokens: &[Token],
+        granularity_levels: &[GranularityLevel],
+        prompt: &ComposedPrompt,
+        params: Option<&GenerationParams>,
+        images: &[GeneratedImage],
+    ) -> Self {
+        let token_rows = granularity_levels
+            .iter()
+            .map(|level| {
+                let formatted = |polarity: TokenPolarity| -> Vec<String> {
+                    tokens
+                        .iter()
+                        .filter(|t| t.granularity_id == level.id && t.polarity == polarity)
+                        .map(|t| t.format_for_prompt(true))
+                        .collect()
+                };
+                SheetTokenRow {
+                    granularity: level.name.clone(),
+                    positive: formatted(TokenPolarity::Positive),
+                    negative: if template.shows_negative() {
+                        formatted(TokenPolarity::Negative)
+                    } else {
+                        Vec::new()
+                    },
+                }
+            })
+            .filter(|row| !row.positive.is_empty() || !row.negative.is_empty())
+            .collect();
+
+        let parameters = match params {
+            Some(params) if template.shows_parameters() => parameter_lines(params),
+            _ => Vec::new(),
+        };
+
+        // Favorites first, newest first within each group
+        let mut gallery: Vec<&GeneratedImage> = images.iter().collect();
+        gallery.sort_by_key(|image| !image.starred);
+
+        Self {
+            template,
+            title: persona.name.clone(),
+            description: persona
+                .description
+                .as_ref()
+                .filter(|d| !d.trim().is_empty())
+                .cloned(),
+            tags: persona.tags.clone(),
+            content_rating: persona.content_rating,
+            updated_at: persona.updated_at,
+            token_rows,
+            positive_prompt: prompt.positive_prompt.clone(),
+            negative_prompt: if template.shows_negative() {
+                prompt.negative_prompt.clone()
+            } else {
+                String::new()
+            },
+            parameters,
+            images: gallery
+                .into_iter()
+                .take(template.max_images())
+                .map(|image| image.path.clone())
+                .collect(),
+        }
+    }
+
+    /// Returns a file name for the exported sheet, from the persona name with
+    /// characters that are invalid in file names replaced.
+    #[must_use]
+    pub fn file_name(&self) -> String {
+        let name: String = self
+            .title
+            .chars()
+            .map(|c| {
+                if c.is_control()
+                    || matches!(c, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|')
+                {
+                    '-'
+                } else {
+                    c
+                }
+            })
+            .collect();
+        let name = name.trim().trim_matches('.');
+        if name.is_empty() {
+            "persona-sheet.pdf".to_string()
+        } else {
+            format!("{name}.pdf")
+        }
+    }
+
+    /// Lays the sheet out on pages.
+    ///
+    /// # Arguments
+    ///
+    /// * `image_sizes` - Pixel size of each image in [`Self::images`], or
+    ///   `None` for images that could not be read, which are left out
+    ///
+    /// # Returns
+    ///
+    /// The pages in order, at least one, each with a numbered footer.
+    #[must_use]
+    pub fn layout(&self, image_sizes: &[Option<(u32, u32)>]) -> Vec<SheetPage> {
+        let mut layout = Layout::new();
+
+        // Metadata
+        layout.paragraph(&self.title, SheetFont::Bold, 22.0);
+        let rating = match self.content_rating {
+            ContentRating::General => "General",
+            ContentRating::Mature => "Mature",
+        };
+        layout.paragraph(
+            &format!(
+                "Rating: {rating} \u{b7} Updated {}",
+                self.updated_at.format("%Y-%m-%d")
+            ),
+            SheetFont::Regular,
+            9.0,
+        );
+        if !self.tags.is_empty() {
+            layout.paragraph(
+                &format!("Tags: {}", self.tags.join(", ")),
+                SheetFont::Regular,
+                9.0,
+            );
+        }
+        if let Some(description) = &self.description {
+            layout.gap(6.0);
+            for line in description.lines() {
+                layout.paragraph(line, SheetFont::Regular, 10.5);
+            }
+        }
+
+        let images: Vec<(usize, (u32, u32))> = image_sizes
+            .iter()
+            .take(self.images.len())
+            .enumerate()
+            .filter_map(|(index, size)| size.map(|size| (index, size)))
+            .filter(|(_, (width, height))| *width > 0 && *height > 0)
+            .collect();
+        if !images.is_empty() {
+            layout.heading("Reference images");
+            layout.images(&images, self.template.image_columns());
+        }
+
+        layout.heading("Tokens");
+        if self.token_rows.is_empty() {
+            layout.paragraph("No tokens.", SheetFont::Regular, 10.0);
+        } else {
+            layout.token_table(&self.token_rows, self.template.shows_negative());
+        }
+
+        if !self.positive_prompt.is_empty() || !self.negative_prompt.is_empty() {
+            layout.heading("Prompts");
+            for (label, prompt) in [
+                ("Positive", &self.positive_prompt),
+                ("Negative", &self.negative_prompt),
+            ] {
+                if !prompt.is_empty() {
+                    layout.paragraph(label, SheetFont::Bold, 10.0);
+                    layout.paragraph(prompt, SheetFont::Mono, 8.5);
+                    layout.gap(4.0);
+                }
+            }
+        }
+
+        if !self.parameters.is_empty() {
+            layout.heading("Generation parameters");
+            for (label, value) in &self.parameters {
+                layout.paragraph(&format!("{label}: {value}"), SheetFont::Regular, 10.0);
+            }
+        }
+
+        layout.finish(&self.title)
+    }
+}
+
+/// Labels and values of the parameters shown on a sheet (internal helper).
+fn parameter_lines(params: &GenerationParams) -> Vec<(String, String)> {
+    let mut lines = vec![
+        ("Model".to_string(), params.model_id.clone()),
+        (
+            "Seed".to_string(),
+            if params.seed < 0 {
+                "random".to_string()
+            } else {
+                params.seed.to_string()
+            },
+        ),
+        ("Steps".to_string(), params.steps.to_string()),
+        ("CFG scale".to_string(), format!("{:.1}", params.cfg_scale)),
+    ];
+    if let Some(sampler) = &params.sampler {
+        lines.push(("Sampler".to_string(), sampler.clone()));
+    }
+    if let Some(scheduler) = &params.scheduler {
+        lines.push(("Scheduler".to_string(), scheduler.clone()));
+    }
+    if let (Some(width), Some(height)) = (params.width, params.height) {
+        lines.push(("Size".to_string(), format!("{width}\u{d7}{height}")));
+    }
+    lines
+}
+
+/// A font of the sheet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SheetFont {
+    /// Helvetica
+    Regular,
+    /// Helvetica Bold
+    Bold,
+    /// Courier, for prompts
+    Mono,
+}
+
+/// Something drawn on a page. Coordinates are in points from the bottom-left
+/// corner of the page.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SheetItem {
+    /// A single line of text; `y` is its baseline
+    Text {
+        x: f32,
+        y: f32,
+        size: f32,
+        font: SheetFont,
+        text: String,
+    },
+    /// A thin gray rule
+    Line { x1: f32, y1: f32, x2: f32, y2: f32 },
+    /// An image of [`CharacterSheet::images`], scaled into a box;
+    /// `(x, y)` is its bottom-left corner
+    Image {
+        index: usize,
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+    },
+}
+
+/// The items of one page.
+#[derive(Debug, Clone, Default)]
+pub struct SheetPage {
+    /// Items in drawing order
+    pub items: Vec<SheetItem>,
+}
+
+/// Flows content down pages, starting new pages as they fill.
+struct Layout {
+    pages: Vec<SheetPage>,
+    /// Top of the remaining space on the last page
+    y: f32,
+}
+
+impl Layout {
+    fn new() -> Self {
+        Self {
+            pages: vec![SheetPage::default()],
+            y: PAGE_HEIGHT - MARGIN,
+        }
+    }
+
+    fn push(&mut self, item: SheetItem) {
+        if let Some(page) = self.pages.last_mut() {
+            page.items.push(item);
+        }
+    }
+
+    /// Starts a new page unless `height` fits on the current one.
+    /// Returns whether a page was started.
+    fn reserve(&mut self, height: f32) -> bool {
+        let at_top = self.y >= PAGE_HEIGHT - MARGIN;
+        if self.y - height >= MARGIN + FOOTER_HEIGHT || at_top {
+            return false;
+        }
+        self.pages.push(SheetPage::default());
+        self.y = PAGE_HEIGHT - MARGIN;
+        true
+    }
+
+    fn gap(&mut self, height: f32) {
+        self.y -= height;
+    }
+
+    fn text(&mut self, x: f32, size: f32, font: SheetFont, text: String) {
+        self.push(SheetItem::Text {
+            x,
+            y: self.y - size,
+            size,
+            font,
+            text,
+        });
+    }
+
+    /// Writes text wrapped to the content width.
+    fn paragraph(&mut self, text: &str, font: SheetFont, size: f32) {
+        let line_height = size * LEADING;
+        for line in wrap(text, font, size, CONTENT_WIDTH) {
+            self.reserve(line_height);
+            self.text(MARGIN, size, font, line);
+            self.y -= line_height;
+        }
+    }
+
+    /// Writes a section title with a rule under it, on a new page if the
+    /// title would otherwise end a page.
+    fn heading(&mut self, title: &str) {
+        self.gap(14.0);
+        self.reserve(60.0);
+        self.text(MARGIN, 13.0, SheetFont::Bold, title.to_string());
+        self.y -= 13.0 * LEADING;
+        self.push(SheetItem::Line {
+            x1: MARGIN,
+            y1: self.y,
+            x2: PAGE_WIDTH - MARGIN,
+            y2: self.y,
+        });
+        self.gap(6.0);
+    }
+
+    /// Places images in rows of square boxes, keeping their aspect ratio.
+    fn images(&mut self, images: &[(usize, (u32, u32))], columns: usize) {
+        let columns = columns.max(1);
+        let box_size = GAP.mul_add(-((columns - 1) as f32), CONTENT_WIDTH) / columns as f32;
+        for row in images.chunks(columns) {
+            self.reserve(box_size);
+            for (column, (index, (width, height))) in row.iter().enumerate() {
+                let scale = (box_size / *width as f32).min(box_size / *height as f32);
+                let (width, height) = (*width as f32 * scale, *height as f32 * scale);
+                let left = (column as f32).mul_add(box_size + GAP, MARGIN);
+                self.push(SheetItem::Image {
+                    index: *index,
+                    x: left + (box_size - width) / 2.0,
+                    y: self.y - height,
+                    width,
+                    height,
+                });
+            }
+            self.y -= box_size + GAP;
+        }
+    }
+
+    /// Writes the token table, one row per granularity level. Rows longer
+    /// than a page continue on the next, under a repeated header.
+    fn token_table(&mut self, rows: &[SheetTokenRow], show_negative: bool) {
+        const SIZE: f32 = 9.0;
+        let line_height = SIZE * LEADING;
+        let token_columns = if show_negative { 2.0 } else { 1.0 };
+        let token_width =
+            GAP.mul_add(-token_columns, CONTENT_WIDTH - LEVEL_COLUMN_WIDTH) / token_columns;
+        let xs = [
+            MARGIN,
+            MARGIN + LEVEL_COLUMN_WIDTH + GAP,
+            2.0f32.mul_add(GAP, MARGIN + LEVEL_COLUMN_WIDTH) + token_width,
+        ];
+        let headers: &[&str] = if show_negative {
+            &["Level", "Positive", "Negative"]
+        } else {
+            &["Level", "Positive"]
+        };
+
+        let header = |layout: &mut Self| {
+            for (x, title) in xs.iter().zip(headers) {
+                layout.text(*x, SIZE, SheetFont::Bold, (*title).to_string());
+            }
+            layout.y -= line_height;
+            layout.rule();
+        };
+
+        self.reserve(line_height * 3.0);
+        header(self);
+        for row in rows {
+            let mut cells = vec![
+                wrap(&row.granularity, SheetFont::Bold, SIZE, LEVEL_COLUMN_WIDTH),
+                wrap(
+                    &row.positive.join(", "),
+                    SheetFont::Regular,
+                    SIZE,
+                    token_width,
+                ),
+            ];
+            if show_negative {
+                cells.push(wrap(
+                    &row.negative.join(", "),
+                    SheetFont::Regular,
+                    SIZE,
+                    token_width,
+                ));
+            }
+
+            self.gap(3.0);
+            let lines = cells.iter().map(Vec::len).max().unwrap_or(0);
+            for line in 0..lines {
+                if self.reserve(line_height) {
+                    header(self);
+                    self.gap(3.0);
+                }
+                for (cell, x) in cells.iter().zip(xs) {
+                    if let Some(text) = cell.get(line) {
+                        let font = if x == MARGIN {
+                            SheetFont::Bold
+                        } else {
+                            SheetFont::Regular
+                        };
+                        self.text(x, SIZE, font, text.clone());
+                    }
+                }
+                self.y -= line_height;
+            }
+            self.rule();
+        }
+    }
+
+    fn rule(&mut self) {
+        self.gap(2.0);
+        self.push(SheetItem::Line {
+            x1: MARGIN,
+            y1: self.y,
+            x2: PAGE_WIDTH - MARGIN,
+            y2: self.y,
+        });
+    }
+
+    /// Adds the page footers and returns the pages.
+    fn finish(mut self, title: &str) -> Vec<SheetPage> {
+        let count = self.pages.len();
+        for (number, page) in self.pages.iter_mut().enumerate() {
+            page.items.push(SheetItem::Text {
+                x: MARGIN,
+                y: MARGIN / 2.0,
+                size: 8.0,
+                font: SheetFont::Regular,
+                text: format!("{title} \u{b7} page {} of {count}", number + 1),
+            });
+        }
+        self.pages
+    }
+}
+
+/// Breaks text into lines no wider than `width`, at spaces where possible.
+fn wrap(text: &str, font: SheetFont, size: f32, width: f32) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        let candidate = if line.is_empty() {
+            word.to_string()
+        } else {
+            format!("{line} {word}")
+        };
+        if text_width(&candidate, font, size) <= width {
+            line = candidate;
+            continue;
+        }
+        if !line.is_empty() {
+            lines.push(std::mem::take(&mut line));
+        }
+        // Words wider than a line are cut wherever they overflow
+        for c in word.chars() {
+            line.push(c);
+            if text_width(&line, font, size) > width && line.chars().count() > 1 {
+                line.pop();
+                lines.push(std::mem::replace(&mut line, c.to_string()));
+            }
+        }
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// Returns the width of a line of text in points.
+#[must_use]
+pub fn text_width(text: &str, font: SheetFont, size: f32) -> f32 {
+    let units: u32 = text.chars().map(|c| glyph_width(c, font)).sum();
+    units as f32 * size / 1000.0
+}
+
+/// Advance width of a character in 1/1000 em, from the standard font
+/// metrics. Characters outside printable ASCII are counted as an average
+/// letter (internal helper).
+fn glyph_width(c: char, font: SheetFont) -> u32 {
+    /// Helvetica widths of ' ' to '~'
+    const REGULAR: [u16; 95] = [
+        278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556,
+        556, 556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556, 1015, 667, 667, 722,
+        722, 667, 611, 778, 722, 278, 500, 667, 556, 833, 722, 778, 667, 778, 722, 667, 611, 722,
+        667, 944, 667, 667, 611, 278, 278, 278, 469, 556, 333, 556, 556, 500, 556, 556, 278, 556,
+        556, 222, 222, 500, 222, 833, 556, 556, 556, 556, 333, 500, 278, 556, 500, 722, 500, 500,
+        500, 334, 260, 334, 584,
+    ];
+    /// Helvetica Bold widths of ' ' to '~'
+    const BOLD: [u16; 95] = [
+        278, 333, 474, 556, 556, 889, 722, 238, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556,
+        556, 556, 556, 556, 556, 556, 556, 556, 333, 333, 584, 584, 584, 611, 975, 722, 722, 722,
+        722, 667, 611, 778, 722, 278, 556, 722, 611, 833, 722, 778, 667, 778, 722, 667, 611, 722,
+        667, 944, 667, 667, 611, 333, 278, 333, 584, 556, 333, 556, 611, 556, 611, 556, 333, 611,
+        611, 278, 278, 556, 278, 889, 611, 611, 611, 611, 389, 556, 333, 611, 556, 778, 556, 556,
+        500, 389, 280, 389, 584,
+    ];
+
+    let table = match font {
+        SheetFont::Mono => return 600,
+        SheetFont::Regular => &REGULAR,
+        SheetFont::Bold => &BOLD,
+    };
+    (c as usize)
+        .checked_sub(' ' as usize)
+        .and_then(|index| table.get(index))
+        .map_or(556, |width| u32::from(*width))
+}
@@ -0,0 +1,130 @@
+//! Persona Similarity
+//!
+//! Ranks personas by how much they overlap with a given persona, to surface
+//! near-duplicate characters.
+//!
+//! # Scoring
+//!
+//! - **Tokens**: Jaccard index of the two token sets. Tokens are compared by
+//!   polarity and normalized content (case, accents, and whitespace ignored),
+//!   with aliased tokens compared by their phrasing (see `domain::alias`);
+//!   granularity and weight are not compared, so the same token filed under
+//!   another level still counts as shared.
+//! - **Tags**: Jaccard index of the two tag sets, compared the same way.
+//!
+//! The score weighs token overlap at [`TOKEN_SCORE_WEIGHT`] and tag overlap at
+//! the rest. If neither persona has tags, the score is the token overlap alone.
+
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use super::alias::AliasMap;
+use super::collation;
+use super::persona::Persona;
+use super::tag::normalize_tag;
+use super::token::{Token, TokenPolarity};
+
+/// Share of the score given to token overlap; tag overlap gets the rest.
+pub const TOKEN_SCORE_WEIGHT: f64 = 0.8;
+
+/// A persona ranked by similarity to another persona.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimilarPersona {
+    /// The similar persona
+    pub persona: Persona,
+    /// Overall similarity from 0 (nothing shared) to 1 (same tokens and tags)
+    pub score: f64,
+    /// Jaccard index of the token sets
+    pub token_similarity: f64,
+    /// Number of tokens both personas have
+    pub shared_token_count: usize,
+    /// Tags both personas have, as spelled on the similar persona
+    pub shared_tags: Vec<String>,
+}
+
+/// A persona's tokens and tags, normalized for comparison.
+#[derive(Debug, Clone)]
+pub struct SimilarityProfile {
+    tokens: HashSet<(TokenPolarity, String)>,
+    tags: HashSet<String>,
+}
+
+impl SimilarityProfile {
+    /// Builds the profile of a persona with its tokens, resolving aliases.
+    #[must_use]
+    pub fn new(persona: &Persona, tokens: &[Token], aliases: &AliasMap) -> Self {
+        Self {
+            tokens: tokens
+                .iter()
+                .map(|token| (token.polarity, aliases.key(&token.content)))
+                .filter(|(_, content)| !content.is_empty())
+                .collect(),
+            tags: persona
+                .tags
+                .iter()
+                .map(|tag| collation::fold(&normalize_tag(tag)))
+                .filter(|tag| !tag.is_empty())
+                .collect(),
+        }
+    }
+
+    /// Compares another persona to this profile.
+    ///
+    /// # Returns
+    ///
+    /// The ranked persona, or `None` if it shares no tokens and no tags.
+    #[must_use]
+    pub fn compare(&self, persona: Persona, other: &Self) -> Option<SimilarPersona> {
+        let shared_token_count = self.tokens.intersection(&other.tokens).count();
+        let shared_tags: Vec<String> = persona
+            .tags
+            .iter()
+            .filter(|tag| self.tags.contains(&collation::fold(&normalize_tag(tag))))
+            .cloned()
+            .collect();
+        if shared_token_count == 0 && shared_tags.is_empty() {
+            return None;
+        }
+
+        let token_similarity = jaccard(shared_token_count, self.tokens.len() + other.tokens.len());
+        let score = if self.tags.is_empty() && other.tags.is_empty() {
+            token_similarity
+        } else {
+            let shared_tag_count = self.tags.intersection(&other.tags).count();
+            let tag_similarity = jaccard(shared_tag_count, self.tags.len() + other.tags.len());
+            TOKEN_SCORE_WEIGHT.mul_add(
+                token_similarity,
+                (1.0 - TOKEN_SCORE_WEIGHT) * tag_similarity,
+            )
+        };
+
+        Some(SimilarPersona {
+            persona,
+            score,
+            token_similarity,
+            shared_token_count,
+            shared_tags,
+        })
+    }
+}
+
+/// Orders similar personas, most similar first, then by name.
+pub fn sort_by_similarity(similar: &mut [SimilarPersona]) {
+    similar.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| collation::compare(&a.persona.name, &b.persona.name))
+    });
+}
+
+/// Jaccard index of two sets, from the size of their intersection and the
+/// sum of their sizes.
+fn jaccard(shared: usize, total: usize) -> f64 {
+    let union = total - shared;
+    if union == 0 {
+        0.0
+    } else {
+        shared as f64 / union as f64
+    }
+}
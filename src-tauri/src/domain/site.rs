@@ -0,0 +1,386 @@
+//! Library Sites
+//!
+//! A static HTML export of selected personas, for publishing a character wiki
+//! without sharing the database. The site works from any web server or
+//! straight from disk, with no server-side code.
+//!
+//! # Layout
+//!
+//! ```text
+//! index.html              Persona cards with a client-side search box
+//! style.css
+//! personas/<slug>.html    One page per persona, with the content of its
+//!                         persona sheet (see `super::sheet`)
+//! images/<slug>-<n>.<ext> Copies of the reference images
+//! ```
+//!
+//! Pages show what a full persona sheet shows, except generation parameters.
+//! Nothing else from the library is written: no file paths, AI settings, or
+//! other personas.
+//!
+//! # Search
+//!
+//! Each card on the index carries its name, tags, description, and tokens,
+//! folded as in `super::collation`; a small inline script hides the cards
+//! that do not contain every typed word, ignoring case and accents.
+
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+
+use super::collation;
+use super::persona::ContentRating;
+use super::sheet::CharacterSheet;
+
+/// Generator name written in the index, to recognize earlier exports.
+const GENERATOR: &str = "Persona Prompt Manager";
+
+/// Folder of the persona pages, relative to the site root.
+pub const PAGES_DIR: &str = "personas";
+
+/// Folder of the copied images, relative to the site root.
+pub const IMAGES_DIR: &str = "images";
+
+/// Image file extensions browsers display, lowercase.
+const IMAGE_EXTENSIONS: [&str; 5] = ["png", "jpg", "jpeg", "webp", "gif"];
+
+/// Stylesheet of the site, written as `style.css`.
+pub const STYLESHEET: &str = r"* { box-sizing: border-box; }
+body { margin: 0; font: 15px/1.5 system-ui, sans-serif; color: #1f2328; background: #f6f7f9; }
+header, main, footer { max-width: 1080px; margin: 0 auto; padding: 16px 24px; }
+header h1 { margin: 8px 0; }
+a { color: #3b5bdb; text-decoration: none; }
+a:hover { text-decoration: underline; }
+input[type=search] { width: 100%; padding: 10px 12px; font-size: 16px; border: 1px solid #ccd; border-radius: 8px; }
+.cards { display: grid; grid-template-columns: repeat(auto-fill, minmax(220px, 1fr)); gap: 16px; }
+.card { background: #fff; border-radius: 10px; overflow: hidden; box-shadow: 0 1px 3px rgba(0,0,0,.12); color: inherit; }
+.card:hover { text-decoration: none; box-shadow: 0 2px 8px rgba(0,0,0,.2); }
+.card img, .card .placeholder { width: 100%; aspect-ratio: 1; object-fit: cover; display: block; background: #e3e6eb; }
+.card div { padding: 10px 12px; }
+.card h2 { font-size: 17px; margin: 0 0 4px; }
+.tags { display: flex; flex-wrap: wrap; gap: 4px; margin: 4px 0; padding: 0; list-style: none; }
+.tags li { background: #e7ebf3; border-radius: 10px; padding: 0 8px; font-size: 12px; }
+.meta { color: #667; font-size: 13px; }
+.gallery { display: grid; grid-template-columns: repeat(auto-fill, minmax(200px, 1fr)); gap: 12px; }
+.gallery img { width: 100%; border-radius: 8px; display: block; }
+table { width: 100%; border-collapse: collapse; background: #fff; }
+th, td { text-align: left; vertical-align: top; padding: 6px 10px; border-bottom: 1px solid #e3e6eb; }
+pre { white-space: pre-wrap; word-break: break-word; background: #fff; padding: 12px; border-radius: 8px; }
+footer { color: #667; font-size: 13px; }
+";
+
+/// Client-side search of the index cards.
+const SEARCH_SCRIPT: &str = r"const search = document.getElementById('search');
+const cards = Array.from(document.querySelectorAll('.card'));
+const empty = document.getElementById('empty');
+search.addEventListener('input', () => {
+  const terms = search.value
+    .normalize('NFKD')
+    .replace(/[\u0300-\u036f]/g, '')
+    .toLowerCase()
+    .split(/\s+/)
+    .filter(Boolean);
+  let shown = 0;
+  for (const card of cards) {
+    const visible = terms.every((term) => card.dataset.search.includes(term));
+    card.hidden = !visible;
+    if (visible) shown += 1;
+  }
+  empty.hidden = shown > 0;
+});";
+
+/// A persona published on the site.
+#[derive(Debug, Clone)]
+pub struct SitePersona {
+    /// Path of the persona page, relative to the site root
+    pub page: String,
+    /// The persona's content
+    pub sheet: CharacterSheet,
+    /// Paths of the copied images, relative to the site root, in sheet order
+    pub images: Vec<String>,
+}
+
+impl SitePersona {
+    /// Creates the site entry of a persona, before its images are copied.
+    ///
+    /// # Arguments
+    ///
+    /// * `persona_id` - UUID of the persona, keeping file names unique
+    /// * `sheet` - The persona's content
+    #[must_use]
+    pub fn new(persona_id: &str, sheet: CharacterSheet) -> Self {
+        let mut slug = String::new();
+        for c in collation::fold(&sheet.title).chars() {
+            if c.is_ascii_alphanumeric() {
+                slug.push(c);
+            } else if !slug.is_empty() && !slug.ends_with('-') {
+                slug.push('-');
+            }
+        }
+        let slug: String = slug.chars().take(40).collect();
+        let slug = match slug.trim_end_matches('-') {
+            "" => "persona",
+            slug => slug,
+        };
+        let short_id: String = persona_id.chars().take(8).collect();
+
+        Self {
+            page: format!("{PAGES_DIR}/{slug}-{short_id}.html"),
+            sheet,
+            images: Vec::new(),
+        }
+    }
+
+    /// Returns the site path for the persona's image at `index` of the sheet.
+    #[must_use]
+    pub fn image_path(&self, index: usize, extension: &str) -> String {
+        let slug = self
+            .page
+            .trim_start_matches(PAGES_DIR)
+            .trim_start_matches('/')
+            .trim_end_matches(".html");
+        format!("{IMAGES_DIR}/{slug}-{}.{extension}", index + 1)
+    }
+
+    /// Returns the site path and source path of each reference image in a
+    /// format browsers display, in sheet order.
+    #[must_use]
+    pub fn image_sources(&self) -> Vec<(String, String)> {
+        self.sheet
+            .images
+            .iter()
+            .enumerate()
+            .filter_map(|(index, source)| {
+                let extension = Path::new(source)
+                    .extension()
+                    .map(|ext| ext.to_string_lossy().to_lowercase())
+                    .filter(|ext| IMAGE_EXTENSIONS.contains(&ext.as_str()))?;
+                Some((self.image_path(index, &extension), source.clone()))
+            })
+            .collect()
+    }
+}
+
+/// Returns the start of the persona UUID in a page or image site path, as
+/// written by [`SitePersona::new`].
+#[must_use]
+pub fn persona_id_prefix(path: &str) -> Option<&str> {
+    let name = path.rsplit('/').next()?;
+    let stem = name.split('.').next()?;
+    let stem = if path.starts_with(IMAGES_DIR) {
+        stem.rsplit_once('-')?.0
+    } else {
+        stem
+    };
+    let (_, prefix) = stem.rsplit_once('-')?;
+    (!prefix.is_empty()).then_some(prefix)
+}
+
+/// Returns whether an existing `index.html` was written by a site export, so
+/// its folder can be exported to again.
+#[must_use]
+pub fn is_site_index(html: &str) -> bool {
+    html.contains(&format!(r#"<meta name="generator" content="{GENERATOR}">"#))
+}
+
+/// Renders the index page listing every persona.
+///
+/// # Arguments
+///
+/// * `personas` - Published personas, in display order
+/// * `generated_at` - Export time, shown in the footer
+#[must_use]
+pub fn render_index(personas: &[SitePersona], generated_at: DateTime<Utc>) -> String {
+    let mut cards = String::new();
+    for persona in personas {
+        let sheet = &persona.sheet;
+        let mut haystack = vec![sheet.title.clone()];
+        haystack.extend(sheet.tags.iter().cloned());
+        haystack.extend(sheet.description.iter().cloned());
+        for row in &sheet.token_rows {
+            haystack.extend(row.positive.iter().cloned());
+        }
+        let haystack = collation::fold(&haystack.join(" "))
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        let thumbnail = persona.images.first().map_or_else(
+            || r#"<span class="placeholder"></span>"#.to_string(),
+            |image| format!(r#"<img src="{}" alt="" loading="lazy">"#, escape(image)),
+        );
+        cards.push_str(&format!(
+            r#"<a class="card" href="{}" data-search="{}">{thumbnail}<div><h2>{}</h2>{}</div></a>
+"#,
+            escape(&persona.page),
+            escape(&haystack),
+            escape(&sheet.title),
+            tag_list(&sheet.tags),
+        ));
+    }
+
+    let count = match personas.len() {
+        1 => "1 persona".to_string(),
+        n => format!("{n} personas"),
+    };
+    document(
+        "Persona Library",
+        "",
+        &format!(
+            r#"<header><h1>Persona Library</h1><p class="meta">{count}</p>
+<input type="search" id="search" placeholder="Search names, tags, and tokens" autofocus></header>
+<main><div class="cards">
+{cards}</div><p id="empty" hidden>No persona matches.</p></main>
+{}
+<script>{SEARCH_SCRIPT}</script>"#,
+            footer(generated_at)
+        ),
+    )
+}
+
+/// Renders the page of one persona.
+///
+/// # Arguments
+///
+/// * `persona` - The published persona
+/// * `generated_at` - Export time, shown in the footer
+#[must_use]
+pub fn render_page(persona: &SitePersona, generated_at: DateTime<Utc>) -> String {
+    let sheet = &persona.sheet;
+    let mut body = format!(
+        r#"<header><a href="../index.html">&larr; All personas</a><h1>{}</h1>
+<p class="meta">{} &middot; Updated {}</p>{}</header>
+<main>
+"#,
+        escape(&sheet.title),
+        match sheet.content_rating {
+            ContentRating::General => "General",
+            ContentRating::Mature => "Mature",
+        },
+        sheet.updated_at.format("%Y-%m-%d"),
+        tag_list(&sheet.tags),
+    );
+
+    if let Some(description) = &sheet.description {
+        for paragraph in description.split("\n\n") {
+            body.push_str(&format!(
+                "<p>{}</p>\n",
+                escape(paragraph.trim()).replace('\n', "<br>")
+            ));
+        }
+    }
+
+    if !persona.images.is_empty() {
+        body.push_str("<h2>Reference images</h2>\n<div class=\"gallery\">\n");
+        for image in &persona.images {
+            let src = format!("../{}", escape(image));
+            body.push_str(&format!(
+                r#"<a href="{src}"><img src="{src}" alt="" loading="lazy"></a>
+"#
+            ));
+        }
+        body.push_str("</div>\n");
+    }
+
+    body.push_str("<h2>Tokens</h2>\n");
+    if sheet.token_rows.is_empty() {
+        body.push_str("<p>No tokens.</p>\n");
+    } else {
+        let show_negative = sheet.template.shows_negative();
+        body.push_str("<table><thead><tr><th>Level</th><th>Positive</th>");
+        if show_negative {
+            body.push_str("<th>Negative</th>");
+        }
+        body.push_str("</tr></thead><tbody>\n");
+        for row in &sheet.token_rows {
+            body.push_str(&format!(
+                "<tr><th>{}</th><td>{}</td>",
+                escape(&row.granularity),
+                escape(&row.positive.join(", "))
+            ));
+            if show_negative {
+                body.push_str(&format!("<td>{}</td>", escape(&row.negative.join(", "))));
+            }
+            body.push_str("</tr>\n");
+        }
+        body.push_str("</tbody></table>\n");
+    }
+
+    if !sheet.positive_prompt.is_empty() || !sheet.negative_prompt.is_empty() {
+        body.push_str("<h2>Prompts</h2>\n");
+        for (label, prompt) in [
+            ("Positive", &sheet.positive_prompt),
+            ("Negative", &sheet.negative_prompt),
+        ] {
+            if !prompt.is_empty() {
+                body.push_str(&format!(
+                    "<h3>{label}</h3>\n<pre>{}</pre>\n",
+                    escape(prompt)
+                ));
+            }
+        }
+    }
+
+    body.push_str("</main>\n");
+    body.push_str(&footer(generated_at));
+    document(&sheet.title, "../", &body)
+}
+
+/// Wraps a page body in the HTML skeleton (internal helper).
+fn document(title: &str, root: &str, body: &str) -> String {
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<meta name="generator" content="{GENERATOR}">
+<title>{}</title>
+<link rel="stylesheet" href="{root}style.css">
+</head>
+<body>
+{body}
+</body>
+</html>
+"#,
+        escape(title)
+    )
+}
+
+/// Renders tags as a list of chips; empty without tags (internal helper).
+fn tag_list(tags: &[String]) -> String {
+    if tags.is_empty() {
+        return String::new();
+    }
+    let mut list = r#"<ul class="tags">"#.to_string();
+    for tag in tags {
+        list.push_str(&format!("<li>{}</li>", escape(tag)));
+    }
+    list.push_str("</ul>");
+    list
+}
+
+/// Renders the page footer (internal helper).
+fn footer(generated_at: DateTime<Utc>) -> String {
+    format!(
+        "<footer>Exported with {GENERATOR} on {}</footer>",
+        generated_at.format("%Y-%m-%d")
+    )
+}
+
+/// Escapes text for HTML content and attribute values.
+#[must_use]
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
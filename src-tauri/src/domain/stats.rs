@@ -0,0 +1,99 @@
+//! Library Statistics
+//!
+//! Aggregate counts over the whole library for the dashboard: how many
+//! personas and tokens there are, how tokens spread across granularity
+//! levels, which tags are used most, and which model families personas target.
+//!
+//! Counts are computed in SQL, so gathering them does not load every persona
+//! and token. In safe mode, mature-rated personas and their tokens are left
+//! out, as everywhere else.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::collation;
+
+/// Aggregate counts over the library.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LibraryStats {
+    /// Number of personas, including archived ones
+    pub persona_count: u32,
+    /// Number of archived personas
+    pub archived_persona_count: u32,
+    /// Number of tokens across all personas
+    pub token_count: u32,
+    /// Average number of tokens per persona (0 without personas)
+    pub avg_tokens_per_persona: f64,
+    /// Token counts per granularity level, in level order
+    pub tokens_per_granularity: Vec<GranularityTokenCount>,
+    /// Number of personas per tag, most used first
+    pub tag_histogram: Vec<TagCount>,
+    /// Number of personas per target model family, most used first
+    pub personas_per_model_family: Vec<ModelFamilyCount>,
+}
+
+/// Token counts of one granularity level.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GranularityTokenCount {
+    /// The level's ID
+    pub granularity_id: String,
+    /// The level's display name (the ID for levels that no longer exist)
+    pub name: String,
+    /// Number of positive tokens
+    pub positive: u32,
+    /// Number of negative tokens
+    pub negative: u32,
+}
+
+/// Number of personas with a tag.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagCount {
+    /// The tag, as stored
+    pub tag: String,
+    /// Number of personas with the tag
+    pub count: u32,
+}
+
+/// Number of personas targeting a model family.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelFamilyCount {
+    /// Model family identifier (sdxl, pixart, flux, ...)
+    pub family: String,
+    /// Number of personas whose generation parameters target the family
+    pub count: u32,
+}
+
+/// Sorts tag counts, most used first, then alphabetically.
+pub fn sort_tag_counts(tags: &mut [TagCount]) {
+    tags.sort_by(|a, b| {
+        b.count
+            .cmp(&a.count)
+            .then_with(|| collation::compare(&a.tag, &b.tag))
+    });
+}
+
+/// Sums persona counts per model into counts per model family.
+///
+/// # Arguments
+///
+/// * `counts` - Model family and persona count of each model
+///
+/// # Returns
+///
+/// Counts per family, most used first, then by family name.
+pub fn model_family_counts(
+    counts: impl IntoIterator<Item = (String, u32)>,
+) -> Vec<ModelFamilyCount> {
+    let mut by_family: HashMap<String, u32> = HashMap::new();
+    for (family, count) in counts {
+        *by_family.entry(family).or_default() += count;
+    }
+
+    let mut families: Vec<ModelFamilyCount> = by_family
+        .into_iter()
+        .map(|(family, count)| ModelFamilyCount { family, count })
+        .collect();
+    families.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.family.cmp(&b.family)));
+    families
+}
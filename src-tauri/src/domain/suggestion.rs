@@ -0,0 +1,199 @@
+//! Pending Suggestions
+//!
+//! An inbox of AI-suggested tokens awaiting review. AI token suggestions for a
+//! persona land here instead of living only in an IPC response, so they
+//! survive a window reload; the user accepts them, which appends them as
+//! tokens, or rejects them.
+//!
+//! # Sources
+//!
+//! [`SuggestionSource`] records what produced a suggestion: the token
+//! suggestions of the compose view, or a scheduled style refresh (see
+//! `domain::schedule`).
+//!
+//! # Duplicates
+//!
+//! A persona holds each suggestion at most once (same granularity, polarity,
+//! and content, ignoring case), whatever its source. Suggestions matching one
+//! of the persona's tokens are never staged.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::ai::TokenGenerationResponse;
+use super::token::{GeneratedTokenSelection, Token, TokenPolarity};
+
+/// Event name emitted when new suggestions were staged.
+pub const SUGGESTIONS_STAGED_EVENT: &str = "suggestions-staged";
+
+/// What produced a pending suggestion.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SuggestionSource {
+    /// AI token suggestions requested for a persona
+    TokenSuggestions,
+    /// A scheduled style refresh
+    ScheduledRefresh,
+}
+
+impl SuggestionSource {
+    /// Returns the string representation for database storage.
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::TokenSuggestions => "token_suggestions",
+            Self::ScheduledRefresh => "scheduled_refresh",
+        }
+    }
+
+    /// Parses from database string representation.
+    #[must_use]
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "token_suggestions" => Some(Self::TokenSuggestions),
+            "scheduled_refresh" => Some(Self::ScheduledRefresh),
+            _ => None,
+        }
+    }
+}
+
+/// An AI-suggested token waiting to be accepted or rejected.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingSuggestion {
+    /// Unique identifier (UUID v4)
+    pub id: String,
+    /// Persona the token is suggested for
+    pub persona_id: String,
+    /// What produced the suggestion
+    pub source: SuggestionSource,
+    /// Scheduled task that staged the suggestion, if any; `None` once the task is deleted
+    pub task_id: Option<String>,
+    /// Granularity level ID
+    pub granularity_id: String,
+    /// Token polarity
+    pub polarity: TokenPolarity,
+    /// Suggested token text
+    pub content: String,
+    /// Suggested weight
+    pub weight: f64,
+    /// AI's explanation for the suggestion
+    pub rationale: Option<String>,
+    /// When the suggestion was staged
+    pub created_at: DateTime<Utc>,
+}
+
+impl PendingSuggestion {
+    /// Collects the suggestions of a token response worth staging.
+    ///
+    /// Suggestions matching one of the persona's tokens or another suggestion
+    /// of the response (case-insensitive) are left out.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - What produced the response
+    /// * `task_id` - Scheduled task that requested it, if any
+    /// * `persona_id` - Persona the tokens are suggested for
+    /// * `granularity_id` - Granularity level the tokens were requested for
+    /// * `response` - The AI's answer
+    /// * `tokens` - The persona's tokens, inherited ones included
+    #[must_use]
+    pub fn from_response(
+        source: SuggestionSource,
+        task_id: Option<&str>,
+        persona_id: &str,
+        granularity_id: &str,
+        response: TokenGenerationResponse,
+        tokens: &[Token],
+    ) -> Vec<Self> {
+        let now = Utc::now();
+        let mut seen: Vec<(TokenPolarity, String)> = tokens
+            .iter()
+            .map(|t| (t.polarity, t.content.trim().to_lowercase()))
+            .collect();
+
+        let generated = response
+            .positive_tokens
+            .into_iter()
+            .map(|t| (TokenPolarity::Positive, t))
+            .chain(
+                response
+                    .negative_tokens
+                    .into_iter()
+                    .map(|t| (TokenPolarity::Negative, t)),
+            );
+
+        let mut suggestions = Vec::new();
+        for (polarity, generated) in generated {
+            let content = generated.content.trim().to_string();
+            let key = (polarity, content.to_lowercase());
+            if content.is_empty() || seen.contains(&key) {
+                continue;
+            }
+            seen.push(key);
+            suggestions.push(Self {
+                id: Uuid::new_v4().to_string(),
+                persona_id: persona_id.to_string(),
+                source,
+                task_id: task_id.map(str::to_string),
+                granularity_id: granularity_id.to_string(),
+                polarity,
+                content,
+                weight: generated.suggested_weight,
+                rationale: generated.rationale,
+                created_at: now,
+            });
+        }
+        suggestions
+    }
+
+    /// Returns the suggestion as a token to append to its persona.
+    #[must_use]
+    pub fn to_selection(&self) -> GeneratedTokenSelection {
+        GeneratedTokenSelection {
+            granularity_id: self.granularity_id.clone(),
+            polarity: self.polarity,
+            content: self.content.clone(),
+            weight: self.weight,
+        }
+    }
+}
+
+/// Payload of [`SUGGESTIONS_STAGED_EVENT`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SuggestionsStaged {
+    /// What produced the suggestions
+    pub source: SuggestionSource,
+    /// Scheduled task whose run staged the suggestions, if any
+    pub task_id: Option<String>,
+    /// Personas that received new suggestions
+    pub persona_ids: Vec<String>,
+    /// Number of new suggestions
+    pub count: usize,
+}
+
+impl SuggestionsStaged {
+    /// Summarizes newly staged suggestions, or `None` if there are none.
+    #[must_use]
+    pub fn from_staged(
+        source: SuggestionSource,
+        task_id: Option<&str>,
+        staged: &[PendingSuggestion],
+    ) -> Option<Self> {
+        if staged.is_empty() {
+            return None;
+        }
+        let mut persona_ids: Vec<String> = Vec::new();
+        for suggestion in staged {
+            if !persona_ids.contains(&suggestion.persona_id) {
+                persona_ids.push(suggestion.persona_id.clone());
+            }
+        }
+        Some(Self {
+            source,
+            task_id: task_id.map(str::to_string),
+            persona_ids,
+            count: staged.len(),
+        })
+    }
+}
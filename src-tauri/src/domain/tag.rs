@@ -0,0 +1,144 @@
+//! Hierarchical Tags
+//!
+//! Persona tags may be namespaced with `/`, like `project/clientA` or
+//! `style/anime`. Tags are still stored as plain strings; the hierarchy is
+//! derived from them.
+//!
+//! # Namespace Matching
+//!
+//! A namespace matches its own tag and every tag below it, ignoring case and
+//! accents (see [`super::collation`]): `project` matches `project`,
+//! `project/clientA`, and `project/clientA/hero`, but not `projects`.
+
+use serde::{Deserialize, Serialize};
+
+use super::collation;
+use super::persona::Persona;
+
+/// Separator between the segments of a namespaced tag.
+pub const TAG_SEPARATOR: char = '/';
+
+/// A node of the tag tree: one tag segment with the tags below it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagNode {
+    /// Last segment of the path (e.g., "clientA")
+    pub name: String,
+    /// Full tag path (e.g., "project/clientA")
+    pub path: String,
+    /// Number of personas tagged with this path or a tag below it
+    pub persona_count: usize,
+    /// Tags directly below this one, sorted by name (case- and accent-insensitive)
+    pub children: Vec<Self>,
+}
+
+/// Normalizes a tag: trims every segment and drops empty ones.
+///
+/// `" project / clientA/"` becomes `"project/clientA"`; a tag with no
+/// non-empty segment becomes the empty string.
+#[must_use]
+pub fn normalize_tag(tag: &str) -> String {
+    tag.split(TAG_SEPARATOR)
+        .map(str::trim)
+        .filter(|segment| !segment.is_empty())
+        .collect::<Vec<_>>()
+        .join(&TAG_SEPARATOR.to_string())
+}
+
+/// Normalizes a list of tags, dropping tags that end up empty.
+#[must_use]
+pub fn normalize_tags(tags: &[String]) -> Vec<String> {
+    tags.iter()
+        .map(|tag| normalize_tag(tag))
+        .filter(|tag| !tag.is_empty())
+        .collect()
+}
+
+/// Returns true if `tag` is `namespace` or a tag below it (case- and
+/// accent-insensitive).
+#[must_use]
+pub fn in_namespace(tag: &str, namespace: &str) -> bool {
+    let tag = collation::fold(&normalize_tag(tag));
+    let namespace = collation::fold(&normalize_tag(namespace));
+    if namespace.is_empty() {
+        return false;
+    }
+
+    tag.strip_prefix(&namespace)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with(TAG_SEPARATOR))
+}
+
+/// Builds the tag tree of a set of personas.
+///
+/// Paths differing only in case or accents are merged under the first
+/// spelling seen.
+/// A persona counts once per node even if several of its tags fall under it.
+///
+/// # Returns
+///
+/// Top-level nodes sorted by name (case- and accent-insensitive).
+#[must_use]
+pub fn build_tag_tree(personas: &[Persona]) -> Vec<TagNode> {
+    let mut roots: Vec<TagNode> = Vec::new();
+
+    for persona in personas {
+        // Every path prefix of every tag, once per persona
+        let mut paths: Vec<Vec<String>> = Vec::new();
+        for tag in normalize_tags(&persona.tags) {
+            let segments: Vec<String> = tag.split(TAG_SEPARATOR).map(str::to_string).collect();
+            for depth in 1..=segments.len() {
+                let path = &segments[..depth];
+                if !paths.iter().any(|p| paths_equal(p, path)) {
+                    paths.push(path.to_vec());
+                }
+            }
+        }
+
+        for path in paths {
+            let mut level = &mut roots;
+            let mut parent_path: Option<String> = None;
+            for (depth, segment) in path.iter().enumerate() {
+                let existing = level
+                    .iter()
+                    .position(|node| same_segment(&node.name, segment));
+                let index = existing.unwrap_or_else(|| {
+                    level.push(TagNode {
+                        name: segment.clone(),
+                        path: parent_path.as_ref().map_or_else(
+                            || segment.clone(),
+                            |parent| format!("{parent}{TAG_SEPARATOR}{segment}"),
+                        ),
+                        persona_count: 0,
+                        children: Vec::new(),
+                    });
+                    level.len() - 1
+                });
+                if depth == path.len() - 1 {
+                    level[index].persona_count += 1;
+                }
+                parent_path = Some(level[index].path.clone());
+                level = &mut level[index].children;
+            }
+        }
+    }
+
+    sort_nodes(&mut roots);
+    roots
+}
+
+/// Compares two tag segments, ignoring case and accents.
+fn same_segment(a: &str, b: &str) -> bool {
+    collation::eq(a, b)
+}
+
+/// Compares two tag paths segment by segment, ignoring case and accents.
+fn paths_equal(a: &[String], b: &[String]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).all(|(x, y)| same_segment(x, y))
+}
+
+/// Sorts nodes and their descendants by name, ignoring case and accents.
+fn sort_nodes(nodes: &mut [TagNode]) {
+    nodes.sort_by(|a, b| collation::compare(&a.name, &b.name));
+    for node in nodes {
+        sort_nodes(&mut node.children);
+    }
+}
@@ -0,0 +1,166 @@
+//! Usage Telemetry
+//!
+//! Anonymous feature-usage counters, collected only when the user opts in
+//! ([`AppSettings::telemetry`](super::settings::AppSettings::telemetry), off by
+//! default) to learn which features are actually used.
+//!
+//! # Privacy
+//!
+//! - Only a count per [`Feature`] and the first and last day it was used are
+//!   kept; never persona names, token content, prompts, or identifiers
+//! - Counters stay in the local database; the [`UsageReport`] shows exactly
+//!   what would be shared, and nothing is transmitted by the app
+//! - Opting out deletes the counters
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+/// A feature whose use is counted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Feature {
+    /// Creating a persona from scratch
+    CreatePersona,
+    /// Creating a persona from a built-in template
+    CreateFromTemplate,
+    /// Creating a persona with the step-by-step wizard
+    PersonaWizard,
+    /// Duplicating a persona
+    DuplicatePersona,
+    /// Comparing two personas
+    ComparePersonas,
+    /// Composing a prompt
+    ComposePrompt,
+    /// Generating a prompt matrix
+    PromptMatrix,
+    /// Linting a prompt
+    LintPrompt,
+    /// Generating token suggestions with AI
+    AiTokenSuggestions,
+    /// Generating a whole persona with AI
+    AiGeneratePersona,
+    /// Transferring a persona's style with AI
+    AiStyleTransfer,
+    /// Blending personas with AI
+    AiBlendPersonas,
+    /// Checking a generated image against its persona with AI
+    AiVerifyImage,
+    /// Running a scheduled AI style refresh
+    AiScheduledRefresh,
+    /// Applying a built-in token pack
+    ApplyTokenPack,
+    /// Find-and-replace across tokens
+    FindReplace,
+    /// Reverting a token to an earlier revision
+    RevertToken,
+    /// Exporting personas as JSON
+    ExportPersonas,
+    /// Importing personas from JSON
+    ImportPersonas,
+    /// Encoding or decoding a share-code
+    ShareCode,
+    /// Exporting a persona sheet as PDF
+    PersonaSheet,
+    /// Exporting personas as a static HTML site
+    LibrarySite,
+    /// Sharing a smart collection over the network
+    ShareSession,
+    /// Quick search
+    QuickSearch,
+    /// Querying personas through a smart collection
+    SmartCollection,
+}
+
+impl Feature {
+    /// Every counted feature.
+    pub const ALL: [Self; 25] = [
+        Self::CreatePersona,
+        Self::CreateFromTemplate,
+        Self::PersonaWizard,
+        Self::DuplicatePersona,
+        Self::ComparePersonas,
+        Self::ComposePrompt,
+        Self::PromptMatrix,
+        Self::LintPrompt,
+        Self::AiTokenSuggestions,
+        Self::AiGeneratePersona,
+        Self::AiStyleTransfer,
+        Self::AiBlendPersonas,
+        Self::AiVerifyImage,
+        Self::AiScheduledRefresh,
+        Self::ApplyTokenPack,
+        Self::FindReplace,
+        Self::RevertToken,
+        Self::ExportPersonas,
+        Self::ImportPersonas,
+        Self::ShareCode,
+        Self::PersonaSheet,
+        Self::LibrarySite,
+        Self::ShareSession,
+        Self::QuickSearch,
+        Self::SmartCollection,
+    ];
+
+    /// Returns the stable identifier stored in the database.
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::CreatePersona => "create_persona",
+            Self::CreateFromTemplate => "create_from_template",
+            Self::PersonaWizard => "persona_wizard",
+            Self::DuplicatePersona => "duplicate_persona",
+            Self::ComparePersonas => "compare_personas",
+            Self::ComposePrompt => "compose_prompt",
+            Self::PromptMatrix => "prompt_matrix",
+            Self::LintPrompt => "lint_prompt",
+            Self::AiTokenSuggestions => "ai_token_suggestions",
+            Self::AiGeneratePersona => "ai_generate_persona",
+            Self::AiStyleTransfer => "ai_style_transfer",
+            Self::AiBlendPersonas => "ai_blend_personas",
+            Self::AiVerifyImage => "ai_verify_image",
+            Self::AiScheduledRefresh => "ai_scheduled_refresh",
+            Self::ApplyTokenPack => "apply_token_pack",
+            Self::FindReplace => "find_replace",
+            Self::RevertToken => "revert_token",
+            Self::ExportPersonas => "export_personas",
+            Self::ImportPersonas => "import_personas",
+            Self::ShareCode => "share_code",
+            Self::PersonaSheet => "persona_sheet",
+            Self::LibrarySite => "library_site",
+            Self::ShareSession => "share_session",
+            Self::QuickSearch => "quick_search",
+            Self::SmartCollection => "smart_collection",
+        }
+    }
+
+    /// Parses a stored identifier; unknown ones (e.g., from a newer version)
+    /// yield `None`.
+    #[must_use]
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|feature| feature.as_str() == s)
+    }
+}
+
+/// How often one feature was used.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeatureUsage {
+    /// The feature
+    pub feature: Feature,
+    /// Number of uses
+    pub count: u64,
+    /// Day of the first counted use
+    pub first_used_on: NaiveDate,
+    /// Day of the last counted use
+    pub last_used_on: NaiveDate,
+}
+
+/// Everything telemetry has collected, exactly as it would be shared.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageReport {
+    /// Whether counting is enabled
+    pub enabled: bool,
+    /// Application version
+    pub app_version: String,
+    /// Counters, most used first
+    pub features: Vec<FeatureUsage>,
+}
@@ -0,0 +1,56 @@
+//! Persona Template Library
+//!
+//! Built-in archetypes (fantasy knight, cyberpunk hacker, ...) that give new
+//! personas a structured starting point without requiring an AI provider.
+//!
+//! Templates are embedded in the binary from `templates/persona_templates.json`
+//! and parsed once on first use. Each template carries a description, tags, and
+//! tokens across the granularity levels, in the order they are saved.
+
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+
+use super::token::GeneratedTokenSelection;
+
+/// Embedded template definitions.
+const TEMPLATES_JSON: &str = include_str!("templates/persona_templates.json");
+
+/// Templates parsed from [`TEMPLATES_JSON`] on first use.
+static TEMPLATES: OnceLock<Vec<PersonaTemplate>> = OnceLock::new();
+
+/// A built-in persona archetype.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PersonaTemplate {
+    /// Stable identifier (e.g., "`fantasy_knight`")
+    pub id: String,
+    /// Human-readable archetype name
+    pub name: String,
+    /// Description copied to personas created from the template
+    pub description: String,
+    /// Tags copied to personas created from the template
+    pub tags: Vec<String>,
+    /// Tokens created for the persona, in display order
+    pub tokens: Vec<GeneratedTokenSelection>,
+}
+
+impl PersonaTemplate {
+    /// Returns all built-in templates.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the embedded template JSON is malformed, which is a build defect.
+    #[must_use]
+    pub fn all() -> &'static [Self] {
+        TEMPLATES.get_or_init(|| {
+            serde_json::from_str(TEMPLATES_JSON)
+                .expect("embedded persona templates must be valid JSON")
+        })
+    }
+
+    /// Finds a built-in template by ID.
+    #[must_use]
+    pub fn find(id: &str) -> Option<&'static Self> {
+        Self::all().iter().find(|template| template.id == id)
+    }
+}
@@ -23,15 +23,21 @@
 //! 6. **Midsection**: Waist, hips, midriff (e.g., "narrow waist", "wide hips")
 //! 7. **Lower Body**: Legs, thighs, feet (e.g., "long legs", "slender ankles")
 
+use std::collections::HashSet;
+
 use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use super::i18n::{self, Locale};
+use crate::error::AppError;
+
 /// Token polarity determines whether a token describes desired or undesired characteristics.
 ///
 /// - **Positive**: Include this characteristic in the generated image
 /// - **Negative**: Exclude this characteristic from the generated image
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum TokenPolarity {
     /// Token describes a desired characteristic
@@ -186,7 +192,7 @@ pub struct GranularityLevel {
     pub color: String,
     /// Sort order for UI presentation
     pub display_order: i32,
-    /// Whether this is a built-in level (always true currently)
+    /// Whether this is a built-in level (false for custom levels)
     pub is_default: bool,
     /// Creation timestamp
     pub created_at: DateTime<Utc>,
@@ -264,9 +270,27 @@ pub struct BatchCreateTokenRequest {
     pub weight: f64,
 }
 
+/// A single AI suggestion the user accepted, to be saved as a token.
+///
+/// Used by `apply_generated_tokens` to save a whole selection in one call.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeneratedTokenSelection {
+    /// Granularity level ID
+    pub granularity_id: String,
+    /// Token polarity
+    pub polarity: TokenPolarity,
+    /// Descriptive content
+    pub content: String,
+    /// Weight modifier (defaults to 1.0)
+    #[serde(default = "default_weight")]
+    pub weight: f64,
+}
+
 /// Request payload for updating an existing token.
 ///
-/// All fields are optional; only provided fields are updated.
+/// All fields are optional; only provided fields are updated. As with
+/// persona updates, `expected_updated_at` guards against overwriting a
+/// concurrent edit.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UpdateTokenRequest {
     /// New content text
@@ -277,6 +301,9 @@ pub struct UpdateTokenRequest {
     pub granularity_id: Option<String>,
     /// New polarity
     pub polarity: Option<TokenPolarity>,
+    /// Last modification time the edit was based on; `None` skips the check
+    #[serde(default)]
+    pub expected_updated_at: Option<DateTime<Utc>>,
 }
 
 /// Request payload for reordering tokens within a persona.
@@ -292,6 +319,65 @@ pub struct ReorderTokensRequest {
     pub token_orders: Vec<TokenOrderUpdate>,
 }
 
+impl ReorderTokensRequest {
+    /// Builds a request that reorders the tokens of one granularity level.
+    ///
+    /// The level keeps the global positions its tokens already occupy, and its
+    /// tokens fill those positions in the given order, so tokens of other
+    /// levels do not move relative to each other. All tokens are renumbered
+    /// from zero.
+    ///
+    /// # Arguments
+    ///
+    /// * `persona_id` - Parent persona UUID
+    /// * `tokens` - All of the persona's tokens, sorted by display order
+    /// * `granularity_id` - Level whose tokens are reordered
+    /// * `ordered_token_ids` - Every token ID of that level, in the new order
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Validation` if `ordered_token_ids` does not list each
+    /// token of the level exactly once.
+    pub fn within_granularity(
+        persona_id: &str,
+        tokens: &[Token],
+        granularity_id: &str,
+        ordered_token_ids: &[String],
+    ) -> Result<Self, AppError> {
+        let slots: Vec<usize> = tokens
+            .iter()
+            .enumerate()
+            .filter(|(_, token)| token.granularity_id == granularity_id)
+            .map(|(position, _)| position)
+            .collect();
+
+        let expected: HashSet<&str> = slots.iter().map(|&slot| tokens[slot].id.as_str()).collect();
+        let given: HashSet<&str> = ordered_token_ids.iter().map(String::as_str).collect();
+        if given.len() != ordered_token_ids.len() || given != expected {
+            return Err(AppError::Validation(format!(
+                "The new order must list each token of granularity '{granularity_id}' exactly once"
+            )));
+        }
+
+        let mut order: Vec<&str> = tokens.iter().map(|token| token.id.as_str()).collect();
+        for (&slot, token_id) in slots.iter().zip(ordered_token_ids) {
+            order[slot] = token_id;
+        }
+
+        Ok(Self {
+            persona_id: persona_id.to_string(),
+            token_orders: order
+                .into_iter()
+                .enumerate()
+                .map(|(position, token_id)| TokenOrderUpdate {
+                    token_id: token_id.to_string(),
+                    display_order: position as i32,
+                })
+                .collect(),
+        })
+    }
+}
+
 /// Single token ordering update within a reorder request.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TokenOrderUpdate {
@@ -301,6 +387,24 @@ pub struct TokenOrderUpdate {
     pub display_order: i32,
 }
 
+/// Content and weight a token had before one of its edits.
+///
+/// A revision is recorded each time an update changes a token's content or
+/// weight; reverting to it restores both values.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenRevision {
+    /// Token UUID
+    pub token_id: String,
+    /// Revision number, increasing with each edit of the token
+    pub revision: i32,
+    /// Content before the edit
+    pub content: String,
+    /// Weight before the edit
+    pub weight: f64,
+    /// When the edit was made
+    pub recorded_at: DateTime<Utc>,
+}
+
 impl From<Granularity> for GranularityLevel {
     fn from(g: Granularity) -> Self {
         Self {
@@ -315,11 +419,56 @@ impl From<Granularity> for GranularityLevel {
 }
 
 impl GranularityLevel {
-    /// Returns all granularity levels in display order.
+    /// Color used for granularities with no stored level.
+    pub const FALLBACK_COLOR: &'static str = "neutral";
+
+    /// Returns all built-in granularity levels in display order.
+    ///
+    /// These seed the `granularity_levels` table; read levels from the
+    /// database to get their stored colors and order.
     #[must_use]
     pub fn all() -> Vec<Self> {
         Granularity::all().iter().map(|&g| g.into()).collect()
     }
+
+    /// Returns the level with the given ID, or a fallback level for unknown IDs.
+    ///
+    /// The fallback uses the ID as its name, [`Self::FALLBACK_COLOR`], and sorts
+    /// after every stored level. Its `created_at` is the Unix epoch, so repeated
+    /// calls return equal values.
+    #[must_use]
+    pub fn find_or_fallback(levels: &[Self], id: &str) -> Self {
+        levels
+            .iter()
+            .find(|l| l.id == id)
+            .cloned()
+            .unwrap_or_else(|| Self {
+                id: id.to_string(),
+                name: id.to_string(),
+                color: Self::FALLBACK_COLOR.to_string(),
+                display_order: i32::MAX,
+                is_default: false,
+                created_at: DateTime::UNIX_EPOCH,
+            })
+    }
+
+    /// Translates the name of a built-in level into a locale.
+    ///
+    /// Custom levels, and built-in levels stored under a name other than the
+    /// built-in one, keep their name.
+    #[must_use]
+    pub fn localized(mut self, locale: Locale) -> Self {
+        let builtin = Granularity::parse(&self.id)
+            .filter(|g| self.is_default && g.display_name() == self.name);
+        if let Some(granularity) = builtin {
+            self.name = i18n::message(
+                locale,
+                &format!("granularity.{}", granularity.as_str()),
+                &[],
+            );
+        }
+        self
+    }
 }
 
 impl Token {
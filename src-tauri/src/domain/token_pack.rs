@@ -0,0 +1,56 @@
+//! Starter Token Packs
+//!
+//! Curated negative prompt packs for common model families (SD 1.5, SDXL,
+//! anime-specialized checkpoints) that give personas sane negatives without
+//! requiring an AI provider.
+//!
+//! Packs are embedded in the binary from `templates/token_packs.json` and
+//! parsed once on first use. Applying a pack adds its tokens to an existing
+//! persona, skipping tokens the persona already has.
+
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+
+use super::token::GeneratedTokenSelection;
+
+/// Embedded pack definitions.
+const TOKEN_PACKS_JSON: &str = include_str!("templates/token_packs.json");
+
+/// Packs parsed from [`TOKEN_PACKS_JSON`] on first use.
+static TOKEN_PACKS: OnceLock<Vec<TokenPack>> = OnceLock::new();
+
+/// A built-in set of tokens for a model family.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenPack {
+    /// Stable identifier (e.g., "`sdxl_negatives`")
+    pub id: String,
+    /// Human-readable pack name
+    pub name: String,
+    /// What the pack covers and when to use it
+    pub description: String,
+    /// Model families the pack is tuned for (e.g., "sd15", "sdxl")
+    pub model_families: Vec<String>,
+    /// Tokens added to the persona, in display order
+    pub tokens: Vec<GeneratedTokenSelection>,
+}
+
+impl TokenPack {
+    /// Returns all built-in packs.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the embedded pack JSON is malformed, which is a build defect.
+    #[must_use]
+    pub fn all() -> &'static [Self] {
+        TOKEN_PACKS.get_or_init(|| {
+            serde_json::from_str(TOKEN_PACKS_JSON).expect("embedded token packs must be valid JSON")
+        })
+    }
+
+    /// Finds a built-in pack by ID.
+    #[must_use]
+    pub fn find(id: &str) -> Option<&'static Self> {
+        Self::all().iter().find(|pack| pack.id == id)
+    }
+}
@@ -0,0 +1,55 @@
+//! Application Updates
+//!
+//! Types describing an available application update and the record kept of
+//! the last one installed. The record follows the update from the database
+//! snapshot taken before installing to the check made when the new version
+//! first starts, so a failed migration can be diagnosed and the snapshot
+//! found (see `infrastructure::update`).
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// An update offered by the release server.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AvailableUpdate {
+    /// Version that would be installed (e.g., "0.3.0")
+    pub version: String,
+    /// Version currently running
+    pub current_version: String,
+    /// Release date, as published
+    pub date: Option<String>,
+    /// Release notes, as published
+    pub notes: Option<String>,
+}
+
+/// Where the last update stands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UpdateOutcome {
+    /// Installed (or installing), not yet started in the new version
+    Pending,
+    /// The new version migrated and verified the database
+    Succeeded,
+    /// The new version failed to migrate the database, which was restored
+    /// from the snapshot
+    RolledBack,
+}
+
+/// Record of the last application update.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateReport {
+    /// Version the update started from
+    pub from_version: String,
+    /// Version being installed
+    pub to_version: String,
+    /// Copy of the database taken right before installing
+    pub snapshot_path: String,
+    /// When the snapshot was taken
+    pub started_at: DateTime<Utc>,
+    /// Where the update stands
+    pub outcome: UpdateOutcome,
+    /// When the new version checked the database, if it has started yet
+    pub checked_at: Option<DateTime<Utc>>,
+    /// Why the database check failed; `None` unless rolled back
+    pub error: Option<String>,
+}
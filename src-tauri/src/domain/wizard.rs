@@ -0,0 +1,381 @@
+//! Persona Creation Wizard
+//!
+//! A guided alternative to creating a persona in one form: the user walks
+//! through [`WizardStep::ALL`] in order (style, physique, face, hair), adding
+//! tokens for each step's granularity levels. Drafts are stored after every
+//! step, so creation can be resumed after the app was closed; finishing the
+//! wizard creates the persona with all tokens and deletes the draft.
+//!
+//! # Steps
+//!
+//! A step can be submitted once every earlier step is; submitted steps can
+//! be submitted again to change them. Each step needs at least one positive
+//! token. With an AI provider, the AI suggests tokens for each of the step's
+//! granularity levels, added after the user's own (see
+//! [`PersonaWizard::fill_request`]).
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::ai::TokenGenerationRequest;
+use super::persona::ContentRating;
+use super::token::{GeneratedTokenSelection, Granularity, TokenPolarity};
+use crate::error::AppError;
+
+/// Largest number of tokens the AI is asked for per granularity level.
+pub const MAX_WIZARD_FILL_COUNT: usize = 10;
+
+/// A step of the persona creation wizard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WizardStep {
+    /// Art style and quality tags
+    Style,
+    /// Body type and overall physical traits
+    Physique,
+    /// Eyes, face shape, facial features
+    Face,
+    /// Hair color, length, style
+    Hair,
+}
+
+impl WizardStep {
+    /// All steps, in the order they are walked through.
+    pub const ALL: [Self; 4] = [Self::Style, Self::Physique, Self::Face, Self::Hair];
+
+    /// Returns the string representation for database storage.
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Style => "style",
+            Self::Physique => "physique",
+            Self::Face => "face",
+            Self::Hair => "hair",
+        }
+    }
+
+    /// Parses from database string representation.
+    #[must_use]
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "style" => Some(Self::Style),
+            "physique" => Some(Self::Physique),
+            "face" => Some(Self::Face),
+            "hair" => Some(Self::Hair),
+            _ => None,
+        }
+    }
+
+    /// Returns the granularity levels the step's tokens belong to.
+    #[must_use]
+    pub const fn granularities(&self) -> &'static [Granularity] {
+        match self {
+            Self::Style => &[Granularity::Style],
+            Self::Physique => &[
+                Granularity::General,
+                Granularity::UpperBody,
+                Granularity::Midsection,
+                Granularity::LowerBody,
+            ],
+            Self::Face => &[Granularity::Face],
+            Self::Hair => &[Granularity::Hair],
+        }
+    }
+
+    /// Returns whether a granularity level ID belongs to the step.
+    #[must_use]
+    pub fn contains(&self, granularity_id: &str) -> bool {
+        self.granularities()
+            .iter()
+            .any(|granularity| granularity.as_str() == granularity_id)
+    }
+}
+
+/// A persona being created with the wizard, stored as a draft.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PersonaWizard {
+    /// Unique identifier (UUID v4)
+    pub id: String,
+    /// Name of the persona to create
+    pub name: String,
+    /// Description of the persona to create
+    pub description: Option<String>,
+    /// Tags of the persona to create
+    pub tags: Vec<String>,
+    /// Audience rating of the persona to create
+    pub content_rating: ContentRating,
+    /// Image model for the persona's generation parameters (default model when unset)
+    pub image_model_id: Option<String>,
+    /// Tokens of all submitted steps, in step order
+    pub tokens: Vec<GeneratedTokenSelection>,
+    /// Steps submitted so far
+    pub completed_steps: Vec<WizardStep>,
+    /// When the wizard was started
+    pub created_at: DateTime<Utc>,
+    /// When a step was last submitted
+    pub updated_at: DateTime<Utc>,
+}
+
+impl PersonaWizard {
+    /// Starts a wizard; the name must already be validated.
+    #[must_use]
+    pub fn new(name: String, request: StartPersonaWizardRequest) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4().to_string(),
+            name,
+            description: request
+                .description
+                .map(|description| description.trim().to_string())
+                .filter(|description| !description.is_empty()),
+            tags: request.tags,
+            content_rating: request.content_rating,
+            image_model_id: request.image_model_id,
+            tokens: Vec::new(),
+            completed_steps: Vec::new(),
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// Returns the first step not yet submitted, or `None` when all are.
+    #[must_use]
+    pub fn current_step(&self) -> Option<WizardStep> {
+        WizardStep::ALL
+            .into_iter()
+            .find(|step| !self.completed_steps.contains(step))
+    }
+
+    /// Returns the tokens submitted for a step.
+    pub fn step_tokens(
+        &self,
+        step: WizardStep,
+    ) -> impl Iterator<Item = &GeneratedTokenSelection> + '_ {
+        self.tokens
+            .iter()
+            .filter(move |token| step.contains(&token.granularity_id))
+    }
+
+    /// Checks that a step can be submitted and its tokens are valid.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Validation` if an earlier step is not submitted yet,
+    /// or a token is empty or belongs to another step's granularity.
+    pub fn check_step(
+        &self,
+        step: WizardStep,
+        tokens: &[GeneratedTokenSelection],
+    ) -> Result<(), AppError> {
+        if let Some(missing) = WizardStep::ALL
+            .into_iter()
+            .take_while(|earlier| *earlier != step)
+            .find(|earlier| !self.completed_steps.contains(earlier))
+        {
+            return Err(AppError::Validation(format!(
+                "Complete the '{}' step first",
+                missing.as_str()
+            )));
+        }
+
+        for token in tokens {
+            if token.content.trim().is_empty() {
+                return Err(AppError::Validation(
+                    "Token content cannot be empty".to_string(),
+                ));
+            }
+            if !step.contains(&token.granularity_id) {
+                return Err(AppError::Validation(format!(
+                    "Granularity '{}' does not belong to the '{}' step",
+                    token.granularity_id,
+                    step.as_str()
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Replaces a step's tokens and marks it submitted.
+    ///
+    /// Tokens repeating an earlier one of the step (same granularity and
+    /// polarity, ignoring case) are dropped.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Validation` if [`Self::check_step`] fails or the step
+    /// has no positive token.
+    pub fn submit(
+        &mut self,
+        step: WizardStep,
+        tokens: Vec<GeneratedTokenSelection>,
+    ) -> Result<(), AppError> {
+        self.check_step(step, &tokens)?;
+
+        let mut seen: Vec<(String, TokenPolarity, String)> = Vec::new();
+        let mut step_tokens = Vec::new();
+        for mut token in tokens {
+            token.content = token.content.trim().to_string();
+            let key = (
+                token.granularity_id.clone(),
+                token.polarity,
+                token.content.to_lowercase(),
+            );
+            if !seen.contains(&key) {
+                seen.push(key);
+                step_tokens.push(token);
+            }
+        }
+        if !step_tokens
+            .iter()
+            .any(|token| token.polarity == TokenPolarity::Positive)
+        {
+            return Err(AppError::Validation(format!(
+                "The '{}' step needs at least one positive token",
+                step.as_str()
+            )));
+        }
+
+        // Keep tokens in step order, whichever step is resubmitted
+        let mut tokens = Vec::with_capacity(self.tokens.len() + step_tokens.len());
+        for other in WizardStep::ALL {
+            if other == step {
+                tokens.append(&mut step_tokens);
+            } else {
+                tokens.extend(self.step_tokens(other).cloned());
+            }
+        }
+        self.tokens = tokens;
+
+        if !self.completed_steps.contains(&step) {
+            self.completed_steps.push(step);
+        }
+        self.updated_at = Utc::now();
+        Ok(())
+    }
+
+    /// Checks that every step was submitted.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Validation` naming the first missing step.
+    pub fn ensure_complete(&self) -> Result<(), AppError> {
+        match self.current_step() {
+            Some(step) => Err(AppError::Validation(format!(
+                "Complete the '{}' step first",
+                step.as_str()
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// Builds the token request asking the AI to fill one granularity level.
+    ///
+    /// Tokens of other steps and the user's tokens for the step count as
+    /// existing tokens; outside the style step, the style tokens are sent as
+    /// style hints.
+    ///
+    /// # Arguments
+    ///
+    /// * `granularity` - The level to fill
+    /// * `step_tokens` - The tokens submitted with the step
+    /// * `count` - Number of positive tokens to ask for
+    #[must_use]
+    pub fn fill_request(
+        &self,
+        granularity: Granularity,
+        step_tokens: &[GeneratedTokenSelection],
+        count: usize,
+    ) -> TokenGenerationRequest {
+        let existing = |polarity: TokenPolarity| -> Vec<String> {
+            self.tokens
+                .iter()
+                .chain(step_tokens)
+                .filter(|token| token.polarity == polarity)
+                .map(|token| token.content.clone())
+                .collect()
+        };
+        let style = Granularity::Style.as_str();
+        let style_hints: Vec<&str> = self
+            .tokens
+            .iter()
+            .filter(|token| {
+                token.granularity_id == style && token.polarity == TokenPolarity::Positive
+            })
+            .map(|token| token.content.as_str())
+            .collect();
+
+        TokenGenerationRequest {
+            persona_name: self.name.clone(),
+            persona_description: self.description.clone(),
+            granularity_name: granularity.as_str().to_string(),
+            positive_count: count,
+            negative_count: 0,
+            existing_positive_tokens: existing(TokenPolarity::Positive),
+            existing_negative_tokens: existing(TokenPolarity::Negative),
+            style_hints: (granularity != Granularity::Style && !style_hints.is_empty())
+                .then(|| style_hints.join(", ")),
+            image_model_id: self.image_model_id.clone(),
+            ai_instructions: None,
+            current_positive_prompt: None,
+            current_negative_prompt: None,
+            positive_token_count: None,
+            negative_token_count: None,
+            max_usable_tokens: None,
+            output_language: None,
+            persona_id: None,
+            granularity_id: None,
+        }
+    }
+}
+
+/// Request payload for starting the persona creation wizard.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StartPersonaWizardRequest {
+    /// Name of the persona to create (required, must be free)
+    pub name: String,
+    /// Optional description
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Optional tags (defaults to empty vector)
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Audience rating (defaults to general)
+    #[serde(default)]
+    pub content_rating: ContentRating,
+    /// Image model for the generation parameters (defaults to the app default)
+    #[serde(default)]
+    pub image_model_id: Option<String>,
+}
+
+/// Request payload for submitting a wizard step.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WizardStepRequest {
+    /// The step to submit
+    pub step: WizardStep,
+    /// The user's tokens for the step's granularity levels, in order
+    #[serde(default)]
+    pub tokens: Vec<GeneratedTokenSelection>,
+    /// Number of tokens the AI adds per granularity level; requires an AI provider
+    #[serde(default)]
+    pub ai_fill_count: Option<usize>,
+}
+
+impl WizardStepRequest {
+    /// Validates the AI fill count, if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Validation` if the count is outside 1 to
+    /// [`MAX_WIZARD_FILL_COUNT`].
+    pub fn validate(&self) -> Result<(), AppError> {
+        match self.ai_fill_count {
+            Some(count) if !(1..=MAX_WIZARD_FILL_COUNT).contains(&count) => {
+                Err(AppError::Validation(format!(
+                    "AI fill count must be between 1 and {MAX_WIZARD_FILL_COUNT}"
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+}
@@ -10,16 +10,32 @@
 //! - **Validation**: Input validation failures
 //! - **Io**: File system errors
 //! - **Serialization**: JSON parsing errors
+//! - **Offline**: Network access attempted while offline mode is enabled
+//! - **Conflict**: Concurrent modification detected by an `updated_at` check
+//! - **`SchemaTooNew`**: Database written by a newer version of the app
+//! - **Encryption**: Database passphrase missing, wrong, or unsupported
 //! - **Internal**: Unexpected internal errors
 //!
 //! # Tauri Compatibility
 //!
 //! `AppError` implements `Serialize` to enable passing error information
-//! to the frontend. The error message is serialized as a string.
+//! to the frontend, as `{ "code": code, "error": message }`. The code is the
+//! stable name of the category (e.g., "`not_found`"); the message is rendered
+//! in the current display locale (see `domain::i18n`). `Conflict` also carries
+//! `"current": entity` so the frontend can merge with the current data.
+//!
+//! `Display` always renders English, for logs.
+
+use std::fmt::Display;
 
+use chrono::{DateTime, Utc};
+use serde::ser::SerializeStruct;
 use serde::Serialize;
 use thiserror::Error;
 
+use crate::domain::i18n::{self, Locale};
+use crate::infrastructure::locale;
+
 /// Unified application error type.
 ///
 /// This enum captures all error conditions that can occur in the application,
@@ -46,6 +62,35 @@ pub enum AppError {
     #[error("Serialization error: {0}")]
     Serialization(#[from] serde_json::Error),
 
+    /// Network access was needed but offline mode is enabled
+    #[error("Offline mode is enabled: {0} requires a network connection")]
+    Offline(String),
+
+    /// The entity changed since the client last read it
+    #[error("Conflict: {message}")]
+    Conflict {
+        /// What changed, for display
+        message: String,
+        /// The entity as currently stored
+        current: serde_json::Value,
+    },
+
+    /// The database schema is newer than this build understands
+    #[error(
+        "Database schema v{found} is newer than this version of the app supports \
+         (v{supported}); update the app to open it"
+    )]
+    SchemaTooNew {
+        /// Schema version stored in the database
+        found: i32,
+        /// Latest schema version this build supports
+        supported: i32,
+    },
+
+    /// The database cannot be opened or re-keyed with the given passphrase
+    #[error("Database encryption error: {0}")]
+    Encryption(String),
+
     /// Unexpected internal error (mutex poisoning, etc.)
     #[error("Internal error: {0}")]
     Internal(String),
@@ -53,14 +98,98 @@ pub enum AppError {
 
 /// Implements `Serialize` for Tauri IPC compatibility.
 ///
-/// Errors are serialized as their display string, which provides
-/// user-friendly error messages to the frontend.
+/// Errors are serialized with their code and their message in the current
+/// display locale.
 impl Serialize for AppError {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
     {
-        serializer.serialize_str(&self.to_string())
+        let message = self.localized(locale::current());
+
+        if let Self::Conflict { current, .. } = self {
+            let mut conflict = serializer.serialize_struct("Conflict", 3)?;
+            conflict.serialize_field("code", self.code())?;
+            conflict.serialize_field("error", &message)?;
+            conflict.serialize_field("current", current)?;
+            return conflict.end();
+        }
+
+        let mut error = serializer.serialize_struct("AppError", 2)?;
+        error.serialize_field("code", self.code())?;
+        error.serialize_field("error", &message)?;
+        error.end()
+    }
+}
+
+impl AppError {
+    /// Returns the stable code of the error's category (e.g., "`not_found`").
+    #[must_use]
+    pub const fn code(&self) -> &'static str {
+        match self {
+            Self::Database(_) => "database",
+            Self::NotFound(_) => "not_found",
+            Self::Validation(_) => "validation",
+            Self::Io(_) => "io",
+            Self::Serialization(_) => "serialization",
+            Self::Offline(_) => "offline",
+            Self::Conflict { .. } => "conflict",
+            Self::SchemaTooNew { .. } => "schema_too_new",
+            Self::Encryption(_) => "encryption",
+            Self::Internal(_) => "internal",
+        }
+    }
+
+    /// Renders the error message in a locale.
+    ///
+    /// The category wording is translated; the detail is inserted as-is.
+    #[must_use]
+    pub fn localized(&self, locale: Locale) -> String {
+        let code = format!("error.{}", self.code());
+        let detail: &dyn Display = match self {
+            Self::Database(e) => e,
+            Self::Io(e) => e,
+            Self::Serialization(e) => e,
+            Self::NotFound(detail)
+            | Self::Validation(detail)
+            | Self::Offline(detail)
+            | Self::Encryption(detail)
+            | Self::Internal(detail)
+            | Self::Conflict {
+                message: detail, ..
+            } => detail,
+            Self::SchemaTooNew { found, supported } => {
+                return i18n::message(locale, &code, &[("found", found), ("supported", supported)]);
+            }
+        };
+        i18n::message(locale, &code, &[("detail", detail)])
+    }
+
+    /// Fails with `Conflict` if `current_updated_at` differs from the expected one.
+    ///
+    /// # Arguments
+    ///
+    /// * `expected` - The `updated_at` the client's edit was based on, if sent
+    /// * `current_updated_at` - The stored `updated_at`
+    /// * `what` - Entity description for the message (e.g., "Persona 'Alice'")
+    /// * `current` - The stored entity, returned to the client for merging
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Conflict` if the timestamps differ.
+    pub fn check_version<T: Serialize>(
+        expected: Option<DateTime<Utc>>,
+        current_updated_at: DateTime<Utc>,
+        what: &str,
+        current: &T,
+    ) -> Result<(), Self> {
+        match expected {
+            Some(expected) if expected != current_updated_at => Err(Self::Conflict {
+                message: format!("{what} was modified elsewhere; review the changes and retry"),
+                current: serde_json::to_value(current)?,
+            }),
+            _ => Ok(()),
+        }
     }
 }
 
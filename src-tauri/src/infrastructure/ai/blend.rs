@@ -0,0 +1,250 @@
+//! AI persona blending
+//!
+//! Asks the model to synthesize a child persona from several weighted parents.
+//! Unlike the deterministic interleave in [`crate::domain::blend`], the model
+//! may merge or rephrase parent traits into new tokens, following the user's
+//! guidance on which traits to inherit.
+
+use genai::chat::{ChatMessage, ChatOptions, ChatRequest, JsonSpec};
+use serde_json::json;
+
+use super::request_log::AiRequestLog;
+use super::{
+    build_client, build_genai_model_identifier, build_output_language_section,
+    ensure_provider_online, exec_chat_logged, extract_json_object, record_exchange,
+    resolve_api_key,
+};
+use crate::domain::ai::{AiLogEntry, AiProviderConfig};
+use crate::domain::blend::{
+    blend_tags, BlendMode, BlendParent, PersonaBlendDraft, PersonaBlendRequest,
+};
+use crate::domain::token::{GeneratedTokenSelection, Granularity, TokenPolarity};
+use crate::error::AppError;
+use crate::infrastructure::tokenizer::{get_config_for_model, get_prompt_context_for_model};
+
+/// A single token as returned by the model.
+#[derive(Debug, Clone, serde::Deserialize)]
+struct BlendTokenRaw {
+    content: String,
+    suggested_weight: f64,
+    granularity_id: String,
+    polarity: TokenPolarity,
+}
+
+/// Internal structure for parsing the AI response
+#[derive(Debug, Clone, serde::Deserialize)]
+struct BlendRaw {
+    #[serde(default)]
+    name: Option<String>,
+    description: String,
+    tokens: Vec<BlendTokenRaw>,
+}
+
+/// Build the system prompt for persona blending
+fn build_blend_system_prompt(
+    model_name: &str,
+    usable_tokens: usize,
+    output_language: &str,
+) -> String {
+    let output_language_section = build_output_language_section(output_language, "the description");
+    format!(
+        r"You are an expert prompt engineer for {model_name} image generation, specializing in character design.
+
+Your task is to create a new CHILD persona that inherits traits from several PARENT personas, like a family member or a variant of the same character.
+
+Token budget: {usable_tokens} tokens per prompt.
+
+BLENDING RULES:
+1. Each parent's influence must match its weight: a 70% parent shapes most traits
+2. Inherit concrete visual traits (hair, eyes, build, style) rather than averaging them into vagueness
+3. When parents conflict on a trait, pick one parent's version or a plausible mix, never both
+4. The child must be visually coherent as a single character
+5. Follow the user's inheritance guidance when given; it overrides the weights for the traits it names
+
+TOKEN RULES:
+- Every token belongs to exactly one granularity: style, general, hair, face, upper_body, midsection, lower_body
+- Positive tokens describe traits to include; negative tokens describe elements to exclude
+- Keep weights between 0.6 and 1.5 (1.0 = normal emphasis)
+- Order tokens by importance within each granularity{output_language_section}"
+    )
+}
+
+/// Build the user prompt describing the parents
+fn build_blend_user_prompt(request: &PersonaBlendRequest, parents: &[BlendParent<'_>]) -> String {
+    let mut sections = Vec::new();
+
+    for (i, parent) in parents.iter().enumerate() {
+        let mut section = format!(
+            "PARENT {number}: {name} (weight {weight:.0}%)",
+            number = i + 1,
+            name = parent.persona.name,
+            weight = parent.weight * 100.0,
+        );
+        if let Some(desc) = parent.persona.description.as_deref() {
+            if !desc.is_empty() {
+                section.push_str(&format!("\nDescription:\n```\n{desc}\n```"));
+            }
+        }
+        for &granularity in Granularity::all() {
+            let tokens: Vec<String> = parent
+                .tokens
+                .iter()
+                .filter(|t| t.granularity_id == granularity.as_str())
+                .map(|t| match t.polarity {
+                    TokenPolarity::Positive => t.content.clone(),
+                    TokenPolarity::Negative => format!("NOT {}", t.content),
+                })
+                .collect();
+            if !tokens.is_empty() {
+                section.push_str(&format!(
+                    "\n{}: {}",
+                    granularity.display_name(),
+                    tokens.join(", ")
+                ));
+            }
+        }
+        sections.push(section);
+    }
+
+    if let Some(traits) = request.inherit_traits.as_deref() {
+        if !traits.trim().is_empty() {
+            sections.push(format!(
+                "INHERITANCE GUIDANCE:\n```\n{}\n```",
+                traits.trim()
+            ));
+        }
+    }
+
+    sections.push(
+        r#"EXPECTED OUTPUT:
+Respond with a JSON object containing:
+- "name" (string): A short name for the child persona
+- "description" (string): 2-3 sentences describing the child and what it inherits from each parent
+- "tokens" (array): Token objects with "content", "suggested_weight", "granularity_id", and "polarity" ("positive" or "negative")"#
+            .to_string(),
+    );
+
+    sections.join("\n\n")
+}
+
+/// Build the JSON schema for the blend response
+fn build_blend_json_schema() -> serde_json::Value {
+    let granularity_ids: Vec<&str> = Granularity::all().iter().map(Granularity::as_str).collect();
+
+    json!({
+        "type": "object",
+        "properties": {
+            "name": { "type": "string" },
+            "description": { "type": "string" },
+            "tokens": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "content": { "type": "string" },
+                        "suggested_weight": { "type": "number" },
+                        "granularity_id": { "type": "string", "enum": granularity_ids },
+                        "polarity": { "type": "string", "enum": ["positive", "negative"] }
+                    },
+                    "required": ["content", "suggested_weight", "granularity_id", "polarity"]
+                }
+            }
+        },
+        "required": ["name", "description", "tokens"]
+    })
+}
+
+/// Parse the AI response
+fn parse_blend_response(content: &str) -> Result<BlendRaw, AppError> {
+    let json_str = extract_json_object(content);
+
+    serde_json::from_str(json_str).map_err(|e| {
+        AppError::Internal(format!(
+            "Failed to parse AI response: {e}. Response was: {content}"
+        ))
+    })
+}
+
+/// Synthesize a child persona draft from weighted parents.
+///
+/// `image_model_id` is the heaviest parent's image model, used for the prompt
+/// budget. The description is written in `output_language`. Tokens with an
+/// unknown granularity or empty content are dropped.
+///
+/// # Errors
+///
+/// Returns `AppError::Internal` if the AI request or response parsing fails.
+#[tracing::instrument(skip_all, fields(provider = ?config.provider, model = %config.model), err)]
+pub async fn synthesize_blend(
+    config: &AiProviderConfig,
+    request: &PersonaBlendRequest,
+    parents: &[BlendParent<'_>],
+    image_model_id: &str,
+    output_language: &str,
+    log: &AiRequestLog,
+) -> Result<PersonaBlendDraft, AppError> {
+    ensure_provider_online(config)?;
+    let api_key = resolve_api_key(config)?;
+    let client = build_client(api_key.clone());
+    let prompt_context = get_prompt_context_for_model(Some(image_model_id));
+    let tokenizer_config = get_config_for_model(image_model_id);
+
+    let system_prompt = build_blend_system_prompt(
+        &prompt_context.display_name,
+        tokenizer_config.usable_tokens,
+        output_language,
+    );
+    let user_prompt = build_blend_user_prompt(request, parents);
+    let mut log_entry = AiLogEntry::new(config, "persona_blend", &system_prompt, &user_prompt);
+
+    let chat_request = ChatRequest::default()
+        .with_system(system_prompt)
+        .append_message(ChatMessage::user(user_prompt));
+    let chat_options = ChatOptions::default()
+        .with_response_format(JsonSpec::new("persona_blend", build_blend_json_schema()));
+
+    let parsed = exec_chat_logged(
+        &client,
+        &build_genai_model_identifier(config),
+        chat_request,
+        &chat_options,
+        "AI persona blending failed",
+        &mut log_entry,
+    )
+    .await
+    .and_then(|content| parse_blend_response(&content));
+    record_exchange(
+        log,
+        &mut log_entry,
+        parsed.as_ref().err(),
+        api_key.as_deref(),
+    );
+    let parsed = parsed?;
+
+    let tokens = parsed
+        .tokens
+        .into_iter()
+        .filter(|t| !t.content.trim().is_empty() && Granularity::parse(&t.granularity_id).is_some())
+        .map(|t| GeneratedTokenSelection {
+            granularity_id: t.granularity_id,
+            polarity: t.polarity,
+            content: t.content.trim().to_string(),
+            weight: t.suggested_weight,
+        })
+        .collect();
+
+    let name = match (request.name.as_deref().map(str::trim), parsed.name) {
+        (Some(requested), _) if !requested.is_empty() => requested.to_string(),
+        (_, Some(generated)) if !generated.trim().is_empty() => generated.trim().to_string(),
+        _ => request.draft_name(parents),
+    };
+
+    Ok(PersonaBlendDraft {
+        name,
+        description: Some(parsed.description).filter(|d| !d.trim().is_empty()),
+        tags: blend_tags(parents),
+        tokens,
+        parent_ids: parents.iter().map(|p| p.persona.id.clone()).collect(),
+        mode: BlendMode::Ai,
+    })
+}
@@ -0,0 +1,322 @@
+//! Per-granularity token budget planning and verification
+//!
+//! Persona generation runs in two passes so the prompt fits the image model's
+//! token limit:
+//!
+//! 1. **Planning**: the model splits the usable budget across granularities
+//!    based on what matters for this character ([`build_planning_system_prompt`]).
+//! 2. **Generation**: the persona is generated with those per-granularity budgets
+//!    as explicit constraints.
+//!
+//! The generated tokens are then measured with the target model's tokenizer and
+//! trimmed ([`fit_tokens_to_budget`]) so the result is guaranteed to fit.
+//!
+//! Suggestions from any generation are finally annotated with their real
+//! encoder cost ([`annotate_token_costs`]) so the UI can show their impact.
+
+use serde_json::json;
+
+use super::extract_json_object;
+use crate::domain::ai::{GeneratedToken, GranularityBudget};
+use crate::domain::token::Granularity;
+use crate::error::AppError;
+use crate::infrastructure::tokenizer::count_tokens;
+
+/// Separator used between tokens when measuring, matching prompt composition.
+const TOKEN_SEPARATOR: &str = ", ";
+
+/// Relative share of the budget per granularity when no plan is available.
+const DEFAULT_SHARES: [(Granularity, usize); 7] = [
+    (Granularity::Style, 2),
+    (Granularity::General, 2),
+    (Granularity::Hair, 2),
+    (Granularity::Face, 2),
+    (Granularity::UpperBody, 1),
+    (Granularity::Midsection, 1),
+    (Granularity::LowerBody, 1),
+];
+
+/// Token budget allocated to a single granularity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BudgetAllocation {
+    /// Granularity the budget applies to
+    pub granularity: Granularity,
+    /// Encoder tokens available to this granularity
+    pub tokens: usize,
+}
+
+/// Internal structure for parsing the planning response
+#[derive(Debug, Clone, serde::Deserialize)]
+struct BudgetPlanRaw {
+    allocations: Vec<AllocationRaw>,
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+struct AllocationRaw {
+    granularity_id: String,
+    token_budget: usize,
+}
+
+/// Build the system prompt for the budget planning pass
+#[must_use]
+pub fn build_planning_system_prompt(model_name: &str, usable_tokens: usize) -> String {
+    format!(
+        r#"You are an expert prompt engineer for {model_name} image generation.
+
+Before a persona's tokens are written, you plan how the prompt's {usable_tokens}-token budget is spent.
+
+Split the budget across these granularity categories:
+- style: Quality and style modifiers
+- general: Overall physical traits
+- hair: Hair characteristics
+- face: Facial features
+- upper_body: Upper body details
+- midsection: Midsection details
+- lower_body: Lower body details
+
+PLANNING RULES:
+1. The allocations must add up to at most {usable_tokens}
+2. Give more budget to the features that define this character
+3. Give 0 to categories the character description gives no reason to describe
+4. Weighted tokens cost extra (parentheses, colon, and decimal), so leave headroom in emphasized categories
+
+Respond with a JSON object containing an "allocations" array of {{"granularity_id", "token_budget"}} objects."#
+    )
+}
+
+/// Build the JSON schema for the budget planning response
+pub fn build_planning_json_schema() -> serde_json::Value {
+    let granularity_ids: Vec<&str> = Granularity::all().iter().map(Granularity::as_str).collect();
+
+    json!({
+        "type": "object",
+        "properties": {
+            "allocations": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "granularity_id": { "type": "string", "enum": granularity_ids },
+                        "token_budget": { "type": "integer", "minimum": 0 }
+                    },
+                    "required": ["granularity_id", "token_budget"]
+                }
+            }
+        },
+        "required": ["allocations"]
+    })
+}
+
+/// Parse the planning response into normalized allocations
+pub fn parse_budget_plan(
+    content: &str,
+    usable_tokens: usize,
+) -> Result<Vec<BudgetAllocation>, AppError> {
+    let json_str = extract_json_object(content);
+
+    let parsed: BudgetPlanRaw = serde_json::from_str(json_str).map_err(|e| {
+        AppError::Internal(format!(
+            "Failed to parse AI budget plan: {e}. Response was: {content}"
+        ))
+    })?;
+
+    let shares: Vec<(Granularity, usize)> = Granularity::all()
+        .iter()
+        .map(|granularity| {
+            let share = parsed
+                .allocations
+                .iter()
+                .filter(|a| a.granularity_id == granularity.as_str())
+                .map(|a| a.token_budget)
+                .sum();
+            (*granularity, share)
+        })
+        .collect();
+
+    if shares.iter().all(|(_, share)| *share == 0) {
+        return Ok(default_allocations(usable_tokens));
+    }
+
+    Ok(normalize_allocations(&shares, usable_tokens))
+}
+
+/// Fallback allocation used when the planning pass yields no usable plan
+#[must_use]
+pub fn default_allocations(usable_tokens: usize) -> Vec<BudgetAllocation> {
+    let total: usize = DEFAULT_SHARES.iter().map(|(_, share)| share).sum();
+
+    DEFAULT_SHARES
+        .iter()
+        .map(|&(granularity, share)| BudgetAllocation {
+            granularity,
+            tokens: share * usable_tokens / total,
+        })
+        .collect()
+}
+
+/// Scale relative shares down so they add up to at most the usable budget.
+///
+/// Shares that already fit are kept as-is.
+fn normalize_allocations(
+    shares: &[(Granularity, usize)],
+    usable_tokens: usize,
+) -> Vec<BudgetAllocation> {
+    let total: usize = shares.iter().map(|(_, share)| share).sum();
+
+    shares
+        .iter()
+        .map(|&(granularity, share)| BudgetAllocation {
+            granularity,
+            tokens: if total <= usable_tokens {
+                share
+            } else {
+                share * usable_tokens / total
+            },
+        })
+        .collect()
+}
+
+/// Build the user prompt section listing the per-granularity budgets
+#[must_use]
+pub fn build_budget_section(allocations: &[BudgetAllocation]) -> String {
+    let lines: Vec<String> = allocations
+        .iter()
+        .map(|a| {
+            if a.tokens == 0 {
+                format!("- {}: 0 (generate no tokens)", a.granularity.as_str())
+            } else {
+                format!("- {}: {} tokens", a.granularity.as_str(), a.tokens)
+            }
+        })
+        .collect();
+
+    format!(
+        "TOKEN BUDGET PER GRANULARITY:\nStay within these encoder-token budgets; tokens beyond a category's budget will be removed.\n{}",
+        lines.join("\n")
+    )
+}
+
+/// Format a generated token the way it appears in a composed prompt
+fn format_for_prompt(token: &GeneratedToken) -> String {
+    if (token.suggested_weight - 1.0).abs() > f64::EPSILON {
+        format!("({}:{:.1})", token.content, token.suggested_weight)
+    } else {
+        token.content.clone()
+    }
+}
+
+/// Count encoder tokens for a list of generated tokens joined as a prompt
+fn count_joined(tokens: &[&GeneratedToken], model_id: &str) -> usize {
+    let joined = tokens
+        .iter()
+        .map(|token| format_for_prompt(token))
+        .collect::<Vec<_>>()
+        .join(TOKEN_SEPARATOR);
+    count_tokens(&joined, Some(model_id)).count
+}
+
+/// Trim generated tokens so each granularity and the whole prompt fit their budgets.
+///
+/// Tokens are removed from the end of each granularity (the model places its
+/// most important tokens first), then from the end of the whole list if the
+/// combined prompt still exceeds `usable_tokens`. Returns the kept tokens in
+/// their original order and the verified per-granularity counts.
+#[must_use]
+pub fn fit_tokens_to_budget(
+    tokens: Vec<GeneratedToken>,
+    allocations: &[BudgetAllocation],
+    usable_tokens: usize,
+    model_id: &str,
+) -> (Vec<GeneratedToken>, Vec<GranularityBudget>) {
+    let mut keep = vec![true; tokens.len()];
+
+    // Pass 1: per-granularity budgets
+    for allocation in allocations {
+        let indices: Vec<usize> = tokens
+            .iter()
+            .enumerate()
+            .filter(|(_, t)| t.granularity_id.as_deref() == Some(allocation.granularity.as_str()))
+            .map(|(i, _)| i)
+            .collect();
+
+        let mut kept = indices.len();
+        while kept > 0 {
+            let group: Vec<&GeneratedToken> = indices[..kept].iter().map(|&i| &tokens[i]).collect();
+            if count_joined(&group, model_id) <= allocation.tokens {
+                break;
+            }
+            kept -= 1;
+            keep[indices[kept]] = false;
+        }
+    }
+
+    // Pass 2: whole-prompt budget
+    loop {
+        let kept: Vec<&GeneratedToken> = tokens
+            .iter()
+            .zip(&keep)
+            .filter_map(|(t, &k)| k.then_some(t))
+            .collect();
+        if kept.is_empty() || count_joined(&kept, model_id) <= usable_tokens {
+            break;
+        }
+        if let Some(last) = keep.iter().rposition(|&k| k) {
+            keep[last] = false;
+        }
+    }
+
+    let kept: Vec<GeneratedToken> = tokens
+        .into_iter()
+        .zip(keep)
+        .filter_map(|(t, k)| k.then_some(t))
+        .collect();
+
+    let budgets = allocations
+        .iter()
+        .map(|allocation| {
+            let group: Vec<&GeneratedToken> = kept
+                .iter()
+                .filter(|t| t.granularity_id.as_deref() == Some(allocation.granularity.as_str()))
+                .collect();
+            GranularityBudget {
+                granularity_id: allocation.granularity.as_str().to_string(),
+                allocated_tokens: allocation.tokens,
+                used_tokens: if group.is_empty() {
+                    0
+                } else {
+                    count_joined(&group, model_id)
+                },
+            }
+        })
+        .collect();
+
+    (kept, budgets)
+}
+
+/// Annotate suggestions with their encoder cost and running prompt total.
+///
+/// Each token's `cumulative_tokens` is the size of `base_prompt` plus this and
+/// every preceding suggestion, joined as a composed prompt would be. Tokens
+/// whose running total passes `usable_tokens` are flagged with `exceeds_budget`.
+pub fn annotate_token_costs(
+    tokens: &mut [GeneratedToken],
+    base_prompt: Option<&str>,
+    usable_tokens: usize,
+    model_id: &str,
+) {
+    let mut prompt = base_prompt.map(str::trim).unwrap_or_default().to_string();
+
+    for token in tokens {
+        let formatted = format_for_prompt(token);
+        token.token_cost = Some(count_tokens(&formatted, Some(model_id)).count);
+
+        if !prompt.is_empty() {
+            prompt.push_str(TOKEN_SEPARATOR);
+        }
+        prompt.push_str(&formatted);
+
+        let cumulative = count_tokens(&prompt, Some(model_id)).count;
+        token.cumulative_tokens = Some(cumulative);
+        token.exceeds_budget = cumulative > usable_tokens;
+    }
+}
@@ -0,0 +1,297 @@
+//! Image consistency check
+//!
+//! Shows a generated image to a vision model together with the persona's
+//! tokens, referenced by position, and asks which of them the image shows.
+//! The verdicts are mapped back to the tokens and scored per granularity
+//! level, so drift from the character (wrong hair color, missing outfit) can
+//! be spotted without comparing images by eye.
+
+use std::fs;
+use std::path::Path;
+
+use base64::Engine;
+use genai::chat::{ChatMessage, ChatOptions, ChatRequest, ContentPart, JsonSpec, MessageContent};
+use serde_json::json;
+
+use super::request_log::AiRequestLog;
+use super::style_transfer::PersonaTokens;
+use super::{
+    build_client, build_genai_model_identifier, build_output_language_section,
+    ensure_provider_online, exec_chat_logged, extract_json_object, record_exchange,
+    resolve_api_key,
+};
+use crate::domain::ai::{AiLogEntry, AiProviderConfig, ImageConsistencyReport, TokenPresence};
+use crate::domain::token::Token;
+use crate::error::AppError;
+
+/// Largest image file sent to the model; providers reject bigger uploads.
+const MAX_IMAGE_BYTES: u64 = 20 * 1024 * 1024;
+
+/// A verdict on a single token as returned by the model.
+#[derive(Debug, Clone, serde::Deserialize)]
+struct TokenVerdictRaw {
+    index: usize,
+    present: bool,
+    #[serde(default)]
+    note: Option<String>,
+}
+
+/// Internal structure for parsing the AI response
+#[derive(Debug, Clone, serde::Deserialize)]
+struct ConsistencyRaw {
+    tokens: Vec<TokenVerdictRaw>,
+    #[serde(default)]
+    summary: Option<String>,
+}
+
+/// Returns the MIME type of an image file the vision models accept.
+fn image_mime_type(path: &Path) -> Option<&'static str> {
+    let extension = path.extension()?.to_string_lossy().to_lowercase();
+    match extension.as_str() {
+        "png" => Some("image/png"),
+        "jpg" | "jpeg" => Some("image/jpeg"),
+        "webp" => Some("image/webp"),
+        "gif" => Some("image/gif"),
+        _ => None,
+    }
+}
+
+/// Read an image file and encode it for the request.
+///
+/// Returns the MIME type and the base64-encoded content.
+fn load_image(image_path: &str) -> Result<(&'static str, String), AppError> {
+    let path = Path::new(image_path);
+    if !path.is_absolute() {
+        return Err(AppError::Validation(format!(
+            "Invalid image path '{image_path}': expected an absolute file path"
+        )));
+    }
+    let mime_type = image_mime_type(path).ok_or_else(|| {
+        AppError::Validation(format!(
+            "Unsupported image '{image_path}': expected PNG, JPEG, WebP, or GIF"
+        ))
+    })?;
+    let size = fs::metadata(path)
+        .map_err(|_| AppError::Validation(format!("Image file '{image_path}' not found")))?
+        .len();
+    if size > MAX_IMAGE_BYTES {
+        return Err(AppError::Validation(format!(
+            "Image '{image_path}' is larger than {} MB",
+            MAX_IMAGE_BYTES / 1024 / 1024
+        )));
+    }
+
+    let data = base64::engine::general_purpose::STANDARD.encode(fs::read(path)?);
+    Ok((mime_type, data))
+}
+
+/// Build the system prompt for the consistency check
+fn build_consistency_system_prompt(output_language: &str) -> String {
+    let output_language_section =
+        build_output_language_section(output_language, "every note and the summary");
+    format!(
+        r"You are an expert at reviewing AI-generated character images against their prompt.
+
+Your task is to decide, for each listed token, whether the attached image shows it.
+
+REVIEW RULES:
+1. Judge only what is visible in the image; do not guess hidden details
+2. A token is present when the image clearly shows it, even if slightly different in shade or style
+3. A token is absent when the image contradicts it or does not show it at all
+4. For negative tokens, report whether the unwanted element appears anyway
+5. Use the note to say what the image shows instead when a token is absent{output_language_section}"
+    )
+}
+
+/// Format tokens as a numbered list the model can reference by index.
+fn format_indexed_tokens(tokens: &[Token]) -> String {
+    tokens
+        .iter()
+        .enumerate()
+        .map(|(i, token)| {
+            format!(
+                "[{index}] ({granularity}, {polarity}) {content}",
+                index = i + 1,
+                granularity = token.granularity_id,
+                polarity = token.polarity.as_str(),
+                content = token.content,
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Build the text part of the user message for the consistency check
+fn build_consistency_user_prompt(persona: PersonaTokens<'_>) -> String {
+    let mut sections = Vec::new();
+
+    let mut persona_section = format!("CHARACTER: {}", persona.persona.name);
+    if let Some(desc) = persona.persona.description.as_deref() {
+        if !desc.is_empty() {
+            persona_section.push_str(&format!("\nCharacter Description:\n```\n{desc}\n```"));
+        }
+    }
+    persona_section.push_str(&format!(
+        "\n\nTokens to check:\n{}",
+        format_indexed_tokens(persona.tokens)
+    ));
+    sections.push(persona_section);
+
+    sections.push(
+        r#"EXPECTED OUTPUT:
+Respond with a JSON object containing:
+- "tokens" (array, required): One entry per token, each with:
+  - "index" (integer, required): The [number] of the token
+  - "present" (boolean, required): Whether the image shows the token
+  - "note" (string, optional): What the image shows instead, or other remarks
+- "summary" (string, optional): One or two sentences on how well the image matches the character"#
+            .to_string(),
+    );
+
+    sections.join("\n\n")
+}
+
+/// Build the JSON schema for the consistency response
+fn build_consistency_json_schema(token_count: usize) -> serde_json::Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "tokens": {
+                "type": "array",
+                "maxItems": token_count,
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "index": { "type": "integer", "minimum": 1, "maximum": token_count },
+                        "present": { "type": "boolean" },
+                        "note": { "type": "string" }
+                    },
+                    "required": ["index", "present"]
+                }
+            },
+            "summary": { "type": "string" }
+        },
+        "required": ["tokens"]
+    })
+}
+
+/// Parse the AI response into raw verdicts
+fn parse_consistency_response(content: &str) -> Result<ConsistencyRaw, AppError> {
+    let json_str = extract_json_object(content);
+
+    serde_json::from_str(json_str).map_err(|e| {
+        AppError::Internal(format!(
+            "Failed to parse AI response: {e}. Response was: {content}"
+        ))
+    })
+}
+
+/// Match raw verdicts back to the persona's tokens.
+///
+/// Out-of-range and repeated indices (first wins) are dropped, and so are
+/// tokens without a verdict. Results follow the persona's token order.
+fn resolve_verdicts(raw: Vec<TokenVerdictRaw>, tokens: &[Token]) -> Vec<(String, TokenPresence)> {
+    let mut by_index: Vec<Option<TokenVerdictRaw>> = vec![None; tokens.len()];
+    for verdict in raw {
+        if let Some(slot) = verdict
+            .index
+            .checked_sub(1)
+            .and_then(|i| by_index.get_mut(i))
+        {
+            if slot.is_none() {
+                *slot = Some(verdict);
+            }
+        }
+    }
+
+    tokens
+        .iter()
+        .zip(by_index)
+        .filter_map(|(token, verdict)| {
+            let verdict = verdict?;
+            let note = verdict.note.filter(|note| !note.trim().is_empty());
+            Some((
+                token.granularity_id.clone(),
+                TokenPresence::new(token, verdict.present, note),
+            ))
+        })
+        .collect()
+}
+
+/// Check which of a persona's tokens a generated image shows.
+///
+/// The configured model must accept images. Notes and the summary are
+/// written in `output_language`.
+///
+/// # Errors
+///
+/// Returns `AppError::Validation` if the persona has no tokens, or the image
+/// is missing, too large, or not a PNG, JPEG, WebP, or GIF file, and
+/// `AppError::Internal` if the AI request or response parsing fails.
+#[tracing::instrument(skip_all, fields(provider = ?config.provider, model = %config.model), err)]
+pub async fn verify_image(
+    config: &AiProviderConfig,
+    persona: PersonaTokens<'_>,
+    image_path: &str,
+    output_language: &str,
+    log: &AiRequestLog,
+) -> Result<ImageConsistencyReport, AppError> {
+    if persona.tokens.is_empty() {
+        return Err(AppError::Validation(format!(
+            "Persona '{}' has no tokens to check",
+            persona.persona.name
+        )));
+    }
+    let (mime_type, image_data) = load_image(image_path)?;
+
+    ensure_provider_online(config)?;
+    let api_key = resolve_api_key(config)?;
+    let client = build_client(api_key.clone());
+
+    let system_prompt = build_consistency_system_prompt(output_language);
+    let user_prompt = build_consistency_user_prompt(persona);
+    // The log keeps the image path, not the image
+    let mut log_entry = AiLogEntry::new(
+        config,
+        "verify_image",
+        &system_prompt,
+        &format!("{user_prompt}\n\n[image: {image_path}]"),
+    );
+
+    let chat_request = ChatRequest::default()
+        .with_system(system_prompt)
+        .append_message(ChatMessage::user(MessageContent::from_parts(vec![
+            ContentPart::from_text(user_prompt),
+            ContentPart::from_binary_base64(mime_type, image_data, None),
+        ])));
+    let chat_options = ChatOptions::default().with_response_format(JsonSpec::new(
+        "image_consistency",
+        build_consistency_json_schema(persona.tokens.len()),
+    ));
+
+    let parsed = exec_chat_logged(
+        &client,
+        &build_genai_model_identifier(config),
+        chat_request,
+        &chat_options,
+        "AI image check failed",
+        &mut log_entry,
+    )
+    .await
+    .and_then(|content| parse_consistency_response(&content));
+    record_exchange(
+        log,
+        &mut log_entry,
+        parsed.as_ref().err(),
+        api_key.as_deref(),
+    );
+    let parsed = parsed?;
+
+    Ok(ImageConsistencyReport::new(
+        persona.persona.id.clone(),
+        image_path.to_string(),
+        resolve_verdicts(parsed.tokens, persona.tokens),
+        parsed.summary.filter(|summary| !summary.trim().is_empty()),
+        config,
+    ))
+}
@@ -2,6 +2,21 @@
 //!
 //! Provides a unified interface for AI-powered generation using various providers.
 //! Supports `OpenAI`, Anthropic, Google, xAI, and Ollama.
+//!
+//! Requests are throttled per provider by [`rate_limit::AiRateLimiter`], and
+//! exchanges can be recorded for debugging by [`request_log::AiRequestLog`].
+//!
+//! Persona-to-persona operations live in their own submodules:
+//! [`style_transfer`] restyles one persona's tokens after another's,
+//! [`blend`] synthesizes a child persona from several parents, and
+//! [`consistency`] checks a generated image against its persona.
+
+pub mod blend;
+pub mod budget;
+pub mod consistency;
+pub mod rate_limit;
+pub mod request_log;
+pub mod style_transfer;
 
 use genai::chat::{ChatMessage, ChatOptions, ChatRequest, ChatResponse, JsonSpec};
 use genai::resolver::{AuthData, AuthResolver};
@@ -9,19 +24,68 @@ use genai::Client;
 use serde_json::json;
 
 use crate::domain::ai::{
-    AiPersonaGenerationRequest, AiPersonaGenerationResponse, AiProvider, AiProviderConfig,
-    GeneratedToken, TokenGenerationRequest, TokenGenerationResponse,
+    AiLogEntry, AiPersonaGenerationRequest, AiPersonaGenerationResponse, AiProvider,
+    AiProviderConfig, GeneratedToken, TokenGenerationRequest, TokenGenerationResponse,
+    DEFAULT_AI_OUTPUT_LANGUAGE,
 };
+use crate::domain::banned_term::{strip_banned, BannedTerm};
+use crate::domain::token::Granularity;
 use crate::domain::DEFAULT_IMAGE_MODEL_ID;
 use crate::error::AppError;
 use crate::infrastructure::tokenizer::{
     get_config_for_model, get_prompt_context_for_model, ImageModelPromptContext, TokenizerConfig,
 };
+use crate::infrastructure::{keyring, offline};
+use request_log::AiRequestLog;
 
 // ============================================================================
 // Provider Configuration
 // ============================================================================
 
+/// Resolve the API key for the configured provider.
+///
+/// The key is resolved here, at call time, so it never has to pass through
+/// the frontend: an explicit override in the config wins, then the provider's
+/// environment variable in passthrough mode, then the OS keyring.
+fn resolve_api_key(config: &AiProviderConfig) -> Result<Option<String>, AppError> {
+    if config.api_key.is_some() {
+        Ok(config.api_key.clone())
+    } else if !config.provider.requires_api_key() {
+        Ok(None)
+    } else if config.use_env_credentials {
+        keyring::get_env_api_key(&config.provider)
+    } else {
+        keyring::get_api_key(&config.provider)
+    }
+}
+
+/// Fail fast if offline mode blocks the configured provider.
+///
+/// Ollama runs locally and stays available offline; every other provider is
+/// a remote API.
+pub fn ensure_provider_online(config: &AiProviderConfig) -> Result<(), AppError> {
+    if config.provider.api_host().is_none() {
+        return Ok(());
+    }
+    offline::ensure_online(&format!("{} request", config.provider.display_name()))
+}
+
+/// Build a genai client authenticated with the given API key.
+///
+/// Without a key (Ollama), genai falls back to its own environment lookup.
+fn build_client(api_key: Option<String>) -> Client {
+    let Some(api_key) = api_key else {
+        return Client::default();
+    };
+
+    let auth_resolver = AuthResolver::from_resolver_fn(
+        move |_model_iden| -> Result<Option<AuthData>, genai::resolver::Error> {
+            Ok(Some(AuthData::from_single(api_key.clone())))
+        },
+    );
+    Client::builder().with_auth_resolver(auth_resolver).build()
+}
+
 /// Build the model identifier for the genai client.
 fn build_genai_model_identifier(config: &AiProviderConfig) -> String {
     match config.provider {
@@ -34,6 +98,80 @@ fn build_genai_model_identifier(config: &AiProviderConfig) -> String {
     }
 }
 
+/// Verify that the configured credentials are accepted by the provider.
+///
+/// Sends a minimal chat request to the configured model; any provider error
+/// (invalid key, revoked key, unknown model) is reported as a validation failure.
+#[tracing::instrument(skip_all, fields(provider = ?config.provider, model = %config.model), err)]
+pub async fn verify_api_key(config: &AiProviderConfig) -> Result<(), AppError> {
+    ensure_provider_online(config)?;
+    let client = build_client(resolve_api_key(config)?);
+    let model_id = build_genai_model_identifier(config);
+
+    let chat_request = ChatRequest::default().append_message(ChatMessage::user("ping"));
+    let chat_options = ChatOptions::default().with_max_tokens(16);
+
+    client
+        .exec_chat(&model_id, chat_request, Some(&chat_options))
+        .await
+        .map_err(|e| AppError::Validation(format!("API key verification failed: {e}")))?;
+
+    Ok(())
+}
+
+/// Execute a chat request and return the response text.
+///
+/// The raw response is stored on `log_entry` before it is returned, so it can be
+/// recorded even if parsing it fails later.
+async fn exec_chat_logged(
+    client: &Client,
+    model_id: &str,
+    chat_request: ChatRequest,
+    chat_options: &ChatOptions,
+    failure_context: &str,
+    log_entry: &mut AiLogEntry,
+) -> Result<String, AppError> {
+    let response: ChatResponse = client
+        .exec_chat(model_id, chat_request, Some(chat_options))
+        .await
+        .map_err(|e| AppError::Internal(format!("{failure_context}: {e}")))?;
+
+    let content = response
+        .first_text()
+        .ok_or_else(|| AppError::Internal("No response content from AI".to_string()))?
+        .to_string();
+
+    log_entry.response = Some(content.clone());
+    Ok(content)
+}
+
+/// Finish a log entry with the exchange outcome and record it.
+fn record_exchange(
+    log: &AiRequestLog,
+    log_entry: &mut AiLogEntry,
+    error: Option<&AppError>,
+    api_key: Option<&str>,
+) {
+    log_entry.error = error.map(ToString::to_string);
+    log.record(log_entry, api_key);
+}
+
+/// Build the system prompt section setting the language of free-text fields.
+///
+/// Empty for English. Tokens and tags stay English whatever the language,
+/// because image models are trained on English prompts.
+fn build_output_language_section(output_language: &str, prose_fields: &str) -> String {
+    if output_language.eq_ignore_ascii_case(DEFAULT_AI_OUTPUT_LANGUAGE) {
+        return String::new();
+    }
+
+    format!(
+        "\n\nOUTPUT LANGUAGE:
+Write {prose_fields} in {output_language}.
+Every token, tag, and granularity_id must stay in English: image models only understand English prompts."
+    )
+}
+
 // ============================================================================
 // Persona Generation
 // ============================================================================
@@ -47,6 +185,7 @@ fn build_persona_generation_system_prompt(
     existing_tags: &[String],
     improve_description_via_ai: bool,
     skip_ai_description: bool,
+    output_language: &str,
 ) -> String {
     let existing_tags_section = if existing_tags.is_empty() {
         String::new()
@@ -120,12 +259,16 @@ Place high-impact, character-defining tokens earlier for maximum influence.
 TAG INFERENCE:
 Derive 1-3 relevant tags from the style and description.{existing_tags_section}
 
-{description_instruction}"#,
+{description_instruction}{output_language_section}"#,
         model_name = prompt_context.display_name,
         family = prompt_context.family,
         total_tokens = tokenizer_config.usable_tokens,
         existing_tags_section = existing_tags_section,
         description_instruction = description_instruction,
+        output_language_section = build_output_language_section(
+            output_language,
+            "the description, the refined ai_instructions, and every rationale",
+        ),
     )
 }
 
@@ -444,18 +587,21 @@ struct PersonaGenerationRaw {
     tokens: Vec<GeneratedToken>,
 }
 
+/// Returns the outermost JSON object of a response, dropping any prose or
+/// code fences around it (internal helper).
+///
+/// The whole response is returned if it has no `{ ... }` span, so the parse
+/// error shows what the model sent.
+fn extract_json_object(content: &str) -> &str {
+    match (content.find('{'), content.rfind('}')) {
+        (Some(start), Some(end)) if start < end => &content[start..=end],
+        _ => content,
+    }
+}
+
 /// Parse the AI response for persona generation
 fn parse_persona_response(content: &str) -> Result<PersonaGenerationRaw, AppError> {
-    // Try to extract JSON object from the response
-    let json_str = if let Some(start) = content.find('{') {
-        if let Some(end) = content.rfind('}') {
-            &content[start..=end]
-        } else {
-            content
-        }
-    } else {
-        content
-    };
+    let json_str = extract_json_object(content);
 
     serde_json::from_str(json_str).map_err(|e| {
         AppError::Internal(format!(
@@ -464,33 +610,82 @@ fn parse_persona_response(content: &str) -> Result<PersonaGenerationRaw, AppErro
     })
 }
 
+/// Plan how the token budget is split across granularities (first pass).
+///
+/// Falls back to the default split if the plan cannot be parsed; request
+/// failures are returned as errors.
+async fn plan_persona_budget(
+    client: &Client,
+    config: &AiProviderConfig,
+    model_name: &str,
+    usable_tokens: usize,
+    character_prompt: &str,
+    log: &AiRequestLog,
+    api_key: Option<&str>,
+) -> Result<Vec<budget::BudgetAllocation>, AppError> {
+    let system_prompt = budget::build_planning_system_prompt(model_name, usable_tokens);
+    let mut log_entry = AiLogEntry::new(
+        config,
+        "persona_budget_planning",
+        &system_prompt,
+        character_prompt,
+    );
+
+    let chat_request = ChatRequest::default()
+        .with_system(system_prompt)
+        .append_message(ChatMessage::user(character_prompt));
+    let chat_options = ChatOptions::default().with_response_format(JsonSpec::new(
+        "budget_plan",
+        budget::build_planning_json_schema(),
+    ));
+
+    let plan = exec_chat_logged(
+        client,
+        &build_genai_model_identifier(config),
+        chat_request,
+        &chat_options,
+        "AI persona budget planning failed",
+        &mut log_entry,
+    )
+    .await
+    .and_then(|content| budget::parse_budget_plan(&content, usable_tokens));
+    record_exchange(log, &mut log_entry, plan.as_ref().err(), api_key);
+
+    match plan {
+        // The model answered but the plan was unusable; generation can still proceed
+        Err(_) if log_entry.response.is_some() => Ok(budget::default_allocations(usable_tokens)),
+        result => result,
+    }
+}
+
 /// Generate a complete persona using AI
 ///
 /// Takes user inputs (name, style, character description, physical criteria) and
 /// generates a fully-formed persona with tokens organized by granularity.
+///
+/// Runs in two passes: a planning pass allocates the token budget per
+/// granularity, then the generation pass writes tokens within those budgets.
+/// The result is verified with the target model's tokenizer and trimmed to fit.
+/// Tokens containing one of `banned_terms` are dropped before the check and
+/// reported in `filtered`. The description and rationales are written in
+/// `output_language`; tokens and tags stay English.
+#[tracing::instrument(skip_all, fields(provider = ?config.provider, model = %config.model), err)]
 pub async fn generate_persona(
     config: &AiProviderConfig,
     request: &AiPersonaGenerationRequest,
+    banned_terms: &[BannedTerm],
+    output_language: &str,
+    log: &AiRequestLog,
 ) -> Result<AiPersonaGenerationResponse, AppError> {
-    // Build client with API key from config
-    let client = if let Some(api_key) = &config.api_key {
-        let api_key = api_key.clone();
-        let auth_resolver = AuthResolver::from_resolver_fn(
-            move |_model_iden| -> Result<Option<AuthData>, genai::resolver::Error> {
-                Ok(Some(AuthData::from_single(api_key.clone())))
-            },
-        );
-        Client::builder().with_auth_resolver(auth_resolver).build()
-    } else {
-        // Fall back to environment variables (for Ollama or if no key provided)
-        Client::default()
-    };
+    ensure_provider_online(config)?;
+    let api_key = resolve_api_key(config)?;
+    let client = build_client(api_key.clone());
 
     // Get model context for the selected image model
     let image_model_id_str = request.image_model_id.as_deref();
     let prompt_context = get_prompt_context_for_model(image_model_id_str);
-    let tokenizer_config =
-        get_config_for_model(image_model_id_str.unwrap_or(DEFAULT_IMAGE_MODEL_ID));
+    let image_model_id = image_model_id_str.unwrap_or(DEFAULT_IMAGE_MODEL_ID);
+    let tokenizer_config = get_config_for_model(image_model_id);
 
     let system_prompt = build_persona_generation_system_prompt(
         &prompt_context,
@@ -498,8 +693,28 @@ pub async fn generate_persona(
         &request.existing_tags,
         request.improve_description_via_ai,
         request.skip_ai_description,
+        output_language,
+    );
+    let character_prompt = build_persona_generation_user_prompt(request);
+
+    // Pass 1: plan the per-granularity budget
+    let allocations = plan_persona_budget(
+        &client,
+        config,
+        &prompt_context.display_name,
+        tokenizer_config.usable_tokens,
+        &character_prompt,
+        log,
+        api_key.as_deref(),
+    )
+    .await?;
+
+    // Pass 2: generate tokens within the planned budgets
+    let user_prompt = format!(
+        "{character_prompt}\n\n{}",
+        budget::build_budget_section(&allocations)
     );
-    let user_prompt = build_persona_generation_user_prompt(request);
+    let mut log_entry = AiLogEntry::new(config, "persona_generation", &system_prompt, &user_prompt);
 
     let chat_request = ChatRequest::default()
         .with_system(system_prompt)
@@ -523,16 +738,39 @@ pub async fn generate_persona(
 
     let model_id = build_genai_model_identifier(config);
 
-    let response: ChatResponse = client
-        .exec_chat(&model_id, chat_request, Some(&chat_options))
-        .await
-        .map_err(|e| AppError::Internal(format!("AI persona generation failed: {e}")))?;
+    let parsed = exec_chat_logged(
+        &client,
+        &model_id,
+        chat_request,
+        &chat_options,
+        "AI persona generation failed",
+        &mut log_entry,
+    )
+    .await
+    .and_then(|content| parse_persona_response(&content));
+    record_exchange(
+        log,
+        &mut log_entry,
+        parsed.as_ref().err(),
+        api_key.as_deref(),
+    );
+    let parsed = parsed?;
 
-    let content = response
-        .first_text()
-        .ok_or_else(|| AppError::Internal("No response content from AI".to_string()))?;
+    let (tokens, filtered) = strip_banned(parsed.tokens, banned_terms, |t| &t.content);
 
-    let parsed = parse_persona_response(content)?;
+    // Verify with the real tokenizer and trim anything over budget
+    let (mut tokens, granularity_budgets) = budget::fit_tokens_to_budget(
+        tokens,
+        &allocations,
+        tokenizer_config.usable_tokens,
+        image_model_id,
+    );
+    budget::annotate_token_costs(
+        &mut tokens,
+        None,
+        tokenizer_config.usable_tokens,
+        image_model_id,
+    );
 
     Ok(AiPersonaGenerationResponse {
         // Use empty string if description was omitted (when not improving via AI)
@@ -540,9 +778,11 @@ pub async fn generate_persona(
         // Use None if ai_instructions was omitted (when not improving via AI)
         ai_instructions: parsed.ai_instructions,
         tags: parsed.tags,
-        tokens: parsed.tokens,
+        tokens,
+        granularity_budgets,
         provider: config.provider,
         model: config.model.clone(),
+        filtered,
     })
 }
 
@@ -552,10 +792,61 @@ pub async fn generate_persona(
 //
 // Generates additional positive/negative tokens during prompt composition.
 
+/// Resolve the granularity a token request is scoped to.
+///
+/// Accepts either the ID ("`upper_body`") or display name ("Upper Body"), in any
+/// case. Returns `None` for ad-hoc requests or unrecognized names.
+fn resolve_granularity_focus(granularity_name: &str) -> Option<Granularity> {
+    let name = granularity_name.trim();
+    Granularity::all().iter().copied().find(|granularity| {
+        granularity.as_str().eq_ignore_ascii_case(name)
+            || granularity.display_name().eq_ignore_ascii_case(name)
+    })
+}
+
+/// Describe what belongs to a granularity, for scoping token suggestions.
+const fn granularity_focus_scope(granularity: Granularity) -> &'static str {
+    match granularity {
+        Granularity::Style => {
+            "art style, medium, rendering quality, and overall aesthetic modifiers"
+        }
+        Granularity::General => {
+            "overall physical traits such as body type, skin tone, complexion, and age"
+        }
+        Granularity::Hair => "hair color, length, texture, hairstyle, and hair accessories",
+        Granularity::Face => "eyes, face shape, facial features, expression, and makeup",
+        Granularity::UpperBody => "torso, chest, shoulders, arms, hands, and upper-body clothing",
+        Granularity::Midsection => "waist, hips, midriff, and clothing worn around the waist",
+        Granularity::LowerBody => "legs, thighs, feet, and lower-body clothing and footwear",
+    }
+}
+
+/// Build the section of the system prompt describing what the tokens should target
+fn build_token_focus_context(focus: Option<Granularity>) -> String {
+    match focus {
+        None => "ADHOC TOKEN CONTEXT:
+You are generating ad-hoc tokens for scene-specific enhancement: context, action, mood, lighting, composition, and quality modifiers.
+These tokens are NOT body-region specific - they enhance the overall image generation for a particular scene or context."
+            .to_string(),
+        Some(granularity) => format!(
+            "GRANULARITY FOCUS: {name}
+You are generating tokens for the {name} section of the persona ONLY.
+This section covers: {scope}.
+Every token must describe this section. Do not suggest tokens for other body regions, the scene, or overall style unless they directly concern {name}.
+Set each token's granularity_id to the category it actually describes; tokens outside \"{id}\" will be discarded.",
+            name = granularity.display_name(),
+            scope = granularity_focus_scope(granularity),
+            id = granularity.as_str(),
+        ),
+    }
+}
+
 /// Build the system prompt for token generation
 fn build_token_generation_system_prompt(
     prompt_context: &ImageModelPromptContext,
     tokenizer_config: &crate::infrastructure::tokenizer::TokenizerConfig,
+    focus: Option<Granularity>,
+    output_language: &str,
 ) -> String {
     format!(
         r"You are an expert prompt engineer for {model_name} ({family} family) image generation, specializing in token enhancement and refinement.
@@ -600,22 +891,25 @@ Weights control emphasis in the final image prompt. Use this scale:
 LIMITS: Never exceed 1.5 (causes artifacts). Never go below 0.6 (may not render).
 DISTRIBUTION: ~50-60% at 1.0, ~25% at 0.8-0.9, ~15% at 1.1-1.2, ~5% at 1.3+
 
-ADHOC TOKEN CONTEXT:
-You are generating ad-hoc tokens for scene-specific enhancement: context, action, mood, lighting, composition, and quality modifiers.
-These tokens are NOT body-region specific - they enhance the overall image generation for a particular scene or context.
+{focus_context}
 
 SEMANTIC COHERENCE:
 - Maintain consistency with the persona's established visual identity
 - New tokens should feel like natural extensions, not contradictions
-- Consider how tokens will interact when combined in the final prompt",
+- Consider how tokens will interact when combined in the final prompt{output_language_section}",
         model_name = prompt_context.display_name,
         family = prompt_context.family,
         limit = tokenizer_config.usable_tokens,
+        focus_context = build_token_focus_context(focus),
+        output_language_section = build_output_language_section(output_language, "every rationale"),
     )
 }
 
 /// Build the user prompt for token generation
-fn build_token_generation_user_prompt(request: &TokenGenerationRequest) -> String {
+fn build_token_generation_user_prompt(
+    request: &TokenGenerationRequest,
+    focus: Option<Granularity>,
+) -> String {
     let model_id = request.image_model_id.as_deref();
     let tokenizer_config = get_config_for_model(model_id.unwrap_or(DEFAULT_IMAGE_MODEL_ID));
     let mut sections = Vec::new();
@@ -663,9 +957,20 @@ fn build_token_generation_user_prompt(request: &TokenGenerationRequest) -> Strin
     }
 
     // Section 3: Task Specification
-    sections.push(
-        "TASK:\nGenerate positive and negative tokens that complement the existing prompt.\nFocus on enhancing the scene/context described below while maintaining coherence with the persona's identity.".to_string(),
-    );
+    sections.push(format!(
+        "TASK:\nGenerate exactly {positive} positive token(s) and exactly {negative} negative token(s) that complement the existing prompt.\nFocus on enhancing the scene/context described below while maintaining coherence with the persona's identity.",
+        positive = request.positive_count,
+        negative = request.negative_count,
+    ));
+
+    // Section 3b: Granularity Focus
+    if let Some(granularity) = focus {
+        sections.push(format!(
+            "FOCUS AREA: {name} ONLY\nEvery positive and negative token must concern {scope}.",
+            name = granularity.display_name(),
+            scope = granularity_focus_scope(granularity),
+        ));
+    }
 
     // Section 4: Scene/Context Description
     if let Some(hints) = &request.style_hints {
@@ -687,6 +992,10 @@ fn build_token_generation_user_prompt(request: &TokenGenerationRequest) -> Strin
 
     // Section 6: Constraints (structured and comprehensive)
     let mut constraints = vec![
+        format!(
+            "Return at most {} positive and {} negative tokens; extra tokens will be discarded",
+            request.positive_count, request.negative_count
+        ),
         "Generate tokens based ONLY on the provided persona and context description".to_string(),
         "Each token must be visually descriptive and suitable for image generation".to_string(),
         "Maintain semantic coherence with the persona's established character".to_string(),
@@ -784,16 +1093,7 @@ struct TokensRaw {
 fn parse_token_generation_response(
     content: &str,
 ) -> Result<(Vec<GeneratedToken>, Vec<GeneratedToken>), AppError> {
-    // Try to extract JSON object from the response
-    let json_str = if let Some(start) = content.find('{') {
-        if let Some(end) = content.rfind('}') {
-            &content[start..=end]
-        } else {
-            content
-        }
-    } else {
-        content
-    };
+    let json_str = extract_json_object(content);
 
     let parsed: TokensRaw = serde_json::from_str(json_str).map_err(|e| {
         AppError::Internal(format!(
@@ -805,91 +1105,163 @@ fn parse_token_generation_response(
 }
 
 /// Build the JSON schema for token generation response
-fn build_token_generation_json_schema() -> serde_json::Value {
+///
+/// Array sizes are capped at the requested counts so providers that enforce
+/// the schema cannot return more tokens than asked for. Focused requests also
+/// require a `granularity_id` per token so off-topic suggestions can be dropped.
+fn build_token_generation_json_schema(
+    positive_count: usize,
+    negative_count: usize,
+    focus: Option<Granularity>,
+) -> serde_json::Value {
+    let mut item = json!({
+        "type": "object",
+        "properties": {
+            "content": { "type": "string" },
+            "suggested_weight": { "type": "number" },
+            "rationale": { "type": "string" }
+        },
+        "required": ["content", "suggested_weight"]
+    });
+
+    if focus.is_some() {
+        let granularity_ids: Vec<&str> =
+            Granularity::all().iter().map(Granularity::as_str).collect();
+        item["properties"]["granularity_id"] = json!({ "type": "string", "enum": granularity_ids });
+        item["required"] = json!(["content", "suggested_weight", "granularity_id"]);
+    }
+
     json!({
         "type": "object",
         "properties": {
             "positive": {
                 "type": "array",
-                "items": {
-                    "type": "object",
-                    "properties": {
-                        "content": { "type": "string" },
-                        "suggested_weight": { "type": "number" },
-                        "rationale": { "type": "string" }
-                    },
-                    "required": ["content", "suggested_weight"]
-                }
+                "maxItems": positive_count,
+                "items": item.clone()
             },
             "negative": {
                 "type": "array",
-                "items": {
-                    "type": "object",
-                    "properties": {
-                        "content": { "type": "string" },
-                        "suggested_weight": { "type": "number" },
-                        "rationale": { "type": "string" }
-                    },
-                    "required": ["content", "suggested_weight"]
-                }
+                "maxItems": negative_count,
+                "items": item
             }
         },
         "required": ["positive", "negative"]
     })
 }
 
+/// Drop tokens the model tagged with a different granularity than the focus.
+///
+/// Tokens without a `granularity_id` (providers that ignore the schema) are kept.
+fn retain_focused_tokens(tokens: &mut Vec<GeneratedToken>, focus: Option<Granularity>) {
+    let Some(granularity) = focus else {
+        return;
+    };
+
+    tokens.retain(|token| {
+        token
+            .granularity_id
+            .as_deref()
+            .map_or(true, |id| id == granularity.as_str())
+    });
+}
+
 /// Generate tokens using an AI provider
+///
+/// Suggestions containing one of `banned_terms` are dropped and reported in
+/// `filtered`. Rationales are written in `output_language`.
+#[tracing::instrument(skip_all, fields(provider = ?config.provider, model = %config.model), err)]
 pub async fn generate_tokens(
     config: &AiProviderConfig,
     request: &TokenGenerationRequest,
+    banned_terms: &[BannedTerm],
+    output_language: &str,
+    log: &AiRequestLog,
 ) -> Result<TokenGenerationResponse, AppError> {
-    // Build client with API key from config (not environment variable)
-    let client = if let Some(api_key) = &config.api_key {
-        let api_key = api_key.clone();
-        let auth_resolver = AuthResolver::from_resolver_fn(
-            move |_model_iden| -> Result<Option<AuthData>, genai::resolver::Error> {
-                Ok(Some(AuthData::from_single(api_key.clone())))
-            },
-        );
-        Client::builder().with_auth_resolver(auth_resolver).build()
-    } else {
-        // Fall back to environment variables (for Ollama or if no key provided)
-        Client::default()
-    };
+    ensure_provider_online(config)?;
+    let api_key = resolve_api_key(config)?;
+    let client = build_client(api_key.clone());
 
     let model_id_str = request.image_model_id.as_deref();
     let prompt_context = get_prompt_context_for_model(model_id_str);
     let tokenizer_config = get_config_for_model(model_id_str.unwrap_or(DEFAULT_IMAGE_MODEL_ID));
 
-    let system_prompt = build_token_generation_system_prompt(&prompt_context, &tokenizer_config);
-    let user_prompt = build_token_generation_user_prompt(request);
+    let focus = resolve_granularity_focus(&request.granularity_name);
+
+    let system_prompt = build_token_generation_system_prompt(
+        &prompt_context,
+        &tokenizer_config,
+        focus,
+        output_language,
+    );
+    let user_prompt = build_token_generation_user_prompt(request, focus);
+    let mut log_entry = AiLogEntry::new(config, "token_generation", &system_prompt, &user_prompt);
 
     let chat_request = ChatRequest::default()
         .with_system(system_prompt)
         .append_message(ChatMessage::user(user_prompt));
 
     // Create ChatOptions with structured response format for API-level schema enforcement
-    let json_schema = build_token_generation_json_schema();
+    let json_schema =
+        build_token_generation_json_schema(request.positive_count, request.negative_count, focus);
     let chat_options =
         ChatOptions::default().with_response_format(JsonSpec::new("tokens", json_schema));
 
     let model_id = build_genai_model_identifier(config);
 
-    let response: ChatResponse = client
-        .exec_chat(&model_id, chat_request, Some(&chat_options))
-        .await
-        .map_err(|e| AppError::Internal(format!("AI request failed: {e}")))?;
+    let parsed = exec_chat_logged(
+        &client,
+        &model_id,
+        chat_request,
+        &chat_options,
+        "AI request failed",
+        &mut log_entry,
+    )
+    .await
+    .and_then(|content| parse_token_generation_response(&content));
+    record_exchange(
+        log,
+        &mut log_entry,
+        parsed.as_ref().err(),
+        api_key.as_deref(),
+    );
+    let (positive_tokens, negative_tokens) = parsed?;
 
-    let content = response
-        .first_text()
-        .ok_or_else(|| AppError::Internal("No response content from AI".to_string()))?;
+    let (mut positive_tokens, mut filtered) =
+        strip_banned(positive_tokens, banned_terms, |t| &t.content);
+    let (mut negative_tokens, filtered_negative) =
+        strip_banned(negative_tokens, banned_terms, |t| &t.content);
+    filtered.extend(filtered_negative);
+
+    retain_focused_tokens(&mut positive_tokens, focus);
+    retain_focused_tokens(&mut negative_tokens, focus);
 
-    let (positive_tokens, negative_tokens) = parse_token_generation_response(content)?;
+    // Providers that ignore maxItems may still over-deliver; keep the first N
+    positive_tokens.truncate(request.positive_count);
+    negative_tokens.truncate(request.negative_count);
+
+    // Show the real prompt impact of each suggestion before it is accepted
+    let image_model_id = model_id_str.unwrap_or(DEFAULT_IMAGE_MODEL_ID);
+    let usable_tokens = request
+        .max_usable_tokens
+        .unwrap_or(tokenizer_config.usable_tokens);
+    budget::annotate_token_costs(
+        &mut positive_tokens,
+        request.current_positive_prompt.as_deref(),
+        usable_tokens,
+        image_model_id,
+    );
+    budget::annotate_token_costs(
+        &mut negative_tokens,
+        request.current_negative_prompt.as_deref(),
+        usable_tokens,
+        image_model_id,
+    );
 
     Ok(TokenGenerationResponse {
         positive_tokens,
         negative_tokens,
         provider: config.provider,
         model: config.model.clone(),
+        filtered,
     })
 }
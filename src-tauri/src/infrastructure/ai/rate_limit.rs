@@ -0,0 +1,215 @@
+//! Per-provider rate limiting for AI requests
+//!
+//! Each provider gets its own limiter combining three guards:
+//!
+//! - **Concurrency**: at most `max_concurrent` requests in flight
+//! - **Spacing**: at least `min_interval` between request starts
+//! - **Throughput**: at most `requests_per_minute` starts in any 60-second window
+//!
+//! Requests beyond these limits wait in a FIFO queue instead of being sent and
+//! rejected with HTTP 429. Callers are told their queue position while waiting.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use tokio::sync::{watch, OwnedSemaphorePermit, Semaphore};
+
+use crate::domain::ai::AiProvider;
+use crate::error::AppError;
+
+/// Length of the sliding window used for requests-per-minute limits.
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+
+/// Rate limit settings for a single provider.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitConfig {
+    /// Maximum number of requests in flight at once
+    pub max_concurrent: usize,
+    /// Minimum delay between the start of two requests
+    pub min_interval: Duration,
+    /// Maximum request starts per minute (`None` for unlimited)
+    pub requests_per_minute: Option<usize>,
+}
+
+impl RateLimitConfig {
+    /// Returns conservative defaults suited to each provider's entry-tier limits.
+    ///
+    /// Ollama runs locally on a single model instance, so requests are serialized
+    /// rather than throttled.
+    #[must_use]
+    pub const fn for_provider(provider: &AiProvider) -> Self {
+        match provider {
+            AiProvider::OpenAI => Self {
+                max_concurrent: 4,
+                min_interval: Duration::from_millis(200),
+                requests_per_minute: Some(60),
+            },
+            AiProvider::Anthropic => Self {
+                max_concurrent: 4,
+                min_interval: Duration::from_millis(250),
+                requests_per_minute: Some(50),
+            },
+            AiProvider::Google => Self {
+                max_concurrent: 4,
+                min_interval: Duration::from_millis(200),
+                requests_per_minute: Some(60),
+            },
+            AiProvider::XAi => Self {
+                max_concurrent: 4,
+                min_interval: Duration::from_millis(200),
+                requests_per_minute: Some(60),
+            },
+            AiProvider::Ollama => Self {
+                max_concurrent: 1,
+                min_interval: Duration::ZERO,
+                requests_per_minute: None,
+            },
+        }
+    }
+}
+
+/// Limiter state for a single provider.
+struct ProviderLimiter {
+    config: RateLimitConfig,
+    semaphore: Arc<Semaphore>,
+    /// Tickets handed out to queued requests
+    enqueued: AtomicUsize,
+    /// Number of tickets that have left the queue (started or cancelled)
+    dequeued: watch::Sender<usize>,
+    /// Start times of recent requests, oldest first
+    recent_starts: tokio::sync::Mutex<VecDeque<Instant>>,
+}
+
+impl ProviderLimiter {
+    fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config,
+            semaphore: Arc::new(Semaphore::new(config.max_concurrent.max(1))),
+            enqueued: AtomicUsize::new(0),
+            dequeued: watch::Sender::new(0),
+            recent_starts: tokio::sync::Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Waits until the spacing and per-minute limits allow another request to start.
+    async fn wait_for_slot(&self) {
+        let mut recent = self.recent_starts.lock().await;
+
+        loop {
+            let now = Instant::now();
+            while recent
+                .front()
+                .is_some_and(|start| now.duration_since(*start) >= RATE_WINDOW)
+            {
+                recent.pop_front();
+            }
+
+            let mut wait = recent.back().map_or(Duration::ZERO, |last| {
+                self.config
+                    .min_interval
+                    .saturating_sub(now.duration_since(*last))
+            });
+
+            if let (Some(rpm), Some(oldest)) = (self.config.requests_per_minute, recent.front()) {
+                if recent.len() >= rpm {
+                    wait = wait.max(RATE_WINDOW.saturating_sub(now.duration_since(*oldest)));
+                }
+            }
+
+            if wait.is_zero() {
+                recent.push_back(now);
+                return;
+            }
+
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+/// Marks a queued request as dequeued when it starts or is cancelled.
+struct QueueTicket<'a> {
+    dequeued: &'a watch::Sender<usize>,
+}
+
+impl Drop for QueueTicket<'_> {
+    fn drop(&mut self) {
+        self.dequeued.send_modify(|count| *count += 1);
+    }
+}
+
+/// Permission to run one AI request; the concurrency slot is released on drop.
+pub struct RateLimitPermit {
+    _permit: OwnedSemaphorePermit,
+}
+
+/// Per-provider rate limiter shared across all AI commands via Tauri managed state.
+#[derive(Default)]
+pub struct AiRateLimiter {
+    limiters: Mutex<HashMap<AiProvider, Arc<ProviderLimiter>>>,
+}
+
+impl AiRateLimiter {
+    fn limiter_for(&self, provider: AiProvider) -> Result<Arc<ProviderLimiter>, AppError> {
+        let mut limiters = self
+            .limiters
+            .lock()
+            .map_err(|_| AppError::Internal("Failed to acquire rate limiter lock".to_string()))?;
+
+        Ok(Arc::clone(limiters.entry(provider).or_insert_with(|| {
+            Arc::new(ProviderLimiter::new(RateLimitConfig::for_provider(
+                &provider,
+            )))
+        })))
+    }
+
+    /// Waits for the provider's limits to allow a new request.
+    ///
+    /// `on_queue` is called with the number of requests still ahead in the queue
+    /// whenever it changes, and with `0` once the request is allowed to start.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Internal` if the limiter state cannot be accessed.
+    pub async fn acquire(
+        &self,
+        provider: AiProvider,
+        on_queue: impl Fn(usize) + Send,
+    ) -> Result<RateLimitPermit, AppError> {
+        let limiter = self.limiter_for(provider)?;
+
+        let ticket = limiter.enqueued.fetch_add(1, Ordering::SeqCst);
+        let queue_ticket = QueueTicket {
+            dequeued: &limiter.dequeued,
+        };
+        let mut dequeued = limiter.dequeued.subscribe();
+
+        let acquire = Arc::clone(&limiter.semaphore).acquire_owned();
+        tokio::pin!(acquire);
+
+        let mut last_reported = None;
+        let permit = loop {
+            let ahead = ticket.saturating_sub(*dequeued.borrow_and_update());
+            if ahead > 0 && last_reported != Some(ahead) {
+                on_queue(ahead);
+                last_reported = Some(ahead);
+            }
+
+            tokio::select! {
+                permit = &mut acquire => {
+                    break permit.map_err(|_| {
+                        AppError::Internal("AI rate limiter was closed".to_string())
+                    })?;
+                }
+                _ = dequeued.changed() => {}
+            }
+        };
+
+        limiter.wait_for_slot().await;
+        drop(queue_ticket);
+        on_queue(0);
+
+        Ok(RateLimitPermit { _permit: permit })
+    }
+}
@@ -0,0 +1,235 @@
+//! Opt-in debug log of AI requests and responses
+//!
+//! When enabled, every AI exchange (prompts, raw response, and any request or
+//! parse error) is appended as a JSON line to `ai-logs/ai-requests.jsonl` under
+//! the app data directory. This makes failures such as malformed JSON responses
+//! diagnosable after the fact.
+//!
+//! # Redaction
+//!
+//! Entries are redacted before they touch the disk: the API key used for the
+//! request is removed verbatim, and anything shaped like a provider key
+//! (`sk-…`, `AIza…`, `xai-…`, `Bearer …`) is masked as well.
+//!
+//! # Rotation
+//!
+//! The active file is rotated once it exceeds [`MAX_LOG_FILE_BYTES`], keeping
+//! at most [`MAX_ROTATED_FILES`] older files.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use crate::domain::ai::AiLogEntry;
+use crate::error::AppError;
+
+/// Directory (under app data) holding the log files.
+const LOG_DIR_NAME: &str = "ai-logs";
+
+/// Base name of the active log file.
+const LOG_FILE_STEM: &str = "ai-requests";
+
+/// Size at which the active log file is rotated.
+pub const MAX_LOG_FILE_BYTES: u64 = 1024 * 1024;
+
+/// Number of rotated log files kept alongside the active one.
+pub const MAX_ROTATED_FILES: usize = 3;
+
+/// Placeholder written in place of redacted secrets.
+const REDACTED: &str = "[REDACTED]";
+
+/// Prefixes that introduce provider API keys or bearer tokens.
+const SECRET_PREFIXES: &[&str] = &["sk-", "AIza", "xai-", "Bearer "];
+
+/// Minimum length of the key body after a prefix for it to be treated as a secret.
+const MIN_SECRET_LEN: usize = 16;
+
+/// Masks anything shaped like an API key, plus the given known secrets.
+#[must_use]
+pub fn redact_secrets(text: &str, known_secrets: &[&str]) -> String {
+    let mut text = text.to_string();
+    for secret in known_secrets.iter().filter(|s| !s.is_empty()) {
+        text = text.replace(secret, REDACTED);
+    }
+
+    let mut redacted = String::with_capacity(text.len());
+    let mut rest = text.as_str();
+
+    while let Some((index, prefix)) = SECRET_PREFIXES
+        .iter()
+        .filter_map(|prefix| rest.find(prefix).map(|index| (index, *prefix)))
+        .min_by_key(|(index, _)| *index)
+    {
+        let body_start = index + prefix.len();
+        let body = &rest[body_start..];
+        let body_len = body
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.'))
+            .unwrap_or(body.len());
+
+        redacted.push_str(&rest[..body_start]);
+        if body_len >= MIN_SECRET_LEN {
+            redacted.push_str(REDACTED);
+        } else {
+            redacted.push_str(&body[..body_len]);
+        }
+        rest = &body[body_len..];
+    }
+
+    redacted.push_str(rest);
+    redacted
+}
+
+/// Rotating, redacting log of AI exchanges, shared via Tauri managed state.
+pub struct AiRequestLog {
+    dir: PathBuf,
+    enabled: AtomicBool,
+    /// Serializes writes and rotation
+    write_lock: Mutex<()>,
+}
+
+impl AiRequestLog {
+    /// Creates a disabled log storing its files under the given app data directory.
+    #[must_use]
+    pub fn new(app_data_dir: &Path) -> Self {
+        Self {
+            dir: app_data_dir.join(LOG_DIR_NAME),
+            enabled: AtomicBool::new(false),
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    /// Returns whether exchanges are currently being recorded.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Turns recording on or off.
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Path of a log file: index 0 is the active file, higher indices are older.
+    fn file_path(&self, index: usize) -> PathBuf {
+        if index == 0 {
+            self.dir.join(format!("{LOG_FILE_STEM}.jsonl"))
+        } else {
+            self.dir.join(format!("{LOG_FILE_STEM}.{index}.jsonl"))
+        }
+    }
+
+    /// Records an exchange if logging is enabled, redacting the given secret.
+    ///
+    /// Logging is best-effort: write failures never affect the AI request itself.
+    pub fn record(&self, entry: &AiLogEntry, api_key: Option<&str>) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        let secrets: Vec<&str> = api_key.into_iter().collect();
+        let redacted = AiLogEntry {
+            system_prompt: redact_secrets(&entry.system_prompt, &secrets),
+            user_prompt: redact_secrets(&entry.user_prompt, &secrets),
+            response: entry
+                .response
+                .as_deref()
+                .map(|r| redact_secrets(r, &secrets)),
+            error: entry.error.as_deref().map(|e| redact_secrets(e, &secrets)),
+            ..entry.clone()
+        };
+
+        let _ = self.append(&redacted);
+    }
+
+    /// Appends an entry to the active file, rotating it first if it is full.
+    fn append(&self, entry: &AiLogEntry) -> Result<(), AppError> {
+        let _guard = self
+            .write_lock
+            .lock()
+            .map_err(|_| AppError::Internal("Failed to acquire AI log lock".to_string()))?;
+
+        fs::create_dir_all(&self.dir)?;
+
+        let active = self.file_path(0);
+        if fs::metadata(&active).is_ok_and(|meta| meta.len() >= MAX_LOG_FILE_BYTES) {
+            self.rotate()?;
+        }
+
+        let mut line = serde_json::to_string(entry)?;
+        line.push('\n');
+
+        let mut file = OpenOptions::new().create(true).append(true).open(&active)?;
+        file.write_all(line.as_bytes())?;
+
+        Ok(())
+    }
+
+    /// Shifts every log file one slot older, dropping the oldest.
+    fn rotate(&self) -> Result<(), AppError> {
+        let oldest = self.file_path(MAX_ROTATED_FILES);
+        if oldest.exists() {
+            fs::remove_file(&oldest)?;
+        }
+
+        for index in (0..MAX_ROTATED_FILES).rev() {
+            let from = self.file_path(index);
+            if from.exists() {
+                fs::rename(&from, self.file_path(index + 1))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns up to `limit` of the most recent entries, newest first.
+    ///
+    /// Lines that fail to parse (e.g., a truncated final write) are skipped.
+    pub fn recent(&self, limit: usize) -> Result<Vec<AiLogEntry>, AppError> {
+        let _guard = self
+            .write_lock
+            .lock()
+            .map_err(|_| AppError::Internal("Failed to acquire AI log lock".to_string()))?;
+
+        let mut entries = Vec::new();
+
+        for index in 0..=MAX_ROTATED_FILES {
+            let path = self.file_path(index);
+            if !path.exists() {
+                continue;
+            }
+
+            let content = fs::read_to_string(&path)?;
+            entries.extend(
+                content
+                    .lines()
+                    .rev()
+                    .filter_map(|line| serde_json::from_str::<AiLogEntry>(line).ok()),
+            );
+
+            if entries.len() >= limit {
+                break;
+            }
+        }
+
+        entries.truncate(limit);
+        Ok(entries)
+    }
+
+    /// Deletes all log files.
+    pub fn clear(&self) -> Result<(), AppError> {
+        let _guard = self
+            .write_lock
+            .lock()
+            .map_err(|_| AppError::Internal("Failed to acquire AI log lock".to_string()))?;
+
+        for index in 0..=MAX_ROTATED_FILES {
+            let path = self.file_path(index);
+            if path.exists() {
+                fs::remove_file(&path)?;
+            }
+        }
+
+        Ok(())
+    }
+}
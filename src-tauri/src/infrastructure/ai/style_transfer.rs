@@ -0,0 +1,324 @@
+//! Style transfer between personas
+//!
+//! Adapts a target persona's tokens to the visual style defined by a source
+//! persona's Style tokens (and optionally its description). The model proposes
+//! per-token rewrites referenced by position in the target's token list; the
+//! proposal is mapped back to token IDs so the user can review each change
+//! before anything is saved.
+
+use genai::chat::{ChatMessage, ChatOptions, ChatRequest, JsonSpec};
+use serde_json::json;
+
+use super::request_log::AiRequestLog;
+use super::{
+    build_client, build_genai_model_identifier, build_output_language_section,
+    ensure_provider_online, exec_chat_logged, extract_json_object, record_exchange,
+    resolve_api_key,
+};
+use crate::domain::ai::{
+    AiLogEntry, AiProviderConfig, StyleTransferProposal, StyleTransferRequest, TokenStyleChange,
+};
+use crate::domain::persona::Persona;
+use crate::domain::token::{Granularity, Token};
+use crate::error::AppError;
+use crate::infrastructure::tokenizer::get_prompt_context_for_model;
+
+/// A persona together with its tokens, as loaded from the database.
+#[derive(Debug, Clone, Copy)]
+pub struct PersonaTokens<'a> {
+    /// The persona
+    pub persona: &'a Persona,
+    /// All of the persona's tokens, in display order
+    pub tokens: &'a [Token],
+}
+
+/// A single change as returned by the model, before it is matched to a token.
+#[derive(Debug, Clone, serde::Deserialize)]
+struct StyleChangeRaw {
+    index: usize,
+    content: String,
+    suggested_weight: f64,
+    #[serde(default)]
+    rationale: Option<String>,
+}
+
+/// Internal structure for parsing the AI response
+#[derive(Debug, Clone, serde::Deserialize)]
+struct StyleTransferRaw {
+    changes: Vec<StyleChangeRaw>,
+}
+
+/// Build the system prompt for style transfer
+fn build_style_transfer_system_prompt(model_name: &str, output_language: &str) -> String {
+    let output_language_section = build_output_language_section(output_language, "every rationale");
+    format!(
+        r"You are an expert prompt engineer for {model_name} image generation, specializing in restyling character prompts.
+
+Your task is to adapt an existing persona's tokens to a different visual style while preserving the character's identity.
+
+STYLE TRANSFER RULES:
+1. Keep what makes the character recognizable: hair, eyes, build, distinctive features
+2. Rewrite tokens so their vocabulary, rendering terms, and emphasis fit the target style
+3. Replace Style tokens that conflict with the target style; keep compatible ones
+4. Adjust weights where the target style needs different emphasis
+5. Negative tokens should exclude elements that would break the target style
+6. Only propose a change when it meaningfully improves the fit; omit tokens that already fit
+
+WEIGHT LIMITS: Keep weights between 0.6 and 1.5 (1.0 = normal emphasis).{output_language_section}"
+    )
+}
+
+/// Format tokens as a numbered list the model can reference by index.
+fn format_indexed_tokens(tokens: &[Token]) -> String {
+    tokens
+        .iter()
+        .enumerate()
+        .map(|(i, token)| {
+            format!(
+                "[{index}] ({granularity}, {polarity}, weight {weight}) {content}",
+                index = i + 1,
+                granularity = token.granularity_id,
+                polarity = token.polarity.as_str(),
+                weight = token.weight,
+                content = token.content,
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Build the user prompt for style transfer
+fn build_style_transfer_user_prompt(
+    source: PersonaTokens<'_>,
+    target: PersonaTokens<'_>,
+    include_source_description: bool,
+) -> String {
+    let mut sections = Vec::new();
+
+    // Section 1: Source Style
+    let style_tokens: Vec<String> = source
+        .tokens
+        .iter()
+        .filter(|token| token.granularity_id == Granularity::Style.as_str())
+        .map(|token| format!("{} ({})", token.content, token.polarity.as_str()))
+        .collect();
+    let mut style_section = format!("TARGET STYLE (from persona \"{}\"):", source.persona.name);
+    if !style_tokens.is_empty() {
+        style_section.push_str(&format!("\nStyle tokens: {}", style_tokens.join(", ")));
+    }
+    if include_source_description {
+        if let Some(desc) = source.persona.description.as_deref() {
+            if !desc.is_empty() {
+                style_section.push_str(&format!("\nStyle description:\n```\n{desc}\n```"));
+            }
+        }
+    }
+    sections.push(style_section);
+
+    // Section 2: Persona to Restyle
+    let mut persona_section = format!("PERSONA TO RESTYLE: {}", target.persona.name);
+    if let Some(desc) = target.persona.description.as_deref() {
+        if !desc.is_empty() {
+            persona_section.push_str(&format!("\nCharacter Description:\n```\n{desc}\n```"));
+        }
+    }
+    persona_section.push_str(&format!(
+        "\n\nCurrent tokens:\n{}",
+        format_indexed_tokens(target.tokens)
+    ));
+    sections.push(persona_section);
+
+    // Section 3: Custom AI Instructions
+    if let Some(instructions) = target.persona.ai_instructions.as_deref() {
+        if !instructions.is_empty() {
+            sections.push(format!(
+                "CUSTOM INSTRUCTIONS (from persona configuration):\n```\n{instructions}\n```"
+            ));
+        }
+    }
+
+    // Section 4: Expected Output Format
+    sections.push(
+        r#"EXPECTED OUTPUT:
+Respond with a JSON object containing a "changes" array. Each change has:
+- "index" (integer, required): The [number] of the token to change
+- "content" (string, required): The restyled token text
+- "suggested_weight" (number, required): The restyled weight
+- "rationale" (string, optional): Brief explanation of the change
+
+Propose at most one change per token. Omit tokens that need no change."#
+            .to_string(),
+    );
+
+    sections.join("\n\n")
+}
+
+/// Build the JSON schema for the style transfer response
+fn build_style_transfer_json_schema(token_count: usize) -> serde_json::Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "changes": {
+                "type": "array",
+                "maxItems": token_count,
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "index": { "type": "integer", "minimum": 1, "maximum": token_count },
+                        "content": { "type": "string" },
+                        "suggested_weight": { "type": "number" },
+                        "rationale": { "type": "string" }
+                    },
+                    "required": ["index", "content", "suggested_weight"]
+                }
+            }
+        },
+        "required": ["changes"]
+    })
+}
+
+/// Parse the AI response into raw changes
+fn parse_style_transfer_response(content: &str) -> Result<Vec<StyleChangeRaw>, AppError> {
+    let json_str = extract_json_object(content);
+
+    let parsed: StyleTransferRaw = serde_json::from_str(json_str).map_err(|e| {
+        AppError::Internal(format!(
+            "Failed to parse AI response: {e}. Response was: {content}"
+        ))
+    })?;
+
+    Ok(parsed.changes)
+}
+
+/// Match raw changes back to the target's tokens.
+///
+/// Out-of-range indices, repeated indices (first wins), empty content, and
+/// changes identical to the current token are dropped. Results follow the
+/// target's token order.
+fn resolve_changes(raw: Vec<StyleChangeRaw>, tokens: &[Token]) -> Vec<TokenStyleChange> {
+    let mut by_index: Vec<Option<StyleChangeRaw>> = vec![None; tokens.len()];
+    for change in raw {
+        if let Some(slot) = change
+            .index
+            .checked_sub(1)
+            .and_then(|i| by_index.get_mut(i))
+        {
+            if slot.is_none() {
+                *slot = Some(change);
+            }
+        }
+    }
+
+    tokens
+        .iter()
+        .zip(by_index)
+        .filter_map(|(token, change)| {
+            let change = change?;
+            let content = change.content.trim();
+            let unchanged = content == token.content
+                && (change.suggested_weight - token.weight).abs() < f64::EPSILON;
+            if content.is_empty() || unchanged {
+                return None;
+            }
+
+            Some(TokenStyleChange {
+                token_id: token.id.clone(),
+                granularity_id: token.granularity_id.clone(),
+                polarity: token.polarity,
+                original_content: token.content.clone(),
+                original_weight: token.weight,
+                proposed_content: content.to_string(),
+                proposed_weight: change.suggested_weight,
+                rationale: change.rationale,
+            })
+        })
+        .collect()
+}
+
+/// Propose changes adapting the target persona's tokens to the source's style.
+///
+/// `image_model_id` is the target persona's image model, used to phrase the
+/// prompt for that model family. Rationales are written in `output_language`.
+///
+/// # Errors
+///
+/// Returns `AppError::Validation` if the source has no Style tokens (and no
+/// description is included) or the target has no tokens, and
+/// `AppError::Internal` if the AI request or response parsing fails.
+#[tracing::instrument(skip_all, fields(provider = ?config.provider, model = %config.model), err)]
+pub async fn transfer_style(
+    config: &AiProviderConfig,
+    request: &StyleTransferRequest,
+    source: PersonaTokens<'_>,
+    target: PersonaTokens<'_>,
+    image_model_id: &str,
+    output_language: &str,
+    log: &AiRequestLog,
+) -> Result<StyleTransferProposal, AppError> {
+    let has_style_tokens = source
+        .tokens
+        .iter()
+        .any(|token| token.granularity_id == Granularity::Style.as_str());
+    let has_description = request.include_source_description
+        && source
+            .persona
+            .description
+            .as_deref()
+            .is_some_and(|d| !d.is_empty());
+    if !has_style_tokens && !has_description {
+        return Err(AppError::Validation(format!(
+            "Persona '{}' has no Style tokens to transfer",
+            source.persona.name
+        )));
+    }
+    if target.tokens.is_empty() {
+        return Err(AppError::Validation(format!(
+            "Persona '{}' has no tokens to restyle",
+            target.persona.name
+        )));
+    }
+
+    ensure_provider_online(config)?;
+    let api_key = resolve_api_key(config)?;
+    let client = build_client(api_key.clone());
+    let prompt_context = get_prompt_context_for_model(Some(image_model_id));
+
+    let system_prompt =
+        build_style_transfer_system_prompt(&prompt_context.display_name, output_language);
+    let user_prompt =
+        build_style_transfer_user_prompt(source, target, request.include_source_description);
+    let mut log_entry = AiLogEntry::new(config, "style_transfer", &system_prompt, &user_prompt);
+
+    let chat_request = ChatRequest::default()
+        .with_system(system_prompt)
+        .append_message(ChatMessage::user(user_prompt));
+    let chat_options = ChatOptions::default().with_response_format(JsonSpec::new(
+        "style_transfer",
+        build_style_transfer_json_schema(target.tokens.len()),
+    ));
+
+    let parsed = exec_chat_logged(
+        &client,
+        &build_genai_model_identifier(config),
+        chat_request,
+        &chat_options,
+        "AI style transfer failed",
+        &mut log_entry,
+    )
+    .await
+    .and_then(|content| parse_style_transfer_response(&content));
+    record_exchange(
+        log,
+        &mut log_entry,
+        parsed.as_ref().err(),
+        api_key.as_deref(),
+    );
+
+    Ok(StyleTransferProposal {
+        source_persona_id: source.persona.id.clone(),
+        target_persona_id: target.persona.id.clone(),
+        changes: resolve_changes(parsed?, target.tokens),
+        provider: config.provider,
+        model: config.model.clone(),
+    })
+}
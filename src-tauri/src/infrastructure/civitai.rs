@@ -0,0 +1,196 @@
+//! Civitai Client
+//!
+//! Looks up checkpoints and `LoRA` models on the public Civitai API, by file
+//! hash or by name. No API key is needed for these endpoints.
+//!
+//! Requests are blocking; call them from `tokio::task::spawn_blocking`. They
+//! honor the proxy settings, which are applied through the standard proxy
+//! environment variables (see `infrastructure::proxy`), and fail fast in
+//! offline mode.
+
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use super::offline;
+use crate::domain::civitai::{normalize_model_hash, CivitaiModel};
+use crate::error::AppError;
+
+/// Base URL of the Civitai REST API.
+const CIVITAI_API_URL: &str = "https://civitai.com/api/v1";
+
+/// Time limit of a whole request, including reading the response.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Model types searched by name: the ones that carry a base model and
+/// trigger words worth importing.
+const SEARCHED_MODEL_TYPES: &[&str] = &["Checkpoint", "LORA", "LoCon", "DoRA"];
+
+/// A model version as returned by `/model-versions/by-hash`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct VersionResponse {
+    id: u64,
+    model_id: u64,
+    name: String,
+    #[serde(default)]
+    base_model: String,
+    #[serde(default)]
+    trained_words: Vec<String>,
+    model: VersionModel,
+}
+
+/// The model a version belongs to.
+#[derive(Debug, Deserialize)]
+struct VersionModel {
+    name: String,
+    #[serde(rename = "type")]
+    model_type: String,
+}
+
+/// A page of results from `/models`.
+#[derive(Debug, Deserialize)]
+struct SearchResponse {
+    #[serde(default)]
+    items: Vec<SearchItem>,
+}
+
+/// A model in the search results, with its versions newest first.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SearchItem {
+    id: u64,
+    name: String,
+    #[serde(rename = "type")]
+    model_type: String,
+    #[serde(default)]
+    model_versions: Vec<SearchVersion>,
+}
+
+/// A version of a model in the search results.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SearchVersion {
+    id: u64,
+    name: String,
+    #[serde(default)]
+    base_model: String,
+    #[serde(default)]
+    trained_words: Vec<String>,
+}
+
+/// Looks up the model version a file hash belongs to.
+///
+/// # Arguments
+///
+/// * `hash` - SHA-256, BLAKE3, `AutoV1`, or `AutoV2` hash of the model file
+///
+/// # Returns
+///
+/// The matching version, or `None` if Civitai doesn't know the hash.
+///
+/// # Errors
+///
+/// Returns `AppError::Validation` for a malformed hash, `AppError::Offline` in
+/// offline mode, and `AppError::Internal` if the request fails.
+pub fn lookup_by_hash(hash: &str) -> Result<Option<CivitaiModel>, AppError> {
+    let hash = normalize_model_hash(hash)?;
+    offline::ensure_online("Looking up model on Civitai")?;
+
+    let response = match agent()
+        .get(&format!("{CIVITAI_API_URL}/model-versions/by-hash/{hash}"))
+        .call()
+    {
+        Ok(response) => response,
+        Err(ureq::Error::Status(404, _)) => return Ok(None),
+        Err(e) => return Err(request_error(e)),
+    };
+
+    let version: VersionResponse = response
+        .into_json()
+        .map_err(|e| AppError::Internal(format!("Invalid Civitai response: {e}")))?;
+
+    Ok(Some(CivitaiModel::new(
+        version.model_id,
+        version.id,
+        version.model.name,
+        version.name,
+        version.model.model_type,
+        version.base_model,
+        &version.trained_words,
+    )))
+}
+
+/// Searches checkpoints and `LoRA` models by name.
+///
+/// Each matching model is returned with its latest version.
+///
+/// # Arguments
+///
+/// * `query` - Name or part of a name
+/// * `limit` - Maximum number of models to return
+///
+/// # Errors
+///
+/// Returns `AppError::Validation` for an empty query, `AppError::Offline` in
+/// offline mode, and `AppError::Internal` if the request fails.
+pub fn search_by_name(query: &str, limit: u32) -> Result<Vec<CivitaiModel>, AppError> {
+    let query = query.trim();
+    if query.is_empty() {
+        return Err(AppError::Validation(
+            "Model name cannot be empty".to_string(),
+        ));
+    }
+    offline::ensure_online("Searching models on Civitai")?;
+
+    let request = SEARCHED_MODEL_TYPES.iter().fold(
+        agent()
+            .get(&format!("{CIVITAI_API_URL}/models"))
+            .query("query", query)
+            .query("limit", &limit.to_string()),
+        |request, model_type| request.query("types", model_type),
+    );
+
+    let response: SearchResponse = request
+        .call()
+        .map_err(request_error)?
+        .into_json()
+        .map_err(|e| AppError::Internal(format!("Invalid Civitai response: {e}")))?;
+
+    Ok(response
+        .items
+        .into_iter()
+        .filter_map(|item| {
+            let version = item.model_versions.into_iter().next()?;
+            Some(CivitaiModel::new(
+                item.id,
+                version.id,
+                item.name,
+                version.name,
+                item.model_type,
+                version.base_model,
+                &version.trained_words,
+            ))
+        })
+        .collect())
+}
+
+/// HTTP agent with the request timeout, using the proxy from the environment.
+fn agent() -> ureq::Agent {
+    ureq::AgentBuilder::new()
+        .timeout(REQUEST_TIMEOUT)
+        .try_proxy_from_env(true)
+        .build()
+}
+
+/// Converts a failed request into an `AppError`.
+fn request_error(error: ureq::Error) -> AppError {
+    match error {
+        ureq::Error::Status(status, _) => {
+            AppError::Internal(format!("Civitai request failed with status {status}"))
+        }
+        ureq::Error::Transport(transport) => {
+            AppError::Internal(format!("Failed to reach Civitai: {transport}"))
+        }
+    }
+}
@@ -6,18 +6,42 @@
 //!
 //! # Initialization Sequence
 //!
-//! 1. Open or create the database file
+//! 1. Open or create the database file, keyed with its passphrase if encrypted
 //! 2. Enable foreign key constraint enforcement
 //! 3. Enable WAL (Write-Ahead Logging) mode
-//! 4. Run pending schema migrations
+//! 4. Back up the database if migrations are pending
+//! 5. Run pending schema migrations
+//!
+//! # Encryption
+//!
+//! `SQLite` is built with `SQLCipher`. A database opened without a passphrase
+//! stays a plain `SQLite` file; [`Database::rekey`] rewrites it encrypted with a
+//! passphrase, with another passphrase, or back to plaintext.
+//!
+//! Backups in the `backups` directory are taken with `VACUUM INTO`, which
+//! keys the copy like the database. Re-keying the database rewrites the
+//! backups with the new passphrase too, so they can still be restored and no
+//! plaintext copy outlives encryption.
 
-use rusqlite::Connection;
-use std::path::Path;
+use chrono::Utc;
+use rusqlite::{params, Connection, ErrorCode};
+use std::fs::{File, OpenOptions};
+use std::io::{ErrorKind, Read, Write};
+use std::path::{Path, PathBuf};
 
 use crate::error::AppError;
 
 use super::migrations;
 
+/// Directory, next to the database file, holding backups taken before migrations.
+pub(crate) const MIGRATION_BACKUP_DIR: &str = "backups";
+
+/// Header every plaintext `SQLite` file starts with; encrypted files have none.
+const SQLITE_HEADER: [u8; 16] = *b"SQLite format 3\0";
+
+/// Extension of the staging file written while the database is re-keyed.
+const REKEY_STAGING_EXTENSION: &str = "db-rekey";
+
 /// Wrapper around an `SQLite` connection with application-specific configuration.
 ///
 /// This struct owns the database connection and provides access to repositories
@@ -34,25 +58,49 @@ impl Database {
     ///
     /// Automatically creates the database file if it doesn't exist,
     /// applies any pending migrations, and configures the connection
-    /// for optimal performance.
+    /// for optimal performance. An existing database is first copied to the
+    /// `backups` directory next to it when migrations are pending, so a failed
+    /// upgrade can be recovered.
     ///
     /// # Arguments
     ///
     /// * `path` - File system path for the database file
+    /// * `passphrase` - Passphrase of an encrypted database, or `None` for plaintext
     ///
     /// # Errors
     ///
-    /// Returns `AppError::Database` if the connection fails or migrations error.
-    pub fn new(path: &Path) -> Result<Self, AppError> {
+    /// Returns `AppError::Encryption` if the passphrase is wrong or missing, or
+    /// encryption is not supported by this build.
+    /// Returns `AppError::SchemaTooNew` if the database was written by a newer
+    /// version of the app; it is left untouched.
+    /// Returns `AppError::Database` if the connection, backup, or migrations fail.
+    /// Returns `AppError::Io` if the backup directory cannot be created.
+    pub fn new(path: &Path, passphrase: Option<&str>) -> Result<Self, AppError> {
         let conn = Connection::open(path)?;
 
+        if let Some(passphrase) = passphrase {
+            Self::apply_passphrase(&conn, passphrase)?;
+        }
+        Self::check_readable(&conn, passphrase.is_some())?;
+
+        // Refuse schemas this build cannot read before changing anything
+        if let Some(version) = migrations::read_schema_version(&conn)? {
+            if version > migrations::SCHEMA_VERSION {
+                return Err(AppError::SchemaTooNew {
+                    found: version,
+                    supported: migrations::SCHEMA_VERSION,
+                });
+            }
+        }
+
         // Enable foreign key constraints for referential integrity
         conn.execute_batch("PRAGMA foreign_keys = ON;")?;
 
         // Enable WAL mode for better concurrent access and crash resilience
         conn.execute_batch("PRAGMA journal_mode = WAL;")?;
 
-        migrations::run_migrations(&conn)?;
+        let backup_path = Self::backup_before_migration(&conn, path)?;
+        migrations::run_migrations(&conn, backup_path.as_deref())?;
 
         Ok(Self { conn })
     }
@@ -67,11 +115,256 @@ impl Database {
 
         conn.execute_batch("PRAGMA foreign_keys = ON;")?;
 
-        migrations::run_migrations(&conn)?;
+        migrations::run_migrations(&conn, None)?;
 
         Ok(Self { conn })
     }
 
+    /// Copies an existing database into the `backups` directory next to it if
+    /// migrations are pending.
+    ///
+    /// Returns the backup path, or `None` if the database is new or current.
+    fn backup_before_migration(
+        conn: &Connection,
+        path: &Path,
+    ) -> Result<Option<PathBuf>, AppError> {
+        let Some(version) = migrations::read_schema_version(conn)? else {
+            return Ok(None);
+        };
+        if version >= migrations::SCHEMA_VERSION {
+            return Ok(None);
+        }
+
+        let dir = path
+            .parent()
+            .unwrap_or_else(|| Path::new("."))
+            .join(MIGRATION_BACKUP_DIR);
+        std::fs::create_dir_all(&dir)?;
+        let backup_path = dir.join(format!(
+            "ppm-v{version}-{}.db",
+            Utc::now().format("%Y%m%dT%H%M%SZ")
+        ));
+
+        // VACUUM INTO writes a consistent copy, including changes still in the WAL
+        conn.execute("VACUUM INTO ?1", [backup_path.to_string_lossy()])?;
+        tracing::info!(
+            path = %backup_path.display(),
+            from = version,
+            "Backed up database before migrating"
+        );
+
+        Ok(Some(backup_path))
+    }
+
+    /// Returns whether this build can encrypt databases.
+    pub fn encryption_available(&self) -> bool {
+        Self::cipher_available(&self.conn)
+    }
+
+    /// Returns whether the file at `path` is an encrypted database.
+    ///
+    /// Any existing, non-empty file that lacks the plaintext `SQLite` header is
+    /// considered encrypted; a missing or empty file is a new database.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Io` if the file exists but cannot be read.
+    pub fn is_encrypted_file(path: &Path) -> Result<bool, AppError> {
+        let mut file = match File::open(path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(false),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut header = [0u8; 16];
+        match file.read_exact(&mut header) {
+            Ok(()) => Ok(header != SQLITE_HEADER),
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Rewrites the database with a new passphrase, or as plaintext.
+    ///
+    /// The data is copied with `sqlcipher_export` into a staging file that then
+    /// replaces the database, so the original is untouched if the copy fails.
+    /// The connection is reopened on the rewritten file, and the backups next
+    /// to it are rewritten the same way (see [`Self::rekey_backups`]).
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - File system path of this database
+    /// * `current` - Passphrase the database is currently keyed with, if any
+    /// * `new` - Passphrase to encrypt with, or `None` for plaintext
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Encryption` if encryption is not supported by this build.
+    /// Returns `AppError::Database` or `AppError::Io` if the copy or the file swap
+    /// fails; the connection stays on the original database.
+    #[tracing::instrument(level = "debug", skip_all, fields(encrypt = new.is_some()))]
+    pub fn rekey(
+        &mut self,
+        path: &Path,
+        current: Option<&str>,
+        new: Option<&str>,
+    ) -> Result<(), AppError> {
+        if !self.encryption_available() {
+            return Err(AppError::Encryption(
+                "This build does not support encrypted databases".to_string(),
+            ));
+        }
+
+        let staging = path.with_extension(REKEY_STAGING_EXTENSION);
+        Self::export_rekeyed(&self.conn, &staging, new)?;
+
+        // Close the connection so the file can be replaced, then drop its WAL
+        self.conn = Connection::open_in_memory()?;
+        let _ = std::fs::remove_file(path.with_extension("db-wal"));
+        let _ = std::fs::remove_file(path.with_extension("db-shm"));
+
+        if let Err(e) = std::fs::rename(&staging, path) {
+            *self = Self::new(path, current)?;
+            return Err(e.into());
+        }
+
+        *self = Self::new(path, new)?;
+        Self::rekey_backups(path, current, new);
+        Ok(())
+    }
+
+    /// Copies the database behind `conn` into a new file at `target`, keyed
+    /// with `new` or in plaintext.
+    ///
+    /// Any previous file at `target` is replaced; a partial copy is removed.
+    fn export_rekeyed(conn: &Connection, target: &Path, new: Option<&str>) -> Result<(), AppError> {
+        let _ = std::fs::remove_file(target);
+
+        // An empty key attaches the target as plaintext
+        conn.execute(
+            "ATTACH DATABASE ?1 AS rekeyed KEY ?2",
+            params![target.to_string_lossy(), new.unwrap_or_default()],
+        )?;
+        let exported = conn.query_row("SELECT sqlcipher_export('rekeyed')", [], |_| Ok(()));
+        conn.execute_batch("DETACH DATABASE rekeyed;")?;
+        if let Err(e) = exported {
+            let _ = std::fs::remove_file(target);
+            return Err(e.into());
+        }
+        Ok(())
+    }
+
+    /// Rewrites the migration and update backups next to the database with the
+    /// passphrase it was re-keyed to.
+    ///
+    /// The database is already re-keyed, so failures are logged rather than
+    /// returned. A plaintext backup that cannot be encrypted is overwritten and
+    /// deleted instead; a backup keyed with an unknown passphrase is left as is.
+    fn rekey_backups(path: &Path, current: Option<&str>, new: Option<&str>) {
+        let dir = path
+            .parent()
+            .unwrap_or_else(|| Path::new("."))
+            .join(MIGRATION_BACKUP_DIR);
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            return;
+        };
+
+        for backup in entries.filter_map(Result::ok).map(|entry| entry.path()) {
+            if backup.extension().and_then(|ext| ext.to_str()) != Some("db") {
+                continue;
+            }
+
+            let plaintext = !Self::is_encrypted_file(&backup).unwrap_or(true);
+            if plaintext && new.is_none() {
+                continue;
+            }
+            let key = if plaintext { None } else { current };
+            let Err(e) = Self::rekey_backup(&backup, key, new) else {
+                continue;
+            };
+            if !plaintext {
+                tracing::warn!(path = %backup.display(), error = %e, "Failed to re-key backup");
+                continue;
+            }
+
+            tracing::warn!(
+                path = %backup.display(),
+                error = %e,
+                "Failed to encrypt backup; deleting it"
+            );
+            if let Err(e) = shred_file(&backup) {
+                tracing::error!(
+                    path = %backup.display(),
+                    error = %e,
+                    "Failed to delete plaintext backup"
+                );
+            }
+        }
+    }
+
+    /// Rewrites one backup file, keyed with `current`, with the `new` key.
+    fn rekey_backup(
+        backup: &Path,
+        current: Option<&str>,
+        new: Option<&str>,
+    ) -> Result<(), AppError> {
+        let staging = backup.with_extension(REKEY_STAGING_EXTENSION);
+        {
+            let conn = Connection::open(backup)?;
+            if let Some(current) = current {
+                Self::apply_passphrase(&conn, current)?;
+            }
+            Self::check_readable(&conn, current.is_some())?;
+            Self::export_rekeyed(&conn, &staging, new)?;
+        }
+
+        if let Err(e) = std::fs::rename(&staging, backup) {
+            let _ = std::fs::remove_file(&staging);
+            return Err(e.into());
+        }
+        Ok(())
+    }
+
+    /// Keys the connection so `SQLCipher` decrypts the file, or encrypts a new one.
+    fn apply_passphrase(conn: &Connection, passphrase: &str) -> Result<(), AppError> {
+        if !Self::cipher_available(conn) {
+            return Err(AppError::Encryption(
+                "This build does not support encrypted databases".to_string(),
+            ));
+        }
+
+        conn.pragma_update(None, "key", passphrase)?;
+        Ok(())
+    }
+
+    /// Fails with `Encryption` if the file cannot be read with the key in use.
+    ///
+    /// `SQLCipher` only checks the key on first access, and reports a wrong or
+    /// missing one as "file is not a database".
+    fn check_readable(conn: &Connection, keyed: bool) -> Result<(), AppError> {
+        match conn.query_row("SELECT COUNT(*) FROM sqlite_master", [], |row| {
+            row.get::<_, i64>(0)
+        }) {
+            Ok(_) => Ok(()),
+            Err(rusqlite::Error::SqliteFailure(error, _))
+                if error.code == ErrorCode::NotADatabase =>
+            {
+                Err(AppError::Encryption(if keyed {
+                    "Incorrect database passphrase".to_string()
+                } else {
+                    "The database is encrypted and no passphrase is available".to_string()
+                }))
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Returns whether `SQLite` was built with `SQLCipher`.
+    fn cipher_available(conn: &Connection) -> bool {
+        conn.query_row("PRAGMA cipher_version", [], |row| row.get::<_, String>(0))
+            .is_ok()
+    }
+
     /// Returns a reference to the underlying `SQLite` connection.
     ///
     /// Use this to pass the connection to repository methods.
@@ -79,3 +372,94 @@ impl Database {
         &self.conn
     }
 }
+
+/// Overwrites a file with zeros before deleting it, so its plaintext is not
+/// left in the freed blocks.
+///
+/// Best effort: copy-on-write filesystems and SSD wear leveling may keep the
+/// old blocks regardless.
+fn shred_file(path: &Path) -> std::io::Result<()> {
+    let len = std::fs::metadata(path)?.len();
+    {
+        let mut file = OpenOptions::new().write(true).open(path)?;
+        let zeros = [0u8; 8192];
+        let mut remaining = len;
+        while remaining > 0 {
+            let chunk = remaining.min(zeros.len() as u64) as usize;
+            file.write_all(&zeros[..chunk])?;
+            remaining -= chunk as u64;
+        }
+        file.sync_all()?;
+    }
+    std::fs::remove_file(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh directory for a database and its backups.
+    fn scratch_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("ppm-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join(MIGRATION_BACKUP_DIR)).unwrap();
+        dir
+    }
+
+    fn backup_files(dir: &Path) -> Vec<PathBuf> {
+        let mut files: Vec<PathBuf> = std::fs::read_dir(dir.join(MIGRATION_BACKUP_DIR))
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        files.sort();
+        files
+    }
+
+    #[test]
+    fn rekey_leaves_no_plaintext_backup() {
+        let dir = scratch_dir();
+        let path = dir.join("ppm.db");
+        let mut db = Database::new(&path, None).unwrap();
+        for name in [
+            "ppm-v30-20260101T000000Z.db",
+            "ppm-pre-update-1.0.0-20260101T000000Z.db",
+        ] {
+            let backup = dir.join(MIGRATION_BACKUP_DIR).join(name);
+            db.conn
+                .execute("VACUUM INTO ?1", [backup.to_string_lossy()])
+                .unwrap();
+            assert!(!Database::is_encrypted_file(&backup).unwrap());
+        }
+
+        db.rekey(&path, None, Some("correct horse")).unwrap();
+
+        let backups = backup_files(&dir);
+        assert_eq!(backups.len(), 2);
+        for backup in backups {
+            assert!(Database::is_encrypted_file(&backup).unwrap());
+            let conn = Connection::open(&backup).unwrap();
+            Database::apply_passphrase(&conn, "correct horse").unwrap();
+            Database::check_readable(&conn, true).unwrap();
+        }
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn migration_backup_of_an_encrypted_database_uses_its_key() {
+        let dir = scratch_dir();
+        let path = dir.join("ppm.db");
+        let db = Database::new(&path, Some("correct horse")).unwrap();
+        db.conn
+            .execute("UPDATE schema_version SET version = 30", [])
+            .unwrap();
+
+        let backup = Database::backup_before_migration(&db.conn, &path)
+            .unwrap()
+            .unwrap();
+
+        assert!(Database::is_encrypted_file(&backup).unwrap());
+        let conn = Connection::open(&backup).unwrap();
+        Database::apply_passphrase(&conn, "correct horse").unwrap();
+        Database::check_readable(&conn, true).unwrap();
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
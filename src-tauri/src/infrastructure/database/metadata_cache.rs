@@ -0,0 +1,83 @@
+//! Metadata Cache
+//!
+//! Keeps read-mostly library metadata in memory, so hot read paths such as
+//! prompt composition do not query it on every call and always serve the
+//! same values for unchanged data. Currently caches the granularity levels.
+//!
+//! # Invalidation
+//!
+//! The cache is filled on first read. Commands that change cached tables, or
+//! replace the database file, call [`MetadataCache::invalidate`] after their
+//! writes commit; the next read reloads from the database.
+//!
+//! # Usage
+//!
+//! ```rust,ignore
+//! let levels = state.metadata.granularity_levels(conn)?;
+//! // After creating a level
+//! state.metadata.invalidate();
+//! ```
+
+use std::sync::RwLock;
+
+use rusqlite::Connection;
+
+use crate::domain::token::GranularityLevel;
+use crate::error::AppError;
+
+use super::repositories::GranularityRepository;
+
+/// In-memory cache of library metadata, held in the application state.
+#[derive(Debug, Default)]
+pub struct MetadataCache {
+    /// Granularity levels in display order, or `None` until first read
+    granularity_levels: RwLock<Option<Vec<GranularityLevel>>>,
+}
+
+impl MetadataCache {
+    /// Returns all granularity levels, loading them on a cache miss.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference, used on a cache miss
+    ///
+    /// # Returns
+    ///
+    /// All levels ordered by `display_order`, as returned by
+    /// [`GranularityRepository::find_all`].
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Database` if loading the levels fails.
+    /// Returns `AppError::Internal` if the cache lock is poisoned.
+    pub fn granularity_levels(&self, conn: &Connection) -> Result<Vec<GranularityLevel>, AppError> {
+        {
+            let cache = self.granularity_levels.read().map_err(|_| {
+                AppError::Internal("Failed to acquire metadata cache read lock".to_string())
+            })?;
+            if let Some(levels) = cache.as_ref() {
+                return Ok(levels.clone());
+            }
+        }
+
+        let levels = GranularityRepository::find_all(conn)?;
+        let mut cache = self.granularity_levels.write().map_err(|_| {
+            AppError::Internal("Failed to acquire metadata cache write lock".to_string())
+        })?;
+        *cache = Some(levels.clone());
+
+        Ok(levels)
+    }
+
+    /// Clears the cache, so the next read reloads from the database.
+    ///
+    /// A poisoned lock is cleared as well, since the cache holds no state
+    /// that must survive a panic.
+    pub fn invalidate(&self) {
+        let mut cache = self
+            .granularity_levels
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        *cache = None;
+    }
+}
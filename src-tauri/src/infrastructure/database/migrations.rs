@@ -9,33 +9,390 @@
 //! The application uses a simple version-based migration system:
 //! 1. Check current schema version from `schema_version` table
 //! 2. Run any migrations newer than the current version
-//! 3. Update the version number on successful completion
+//! 3. Update the version number after each successful migration
 //!
-//! # Current Schema (v2)
+//! Each migration and its version update run in one transaction, so a failed
+//! migration leaves no half-applied schema behind and the next start retries
+//! it from scratch.
+//!
+//! Every migration run, successful or not, is recorded in `migration_history`
+//! with its duration and the backup taken before the upgrade (see
+//! [`Database::new`](super::Database::new)).
+//!
+//! # Current Schema (v32)
 //!
 //! ## Tables
 //!
 //! - **personas**: Core persona entities with name, description, tags, and AI config
 //! - **`generation_params`**: Image generation settings (1:1 relationship via FK)
 //! - **tokens**: Prompt tokens with granularity, polarity, weights, and global ordering
+//! - **`granularity_levels`**: Granularity level names, colors, and display order
+//! - **`prompt_cache`**: Last composed prompt per persona (1:1 relationship via FK)
+//! - **`token_count_cache`**: Token counts keyed by text hash and tokenizer
+//! - **`app_settings`**: Backend settings (e.g., network proxy) as JSON values by key
+//! - **`smart_collections`**: Named persona queries, stored as JSON
+//! - **`persona_activity`**: Latest opened, modified, and composed time per persona
+//! - **`search_index`**: FTS5 index of persona, token, and smart collection text
+//! - **`search_entries`**: Indexed entity of each `search_index` row, keyed by its rowid
+//! - **`token_revisions`**: Previous content and weight of edited tokens
+//! - **`composition_defaults`**: Per-persona composition settings (1:1 relationship via FK)
+//! - **`banned_terms`**: User-managed terms that tokens must not contain
+//! - **`token_aliases`**: User-maintained token variants and the phrasing they stand for
+//! - **`feature_usage`**: Opt-in feature usage counters (see `domain::telemetry`)
+//! - **`generated_images`**: Result gallery of image paths per persona, with prompt hash, seed,
+//!   and parameters
+//! - **`scheduled_tasks`**: Recurring background tasks with their schedule and AI config
+//! - **`pending_suggestions`**: Inbox of AI-suggested tokens awaiting review, with their source
+//! - **`persona_wizards`**: Drafts of personas being created step by step
+//! - **`drafts`**: Autosaved incomplete personas of the create form
+//! - **`migration_history`**: Migration runs with timing, backup path, and error (bookkeeping,
+//!   like `schema_version`)
 //!
 //! ## v2 Changes
 //!
 //! - Token `display_order` is now global per persona (not per granularity/polarity group)
 //! - Index changed from `(persona_id, granularity_id, polarity, display_order)` to `(persona_id, display_order)`
 //!
+//! ## v3 Changes
+//!
+//! - Personas store a `content_hash` (description + tokens) used for duplicate detection on import
+//!
+//! ## v4 Changes
+//!
+//! - Granularity levels are persisted in `granularity_levels`, seeded with the built-in levels,
+//!   so their color and order are stored data rather than hardcoded
+//!
+//! ## v5 Changes
+//!
+//! - `prompt_cache` stores the last composed prompt per persona for list previews;
+//!   rows are deleted whenever the persona's tokens or generation params change
+//!
+//! ## v6 Changes
+//!
+//! - `token_count_cache` stores exact token counts by (text hash, tokenizer) so counts
+//!   survive restarts and do not require loading the tokenizer again
+//!
+//! ## v7 Changes
+//!
+//! - `app_settings` stores backend settings that must apply before the frontend loads
+//!
+//! ## v8 Changes
+//!
+//! - Personas have an `archived` flag, so old personas can be set aside without deleting them
+//!
+//! ## v9 Changes
+//!
+//! - `smart_collections` stores saved persona queries; members are computed on demand
+//!
+//! ## v10 Changes
+//!
+//! - `persona_activity` records when each persona was last opened, modified, and composed,
+//!   seeded from `updated_at` and cached prompts
+//!
+//! ## v11 Changes
+//!
+//! - `search_index` is a full-text index for quick search, kept in sync by triggers on
+//!   `personas`, `tokens`, and `smart_collections`
+//!
+//! ## v12 Changes
+//!
+//! - `token_revisions` keeps the previous content and weight of each token edit, so
+//!   edits can be reverted
+//!
+//! ## v13 Changes
+//!
+//! - `generation_params` stores an optional output `width` and `height`
+//!
+//! ## v14 Changes
+//!
+//! - `composition_defaults` stores a persona's separator, weight toggle, and weight
+//!   syntax, used when a prompt is composed without explicit options
+//!
+//! ## v15 Changes
+//!
+//! - `personas` and `smart_collections` store an optional label `color` (`#rrggbb`)
+//!
+//! ## v16 Changes
+//!
+//! - `personas` store a `content_rating` (`general` or `mature`) used by safe mode
+//!
+//! ## v17 Changes
+//!
+//! - `banned_terms` holds the user's blacklist, unique ignoring case
+//!
+//! ## v18 Changes
+//!
+//! - `personas` can name a `base_persona_id` (set to NULL when the base is deleted), with the
+//!   `inherited_granularities` and `param_overrides` of the variant as JSON arrays
+//!
+//! ## v19 Changes
+//!
+//! - `feature_usage` counts uses per feature, with the first and last day of use, while
+//!   telemetry is enabled
+//!
+//! ## v20 Changes
+//!
+//! - `personas` store an optional `ai_output_language` for AI-written descriptions and rationales
+//!
+//! ## v21 Changes
+//!
+//! - `generated_images` links image files to the persona, prompt hash, seed, and parameters
+//!   that produced them, with a `starred` flag for favorites
+//!
+//! ## v22 Changes
+//!
+//! - `generated_images` keep the exact prompts and a JSON snapshot of the persona at attach
+//!   time, for reproduction bundles
+//!
+//! ## v23 Changes
+//!
+//! - `composition_defaults` store optional `dual_prompt` settings (JSON) for composing a
+//!   separate `prompt_2` for the second text encoder
+//!
+//! ## v24 Changes
+//!
+//! - `generation_params` store `ControlNet` and IP-Adapter `conditioning` entries as a JSON
+//!   array
+//!
+//! ## v25 Changes
+//!
+//! - `generated_images` cache a `face_embedding` (JSON array, empty when no face was found)
+//!
+//! ## v26 Changes
+//!
+//! - `scheduled_tasks` stores recurring background tasks (e.g., weekly style refresh)
+//! - `pending_suggestions` stages the tokens they suggest, unique per persona, granularity,
+//!   polarity, and content (ignoring case), until accepted or dismissed
+//!
+//! ## v27 Changes
+//!
+//! - `pending_suggestions` records the `source` of each suggestion (scheduled refresh or
+//!   token suggestions), as it now collects every AI token suggestion for review
+//!
+//! ## v28 Changes
+//!
+//! - `persona_wizards` stores persona creation wizard drafts, with the tokens and submitted
+//!   steps as JSON, until the persona is created or the draft discarded
+//!
+//! ## v29 Changes
+//!
+//! - `drafts` stores autosaved incomplete personas, with tags and tokens as JSON; names are
+//!   neither validated nor unique until the draft is promoted
+//!
+//! ## v30 Changes
+//!
+//! - `tokens` derive a `content_key` (trimmed, lowercase content), indexed for prefix
+//!   autocompletion
+//!
+//! ## v31 Changes
+//!
+//! - `token_aliases` maps token variants to their preferred phrasing, unique per alias
+//!   ignoring case
+//!
+//! ## v32 Changes
+//!
+//! - `search_index` rows are keyed by the rowid of a `search_entries` row naming the entity,
+//!   so the sync triggers update and delete index rows by rowid instead of scanning the
+//!   index's unindexed columns
+//!
 //! ## Constraints
 //!
 //! - Persona names must be unique
 //! - Tokens have a composite unique constraint (`persona_id`, `granularity_id`, polarity, content)
-//! - Foreign keys cascade deletes from personas to params and tokens
+//! - Foreign keys cascade deletes from personas to params, composition defaults, tokens,
+//!   cached prompts, activity, generated images, and pending suggestions, and from tokens to
+//!   their revisions; deleting a scheduled task keeps its suggestions
 
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
 use rusqlite::{params, Connection};
 
+use crate::domain::diagnostics::MigrationRecord;
+use crate::domain::persona::compute_content_hash;
+use crate::domain::token::GranularityLevel;
 use crate::error::AppError;
 
+use super::repositories::TokenRepository;
+use super::unit_of_work::unit_of_work;
+
 /// Current schema version. Increment when adding new migrations.
-pub const SCHEMA_VERSION: i32 = 2;
+pub const SCHEMA_VERSION: i32 = 32;
+
+/// A schema migration: the version it upgrades to and the function applying it.
+struct Migration {
+    /// Schema version after the migration
+    version: i32,
+    /// What the migration changes, recorded in the migration history
+    description: &'static str,
+    /// Applies the migration
+    apply: fn(&Connection) -> Result<(), AppError>,
+}
+
+/// All migrations in version order. Append new migrations here.
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "Initial consolidated schema",
+        apply: migrate_v1,
+    },
+    Migration {
+        version: 2,
+        description: "Convert to global token ordering per persona",
+        apply: migrate_v2,
+    },
+    Migration {
+        version: 3,
+        description: "Add per-persona content hashes",
+        apply: migrate_v3,
+    },
+    Migration {
+        version: 4,
+        description: "Persist granularity levels",
+        apply: migrate_v4,
+    },
+    Migration {
+        version: 5,
+        description: "Add the per-persona prompt cache",
+        apply: migrate_v5,
+    },
+    Migration {
+        version: 6,
+        description: "Add the persistent token count cache",
+        apply: migrate_v6,
+    },
+    Migration {
+        version: 7,
+        description: "Add backend settings storage",
+        apply: migrate_v7,
+    },
+    Migration {
+        version: 8,
+        description: "Add the persona archive flag",
+        apply: migrate_v8,
+    },
+    Migration {
+        version: 9,
+        description: "Add smart collections",
+        apply: migrate_v9,
+    },
+    Migration {
+        version: 10,
+        description: "Add persona activity timestamps",
+        apply: migrate_v10,
+    },
+    Migration {
+        version: 11,
+        description: "Add the quick search index",
+        apply: migrate_v11,
+    },
+    Migration {
+        version: 12,
+        description: "Add token revisions",
+        apply: migrate_v12,
+    },
+    Migration {
+        version: 13,
+        description: "Add output resolution to generation parameters",
+        apply: migrate_v13,
+    },
+    Migration {
+        version: 14,
+        description: "Add per-persona composition defaults",
+        apply: migrate_v14,
+    },
+    Migration {
+        version: 15,
+        description: "Add label colors to personas and smart collections",
+        apply: migrate_v15,
+    },
+    Migration {
+        version: 16,
+        description: "Add content ratings to personas",
+        apply: migrate_v16,
+    },
+    Migration {
+        version: 17,
+        description: "Add the banned terms blacklist",
+        apply: migrate_v17,
+    },
+    Migration {
+        version: 18,
+        description: "Add base persona inheritance",
+        apply: migrate_v18,
+    },
+    Migration {
+        version: 19,
+        description: "Add feature usage counters",
+        apply: migrate_v19,
+    },
+    Migration {
+        version: 20,
+        description: "Add persona AI output language",
+        apply: migrate_v20,
+    },
+    Migration {
+        version: 21,
+        description: "Add the result gallery",
+        apply: migrate_v21,
+    },
+    Migration {
+        version: 22,
+        description: "Record prompts and persona snapshots for gallery images",
+        apply: migrate_v22,
+    },
+    Migration {
+        version: 23,
+        description: "Add dual prompt settings to composition defaults",
+        apply: migrate_v23,
+    },
+    Migration {
+        version: 24,
+        description: "Add conditioning entries to generation params",
+        apply: migrate_v24,
+    },
+    Migration {
+        version: 25,
+        description: "Cache face embeddings of gallery images",
+        apply: migrate_v25,
+    },
+    Migration {
+        version: 26,
+        description: "Add scheduled tasks and pending suggestions",
+        apply: migrate_v26,
+    },
+    Migration {
+        version: 27,
+        description: "Record the source of pending suggestions",
+        apply: migrate_v27,
+    },
+    Migration {
+        version: 28,
+        description: "Add persona wizard drafts",
+        apply: migrate_v28,
+    },
+    Migration {
+        version: 29,
+        description: "Add persona drafts",
+        apply: migrate_v29,
+    },
+    Migration {
+        version: 30,
+        description: "Index normalized token content for autocompletion",
+        apply: migrate_v30,
+    },
+    Migration {
+        version: 31,
+        description: "Add token aliases",
+        apply: migrate_v31,
+    },
+    Migration {
+        version: 32,
+        description: "Key the search index by rowid",
+        apply: migrate_v32,
+    },
+];
 
 /// Returns the current schema version for this application.
 #[must_use]
@@ -78,30 +435,98 @@ pub fn read_schema_version(conn: &Connection) -> Result<Option<i32>, AppError> {
     Ok(version)
 }
 
+/// Reads the recorded migration runs, most recent first.
+///
+/// Migrations that ran before the history was introduced are not listed.
+///
+/// # Arguments
+///
+/// * `conn` - Reference to the `SQLite` connection
+///
+/// # Errors
+///
+/// Returns `AppError::Database` if the query fails.
+pub fn read_migration_history(conn: &Connection) -> Result<Vec<MigrationRecord>, AppError> {
+    let mut stmt = conn.prepare(
+        r"
+        SELECT version, description, applied_at, duration_ms, backup_path, error
+        FROM migration_history
+        ORDER BY id DESC
+        ",
+    )?;
+
+    let records = stmt
+        .query_map([], |row| {
+            Ok(MigrationRecord {
+                version: row.get(0)?,
+                description: row.get(1)?,
+                // Timestamps stored as RFC3339 strings; fallback to now if parsing fails
+                applied_at: chrono::DateTime::parse_from_rfc3339(&row.get::<_, String>(2)?)
+                    .map_or_else(|_| Utc::now(), |dt| dt.with_timezone(&Utc)),
+                duration_ms: u64::try_from(row.get::<_, i64>(3)?).unwrap_or_default(),
+                backup_path: row.get(4)?,
+                error: row.get(5)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(records)
+}
+
 /// Runs all pending migrations to bring the schema up to date.
 ///
 /// This function is idempotent - running it multiple times has no effect
-/// if the schema is already at the current version.
+/// if the schema is already at the current version. Each migration runs in
+/// its own transaction together with the schema version update, and is
+/// recorded in `migration_history`, including failures. A failed migration
+/// is rolled back entirely, so a failed upgrade resumes at that migration.
 ///
 /// # Arguments
 ///
 /// * `conn` - Reference to the `SQLite` connection
+/// * `backup_path` - Backup of the database taken before migrating, if any
 ///
 /// # Errors
 ///
 /// Returns `AppError::Database` if any migration fails.
-pub fn run_migrations(conn: &Connection) -> Result<(), AppError> {
+pub fn run_migrations(conn: &Connection, backup_path: Option<&Path>) -> Result<(), AppError> {
     let current_version = get_schema_version(conn)?;
+    create_migration_history(conn)?;
 
     if current_version < SCHEMA_VERSION {
-        if current_version < 1 {
-            migrate_v1(conn)?;
-        }
-        if current_version < 2 {
-            migrate_v2(conn)?;
-        }
+        tracing::info!(
+            from = current_version,
+            to = SCHEMA_VERSION,
+            "Migrating database schema"
+        );
+
+        apply_migrations(conn, MIGRATIONS, current_version, backup_path)?;
+    }
 
-        set_schema_version(conn, SCHEMA_VERSION)?;
+    Ok(())
+}
+
+/// Applies the migrations newer than `current_version`, one transaction each.
+///
+/// A failure is recorded after its transaction was rolled back, so the
+/// history keeps it.
+fn apply_migrations(
+    conn: &Connection,
+    migrations: &[Migration],
+    current_version: i32,
+    backup_path: Option<&Path>,
+) -> Result<(), AppError> {
+    for migration in migrations.iter().filter(|m| m.version > current_version) {
+        let started = Instant::now();
+        let result = unit_of_work(conn, |conn| {
+            (migration.apply)(conn)?;
+            set_schema_version(conn, migration.version)?;
+            record_migration(conn, migration, started.elapsed(), backup_path, None)
+        });
+        if let Err(e) = result {
+            record_migration(conn, migration, started.elapsed(), backup_path, Some(&e))?;
+            return Err(e);
+        }
     }
 
     Ok(())
@@ -126,6 +551,54 @@ fn get_schema_version(conn: &Connection) -> Result<i32, AppError> {
     Ok(version.unwrap_or(0))
 }
 
+/// Creates the `migration_history` table if it doesn't exist.
+///
+/// Like `schema_version`, the table is bookkeeping for the migrations
+/// themselves, so it is created before any migration runs.
+fn create_migration_history(conn: &Connection) -> Result<(), AppError> {
+    conn.execute_batch(
+        r"
+        CREATE TABLE IF NOT EXISTS migration_history (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            version INTEGER NOT NULL,
+            description TEXT NOT NULL,
+            applied_at TEXT NOT NULL,
+            duration_ms INTEGER NOT NULL,
+            backup_path TEXT,
+            error TEXT
+        );
+        ",
+    )?;
+
+    Ok(())
+}
+
+/// Records one migration run in `migration_history`.
+fn record_migration(
+    conn: &Connection,
+    migration: &Migration,
+    duration: Duration,
+    backup_path: Option<&Path>,
+    error: Option<&AppError>,
+) -> Result<(), AppError> {
+    conn.execute(
+        r"
+        INSERT INTO migration_history (version, description, applied_at, duration_ms, backup_path, error)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+        ",
+        params![
+            migration.version,
+            migration.description,
+            Utc::now().to_rfc3339(),
+            i64::try_from(duration.as_millis()).unwrap_or(i64::MAX),
+            backup_path.map(|path| path.to_string_lossy().to_string()),
+            error.map(ToString::to_string),
+        ],
+    )?;
+
+    Ok(())
+}
+
 /// Updates the schema version in the database.
 fn set_schema_version(conn: &Connection, version: i32) -> Result<(), AppError> {
     conn.execute("DELETE FROM schema_version", [])?;
@@ -266,3 +739,950 @@ fn migrate_v2(conn: &Connection) -> Result<(), AppError> {
 
     Ok(())
 }
+
+/// Migration v3: Add per-persona content hashes.
+///
+/// Adds the `content_hash` column and backfills it for existing personas so
+/// that imports can detect duplicates regardless of persona name.
+fn migrate_v3(conn: &Connection) -> Result<(), AppError> {
+    conn.execute_batch(
+        r"
+        ALTER TABLE personas ADD COLUMN content_hash TEXT;
+        CREATE INDEX IF NOT EXISTS idx_personas_content_hash ON personas(content_hash);
+        ",
+    )?;
+
+    let mut stmt = conn.prepare("SELECT id, description FROM personas")?;
+    let personas: Vec<(String, Option<String>)> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    for (persona_id, description) in &personas {
+        let tokens = TokenRepository::find_by_persona(conn, persona_id)?;
+        let hash = compute_content_hash(description.as_deref(), &tokens);
+        conn.execute(
+            "UPDATE personas SET content_hash = ?1 WHERE id = ?2",
+            params![hash, persona_id],
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Migration v4: Persist granularity levels.
+///
+/// Creates the `granularity_levels` table and seeds it with the built-in
+/// levels, so each level's color and display order are stored explicitly.
+fn migrate_v4(conn: &Connection) -> Result<(), AppError> {
+    conn.execute_batch(
+        r"
+        CREATE TABLE IF NOT EXISTS granularity_levels (
+            id TEXT PRIMARY KEY NOT NULL,
+            name TEXT NOT NULL,
+            color TEXT NOT NULL,
+            display_order INTEGER NOT NULL,
+            is_default INTEGER NOT NULL DEFAULT 0,
+            created_at TEXT NOT NULL
+        );
+        ",
+    )?;
+
+    for level in GranularityLevel::all() {
+        conn.execute(
+            r"
+            INSERT OR IGNORE INTO granularity_levels (id, name, color, display_order, is_default, created_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            ",
+            params![
+                level.id,
+                level.name,
+                level.color,
+                level.display_order,
+                level.is_default,
+                level.created_at.to_rfc3339(),
+            ],
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Migration v5: Add the per-persona prompt cache.
+///
+/// Creates the `prompt_cache` table. It starts empty; entries are written as
+/// prompts are composed.
+fn migrate_v5(conn: &Connection) -> Result<(), AppError> {
+    conn.execute_batch(
+        r"
+        CREATE TABLE IF NOT EXISTS prompt_cache (
+            persona_id TEXT PRIMARY KEY NOT NULL,
+            options_hash TEXT NOT NULL,
+            positive_prompt TEXT NOT NULL,
+            negative_prompt TEXT NOT NULL,
+            positive_token_count INTEGER NOT NULL,
+            negative_token_count INTEGER NOT NULL,
+            created_at TEXT NOT NULL,
+            FOREIGN KEY (persona_id) REFERENCES personas(id) ON DELETE CASCADE
+        );
+        ",
+    )?;
+
+    Ok(())
+}
+
+/// Migration v6: Add the persistent token count cache.
+///
+/// Creates the `token_count_cache` table. It starts empty; entries are written
+/// as texts are counted with a real tokenizer.
+fn migrate_v6(conn: &Connection) -> Result<(), AppError> {
+    conn.execute_batch(
+        r"
+        CREATE TABLE IF NOT EXISTS token_count_cache (
+            text_hash TEXT NOT NULL,
+            tokenizer_id TEXT NOT NULL,
+            count INTEGER NOT NULL,
+            created_at TEXT NOT NULL,
+            PRIMARY KEY (text_hash, tokenizer_id)
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_token_count_cache_created_at ON token_count_cache(created_at);
+        ",
+    )?;
+
+    Ok(())
+}
+
+/// Migration v7: Add backend settings storage.
+///
+/// Creates the `app_settings` key-value table. Missing keys fall back to
+/// default settings, so no rows are seeded.
+fn migrate_v7(conn: &Connection) -> Result<(), AppError> {
+    conn.execute_batch(
+        r"
+        CREATE TABLE IF NOT EXISTS app_settings (
+            key TEXT PRIMARY KEY NOT NULL,
+            value TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );
+        ",
+    )?;
+
+    Ok(())
+}
+
+/// Migration v8: Add the persona archive flag.
+///
+/// Adds the `archived` column; existing personas start out unarchived.
+fn migrate_v8(conn: &Connection) -> Result<(), AppError> {
+    conn.execute_batch(
+        r"
+        ALTER TABLE personas ADD COLUMN archived INTEGER NOT NULL DEFAULT 0;
+        ",
+    )?;
+
+    Ok(())
+}
+
+/// Migration v9: Add smart collections.
+///
+/// Creates the `smart_collections` table. Only definitions are stored, so
+/// no data needs to be migrated.
+fn migrate_v9(conn: &Connection) -> Result<(), AppError> {
+    conn.execute_batch(
+        r"
+        CREATE TABLE IF NOT EXISTS smart_collections (
+            id TEXT PRIMARY KEY NOT NULL,
+            name TEXT NOT NULL UNIQUE,
+            query TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );
+        ",
+    )?;
+
+    Ok(())
+}
+
+/// Migration v10: Add persona activity timestamps.
+///
+/// Creates the `persona_activity` table and seeds it with what existing data
+/// reveals: each persona's `updated_at` as its last modification, and cached
+/// prompts as its last composition. Opened times start empty.
+fn migrate_v10(conn: &Connection) -> Result<(), AppError> {
+    conn.execute_batch(
+        r"
+        CREATE TABLE IF NOT EXISTS persona_activity (
+            persona_id TEXT NOT NULL,
+            kind TEXT NOT NULL,
+            occurred_at TEXT NOT NULL,
+            PRIMARY KEY (persona_id, kind),
+            FOREIGN KEY (persona_id) REFERENCES personas(id) ON DELETE CASCADE
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_persona_activity_kind ON persona_activity(kind, occurred_at);
+
+        INSERT OR IGNORE INTO persona_activity (persona_id, kind, occurred_at)
+            SELECT id, 'modified', updated_at FROM personas;
+
+        INSERT OR IGNORE INTO persona_activity (persona_id, kind, occurred_at)
+            SELECT persona_id, 'composed', created_at FROM prompt_cache;
+        ",
+    )?;
+
+    Ok(())
+}
+
+/// Migration v11: Add the quick search index.
+///
+/// Creates the `search_index` FTS5 table, the triggers that keep it in sync
+/// with personas, tokens, and smart collections, and indexes existing rows.
+/// The tokenizer ignores case and diacritics.
+fn migrate_v11(conn: &Connection) -> Result<(), AppError> {
+    conn.execute_batch(
+        r"
+        CREATE VIRTUAL TABLE IF NOT EXISTS search_index USING fts5(
+            kind UNINDEXED,
+            entity_id UNINDEXED,
+            persona_id UNINDEXED,
+            title,
+            body,
+            tokenize = 'unicode61 remove_diacritics 2'
+        );
+
+        -- Personas: name as title, description and tags as body
+        CREATE TRIGGER IF NOT EXISTS search_personas_insert AFTER INSERT ON personas BEGIN
+            INSERT INTO search_index (kind, entity_id, persona_id, title, body)
+            VALUES ('persona', new.id, new.id, new.name,
+                    COALESCE(new.description, '') || ' ' || new.tags);
+        END;
+        CREATE TRIGGER IF NOT EXISTS search_personas_update
+        AFTER UPDATE OF name, description, tags ON personas BEGIN
+            DELETE FROM search_index WHERE kind = 'persona' AND entity_id = old.id;
+            INSERT INTO search_index (kind, entity_id, persona_id, title, body)
+            VALUES ('persona', new.id, new.id, new.name,
+                    COALESCE(new.description, '') || ' ' || new.tags);
+        END;
+        CREATE TRIGGER IF NOT EXISTS search_personas_delete AFTER DELETE ON personas BEGIN
+            DELETE FROM search_index WHERE persona_id = old.id;
+        END;
+
+        -- Tokens: content as title
+        CREATE TRIGGER IF NOT EXISTS search_tokens_insert AFTER INSERT ON tokens BEGIN
+            INSERT INTO search_index (kind, entity_id, persona_id, title, body)
+            VALUES ('token', new.id, new.persona_id, new.content, '');
+        END;
+        CREATE TRIGGER IF NOT EXISTS search_tokens_update AFTER UPDATE OF content ON tokens BEGIN
+            DELETE FROM search_index WHERE kind = 'token' AND entity_id = old.id;
+            INSERT INTO search_index (kind, entity_id, persona_id, title, body)
+            VALUES ('token', new.id, new.persona_id, new.content, '');
+        END;
+        CREATE TRIGGER IF NOT EXISTS search_tokens_delete AFTER DELETE ON tokens BEGIN
+            DELETE FROM search_index WHERE kind = 'token' AND entity_id = old.id;
+        END;
+
+        -- Smart collections: name as title
+        CREATE TRIGGER IF NOT EXISTS search_collections_insert
+        AFTER INSERT ON smart_collections BEGIN
+            INSERT INTO search_index (kind, entity_id, persona_id, title, body)
+            VALUES ('collection', new.id, NULL, new.name, '');
+        END;
+        CREATE TRIGGER IF NOT EXISTS search_collections_update
+        AFTER UPDATE OF name ON smart_collections BEGIN
+            DELETE FROM search_index WHERE kind = 'collection' AND entity_id = old.id;
+            INSERT INTO search_index (kind, entity_id, persona_id, title, body)
+            VALUES ('collection', new.id, NULL, new.name, '');
+        END;
+        CREATE TRIGGER IF NOT EXISTS search_collections_delete
+        AFTER DELETE ON smart_collections BEGIN
+            DELETE FROM search_index WHERE kind = 'collection' AND entity_id = old.id;
+        END;
+
+        -- Index existing rows
+        DELETE FROM search_index;
+        INSERT INTO search_index (kind, entity_id, persona_id, title, body)
+            SELECT 'persona', id, id, name, COALESCE(description, '') || ' ' || tags
+            FROM personas;
+        INSERT INTO search_index (kind, entity_id, persona_id, title, body)
+            SELECT 'token', id, persona_id, content, '' FROM tokens;
+        INSERT INTO search_index (kind, entity_id, persona_id, title, body)
+            SELECT 'collection', id, NULL, name, '' FROM smart_collections;
+        ",
+    )?;
+
+    Ok(())
+}
+
+/// Migration v12: Add token revisions.
+///
+/// Creates the `token_revisions` table. Edits made before this version were
+/// not recorded, so existing tokens start with an empty history.
+fn migrate_v12(conn: &Connection) -> Result<(), AppError> {
+    conn.execute_batch(
+        r"
+        CREATE TABLE IF NOT EXISTS token_revisions (
+            token_id TEXT NOT NULL,
+            revision INTEGER NOT NULL,
+            content TEXT NOT NULL,
+            weight REAL NOT NULL,
+            recorded_at TEXT NOT NULL,
+            PRIMARY KEY (token_id, revision),
+            FOREIGN KEY (token_id) REFERENCES tokens(id) ON DELETE CASCADE
+        );
+        ",
+    )?;
+
+    Ok(())
+}
+
+/// Migration v13: Add output resolution to generation parameters.
+///
+/// Both columns are nullable; existing personas keep the model's default size.
+fn migrate_v13(conn: &Connection) -> Result<(), AppError> {
+    conn.execute_batch(
+        r"
+        ALTER TABLE generation_params ADD COLUMN width INTEGER;
+        ALTER TABLE generation_params ADD COLUMN height INTEGER;
+        ",
+    )?;
+
+    Ok(())
+}
+
+/// Migration v14: Add per-persona composition defaults.
+///
+/// Creates the `composition_defaults` table. Personas without a row keep
+/// composing with the global defaults.
+fn migrate_v14(conn: &Connection) -> Result<(), AppError> {
+    conn.execute_batch(
+        r"
+        CREATE TABLE IF NOT EXISTS composition_defaults (
+            persona_id TEXT PRIMARY KEY NOT NULL,
+            separator TEXT NOT NULL,
+            include_weights INTEGER NOT NULL,
+            weight_syntax TEXT NOT NULL,
+            FOREIGN KEY (persona_id) REFERENCES personas(id) ON DELETE CASCADE
+        );
+        ",
+    )?;
+
+    Ok(())
+}
+
+/// Migration v15: Add label colors to personas and smart collections.
+///
+/// Both columns are nullable; existing personas and collections have no color.
+fn migrate_v15(conn: &Connection) -> Result<(), AppError> {
+    conn.execute_batch(
+        r"
+        ALTER TABLE personas ADD COLUMN color TEXT;
+        ALTER TABLE smart_collections ADD COLUMN color TEXT;
+        ",
+    )?;
+
+    Ok(())
+}
+
+/// Migration v16: Add content ratings to personas.
+///
+/// Existing personas are rated general.
+fn migrate_v16(conn: &Connection) -> Result<(), AppError> {
+    conn.execute_batch(
+        r"
+        ALTER TABLE personas ADD COLUMN content_rating TEXT NOT NULL DEFAULT 'general';
+        ",
+    )?;
+
+    Ok(())
+}
+
+/// Migration v17: Add the banned terms blacklist.
+///
+/// Creates the `banned_terms` table; the list starts empty.
+fn migrate_v17(conn: &Connection) -> Result<(), AppError> {
+    conn.execute_batch(
+        r"
+        CREATE TABLE IF NOT EXISTS banned_terms (
+            id TEXT PRIMARY KEY NOT NULL,
+            term TEXT NOT NULL UNIQUE COLLATE NOCASE,
+            created_at TEXT NOT NULL
+        );
+        ",
+    )?;
+
+    Ok(())
+}
+
+/// Migration v18: Add base persona inheritance.
+///
+/// Existing personas have no base, inherit no levels, and override nothing.
+fn migrate_v18(conn: &Connection) -> Result<(), AppError> {
+    conn.execute_batch(
+        r"
+        ALTER TABLE personas ADD COLUMN base_persona_id TEXT
+            REFERENCES personas(id) ON DELETE SET NULL;
+        ALTER TABLE personas ADD COLUMN inherited_granularities TEXT NOT NULL DEFAULT '[]';
+        ALTER TABLE personas ADD COLUMN param_overrides TEXT NOT NULL DEFAULT '[]';
+        CREATE INDEX IF NOT EXISTS idx_personas_base ON personas(base_persona_id);
+        ",
+    )?;
+
+    Ok(())
+}
+
+/// Migration v19: Add feature usage counters.
+///
+/// The table starts empty; counting begins once telemetry is enabled.
+fn migrate_v19(conn: &Connection) -> Result<(), AppError> {
+    conn.execute_batch(
+        r"
+        CREATE TABLE IF NOT EXISTS feature_usage (
+            feature TEXT PRIMARY KEY NOT NULL,
+            count INTEGER NOT NULL DEFAULT 0,
+            first_used_on TEXT NOT NULL,
+            last_used_on TEXT NOT NULL
+        );
+        ",
+    )?;
+
+    Ok(())
+}
+
+/// Migration v20: Add persona AI output language.
+///
+/// Existing personas have none and follow the global setting.
+fn migrate_v20(conn: &Connection) -> Result<(), AppError> {
+    conn.execute_batch(
+        r"
+        ALTER TABLE personas ADD COLUMN ai_output_language TEXT;
+        ",
+    )?;
+
+    Ok(())
+}
+
+/// Migration v21: Add the result gallery.
+///
+/// Creates the `generated_images` table; galleries start empty.
+fn migrate_v21(conn: &Connection) -> Result<(), AppError> {
+    conn.execute_batch(
+        r"
+        CREATE TABLE IF NOT EXISTS generated_images (
+            id TEXT PRIMARY KEY NOT NULL,
+            persona_id TEXT NOT NULL,
+            path TEXT NOT NULL,
+            prompt_hash TEXT NOT NULL,
+            seed INTEGER,
+            params TEXT,
+            starred INTEGER NOT NULL DEFAULT 0,
+            created_at TEXT NOT NULL,
+            FOREIGN KEY (persona_id) REFERENCES personas(id) ON DELETE CASCADE
+        );
+        CREATE INDEX IF NOT EXISTS idx_generated_images_persona
+            ON generated_images(persona_id, created_at);
+        ",
+    )?;
+
+    Ok(())
+}
+
+/// Migration v22: Record prompts and persona snapshots for gallery images.
+///
+/// Images attached earlier keep only their prompt hash.
+fn migrate_v22(conn: &Connection) -> Result<(), AppError> {
+    conn.execute_batch(
+        r"
+        ALTER TABLE generated_images ADD COLUMN positive_prompt TEXT;
+        ALTER TABLE generated_images ADD COLUMN negative_prompt TEXT;
+        ALTER TABLE generated_images ADD COLUMN persona_snapshot TEXT;
+        ",
+    )?;
+
+    Ok(())
+}
+
+/// Migration v23: Add dual prompt settings to composition defaults.
+///
+/// Existing defaults keep composing a single positive prompt.
+fn migrate_v23(conn: &Connection) -> Result<(), AppError> {
+    conn.execute_batch(
+        r"
+        ALTER TABLE composition_defaults ADD COLUMN dual_prompt TEXT;
+        ",
+    )?;
+
+    Ok(())
+}
+
+/// Migration v24: Add `ControlNet` and IP-Adapter entries to generation params.
+///
+/// Existing parameters start without conditioning.
+fn migrate_v24(conn: &Connection) -> Result<(), AppError> {
+    conn.execute_batch(
+        r"
+        ALTER TABLE generation_params ADD COLUMN conditioning TEXT NOT NULL DEFAULT '[]';
+        ",
+    )?;
+
+    Ok(())
+}
+
+/// Migration v25: Cache face embeddings of gallery images.
+///
+/// Existing images are embedded on the next gallery analysis.
+fn migrate_v25(conn: &Connection) -> Result<(), AppError> {
+    conn.execute_batch(
+        r"
+        ALTER TABLE generated_images ADD COLUMN face_embedding TEXT;
+        ",
+    )?;
+
+    Ok(())
+}
+
+/// Migration v26: Add scheduled tasks and the suggestions they stage.
+fn migrate_v26(conn: &Connection) -> Result<(), AppError> {
+    conn.execute_batch(
+        r"
+        CREATE TABLE IF NOT EXISTS scheduled_tasks (
+            id TEXT PRIMARY KEY NOT NULL,
+            name TEXT NOT NULL,
+            kind TEXT NOT NULL,
+            persona_ids TEXT NOT NULL DEFAULT '[]',
+            frequency TEXT NOT NULL,
+            config TEXT NOT NULL,
+            token_count INTEGER NOT NULL,
+            enabled INTEGER NOT NULL DEFAULT 1,
+            last_run_at TEXT,
+            next_run_at TEXT NOT NULL,
+            last_error TEXT,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS pending_suggestions (
+            id TEXT PRIMARY KEY NOT NULL,
+            persona_id TEXT NOT NULL,
+            task_id TEXT,
+            granularity_id TEXT NOT NULL,
+            polarity TEXT NOT NULL,
+            content TEXT NOT NULL,
+            weight REAL NOT NULL DEFAULT 1.0,
+            rationale TEXT,
+            created_at TEXT NOT NULL,
+            FOREIGN KEY (persona_id) REFERENCES personas(id) ON DELETE CASCADE,
+            FOREIGN KEY (task_id) REFERENCES scheduled_tasks(id) ON DELETE SET NULL,
+            UNIQUE (persona_id, granularity_id, polarity, content COLLATE NOCASE)
+        );
+        CREATE INDEX IF NOT EXISTS idx_pending_suggestions_persona
+            ON pending_suggestions(persona_id, created_at);
+        ",
+    )?;
+
+    Ok(())
+}
+
+/// Migration v27: Record where pending suggestions come from.
+///
+/// Suggestions staged before this version came from scheduled refreshes.
+fn migrate_v27(conn: &Connection) -> Result<(), AppError> {
+    conn.execute_batch(
+        r"
+        ALTER TABLE pending_suggestions ADD COLUMN source TEXT NOT NULL
+            DEFAULT 'scheduled_refresh';
+        ",
+    )?;
+
+    Ok(())
+}
+
+/// Migration v28: Add drafts of the persona creation wizard.
+///
+/// Drafts reference no persona; the persona is only created when the wizard
+/// finishes.
+fn migrate_v28(conn: &Connection) -> Result<(), AppError> {
+    conn.execute_batch(
+        r"
+        CREATE TABLE IF NOT EXISTS persona_wizards (
+            id TEXT PRIMARY KEY NOT NULL,
+            name TEXT NOT NULL,
+            description TEXT,
+            tags TEXT NOT NULL DEFAULT '[]',
+            content_rating TEXT NOT NULL DEFAULT 'general',
+            image_model_id TEXT,
+            tokens TEXT NOT NULL DEFAULT '[]',
+            completed_steps TEXT NOT NULL DEFAULT '[]',
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );
+        ",
+    )?;
+
+    Ok(())
+}
+
+/// Migration v29: Add autosaved persona drafts.
+///
+/// Drafts have no unique name constraint, so they never block creating a
+/// persona.
+fn migrate_v29(conn: &Connection) -> Result<(), AppError> {
+    conn.execute_batch(
+        r"
+        CREATE TABLE IF NOT EXISTS drafts (
+            id TEXT PRIMARY KEY NOT NULL,
+            name TEXT NOT NULL DEFAULT '',
+            description TEXT,
+            tags TEXT NOT NULL DEFAULT '[]',
+            content_rating TEXT NOT NULL DEFAULT 'general',
+            tokens TEXT NOT NULL DEFAULT '[]',
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );
+        ",
+    )?;
+
+    Ok(())
+}
+
+/// Migration v30: Index normalized token content for autocompletion.
+///
+/// `content_key` is a virtual generated column, so it can never disagree with
+/// the content; only the index stores it. `lower` folds ASCII only, which is
+/// enough for tokens, as they are always English.
+fn migrate_v30(conn: &Connection) -> Result<(), AppError> {
+    conn.execute_batch(
+        r"
+        ALTER TABLE tokens ADD COLUMN content_key TEXT
+            GENERATED ALWAYS AS (lower(trim(content))) VIRTUAL;
+        CREATE INDEX IF NOT EXISTS idx_tokens_content_key ON tokens(content_key, granularity_id);
+        ",
+    )?;
+
+    Ok(())
+}
+
+/// Migration v31: Add token aliases.
+fn migrate_v31(conn: &Connection) -> Result<(), AppError> {
+    conn.execute_batch(
+        r"
+        CREATE TABLE IF NOT EXISTS token_aliases (
+            id TEXT PRIMARY KEY NOT NULL,
+            alias TEXT NOT NULL UNIQUE COLLATE NOCASE,
+            canonical TEXT NOT NULL,
+            created_at TEXT NOT NULL
+        );
+        ",
+    )?;
+
+    Ok(())
+}
+
+/// Migration v32: Key the search index by rowid.
+///
+/// FTS5 cannot index `kind` and `entity_id`, so the v11 triggers scanned the
+/// whole index on every update and delete. Each index row now takes the rowid
+/// of a `search_entries` row, found through its unique (kind, entity) index.
+/// Entity rowids are not used directly, since `VACUUM` may renumber the rowids
+/// of tables without an `INTEGER PRIMARY KEY`. The index is rebuilt.
+fn migrate_v32(conn: &Connection) -> Result<(), AppError> {
+    conn.execute_batch(
+        r"
+        DROP TRIGGER IF EXISTS search_personas_insert;
+        DROP TRIGGER IF EXISTS search_personas_update;
+        DROP TRIGGER IF EXISTS search_personas_delete;
+        DROP TRIGGER IF EXISTS search_tokens_insert;
+        DROP TRIGGER IF EXISTS search_tokens_update;
+        DROP TRIGGER IF EXISTS search_tokens_delete;
+        DROP TRIGGER IF EXISTS search_collections_insert;
+        DROP TRIGGER IF EXISTS search_collections_update;
+        DROP TRIGGER IF EXISTS search_collections_delete;
+        DELETE FROM search_index;
+
+        CREATE TABLE IF NOT EXISTS search_entries (
+            id INTEGER PRIMARY KEY,
+            kind TEXT NOT NULL,
+            entity_id TEXT NOT NULL,
+            persona_id TEXT,
+            UNIQUE (kind, entity_id)
+        );
+        CREATE INDEX IF NOT EXISTS idx_search_entries_persona ON search_entries(persona_id);
+
+        -- Personas: name as title, description and tags as body
+        CREATE TRIGGER IF NOT EXISTS search_personas_insert AFTER INSERT ON personas BEGIN
+            INSERT INTO search_entries (kind, entity_id, persona_id)
+            VALUES ('persona', new.id, new.id);
+            INSERT INTO search_index (rowid, kind, entity_id, persona_id, title, body)
+            VALUES (
+                (SELECT id FROM search_entries WHERE kind = 'persona' AND entity_id = new.id),
+                'persona', new.id, new.id, new.name,
+                COALESCE(new.description, '') || ' ' || new.tags
+            );
+        END;
+        CREATE TRIGGER IF NOT EXISTS search_personas_update
+        AFTER UPDATE OF name, description, tags ON personas BEGIN
+            DELETE FROM search_index WHERE rowid =
+                (SELECT id FROM search_entries WHERE kind = 'persona' AND entity_id = old.id);
+            INSERT INTO search_index (rowid, kind, entity_id, persona_id, title, body)
+            VALUES (
+                (SELECT id FROM search_entries WHERE kind = 'persona' AND entity_id = new.id),
+                'persona', new.id, new.id, new.name,
+                COALESCE(new.description, '') || ' ' || new.tags
+            );
+        END;
+        CREATE TRIGGER IF NOT EXISTS search_personas_delete AFTER DELETE ON personas BEGIN
+            DELETE FROM search_index WHERE rowid IN
+                (SELECT id FROM search_entries WHERE persona_id = old.id);
+            DELETE FROM search_entries WHERE persona_id = old.id;
+        END;
+
+        -- Tokens: content as title
+        CREATE TRIGGER IF NOT EXISTS search_tokens_insert AFTER INSERT ON tokens BEGIN
+            INSERT INTO search_entries (kind, entity_id, persona_id)
+            VALUES ('token', new.id, new.persona_id);
+            INSERT INTO search_index (rowid, kind, entity_id, persona_id, title, body)
+            VALUES (
+                (SELECT id FROM search_entries WHERE kind = 'token' AND entity_id = new.id),
+                'token', new.id, new.persona_id, new.content, ''
+            );
+        END;
+        CREATE TRIGGER IF NOT EXISTS search_tokens_update AFTER UPDATE OF content ON tokens BEGIN
+            DELETE FROM search_index WHERE rowid =
+                (SELECT id FROM search_entries WHERE kind = 'token' AND entity_id = old.id);
+            INSERT INTO search_index (rowid, kind, entity_id, persona_id, title, body)
+            VALUES (
+                (SELECT id FROM search_entries WHERE kind = 'token' AND entity_id = new.id),
+                'token', new.id, new.persona_id, new.content, ''
+            );
+        END;
+        CREATE TRIGGER IF NOT EXISTS search_tokens_delete AFTER DELETE ON tokens BEGIN
+            DELETE FROM search_index WHERE rowid =
+                (SELECT id FROM search_entries WHERE kind = 'token' AND entity_id = old.id);
+            DELETE FROM search_entries WHERE kind = 'token' AND entity_id = old.id;
+        END;
+
+        -- Smart collections: name as title
+        CREATE TRIGGER IF NOT EXISTS search_collections_insert
+        AFTER INSERT ON smart_collections BEGIN
+            INSERT INTO search_entries (kind, entity_id, persona_id)
+            VALUES ('collection', new.id, NULL);
+            INSERT INTO search_index (rowid, kind, entity_id, persona_id, title, body)
+            VALUES (
+                (SELECT id FROM search_entries WHERE kind = 'collection' AND entity_id = new.id),
+                'collection', new.id, NULL, new.name, ''
+            );
+        END;
+        CREATE TRIGGER IF NOT EXISTS search_collections_update
+        AFTER UPDATE OF name ON smart_collections BEGIN
+            DELETE FROM search_index WHERE rowid =
+                (SELECT id FROM search_entries WHERE kind = 'collection' AND entity_id = old.id);
+            INSERT INTO search_index (rowid, kind, entity_id, persona_id, title, body)
+            VALUES (
+                (SELECT id FROM search_entries WHERE kind = 'collection' AND entity_id = new.id),
+                'collection', new.id, NULL, new.name, ''
+            );
+        END;
+        CREATE TRIGGER IF NOT EXISTS search_collections_delete
+        AFTER DELETE ON smart_collections BEGIN
+            DELETE FROM search_index WHERE rowid =
+                (SELECT id FROM search_entries WHERE kind = 'collection' AND entity_id = old.id);
+            DELETE FROM search_entries WHERE kind = 'collection' AND entity_id = old.id;
+        END;
+
+        -- Index existing rows
+        INSERT INTO search_entries (kind, entity_id, persona_id)
+            SELECT 'persona', id, id FROM personas;
+        INSERT INTO search_entries (kind, entity_id, persona_id)
+            SELECT 'token', id, persona_id FROM tokens;
+        INSERT INTO search_entries (kind, entity_id, persona_id)
+            SELECT 'collection', id, NULL FROM smart_collections;
+        INSERT INTO search_index (rowid, kind, entity_id, persona_id, title, body)
+            SELECT e.id, 'persona', p.id, p.id, p.name,
+                   COALESCE(p.description, '') || ' ' || p.tags
+            FROM personas p JOIN search_entries e ON e.kind = 'persona' AND e.entity_id = p.id;
+        INSERT INTO search_index (rowid, kind, entity_id, persona_id, title, body)
+            SELECT e.id, 'token', t.id, t.persona_id, t.content, ''
+            FROM tokens t JOIN search_entries e ON e.kind = 'token' AND e.entity_id = t.id;
+        INSERT INTO search_index (rowid, kind, entity_id, persona_id, title, body)
+            SELECT e.id, 'collection', c.id, NULL, c.name, ''
+            FROM smart_collections c
+            JOIN search_entries e ON e.kind = 'collection' AND e.entity_id = c.id;
+        ",
+    )?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::persona::{ContentRating, CreatePersonaRequest};
+    use crate::domain::token::{CreateTokenRequest, TokenPolarity};
+    use crate::infrastructure::database::repositories::PersonaRepository;
+
+    fn create_entries(conn: &Connection) -> Result<(), AppError> {
+        conn.execute_batch("CREATE TABLE entries (name TEXT NOT NULL)")?;
+        Ok(())
+    }
+
+    fn add_column_then_fail(conn: &Connection) -> Result<(), AppError> {
+        conn.execute_batch("ALTER TABLE entries ADD COLUMN note TEXT")?;
+        Err(AppError::Internal("interrupted".to_string()))
+    }
+
+    fn add_column(conn: &Connection) -> Result<(), AppError> {
+        conn.execute_batch("ALTER TABLE entries ADD COLUMN note TEXT")?;
+        Ok(())
+    }
+
+    fn columns(conn: &Connection) -> Vec<String> {
+        conn.prepare("SELECT name FROM pragma_table_info('entries')")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn failed_migration_is_rolled_back_and_retried_cleanly() {
+        let conn = Connection::open_in_memory().unwrap();
+        get_schema_version(&conn).unwrap();
+        create_migration_history(&conn).unwrap();
+        let failing = [
+            Migration {
+                version: 1,
+                description: "Create entries",
+                apply: create_entries,
+            },
+            Migration {
+                version: 2,
+                description: "Add note",
+                apply: add_column_then_fail,
+            },
+        ];
+
+        let result = apply_migrations(&conn, &failing, 0, None);
+
+        assert!(matches!(result, Err(AppError::Internal(_))));
+        assert_eq!(get_schema_version(&conn).unwrap(), 1);
+        assert_eq!(columns(&conn), ["name"]);
+        let history = read_migration_history(&conn).unwrap();
+        assert_eq!(history.len(), 2);
+        assert!(history[0].error.is_some());
+        assert!(history[1].error.is_none());
+
+        let fixed = [Migration {
+            version: 2,
+            description: "Add note",
+            apply: add_column,
+        }];
+        apply_migrations(&conn, &fixed, 1, None).unwrap();
+
+        assert_eq!(get_schema_version(&conn).unwrap(), 2);
+        assert_eq!(columns(&conn), ["name", "note"]);
+    }
+
+    #[test]
+    fn all_migrations_apply_to_a_new_database() {
+        let conn = Connection::open_in_memory().unwrap();
+
+        run_migrations(&conn, None).unwrap();
+
+        assert_eq!(get_schema_version(&conn).unwrap(), SCHEMA_VERSION);
+        assert!(conn.is_autocommit());
+    }
+
+    fn search_titles(conn: &Connection, kind: &str) -> Vec<String> {
+        conn.prepare(
+            "SELECT i.title FROM search_index i \
+             JOIN search_entries e ON e.id = i.rowid AND e.kind = i.kind AND e.entity_id = i.entity_id \
+             WHERE i.kind = ?1 ORDER BY i.title",
+        )
+        .unwrap()
+        .query_map([kind], |row| row.get(0))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap()
+    }
+
+    fn count(conn: &Connection, table: &str) -> i64 {
+        conn.query_row(&format!("SELECT COUNT(*) FROM {table}"), [], |row| {
+            row.get(0)
+        })
+        .unwrap()
+    }
+
+    fn create_persona_with_tokens(conn: &Connection, name: &str, contents: &[&str]) -> String {
+        let persona = PersonaRepository::create(
+            conn,
+            &CreatePersonaRequest {
+                name: name.to_string(),
+                description: None,
+                tags: Vec::new(),
+                content_rating: ContentRating::General,
+            },
+        )
+        .unwrap();
+        for content in contents {
+            TokenRepository::create(
+                conn,
+                &CreateTokenRequest {
+                    persona_id: persona.id.clone(),
+                    granularity_id: "hair".to_string(),
+                    polarity: TokenPolarity::Positive,
+                    content: (*content).to_string(),
+                    weight: 1.0,
+                },
+            )
+            .unwrap();
+        }
+        persona.id
+    }
+
+    #[test]
+    fn search_index_is_rebuilt_keyed_by_rowid() {
+        let conn = Connection::open_in_memory().unwrap();
+        get_schema_version(&conn).unwrap();
+        create_migration_history(&conn).unwrap();
+        apply_migrations(&conn, &MIGRATIONS[..31], 0, None).unwrap();
+        create_persona_with_tokens(&conn, "Aria", &["red hair", "blue eyes"]);
+
+        apply_migrations(&conn, MIGRATIONS, 31, None).unwrap();
+
+        assert_eq!(search_titles(&conn, "persona"), ["Aria"]);
+        assert_eq!(search_titles(&conn, "token"), ["blue eyes", "red hair"]);
+        assert_eq!(count(&conn, "search_entries"), 3);
+    }
+
+    #[test]
+    fn search_index_follows_edits_and_deletes() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("PRAGMA foreign_keys = ON").unwrap();
+        run_migrations(&conn, None).unwrap();
+        let aria = create_persona_with_tokens(&conn, "Aria", &["red hair", "blue eyes"]);
+        create_persona_with_tokens(&conn, "Bella", &["black hair"]);
+
+        conn.execute(
+            "UPDATE tokens SET content = 'silver hair' WHERE content = 'red hair'",
+            [],
+        )
+        .unwrap();
+        conn.execute("DELETE FROM tokens WHERE content = 'blue eyes'", [])
+            .unwrap();
+        conn.execute(
+            "UPDATE personas SET name = 'Aria Prime' WHERE id = ?1",
+            [&aria],
+        )
+        .unwrap();
+
+        assert_eq!(search_titles(&conn, "persona"), ["Aria Prime", "Bella"]);
+        assert_eq!(search_titles(&conn, "token"), ["black hair", "silver hair"]);
+        assert_eq!(count(&conn, "search_entries"), count(&conn, "search_index"));
+
+        conn.execute("DELETE FROM personas WHERE id = ?1", [&aria])
+            .unwrap();
+
+        assert_eq!(search_titles(&conn, "persona"), ["Bella"]);
+        assert_eq!(search_titles(&conn, "token"), ["black hair"]);
+        assert_eq!(count(&conn, "search_entries"), 2);
+        assert_eq!(count(&conn, "search_index"), 2);
+    }
+}
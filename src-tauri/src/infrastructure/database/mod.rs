@@ -9,13 +9,16 @@
 //! - **Connection**: Single `SQLite` connection with WAL mode
 //! - **Migrations**: Version-controlled schema evolution
 //! - **Repositories**: Type-safe data access objects
+//! - **Unit of Work**: Transactions grouping the writes of one operation
+//! - **Metadata Cache**: In-memory copy of read-mostly metadata (granularity levels)
 //!
 //! # `SQLite` Configuration
 //!
 //! The database is configured for desktop application use:
 //! - **WAL Mode**: Write-Ahead Logging for better concurrent access
 //! - **Foreign Keys**: Enabled for referential integrity
-//! - **Location**: `{app_data_dir}/ppm.db`
+//! - **Location**: `{app_data_dir}/ppm.db`, where the data directory can be
+//!   overridden with `PPM_DATA_DIR` or `--data-dir`
 //!
 //! # Schema Overview
 //!
@@ -24,7 +27,11 @@
 //! - `tokens`: Prompt tokens with granularity, polarity, and weights
 
 pub mod connection;
+pub mod metadata_cache;
 pub mod migrations;
 pub mod repositories;
+pub mod unit_of_work;
 
 pub use connection::Database;
+pub use metadata_cache::MetadataCache;
+pub use unit_of_work::unit_of_work;
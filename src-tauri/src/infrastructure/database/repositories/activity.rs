@@ -0,0 +1,110 @@
+//! Persona Activity Repository
+//!
+//! Provides data access operations for the latest opened, modified, and
+//! composed timestamps of each persona. All methods are stateless and take a
+//! connection reference as their first parameter.
+//!
+//! Only the latest timestamp per persona and kind is kept; rows are removed
+//! with their persona by a cascading foreign key.
+//!
+//! # Usage
+//!
+//! ```rust,ignore
+//! ActivityRepository::record(&conn, &persona_id, ActivityKind::Opened)?;
+//! let recent = ActivityRepository::find_recent(&conn, ActivityKind::Opened, 10)?;
+//! ```
+
+use chrono::Utc;
+use rusqlite::{params, Connection};
+
+use crate::domain::activity::{ActivityKind, RecentPersona};
+use crate::error::AppError;
+
+use super::PersonaRepository;
+
+/// Repository for persona activity database operations.
+///
+/// This struct contains no state; all methods take a connection reference
+/// and can be composed within external transactions.
+pub struct ActivityRepository;
+
+impl ActivityRepository {
+    /// Records that an activity happened to a persona now, replacing the
+    /// previous timestamp of the same kind.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `persona_id` - The persona's UUID
+    /// * `kind` - What happened
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Database` for database errors, including an unknown persona.
+    #[tracing::instrument(level = "debug", skip_all, fields(persona_id = %persona_id))]
+    pub fn record(conn: &Connection, persona_id: &str, kind: ActivityKind) -> Result<(), AppError> {
+        conn.execute(
+            r"
+            INSERT OR REPLACE INTO persona_activity (persona_id, kind, occurred_at)
+            VALUES (?1, ?2, ?3)
+            ",
+            params![persona_id, kind.as_str(), Utc::now().to_rfc3339()],
+        )?;
+        Ok(())
+    }
+
+    /// Lists the personas with the most recent activity of a kind.
+    ///
+    /// Archived personas and personas without such activity are left out.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `kind` - Activity to sort by
+    /// * `limit` - Maximum number of personas to return
+    /// * `hide_mature` - Leave out mature-rated personas
+    ///
+    /// # Returns
+    ///
+    /// Personas with their activity timestamp, most recent first.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Database` for database errors.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub fn find_recent(
+        conn: &Connection,
+        kind: ActivityKind,
+        limit: usize,
+        hide_mature: bool,
+    ) -> Result<Vec<RecentPersona>, AppError> {
+        let mut stmt = conn.prepare(
+            r"
+            SELECT a.persona_id, a.occurred_at
+            FROM persona_activity a
+            JOIN personas p ON p.id = a.persona_id
+            WHERE a.kind = ?1 AND p.archived = 0
+              AND (?3 = 0 OR p.content_rating != 'mature')
+            ORDER BY a.occurred_at DESC
+            LIMIT ?2
+            ",
+        )?;
+
+        let rows = stmt
+            .query_map(params![kind.as_str(), limit as i64, hide_mature], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        rows.into_iter()
+            .map(|(persona_id, occurred_at)| {
+                Ok(RecentPersona {
+                    persona: PersonaRepository::find_by_id(conn, &persona_id)?,
+                    // Timestamps stored as RFC3339 strings; fallback to now if parsing fails
+                    at: chrono::DateTime::parse_from_rfc3339(&occurred_at)
+                        .map_or_else(|_| Utc::now(), |dt| dt.with_timezone(&Utc)),
+                })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,148 @@
+//! Banned Term Repository
+//!
+//! Provides data access operations for the user's banned terms blacklist.
+//! All methods are stateless and take a connection reference as their first parameter.
+//!
+//! Cached prompts are composed without banned tokens, so adding or removing a
+//! term clears every cached prompt (see [`PromptCacheRepository::invalidate_all`]).
+//!
+//! # Usage
+//!
+//! ```rust,ignore
+//! let term = BannedTermRepository::create(&conn, "gore")?;
+//! let terms = BannedTermRepository::find_all(&conn)?;
+//! ```
+
+use chrono::Utc;
+use rusqlite::{params, Connection};
+
+use super::PromptCacheRepository;
+use crate::domain::banned_term::{normalize_term, BannedTerm};
+use crate::domain::collation;
+use crate::domain::repository::BannedTermRepo;
+use crate::error::AppError;
+
+/// Repository for banned term database operations.
+///
+/// This struct contains no state; all methods take a connection reference
+/// and can be composed within external transactions.
+pub struct BannedTermRepository;
+
+impl BannedTermRepository {
+    /// Retrieves all banned terms, ordered alphabetically (case- and
+    /// accent-insensitive).
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Database` for database errors.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub fn find_all(conn: &Connection) -> Result<Vec<BannedTerm>, AppError> {
+        let mut stmt = conn.prepare(
+            r"
+            SELECT id, term, created_at
+            FROM banned_terms
+            ",
+        )?;
+
+        let mut terms = stmt
+            .query_map([], Self::row_to_term)?
+            .collect::<Result<Vec<_>, _>>()?;
+        terms.sort_by(|a, b| collation::compare(&a.term, &b.term));
+
+        Ok(terms)
+    }
+
+    /// Adds a term to the blacklist and clears all cached prompts.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `term` - The word or phrase to ban; surrounding and repeated
+    ///   whitespace is removed
+    ///
+    /// # Returns
+    ///
+    /// The newly created banned term.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Validation` if the term has no letter or digit, or
+    /// is already banned (ignoring case).
+    /// Returns `AppError::Database` for other database errors.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub fn create(conn: &Connection, term: &str) -> Result<BannedTerm, AppError> {
+        let term = normalize_term(term)?;
+
+        let exists: bool = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM banned_terms WHERE term = ?1)",
+            [&term],
+            |row| row.get(0),
+        )?;
+        if exists {
+            return Err(AppError::Validation(format!(
+                "The term '{term}' is already banned"
+            )));
+        }
+
+        let banned = BannedTerm::new(term);
+        conn.execute(
+            "INSERT INTO banned_terms (id, term, created_at) VALUES (?1, ?2, ?3)",
+            params![banned.id, banned.term, banned.created_at.to_rfc3339()],
+        )?;
+        PromptCacheRepository::invalidate_all(conn)?;
+
+        Ok(banned)
+    }
+
+    /// Removes a term from the blacklist and clears all cached prompts.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `id` - The banned term's UUID
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::NotFound` if the term doesn't exist.
+    /// Returns `AppError::Database` for other database errors.
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id))]
+    pub fn delete(conn: &Connection, id: &str) -> Result<(), AppError> {
+        let rows = conn.execute("DELETE FROM banned_terms WHERE id = ?1", [id])?;
+        if rows == 0 {
+            return Err(AppError::NotFound(format!(
+                "Banned term with id '{id}' not found"
+            )));
+        }
+        PromptCacheRepository::invalidate_all(conn)?;
+        Ok(())
+    }
+
+    /// Helper to convert a row to a `BannedTerm`
+    ///
+    /// Column mapping:
+    /// 0: id, 1: term, 2: `created_at`
+    fn row_to_term(row: &rusqlite::Row) -> rusqlite::Result<BannedTerm> {
+        Ok(BannedTerm {
+            id: row.get(0)?,
+            term: row.get(1)?,
+            // Timestamps stored as RFC3339 strings; fallback to now if parsing fails
+            created_at: chrono::DateTime::parse_from_rfc3339(&row.get::<_, String>(2)?)
+                .map_or_else(|_| Utc::now(), |dt| dt.with_timezone(&Utc)),
+        })
+    }
+}
+
+/// The `SQLite` implementation of [`BannedTermRepo`], delegating to [`BannedTermRepository`].
+impl BannedTermRepo for Connection {
+    fn find_all(&self) -> Result<Vec<BannedTerm>, AppError> {
+        BannedTermRepository::find_all(self)
+    }
+
+    fn create(&self, term: &str) -> Result<BannedTerm, AppError> {
+        BannedTermRepository::create(self, term)
+    }
+}
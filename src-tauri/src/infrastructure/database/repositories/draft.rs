@@ -0,0 +1,169 @@
+//! Draft Repository
+//!
+//! Provides data access operations for autosaved persona drafts.
+//! All methods are stateless and take a connection reference as their first parameter.
+//!
+//! Tags and tokens are stored as JSON. Draft names are not unique.
+//!
+//! # Usage
+//!
+//! ```rust,ignore
+//! DraftRepository::save(&conn, &draft)?;
+//! let drafts = DraftRepository::find_all(&conn)?;
+//! ```
+
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection};
+
+use crate::domain::draft::PersonaDraft;
+use crate::domain::persona::ContentRating;
+use crate::error::AppError;
+
+/// Repository for persona draft database operations.
+///
+/// This struct contains no state; all methods take a connection reference
+/// and can be composed within external transactions.
+pub struct DraftRepository;
+
+impl DraftRepository {
+    /// Finds a draft by its unique identifier.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `id` - The draft's UUID
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::NotFound` if no draft exists with the given ID.
+    /// Returns `AppError::Database` for other database errors.
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id))]
+    pub fn find_by_id(conn: &Connection, id: &str) -> Result<PersonaDraft, AppError> {
+        conn.query_row(
+            r"
+            SELECT id, name, description, tags, content_rating, tokens, created_at, updated_at
+            FROM drafts WHERE id = ?1
+            ",
+            [id],
+            Self::row_to_draft,
+        )
+        .map_err(|e| match e {
+            rusqlite::Error::QueryReturnedNoRows => {
+                AppError::NotFound(format!("Draft with id '{id}' not found"))
+            }
+            _ => AppError::Database(e),
+        })
+    }
+
+    /// Retrieves all drafts, most recently saved first.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Database` for database errors.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub fn find_all(conn: &Connection) -> Result<Vec<PersonaDraft>, AppError> {
+        let mut stmt = conn.prepare(
+            r"
+            SELECT id, name, description, tags, content_rating, tokens, created_at, updated_at
+            FROM drafts
+            ORDER BY updated_at DESC
+            ",
+        )?;
+
+        let drafts = stmt
+            .query_map([], Self::row_to_draft)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(drafts)
+    }
+
+    /// Inserts a draft, or overwrites the stored one with the same ID.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `draft` - The draft to store
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Database` for database errors.
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %draft.id))]
+    pub fn save(conn: &Connection, draft: &PersonaDraft) -> Result<(), AppError> {
+        conn.execute(
+            r"
+            INSERT INTO drafts (id, name, description, tags, content_rating, tokens,
+                created_at, updated_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+            ON CONFLICT(id) DO UPDATE SET
+                name = excluded.name,
+                description = excluded.description,
+                tags = excluded.tags,
+                content_rating = excluded.content_rating,
+                tokens = excluded.tokens,
+                updated_at = excluded.updated_at
+            ",
+            params![
+                draft.id,
+                draft.name,
+                draft.description,
+                serde_json::to_string(&draft.tags)?,
+                draft.content_rating.as_str(),
+                serde_json::to_string(&draft.tokens)?,
+                draft.created_at.to_rfc3339(),
+                draft.updated_at.to_rfc3339(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Deletes a draft.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `id` - The draft's UUID
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::NotFound` if the draft doesn't exist.
+    /// Returns `AppError::Database` for other database errors.
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id))]
+    pub fn delete(conn: &Connection, id: &str) -> Result<(), AppError> {
+        let rows = conn.execute("DELETE FROM drafts WHERE id = ?1", [id])?;
+        if rows == 0 {
+            return Err(AppError::NotFound(format!(
+                "Draft with id '{id}' not found"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Helper to convert a row to a `PersonaDraft`
+    ///
+    /// Column mapping:
+    /// 0: id, 1: name, 2: description, 3: tags (JSON), 4: `content_rating`, 5: tokens (JSON),
+    /// 6: `created_at`, 7: `updated_at`
+    fn row_to_draft(row: &rusqlite::Row) -> rusqlite::Result<PersonaDraft> {
+        let tags_json: String = row.get(3)?;
+        let tokens_json: String = row.get(5)?;
+        let parse_time = |value: &str| {
+            DateTime::parse_from_rfc3339(value)
+                .map_or_else(|_| Utc::now(), |dt| dt.with_timezone(&Utc))
+        };
+
+        Ok(PersonaDraft {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            description: row.get(2)?,
+            tags: serde_json::from_str(&tags_json).unwrap_or_default(),
+            content_rating: ContentRating::parse(&row.get::<_, String>(4)?).unwrap_or_default(),
+            tokens: serde_json::from_str(&tokens_json).unwrap_or_default(),
+            created_at: parse_time(&row.get::<_, String>(6)?),
+            updated_at: parse_time(&row.get::<_, String>(7)?),
+        })
+    }
+}
@@ -0,0 +1,119 @@
+//! Feature Usage Repository
+//!
+//! Provides data access operations for the opt-in feature usage counters (see
+//! `domain::telemetry`). All methods are stateless and take a connection
+//! reference as their first parameter.
+//!
+//! Days are stored as `YYYY-MM-DD` strings; no finer time is kept.
+//!
+//! # Usage
+//!
+//! ```rust,ignore
+//! FeatureUsageRepository::increment(&conn, Feature::ComposePrompt, Utc::now().date_naive())?;
+//! let usage = FeatureUsageRepository::find_all(&conn)?;
+//! ```
+
+use chrono::NaiveDate;
+use rusqlite::{params, Connection};
+
+use crate::domain::telemetry::{Feature, FeatureUsage};
+use crate::error::AppError;
+
+/// Format of the stored days.
+const DAY_FORMAT: &str = "%Y-%m-%d";
+
+/// Repository for feature usage counter database operations.
+///
+/// This struct contains no state; all methods take a connection reference
+/// and can be composed within external transactions.
+pub struct FeatureUsageRepository;
+
+impl FeatureUsageRepository {
+    /// Counts one use of a feature.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `feature` - The feature used
+    /// * `day` - Day of the use
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Database` for database errors.
+    #[tracing::instrument(level = "debug", skip_all, fields(feature = feature.as_str()))]
+    pub fn increment(conn: &Connection, feature: Feature, day: NaiveDate) -> Result<(), AppError> {
+        let day = day.format(DAY_FORMAT).to_string();
+        conn.execute(
+            r"
+            INSERT INTO feature_usage (feature, count, first_used_on, last_used_on)
+            VALUES (?1, 1, ?2, ?2)
+            ON CONFLICT(feature) DO UPDATE SET
+                count = count + 1,
+                last_used_on = excluded.last_used_on
+            ",
+            params![feature.as_str(), day],
+        )?;
+        Ok(())
+    }
+
+    /// Retrieves all counters, most used first.
+    ///
+    /// Counters of features this version does not know (e.g., recorded by a
+    /// newer version) are left out.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Database` for database errors.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub fn find_all(conn: &Connection) -> Result<Vec<FeatureUsage>, AppError> {
+        let mut stmt = conn.prepare(
+            r"
+            SELECT feature, count, first_used_on, last_used_on
+            FROM feature_usage
+            ORDER BY count DESC, feature
+            ",
+        )?;
+
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, i64>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, String>(3)?,
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|(feature, count, first, last)| {
+                Some(FeatureUsage {
+                    feature: Feature::parse(&feature)?,
+                    count: u64::try_from(count).unwrap_or_default(),
+                    first_used_on: NaiveDate::parse_from_str(&first, DAY_FORMAT).ok()?,
+                    last_used_on: NaiveDate::parse_from_str(&last, DAY_FORMAT).ok()?,
+                })
+            })
+            .collect())
+    }
+
+    /// Deletes all counters.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Database` for database errors.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub fn clear(conn: &Connection) -> Result<(), AppError> {
+        conn.execute("DELETE FROM feature_usage", [])?;
+        Ok(())
+    }
+}
@@ -0,0 +1,337 @@
+//! Generated Image Repository
+//!
+//! Provides data access operations for the result gallery (see
+//! `domain::gallery`). All methods are stateless and take a connection
+//! reference as their first parameter.
+//!
+//! Rows only reference image files; no method touches the files themselves.
+//! Each row also keeps a JSON snapshot of the persona at attach time, read only
+//! when a reproduction bundle is exported, and a cached face embedding used to
+//! group images by face.
+//!
+//! # Usage
+//!
+//! ```rust,ignore
+//! GeneratedImageRepository::create(&conn, &image, Some(&snapshot))?;
+//! let gallery = GeneratedImageRepository::find_by_persona(&conn, &persona_id, false)?;
+//! ```
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection};
+
+use crate::domain::export::PersonaExport;
+use crate::domain::gallery::GeneratedImage;
+use crate::domain::persona::GenerationParams;
+use crate::error::AppError;
+
+/// Repository for generated image database operations.
+///
+/// This struct contains no state; all methods take a connection reference
+/// and can be composed within external transactions.
+pub struct GeneratedImageRepository;
+
+impl GeneratedImageRepository {
+    /// Adds an image to a persona's gallery.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `image` - The image to store
+    /// * `persona_snapshot` - The persona as it is now, for reproduction bundles
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Database` for database errors, including a persona
+    /// that does not exist.
+    #[tracing::instrument(level = "debug", skip_all, fields(persona_id = %image.persona_id))]
+    pub fn create(
+        conn: &Connection,
+        image: &GeneratedImage,
+        persona_snapshot: Option<&PersonaExport>,
+    ) -> Result<(), AppError> {
+        let params_json = image
+            .params
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()?;
+        let snapshot_json = persona_snapshot.map(serde_json::to_string).transpose()?;
+
+        conn.execute(
+            r"
+            INSERT INTO generated_images
+                (id, persona_id, path, prompt_hash, seed, params, starred, created_at,
+                 positive_prompt, negative_prompt, persona_snapshot)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+            ",
+            params![
+                image.id,
+                image.persona_id,
+                image.path,
+                image.prompt_hash,
+                image.seed,
+                params_json,
+                image.starred,
+                image.created_at.to_rfc3339(),
+                image.positive_prompt,
+                image.negative_prompt,
+                snapshot_json,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Finds a gallery image by its unique identifier.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `id` - The image's UUID
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::NotFound` if no image exists with the given ID.
+    /// Returns `AppError::Database` for other database errors.
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id))]
+    pub fn find_by_id(conn: &Connection, id: &str) -> Result<GeneratedImage, AppError> {
+        conn.query_row(
+            r"
+            SELECT id, persona_id, path, prompt_hash, seed, params, starred, created_at,
+                   positive_prompt, negative_prompt
+            FROM generated_images WHERE id = ?1
+            ",
+            [id],
+            Self::row_to_image,
+        )
+        .map_err(|e| match e {
+            rusqlite::Error::QueryReturnedNoRows => {
+                AppError::NotFound(format!("Generated image with id '{id}' not found"))
+            }
+            _ => AppError::Database(e),
+        })
+    }
+
+    /// Retrieves a persona's gallery, newest first.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `persona_id` - The persona's UUID
+    /// * `starred_only` - Whether to return favorites only
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Database` for database errors.
+    #[tracing::instrument(level = "debug", skip_all, fields(persona_id = %persona_id))]
+    pub fn find_by_persona(
+        conn: &Connection,
+        persona_id: &str,
+        starred_only: bool,
+    ) -> Result<Vec<GeneratedImage>, AppError> {
+        let mut stmt = conn.prepare(
+            r"
+            SELECT id, persona_id, path, prompt_hash, seed, params, starred, created_at,
+                   positive_prompt, negative_prompt
+            FROM generated_images
+            WHERE persona_id = ?1 AND (starred OR NOT ?2)
+            ORDER BY created_at DESC
+            ",
+        )?;
+
+        let images = stmt
+            .query_map(params![persona_id, starred_only], Self::row_to_image)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(images)
+    }
+
+    /// Retrieves the persona snapshot recorded when an image was attached.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `id` - The image's UUID
+    ///
+    /// # Returns
+    ///
+    /// The snapshot, or `None` if none was recorded or it can no longer be read.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::NotFound` if the image doesn't exist.
+    /// Returns `AppError::Database` for other database errors.
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id))]
+    pub fn find_persona_snapshot(
+        conn: &Connection,
+        id: &str,
+    ) -> Result<Option<PersonaExport>, AppError> {
+        let json: Option<String> = conn
+            .query_row(
+                "SELECT persona_snapshot FROM generated_images WHERE id = ?1",
+                [id],
+                |row| row.get(0),
+            )
+            .map_err(|e| match e {
+                rusqlite::Error::QueryReturnedNoRows => {
+                    AppError::NotFound(format!("Generated image with id '{id}' not found"))
+                }
+                _ => AppError::Database(e),
+            })?;
+
+        Ok(json.and_then(|json| serde_json::from_str(&json).ok()))
+    }
+
+    /// Retrieves the cached face embeddings of a persona's gallery images.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `persona_id` - The persona's UUID
+    ///
+    /// # Returns
+    ///
+    /// Embeddings by image ID; empty for images without a face. Images not
+    /// embedded yet, or whose embedding can no longer be read, are missing.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Database` for database errors.
+    #[tracing::instrument(level = "debug", skip_all, fields(persona_id = %persona_id))]
+    pub fn find_face_embeddings(
+        conn: &Connection,
+        persona_id: &str,
+    ) -> Result<HashMap<String, Vec<f32>>, AppError> {
+        let mut stmt = conn.prepare(
+            r"
+            SELECT id, face_embedding FROM generated_images
+            WHERE persona_id = ?1 AND face_embedding IS NOT NULL
+            ",
+        )?;
+
+        let rows = stmt
+            .query_map([persona_id], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|(id, json)| Some((id, serde_json::from_str(&json).ok()?)))
+            .collect())
+    }
+
+    /// Caches an image's face embedding.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `id` - The image's UUID
+    /// * `embedding` - The embedding, empty if the image shows no face
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Database` for database errors.
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id))]
+    pub fn set_face_embedding(
+        conn: &Connection,
+        id: &str,
+        embedding: &[f32],
+    ) -> Result<(), AppError> {
+        conn.execute(
+            "UPDATE generated_images SET face_embedding = ?1 WHERE id = ?2",
+            params![serde_json::to_string(embedding)?, id],
+        )?;
+        Ok(())
+    }
+
+    /// Marks an image as a favorite, or unmarks it.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `id` - The image's UUID
+    /// * `starred` - Whether the image is a favorite
+    ///
+    /// # Returns
+    ///
+    /// The updated image.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::NotFound` if the image doesn't exist.
+    /// Returns `AppError::Database` for other database errors.
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id))]
+    pub fn set_starred(
+        conn: &Connection,
+        id: &str,
+        starred: bool,
+    ) -> Result<GeneratedImage, AppError> {
+        let rows = conn.execute(
+            "UPDATE generated_images SET starred = ?1 WHERE id = ?2",
+            params![starred, id],
+        )?;
+        if rows == 0 {
+            return Err(AppError::NotFound(format!(
+                "Generated image with id '{id}' not found"
+            )));
+        }
+        Self::find_by_id(conn, id)
+    }
+
+    /// Removes gallery entries added before a cutoff.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `before` - Entries added before this time are removed
+    /// * `include_starred` - Whether favorites are removed too
+    ///
+    /// # Returns
+    ///
+    /// The number of entries removed.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Database` for database errors.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub fn purge_before(
+        conn: &Connection,
+        before: DateTime<Utc>,
+        include_starred: bool,
+    ) -> Result<usize, AppError> {
+        let removed = conn.execute(
+            "DELETE FROM generated_images WHERE created_at < ?1 AND (NOT starred OR ?2)",
+            params![before.to_rfc3339(), include_starred],
+        )?;
+        Ok(removed)
+    }
+
+    /// Helper to convert a row to a `GeneratedImage`
+    ///
+    /// Column mapping:
+    /// 0: id, 1: `persona_id`, 2: path, 3: `prompt_hash`, 4: seed,
+    /// 5: params (JSON), 6: starred, 7: `created_at`, 8: `positive_prompt`,
+    /// 9: `negative_prompt`
+    fn row_to_image(row: &rusqlite::Row) -> rusqlite::Result<GeneratedImage> {
+        // Parameters stored as JSON; unreadable snapshots are treated as unknown
+        let params: Option<GenerationParams> = row
+            .get::<_, Option<String>>(5)?
+            .and_then(|json| serde_json::from_str(&json).ok());
+
+        Ok(GeneratedImage {
+            id: row.get(0)?,
+            persona_id: row.get(1)?,
+            path: row.get(2)?,
+            prompt_hash: row.get(3)?,
+            positive_prompt: row.get(8)?,
+            negative_prompt: row.get(9)?,
+            seed: row.get(4)?,
+            params,
+            starred: row.get(6)?,
+            // Timestamps stored as RFC3339 strings; fallback to now if parsing fails
+            created_at: chrono::DateTime::parse_from_rfc3339(&row.get::<_, String>(7)?)
+                .map_or_else(|_| Utc::now(), |dt| dt.with_timezone(&Utc)),
+        })
+    }
+}
@@ -0,0 +1,154 @@
+//! Granularity Level Repository
+//!
+//! Provides data access operations for granularity levels.
+//! All methods are stateless and take a connection reference as their first parameter.
+//!
+//! # Usage
+//!
+//! ```rust,ignore
+//! let levels = GranularityRepository::find_all(&conn)?;
+//! let custom = GranularityRepository::create(&conn, "accessories", "Accessories")?;
+//! ```
+
+use chrono::Utc;
+use rusqlite::{params, Connection};
+
+use crate::domain::repository::GranularityRepo;
+use crate::domain::token::GranularityLevel;
+use crate::error::AppError;
+
+/// Repository for granularity level database operations.
+///
+/// This struct contains no state; all methods take a connection reference
+/// and can be composed within external transactions.
+pub struct GranularityRepository;
+
+impl GranularityRepository {
+    /// Retrieves all granularity levels.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    ///
+    /// # Returns
+    ///
+    /// All levels ordered by `display_order`, ties broken by ID.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Database` for database errors.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub fn find_all(conn: &Connection) -> Result<Vec<GranularityLevel>, AppError> {
+        let mut stmt = conn.prepare(
+            r"
+            SELECT id, name, color, display_order, is_default, created_at
+            FROM granularity_levels
+            ORDER BY display_order, id
+            ",
+        )?;
+
+        let levels = stmt
+            .query_map([], Self::row_to_level)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(levels)
+    }
+
+    /// Creates a custom granularity level after every existing level.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `id` - ID of the new level (e.g., "accessories")
+    /// * `name` - Display name; trimmed
+    ///
+    /// # Returns
+    ///
+    /// The new level, using [`GranularityLevel::FALLBACK_COLOR`].
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Validation` if the ID or name is empty or the ID is taken.
+    /// Returns `AppError::Database` for other database errors.
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id))]
+    pub fn create(conn: &Connection, id: &str, name: &str) -> Result<GranularityLevel, AppError> {
+        let id = id.trim();
+        let name = name.trim();
+        if id.is_empty() || name.is_empty() {
+            return Err(AppError::Validation(
+                "Granularity level ID and name are required".to_string(),
+            ));
+        }
+
+        let exists: bool = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM granularity_levels WHERE id = ?1)",
+            [id],
+            |row| row.get(0),
+        )?;
+        if exists {
+            return Err(AppError::Validation(format!(
+                "Granularity level '{id}' already exists"
+            )));
+        }
+
+        let display_order: i32 = conn.query_row(
+            "SELECT COALESCE(MAX(display_order), -1) + 1 FROM granularity_levels",
+            [],
+            |row| row.get(0),
+        )?;
+
+        let level = GranularityLevel {
+            id: id.to_string(),
+            name: name.to_string(),
+            color: GranularityLevel::FALLBACK_COLOR.to_string(),
+            display_order,
+            is_default: false,
+            created_at: Utc::now(),
+        };
+
+        conn.execute(
+            r"
+            INSERT INTO granularity_levels (id, name, color, display_order, is_default, created_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            ",
+            params![
+                level.id,
+                level.name,
+                level.color,
+                level.display_order,
+                level.is_default,
+                level.created_at.to_rfc3339(),
+            ],
+        )?;
+
+        Ok(level)
+    }
+
+    /// Helper function to convert a row to a `GranularityLevel`
+    ///
+    /// Column mapping:
+    /// 0: id, 1: name, 2: color, 3: `display_order`, 4: `is_default`, 5: `created_at`
+    fn row_to_level(row: &rusqlite::Row) -> Result<GranularityLevel, rusqlite::Error> {
+        Ok(GranularityLevel {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            color: row.get(2)?,
+            display_order: row.get(3)?,
+            is_default: row.get(4)?,
+            // Timestamps stored as RFC3339 strings; fallback to now if parsing fails
+            created_at: chrono::DateTime::parse_from_rfc3339(&row.get::<_, String>(5)?)
+                .map_or_else(|_| Utc::now(), |dt| dt.with_timezone(&Utc)),
+        })
+    }
+}
+
+/// The `SQLite` implementation of [`GranularityRepo`], delegating to [`GranularityRepository`].
+impl GranularityRepo for Connection {
+    fn find_all(&self) -> Result<Vec<GranularityLevel>, AppError> {
+        GranularityRepository::find_all(self)
+    }
+
+    fn create(&self, id: &str, name: &str) -> Result<GranularityLevel, AppError> {
+        GranularityRepository::create(self, id, name)
+    }
+}
@@ -8,15 +8,67 @@
 //! - **Stateless**: Repository structs contain no state; methods take connection references
 //! - **Type-Safe**: All queries return strongly-typed domain entities
 //! - **SQL Encapsulation**: Raw SQL is contained within repository methods
-//! - **Transaction Support**: Methods can be composed within external transactions
+//! - **Transaction Support**: Methods can be composed within external transactions;
+//!   commands group multi-write operations with `unit_of_work`
 //!
 //! # Available Repositories
 //!
 //! - [`PersonaRepository`]: CRUD operations for personas and generation parameters
 //! - [`TokenRepository`]: Token management including batch operations and reordering
+//! - [`TokenRevisionRepository`]: Previous content and weight of edited tokens
+//! - [`GranularityRepository`]: Persisted granularity levels (names, colors, order)
+//! - [`PromptCacheRepository`]: Last composed prompt per persona, invalidated on change
+//! - [`TokenCountCacheRepository`]: Token counts by text hash and tokenizer
+//! - [`SettingsRepository`]: Backend application settings
+//! - [`SmartCollectionRepository`]: Saved persona queries
+//! - [`ActivityRepository`]: Latest opened, modified, and composed time per persona
+//! - [`SearchRepository`]: Full-text quick search across personas, tokens, and collections
+//! - [`BannedTermRepository`]: User-managed banned terms blacklist
+//! - [`TokenAliasRepository`]: User-maintained token variants and their phrasing
+//! - [`StatsRepository`]: Aggregate library statistics for the dashboard
+//! - [`FeatureUsageRepository`]: Opt-in feature usage counters
+//! - [`GeneratedImageRepository`]: Result gallery of generated images per persona
+//! - [`ScheduledTaskRepository`]: Recurring background tasks and their last run
+//! - [`PendingSuggestionRepository`]: Inbox of AI-suggested tokens awaiting review
+//! - [`PersonaWizardRepository`]: Drafts of the persona creation wizard
+//! - [`DraftRepository`]: Autosaved incomplete personas of the create form
 
+pub mod activity;
+pub mod banned_term;
+pub mod draft;
+pub mod feature_usage;
+pub mod generated_image;
+pub mod granularity;
+pub mod pending_suggestion;
 pub mod persona;
+pub mod persona_wizard;
+pub mod prompt_cache;
+pub mod scheduled_task;
+pub mod search;
+pub mod settings;
+pub mod smart_collection;
+pub mod stats;
 pub mod token;
+pub mod token_alias;
+pub mod token_count_cache;
+pub mod token_revision;
 
+pub use activity::ActivityRepository;
+pub use banned_term::BannedTermRepository;
+pub use draft::DraftRepository;
+pub use feature_usage::FeatureUsageRepository;
+pub use generated_image::GeneratedImageRepository;
+pub use granularity::GranularityRepository;
+pub use pending_suggestion::PendingSuggestionRepository;
 pub use persona::PersonaRepository;
+pub use persona_wizard::PersonaWizardRepository;
+pub use prompt_cache::PromptCacheRepository;
+pub use scheduled_task::ScheduledTaskRepository;
+pub use search::SearchRepository;
+pub use settings::SettingsRepository;
+pub use smart_collection::SmartCollectionRepository;
+pub use stats::StatsRepository;
 pub use token::TokenRepository;
+pub use token_alias::TokenAliasRepository;
+pub use token_count_cache::TokenCountCacheRepository;
+pub use token_revision::TokenRevisionRepository;
@@ -0,0 +1,220 @@
+//! Pending Suggestion Repository
+//!
+//! Provides data access operations for the inbox of AI-suggested tokens.
+//! All methods are stateless and take a connection reference as their first parameter.
+//!
+//! A persona holds each suggestion at most once (same granularity, polarity,
+//! and content, ignoring case); staging it again is a no-op.
+//!
+//! # Usage
+//!
+//! ```rust,ignore
+//! let staged = PendingSuggestionRepository::stage(&conn, suggestions)?;
+//! let pending = PendingSuggestionRepository::find_by_persona(&conn, &persona_id)?;
+//! ```
+
+use chrono::Utc;
+use rusqlite::{params, Connection};
+
+use crate::domain::suggestion::{PendingSuggestion, SuggestionSource};
+use crate::domain::token::TokenPolarity;
+use crate::error::AppError;
+
+/// Repository for pending suggestion database operations.
+///
+/// This struct contains no state; all methods take a connection reference
+/// and can be composed within external transactions.
+pub struct PendingSuggestionRepository;
+
+impl PendingSuggestionRepository {
+    /// Retrieves every pending suggestion, newest first.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Database` for database errors.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub fn find_all(conn: &Connection) -> Result<Vec<PendingSuggestion>, AppError> {
+        let mut stmt = conn.prepare(
+            r"
+            SELECT id, persona_id, source, task_id, granularity_id, polarity, content, weight,
+                   rationale, created_at
+            FROM pending_suggestions
+            ORDER BY created_at DESC, rowid
+            ",
+        )?;
+
+        let suggestions = stmt
+            .query_map([], Self::row_to_suggestion)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(suggestions)
+    }
+
+    /// Retrieves a persona's pending suggestions, newest first.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `persona_id` - UUID of the persona
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Database` for database errors.
+    #[tracing::instrument(level = "debug", skip_all, fields(persona_id = %persona_id))]
+    pub fn find_by_persona(
+        conn: &Connection,
+        persona_id: &str,
+    ) -> Result<Vec<PendingSuggestion>, AppError> {
+        let mut stmt = conn.prepare(
+            r"
+            SELECT id, persona_id, source, task_id, granularity_id, polarity, content, weight,
+                   rationale, created_at
+            FROM pending_suggestions
+            WHERE persona_id = ?1
+            ORDER BY created_at DESC, rowid
+            ",
+        )?;
+
+        let suggestions = stmt
+            .query_map([persona_id], Self::row_to_suggestion)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(suggestions)
+    }
+
+    /// Retrieves pending suggestions by ID, in the order given.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `ids` - UUIDs of the suggestions
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::NotFound` if one of the suggestions doesn't exist.
+    /// Returns `AppError::Database` for other database errors.
+    #[tracing::instrument(level = "debug", skip_all, fields(count = ids.len()))]
+    pub fn find_by_ids(
+        conn: &Connection,
+        ids: &[String],
+    ) -> Result<Vec<PendingSuggestion>, AppError> {
+        ids.iter()
+            .map(|id| {
+                conn.query_row(
+                    r"
+                    SELECT id, persona_id, source, task_id, granularity_id, polarity, content,
+                           weight, rationale, created_at
+                    FROM pending_suggestions WHERE id = ?1
+                    ",
+                    [id],
+                    Self::row_to_suggestion,
+                )
+                .map_err(|e| match e {
+                    rusqlite::Error::QueryReturnedNoRows => {
+                        AppError::NotFound(format!("Pending suggestion with id '{id}' not found"))
+                    }
+                    _ => AppError::Database(e),
+                })
+            })
+            .collect()
+    }
+
+    /// Stages suggestions, skipping those the persona already holds.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `suggestions` - The suggestions to stage
+    ///
+    /// # Returns
+    ///
+    /// The suggestions actually staged.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Database` for database errors (e.g., a deleted persona).
+    #[tracing::instrument(level = "debug", skip_all, fields(count = suggestions.len()))]
+    pub fn stage(
+        conn: &Connection,
+        suggestions: Vec<PendingSuggestion>,
+    ) -> Result<Vec<PendingSuggestion>, AppError> {
+        let mut stmt = conn.prepare(
+            r"
+            INSERT OR IGNORE INTO pending_suggestions (id, persona_id, source, task_id,
+                granularity_id, polarity, content, weight, rationale, created_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+            ",
+        )?;
+
+        let mut staged = Vec::new();
+        for suggestion in suggestions {
+            let rows = stmt.execute(params![
+                suggestion.id,
+                suggestion.persona_id,
+                suggestion.source.as_str(),
+                suggestion.task_id,
+                suggestion.granularity_id,
+                suggestion.polarity.as_str(),
+                suggestion.content,
+                suggestion.weight,
+                suggestion.rationale,
+                suggestion.created_at.to_rfc3339(),
+            ])?;
+            if rows > 0 {
+                staged.push(suggestion);
+            }
+        }
+
+        Ok(staged)
+    }
+
+    /// Deletes a pending suggestion.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `id` - The suggestion's UUID
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::NotFound` if the suggestion doesn't exist.
+    /// Returns `AppError::Database` for other database errors.
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id))]
+    pub fn delete(conn: &Connection, id: &str) -> Result<(), AppError> {
+        let rows = conn.execute("DELETE FROM pending_suggestions WHERE id = ?1", [id])?;
+        if rows == 0 {
+            return Err(AppError::NotFound(format!(
+                "Pending suggestion with id '{id}' not found"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Helper to convert a row to a `PendingSuggestion`
+    ///
+    /// Column mapping:
+    /// 0: id, 1: `persona_id`, 2: source, 3: `task_id`, 4: `granularity_id`, 5: polarity,
+    /// 6: content, 7: weight, 8: rationale, 9: `created_at`
+    fn row_to_suggestion(row: &rusqlite::Row) -> rusqlite::Result<PendingSuggestion> {
+        Ok(PendingSuggestion {
+            id: row.get(0)?,
+            persona_id: row.get(1)?,
+            source: SuggestionSource::parse(&row.get::<_, String>(2)?)
+                .unwrap_or(SuggestionSource::TokenSuggestions),
+            task_id: row.get(3)?,
+            granularity_id: row.get(4)?,
+            polarity: TokenPolarity::parse(&row.get::<_, String>(5)?)
+                .unwrap_or(TokenPolarity::Positive),
+            content: row.get(6)?,
+            weight: row.get(7)?,
+            rationale: row.get(8)?,
+            // Timestamps stored as RFC3339 strings; fallback to now if parsing fails
+            created_at: chrono::DateTime::parse_from_rfc3339(&row.get::<_, String>(9)?)
+                .map_or_else(|_| Utc::now(), |dt| dt.with_timezone(&Utc)),
+        })
+    }
+}
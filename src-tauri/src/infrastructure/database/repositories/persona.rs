@@ -1,6 +1,7 @@
 //! Persona Repository
 //!
-//! Provides data access operations for personas and their generation parameters.
+//! Provides data access operations for personas, their generation parameters,
+//! their composition defaults, and their link to a base persona.
 //! All methods are stateless and take a connection reference as their first parameter.
 //!
 //! # Usage
@@ -10,14 +11,28 @@
 //! let found = PersonaRepository::find_by_id(&conn, &persona.id)?;
 //! ```
 
+use std::collections::HashSet;
+
 use chrono::Utc;
-use rusqlite::{params, Connection};
+use rusqlite::{params, Connection, OptionalExtension};
 
+use crate::domain::activity::ActivityKind;
+use crate::domain::ai::normalize_output_language;
+use crate::domain::inheritance::{
+    overridden_fields, resolve_params, ParamField, MAX_INHERITANCE_DEPTH,
+};
+use crate::domain::naming::{name_key, normalize_name, validate_name};
 use crate::domain::persona::{
-    CreatePersonaRequest, GenerationParams, Persona, UpdatePersonaRequest,
+    compute_content_hash, validate_color, ContentRating, CreatePersonaRequest, GenerationParams,
+    Persona, UpdatePersonaRequest,
 };
+use crate::domain::prompt::{CompositionDefaults, WeightSyntax};
+use crate::domain::repository::PersonaRepo;
+use crate::domain::token::Token;
 use crate::error::AppError;
 
+use super::{ActivityRepository, GranularityRepository, PromptCacheRepository, TokenRepository};
+
 /// Repository for persona database operations.
 ///
 /// This struct contains no state; all methods take a connection reference
@@ -31,11 +46,13 @@ impl PersonaRepository {
     /// Use `create()` for the public API with validation.
     fn insert(conn: &Connection, persona: &Persona) -> Result<(), AppError> {
         let tags_json = serde_json::to_string(&persona.tags)?;
+        // A freshly inserted persona has no tokens yet
+        let content_hash = compute_content_hash(persona.description.as_deref(), &[]);
 
         conn.execute(
             r"
-            INSERT INTO personas (id, name, description, tags, ai_provider_id, ai_model_id, ai_instructions, created_at, updated_at)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+            INSERT INTO personas (id, name, description, tags, ai_provider_id, ai_model_id, ai_instructions, created_at, updated_at, content_hash, archived, color, content_rating, ai_output_language)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)
             ",
             params![
                 persona.id,
@@ -47,6 +64,11 @@ impl PersonaRepository {
                 persona.ai_instructions,
                 persona.created_at.to_rfc3339(),
                 persona.updated_at.to_rfc3339(),
+                content_hash,
+                persona.archived,
+                persona.color,
+                persona.content_rating.as_str(),
+                persona.ai_output_language,
             ],
         )?;
 
@@ -64,8 +86,10 @@ impl PersonaRepository {
     ) -> Result<(), AppError> {
         conn.execute(
             r"
-            INSERT INTO generation_params (persona_id, model_id, seed, steps, cfg_scale, sampler, scheduler)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+            INSERT INTO generation_params
+                (persona_id, model_id, seed, steps, cfg_scale, sampler, scheduler, width, height,
+                 conditioning)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
             ",
             params![
                 params.persona_id,
@@ -75,6 +99,9 @@ impl PersonaRepository {
                 params.cfg_scale,
                 params.sampler,
                 params.scheduler,
+                params.width,
+                params.height,
+                serde_json::to_string(&params.conditioning)?,
             ],
         )?;
         Ok(())
@@ -91,10 +118,12 @@ impl PersonaRepository {
     ///
     /// Returns `AppError::NotFound` if no persona exists with the given ID.
     /// Returns `AppError::Database` for other database errors.
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id))]
     pub fn find_by_id(conn: &Connection, id: &str) -> Result<Persona, AppError> {
         conn.query_row(
             r"
-            SELECT id, name, description, tags, ai_provider_id, ai_model_id, ai_instructions, created_at, updated_at
+            SELECT id, name, description, tags, ai_provider_id, ai_model_id, ai_instructions, created_at, updated_at, archived, color, content_rating,
+                   base_persona_id, inherited_granularities, param_overrides, ai_output_language
             FROM personas WHERE id = ?1
             ",
             [id],
@@ -113,11 +142,17 @@ impl PersonaRepository {
     /// Column mapping:
     /// 0: id, 1: name, 2: description, 3: tags (JSON),
     /// 4: `ai_provider_id`, 5: `ai_model_id`, 6: `ai_instructions`,
-    /// 7: `created_at`, 8: `updated_at`
+    /// 7: `created_at`, 8: `updated_at`, 9: archived, 10: color, 11: `content_rating`,
+    /// 12: `base_persona_id`, 13: `inherited_granularities` (JSON), 14: `param_overrides` (JSON),
+    /// 15: `ai_output_language`
     fn row_to_persona(row: &rusqlite::Row) -> rusqlite::Result<Persona> {
         // Tags stored as JSON array; fallback to empty vec if parsing fails
         let tags_json: String = row.get(3)?;
         let tags: Vec<String> = serde_json::from_str(&tags_json).unwrap_or_default();
+        let inherited_granularities: Vec<String> =
+            serde_json::from_str(&row.get::<_, String>(13)?).unwrap_or_default();
+        let param_overrides: Vec<ParamField> =
+            serde_json::from_str(&row.get::<_, String>(14)?).unwrap_or_default();
 
         Ok(Persona {
             id: row.get(0)?,
@@ -127,11 +162,19 @@ impl PersonaRepository {
             ai_provider_id: row.get(4)?,
             ai_model_id: row.get(5)?,
             ai_instructions: row.get(6)?,
+            ai_output_language: row.get(15)?,
             // Timestamps stored as RFC3339 strings; fallback to now if parsing fails
             created_at: chrono::DateTime::parse_from_rfc3339(&row.get::<_, String>(7)?)
                 .map_or_else(|_| Utc::now(), |dt| dt.with_timezone(&Utc)),
             updated_at: chrono::DateTime::parse_from_rfc3339(&row.get::<_, String>(8)?)
                 .map_or_else(|_| Utc::now(), |dt| dt.with_timezone(&Utc)),
+            archived: row.get(9)?,
+            color: row.get(10)?,
+            // Unknown ratings are treated as general
+            content_rating: ContentRating::parse(&row.get::<_, String>(11)?).unwrap_or_default(),
+            base_persona_id: row.get(12)?,
+            inherited_granularities,
+            param_overrides,
         })
     }
 
@@ -146,17 +189,23 @@ impl PersonaRepository {
     ///
     /// Returns `AppError::NotFound` if no parameters exist for the persona.
     /// Returns `AppError::Database` for other database errors.
+    #[tracing::instrument(level = "debug", skip_all, fields(persona_id = %persona_id))]
     pub fn find_generation_params(
         conn: &Connection,
         persona_id: &str,
     ) -> Result<GenerationParams, AppError> {
         conn.query_row(
             r"
-            SELECT persona_id, model_id, seed, steps, cfg_scale, sampler, scheduler
+            SELECT persona_id, model_id, seed, steps, cfg_scale, sampler, scheduler, width, height,
+                   conditioning
             FROM generation_params WHERE persona_id = ?1
             ",
             [persona_id],
             |row| {
+                // Conditioning stored as a JSON array; unreadable entries are dropped
+                let conditioning =
+                    serde_json::from_str(&row.get::<_, String>(9)?).unwrap_or_default();
+
                 Ok(GenerationParams {
                     persona_id: row.get(0)?,
                     model_id: row.get(1)?,
@@ -165,6 +214,9 @@ impl PersonaRepository {
                     cfg_scale: row.get(4)?,
                     sampler: row.get(5)?,
                     scheduler: row.get(6)?,
+                    width: row.get(7)?,
+                    height: row.get(8)?,
+                    conditioning,
                 })
             },
         )
@@ -176,6 +228,47 @@ impl PersonaRepository {
         })
     }
 
+    /// Finds a persona's generation parameters with those it inherits from
+    /// its base persona (see `domain::inheritance`).
+    ///
+    /// Use this wherever a persona is composed or exported.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `persona_id` - The persona's UUID
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::NotFound` if no parameters exist for the persona.
+    /// Returns `AppError::Database` for other database errors.
+    #[tracing::instrument(level = "debug", skip_all, fields(persona_id = %persona_id))]
+    pub fn find_resolved_generation_params(
+        conn: &Connection,
+        persona_id: &str,
+    ) -> Result<GenerationParams, AppError> {
+        Self::resolve_generation_params(conn, persona_id, MAX_INHERITANCE_DEPTH)
+    }
+
+    /// Resolves a persona's generation parameters, following at most `depth`
+    /// more bases (internal helper).
+    fn resolve_generation_params(
+        conn: &Connection,
+        persona_id: &str,
+        depth: usize,
+    ) -> Result<GenerationParams, AppError> {
+        let local = Self::find_generation_params(conn, persona_id)?;
+        let persona = Self::find_by_id(conn, persona_id)?;
+
+        match &persona.base_persona_id {
+            Some(base_id) if depth > 0 => {
+                let base = Self::resolve_generation_params(conn, base_id, depth - 1)?;
+                Ok(resolve_params(&local, &base, &persona.param_overrides))
+            }
+            _ => Ok(local),
+        }
+    }
+
     /// Retrieves all personas, ordered by creation date (newest first).
     ///
     /// # Arguments
@@ -185,10 +278,12 @@ impl PersonaRepository {
     /// # Errors
     ///
     /// Returns `AppError::Database` for database errors.
+    #[tracing::instrument(level = "debug", skip_all)]
     pub fn find_all(conn: &Connection) -> Result<Vec<Persona>, AppError> {
         let mut stmt = conn.prepare(
             r"
-            SELECT id, name, description, tags, ai_provider_id, ai_model_id, ai_instructions, created_at, updated_at
+            SELECT id, name, description, tags, ai_provider_id, ai_model_id, ai_instructions, created_at, updated_at, archived, color, content_rating,
+                   base_persona_id, inherited_granularities, param_overrides, ai_output_language
             FROM personas ORDER BY created_at DESC
             ",
         )?;
@@ -217,7 +312,11 @@ impl PersonaRepository {
     /// # Errors
     ///
     /// Returns `AppError::NotFound` if the persona doesn't exist.
+    /// Returns `AppError::Conflict` if `expected_updated_at` is stale.
+    /// Returns `AppError::Validation` if a new name breaks the naming rules or is taken,
+    /// or the new color is not a hex color.
     /// Returns `AppError::Database` for other database errors.
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id))]
     pub fn update(
         conn: &Connection,
         id: &str,
@@ -225,9 +324,48 @@ impl PersonaRepository {
     ) -> Result<Persona, AppError> {
         // First fetch the existing persona
         let mut persona = Self::find_by_id(conn, id)?;
+        AppError::check_version(
+            request.expected_updated_at,
+            persona.updated_at,
+            &format!("Persona '{}'", persona.name),
+            &persona,
+        )?;
+
+        // A real rename must follow the naming rules; changing only case or
+        // spacing of the current name is always allowed
+        let name = match &request.name {
+            Some(name) if name_key(name) != name_key(&persona.name) => {
+                let name = validate_name(name)?;
+                if Self::name_exists(conn, &name, Some(id))? {
+                    return Err(AppError::Validation(format!(
+                        "A persona with name '{name}' already exists"
+                    )));
+                }
+                name
+            }
+            Some(name) => normalize_name(name),
+            None => persona.name.clone(),
+        };
+        let color = request
+            .color
+            .as_ref()
+            .map(|color| color.as_deref().map(validate_color).transpose())
+            .transpose()?;
+        let ai_output_language = request
+            .ai_output_language
+            .as_ref()
+            .map(|language| normalize_output_language(language.as_deref()))
+            .transpose()?;
 
         // Apply updates
         persona.update(request);
+        persona.name = name;
+        if let Some(color) = color {
+            persona.color = color;
+        }
+        if let Some(ai_output_language) = ai_output_language {
+            persona.ai_output_language = ai_output_language;
+        }
 
         let tags_json = serde_json::to_string(&persona.tags)?;
 
@@ -235,8 +373,8 @@ impl PersonaRepository {
         conn.execute(
             r"
             UPDATE personas
-            SET name = ?1, description = ?2, tags = ?3, ai_provider_id = ?4, ai_model_id = ?5, ai_instructions = ?6, updated_at = ?7
-            WHERE id = ?8
+            SET name = ?1, description = ?2, tags = ?3, ai_provider_id = ?4, ai_model_id = ?5, ai_instructions = ?6, updated_at = ?7, archived = ?8, color = ?9, content_rating = ?10, ai_output_language = ?11
+            WHERE id = ?12
             ",
             params![
                 persona.name,
@@ -246,15 +384,27 @@ impl PersonaRepository {
                 persona.ai_model_id,
                 persona.ai_instructions,
                 persona.updated_at.to_rfc3339(),
+                persona.archived,
+                persona.color,
+                persona.content_rating.as_str(),
+                persona.ai_output_language,
                 id,
             ],
         )?;
 
+        if request.description.is_some() {
+            Self::refresh_content_hash(conn, id)?;
+        }
+        ActivityRepository::record(conn, id, ActivityKind::Modified)?;
+
         Ok(persona)
     }
 
     /// Updates generation parameters for a persona.
     ///
+    /// For a variant, the fields that differ from its base persona's resolved
+    /// parameters become its overrides; the rest are inherited.
+    ///
     /// # Arguments
     ///
     /// * `conn` - Database connection reference
@@ -263,15 +413,36 @@ impl PersonaRepository {
     /// # Errors
     ///
     /// Returns `AppError::Database` if the update fails.
+    #[tracing::instrument(level = "debug", skip_all)]
     pub fn update_generation_params(
         conn: &Connection,
         params: &GenerationParams,
     ) -> Result<(), AppError> {
+        let base_persona_id: Option<String> = conn
+            .query_row(
+                "SELECT base_persona_id FROM personas WHERE id = ?1",
+                [&params.persona_id],
+                |row| row.get(0),
+            )
+            .optional()?
+            .flatten();
+        if let Some(base_id) = base_persona_id {
+            let base = Self::find_resolved_generation_params(conn, &base_id)?;
+            conn.execute(
+                "UPDATE personas SET param_overrides = ?1 WHERE id = ?2",
+                params![
+                    serde_json::to_string(&overridden_fields(params, &base))?,
+                    params.persona_id,
+                ],
+            )?;
+        }
+
         conn.execute(
             r"
             UPDATE generation_params
-            SET model_id = ?1, seed = ?2, steps = ?3, cfg_scale = ?4, sampler = ?5, scheduler = ?6
-            WHERE persona_id = ?7
+            SET model_id = ?1, seed = ?2, steps = ?3, cfg_scale = ?4, sampler = ?5, scheduler = ?6,
+                width = ?7, height = ?8, conditioning = ?9
+            WHERE persona_id = ?10
             ",
             params![
                 params.model_id,
@@ -280,9 +451,247 @@ impl PersonaRepository {
                 params.cfg_scale,
                 params.sampler,
                 params.scheduler,
+                params.width,
+                params.height,
+                serde_json::to_string(&params.conditioning)?,
                 params.persona_id,
             ],
         )?;
+        PromptCacheRepository::invalidate(conn, &params.persona_id)?;
+        ActivityRepository::record(conn, &params.persona_id, ActivityKind::Modified)?;
+        Ok(())
+    }
+
+    /// Links a persona to a base persona it inherits from, or unlinks it.
+    ///
+    /// Linking to a new base clears the parameter overrides, so the variant
+    /// starts with all of the base's parameters. Unlinking keeps what the
+    /// persona looked like: its resolved parameters and inherited tokens are
+    /// copied onto it.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `id` - The variant persona's UUID
+    /// * `base_persona_id` - The base persona's UUID, or `None` to unlink
+    /// * `inherited_granularities` - Granularity level IDs whose tokens are
+    ///   inherited (ignored when unlinking)
+    ///
+    /// # Returns
+    ///
+    /// The updated persona.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::NotFound` if either persona doesn't exist.
+    /// Returns `AppError::Validation` if the link would form a cycle or a chain
+    /// longer than `MAX_INHERITANCE_DEPTH`, or a level doesn't exist.
+    /// Returns `AppError::Database` for other database errors.
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id))]
+    pub fn set_base_persona(
+        conn: &Connection,
+        id: &str,
+        base_persona_id: Option<&str>,
+        inherited_granularities: &[String],
+    ) -> Result<Persona, AppError> {
+        let persona = Self::find_by_id(conn, id)?;
+
+        let Some(base_id) = base_persona_id else {
+            if persona.base_persona_id.is_some() {
+                Self::detach(conn, &persona)?;
+            }
+            return Self::find_by_id(conn, id);
+        };
+
+        // Walk up from the new base; reaching the persona means a cycle
+        let mut ancestor = Some(base_id.to_string());
+        let mut depth = 0;
+        while let Some(ancestor_id) = ancestor {
+            if ancestor_id == id {
+                return Err(AppError::Validation(
+                    "A persona cannot inherit from itself or its own variants".to_string(),
+                ));
+            }
+            depth += 1;
+            if depth > MAX_INHERITANCE_DEPTH {
+                return Err(AppError::Validation(format!(
+                    "Base personas can be chained at most {MAX_INHERITANCE_DEPTH} levels deep"
+                )));
+            }
+            ancestor = Self::find_by_id(conn, &ancestor_id)?.base_persona_id;
+        }
+
+        let level_ids: HashSet<String> = GranularityRepository::find_all(conn)?
+            .into_iter()
+            .map(|level| level.id)
+            .collect();
+        let mut inherited: Vec<String> = Vec::new();
+        for level_id in inherited_granularities {
+            if !level_ids.contains(level_id) {
+                return Err(AppError::Validation(format!(
+                    "Unknown granularity '{level_id}'"
+                )));
+            }
+            if !inherited.contains(level_id) {
+                inherited.push(level_id.clone());
+            }
+        }
+
+        let overrides = if persona.base_persona_id.as_deref() == Some(base_id) {
+            persona.param_overrides
+        } else {
+            Vec::new()
+        };
+
+        conn.execute(
+            r"
+            UPDATE personas
+            SET base_persona_id = ?1, inherited_granularities = ?2, param_overrides = ?3, updated_at = ?4
+            WHERE id = ?5
+            ",
+            params![
+                base_id,
+                serde_json::to_string(&inherited)?,
+                serde_json::to_string(&overrides)?,
+                Utc::now().to_rfc3339(),
+                id,
+            ],
+        )?;
+        PromptCacheRepository::invalidate(conn, id)?;
+        ActivityRepository::record(conn, id, ActivityKind::Modified)?;
+
+        Self::find_by_id(conn, id)
+    }
+
+    /// Unlinks a variant from its base, copying its resolved parameters and
+    /// inherited tokens onto it (internal helper).
+    fn detach(conn: &Connection, variant: &Persona) -> Result<(), AppError> {
+        let params = Self::find_resolved_generation_params(conn, &variant.id)?;
+        let inherited: Vec<Token> = TokenRepository::find_resolved_by_persona(conn, &variant.id)?
+            .into_iter()
+            .filter(|token| token.persona_id != variant.id)
+            .collect();
+
+        conn.execute(
+            r"
+            UPDATE personas
+            SET base_persona_id = NULL, inherited_granularities = '[]', param_overrides = '[]', updated_at = ?1
+            WHERE id = ?2
+            ",
+            params![Utc::now().to_rfc3339(), variant.id],
+        )?;
+        Self::update_generation_params(conn, &params)?;
+        TokenRepository::copy_to_persona(conn, &variant.id, &inherited)?;
+
+        Ok(())
+    }
+
+    /// Retrieves a persona's composition defaults.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `persona_id` - The persona's UUID
+    ///
+    /// # Returns
+    ///
+    /// The defaults, or `None` if the persona uses the global defaults.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Database` for database errors.
+    #[tracing::instrument(level = "debug", skip_all, fields(persona_id = %persona_id))]
+    pub fn find_composition_defaults(
+        conn: &Connection,
+        persona_id: &str,
+    ) -> Result<Option<CompositionDefaults>, AppError> {
+        let defaults = conn
+            .query_row(
+                r"
+                SELECT separator, include_weights, weight_syntax, dual_prompt
+                FROM composition_defaults WHERE persona_id = ?1
+                ",
+                [persona_id],
+                |row| {
+                    Ok(CompositionDefaults {
+                        separator: row.get(0)?,
+                        include_weights: row.get(1)?,
+                        // Unknown syntaxes cannot be written by this version; fall back defensively
+                        weight_syntax: WeightSyntax::parse(&row.get::<_, String>(2)?)
+                            .unwrap_or_default(),
+                        // Stored as JSON; unreadable settings fall back to a single prompt
+                        dual_prompt: row
+                            .get::<_, Option<String>>(3)?
+                            .and_then(|json| serde_json::from_str(&json).ok()),
+                    })
+                },
+            )
+            .optional()?;
+
+        Ok(defaults)
+    }
+
+    /// Sets or clears a persona's composition defaults.
+    ///
+    /// Clears the persona's cached prompt, which may have been composed with
+    /// the previous defaults.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `persona_id` - The persona's UUID
+    /// * `defaults` - New defaults, or `None` to use the global defaults
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::NotFound` if the persona does not exist.
+    /// Returns `AppError::Database` for other database errors.
+    #[tracing::instrument(level = "debug", skip_all, fields(persona_id = %persona_id))]
+    pub fn set_composition_defaults(
+        conn: &Connection,
+        persona_id: &str,
+        defaults: Option<&CompositionDefaults>,
+    ) -> Result<(), AppError> {
+        // Fail with NotFound rather than a foreign key error
+        Self::find_by_id(conn, persona_id)?;
+
+        match defaults {
+            Some(defaults) => {
+                let dual_prompt_json = defaults
+                    .dual_prompt
+                    .as_ref()
+                    .map(serde_json::to_string)
+                    .transpose()?;
+                conn.execute(
+                    r"
+                    INSERT INTO composition_defaults
+                        (persona_id, separator, include_weights, weight_syntax, dual_prompt)
+                    VALUES (?1, ?2, ?3, ?4, ?5)
+                    ON CONFLICT(persona_id) DO UPDATE SET
+                        separator = excluded.separator,
+                        include_weights = excluded.include_weights,
+                        weight_syntax = excluded.weight_syntax,
+                        dual_prompt = excluded.dual_prompt
+                    ",
+                    params![
+                        persona_id,
+                        defaults.separator,
+                        defaults.include_weights,
+                        defaults.weight_syntax.as_str(),
+                        dual_prompt_json,
+                    ],
+                )?;
+            }
+            None => {
+                conn.execute(
+                    "DELETE FROM composition_defaults WHERE persona_id = ?1",
+                    [persona_id],
+                )?;
+            }
+        }
+
+        PromptCacheRepository::invalidate(conn, persona_id)?;
+        ActivityRepository::record(conn, persona_id, ActivityKind::Modified)?;
         Ok(())
     }
 
@@ -292,6 +701,8 @@ impl PersonaRepository {
     /// - Associated tokens
     /// - Associated generation parameters
     ///
+    /// Variants of the persona are unlinked first and keep what they inherited.
+    ///
     /// # Arguments
     ///
     /// * `conn` - Database connection reference
@@ -301,7 +712,23 @@ impl PersonaRepository {
     ///
     /// Returns `AppError::NotFound` if the persona doesn't exist.
     /// Returns `AppError::Database` for other database errors.
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id))]
     pub fn delete(conn: &Connection, id: &str) -> Result<(), AppError> {
+        // Variants keep what they inherited rather than silently losing it
+        let mut stmt = conn.prepare(
+            r"
+            SELECT id, name, description, tags, ai_provider_id, ai_model_id, ai_instructions, created_at, updated_at, archived, color, content_rating,
+                   base_persona_id, inherited_granularities, param_overrides, ai_output_language
+            FROM personas WHERE base_persona_id = ?1
+            ",
+        )?;
+        let variants = stmt
+            .query_map([id], Self::row_to_persona)?
+            .collect::<Result<Vec<_>, _>>()?;
+        for variant in &variants {
+            Self::detach(conn, variant)?;
+        }
+
         let rows = conn.execute("DELETE FROM personas WHERE id = ?1", [id])?;
         if rows == 0 {
             return Err(AppError::NotFound(format!(
@@ -313,6 +740,7 @@ impl PersonaRepository {
 
     /// Checks if a persona name already exists in the database.
     ///
+    /// Names are compared by [`name_key`], ignoring case and diacritics.
     /// Useful for validating uniqueness before create or update operations.
     ///
     /// # Arguments
@@ -328,30 +756,92 @@ impl PersonaRepository {
     /// # Errors
     ///
     /// Returns `AppError::Database` for database errors.
+    #[tracing::instrument(level = "debug", skip_all)]
     pub fn name_exists(
         conn: &Connection,
         name: &str,
         exclude_id: Option<&str>,
     ) -> Result<bool, AppError> {
-        let exists: bool = match exclude_id {
-            Some(id) => conn.query_row(
-                "SELECT EXISTS(SELECT 1 FROM personas WHERE name = ?1 AND id != ?2)",
-                params![name, id],
-                |row| row.get(0),
-            )?,
-            None => conn.query_row(
-                "SELECT EXISTS(SELECT 1 FROM personas WHERE name = ?1)",
-                [name],
-                |row| row.get(0),
-            )?,
-        };
-        Ok(exists)
+        let key = name_key(name);
+        Ok(Self::name_keys(conn, exclude_id)?.contains(&key))
+    }
+
+    /// Returns the first candidate name not used by another persona.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `candidates` - Names in order of preference, e.g. from [`name_candidates`]
+    /// * `exclude_id` - Optional ID to exclude from the check (for updates)
+    ///
+    /// # Returns
+    ///
+    /// The first available candidate, or `None` if all are taken.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Database` for database errors.
+    ///
+    /// [`name_candidates`]: crate::domain::naming::name_candidates
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub fn available_name(
+        conn: &Connection,
+        candidates: impl IntoIterator<Item = String>,
+        exclude_id: Option<&str>,
+    ) -> Result<Option<String>, AppError> {
+        Ok(Self::available_names(conn, candidates, exclude_id, 1)?.pop())
+    }
+
+    /// Returns up to `limit` candidate names not used by another persona.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `candidates` - Names in order of preference
+    /// * `exclude_id` - Optional ID to exclude from the check (for updates)
+    /// * `limit` - Maximum number of names to return
+    ///
+    /// # Returns
+    ///
+    /// Available candidates in their original order.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Database` for database errors.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub fn available_names(
+        conn: &Connection,
+        candidates: impl IntoIterator<Item = String>,
+        exclude_id: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<String>, AppError> {
+        let keys = Self::name_keys(conn, exclude_id)?;
+        Ok(candidates
+            .into_iter()
+            .filter(|candidate| !keys.contains(&name_key(candidate)))
+            .take(limit)
+            .collect())
+    }
+
+    /// Collects the comparison keys of all persona names (internal helper).
+    fn name_keys(conn: &Connection, exclude_id: Option<&str>) -> Result<HashSet<String>, AppError> {
+        let mut stmt = conn.prepare("SELECT name FROM personas WHERE id != ?1")?;
+        let keys = stmt
+            .query_map([exclude_id.unwrap_or_default()], |row| {
+                row.get::<_, String>(0)
+            })?
+            .map(|name| name.map(|name| name_key(&name)))
+            .collect::<Result<HashSet<_>, _>>()?;
+        Ok(keys)
     }
 
     /// Creates a new persona from a request.
     ///
-    /// Validates name uniqueness before creation. Also creates default
-    /// generation parameters for the persona.
+    /// Normalizes the name and validates its uniqueness before creation. Also
+    /// creates default generation parameters for the persona. Reserved name
+    /// suffixes are not rejected here, since duplication and import create
+    /// suffixed names; commands validate user-entered names with
+    /// [`validate_name`] first.
     ///
     /// # Arguments
     ///
@@ -364,25 +854,180 @@ impl PersonaRepository {
     ///
     /// # Errors
     ///
-    /// Returns `AppError::Validation` if the name already exists.
+    /// Returns `AppError::Validation` if the name is empty or already exists.
     /// Returns `AppError::Database` for other database errors.
+    ///
+    /// [`validate_name`]: crate::domain::naming::validate_name
+    #[tracing::instrument(level = "debug", skip_all)]
     pub fn create(conn: &Connection, request: &CreatePersonaRequest) -> Result<Persona, AppError> {
+        let name = normalize_name(&request.name);
+        if name.is_empty() {
+            return Err(AppError::Validation("Persona name is required".to_string()));
+        }
+
         // Check if name already exists
-        if Self::name_exists(conn, &request.name, None)? {
+        if Self::name_exists(conn, &name, None)? {
             return Err(AppError::Validation(format!(
-                "A persona with name '{}' already exists",
-                request.name
+                "A persona with name '{name}' already exists"
             )));
         }
 
-        let persona = Persona::new(
-            request.name.clone(),
-            request.description.clone(),
-            request.tags.clone(),
-        );
+        let mut persona = Persona::new(name, request.description.clone(), request.tags.clone());
+        persona.content_rating = request.content_rating;
 
         Self::insert(conn, &persona)?;
+        ActivityRepository::record(conn, &persona.id, ActivityKind::Modified)?;
 
         Ok(persona)
     }
+
+    /// Recomputes and stores the content hash for a persona.
+    ///
+    /// Must be called whenever the persona's description or tokens change so
+    /// that duplicate detection stays accurate.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `id` - The persona's UUID
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Database` for database errors.
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id))]
+    pub fn refresh_content_hash(conn: &Connection, id: &str) -> Result<(), AppError> {
+        let description: Option<String> = conn
+            .query_row(
+                "SELECT description FROM personas WHERE id = ?1",
+                [id],
+                |row| row.get(0),
+            )
+            .map_err(|e| match e {
+                rusqlite::Error::QueryReturnedNoRows => {
+                    AppError::NotFound(format!("Persona with id '{id}' not found"))
+                }
+                _ => AppError::Database(e),
+            })?;
+
+        let tokens = TokenRepository::find_by_persona(conn, id)?;
+        let hash = compute_content_hash(description.as_deref(), &tokens);
+
+        conn.execute(
+            "UPDATE personas SET content_hash = ?1 WHERE id = ?2",
+            params![hash, id],
+        )?;
+        Ok(())
+    }
+
+    /// Finds a persona whose stored content hash matches the given hash.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `hash` - Content hash computed with `compute_content_hash`
+    ///
+    /// # Returns
+    ///
+    /// The first matching persona, or `None` if no persona has this content.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Database` for database errors.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub fn find_by_content_hash(
+        conn: &Connection,
+        hash: &str,
+    ) -> Result<Option<Persona>, AppError> {
+        let result = conn.query_row(
+            r"
+            SELECT id, name, description, tags, ai_provider_id, ai_model_id, ai_instructions, created_at, updated_at, archived, color, content_rating,
+                   base_persona_id, inherited_granularities, param_overrides, ai_output_language
+            FROM personas WHERE content_hash = ?1
+            ORDER BY created_at
+            LIMIT 1
+            ",
+            [hash],
+            Self::row_to_persona,
+        );
+
+        match result {
+            Ok(persona) => Ok(Some(persona)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(AppError::Database(e)),
+        }
+    }
+}
+
+/// The `SQLite` implementation of [`PersonaRepo`], delegating to [`PersonaRepository`].
+impl PersonaRepo for Connection {
+    fn find_by_id(&self, id: &str) -> Result<Persona, AppError> {
+        PersonaRepository::find_by_id(self, id)
+    }
+
+    fn find_all(&self) -> Result<Vec<Persona>, AppError> {
+        PersonaRepository::find_all(self)
+    }
+
+    fn create(&self, request: &CreatePersonaRequest) -> Result<Persona, AppError> {
+        PersonaRepository::create(self, request)
+    }
+
+    fn update(&self, id: &str, request: &UpdatePersonaRequest) -> Result<Persona, AppError> {
+        PersonaRepository::update(self, id, request)
+    }
+
+    fn delete(&self, id: &str) -> Result<(), AppError> {
+        PersonaRepository::delete(self, id)
+    }
+
+    fn find_by_content_hash(&self, hash: &str) -> Result<Option<Persona>, AppError> {
+        PersonaRepository::find_by_content_hash(self, hash)
+    }
+
+    fn names(&self, exclude_id: Option<&str>) -> Result<Vec<String>, AppError> {
+        let mut stmt = self.prepare("SELECT name FROM personas WHERE id != ?1")?;
+        let names = stmt
+            .query_map([exclude_id.unwrap_or_default()], |row| row.get(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(names)
+    }
+
+    fn find_resolved_generation_params(
+        &self,
+        persona_id: &str,
+    ) -> Result<GenerationParams, AppError> {
+        PersonaRepository::find_resolved_generation_params(self, persona_id)
+    }
+
+    fn update_generation_params(&self, params: &GenerationParams) -> Result<(), AppError> {
+        PersonaRepository::update_generation_params(self, params)
+    }
+
+    fn find_composition_defaults(
+        &self,
+        persona_id: &str,
+    ) -> Result<Option<CompositionDefaults>, AppError> {
+        PersonaRepository::find_composition_defaults(self, persona_id)
+    }
+
+    fn set_composition_defaults(
+        &self,
+        persona_id: &str,
+        defaults: Option<&CompositionDefaults>,
+    ) -> Result<(), AppError> {
+        PersonaRepository::set_composition_defaults(self, persona_id, defaults)
+    }
+
+    fn name_exists(&self, name: &str, exclude_id: Option<&str>) -> Result<bool, AppError> {
+        PersonaRepository::name_exists(self, name, exclude_id)
+    }
+
+    fn available_names(
+        &self,
+        candidates: impl IntoIterator<Item = String>,
+        exclude_id: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<String>, AppError> {
+        PersonaRepository::available_names(self, candidates, exclude_id, limit)
+    }
 }
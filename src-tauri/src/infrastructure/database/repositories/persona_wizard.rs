@@ -0,0 +1,206 @@
+//! Persona Wizard Repository
+//!
+//! Provides data access operations for drafts of the persona creation wizard.
+//! All methods are stateless and take a connection reference as their first parameter.
+//!
+//! Tags, tokens, and submitted steps are stored as JSON; a draft is saved
+//! whole after every step.
+//!
+//! # Usage
+//!
+//! ```rust,ignore
+//! PersonaWizardRepository::create(&conn, &wizard)?;
+//! let drafts = PersonaWizardRepository::find_all(&conn)?;
+//! ```
+
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection};
+
+use crate::domain::persona::ContentRating;
+use crate::domain::wizard::PersonaWizard;
+use crate::error::AppError;
+
+/// Repository for persona wizard draft database operations.
+///
+/// This struct contains no state; all methods take a connection reference
+/// and can be composed within external transactions.
+pub struct PersonaWizardRepository;
+
+impl PersonaWizardRepository {
+    /// Finds a wizard draft by its unique identifier.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `id` - The draft's UUID
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::NotFound` if no draft exists with the given ID.
+    /// Returns `AppError::Database` for other database errors.
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id))]
+    pub fn find_by_id(conn: &Connection, id: &str) -> Result<PersonaWizard, AppError> {
+        conn.query_row(
+            r"
+            SELECT id, name, description, tags, content_rating, image_model_id, tokens,
+                   completed_steps, created_at, updated_at
+            FROM persona_wizards WHERE id = ?1
+            ",
+            [id],
+            Self::row_to_wizard,
+        )
+        .map_err(|e| match e {
+            rusqlite::Error::QueryReturnedNoRows => {
+                AppError::NotFound(format!("Persona wizard with id '{id}' not found"))
+            }
+            _ => AppError::Database(e),
+        })
+    }
+
+    /// Retrieves all wizard drafts, most recently changed first.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Database` for database errors.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub fn find_all(conn: &Connection) -> Result<Vec<PersonaWizard>, AppError> {
+        let mut stmt = conn.prepare(
+            r"
+            SELECT id, name, description, tags, content_rating, image_model_id, tokens,
+                   completed_steps, created_at, updated_at
+            FROM persona_wizards
+            ORDER BY updated_at DESC
+            ",
+        )?;
+
+        let wizards = stmt
+            .query_map([], Self::row_to_wizard)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(wizards)
+    }
+
+    /// Stores a new wizard draft.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `wizard` - The draft to store
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Database` for database errors.
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %wizard.id))]
+    pub fn create(conn: &Connection, wizard: &PersonaWizard) -> Result<(), AppError> {
+        conn.execute(
+            r"
+            INSERT INTO persona_wizards (id, name, description, tags, content_rating,
+                image_model_id, tokens, completed_steps, created_at, updated_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+            ",
+            params![
+                wizard.id,
+                wizard.name,
+                wizard.description,
+                serde_json::to_string(&wizard.tags)?,
+                wizard.content_rating.as_str(),
+                wizard.image_model_id,
+                serde_json::to_string(&wizard.tokens)?,
+                serde_json::to_string(&wizard.completed_steps)?,
+                wizard.created_at.to_rfc3339(),
+                wizard.updated_at.to_rfc3339(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Stores a draft's tokens and submitted steps.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `wizard` - The draft after a submitted step
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::NotFound` if the draft was deleted meanwhile.
+    /// Returns `AppError::Database` for other database errors.
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %wizard.id))]
+    pub fn save_steps(conn: &Connection, wizard: &PersonaWizard) -> Result<(), AppError> {
+        let rows = conn.execute(
+            r"
+            UPDATE persona_wizards
+            SET tokens = ?1, completed_steps = ?2, updated_at = ?3
+            WHERE id = ?4
+            ",
+            params![
+                serde_json::to_string(&wizard.tokens)?,
+                serde_json::to_string(&wizard.completed_steps)?,
+                wizard.updated_at.to_rfc3339(),
+                wizard.id,
+            ],
+        )?;
+        if rows == 0 {
+            return Err(AppError::NotFound(format!(
+                "Persona wizard with id '{}' not found",
+                wizard.id
+            )));
+        }
+        Ok(())
+    }
+
+    /// Deletes a wizard draft.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `id` - The draft's UUID
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::NotFound` if the draft doesn't exist.
+    /// Returns `AppError::Database` for other database errors.
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id))]
+    pub fn delete(conn: &Connection, id: &str) -> Result<(), AppError> {
+        let rows = conn.execute("DELETE FROM persona_wizards WHERE id = ?1", [id])?;
+        if rows == 0 {
+            return Err(AppError::NotFound(format!(
+                "Persona wizard with id '{id}' not found"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Helper to convert a row to a `PersonaWizard`
+    ///
+    /// Column mapping:
+    /// 0: id, 1: name, 2: description, 3: tags (JSON), 4: `content_rating`,
+    /// 5: `image_model_id`, 6: tokens (JSON), 7: `completed_steps` (JSON), 8: `created_at`,
+    /// 9: `updated_at`
+    fn row_to_wizard(row: &rusqlite::Row) -> rusqlite::Result<PersonaWizard> {
+        let tags_json: String = row.get(3)?;
+        let tokens_json: String = row.get(6)?;
+        let steps_json: String = row.get(7)?;
+        let parse_time = |value: &str| {
+            DateTime::parse_from_rfc3339(value)
+                .map_or_else(|_| Utc::now(), |dt| dt.with_timezone(&Utc))
+        };
+
+        Ok(PersonaWizard {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            description: row.get(2)?,
+            tags: serde_json::from_str(&tags_json).unwrap_or_default(),
+            content_rating: ContentRating::parse(&row.get::<_, String>(4)?).unwrap_or_default(),
+            image_model_id: row.get(5)?,
+            tokens: serde_json::from_str(&tokens_json).unwrap_or_default(),
+            completed_steps: serde_json::from_str(&steps_json).unwrap_or_default(),
+            created_at: parse_time(&row.get::<_, String>(8)?),
+            updated_at: parse_time(&row.get::<_, String>(9)?),
+        })
+    }
+}
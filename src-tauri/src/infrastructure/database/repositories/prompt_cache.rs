@@ -0,0 +1,176 @@
+//! Prompt Cache Repository
+//!
+//! Provides data access operations for the per-persona prompt cache.
+//! All methods are stateless and take a connection reference as their first parameter.
+//!
+//! Each persona has at most one cached prompt: the last one composed without
+//! ad-hoc tokens. Token and generation parameter writes call
+//! [`PromptCacheRepository::invalidate`] so stale prompts are never served.
+//!
+//! # Usage
+//!
+//! ```rust,ignore
+//! PromptCacheRepository::store(&conn, &persona_id, &options.cache_key(), &composed)?;
+//! let cached = PromptCacheRepository::find(&conn, &persona_id)?;
+//! ```
+
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::domain::prompt::{CachedPrompt, ComposedPrompt};
+use crate::error::AppError;
+
+/// Repository for prompt cache database operations.
+///
+/// This struct contains no state; all methods take a connection reference
+/// and can be composed within external transactions.
+pub struct PromptCacheRepository;
+
+impl PromptCacheRepository {
+    /// Retrieves the cached prompt for a persona.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `persona_id` - The persona's UUID
+    ///
+    /// # Returns
+    ///
+    /// The cached prompt, or `None` if nothing is cached.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Database` for database errors.
+    #[tracing::instrument(level = "debug", skip_all, fields(persona_id = %persona_id))]
+    pub fn find(conn: &Connection, persona_id: &str) -> Result<Option<CachedPrompt>, AppError> {
+        let cached = conn
+            .query_row(
+                r"
+                SELECT persona_id, options_hash, positive_prompt, negative_prompt,
+                       positive_token_count, negative_token_count, created_at
+                FROM prompt_cache WHERE persona_id = ?1
+                ",
+                [persona_id],
+                Self::row_to_cached,
+            )
+            .optional()?;
+
+        Ok(cached)
+    }
+
+    /// Stores a composed prompt as the persona's cached prompt, replacing any
+    /// previous entry.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `persona_id` - The persona's UUID
+    /// * `options_hash` - Cache key of the options used to compose the prompt
+    /// * `composed` - The composed prompt
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Database` for database errors.
+    #[tracing::instrument(level = "debug", skip_all, fields(persona_id = %persona_id))]
+    pub fn store(
+        conn: &Connection,
+        persona_id: &str,
+        options_hash: &str,
+        composed: &ComposedPrompt,
+    ) -> Result<CachedPrompt, AppError> {
+        let cached = CachedPrompt {
+            persona_id: persona_id.to_string(),
+            options_hash: options_hash.to_string(),
+            positive_prompt: composed.positive_prompt.clone(),
+            negative_prompt: composed.negative_prompt.clone(),
+            positive_token_count: composed.positive_token_count,
+            negative_token_count: composed.negative_token_count,
+            created_at: Utc::now(),
+        };
+
+        conn.execute(
+            r"
+            INSERT OR REPLACE INTO prompt_cache (persona_id, options_hash, positive_prompt, negative_prompt, positive_token_count, negative_token_count, created_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+            ",
+            params![
+                cached.persona_id,
+                cached.options_hash,
+                cached.positive_prompt,
+                cached.negative_prompt,
+                cached.positive_token_count as i64,
+                cached.negative_token_count as i64,
+                cached.created_at.to_rfc3339(),
+            ],
+        )?;
+
+        Ok(cached)
+    }
+
+    /// Drops the cached prompt for a persona and for every variant that
+    /// inherits from it, directly or through other variants.
+    ///
+    /// Must be called whenever the persona's tokens or generation parameters
+    /// change.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `persona_id` - The persona's UUID
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Database` for database errors.
+    #[tracing::instrument(level = "debug", skip_all, fields(persona_id = %persona_id))]
+    pub fn invalidate(conn: &Connection, persona_id: &str) -> Result<(), AppError> {
+        conn.execute(
+            r"
+            WITH RECURSIVE affected(id) AS (
+                SELECT ?1
+                UNION
+                SELECT p.id FROM personas p JOIN affected a ON p.base_persona_id = a.id
+            )
+            DELETE FROM prompt_cache WHERE persona_id IN (SELECT id FROM affected)
+            ",
+            [persona_id],
+        )?;
+        Ok(())
+    }
+
+    /// Drops every cached prompt.
+    ///
+    /// Must be called when a change affects how all personas compose, such as
+    /// editing the banned terms.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Database` for database errors.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub fn invalidate_all(conn: &Connection) -> Result<(), AppError> {
+        conn.execute("DELETE FROM prompt_cache", [])?;
+        Ok(())
+    }
+
+    /// Helper function to convert a row to a `CachedPrompt`
+    ///
+    /// Column mapping:
+    /// 0: `persona_id`, 1: `options_hash`, 2: `positive_prompt`, 3: `negative_prompt`,
+    /// 4: `positive_token_count`, 5: `negative_token_count`, 6: `created_at`
+    fn row_to_cached(row: &rusqlite::Row) -> Result<CachedPrompt, rusqlite::Error> {
+        Ok(CachedPrompt {
+            persona_id: row.get(0)?,
+            options_hash: row.get(1)?,
+            positive_prompt: row.get(2)?,
+            negative_prompt: row.get(3)?,
+            positive_token_count: usize::try_from(row.get::<_, i64>(4)?).unwrap_or_default(),
+            negative_token_count: usize::try_from(row.get::<_, i64>(5)?).unwrap_or_default(),
+            // Timestamps stored as RFC3339 strings; fallback to now if parsing fails
+            created_at: chrono::DateTime::parse_from_rfc3339(&row.get::<_, String>(6)?)
+                .map_or_else(|_| Utc::now(), |dt| dt.with_timezone(&Utc)),
+        })
+    }
+}
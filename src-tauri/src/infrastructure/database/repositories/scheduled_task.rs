@@ -0,0 +1,302 @@
+//! Scheduled Task Repository
+//!
+//! Provides data access operations for recurring background tasks.
+//! All methods are stateless and take a connection reference as their first parameter.
+//!
+//! The persona list and AI provider configuration are stored as JSON; the
+//! configuration never contains an API key (see `AiProviderConfig`).
+//!
+//! # Usage
+//!
+//! ```rust,ignore
+//! let task = ScheduledTaskRepository::create(&conn, request)?;
+//! let due = ScheduledTaskRepository::find_due(&conn, Utc::now())?;
+//! ```
+
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection};
+
+use crate::domain::ai::{AiProvider, AiProviderConfig};
+use crate::domain::schedule::{
+    CreateScheduledTaskRequest, ScheduleFrequency, ScheduledTask, ScheduledTaskKind,
+    UpdateScheduledTaskRequest,
+};
+use crate::error::AppError;
+
+/// Repository for scheduled task database operations.
+///
+/// This struct contains no state; all methods take a connection reference
+/// and can be composed within external transactions.
+pub struct ScheduledTaskRepository;
+
+impl ScheduledTaskRepository {
+    /// Finds a scheduled task by its unique identifier.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `id` - The task's UUID
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::NotFound` if no task exists with the given ID.
+    /// Returns `AppError::Database` for other database errors.
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id))]
+    pub fn find_by_id(conn: &Connection, id: &str) -> Result<ScheduledTask, AppError> {
+        conn.query_row(
+            r"
+            SELECT id, name, kind, persona_ids, frequency, config, token_count, enabled,
+                   last_run_at, next_run_at, last_error, created_at, updated_at
+            FROM scheduled_tasks WHERE id = ?1
+            ",
+            [id],
+            Self::row_to_task,
+        )
+        .map_err(|e| match e {
+            rusqlite::Error::QueryReturnedNoRows => {
+                AppError::NotFound(format!("Scheduled task with id '{id}' not found"))
+            }
+            _ => AppError::Database(e),
+        })
+    }
+
+    /// Retrieves all scheduled tasks, oldest first.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Database` for database errors.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub fn find_all(conn: &Connection) -> Result<Vec<ScheduledTask>, AppError> {
+        let mut stmt = conn.prepare(
+            r"
+            SELECT id, name, kind, persona_ids, frequency, config, token_count, enabled,
+                   last_run_at, next_run_at, last_error, created_at, updated_at
+            FROM scheduled_tasks
+            ORDER BY created_at
+            ",
+        )?;
+
+        let tasks = stmt
+            .query_map([], Self::row_to_task)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(tasks)
+    }
+
+    /// Retrieves the enabled tasks whose next run is due at `now`.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `now` - Reference time
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Database` for database errors.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub fn find_due(conn: &Connection, now: DateTime<Utc>) -> Result<Vec<ScheduledTask>, AppError> {
+        // Timestamps are compared as parsed values, not RFC 3339 strings
+        Ok(Self::find_all(conn)?
+            .into_iter()
+            .filter(|task| task.is_due(now))
+            .collect())
+    }
+
+    /// Creates a scheduled task from a request.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `request` - The creation request
+    ///
+    /// # Returns
+    ///
+    /// The newly created task, first due one interval from now.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Validation` if the request is invalid.
+    /// Returns `AppError::Database` for other database errors.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub fn create(
+        conn: &Connection,
+        request: CreateScheduledTaskRequest,
+    ) -> Result<ScheduledTask, AppError> {
+        request.validate()?;
+        let task = ScheduledTask::new(request);
+
+        conn.execute(
+            r"
+            INSERT INTO scheduled_tasks (id, name, kind, persona_ids, frequency, config,
+                token_count, enabled, last_run_at, next_run_at, last_error, created_at, updated_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)
+            ",
+            params![
+                task.id,
+                task.name,
+                task.kind.as_str(),
+                serde_json::to_string(&task.persona_ids)?,
+                task.frequency.as_str(),
+                serde_json::to_string(&task.config)?,
+                task.token_count as i64,
+                task.enabled,
+                task.last_run_at.map(|at| at.to_rfc3339()),
+                task.next_run_at.to_rfc3339(),
+                task.last_error,
+                task.created_at.to_rfc3339(),
+                task.updated_at.to_rfc3339(),
+            ],
+        )?;
+
+        Ok(task)
+    }
+
+    /// Updates a scheduled task's settings.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `id` - The task's UUID
+    /// * `request` - The fields to change
+    ///
+    /// # Returns
+    ///
+    /// The updated task.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::NotFound` if the task doesn't exist.
+    /// Returns `AppError::Validation` if a new value is invalid.
+    /// Returns `AppError::Database` for other database errors.
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id))]
+    pub fn update(
+        conn: &Connection,
+        id: &str,
+        request: &UpdateScheduledTaskRequest,
+    ) -> Result<ScheduledTask, AppError> {
+        let mut task = Self::find_by_id(conn, id)?;
+        request.apply(&mut task)?;
+        Self::save(conn, &task)?;
+        Ok(task)
+    }
+
+    /// Stores a task's run record and persona list after a run.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `task` - The task with its run recorded (see `ScheduledTask::record_run`)
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::NotFound` if the task was deleted during the run.
+    /// Returns `AppError::Database` for other database errors.
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %task.id))]
+    pub fn save_run(conn: &Connection, task: &ScheduledTask) -> Result<(), AppError> {
+        let rows = conn.execute(
+            r"
+            UPDATE scheduled_tasks
+            SET persona_ids = ?1, last_run_at = ?2, next_run_at = ?3, last_error = ?4
+            WHERE id = ?5
+            ",
+            params![
+                serde_json::to_string(&task.persona_ids)?,
+                task.last_run_at.map(|at| at.to_rfc3339()),
+                task.next_run_at.to_rfc3339(),
+                task.last_error,
+                task.id,
+            ],
+        )?;
+        if rows == 0 {
+            return Err(AppError::NotFound(format!(
+                "Scheduled task with id '{}' not found",
+                task.id
+            )));
+        }
+        Ok(())
+    }
+
+    /// Deletes a scheduled task. Suggestions it staged are kept.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `id` - The task's UUID
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::NotFound` if the task doesn't exist.
+    /// Returns `AppError::Database` for other database errors.
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id))]
+    pub fn delete(conn: &Connection, id: &str) -> Result<(), AppError> {
+        let rows = conn.execute("DELETE FROM scheduled_tasks WHERE id = ?1", [id])?;
+        if rows == 0 {
+            return Err(AppError::NotFound(format!(
+                "Scheduled task with id '{id}' not found"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Writes every field of a task (internal helper).
+    fn save(conn: &Connection, task: &ScheduledTask) -> Result<(), AppError> {
+        conn.execute(
+            r"
+            UPDATE scheduled_tasks
+            SET name = ?1, persona_ids = ?2, frequency = ?3, config = ?4, token_count = ?5,
+                enabled = ?6, next_run_at = ?7, updated_at = ?8
+            WHERE id = ?9
+            ",
+            params![
+                task.name,
+                serde_json::to_string(&task.persona_ids)?,
+                task.frequency.as_str(),
+                serde_json::to_string(&task.config)?,
+                task.token_count as i64,
+                task.enabled,
+                task.next_run_at.to_rfc3339(),
+                task.updated_at.to_rfc3339(),
+                task.id,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Helper to convert a row to a `ScheduledTask`
+    ///
+    /// Column mapping:
+    /// 0: id, 1: name, 2: kind, 3: `persona_ids` (JSON), 4: frequency, 5: config (JSON),
+    /// 6: `token_count`, 7: enabled, 8: `last_run_at`, 9: `next_run_at`, 10: `last_error`,
+    /// 11: `created_at`, 12: `updated_at`
+    fn row_to_task(row: &rusqlite::Row) -> rusqlite::Result<ScheduledTask> {
+        let persona_ids_json: String = row.get(3)?;
+        let config_json: String = row.get(5)?;
+        let parse_time = |value: &str| {
+            DateTime::parse_from_rfc3339(value)
+                .map_or_else(|_| Utc::now(), |dt| dt.with_timezone(&Utc))
+        };
+
+        Ok(ScheduledTask {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            kind: ScheduledTaskKind::parse(&row.get::<_, String>(2)?).unwrap_or_default(),
+            persona_ids: serde_json::from_str(&persona_ids_json).unwrap_or_default(),
+            frequency: ScheduleFrequency::parse(&row.get::<_, String>(4)?).unwrap_or_default(),
+            // A config that no longer parses falls back to a local provider, so a
+            // broken task never sends personas to a remote one
+            config: serde_json::from_str(&config_json)
+                .unwrap_or_else(|_| AiProviderConfig::new(AiProvider::Ollama)),
+            token_count: usize::try_from(row.get::<_, i64>(6)?).unwrap_or_default(),
+            enabled: row.get(7)?,
+            last_run_at: row.get::<_, Option<String>>(8)?.as_deref().map(parse_time),
+            next_run_at: parse_time(&row.get::<_, String>(9)?),
+            last_error: row.get(10)?,
+            created_at: parse_time(&row.get::<_, String>(11)?),
+            updated_at: parse_time(&row.get::<_, String>(12)?),
+        })
+    }
+}
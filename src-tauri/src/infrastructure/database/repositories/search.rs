@@ -0,0 +1,97 @@
+//! Search Repository
+//!
+//! Provides full-text search over personas, tokens, and smart collections.
+//! All methods are stateless and take a connection reference as their first parameter.
+//!
+//! The `search_index` FTS5 table is maintained by triggers on the source
+//! tables, so repositories writing those tables need no extra calls.
+//!
+//! # Usage
+//!
+//! ```rust,ignore
+//! let results = SearchRepository::search(&conn, "red hair", 20)?;
+//! ```
+
+use rusqlite::{params, Connection};
+
+use crate::domain::search::{fts_query, SearchResult, SearchResultKind};
+use crate::error::AppError;
+
+/// Column weights for `bm25`: kind, `entity_id`, `persona_id`, title, body.
+/// Title matches (names, token content) weigh ten times body matches.
+const BM25_WEIGHTS: &str = "0.0, 0.0, 0.0, 10.0, 1.0";
+
+/// Repository for full-text search operations.
+///
+/// This struct contains no state; all methods take a connection reference
+/// and can be composed within external transactions.
+pub struct SearchRepository;
+
+impl SearchRepository {
+    /// Searches personas, tokens, and smart collections.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `text` - Search text; every word must prefix-match
+    /// * `limit` - Maximum number of results
+    /// * `hide_mature` - Leave out mature-rated personas and their tokens
+    ///
+    /// # Returns
+    ///
+    /// Results ordered by relevance (best first); empty if the text has no words.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Database` for database errors.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub fn search(
+        conn: &Connection,
+        text: &str,
+        limit: usize,
+        hide_mature: bool,
+    ) -> Result<Vec<SearchResult>, AppError> {
+        let Some(query) = fts_query(text) else {
+            return Ok(Vec::new());
+        };
+
+        let mut stmt = conn.prepare(&format!(
+            r"
+            SELECT search_index.kind, search_index.entity_id, search_index.persona_id,
+                   search_index.title,
+                   CASE search_index.kind
+                       WHEN 'persona' THEN p.description
+                       WHEN 'token' THEN p.name
+                   END,
+                   bm25(search_index, {BM25_WEIGHTS}) AS score
+            FROM search_index
+            LEFT JOIN personas p ON p.id = search_index.persona_id
+            WHERE search_index MATCH ?1
+              AND (?3 = 0 OR p.content_rating IS NOT 'mature')
+            ORDER BY score
+            LIMIT ?2
+            "
+        ))?;
+
+        let results = stmt
+            .query_map(params![query, limit as i64, hide_mature], |row| {
+                // Unknown kinds cannot be produced by the triggers; skip defensively
+                let Some(kind) = SearchResultKind::parse(&row.get::<_, String>(0)?) else {
+                    return Ok(None);
+                };
+                Ok(Some(SearchResult {
+                    kind,
+                    id: row.get(1)?,
+                    persona_id: row.get(2)?,
+                    title: row.get(3)?,
+                    subtitle: row.get(4)?,
+                    // bm25 is lower for better matches
+                    rank: -row.get::<_, f64>(5)?,
+                }))
+            })?
+            .filter_map(Result::transpose)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(results)
+    }
+}
@@ -0,0 +1,142 @@
+//! Settings Repository
+//!
+//! Provides data access operations for backend application settings.
+//! All methods are stateless and take a connection reference as their first parameter.
+//!
+//! Settings are stored as a JSON document under a single key, so new fields
+//! with serde defaults need no migration. Bookkeeping values such as the last
+//! backup time live under their own keys.
+//!
+//! # Usage
+//!
+//! ```rust,ignore
+//! let settings = SettingsRepository::load(&conn)?;
+//! SettingsRepository::save(&conn, &settings)?;
+//! SettingsRepository::record_backup(&conn)?;
+//! ```
+
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::domain::settings::AppSettings;
+use crate::error::AppError;
+
+/// Key under which [`AppSettings`] are stored.
+const APP_SETTINGS_KEY: &str = "app";
+
+/// Key under which the time of the last database export is stored.
+const LAST_BACKUP_KEY: &str = "last_backup_at";
+
+/// Repository for settings database operations.
+///
+/// This struct contains no state; all methods take a connection reference
+/// and can be composed within external transactions.
+pub struct SettingsRepository;
+
+impl SettingsRepository {
+    /// Loads the application settings.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    ///
+    /// # Returns
+    ///
+    /// The stored settings, or defaults if none have been saved.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Database` for database errors.
+    /// Returns `AppError::Serialization` if the stored JSON is malformed.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub fn load(conn: &Connection) -> Result<AppSettings, AppError> {
+        let value: Option<String> = conn
+            .query_row(
+                "SELECT value FROM app_settings WHERE key = ?1",
+                [APP_SETTINGS_KEY],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        match value {
+            Some(json) => Ok(serde_json::from_str(&json)?),
+            None => Ok(AppSettings::default()),
+        }
+    }
+
+    /// Saves the application settings, replacing the stored ones.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `settings` - The settings to store
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Database` for database errors.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub fn save(conn: &Connection, settings: &AppSettings) -> Result<(), AppError> {
+        conn.execute(
+            r"
+            INSERT OR REPLACE INTO app_settings (key, value, updated_at)
+            VALUES (?1, ?2, ?3)
+            ",
+            params![
+                APP_SETTINGS_KEY,
+                serde_json::to_string(settings)?,
+                Utc::now().to_rfc3339()
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Records that the database was just exported.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Database` for database errors.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub fn record_backup(conn: &Connection) -> Result<(), AppError> {
+        let now = Utc::now().to_rfc3339();
+        conn.execute(
+            r"
+            INSERT OR REPLACE INTO app_settings (key, value, updated_at)
+            VALUES (?1, ?2, ?2)
+            ",
+            params![LAST_BACKUP_KEY, now],
+        )?;
+        Ok(())
+    }
+
+    /// Retrieves when the database was last exported.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    ///
+    /// # Returns
+    ///
+    /// The time of the last export, or `None` if it was never exported.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Database` for database errors.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub fn last_backup_at(conn: &Connection) -> Result<Option<DateTime<Utc>>, AppError> {
+        let value: Option<String> = conn
+            .query_row(
+                "SELECT value FROM app_settings WHERE key = ?1",
+                [LAST_BACKUP_KEY],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        Ok(value
+            .and_then(|v| DateTime::parse_from_rfc3339(&v).ok())
+            .map(|dt| dt.with_timezone(&Utc)))
+    }
+}
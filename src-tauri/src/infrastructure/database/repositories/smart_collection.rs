@@ -0,0 +1,261 @@
+//! Smart Collection Repository
+//!
+//! Provides data access operations for saved smart collection definitions.
+//! All methods are stateless and take a connection reference as their first parameter.
+//!
+//! Only the definitions are stored (the query as JSON); members are computed
+//! on demand by the `query_personas` command.
+//!
+//! # Usage
+//!
+//! ```rust,ignore
+//! let collection = SmartCollectionRepository::create(&conn, &request)?;
+//! let all = SmartCollectionRepository::find_all(&conn)?;
+//! ```
+
+use chrono::Utc;
+use rusqlite::{params, Connection};
+
+use crate::domain::collation;
+use crate::domain::collection::{
+    CreateSmartCollectionRequest, SmartCollection, UpdateSmartCollectionRequest,
+};
+use crate::domain::persona::validate_color;
+use crate::error::AppError;
+
+/// Repository for smart collection database operations.
+///
+/// This struct contains no state; all methods take a connection reference
+/// and can be composed within external transactions.
+pub struct SmartCollectionRepository;
+
+impl SmartCollectionRepository {
+    /// Finds a smart collection by its unique identifier.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `id` - The collection's UUID
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::NotFound` if no collection exists with the given ID.
+    /// Returns `AppError::Database` for other database errors.
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id))]
+    pub fn find_by_id(conn: &Connection, id: &str) -> Result<SmartCollection, AppError> {
+        conn.query_row(
+            r"
+            SELECT id, name, query, created_at, updated_at, color
+            FROM smart_collections WHERE id = ?1
+            ",
+            [id],
+            Self::row_to_collection,
+        )
+        .map_err(|e| match e {
+            rusqlite::Error::QueryReturnedNoRows => {
+                AppError::NotFound(format!("Smart collection with id '{id}' not found"))
+            }
+            _ => AppError::Database(e),
+        })
+    }
+
+    /// Retrieves all smart collections, ordered by name (case- and
+    /// accent-insensitive).
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Database` for database errors.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub fn find_all(conn: &Connection) -> Result<Vec<SmartCollection>, AppError> {
+        let mut stmt = conn.prepare(
+            r"
+            SELECT id, name, query, created_at, updated_at, color
+            FROM smart_collections
+            ",
+        )?;
+
+        let mut collections = stmt
+            .query_map([], Self::row_to_collection)?
+            .collect::<Result<Vec<_>, _>>()?;
+        // NOCASE only folds ASCII, so sort with the app's collation instead
+        collections.sort_by(|a, b| collation::compare(&a.name, &b.name));
+
+        Ok(collections)
+    }
+
+    /// Creates a smart collection from a request.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `request` - The creation request with name and query
+    ///
+    /// # Returns
+    ///
+    /// The newly created collection.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Validation` if the name is empty or taken, the
+    /// query is invalid, or the color is not a hex color.
+    /// Returns `AppError::Database` for other database errors.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub fn create(
+        conn: &Connection,
+        request: &CreateSmartCollectionRequest,
+    ) -> Result<SmartCollection, AppError> {
+        let name = Self::validate_name(conn, &request.name, None)?;
+        request.query.validate()?;
+        let color = request.color.as_deref().map(validate_color).transpose()?;
+
+        let mut collection = SmartCollection::new(name, request.query.clone());
+        collection.color = color;
+
+        conn.execute(
+            r"
+            INSERT INTO smart_collections (id, name, query, created_at, updated_at, color)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            ",
+            params![
+                collection.id,
+                collection.name,
+                serde_json::to_string(&collection.query)?,
+                collection.created_at.to_rfc3339(),
+                collection.updated_at.to_rfc3339(),
+                collection.color,
+            ],
+        )?;
+
+        Ok(collection)
+    }
+
+    /// Updates a smart collection's name, query, or color.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `id` - The collection's UUID
+    /// * `request` - The fields to change
+    ///
+    /// # Returns
+    ///
+    /// The updated collection.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::NotFound` if the collection doesn't exist.
+    /// Returns `AppError::Validation` if the new name is empty or taken, the
+    /// new query is invalid, or the new color is not a hex color.
+    /// Returns `AppError::Database` for other database errors.
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id))]
+    pub fn update(
+        conn: &Connection,
+        id: &str,
+        request: &UpdateSmartCollectionRequest,
+    ) -> Result<SmartCollection, AppError> {
+        let mut collection = Self::find_by_id(conn, id)?;
+
+        if let Some(name) = &request.name {
+            collection.name = Self::validate_name(conn, name, Some(id))?;
+        }
+        if let Some(query) = &request.query {
+            query.validate()?;
+            collection.query = query.clone();
+        }
+        if let Some(color) = &request.color {
+            collection.color = color.as_deref().map(validate_color).transpose()?;
+        }
+        collection.updated_at = Utc::now();
+
+        conn.execute(
+            r"
+            UPDATE smart_collections
+            SET name = ?1, query = ?2, updated_at = ?3, color = ?4
+            WHERE id = ?5
+            ",
+            params![
+                collection.name,
+                serde_json::to_string(&collection.query)?,
+                collection.updated_at.to_rfc3339(),
+                collection.color,
+                id,
+            ],
+        )?;
+
+        Ok(collection)
+    }
+
+    /// Deletes a smart collection. Personas are not affected.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `id` - The collection's UUID
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::NotFound` if the collection doesn't exist.
+    /// Returns `AppError::Database` for other database errors.
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id))]
+    pub fn delete(conn: &Connection, id: &str) -> Result<(), AppError> {
+        let rows = conn.execute("DELETE FROM smart_collections WHERE id = ?1", [id])?;
+        if rows == 0 {
+            return Err(AppError::NotFound(format!(
+                "Smart collection with id '{id}' not found"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Trims a collection name and checks it is non-empty and unused (internal helper).
+    fn validate_name(
+        conn: &Connection,
+        name: &str,
+        exclude_id: Option<&str>,
+    ) -> Result<String, AppError> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(AppError::Validation(
+                "Smart collection name is required".to_string(),
+            ));
+        }
+
+        let exists: bool = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM smart_collections WHERE name = ?1 AND id != ?2)",
+            params![name, exclude_id.unwrap_or_default()],
+            |row| row.get(0),
+        )?;
+        if exists {
+            return Err(AppError::Validation(format!(
+                "A smart collection named '{name}' already exists"
+            )));
+        }
+
+        Ok(name.to_string())
+    }
+
+    /// Helper to convert a row to a `SmartCollection`
+    ///
+    /// Column mapping:
+    /// 0: id, 1: name, 2: query (JSON), 3: `created_at`, 4: `updated_at`, 5: color
+    fn row_to_collection(row: &rusqlite::Row) -> rusqlite::Result<SmartCollection> {
+        // Query stored as JSON; fallback to an empty query if parsing fails
+        let query_json: String = row.get(2)?;
+
+        Ok(SmartCollection {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            query: serde_json::from_str(&query_json).unwrap_or_default(),
+            // Timestamps stored as RFC3339 strings; fallback to now if parsing fails
+            created_at: chrono::DateTime::parse_from_rfc3339(&row.get::<_, String>(3)?)
+                .map_or_else(|_| Utc::now(), |dt| dt.with_timezone(&Utc)),
+            updated_at: chrono::DateTime::parse_from_rfc3339(&row.get::<_, String>(4)?)
+                .map_or_else(|_| Utc::now(), |dt| dt.with_timezone(&Utc)),
+            color: row.get(5)?,
+        })
+    }
+}
@@ -0,0 +1,160 @@
+//! Library Statistics Repository
+//!
+//! Provides the aggregate queries behind the library dashboard. All methods
+//! are stateless and take a connection reference as their first parameter.
+//!
+//! Every count is a single grouped query, so the cost does not grow with the
+//! number of round trips per persona.
+//!
+//! # Usage
+//!
+//! ```rust,ignore
+//! let stats = StatsRepository::library_stats(&conn, hide_mature)?;
+//! let models = StatsRepository::count_personas_by_model(&conn, hide_mature)?;
+//! ```
+
+use rusqlite::Connection;
+
+use crate::domain::stats::{sort_tag_counts, GranularityTokenCount, LibraryStats, TagCount};
+use crate::error::AppError;
+
+/// Repository for library statistics queries.
+///
+/// This struct contains no state; all methods take a connection reference
+/// and can be composed within external transactions.
+pub struct StatsRepository;
+
+impl StatsRepository {
+    /// Counts personas, tokens, tokens per granularity level, and tag use.
+    ///
+    /// Model families are not known to the database; the returned
+    /// `personas_per_model_family` is empty (see
+    /// [`Self::count_personas_by_model`]).
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `hide_mature` - Leave out mature-rated personas and their tokens
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Database` for database errors.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub fn library_stats(conn: &Connection, hide_mature: bool) -> Result<LibraryStats, AppError> {
+        let (persona_count, archived_persona_count): (u32, u32) = conn.query_row(
+            r"
+            SELECT COUNT(*), COALESCE(SUM(archived), 0)
+            FROM personas
+            WHERE ?1 = 0 OR content_rating != 'mature'
+            ",
+            [hide_mature],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+
+        let token_count: u32 = conn.query_row(
+            r"
+            SELECT COUNT(*)
+            FROM tokens t
+            JOIN personas p ON p.id = t.persona_id
+            WHERE ?1 = 0 OR p.content_rating != 'mature'
+            ",
+            [hide_mature],
+            |row| row.get(0),
+        )?;
+
+        // Levels that were deleted while tokens still reference them sort last
+        let mut stmt = conn.prepare(
+            r"
+            SELECT t.granularity_id,
+                   COALESCE(g.name, t.granularity_id),
+                   SUM(t.polarity = 'positive'),
+                   SUM(t.polarity = 'negative')
+            FROM tokens t
+            JOIN personas p ON p.id = t.persona_id
+            LEFT JOIN granularity_levels g ON g.id = t.granularity_id
+            WHERE ?1 = 0 OR p.content_rating != 'mature'
+            GROUP BY t.granularity_id
+            ORDER BY g.display_order IS NULL, g.display_order, t.granularity_id
+            ",
+        )?;
+        let tokens_per_granularity = stmt
+            .query_map([hide_mature], |row| {
+                Ok(GranularityTokenCount {
+                    granularity_id: row.get(0)?,
+                    name: row.get(1)?,
+                    positive: row.get(2)?,
+                    negative: row.get(3)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut stmt = conn.prepare(
+            r"
+            SELECT tag.value, COUNT(DISTINCT p.id)
+            FROM personas p, json_each(p.tags) tag
+            WHERE ?1 = 0 OR p.content_rating != 'mature'
+            GROUP BY tag.value
+            ",
+        )?;
+        let mut tag_histogram = stmt
+            .query_map([hide_mature], |row| {
+                Ok(TagCount {
+                    tag: row.get(0)?,
+                    count: row.get(1)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        sort_tag_counts(&mut tag_histogram);
+
+        Ok(LibraryStats {
+            persona_count,
+            archived_persona_count,
+            token_count,
+            avg_tokens_per_persona: if persona_count == 0 {
+                0.0
+            } else {
+                f64::from(token_count) / f64::from(persona_count)
+            },
+            tokens_per_granularity,
+            tag_histogram,
+            personas_per_model_family: Vec::new(),
+        })
+    }
+
+    /// Counts personas per target image model.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `hide_mature` - Leave out mature-rated personas
+    ///
+    /// # Returns
+    ///
+    /// Model ID and persona count of each model; personas without generation
+    /// parameters are counted under `None`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Database` for database errors.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub fn count_personas_by_model(
+        conn: &Connection,
+        hide_mature: bool,
+    ) -> Result<Vec<(Option<String>, u32)>, AppError> {
+        let mut stmt = conn.prepare(
+            r"
+            SELECT gp.model_id, COUNT(*)
+            FROM personas p
+            LEFT JOIN generation_params gp ON gp.persona_id = p.id
+            WHERE ?1 = 0 OR p.content_rating != 'mature'
+            GROUP BY gp.model_id
+            ",
+        )?;
+
+        let counts = stmt
+            .query_map([hide_mature], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(counts)
+    }
+}
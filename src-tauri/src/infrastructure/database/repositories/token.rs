@@ -8,16 +8,30 @@
 //! ```rust,ignore
 //! let token = TokenRepository::create(&conn, &request)?;
 //! let tokens = TokenRepository::find_by_persona(&conn, &persona_id)?;
+//! let composed_from = TokenRepository::find_resolved_by_persona(&conn, &persona_id)?;
 //! ```
 
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+
 use chrono::Utc;
 use rusqlite::{params, Connection};
 
+use crate::domain::activity::ActivityKind;
+use crate::domain::alias::AliasMap;
+use crate::domain::autocomplete::{CompletionSource, TokenCompletion};
+use crate::domain::inheritance::{resolve_tokens, MAX_INHERITANCE_DEPTH};
+use crate::domain::repository::TokenRepo;
 use crate::domain::token::{
-    CreateTokenRequest, ReorderTokensRequest, Token, TokenPolarity, UpdateTokenRequest,
+    CreateTokenRequest, GeneratedTokenSelection, Granularity, ReorderTokensRequest, Token,
+    TokenPolarity, TokenRevision, UpdateTokenRequest,
 };
 use crate::error::AppError;
 
+use super::{
+    ActivityRepository, PersonaRepository, PromptCacheRepository, TokenRevisionRepository,
+};
+
 /// Repository for token database operations.
 ///
 /// This struct contains no state; all methods take a connection reference
@@ -60,6 +74,7 @@ impl TokenRepository {
     ///
     /// Returns `AppError::NotFound` if no token exists with the given ID.
     /// Returns `AppError::Database` for other database errors.
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id))]
     pub fn find_by_id(conn: &Connection, id: &str) -> Result<Token, AppError> {
         conn.query_row(
             r"
@@ -89,6 +104,7 @@ impl TokenRepository {
     /// # Errors
     ///
     /// Returns `AppError::Database` for database errors.
+    #[tracing::instrument(level = "debug", skip_all, fields(persona_id = %persona_id))]
     pub fn find_by_persona(conn: &Connection, persona_id: &str) -> Result<Vec<Token>, AppError> {
         let mut stmt = conn.prepare(
             r"
@@ -106,9 +122,158 @@ impl TokenRepository {
         Ok(tokens)
     }
 
+    /// Retrieves a persona's tokens with those it inherits from its base
+    /// persona (see `domain::inheritance`).
+    ///
+    /// Use this wherever a persona is composed or exported; editing works on
+    /// [`Self::find_by_persona`], which returns only the persona's own tokens.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `persona_id` - The persona's UUID
+    ///
+    /// # Returns
+    ///
+    /// The persona's own tokens in display order, followed by inherited tokens,
+    /// which keep the base persona's `persona_id`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Database` for database errors.
+    #[tracing::instrument(level = "debug", skip_all, fields(persona_id = %persona_id))]
+    pub fn find_resolved_by_persona(
+        conn: &Connection,
+        persona_id: &str,
+    ) -> Result<Vec<Token>, AppError> {
+        Self::resolve(conn, persona_id, MAX_INHERITANCE_DEPTH)
+    }
+
+    /// Resolves a persona's tokens, following at most `depth` more bases
+    /// (internal helper).
+    fn resolve(conn: &Connection, persona_id: &str, depth: usize) -> Result<Vec<Token>, AppError> {
+        let local = Self::find_by_persona(conn, persona_id)?;
+        let persona = match PersonaRepository::find_by_id(conn, persona_id) {
+            Ok(persona) => persona,
+            Err(AppError::NotFound(_)) => return Ok(local),
+            Err(e) => return Err(e),
+        };
+
+        match &persona.base_persona_id {
+            Some(base_id) if depth > 0 && !persona.inherited_granularities.is_empty() => {
+                let base = Self::resolve(conn, base_id, depth - 1)?;
+                Ok(resolve_tokens(
+                    local,
+                    base,
+                    &persona.inherited_granularities,
+                ))
+            }
+            _ => Ok(local),
+        }
+    }
+
+    /// Retrieves the tokens of every persona in one query.
+    ///
+    /// Results are grouped by persona and ordered by display order within
+    /// each persona.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Database` for database errors.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub fn find_all(conn: &Connection) -> Result<Vec<Token>, AppError> {
+        let mut stmt = conn.prepare(
+            r"
+            SELECT id, persona_id, granularity_id, polarity, content, weight, display_order, created_at, updated_at
+            FROM tokens
+            ORDER BY persona_id, display_order
+            ",
+        )?;
+
+        let tokens = stmt
+            .query_map([], Self::row_to_token)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(tokens)
+    }
+
+    /// Finds token contents starting with a prefix, for autocompletion.
+    ///
+    /// Matches the indexed `content_key` column by range, so lookups stay fast
+    /// however large the library is. Contents sharing a key are counted
+    /// together.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `prefix_key` - Completion key of the typed prefix (see `domain::autocomplete`)
+    /// * `granularity_id` - Only count tokens of this granularity level (default: all)
+    /// * `limit` - Maximum number of completions
+    /// * `hide_mature` - Leave out tokens of mature-rated personas
+    ///
+    /// # Returns
+    ///
+    /// Completions ordered by the number of tokens using them (most first),
+    /// then alphabetically; empty if the prefix is empty.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Database` for database errors.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub fn complete_prefix(
+        conn: &Connection,
+        prefix_key: &str,
+        granularity_id: Option<&str>,
+        limit: usize,
+        hide_mature: bool,
+    ) -> Result<Vec<TokenCompletion>, AppError> {
+        if prefix_key.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // Every key starting with the prefix sorts between these two
+        let upper = format!("{prefix_key}{}", char::MAX);
+        // MAX prefers lowercase spellings, as usual in prompts
+        let mut stmt = conn.prepare(
+            r"
+            SELECT MAX(t.content), COUNT(*) AS uses
+            FROM tokens t
+            WHERE t.content_key >= ?1 AND t.content_key < ?2
+              AND (?3 IS NULL OR t.granularity_id = ?3)
+              AND (?5 = 0 OR t.persona_id NOT IN (
+                  SELECT id FROM personas WHERE content_rating = 'mature'
+              ))
+            GROUP BY t.content_key
+            ORDER BY uses DESC, t.content_key
+            LIMIT ?4
+            ",
+        )?;
+
+        let completions = stmt
+            .query_map(
+                params![prefix_key, upper, granularity_id, limit as i64, hide_mature],
+                |row| {
+                    Ok(TokenCompletion {
+                        content: row.get(0)?,
+                        source: CompletionSource::Library,
+                        count: u64::try_from(row.get::<_, i64>(1)?).unwrap_or_default(),
+                    })
+                },
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(completions)
+    }
+
     /// Updates a token with the provided changes.
     ///
     /// Fetches the existing token, applies the update request, and persists.
+    /// If the content or weight changes, the previous values are recorded as
+    /// a revision.
     ///
     /// # Arguments
     ///
@@ -123,14 +288,28 @@ impl TokenRepository {
     /// # Errors
     ///
     /// Returns `AppError::NotFound` if the token doesn't exist.
+    /// Returns `AppError::Conflict` if `expected_updated_at` is stale.
     /// Returns `AppError::Database` for other database errors.
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id))]
     pub fn update(
         conn: &Connection,
         id: &str,
         request: &UpdateTokenRequest,
     ) -> Result<Token, AppError> {
         let mut token = Self::find_by_id(conn, id)?;
+        AppError::check_version(
+            request.expected_updated_at,
+            token.updated_at,
+            &format!("Token '{}'", token.content),
+            &token,
+        )?;
+        let previous = token.clone();
         token.update(request);
+        if token.content != previous.content
+            || (token.weight - previous.weight).abs() > f64::EPSILON
+        {
+            TokenRevisionRepository::record(conn, &previous)?;
+        }
 
         conn.execute(
             r"
@@ -148,11 +327,19 @@ impl TokenRepository {
             ],
         )?;
 
+        PersonaRepository::refresh_content_hash(conn, &token.persona_id)?;
+
+        PromptCacheRepository::invalidate(conn, &token.persona_id)?;
+        ActivityRepository::record(conn, &token.persona_id, ActivityKind::Modified)?;
+
         Ok(token)
     }
 
     /// Deletes a token from the database.
     ///
+    /// The remaining tokens of the persona are renumbered so that no gap is
+    /// left in the display order.
+    ///
     /// # Arguments
     ///
     /// * `conn` - Database connection reference
@@ -162,16 +349,86 @@ impl TokenRepository {
     ///
     /// Returns `AppError::NotFound` if the token doesn't exist.
     /// Returns `AppError::Database` for other database errors.
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id))]
     pub fn delete(conn: &Connection, id: &str) -> Result<(), AppError> {
-        let rows = conn.execute("DELETE FROM tokens WHERE id = ?1", [id])?;
-        if rows == 0 {
-            return Err(AppError::NotFound(format!(
-                "Token with id '{id}' not found"
-            )));
-        }
+        let token = Self::find_by_id(conn, id)?;
+        conn.execute("DELETE FROM tokens WHERE id = ?1", [id])?;
+        Self::normalize_token_order(conn, &token.persona_id)?;
+
+        PersonaRepository::refresh_content_hash(conn, &token.persona_id)?;
+
+        PromptCacheRepository::invalidate(conn, &token.persona_id)?;
+        ActivityRepository::record(conn, &token.persona_id, ActivityKind::Modified)?;
+
         Ok(())
     }
 
+    /// Deletes all of a persona's tokens in one granularity level.
+    ///
+    /// The remaining tokens are renumbered so that no gap is left in the
+    /// display order.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `persona_id` - The persona's UUID
+    /// * `granularity_id` - Level whose tokens are deleted
+    ///
+    /// # Returns
+    ///
+    /// The number of deleted tokens.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Database` for database errors.
+    #[tracing::instrument(level = "debug", skip_all, fields(persona_id = %persona_id))]
+    pub fn delete_by_granularity(
+        conn: &Connection,
+        persona_id: &str,
+        granularity_id: &str,
+    ) -> Result<usize, AppError> {
+        let deleted = conn.execute(
+            "DELETE FROM tokens WHERE persona_id = ?1 AND granularity_id = ?2",
+            params![persona_id, granularity_id],
+        )?;
+
+        if deleted > 0 {
+            Self::normalize_token_order(conn, persona_id)?;
+            PersonaRepository::refresh_content_hash(conn, persona_id)?;
+            PromptCacheRepository::invalidate(conn, persona_id)?;
+            ActivityRepository::record(conn, persona_id, ActivityKind::Modified)?;
+        }
+
+        Ok(deleted)
+    }
+
+    /// Deletes all of a persona's tokens.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `persona_id` - The persona's UUID
+    ///
+    /// # Returns
+    ///
+    /// The number of deleted tokens.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Database` for database errors.
+    #[tracing::instrument(level = "debug", skip_all, fields(persona_id = %persona_id))]
+    pub fn delete_by_persona(conn: &Connection, persona_id: &str) -> Result<usize, AppError> {
+        let deleted = conn.execute("DELETE FROM tokens WHERE persona_id = ?1", [persona_id])?;
+
+        if deleted > 0 {
+            PersonaRepository::refresh_content_hash(conn, persona_id)?;
+            PromptCacheRepository::invalidate(conn, persona_id)?;
+            ActivityRepository::record(conn, persona_id, ActivityKind::Modified)?;
+        }
+
+        Ok(deleted)
+    }
+
     /// Calculates the next global display order for a new token (internal helper).
     ///
     /// Returns the next available position after all existing tokens in the persona.
@@ -204,6 +461,7 @@ impl TokenRepository {
     /// # Errors
     ///
     /// Returns `AppError::Database` if the insert fails.
+    #[tracing::instrument(level = "debug", skip_all)]
     pub fn create(conn: &Connection, request: &CreateTokenRequest) -> Result<Token, AppError> {
         let display_order = Self::get_next_display_order(conn, &request.persona_id)?;
 
@@ -217,10 +475,70 @@ impl TokenRepository {
         );
 
         Self::insert(conn, &token)?;
+        PersonaRepository::refresh_content_hash(conn, &token.persona_id)?;
+        PromptCacheRepository::invalidate(conn, &token.persona_id)?;
+        ActivityRepository::record(conn, &token.persona_id, ActivityKind::Modified)?;
 
         Ok(token)
     }
 
+    /// Creates tokens from several requests.
+    ///
+    /// Each token is created as by [`Self::create`], in request order, but the
+    /// content hash, prompt cache, and activity of each persona are refreshed
+    /// once at the end rather than once per token.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `requests` - The creation requests, for one or more personas
+    ///
+    /// # Returns
+    ///
+    /// Returns a vector of the newly created token entities.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Database` if any insert fails.
+    #[tracing::instrument(level = "debug", skip_all, fields(count = requests.len()))]
+    pub fn create_all(
+        conn: &Connection,
+        requests: &[CreateTokenRequest],
+    ) -> Result<Vec<Token>, AppError> {
+        let mut tokens = Vec::with_capacity(requests.len());
+        let mut next_orders: HashMap<&str, i32> = HashMap::new();
+
+        for request in requests {
+            let display_order = match next_orders.entry(&request.persona_id) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => {
+                    entry.insert(Self::get_next_display_order(conn, &request.persona_id)?)
+                }
+            };
+
+            let token = Token::new(
+                request.persona_id.clone(),
+                request.granularity_id.clone(),
+                request.polarity,
+                request.content.clone(),
+                request.weight,
+                *display_order,
+            );
+            *display_order += 1;
+
+            Self::insert(conn, &token)?;
+            tokens.push(token);
+        }
+
+        for persona_id in next_orders.keys() {
+            PersonaRepository::refresh_content_hash(conn, persona_id)?;
+            PromptCacheRepository::invalidate(conn, persona_id)?;
+            ActivityRepository::record(conn, persona_id, ActivityKind::Modified)?;
+        }
+
+        Ok(tokens)
+    }
+
     /// Creates multiple tokens in batch.
     ///
     /// Each token is assigned sequential global display orders starting from the
@@ -242,6 +560,7 @@ impl TokenRepository {
     /// # Errors
     ///
     /// Returns `AppError::Database` if any insert fails.
+    #[tracing::instrument(level = "debug", skip_all, fields(persona_id = %persona_id))]
     pub fn create_batch(
         conn: &Connection,
         persona_id: &str,
@@ -272,9 +591,184 @@ impl TokenRepository {
             display_order += 1;
         }
 
+        if !tokens.is_empty() {
+            PersonaRepository::refresh_content_hash(conn, persona_id)?;
+            PromptCacheRepository::invalidate(conn, persona_id)?;
+            ActivityRepository::record(conn, persona_id, ActivityKind::Modified)?;
+        }
+
         Ok(tokens)
     }
 
+    /// Appends copies of tokens to a persona.
+    ///
+    /// Each copy keeps the granularity, polarity, content, and weight of its
+    /// source token; copies follow the persona's existing tokens in the order
+    /// given.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `persona_id` - The persona receiving the copies
+    /// * `tokens` - Tokens to copy, from any persona
+    ///
+    /// # Returns
+    ///
+    /// Returns a vector of the newly created token entities.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Database` if any insert fails.
+    #[tracing::instrument(level = "debug", skip_all, fields(persona_id = %persona_id))]
+    pub fn copy_to_persona(
+        conn: &Connection,
+        persona_id: &str,
+        tokens: &[Token],
+    ) -> Result<Vec<Token>, AppError> {
+        let first_order = Self::get_next_display_order(conn, persona_id)?;
+
+        let copies: Vec<Token> = tokens
+            .iter()
+            .zip(first_order..)
+            .map(|(source, display_order)| {
+                Token::new(
+                    persona_id.to_string(),
+                    source.granularity_id.clone(),
+                    source.polarity,
+                    source.content.clone(),
+                    source.weight,
+                    display_order,
+                )
+            })
+            .collect();
+        for token in &copies {
+            Self::insert(conn, token)?;
+        }
+
+        if !copies.is_empty() {
+            PersonaRepository::refresh_content_hash(conn, persona_id)?;
+            PromptCacheRepository::invalidate(conn, persona_id)?;
+            ActivityRepository::record(conn, persona_id, ActivityKind::Modified)?;
+        }
+
+        Ok(copies)
+    }
+
+    /// Creates tokens from accepted AI suggestions.
+    ///
+    /// Each selection keeps its own granularity, polarity, and weight. Tokens are
+    /// appended after existing ones in selection order. Empty contents are skipped.
+    /// Run inside a transaction so a failed selection leaves no partial writes.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `persona_id` - The parent persona's UUID
+    /// * `selections` - The accepted suggestions
+    ///
+    /// # Returns
+    ///
+    /// Returns a vector of the newly created token entities.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Validation` if a selection has an unknown granularity.
+    /// Returns `AppError::Database` if any insert fails.
+    #[tracing::instrument(level = "debug", skip_all, fields(persona_id = %persona_id))]
+    pub fn create_from_selections(
+        conn: &Connection,
+        persona_id: &str,
+        selections: &[GeneratedTokenSelection],
+    ) -> Result<Vec<Token>, AppError> {
+        let mut tokens = Vec::new();
+        let mut display_order = Self::get_next_display_order(conn, persona_id)?;
+
+        for selection in selections {
+            let content = selection.content.trim();
+            if content.is_empty() {
+                continue;
+            }
+
+            if Granularity::parse(&selection.granularity_id).is_none() {
+                return Err(AppError::Validation(format!(
+                    "Unknown granularity '{}'",
+                    selection.granularity_id
+                )));
+            }
+
+            let token = Token::new(
+                persona_id.to_string(),
+                selection.granularity_id.clone(),
+                selection.polarity,
+                content.to_string(),
+                selection.weight,
+                display_order,
+            );
+
+            Self::insert(conn, &token)?;
+            tokens.push(token);
+            display_order += 1;
+        }
+
+        if !tokens.is_empty() {
+            PersonaRepository::refresh_content_hash(conn, persona_id)?;
+            PromptCacheRepository::invalidate(conn, persona_id)?;
+            ActivityRepository::record(conn, persona_id, ActivityKind::Modified)?;
+        }
+
+        Ok(tokens)
+    }
+
+    /// Creates tokens from selections, skipping those the persona already has.
+    ///
+    /// A selection is skipped if the persona has a token with the same
+    /// granularity, polarity, and phrasing, or if it repeats an earlier
+    /// selection. Contents are compared by [`AliasMap::key`], so an aliased
+    /// variant counts as its phrasing. The rest are created as by
+    /// [`Self::create_from_selections`].
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `persona_id` - The parent persona's UUID
+    /// * `selections` - Tokens to add, in order
+    /// * `aliases` - The user's aliases
+    ///
+    /// # Returns
+    ///
+    /// Returns a vector of the newly created token entities.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Validation` if a selection has an unknown granularity.
+    /// Returns `AppError::Database` if any insert fails.
+    #[tracing::instrument(level = "debug", skip_all, fields(persona_id = %persona_id))]
+    pub fn create_missing(
+        conn: &Connection,
+        persona_id: &str,
+        selections: &[GeneratedTokenSelection],
+        aliases: &AliasMap,
+    ) -> Result<Vec<Token>, AppError> {
+        let mut existing: HashSet<_> = Self::find_by_persona(conn, persona_id)?
+            .into_iter()
+            .map(|t| (t.granularity_id, t.polarity, aliases.key(&t.content)))
+            .collect();
+
+        let missing: Vec<_> = selections
+            .iter()
+            .filter(|s| {
+                existing.insert((
+                    s.granularity_id.clone(),
+                    s.polarity,
+                    aliases.key(&s.content),
+                ))
+            })
+            .cloned()
+            .collect();
+
+        Self::create_from_selections(conn, persona_id, &missing)
+    }
+
     /// Reorders tokens within a persona by updating display_order values.
     ///
     /// All updates are performed atomically. The frontend computes the new
@@ -289,6 +783,7 @@ impl TokenRepository {
     ///
     /// Returns `AppError::Validation` if any token doesn't belong to the persona.
     /// Returns `AppError::Database` for database errors.
+    #[tracing::instrument(level = "debug", skip_all)]
     pub fn reorder_tokens(
         conn: &Connection,
         request: &ReorderTokensRequest,
@@ -312,10 +807,165 @@ impl TokenRepository {
                 params![order.display_order, &now, &order.token_id],
             )?;
         }
+        PromptCacheRepository::invalidate(conn, &request.persona_id)?;
+        ActivityRepository::record(conn, &request.persona_id, ActivityKind::Modified)?;
 
         Ok(())
     }
 
+    /// Reorders the tokens of one granularity level, leaving the relative
+    /// positions of other levels intact.
+    ///
+    /// See [`ReorderTokensRequest::within_granularity`] for how the global
+    /// order is recomputed.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `persona_id` - The persona's UUID
+    /// * `granularity_id` - Level whose tokens are reordered
+    /// * `ordered_token_ids` - Every token ID of that level, in the new order
+    ///
+    /// # Returns
+    ///
+    /// All of the persona's tokens in their new order.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Validation` if `ordered_token_ids` does not list each
+    /// token of the level exactly once.
+    /// Returns `AppError::Database` for database errors.
+    #[tracing::instrument(level = "debug", skip_all, fields(persona_id = %persona_id))]
+    pub fn reorder_within_granularity(
+        conn: &Connection,
+        persona_id: &str,
+        granularity_id: &str,
+        ordered_token_ids: &[String],
+    ) -> Result<Vec<Token>, AppError> {
+        let tokens = Self::find_by_persona(conn, persona_id)?;
+        let request = ReorderTokensRequest::within_granularity(
+            persona_id,
+            &tokens,
+            granularity_id,
+            ordered_token_ids,
+        )?;
+        Self::reorder_tokens(conn, &request)?;
+
+        Self::find_by_persona(conn, persona_id)
+    }
+
+    /// Renumbers a persona's tokens to consecutive display orders starting at 0.
+    ///
+    /// The current order is kept; tokens sharing a display order are ordered
+    /// by creation time. Only tokens whose position changes are written, and
+    /// the prompt cache is invalidated if any was.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `persona_id` - The persona's UUID
+    ///
+    /// # Returns
+    ///
+    /// `true` if any token was renumbered.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Database` for database errors.
+    #[tracing::instrument(level = "debug", skip_all, fields(persona_id = %persona_id))]
+    pub fn normalize_token_order(conn: &Connection, persona_id: &str) -> Result<bool, AppError> {
+        let mut stmt = conn.prepare(
+            r"
+            SELECT id, display_order
+            FROM tokens
+            WHERE persona_id = ?1
+            ORDER BY display_order, created_at, id
+            ",
+        )?;
+        let orders = stmt
+            .query_map([persona_id], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, i32>(1)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut changed = false;
+        for (position, (id, display_order)) in orders.iter().enumerate() {
+            let position = position as i32;
+            if *display_order != position {
+                conn.execute(
+                    r"UPDATE tokens SET display_order = ?1 WHERE id = ?2",
+                    params![position, id],
+                )?;
+                changed = true;
+            }
+        }
+
+        if changed {
+            PromptCacheRepository::invalidate(conn, persona_id)?;
+        }
+
+        Ok(changed)
+    }
+
+    /// Finds the personas whose token display orders have gaps or duplicates.
+    ///
+    /// Read-only; see [`Self::normalize_all_token_orders`] for the repair.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    ///
+    /// # Returns
+    ///
+    /// The UUIDs of the affected personas.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Database` for database errors.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub fn find_denormalized_personas(conn: &Connection) -> Result<Vec<String>, AppError> {
+        let mut stmt = conn.prepare(
+            r"
+            SELECT persona_id
+            FROM tokens
+            GROUP BY persona_id
+            HAVING MIN(display_order) != 0
+                OR MAX(display_order) != COUNT(*) - 1
+                OR COUNT(DISTINCT display_order) != COUNT(*)
+            ",
+        )?;
+        let persona_ids = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(persona_ids)
+    }
+
+    /// Renumbers the tokens of every persona whose display orders have gaps
+    /// or duplicates.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    ///
+    /// # Returns
+    ///
+    /// The number of personas whose tokens were renumbered.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Database` for database errors.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub fn normalize_all_token_orders(conn: &Connection) -> Result<usize, AppError> {
+        let persona_ids = Self::find_denormalized_personas(conn)?;
+
+        for persona_id in &persona_ids {
+            Self::normalize_token_order(conn, persona_id)?;
+        }
+
+        Ok(persona_ids.len())
+    }
+
     /// Helper function to convert a row to a Token
     ///
     /// Column mapping:
@@ -342,3 +992,78 @@ impl TokenRepository {
         })
     }
 }
+
+/// The `SQLite` implementation of [`TokenRepo`], delegating to [`TokenRepository`].
+impl TokenRepo for Connection {
+    fn find_by_id(&self, id: &str) -> Result<Token, AppError> {
+        TokenRepository::find_by_id(self, id)
+    }
+
+    fn find_by_persona(&self, persona_id: &str) -> Result<Vec<Token>, AppError> {
+        TokenRepository::find_by_persona(self, persona_id)
+    }
+
+    fn create(&self, request: &CreateTokenRequest) -> Result<Token, AppError> {
+        TokenRepository::create(self, request)
+    }
+
+    fn create_from_selections(
+        &self,
+        persona_id: &str,
+        selections: &[GeneratedTokenSelection],
+    ) -> Result<Vec<Token>, AppError> {
+        TokenRepository::create_from_selections(self, persona_id, selections)
+    }
+
+    fn create_batch(
+        &self,
+        persona_id: &str,
+        granularity_id: &str,
+        polarity: TokenPolarity,
+        contents: &[String],
+        weight: f64,
+    ) -> Result<Vec<Token>, AppError> {
+        TokenRepository::create_batch(self, persona_id, granularity_id, polarity, contents, weight)
+    }
+
+    fn create_all(&self, requests: &[CreateTokenRequest]) -> Result<Vec<Token>, AppError> {
+        TokenRepository::create_all(self, requests)
+    }
+
+    fn create_missing(
+        &self,
+        persona_id: &str,
+        selections: &[GeneratedTokenSelection],
+        aliases: &AliasMap,
+    ) -> Result<Vec<Token>, AppError> {
+        TokenRepository::create_missing(self, persona_id, selections, aliases)
+    }
+
+    fn update(&self, id: &str, request: &UpdateTokenRequest) -> Result<Token, AppError> {
+        TokenRepository::update(self, id, request)
+    }
+
+    fn delete(&self, id: &str) -> Result<(), AppError> {
+        TokenRepository::delete(self, id)
+    }
+
+    fn delete_by_persona(&self, persona_id: &str) -> Result<usize, AppError> {
+        TokenRepository::delete_by_persona(self, persona_id)
+    }
+
+    fn delete_by_granularity(
+        &self,
+        persona_id: &str,
+        granularity_id: &str,
+    ) -> Result<usize, AppError> {
+        TokenRepository::delete_by_granularity(self, persona_id, granularity_id)
+    }
+
+    fn normalize_token_order(&self, persona_id: &str) -> Result<bool, AppError> {
+        TokenRepository::normalize_token_order(self, persona_id)
+    }
+
+    fn find_revision(&self, token_id: &str, revision: i32) -> Result<TokenRevision, AppError> {
+        TokenRevisionRepository::find(self, token_id, revision)
+    }
+}
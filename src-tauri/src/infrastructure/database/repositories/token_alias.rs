@@ -0,0 +1,150 @@
+//! Token Alias Repository
+//!
+//! Provides data access operations for the user's token aliases.
+//! All methods are stateless and take a connection reference as their first parameter.
+//!
+//! Cached prompts may be composed with aliases applied, so adding or removing
+//! an alias clears every cached prompt (see [`PromptCacheRepository::invalidate_all`]).
+//!
+//! # Usage
+//!
+//! ```rust,ignore
+//! let alias = TokenAliasRepository::create(&conn, "ginger hair", "red hair")?;
+//! let aliases = TokenAliasRepository::find_all(&conn)?;
+//! ```
+
+use chrono::Utc;
+use rusqlite::{params, Connection};
+
+use super::PromptCacheRepository;
+use crate::domain::alias::{self, TokenAlias};
+use crate::domain::collation;
+use crate::domain::repository::AliasRepo;
+use crate::error::AppError;
+
+/// Repository for token alias database operations.
+///
+/// This struct contains no state; all methods take a connection reference
+/// and can be composed within external transactions.
+pub struct TokenAliasRepository;
+
+impl TokenAliasRepository {
+    /// Retrieves all aliases, ordered alphabetically by alias (case- and
+    /// accent-insensitive).
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Database` for database errors.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub fn find_all(conn: &Connection) -> Result<Vec<TokenAlias>, AppError> {
+        let mut stmt = conn.prepare(
+            r"
+            SELECT id, alias, canonical, created_at
+            FROM token_aliases
+            ",
+        )?;
+
+        let mut aliases = stmt
+            .query_map([], Self::row_to_alias)?
+            .collect::<Result<Vec<_>, _>>()?;
+        aliases.sort_by(|a, b| collation::compare(&a.alias, &b.alias));
+
+        Ok(aliases)
+    }
+
+    /// Adds an alias and clears all cached prompts.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `alias` - The variant to replace; surrounding and repeated
+    ///   whitespace is removed
+    /// * `canonical` - The phrasing to use instead, normalized the same way
+    ///
+    /// # Returns
+    ///
+    /// The newly created alias.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Validation` if either text is empty, the alias
+    /// matches its phrasing, or it would repeat or chain an existing alias
+    /// (see `alias::validate_new`).
+    /// Returns `AppError::Database` for other database errors.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub fn create(conn: &Connection, alias: &str, canonical: &str) -> Result<TokenAlias, AppError> {
+        let alias = alias::normalize_phrase(alias)?;
+        let canonical = alias::normalize_phrase(canonical)?;
+        alias::validate_new(&alias, &canonical, &Self::find_all(conn)?)?;
+
+        let created = TokenAlias::new(alias, canonical);
+        conn.execute(
+            r"
+            INSERT INTO token_aliases (id, alias, canonical, created_at)
+            VALUES (?1, ?2, ?3, ?4)
+            ",
+            params![
+                created.id,
+                created.alias,
+                created.canonical,
+                created.created_at.to_rfc3339(),
+            ],
+        )?;
+        PromptCacheRepository::invalidate_all(conn)?;
+
+        Ok(created)
+    }
+
+    /// Removes an alias and clears all cached prompts.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `id` - The alias's UUID
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::NotFound` if the alias doesn't exist.
+    /// Returns `AppError::Database` for other database errors.
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id))]
+    pub fn delete(conn: &Connection, id: &str) -> Result<(), AppError> {
+        let rows = conn.execute("DELETE FROM token_aliases WHERE id = ?1", [id])?;
+        if rows == 0 {
+            return Err(AppError::NotFound(format!(
+                "Token alias with id '{id}' not found"
+            )));
+        }
+        PromptCacheRepository::invalidate_all(conn)?;
+        Ok(())
+    }
+
+    /// Helper to convert a row to a `TokenAlias`
+    ///
+    /// Column mapping:
+    /// 0: id, 1: alias, 2: canonical, 3: `created_at`
+    fn row_to_alias(row: &rusqlite::Row) -> rusqlite::Result<TokenAlias> {
+        Ok(TokenAlias {
+            id: row.get(0)?,
+            alias: row.get(1)?,
+            canonical: row.get(2)?,
+            // Timestamps stored as RFC3339 strings; fallback to now if parsing fails
+            created_at: chrono::DateTime::parse_from_rfc3339(&row.get::<_, String>(3)?)
+                .map_or_else(|_| Utc::now(), |dt| dt.with_timezone(&Utc)),
+        })
+    }
+}
+
+/// The `SQLite` implementation of [`AliasRepo`], delegating to [`TokenAliasRepository`].
+impl AliasRepo for Connection {
+    fn find_all(&self) -> Result<Vec<TokenAlias>, AppError> {
+        TokenAliasRepository::find_all(self)
+    }
+
+    fn create(&self, alias: &str, canonical: &str) -> Result<TokenAlias, AppError> {
+        TokenAliasRepository::create(self, alias, canonical)
+    }
+}
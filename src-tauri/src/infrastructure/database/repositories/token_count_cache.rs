@@ -0,0 +1,141 @@
+//! Token Count Cache Repository
+//!
+//! Provides data access operations for the persistent token count cache.
+//! All methods are stateless and take a connection reference as their first parameter.
+//!
+//! Counts are keyed by the text's hash (see `tokenizer::text_hash`) and the
+//! tokenizer ID, so the same text counted for two models sharing a tokenizer
+//! is stored once. Only exact counts are cached, never word-based estimates.
+//!
+//! # Usage
+//!
+//! ```rust,ignore
+//! let count = TokenCountCacheRepository::find(&conn, &hash, &config.tokenizer_id)?;
+//! TokenCountCacheRepository::store(&conn, &hash, &config.tokenizer_id, 42)?;
+//! ```
+
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::error::AppError;
+
+/// Maximum number of cached counts kept by [`TokenCountCacheRepository::prune`].
+pub const MAX_CACHED_COUNTS: usize = 10_000;
+
+/// Repository for token count cache database operations.
+///
+/// This struct contains no state; all methods take a connection reference
+/// and can be composed within external transactions.
+pub struct TokenCountCacheRepository;
+
+impl TokenCountCacheRepository {
+    /// Retrieves a cached token count.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `text_hash` - Hash of the counted text
+    /// * `tokenizer_id` - The `HuggingFace` tokenizer ID
+    ///
+    /// # Returns
+    ///
+    /// The cached count, or `None` if the text has not been counted.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Database` for database errors.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub fn find(
+        conn: &Connection,
+        text_hash: &str,
+        tokenizer_id: &str,
+    ) -> Result<Option<usize>, AppError> {
+        let count: Option<i64> = conn
+            .query_row(
+                "SELECT count FROM token_count_cache WHERE text_hash = ?1 AND tokenizer_id = ?2",
+                params![text_hash, tokenizer_id],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        Ok(count.and_then(|c| usize::try_from(c).ok()))
+    }
+
+    /// Stores an exact token count, replacing any previous entry.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `text_hash` - Hash of the counted text
+    /// * `tokenizer_id` - The `HuggingFace` tokenizer ID
+    /// * `count` - Number of tokens
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Database` for database errors.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub fn store(
+        conn: &Connection,
+        text_hash: &str,
+        tokenizer_id: &str,
+        count: usize,
+    ) -> Result<(), AppError> {
+        conn.execute(
+            r"
+            INSERT OR REPLACE INTO token_count_cache (text_hash, tokenizer_id, count, created_at)
+            VALUES (?1, ?2, ?3, ?4)
+            ",
+            params![
+                text_hash,
+                tokenizer_id,
+                count as i64,
+                Utc::now().to_rfc3339()
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Counts the cached entries.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Database` for database errors.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub fn count(conn: &Connection) -> Result<usize, AppError> {
+        let count: i64 = conn.query_row("SELECT COUNT(*) FROM token_count_cache", [], |row| {
+            row.get(0)
+        })?;
+        Ok(usize::try_from(count).unwrap_or_default())
+    }
+
+    /// Deletes the oldest entries so at most `max_entries` remain.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `max_entries` - Number of most recent entries to keep
+    ///
+    /// # Returns
+    ///
+    /// The number of entries deleted.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Database` for database errors.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub fn prune(conn: &Connection, max_entries: usize) -> Result<usize, AppError> {
+        let deleted = conn.execute(
+            r"
+            DELETE FROM token_count_cache WHERE rowid NOT IN (
+                SELECT rowid FROM token_count_cache ORDER BY created_at DESC LIMIT ?1
+            )
+            ",
+            [max_entries as i64],
+        )?;
+        Ok(deleted)
+    }
+}
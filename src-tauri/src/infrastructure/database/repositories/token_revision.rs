@@ -0,0 +1,163 @@
+//! Token Revision Repository
+//!
+//! Provides data access operations for the edit trail of tokens.
+//! All methods are stateless and take a connection reference as their first parameter.
+//!
+//! Revisions are recorded by [`TokenRepository::update`](super::TokenRepository::update)
+//! and removed with their token by a cascading foreign key.
+//!
+//! # Usage
+//!
+//! ```rust,ignore
+//! let history = TokenRevisionRepository::find_by_token(&conn, &token_id)?;
+//! let revision = TokenRevisionRepository::find(&conn, &token_id, 3)?;
+//! ```
+
+use chrono::Utc;
+use rusqlite::{params, Connection};
+
+use crate::domain::token::{Token, TokenRevision};
+use crate::error::AppError;
+
+/// Number of revisions kept per token; older ones are dropped.
+const MAX_REVISIONS_PER_TOKEN: i64 = 50;
+
+/// Repository for token revision database operations.
+///
+/// This struct contains no state; all methods take a connection reference
+/// and can be composed within external transactions.
+pub struct TokenRevisionRepository;
+
+impl TokenRevisionRepository {
+    /// Records a token's current content and weight as its next revision.
+    ///
+    /// Call this with the token as it was before an edit.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `token` - The token before the edit
+    ///
+    /// # Returns
+    ///
+    /// The number of the new revision.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Database` for database errors.
+    #[tracing::instrument(level = "debug", skip_all, fields(token_id = %token.id))]
+    pub fn record(conn: &Connection, token: &Token) -> Result<i32, AppError> {
+        let revision: i32 = conn.query_row(
+            r"SELECT COALESCE(MAX(revision), 0) + 1 FROM token_revisions WHERE token_id = ?1",
+            [&token.id],
+            |row| row.get(0),
+        )?;
+
+        conn.execute(
+            r"
+            INSERT INTO token_revisions (token_id, revision, content, weight, recorded_at)
+            VALUES (?1, ?2, ?3, ?4, ?5)
+            ",
+            params![
+                token.id,
+                revision,
+                token.content,
+                token.weight,
+                Utc::now().to_rfc3339(),
+            ],
+        )?;
+
+        conn.execute(
+            r"DELETE FROM token_revisions WHERE token_id = ?1 AND revision <= ?2",
+            params![token.id, i64::from(revision) - MAX_REVISIONS_PER_TOKEN],
+        )?;
+
+        Ok(revision)
+    }
+
+    /// Lists the recorded revisions of a token.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `token_id` - The token's UUID
+    ///
+    /// # Returns
+    ///
+    /// Revisions, most recent first. Empty if the token was never edited.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Database` for database errors.
+    #[tracing::instrument(level = "debug", skip_all, fields(token_id = %token_id))]
+    pub fn find_by_token(
+        conn: &Connection,
+        token_id: &str,
+    ) -> Result<Vec<TokenRevision>, AppError> {
+        let mut stmt = conn.prepare(
+            r"
+            SELECT token_id, revision, content, weight, recorded_at
+            FROM token_revisions
+            WHERE token_id = ?1
+            ORDER BY revision DESC
+            ",
+        )?;
+
+        let revisions = stmt
+            .query_map([token_id], Self::row_to_revision)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(revisions)
+    }
+
+    /// Finds a single revision of a token.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - Database connection reference
+    /// * `token_id` - The token's UUID
+    /// * `revision` - The revision number
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::NotFound` if the token has no such revision.
+    /// Returns `AppError::Database` for other database errors.
+    #[tracing::instrument(level = "debug", skip_all, fields(token_id = %token_id, revision))]
+    pub fn find(
+        conn: &Connection,
+        token_id: &str,
+        revision: i32,
+    ) -> Result<TokenRevision, AppError> {
+        conn.query_row(
+            r"
+            SELECT token_id, revision, content, weight, recorded_at
+            FROM token_revisions
+            WHERE token_id = ?1 AND revision = ?2
+            ",
+            params![token_id, revision],
+            Self::row_to_revision,
+        )
+        .map_err(|e| match e {
+            rusqlite::Error::QueryReturnedNoRows => AppError::NotFound(format!(
+                "Revision {revision} of token '{token_id}' not found"
+            )),
+            _ => AppError::Database(e),
+        })
+    }
+
+    /// Helper function to convert a row to a `TokenRevision`
+    ///
+    /// Column mapping:
+    /// 0: `token_id`, 1: revision, 2: content, 3: weight, 4: `recorded_at`
+    fn row_to_revision(row: &rusqlite::Row) -> Result<TokenRevision, rusqlite::Error> {
+        Ok(TokenRevision {
+            token_id: row.get(0)?,
+            revision: row.get(1)?,
+            content: row.get(2)?,
+            weight: row.get(3)?,
+            // Timestamps stored as RFC3339 strings; fallback to now if parsing fails
+            recorded_at: chrono::DateTime::parse_from_rfc3339(&row.get::<_, String>(4)?)
+                .map_or_else(|_| Utc::now(), |dt| dt.with_timezone(&Utc)),
+        })
+    }
+}
@@ -0,0 +1,185 @@
+//! Unit of Work
+//!
+//! Groups the writes of one operation into a single `SQLite` transaction so a
+//! crash, error, or power loss never leaves it half applied (e.g., a persona
+//! without generation parameters, or a token whose persona content hash was
+//! not refreshed). Every command that performs more than one write must run
+//! them through [`unit_of_work`] or [`Database::unit_of_work`].
+//!
+//! # Behavior
+//!
+//! - The transaction is `IMMEDIATE`, taking the write lock up front so the
+//!   operation cannot fail halfway with `SQLITE_BUSY`
+//! - It commits when the work returns `Ok` and rolls back on `Err`
+//! - Nested calls run inside a `SAVEPOINT` of the outer transaction instead
+//!   of failing, so repository helpers can use a unit of work themselves and
+//!   still be composed into larger ones. A failed nested call rolls back to
+//!   its savepoint, so a caller that handles the error and carries on does
+//!   not commit the nested call's partial writes
+//!
+//! # Usage
+//!
+//! ```rust,ignore
+//! let persona = db.unit_of_work(|conn| {
+//!     let persona = PersonaRepository::create(conn, &request)?;
+//!     TokenRepository::create_from_selections(conn, &persona.id, &selections)?;
+//!     Ok(persona)
+//! })?;
+//! ```
+
+use rusqlite::{Connection, Transaction, TransactionBehavior};
+
+use crate::domain::repository::UnitOfWork;
+use crate::error::AppError;
+
+use super::Database;
+
+/// Runs `work` inside a transaction, committing only if it succeeds.
+///
+/// If `conn` is already inside a transaction, `work` runs in a savepoint of
+/// it: its writes are discarded if it fails, and otherwise kept until the
+/// outer unit of work commits or rolls back.
+///
+/// # Arguments
+///
+/// * `conn` - Database connection reference
+/// * `work` - The writes to apply atomically
+///
+/// # Errors
+///
+/// Returns the error from `work` (after rolling back), or `AppError::Database`
+/// if the transaction cannot be started or committed.
+pub fn unit_of_work<T>(
+    conn: &Connection,
+    work: impl FnOnce(&Connection) -> Result<T, AppError>,
+) -> Result<T, AppError> {
+    if !conn.is_autocommit() {
+        return nested_unit_of_work(conn, work);
+    }
+
+    // Dropping the transaction on error rolls it back
+    let tx = Transaction::new_unchecked(conn, TransactionBehavior::Immediate)?;
+    let result = work(&tx)?;
+    tx.commit()?;
+
+    Ok(result)
+}
+
+/// Runs `work` in a savepoint of the transaction `conn` is already in.
+fn nested_unit_of_work<T>(
+    conn: &Connection,
+    work: impl FnOnce(&Connection) -> Result<T, AppError>,
+) -> Result<T, AppError> {
+    // Savepoint names may repeat; each statement refers to the innermost one
+    conn.execute_batch("SAVEPOINT unit_of_work")?;
+    match work(conn) {
+        Ok(result) => {
+            conn.execute_batch("RELEASE unit_of_work")?;
+            Ok(result)
+        }
+        Err(e) => {
+            conn.execute_batch("ROLLBACK TO unit_of_work; RELEASE unit_of_work")?;
+            Err(e)
+        }
+    }
+}
+
+impl Database {
+    /// Runs `work` inside a transaction on this database's connection.
+    ///
+    /// See [`unit_of_work`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the error from `work` (after rolling back), or
+    /// `AppError::Database` if the transaction cannot be started or committed.
+    pub fn unit_of_work<T>(
+        &self,
+        work: impl FnOnce(&Connection) -> Result<T, AppError>,
+    ) -> Result<T, AppError> {
+        unit_of_work(self.connection(), work)
+    }
+}
+
+/// Lets services written against the repository traits group their writes
+/// into one `SQLite` transaction.
+impl UnitOfWork for Connection {
+    fn unit_of_work<T>(
+        &self,
+        work: impl FnOnce(&Self) -> Result<T, AppError>,
+    ) -> Result<T, AppError> {
+        unit_of_work(self, work)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn connection() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("CREATE TABLE entries (name TEXT NOT NULL)")
+            .unwrap();
+        conn
+    }
+
+    fn insert(conn: &Connection, name: &str) -> Result<(), AppError> {
+        conn.execute("INSERT INTO entries (name) VALUES (?1)", [name])?;
+        Ok(())
+    }
+
+    fn names(conn: &Connection) -> Vec<String> {
+        conn.prepare("SELECT name FROM entries ORDER BY rowid")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn failed_work_is_rolled_back() {
+        let conn = connection();
+
+        let result = unit_of_work(&conn, |conn| {
+            insert(conn, "partial")?;
+            Err::<(), _>(AppError::Validation("rejected".to_string()))
+        });
+
+        assert!(matches!(result, Err(AppError::Validation(_))));
+        assert!(names(&conn).is_empty());
+        assert!(conn.is_autocommit());
+    }
+
+    #[test]
+    fn handled_nested_failure_keeps_only_the_outer_writes() {
+        let conn = connection();
+
+        unit_of_work(&conn, |conn| {
+            insert(conn, "before")?;
+            let inner = unit_of_work(conn, |conn| {
+                insert(conn, "partial")?;
+                Err::<(), _>(AppError::Validation("rejected".to_string()))
+            });
+            assert!(inner.is_err());
+            unit_of_work(conn, |conn| insert(conn, "nested"))?;
+            insert(conn, "after")
+        })
+        .unwrap();
+
+        assert_eq!(names(&conn), ["before", "nested", "after"]);
+    }
+
+    #[test]
+    fn nested_writes_are_discarded_with_the_outer_transaction() {
+        let conn = connection();
+
+        let result = unit_of_work(&conn, |conn| {
+            unit_of_work(conn, |conn| insert(conn, "nested"))?;
+            Err::<(), _>(AppError::Validation("rejected".to_string()))
+        });
+
+        assert!(result.is_err());
+        assert!(names(&conn).is_empty());
+    }
+}
@@ -0,0 +1,85 @@
+//! Deep link handling for `ppm://` URLs
+//!
+//! Lets a persona share-code be opened straight from a browser link such as
+//! `ppm://import?code=ppm1.…`. The decoded persona is never written to the
+//! database here; it is handed to the frontend, which shows the import preview.
+//!
+//! Links that arrive while the app is running are forwarded immediately via the
+//! [`PERSONA_IMPORT_REQUESTED_EVENT`] event. A link that launched the app is kept
+//! in [`PendingPersonaImport`] until the frontend is ready to ask for it.
+
+use std::sync::Mutex;
+
+use tauri::{AppHandle, Emitter, Manager, Url};
+
+use crate::domain::export::PersonaExport;
+use crate::error::AppError;
+
+/// URL scheme registered for deep links.
+pub const DEEP_LINK_SCHEME: &str = "ppm";
+
+/// Event emitted with a decoded `PersonaExport` when an import link is opened.
+pub const PERSONA_IMPORT_REQUESTED_EVENT: &str = "persona-import-requested";
+
+/// Event emitted with an error message when a deep link cannot be handled.
+pub const DEEP_LINK_ERROR_EVENT: &str = "deep-link-error";
+
+/// Import requested by the deep link that launched the app, awaiting the frontend.
+#[derive(Default)]
+pub struct PendingPersonaImport(pub Mutex<Option<PersonaExport>>);
+
+/// Parses a `ppm://import?code=…` URL and decodes its share-code.
+///
+/// # Errors
+///
+/// Returns `AppError::Validation` if the URL is not an import link or the
+/// share-code is missing or malformed.
+pub fn parse_import_link(url: &Url) -> Result<PersonaExport, AppError> {
+    if url.scheme() != DEEP_LINK_SCHEME || url.host_str() != Some("import") {
+        return Err(AppError::Validation(format!(
+            "Unsupported deep link: {url}"
+        )));
+    }
+
+    let code = url
+        .query_pairs()
+        .find(|(key, _)| key == "code")
+        .map(|(_, value)| value.into_owned())
+        .ok_or_else(|| AppError::Validation("Import link is missing a share-code".to_string()))?;
+
+    PersonaExport::from_share_code(&code)
+}
+
+/// Handles deep link URLs received while the app is running.
+///
+/// Each valid import link is emitted to the frontend; invalid links emit
+/// an error event instead of failing silently.
+pub fn handle_urls(app: &AppHandle, urls: &[Url]) {
+    for url in urls {
+        match parse_import_link(url) {
+            Ok(export) => {
+                let _ = app.emit(PERSONA_IMPORT_REQUESTED_EVENT, export);
+            }
+            Err(e) => {
+                let _ = app.emit(DEEP_LINK_ERROR_EVENT, e.to_string());
+            }
+        }
+    }
+}
+
+/// Stores the import requested by the launch URL so the frontend can fetch it on startup.
+///
+/// Only the last valid import link is kept.
+pub fn store_launch_urls(app: &AppHandle, urls: &[Url]) {
+    let Some(export) = urls
+        .iter()
+        .rev()
+        .find_map(|url| parse_import_link(url).ok())
+    else {
+        return;
+    };
+
+    if let Ok(mut pending) = app.state::<PendingPersonaImport>().0.lock() {
+        *pending = Some(export);
+    }
+}
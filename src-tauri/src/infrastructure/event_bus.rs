@@ -0,0 +1,98 @@
+//! Backend Event Bus
+//!
+//! Lets features react to persona and token mutations without every command
+//! calling them. Commands publish a [`DomainEvent`] once their transaction
+//! commits; every subscriber is called with it, in subscription order, on the
+//! publishing thread.
+//!
+//! # Subscribers
+//!
+//! The app registers one subscriber, which forwards events to the windows
+//! (see [`forward_to_windows`]). Activity history, token revisions, and prompt cache
+//! invalidation are not subscribers: the repositories write them in the same
+//! transaction as the change, so they cannot be lost or observed half-applied.
+//!
+//! Subscribers are registered once at startup. A subscriber's error is logged
+//! and never fails the command or stops later subscribers, since the change
+//! is already committed. Subscribers must not lock the database: commands
+//! publish while still holding it.
+//!
+//! # Usage
+//!
+//! ```rust,ignore
+//! let bus = EventBus::default();
+//! let handle = app.handle().clone();
+//! bus.subscribe("windows", move |event| forward_to_windows(&handle, event));
+//! app.manage(bus);
+//! ```
+
+use std::sync::{PoisonError, RwLock};
+
+use tauri::{AppHandle, Emitter};
+
+use crate::domain::events::DomainEvent;
+use crate::error::AppError;
+
+/// Callback invoked with every published event.
+type Subscriber = Box<dyn Fn(&DomainEvent) -> Result<(), AppError> + Send + Sync>;
+
+/// In-process publish/subscribe bus for domain events, held in Tauri managed state.
+#[derive(Default)]
+pub struct EventBus {
+    /// Subscribers with the names they are logged under
+    subscribers: RwLock<Vec<(&'static str, Subscriber)>>,
+}
+
+impl EventBus {
+    /// Registers a subscriber called with every event published from now on.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Name identifying the subscriber in logs
+    /// * `subscriber` - Callback run for each event
+    pub fn subscribe(
+        &self,
+        name: &'static str,
+        subscriber: impl Fn(&DomainEvent) -> Result<(), AppError> + Send + Sync + 'static,
+    ) {
+        self.subscribers
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push((name, Box::new(subscriber)));
+    }
+
+    /// Calls every subscriber with the event.
+    ///
+    /// Subscriber errors are logged as warnings; publishing itself never fails.
+    pub fn publish(&self, event: &DomainEvent) {
+        let subscribers = self
+            .subscribers
+            .read()
+            .unwrap_or_else(PoisonError::into_inner);
+
+        for (name, subscriber) in subscribers.iter() {
+            if let Err(e) = subscriber(event) {
+                tracing::warn!(
+                    subscriber = name,
+                    event = event.name(),
+                    persona_id = event.persona_id(),
+                    error = %e,
+                    "Event subscriber failed"
+                );
+            }
+        }
+    }
+}
+
+/// Emits an event to every window under its [`DomainEvent::name`].
+///
+/// # Errors
+///
+/// Returns `AppError::Internal` if the event cannot be emitted.
+pub fn forward_to_windows(app: &AppHandle, event: &DomainEvent) -> Result<(), AppError> {
+    let result = match event {
+        DomainEvent::PersonaChanged(payload) => app.emit(event.name(), payload),
+        DomainEvent::TokensChanged(payload) => app.emit(event.name(), payload),
+    };
+    result.map_err(|e| AppError::Internal(format!("Failed to emit {}: {e}", event.name())))
+}
@@ -0,0 +1,64 @@
+//! Face Embedder
+//!
+//! Runs the local face embedding program set in the app settings on a gallery
+//! image (see `domain::face`). The app ships no face model; any program
+//! following this contract can be used, such as a small `InsightFace` script:
+//!
+//! - It is called with the absolute image path as its only argument.
+//! - It prints the embedding of the most prominent face as a JSON array of
+//!   numbers, or `null` (or `[]`) if the image shows no face.
+//! - It exits with a non-zero status on failure, with the reason on stderr.
+//!
+//! Runs are blocking; call them from `tokio::task::spawn_blocking`.
+
+use std::path::Path;
+use std::process::Command;
+
+use crate::error::AppError;
+
+/// Computes the face embedding of an image.
+///
+/// # Arguments
+///
+/// * `program` - Absolute path of the face embedding program
+/// * `image_path` - Absolute path of the image
+///
+/// # Returns
+///
+/// The embedding, or an empty vector if the image shows no face.
+///
+/// # Errors
+///
+/// Returns `AppError::Validation` if the program does not exist, and
+/// `AppError::Internal` if it fails or prints something other than an
+/// array of finite numbers or `null`.
+pub fn embed_face(program: &str, image_path: &str) -> Result<Vec<f32>, AppError> {
+    if !Path::new(program).is_file() {
+        return Err(AppError::Validation(format!(
+            "Face embedder '{program}' not found"
+        )));
+    }
+
+    let output = Command::new(program).arg(image_path).output()?;
+    if !output.status.success() {
+        return Err(AppError::Internal(format!(
+            "Face embedder failed on '{image_path}' ({}): {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    let embedding: Option<Vec<f32>> = serde_json::from_slice(&output.stdout).map_err(|e| {
+        AppError::Internal(format!(
+            "Face embedder printed an invalid embedding for '{image_path}': {e}"
+        ))
+    })?;
+    let embedding = embedding.unwrap_or_default();
+    if embedding.iter().any(|value| !value.is_finite()) {
+        return Err(AppError::Internal(format!(
+            "Face embedder printed an invalid embedding for '{image_path}'"
+        )));
+    }
+
+    Ok(embedding)
+}
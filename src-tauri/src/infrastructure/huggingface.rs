@@ -0,0 +1,172 @@
+//! `HuggingFace` Hub Search
+//!
+//! Searches model repositories on the `HuggingFace` Hub, so a custom image
+//! model can be registered under its exact repo ID instead of one typed from
+//! memory. Each result says which tokenizer the app would use for it, and
+//! whether the model was recognized or only gets the generic CLIP defaults.
+//!
+//! Requests are blocking and follow the same rules as the Civitai client:
+//! proxy from the environment, `AppError::Offline` in offline mode.
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use super::offline;
+use super::proxy::HUGGINGFACE_HOST;
+use super::tokenizer::{get_config_for_model, get_prompt_context_for_model};
+use crate::error::AppError;
+
+/// Pipeline tag searched when none is given.
+pub const DEFAULT_PIPELINE_TAG: &str = "text-to-image";
+
+/// Number of repositories returned by a search.
+const SEARCH_LIMIT: u32 = 20;
+
+/// Time limit of a whole request, including reading the response.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Family of models the tokenizer service does not recognize.
+const FALLBACK_FAMILY: &str = "stable-diffusion";
+
+/// Tag prefix naming the model a repository was derived from.
+const BASE_MODEL_TAG: &str = "base_model:";
+
+/// A model repository found on the `HuggingFace` Hub.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HubModel {
+    /// Repository ID (e.g., "stabilityai/stable-diffusion-xl-base-1.0")
+    pub repo_id: String,
+    /// Task the model performs (e.g., "text-to-image"), if declared
+    pub pipeline_tag: Option<String>,
+    /// Library the weights are for (e.g., "diffusers"), if declared
+    pub library_name: Option<String>,
+    /// Model the repository was fine-tuned from or adapts, if declared
+    pub base_model: Option<String>,
+    /// Downloads over the last 30 days
+    pub downloads: u64,
+    /// Number of likes
+    pub likes: u64,
+    /// Tokenizer the app uses to count tokens for this model
+    pub tokenizer_id: String,
+    /// Maximum prompt tokens for this model
+    pub max_tokens: usize,
+    /// Model family (e.g., "sdxl", "flux")
+    pub family: String,
+    /// Whether the model (or its base model) was recognized; if not, it gets
+    /// the generic CLIP tokenizer and Stable Diffusion conventions
+    pub recognized: bool,
+}
+
+/// A repository as returned by `/api/models`.
+#[derive(Debug, Deserialize)]
+struct ModelResponse {
+    #[serde(alias = "modelId")]
+    id: String,
+    #[serde(default)]
+    pipeline_tag: Option<String>,
+    #[serde(default)]
+    library_name: Option<String>,
+    #[serde(default)]
+    downloads: u64,
+    #[serde(default)]
+    likes: u64,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+impl From<ModelResponse> for HubModel {
+    fn from(model: ModelResponse) -> Self {
+        // Tags look like "base_model:org/name" or "base_model:finetune:org/name"
+        let base_model = model
+            .tags
+            .iter()
+            .filter_map(|tag| tag.strip_prefix(BASE_MODEL_TAG))
+            .map(|base| base.rsplit(':').next().unwrap_or(base))
+            .find(|base| base.contains('/'))
+            .map(ToString::to_string);
+
+        // Fine-tunes are often named without their family; fall back to the base
+        let mut resolved_id = model.id.as_str();
+        let mut family = get_prompt_context_for_model(Some(resolved_id)).family;
+        if family == FALLBACK_FAMILY {
+            if let Some(base) = base_model.as_deref() {
+                let base_family = get_prompt_context_for_model(Some(base)).family;
+                if base_family != FALLBACK_FAMILY {
+                    resolved_id = base;
+                    family = base_family;
+                }
+            }
+        }
+        let config = get_config_for_model(resolved_id);
+
+        Self {
+            recognized: family != FALLBACK_FAMILY,
+            repo_id: model.id,
+            pipeline_tag: model.pipeline_tag,
+            library_name: model.library_name,
+            base_model,
+            downloads: model.downloads,
+            likes: model.likes,
+            tokenizer_id: config.tokenizer_id,
+            max_tokens: config.max_tokens,
+            family,
+        }
+    }
+}
+
+/// Searches model repositories by name, most downloaded first.
+///
+/// # Arguments
+///
+/// * `query` - Repo ID or part of one
+/// * `pipeline_tag` - Task to filter on (e.g., "text-to-image")
+///
+/// # Errors
+///
+/// Returns `AppError::Validation` for an empty query or a malformed pipeline
+/// tag, `AppError::Offline` in offline mode, and `AppError::Internal` if the
+/// request fails.
+pub fn search_models(query: &str, pipeline_tag: &str) -> Result<Vec<HubModel>, AppError> {
+    let query = query.trim();
+    if query.is_empty() {
+        return Err(AppError::Validation(
+            "Search query cannot be empty".to_string(),
+        ));
+    }
+    let pipeline_tag = pipeline_tag.trim();
+    if pipeline_tag.is_empty()
+        || !pipeline_tag
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c == '-')
+    {
+        return Err(AppError::Validation(format!(
+            "Invalid pipeline tag '{pipeline_tag}'"
+        )));
+    }
+    offline::ensure_online("Searching models on HuggingFace")?;
+
+    let models: Vec<ModelResponse> = ureq::AgentBuilder::new()
+        .timeout(REQUEST_TIMEOUT)
+        .try_proxy_from_env(true)
+        .build()
+        .get(&format!("https://{HUGGINGFACE_HOST}/api/models"))
+        .query("search", query)
+        .query("pipeline_tag", pipeline_tag)
+        .query("sort", "downloads")
+        .query("direction", "-1")
+        .query("limit", &SEARCH_LIMIT.to_string())
+        .call()
+        .map_err(|e| match e {
+            ureq::Error::Status(status, _) => {
+                AppError::Internal(format!("HuggingFace search failed with status {status}"))
+            }
+            ureq::Error::Transport(transport) => {
+                AppError::Internal(format!("Failed to reach HuggingFace: {transport}"))
+            }
+        })?
+        .into_json()
+        .map_err(|e| AppError::Internal(format!("Invalid HuggingFace response: {e}")))?;
+
+    Ok(models.into_iter().map(HubModel::from).collect())
+}
@@ -0,0 +1,39 @@
+//! Environment-variable credential passthrough
+//!
+//! Reads API keys from the process environment (e.g., `OPENAI_API_KEY`) at call
+//! time. Used on machines where credentials are injected into the environment
+//! and must not be copied into the OS keyring.
+
+use crate::domain::ai::AiProvider;
+use crate::error::AppError;
+
+/// Read a provider's API key from its environment variable, ignoring blank values
+fn read_env_api_key(var: &str) -> Option<String> {
+    std::env::var(var)
+        .ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+/// Retrieve an API key from the environment for a provider
+///
+/// Returns `None` for providers that don't use an API key (Ollama).
+pub fn get_env_api_key(provider: &AiProvider) -> Result<Option<String>, AppError> {
+    let Some(var) = provider.api_key_env_var() else {
+        return Ok(None);
+    };
+
+    read_env_api_key(var).map(Some).ok_or_else(|| {
+        AppError::Validation(format!(
+            "Environment variable {var} is not set for {}",
+            provider.display_name()
+        ))
+    })
+}
+
+/// Check if the environment provides an API key for a provider
+pub fn has_env_api_key(provider: &AiProvider) -> bool {
+    provider
+        .api_key_env_var()
+        .is_some_and(|var| read_env_api_key(var).is_some())
+}
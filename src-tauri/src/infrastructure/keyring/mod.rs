@@ -1,7 +1,7 @@
 //! Keyring Module - Secure Credential Storage
 //!
-//! This module provides secure storage for API keys using the operating system's
-//! native credential management facilities:
+//! This module provides secure storage for API keys and the database passphrase
+//! using the operating system's native credential management facilities:
 //!
 //! | Platform | Backend                   |
 //! |----------|---------------------------|
@@ -22,6 +22,13 @@
 //!
 //! On Linux, a Secret Service daemon must be running (e.g., gnome-keyring or kwallet).
 //! The application checks for availability at startup via `check_credential_store_available()`.
+//!
+//! # Environment Passthrough
+//!
+//! Where credentials are injected via environment variables, [`env`] reads them
+//! at call time instead of requiring keyring storage.
 
+pub mod env;
 pub mod secrets;
+pub use env::*;
 pub use secrets::*;
@@ -3,6 +3,7 @@
 //! Provides secure storage and retrieval of API keys using the
 //! operating system's native credential store.
 
+use chrono::{DateTime, Utc};
 use keyring::Entry;
 
 use crate::domain::ai::AiProvider;
@@ -11,11 +12,19 @@ use crate::error::AppError;
 /// Service name for keyring entries
 const SERVICE_NAME: &str = "persona-prompt-manager";
 
+/// Keyring entry name holding the database passphrase
+const DATABASE_PASSPHRASE_ENTRY: &str = "database-passphrase";
+
 /// Build the keyring entry name for an AI provider
 fn build_keyring_entry_name(provider: &AiProvider) -> String {
     format!("api-key-{}", provider_to_string_id(provider))
 }
 
+/// Build the keyring entry name holding when a provider's API key was stored
+fn build_stored_at_entry_name(provider: &AiProvider) -> String {
+    format!("api-key-stored-at-{}", provider_to_string_id(provider))
+}
+
 /// Convert provider enum to string ID
 const fn provider_to_string_id(provider: &AiProvider) -> &'static str {
     match provider {
@@ -37,9 +46,44 @@ pub fn store_api_key(provider: &AiProvider, api_key: &str) -> Result<(), AppErro
         .set_password(api_key)
         .map_err(|e| AppError::Internal(format!("Failed to store API key in keyring: {e}")))?;
 
+    record_api_key_stored_at(provider, Utc::now())
+}
+
+/// Record when a provider's API key was stored (the timestamp only, never the key)
+fn record_api_key_stored_at(
+    provider: &AiProvider,
+    stored_at: DateTime<Utc>,
+) -> Result<(), AppError> {
+    let entry_name = build_stored_at_entry_name(provider);
+    let entry = Entry::new(SERVICE_NAME, &entry_name)
+        .map_err(|e| AppError::Internal(format!("Failed to create keyring entry: {e}")))?;
+
+    entry
+        .set_password(&stored_at.to_rfc3339())
+        .map_err(|e| AppError::Internal(format!("Failed to store API key timestamp: {e}")))?;
+
     Ok(())
 }
 
+/// Retrieve when a provider's API key was last stored, if known
+///
+/// Keys stored before timestamps were recorded report `None`.
+pub fn get_api_key_stored_at(provider: &AiProvider) -> Result<Option<DateTime<Utc>>, AppError> {
+    let entry_name = build_stored_at_entry_name(provider);
+    let entry = Entry::new(SERVICE_NAME, &entry_name)
+        .map_err(|e| AppError::Internal(format!("Failed to create keyring entry: {e}")))?;
+
+    match entry.get_password() {
+        Ok(value) => Ok(DateTime::parse_from_rfc3339(&value)
+            .ok()
+            .map(|dt| dt.with_timezone(&Utc))),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(AppError::Internal(format!(
+            "Failed to retrieve API key timestamp: {e}"
+        ))),
+    }
+}
+
 /// Retrieve an API key from the OS keyring
 pub fn get_api_key(provider: &AiProvider) -> Result<Option<String>, AppError> {
     let entry_name = build_keyring_entry_name(provider);
@@ -62,10 +106,60 @@ pub fn delete_api_key(provider: &AiProvider) -> Result<(), AppError> {
         .map_err(|e| AppError::Internal(format!("Failed to create keyring entry: {e}")))?;
 
     match entry.delete_credential() {
-        Ok(()) => Ok(()),
-        Err(keyring::Error::NoEntry) => Ok(()), // Already deleted, that's fine
+        Ok(()) | Err(keyring::Error::NoEntry) => {} // Already deleted, that's fine
+        Err(e) => {
+            return Err(AppError::Internal(format!(
+                "Failed to delete API key from keyring: {e}"
+            )))
+        }
+    }
+
+    let stored_at_entry = Entry::new(SERVICE_NAME, &build_stored_at_entry_name(provider))
+        .map_err(|e| AppError::Internal(format!("Failed to create keyring entry: {e}")))?;
+
+    match stored_at_entry.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(AppError::Internal(format!(
+            "Failed to delete API key timestamp from keyring: {e}"
+        ))),
+    }
+}
+
+/// Store the database passphrase in the OS keyring
+pub fn store_database_passphrase(passphrase: &str) -> Result<(), AppError> {
+    let entry = Entry::new(SERVICE_NAME, DATABASE_PASSPHRASE_ENTRY)
+        .map_err(|e| AppError::Internal(format!("Failed to create keyring entry: {e}")))?;
+
+    entry.set_password(passphrase).map_err(|e| {
+        AppError::Internal(format!(
+            "Failed to store database passphrase in keyring: {e}"
+        ))
+    })
+}
+
+/// Retrieve the database passphrase from the OS keyring, if one is stored
+pub fn get_database_passphrase() -> Result<Option<String>, AppError> {
+    let entry = Entry::new(SERVICE_NAME, DATABASE_PASSPHRASE_ENTRY)
+        .map_err(|e| AppError::Internal(format!("Failed to create keyring entry: {e}")))?;
+
+    match entry.get_password() {
+        Ok(passphrase) => Ok(Some(passphrase)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(AppError::Internal(format!(
+            "Failed to retrieve database passphrase from keyring: {e}"
+        ))),
+    }
+}
+
+/// Delete the database passphrase from the OS keyring
+pub fn delete_database_passphrase() -> Result<(), AppError> {
+    let entry = Entry::new(SERVICE_NAME, DATABASE_PASSPHRASE_ENTRY)
+        .map_err(|e| AppError::Internal(format!("Failed to create keyring entry: {e}")))?;
+
+    match entry.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
         Err(e) => Err(AppError::Internal(format!(
-            "Failed to delete API key from keyring: {e}"
+            "Failed to delete database passphrase from keyring: {e}"
         ))),
     }
 }
@@ -0,0 +1,24 @@
+//! Display locale
+//!
+//! A process-wide setting choosing the language of the display text the
+//! backend produces, such as error messages (see `domain::i18n`).
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::domain::i18n::Locale;
+
+/// Index of the current locale in [`Locale::ALL`].
+static LOCALE: AtomicUsize = AtomicUsize::new(0);
+
+/// Sets the display locale.
+///
+/// Applied at startup from the stored settings, and again whenever they change.
+pub fn set_locale(locale: Locale) {
+    LOCALE.store(locale.index(), Ordering::Relaxed);
+}
+
+/// Returns the display locale.
+#[must_use]
+pub fn current() -> Locale {
+    Locale::from_index(LOCALE.load(Ordering::Relaxed))
+}
@@ -0,0 +1,149 @@
+//! Structured application logging
+//!
+//! Installs a `tracing` subscriber that writes to daily-rotated files in
+//! `logs/` under the app data directory, keeping the last [`MAX_LOG_FILES`]
+//! days. Commands, repositories, and AI calls are instrumented with spans, so
+//! each log line carries the command (and persona, where relevant) it belongs
+//! to. Debug builds also log to stderr.
+//!
+//! # Log Level
+//!
+//! The level can be changed at runtime through [`AppLogging::set_level`]; the
+//! chosen level is stored in `AppSettings` and re-applied at startup.
+//!
+//! # Bug Reports
+//!
+//! [`AppLogging::write_archive`] zips every log file so users can attach them
+//! to a bug report in one go. Log lines never contain API keys: commands
+//! handling credentials skip their arguments.
+
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{fmt, reload, Registry};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use crate::domain::settings::LogLevel;
+use crate::error::AppError;
+
+/// Directory (under app data) holding the log files.
+const LOG_DIR_NAME: &str = "logs";
+
+/// Prefix of the log file names; the date and `.log` are appended.
+const LOG_FILE_PREFIX: &str = "ppm";
+
+/// Number of daily log files kept.
+pub const MAX_LOG_FILES: usize = 7;
+
+/// Handle to the installed logging subscriber.
+///
+/// Managed as Tauri state; dropping it stops the background writer, so it
+/// must live as long as the application.
+pub struct AppLogging {
+    dir: PathBuf,
+    level: reload::Handle<LevelFilter, Registry>,
+    _guard: WorkerGuard,
+}
+
+impl AppLogging {
+    /// Installs the global subscriber, logging at `info` until a level is set.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Io` if the log directory cannot be created, or
+    /// `AppError::Internal` if a subscriber is already installed.
+    pub fn init(app_data_dir: &Path) -> Result<Self, AppError> {
+        let dir = app_data_dir.join(LOG_DIR_NAME);
+        fs::create_dir_all(&dir)?;
+
+        let appender = RollingFileAppender::builder()
+            .rotation(Rotation::DAILY)
+            .filename_prefix(LOG_FILE_PREFIX)
+            .filename_suffix("log")
+            .max_log_files(MAX_LOG_FILES)
+            .build(&dir)
+            .map_err(|e| AppError::Internal(format!("Failed to open log file: {e}")))?;
+        let (writer, guard) = tracing_appender::non_blocking(appender);
+
+        let (filter, level) = reload::Layer::new(level_filter(LogLevel::default()));
+
+        tracing_subscriber::registry()
+            .with(filter)
+            .with(fmt::layer().with_writer(writer).with_ansi(false))
+            .with(cfg!(debug_assertions).then(|| fmt::layer().with_writer(io::stderr)))
+            .try_init()
+            .map_err(|e| AppError::Internal(format!("Failed to install logger: {e}")))?;
+
+        Ok(Self {
+            dir,
+            level,
+            _guard: guard,
+        })
+    }
+
+    /// Changes the level of recorded events.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Internal` if the subscriber is gone.
+    pub fn set_level(&self, level: LogLevel) -> Result<(), AppError> {
+        self.level
+            .reload(level_filter(level))
+            .map_err(|e| AppError::Internal(format!("Failed to change log level: {e}")))
+    }
+
+    /// Writes every log file into a zip archive at `dest`.
+    ///
+    /// # Returns
+    ///
+    /// The number of log files archived.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Io` if a log file cannot be read or the archive
+    /// cannot be created, or `AppError::Internal` if zipping fails.
+    pub fn write_archive(&self, dest: &Path) -> Result<usize, AppError> {
+        let mut files: Vec<PathBuf> = fs::read_dir(&self.dir)?
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file())
+            .collect();
+        files.sort();
+
+        let zip_error = |e: zip::result::ZipError| {
+            AppError::Internal(format!("Failed to write log archive: {e}"))
+        };
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+        let mut zip = ZipWriter::new(File::create(dest)?);
+
+        for path in &files {
+            let name = path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default();
+            zip.start_file(name, options).map_err(zip_error)?;
+            io::copy(&mut File::open(path)?, &mut zip)?;
+        }
+
+        zip.finish().map_err(zip_error)?;
+        Ok(files.len())
+    }
+}
+
+/// Maps the stored level onto the subscriber's filter.
+const fn level_filter(level: LogLevel) -> LevelFilter {
+    match level {
+        LogLevel::Error => LevelFilter::ERROR,
+        LogLevel::Warn => LevelFilter::WARN,
+        LogLevel::Info => LevelFilter::INFO,
+        LogLevel::Debug => LevelFilter::DEBUG,
+        LogLevel::Trace => LevelFilter::TRACE,
+    }
+}
@@ -0,0 +1,684 @@
+//! In-Memory Store
+//!
+//! A fake implementation of the repository traits (see `domain::repository`)
+//! that keeps everything in memory, for exercising services without a
+//! database.
+//!
+//! # Differences from `SQLite`
+//!
+//! The store applies the same naming rules, version checks, and token
+//! revisions as the `SQLite` repositories, but none of their other side
+//! effects: no prompt caches or activity are kept, content hashes are
+//! computed when searched for, and variants are not resolved against their
+//! base persona. It starts with the built-in granularity levels and no banned
+//! terms or aliases. A failed unit of work restores the state from before it started.
+//!
+//! # Usage
+//!
+//! ```rust,ignore
+//! let store = InMemoryStore::default();
+//! let persona = PersonaService::create(&store, request)?;
+//! ```
+
+use std::cell::RefCell;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+use chrono::Utc;
+
+use crate::domain::alias::{self, TokenAlias};
+use crate::domain::banned_term::{normalize_term, BannedTerm};
+use crate::domain::naming::{name_key, normalize_name, validate_name};
+use crate::domain::persona::{
+    compute_content_hash, validate_color, CreatePersonaRequest, GenerationParams, Persona,
+    UpdatePersonaRequest,
+};
+use crate::domain::prompt::CompositionDefaults;
+use crate::domain::repository::{
+    AliasRepo, BannedTermRepo, GranularityRepo, PersonaRepo, TokenRepo, UnitOfWork,
+};
+use crate::domain::token::{
+    CreateTokenRequest, GeneratedTokenSelection, Granularity, GranularityLevel, Token,
+    TokenPolarity, TokenRevision, UpdateTokenRequest,
+};
+use crate::error::AppError;
+
+/// Everything the store holds, cloned to roll back a failed unit of work.
+#[derive(Debug, Clone, Default)]
+struct StoreState {
+    personas: Vec<Persona>,
+    generation_params: HashMap<String, GenerationParams>,
+    composition_defaults: HashMap<String, CompositionDefaults>,
+    tokens: Vec<Token>,
+    revisions: Vec<TokenRevision>,
+    custom_levels: Vec<GranularityLevel>,
+    banned_terms: Vec<BannedTerm>,
+    aliases: Vec<TokenAlias>,
+}
+
+/// Persona and token storage held in memory.
+#[derive(Debug, Default)]
+pub struct InMemoryStore {
+    state: RefCell<StoreState>,
+}
+
+impl InMemoryStore {
+    /// Returns the next display order after the persona's existing tokens.
+    fn next_display_order(state: &StoreState, persona_id: &str) -> i32 {
+        state
+            .tokens
+            .iter()
+            .filter(|t| t.persona_id == persona_id)
+            .map(|t| t.display_order + 1)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Removes the tokens matching `remove` with their revisions, returning
+    /// how many were removed.
+    fn remove_tokens(state: &mut StoreState, remove: impl Fn(&Token) -> bool) -> usize {
+        let removed: Vec<String> = state
+            .tokens
+            .iter()
+            .filter(|t| remove(t))
+            .map(|t| t.id.clone())
+            .collect();
+        state.tokens.retain(|t| !remove(t));
+        state.revisions.retain(|r| !removed.contains(&r.token_id));
+        removed.len()
+    }
+
+    /// Renumbers a persona's tokens from zero, keeping their order.
+    fn renumber(state: &mut StoreState, persona_id: &str) -> bool {
+        let mut tokens: Vec<&mut Token> = state
+            .tokens
+            .iter_mut()
+            .filter(|t| t.persona_id == persona_id)
+            .collect();
+        tokens.sort_by(|a, b| {
+            (a.display_order, a.created_at, &a.id).cmp(&(b.display_order, b.created_at, &b.id))
+        });
+
+        let mut changed = false;
+        for (position, token) in tokens.into_iter().enumerate() {
+            let position = position as i32;
+            if token.display_order != position {
+                token.display_order = position;
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    /// Fails with `AppError::NotFound` unless the persona exists.
+    fn ensure_persona(state: &StoreState, id: &str) -> Result<(), AppError> {
+        if state.personas.iter().any(|p| p.id == id) {
+            Ok(())
+        } else {
+            Err(AppError::NotFound(format!(
+                "Persona with id '{id}' not found"
+            )))
+        }
+    }
+}
+
+impl UnitOfWork for InMemoryStore {
+    fn unit_of_work<T>(
+        &self,
+        work: impl FnOnce(&Self) -> Result<T, AppError>,
+    ) -> Result<T, AppError> {
+        let snapshot = self.state.borrow().clone();
+        let result = work(self);
+        if result.is_err() {
+            *self.state.borrow_mut() = snapshot;
+        }
+        result
+    }
+}
+
+impl PersonaRepo for InMemoryStore {
+    fn find_by_id(&self, id: &str) -> Result<Persona, AppError> {
+        self.state
+            .borrow()
+            .personas
+            .iter()
+            .find(|p| p.id == id)
+            .cloned()
+            .ok_or_else(|| AppError::NotFound(format!("Persona with id '{id}' not found")))
+    }
+
+    fn find_all(&self) -> Result<Vec<Persona>, AppError> {
+        let mut personas = self.state.borrow().personas.clone();
+        personas.sort_by_key(|p| Reverse(p.created_at));
+        Ok(personas)
+    }
+
+    fn create(&self, request: &CreatePersonaRequest) -> Result<Persona, AppError> {
+        let name = normalize_name(&request.name);
+        if name.is_empty() {
+            return Err(AppError::Validation("Persona name is required".to_string()));
+        }
+        if self.name_exists(&name, None)? {
+            return Err(AppError::Validation(format!(
+                "A persona with name '{name}' already exists"
+            )));
+        }
+
+        let mut persona = Persona::new(name, request.description.clone(), request.tags.clone());
+        persona.content_rating = request.content_rating;
+
+        let mut state = self.state.borrow_mut();
+        state.generation_params.insert(
+            persona.id.clone(),
+            GenerationParams::default_for_persona(&persona.id),
+        );
+        state.personas.push(persona.clone());
+
+        Ok(persona)
+    }
+
+    fn update(&self, id: &str, request: &UpdatePersonaRequest) -> Result<Persona, AppError> {
+        let mut persona = PersonaRepo::find_by_id(self, id)?;
+        AppError::check_version(
+            request.expected_updated_at,
+            persona.updated_at,
+            &format!("Persona '{}'", persona.name),
+            &persona,
+        )?;
+
+        let name = match &request.name {
+            Some(name) if name_key(name) != name_key(&persona.name) => {
+                let name = validate_name(name)?;
+                if self.name_exists(&name, Some(id))? {
+                    return Err(AppError::Validation(format!(
+                        "A persona with name '{name}' already exists"
+                    )));
+                }
+                name
+            }
+            Some(name) => normalize_name(name),
+            None => persona.name.clone(),
+        };
+        let color = request
+            .color
+            .as_ref()
+            .map(|color| color.as_deref().map(validate_color).transpose())
+            .transpose()?;
+
+        persona.update(request);
+        persona.name = name;
+        if let Some(color) = color {
+            persona.color = color;
+        }
+
+        let mut state = self.state.borrow_mut();
+        if let Some(stored) = state.personas.iter_mut().find(|p| p.id == id) {
+            stored.clone_from(&persona);
+        }
+
+        Ok(persona)
+    }
+
+    fn delete(&self, id: &str) -> Result<(), AppError> {
+        let mut state = self.state.borrow_mut();
+        Self::ensure_persona(&state, id)?;
+
+        state.personas.retain(|p| p.id != id);
+        state.generation_params.remove(id);
+        state.composition_defaults.remove(id);
+        Self::remove_tokens(&mut state, |t| t.persona_id == id);
+        for variant in &mut state.personas {
+            if variant.base_persona_id.as_deref() == Some(id) {
+                variant.base_persona_id = None;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn find_by_content_hash(&self, hash: &str) -> Result<Option<Persona>, AppError> {
+        let mut personas = PersonaRepo::find_all(self)?;
+        personas.sort_by_key(|p| p.created_at);
+        for persona in personas {
+            let tokens = TokenRepo::find_by_persona(self, &persona.id)?;
+            if compute_content_hash(persona.description.as_deref(), &tokens) == hash {
+                return Ok(Some(persona));
+            }
+        }
+        Ok(None)
+    }
+
+    fn names(&self, exclude_id: Option<&str>) -> Result<Vec<String>, AppError> {
+        Ok(self
+            .state
+            .borrow()
+            .personas
+            .iter()
+            .filter(|p| Some(p.id.as_str()) != exclude_id)
+            .map(|p| p.name.clone())
+            .collect())
+    }
+
+    fn find_resolved_generation_params(
+        &self,
+        persona_id: &str,
+    ) -> Result<GenerationParams, AppError> {
+        self.state
+            .borrow()
+            .generation_params
+            .get(persona_id)
+            .cloned()
+            .ok_or_else(|| {
+                AppError::NotFound(format!(
+                    "Generation params for persona '{persona_id}' not found"
+                ))
+            })
+    }
+
+    fn update_generation_params(&self, params: &GenerationParams) -> Result<(), AppError> {
+        let mut state = self.state.borrow_mut();
+        Self::ensure_persona(&state, &params.persona_id)?;
+        state
+            .generation_params
+            .insert(params.persona_id.clone(), params.clone());
+        Ok(())
+    }
+
+    fn find_composition_defaults(
+        &self,
+        persona_id: &str,
+    ) -> Result<Option<CompositionDefaults>, AppError> {
+        Ok(self
+            .state
+            .borrow()
+            .composition_defaults
+            .get(persona_id)
+            .cloned())
+    }
+
+    fn set_composition_defaults(
+        &self,
+        persona_id: &str,
+        defaults: Option<&CompositionDefaults>,
+    ) -> Result<(), AppError> {
+        let mut state = self.state.borrow_mut();
+        Self::ensure_persona(&state, persona_id)?;
+        match defaults {
+            Some(defaults) => {
+                state
+                    .composition_defaults
+                    .insert(persona_id.to_string(), defaults.clone());
+            }
+            None => {
+                state.composition_defaults.remove(persona_id);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl TokenRepo for InMemoryStore {
+    fn find_by_id(&self, id: &str) -> Result<Token, AppError> {
+        self.state
+            .borrow()
+            .tokens
+            .iter()
+            .find(|t| t.id == id)
+            .cloned()
+            .ok_or_else(|| AppError::NotFound(format!("Token with id '{id}' not found")))
+    }
+
+    fn find_by_persona(&self, persona_id: &str) -> Result<Vec<Token>, AppError> {
+        let mut tokens: Vec<Token> = self
+            .state
+            .borrow()
+            .tokens
+            .iter()
+            .filter(|t| t.persona_id == persona_id)
+            .cloned()
+            .collect();
+        tokens.sort_by_key(|t| t.display_order);
+        Ok(tokens)
+    }
+
+    fn create(&self, request: &CreateTokenRequest) -> Result<Token, AppError> {
+        let mut state = self.state.borrow_mut();
+        Self::ensure_persona(&state, &request.persona_id)?;
+
+        let token = Token::new(
+            request.persona_id.clone(),
+            request.granularity_id.clone(),
+            request.polarity,
+            request.content.clone(),
+            request.weight,
+            Self::next_display_order(&state, &request.persona_id),
+        );
+        state.tokens.push(token.clone());
+
+        Ok(token)
+    }
+
+    fn create_from_selections(
+        &self,
+        persona_id: &str,
+        selections: &[GeneratedTokenSelection],
+    ) -> Result<Vec<Token>, AppError> {
+        let mut state = self.state.borrow_mut();
+        Self::ensure_persona(&state, persona_id)?;
+
+        let mut display_order = Self::next_display_order(&state, persona_id);
+        let mut tokens = Vec::new();
+        for selection in selections {
+            let content = selection.content.trim();
+            if content.is_empty() {
+                continue;
+            }
+            if Granularity::parse(&selection.granularity_id).is_none() {
+                return Err(AppError::Validation(format!(
+                    "Unknown granularity '{}'",
+                    selection.granularity_id
+                )));
+            }
+
+            tokens.push(Token::new(
+                persona_id.to_string(),
+                selection.granularity_id.clone(),
+                selection.polarity,
+                content.to_string(),
+                selection.weight,
+                display_order,
+            ));
+            display_order += 1;
+        }
+        state.tokens.extend(tokens.iter().cloned());
+
+        Ok(tokens)
+    }
+
+    fn update(&self, id: &str, request: &UpdateTokenRequest) -> Result<Token, AppError> {
+        let mut state = self.state.borrow_mut();
+        let token = state
+            .tokens
+            .iter_mut()
+            .find(|t| t.id == id)
+            .ok_or_else(|| AppError::NotFound(format!("Token with id '{id}' not found")))?;
+        AppError::check_version(
+            request.expected_updated_at,
+            token.updated_at,
+            &format!("Token '{}'", token.content),
+            &*token,
+        )?;
+
+        let previous = token.clone();
+        token.update(request);
+        let token = token.clone();
+
+        if token.content != previous.content
+            || (token.weight - previous.weight).abs() > f64::EPSILON
+        {
+            let revision = state
+                .revisions
+                .iter()
+                .filter(|r| r.token_id == id)
+                .map(|r| r.revision + 1)
+                .max()
+                .unwrap_or(1);
+            state.revisions.push(TokenRevision {
+                token_id: previous.id,
+                revision,
+                content: previous.content,
+                weight: previous.weight,
+                recorded_at: Utc::now(),
+            });
+        }
+
+        Ok(token)
+    }
+
+    fn delete(&self, id: &str) -> Result<(), AppError> {
+        let mut state = self.state.borrow_mut();
+        let Some(persona_id) = state
+            .tokens
+            .iter()
+            .find(|t| t.id == id)
+            .map(|t| t.persona_id.clone())
+        else {
+            return Err(AppError::NotFound(format!(
+                "Token with id '{id}' not found"
+            )));
+        };
+        Self::remove_tokens(&mut state, |t| t.id == id);
+        Self::renumber(&mut state, &persona_id);
+        Ok(())
+    }
+
+    fn create_batch(
+        &self,
+        persona_id: &str,
+        granularity_id: &str,
+        polarity: TokenPolarity,
+        contents: &[String],
+        weight: f64,
+    ) -> Result<Vec<Token>, AppError> {
+        let mut state = self.state.borrow_mut();
+        Self::ensure_persona(&state, persona_id)?;
+
+        let mut display_order = Self::next_display_order(&state, persona_id);
+        let mut tokens = Vec::new();
+        for content in contents {
+            if content.trim().is_empty() {
+                continue;
+            }
+            tokens.push(Token::new(
+                persona_id.to_string(),
+                granularity_id.to_string(),
+                polarity,
+                content.trim().to_string(),
+                weight,
+                display_order,
+            ));
+            display_order += 1;
+        }
+        state.tokens.extend(tokens.iter().cloned());
+
+        Ok(tokens)
+    }
+
+    fn create_all(&self, requests: &[CreateTokenRequest]) -> Result<Vec<Token>, AppError> {
+        requests
+            .iter()
+            .map(|request| TokenRepo::create(self, request))
+            .collect()
+    }
+
+    fn delete_by_persona(&self, persona_id: &str) -> Result<usize, AppError> {
+        Ok(Self::remove_tokens(&mut self.state.borrow_mut(), |t| {
+            t.persona_id == persona_id
+        }))
+    }
+
+    fn delete_by_granularity(
+        &self,
+        persona_id: &str,
+        granularity_id: &str,
+    ) -> Result<usize, AppError> {
+        let mut state = self.state.borrow_mut();
+        let deleted = Self::remove_tokens(&mut state, |t| {
+            t.persona_id == persona_id && t.granularity_id == granularity_id
+        });
+        if deleted > 0 {
+            Self::renumber(&mut state, persona_id);
+        }
+        Ok(deleted)
+    }
+
+    fn normalize_token_order(&self, persona_id: &str) -> Result<bool, AppError> {
+        Ok(Self::renumber(&mut self.state.borrow_mut(), persona_id))
+    }
+
+    fn find_revision(&self, token_id: &str, revision: i32) -> Result<TokenRevision, AppError> {
+        self.state
+            .borrow()
+            .revisions
+            .iter()
+            .find(|r| r.token_id == token_id && r.revision == revision)
+            .cloned()
+            .ok_or_else(|| {
+                AppError::NotFound(format!(
+                    "Revision {revision} of token '{token_id}' not found"
+                ))
+            })
+    }
+}
+
+impl GranularityRepo for InMemoryStore {
+    fn find_all(&self) -> Result<Vec<GranularityLevel>, AppError> {
+        let mut levels = GranularityLevel::all();
+        levels.extend(self.state.borrow().custom_levels.iter().cloned());
+        Ok(levels)
+    }
+
+    fn create(&self, id: &str, name: &str) -> Result<GranularityLevel, AppError> {
+        let id = id.trim();
+        let name = name.trim();
+        if id.is_empty() || name.is_empty() {
+            return Err(AppError::Validation(
+                "Granularity level ID and name are required".to_string(),
+            ));
+        }
+
+        let levels = GranularityRepo::find_all(self)?;
+        if levels.iter().any(|level| level.id == id) {
+            return Err(AppError::Validation(format!(
+                "Granularity level '{id}' already exists"
+            )));
+        }
+
+        let level = GranularityLevel {
+            id: id.to_string(),
+            name: name.to_string(),
+            color: GranularityLevel::FALLBACK_COLOR.to_string(),
+            display_order: levels
+                .iter()
+                .map(|l| l.display_order + 1)
+                .max()
+                .unwrap_or(0),
+            is_default: false,
+            created_at: Utc::now(),
+        };
+        self.state.borrow_mut().custom_levels.push(level.clone());
+
+        Ok(level)
+    }
+}
+
+impl BannedTermRepo for InMemoryStore {
+    fn find_all(&self) -> Result<Vec<BannedTerm>, AppError> {
+        Ok(self.state.borrow().banned_terms.clone())
+    }
+
+    fn create(&self, term: &str) -> Result<BannedTerm, AppError> {
+        let term = normalize_term(term)?;
+        let mut state = self.state.borrow_mut();
+        if state
+            .banned_terms
+            .iter()
+            .any(|banned| banned.term.to_lowercase() == term.to_lowercase())
+        {
+            return Err(AppError::Validation(format!(
+                "The term '{term}' is already banned"
+            )));
+        }
+
+        let banned = BannedTerm::new(term);
+        state.banned_terms.push(banned.clone());
+        Ok(banned)
+    }
+}
+
+impl AliasRepo for InMemoryStore {
+    fn find_all(&self) -> Result<Vec<TokenAlias>, AppError> {
+        Ok(self.state.borrow().aliases.clone())
+    }
+
+    fn create(&self, alias: &str, canonical: &str) -> Result<TokenAlias, AppError> {
+        let alias = alias::normalize_phrase(alias)?;
+        let canonical = alias::normalize_phrase(canonical)?;
+        let mut state = self.state.borrow_mut();
+        alias::validate_new(&alias, &canonical, &state.aliases)?;
+
+        let created = TokenAlias::new(alias, canonical);
+        state.aliases.push(created.clone());
+        Ok(created)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_support::{content_update, persona_request, token_request};
+
+    #[test]
+    fn failed_unit_of_work_discards_writes() {
+        let store = InMemoryStore::default();
+
+        let result: Result<(), AppError> = store.unit_of_work(|store| {
+            PersonaRepo::create(store, &persona_request("Aria"))?;
+            Err(AppError::Validation("stop".to_string()))
+        });
+
+        assert!(result.is_err());
+        assert!(PersonaRepo::find_all(&store).unwrap().is_empty());
+    }
+
+    #[test]
+    fn update_records_the_previous_content_as_a_revision() {
+        let store = InMemoryStore::default();
+        let persona = PersonaRepo::create(&store, &persona_request("Aria")).unwrap();
+        let token = TokenRepo::create(&store, &token_request(&persona.id, "red hair")).unwrap();
+
+        TokenRepo::update(&store, &token.id, &content_update("blue hair")).unwrap();
+
+        assert_eq!(
+            store.find_revision(&token.id, 1).unwrap().content,
+            "red hair"
+        );
+        assert!(matches!(
+            store.find_revision(&token.id, 2),
+            Err(AppError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn granularity_levels_list_built_in_levels_before_custom_ones() {
+        let store = InMemoryStore::default();
+
+        GranularityRepo::create(&store, "accessories", "Accessories").unwrap();
+        let levels = GranularityRepo::find_all(&store).unwrap();
+
+        assert_eq!(levels.len(), GranularityLevel::all().len() + 1);
+        assert_eq!(levels.last().unwrap().id, "accessories");
+        assert!(!levels.last().unwrap().is_default);
+    }
+
+    #[test]
+    fn deleting_a_token_renumbers_the_rest() {
+        let store = InMemoryStore::default();
+        let persona = PersonaRepo::create(&store, &persona_request("Aria")).unwrap();
+        let first = TokenRepo::create(&store, &token_request(&persona.id, "red hair")).unwrap();
+        TokenRepo::create(&store, &token_request(&persona.id, "long hair")).unwrap();
+        TokenRepo::create(&store, &token_request(&persona.id, "braid")).unwrap();
+
+        TokenRepo::delete(&store, &first.id).unwrap();
+        let order: Vec<_> = TokenRepo::find_by_persona(&store, &persona.id)
+            .unwrap()
+            .into_iter()
+            .map(|t| (t.content, t.display_order))
+            .collect();
+
+        assert_eq!(
+            order,
+            [("long hair".to_string(), 0), ("braid".to_string(), 1)]
+        );
+    }
+}
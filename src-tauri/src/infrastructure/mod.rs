@@ -7,6 +7,24 @@
 //! - **AI Providers**: LLM integrations for token generation (`OpenAI`, Anthropic, etc.)
 //! - **Tokenizer**: `HuggingFace` tokenizers for accurate prompt length calculation
 //! - **Keyring**: Platform-native secure credential storage
+//! - **Deep Links**: `ppm://` URL handling for shared personas
+//! - **Civitai**: Model metadata lookup for checkpoints and `LoRA` models
+//! - **`HuggingFace` Hub**: Searching model repositories for custom models
+//! - **Face Embedder**: Optional local program computing face embeddings of gallery images
+//! - **Resource Scan**: Finding embeddings and `LoRA` models in local folders
+//! - **PDF**: Rendering persona sheets as PDF documents
+//! - **Proxy**: Network proxy configuration and connectivity diagnostics
+//! - **Locale**: Global language setting for backend display text
+//! - **Offline Mode**: Global switch that blocks network access
+//! - **Safe Mode**: Global switch that hides mature-rated personas
+//! - **Share Server**: Temporary read-only HTTP view of a smart collection
+//! - **Scheduler**: Background timer running scheduled tasks while the app is open
+//! - **Tag Dictionaries**: Cached tag lists offered as token completions
+//! - **Telemetry**: Opt-in local feature usage counters
+//! - **Logging**: Rotating log files for bug reports
+//! - **Updates**: Database snapshot and rollback around application updates
+//! - **Event Bus**: In-process subscribers to persona and token mutations
+//! - **In-Memory Store**: Database-free implementation of the repository traits
 //!
 //! # Architecture Role
 //!
@@ -18,13 +36,49 @@
 //!
 //! - [`database`]: `SQLite` connection management, migrations, and repositories
 //! - [`ai`]: Multi-provider AI adapter using the `genai` crate
+//! - [`civitai`]: Looking up trigger words and base models on Civitai
+//! - [`face_embedder`]: Running the local face embedding program on an image
+//! - [`huggingface`]: Searching the `HuggingFace` Hub for image model repos
 //! - [`tokenizer`]: Model-aware token counting for CLIP and T5 tokenizers
 //! - [`keyring`]: Secure API key storage using OS credential managers
+//! - [`deep_link`]: Decoding of `ppm://import` links into import previews
+//! - [`event_bus`]: Publishing domain events to subscribers such as the windows
+//! - [`locale`]: Display locale used to render messages
+//! - [`logging`]: `tracing` subscriber writing rotated log files
+//! - [`memory`]: `InMemoryStore` fake of the persona and token repositories
+//! - [`offline`]: Offline mode flag checked before any network access
+//! - [`pdf`]: Writing persona sheets as PDF, with their reference images
+//! - [`proxy`]: Applying proxy settings to outgoing HTTP requests
+//! - [`resource_scan`]: Scanning embedding and `LoRA` folders, with file hashes
+//! - [`safe_mode`]: Safe mode flag checked by list, search, and compose commands
+//! - [`share_server`]: Serving share sessions over HTTP until they expire or stop
+//! - [`scheduler`]: Scheduler flag, timer, and guard against overlapping task runs
+//! - [`tag_dictionary`]: Loading tag dictionaries and looking up tags by prefix
+//! - [`telemetry`]: Telemetry flag and feature usage recording
+//! - [`update`]: Staging the database around application updates
 
 pub mod ai;
+pub mod civitai;
 pub mod database;
+pub mod deep_link;
+pub mod event_bus;
+pub mod face_embedder;
+pub mod huggingface;
 pub mod keyring;
+pub mod locale;
+pub mod logging;
+pub mod memory;
+pub mod offline;
+pub mod pdf;
+pub mod proxy;
+pub mod resource_scan;
+pub mod safe_mode;
+pub mod scheduler;
+pub mod share_server;
+pub mod tag_dictionary;
+pub mod telemetry;
 pub mod tokenizer;
+pub mod update;
 
 // Re-export commonly used types for ergonomic imports
 pub use database::Database;
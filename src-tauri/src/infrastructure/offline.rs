@@ -0,0 +1,45 @@
+//! Offline mode
+//!
+//! A process-wide switch that stops the app from touching the network. When
+//! enabled, tokenizer downloads and calls to remote AI providers fail fast with
+//! `AppError::Offline` instead of waiting for a connection timeout, which can
+//! take minutes on an air-gapped machine.
+//!
+//! Tokenizers already in the local `HuggingFace` cache still load, and Ollama
+//! keeps working since it runs locally.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::error::AppError;
+
+/// Whether offline mode is enabled.
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+
+/// Enables or disables offline mode.
+///
+/// Applied at startup from the stored settings, and again whenever they change.
+pub fn set_offline(offline: bool) {
+    OFFLINE.store(offline, Ordering::Relaxed);
+}
+
+/// Returns whether offline mode is enabled.
+#[must_use]
+pub fn is_offline() -> bool {
+    OFFLINE.load(Ordering::Relaxed)
+}
+
+/// Fails if offline mode is enabled.
+///
+/// # Arguments
+///
+/// * `action` - What needs the network, for the error message (e.g., "Downloading tokenizer")
+///
+/// # Errors
+///
+/// Returns `AppError::Offline` if offline mode is enabled.
+pub fn ensure_online(action: &str) -> Result<(), AppError> {
+    if is_offline() {
+        return Err(AppError::Offline(action.to_string()));
+    }
+    Ok(())
+}
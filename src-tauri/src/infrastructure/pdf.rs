@@ -0,0 +1,337 @@
+//! PDF Rendering
+//!
+//! Writes persona sheets laid out by `domain::sheet` as PDF documents with
+//! the `pdf-writer` crate.
+//!
+//! # Fonts
+//!
+//! Text uses the standard Helvetica, Helvetica Bold, and Courier fonts in
+//! `WinAnsiEncoding`, so no font file is embedded and documents stay small.
+//! Characters the encoding lacks (e.g., CJK) are printed as `?`.
+//!
+//! # Images
+//!
+//! JPEG files are embedded as they are. PNG files are decoded, flattened onto
+//! white, and recompressed. Other formats, and files that cannot be read, are
+//! left off the sheet with a warning rather than failing the export.
+
+use std::fs::{self, File};
+use std::io::{BufReader, Write};
+use std::path::Path;
+
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use pdf_writer::{Content, Filter, Finish, Name, Pdf, Rect, Ref, Str, TextStr};
+
+use crate::domain::sheet::{CharacterSheet, SheetFont, SheetItem, PAGE_HEIGHT, PAGE_WIDTH};
+use crate::error::AppError;
+
+/// Application name written as the document creator.
+const CREATOR: &str = "Persona Prompt Manager";
+
+/// An image ready to embed.
+struct PdfImage {
+    width: u32,
+    height: u32,
+    /// Samples, encoded as `filter` says
+    data: Vec<u8>,
+    filter: Filter,
+    /// Whether samples are gray rather than RGB
+    gray: bool,
+}
+
+/// Renders a persona sheet as a PDF document.
+///
+/// # Arguments
+///
+/// * `sheet` - The sheet content
+///
+/// # Returns
+///
+/// The bytes of the PDF file. Images that cannot be read are left out.
+#[must_use]
+pub fn render_sheet(sheet: &CharacterSheet) -> Vec<u8> {
+    let images: Vec<Option<PdfImage>> = sheet
+        .images
+        .iter()
+        .map(|path| match load_image(path) {
+            Ok(image) => Some(image),
+            Err(e) => {
+                tracing::warn!(path = %path, error = %e, "Leaving image off persona sheet");
+                None
+            }
+        })
+        .collect();
+    let sizes: Vec<Option<(u32, u32)>> = images
+        .iter()
+        .map(|image| image.as_ref().map(|image| (image.width, image.height)))
+        .collect();
+    let pages = sheet.layout(&sizes);
+
+    let mut next_id = 1;
+    let mut alloc = || {
+        let id = Ref::new(next_id);
+        next_id += 1;
+        id
+    };
+    let catalog_id = alloc();
+    let page_tree_id = alloc();
+    let info_id = alloc();
+    let fonts = [
+        (Name(b"F1"), alloc(), "Helvetica"),
+        (Name(b"F2"), alloc(), "Helvetica-Bold"),
+        (Name(b"F3"), alloc(), "Courier"),
+    ];
+    let image_ids: Vec<Option<Ref>> = images
+        .iter()
+        .map(|image| image.as_ref().map(|_| alloc()))
+        .collect();
+    let image_names: Vec<String> = (0..images.len()).map(|i| format!("Im{i}")).collect();
+    let page_ids: Vec<(Ref, Ref)> = pages.iter().map(|_| (alloc(), alloc())).collect();
+
+    let mut pdf = Pdf::new();
+    pdf.catalog(catalog_id).pages(page_tree_id);
+    pdf.document_info(info_id)
+        .title(TextStr(&sheet.title))
+        .creator(TextStr(CREATOR));
+    pdf.pages(page_tree_id)
+        .kids(page_ids.iter().map(|(page_id, _)| *page_id))
+        .count(i32::try_from(page_ids.len()).unwrap_or(i32::MAX));
+
+    for (_, id, base_font) in &fonts {
+        pdf.type1_font(*id)
+            .base_font(Name(base_font.as_bytes()))
+            .encoding_predefined(Name(b"WinAnsiEncoding"));
+    }
+
+    for (image, id) in images.iter().zip(&image_ids) {
+        let (Some(image), Some(id)) = (image, id) else {
+            continue;
+        };
+        let mut xobject = pdf.image_xobject(*id, &image.data);
+        xobject.filter(image.filter);
+        xobject.width(i32::try_from(image.width).unwrap_or(i32::MAX));
+        xobject.height(i32::try_from(image.height).unwrap_or(i32::MAX));
+        if image.gray {
+            xobject.color_space().device_gray();
+        } else {
+            xobject.color_space().device_rgb();
+        }
+        xobject.bits_per_component(8);
+        xobject.finish();
+    }
+
+    for (page, (page_id, content_id)) in pages.iter().zip(&page_ids) {
+        let mut writer = pdf.page(*page_id);
+        writer
+            .media_box(Rect::new(0.0, 0.0, PAGE_WIDTH, PAGE_HEIGHT))
+            .parent(page_tree_id)
+            .contents(*content_id);
+        let mut resources = writer.resources();
+        let mut font_resources = resources.fonts();
+        for (name, id, _) in &fonts {
+            font_resources.pair(*name, *id);
+        }
+        font_resources.finish();
+        let mut xobjects = resources.x_objects();
+        for (name, id) in image_names.iter().zip(&image_ids) {
+            if let Some(id) = id {
+                xobjects.pair(Name(name.as_bytes()), *id);
+            }
+        }
+        xobjects.finish();
+        resources.finish();
+        writer.finish();
+
+        let mut content = Content::new();
+        for item in &page.items {
+            match item {
+                SheetItem::Text {
+                    x,
+                    y,
+                    size,
+                    font,
+                    text,
+                } => {
+                    let font = match font {
+                        SheetFont::Regular => fonts[0].0,
+                        SheetFont::Bold => fonts[1].0,
+                        SheetFont::Mono => fonts[2].0,
+                    };
+                    content
+                        .begin_text()
+                        .set_font(font, *size)
+                        .next_line(*x, *y)
+                        .show(Str(&win_ansi(text)))
+                        .end_text();
+                }
+                SheetItem::Line { x1, y1, x2, y2 } => {
+                    content
+                        .set_line_width(0.5)
+                        .set_stroke_gray(0.7)
+                        .move_to(*x1, *y1)
+                        .line_to(*x2, *y2)
+                        .stroke();
+                }
+                SheetItem::Image {
+                    index,
+                    x,
+                    y,
+                    width,
+                    height,
+                } => {
+                    if let Some(name) = image_names.get(*index) {
+                        content
+                            .save_state()
+                            .transform([*width, 0.0, 0.0, *height, *x, *y])
+                            .x_object(Name(name.as_bytes()))
+                            .restore_state();
+                    }
+                }
+            }
+        }
+        pdf.stream(*content_id, &content.finish());
+    }
+
+    pdf.finish()
+}
+
+/// Reads an image file for embedding (internal helper).
+fn load_image(path: &str) -> Result<PdfImage, AppError> {
+    let extension = Path::new(path)
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase());
+    match extension.as_deref() {
+        Some("jpg" | "jpeg") => load_jpeg(path),
+        Some("png") => load_png(path),
+        _ => Err(AppError::Validation(
+            "Only PNG and JPEG images can be printed".to_string(),
+        )),
+    }
+}
+
+/// Reads a JPEG file, keeping its compressed data (internal helper).
+///
+/// The size and color components come from the first start-of-frame marker.
+fn load_jpeg(path: &str) -> Result<PdfImage, AppError> {
+    let data = fs::read(path)?;
+    let invalid = || AppError::Validation("Not a valid JPEG file".to_string());
+    if !data.starts_with(&[0xFF, 0xD8]) {
+        return Err(invalid());
+    }
+
+    let mut pos = 2;
+    while pos + 4 <= data.len() {
+        if data[pos] != 0xFF {
+            return Err(invalid());
+        }
+        let marker = data[pos + 1];
+        let length = usize::from(u16::from_be_bytes([data[pos + 2], data[pos + 3]]));
+        // SOF0 to SOF15, except DHT (C4), JPG (C8), and DAC (CC)
+        if (0xC0..=0xCF).contains(&marker) && !matches!(marker, 0xC4 | 0xC8 | 0xCC) {
+            let frame = data.get(pos + 4..pos + 10).ok_or_else(invalid)?;
+            let height = u32::from(u16::from_be_bytes([frame[1], frame[2]]));
+            let width = u32::from(u16::from_be_bytes([frame[3], frame[4]]));
+            let gray = match frame[5] {
+                1 => true,
+                3 => false,
+                _ => {
+                    return Err(AppError::Validation(
+                        "CMYK JPEG images cannot be printed".to_string(),
+                    ))
+                }
+            };
+            return Ok(PdfImage {
+                width,
+                height,
+                data,
+                filter: Filter::DctDecode,
+                gray,
+            });
+        }
+        pos += 2 + length;
+    }
+    Err(invalid())
+}
+
+/// Decodes a PNG file into flate-compressed 8-bit samples, flattening any
+/// transparency onto white (internal helper).
+fn load_png(path: &str) -> Result<PdfImage, AppError> {
+    let invalid =
+        |e: png::DecodingError| AppError::Validation(format!("Not a valid PNG file: {e}"));
+    let mut decoder = png::Decoder::new(BufReader::new(File::open(path)?));
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().map_err(invalid)?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let frame = reader.next_frame(&mut buffer).map_err(invalid)?;
+    buffer.truncate(frame.buffer_size());
+
+    let (channels, gray) = match frame.color_type {
+        png::ColorType::Grayscale => (1, true),
+        png::ColorType::GrayscaleAlpha => (2, true),
+        png::ColorType::Rgba => (4, false),
+        png::ColorType::Rgb | png::ColorType::Indexed => (3, false),
+    };
+    let samples: Vec<u8> = if channels == 2 || channels == 4 {
+        buffer
+            .chunks_exact(channels)
+            .flat_map(|pixel| {
+                let (color, alpha) = pixel.split_at(channels - 1);
+                let alpha = u16::from(alpha[0]);
+                color.iter().map(move |c| {
+                    let blended = (u16::from(*c) * alpha + 255 * (255 - alpha)) / 255;
+                    u8::try_from(blended).unwrap_or(u8::MAX)
+                })
+            })
+            .collect()
+    } else {
+        buffer
+    };
+
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&samples)?;
+    Ok(PdfImage {
+        width: frame.width,
+        height: frame.height,
+        data: encoder.finish()?,
+        filter: Filter::FlateDecode,
+        gray,
+    })
+}
+
+/// Encodes text in `WinAnsiEncoding`, replacing characters it lacks with `?`.
+fn win_ansi(text: &str) -> Vec<u8> {
+    text.chars()
+        .map(|c| match c {
+            ' '..='~' | '\u{a0}'..='\u{ff}' => u8::try_from(u32::from(c)).unwrap_or(b'?'),
+            '\u{20ac}' => 0x80,
+            '\u{201a}' => 0x82,
+            '\u{192}' => 0x83,
+            '\u{201e}' => 0x84,
+            '\u{2026}' => 0x85,
+            '\u{2020}' => 0x86,
+            '\u{2021}' => 0x87,
+            '\u{2c6}' => 0x88,
+            '\u{2030}' => 0x89,
+            '\u{160}' => 0x8A,
+            '\u{2039}' => 0x8B,
+            '\u{152}' => 0x8C,
+            '\u{17d}' => 0x8E,
+            '\u{2018}' => 0x91,
+            '\u{2019}' => 0x92,
+            '\u{201c}' => 0x93,
+            '\u{201d}' => 0x94,
+            '\u{2022}' => 0x95,
+            '\u{2013}' => 0x96,
+            '\u{2014}' => 0x97,
+            '\u{2dc}' => 0x98,
+            '\u{2122}' => 0x99,
+            '\u{161}' => 0x9A,
+            '\u{203a}' => 0x9B,
+            '\u{153}' => 0x9C,
+            '\u{17e}' => 0x9E,
+            '\u{178}' => 0x9F,
+            _ => b'?',
+        })
+        .collect()
+}
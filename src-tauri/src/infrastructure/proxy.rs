@@ -0,0 +1,244 @@
+//! Network proxy handling
+//!
+//! Both HTTP stacks used by the app read the standard proxy environment
+//! variables: the `HuggingFace` hub client behind tokenizer downloads honors
+//! `HTTP(S)_PROXY`, and the AI client honors `HTTP(S)_PROXY` and `NO_PROXY`.
+//! Explicit [`ProxySettings`] are therefore applied by rewriting those variables
+//! for the running process; disabling them restores the values the app was
+//! launched with.
+//!
+//! AI clients are built per request and pick up changes immediately. A
+//! tokenizer that is already loaded is unaffected, since it is not downloaded
+//! again.
+//!
+//! # Diagnostic
+//!
+//! [`check_connectivity`] opens a TCP connection to each endpoint, or through
+//! the proxy with an HTTP `CONNECT` tunnel, and reports what failed.
+
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+use base64::Engine;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+
+use crate::domain::ai::AiProvider;
+use crate::domain::settings::{ConnectivityCheck, ConnectivityReport, ProxySettings, ProxySource};
+
+/// Host serving tokenizer files.
+pub const HUGGINGFACE_HOST: &str = "huggingface.co";
+
+/// Time allowed for each connectivity check.
+const CHECK_TIMEOUT: Duration = Duration::from_secs(8);
+
+/// HTTPS port used by every checked endpoint.
+const HTTPS_PORT: u16 = 443;
+
+/// Proxy variables, in both spellings since tools disagree on case.
+const HTTP_PROXY_VARS: [&str; 2] = ["HTTP_PROXY", "http_proxy"];
+const HTTPS_PROXY_VARS: [&str; 2] = ["HTTPS_PROXY", "https_proxy"];
+const NO_PROXY_VARS: [&str; 2] = ["NO_PROXY", "no_proxy"];
+
+/// Proxy variables as they were when the app launched.
+static LAUNCH_ENV: OnceLock<Vec<(&'static str, Option<String>)>> = OnceLock::new();
+
+/// Returns the proxy variables captured at launch, capturing them on first use.
+fn launch_env() -> &'static [(&'static str, Option<String>)] {
+    LAUNCH_ENV.get_or_init(|| {
+        HTTP_PROXY_VARS
+            .iter()
+            .chain(&HTTPS_PROXY_VARS)
+            .chain(&NO_PROXY_VARS)
+            .map(|&name| (name, std::env::var(name).ok()))
+            .collect()
+    })
+}
+
+/// Sets both spellings of a variable, or removes them for an empty value.
+fn set_env_pair(names: [&str; 2], value: Option<&str>) {
+    let value = value.map(str::trim).filter(|v| !v.is_empty());
+    for name in names {
+        match value {
+            Some(value) => std::env::set_var(name, value),
+            None => std::env::remove_var(name),
+        }
+    }
+}
+
+/// Applies proxy settings to the process environment.
+///
+/// Must be called at startup, before any HTTP client is created, so the
+/// launch environment is captured unmodified.
+pub fn apply_proxy_settings(settings: &ProxySettings) {
+    let launch = launch_env();
+
+    if settings.enabled {
+        set_env_pair(HTTP_PROXY_VARS, settings.http_proxy.as_deref());
+        set_env_pair(HTTPS_PROXY_VARS, settings.https_proxy.as_deref());
+        set_env_pair(NO_PROXY_VARS, settings.no_proxy.as_deref());
+        return;
+    }
+
+    for (name, value) in launch {
+        match value {
+            Some(value) => std::env::set_var(name, value),
+            None => std::env::remove_var(name),
+        }
+    }
+}
+
+/// Returns where the proxy in effect comes from.
+#[must_use]
+pub fn proxy_source(settings: &ProxySettings) -> ProxySource {
+    if settings.enabled {
+        return ProxySource::Settings;
+    }
+
+    let from_env = launch_env().iter().any(|(name, value)| {
+        !NO_PROXY_VARS.contains(name) && value.as_deref().is_some_and(|v| !v.trim().is_empty())
+    });
+    if from_env {
+        ProxySource::Environment
+    } else {
+        ProxySource::None
+    }
+}
+
+/// Reads the first non-empty variable among `names`.
+fn read_env(names: &[&str]) -> Option<String> {
+    names
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .map(|value| value.trim().to_string())
+        .find(|value| !value.is_empty())
+}
+
+/// Returns the proxy currently used for HTTPS requests to `host`, honoring `NO_PROXY`.
+#[must_use]
+pub fn https_proxy_for(host: &str) -> Option<String> {
+    let bypassed = read_env(&NO_PROXY_VARS).is_some_and(|no_proxy| {
+        no_proxy.split(',').map(str::trim).any(|entry| {
+            let entry = entry.trim_start_matches('.');
+            entry == "*"
+                || (!entry.is_empty() && (host == entry || host.ends_with(&format!(".{entry}"))))
+        })
+    });
+    if bypassed {
+        return None;
+    }
+
+    read_env(&HTTPS_PROXY_VARS).or_else(|| read_env(&["ALL_PROXY", "all_proxy"]))
+}
+
+/// Endpoints checked by the diagnostic: the tokenizer host and every AI API host.
+fn diagnostic_targets() -> Vec<(String, &'static str)> {
+    let mut targets = vec![("HuggingFace (tokenizers)".to_string(), HUGGINGFACE_HOST)];
+    targets.extend(AiProvider::all().iter().filter_map(|provider| {
+        provider
+            .api_host()
+            .map(|host| (provider.display_name().to_string(), host))
+    }));
+    targets
+}
+
+/// Checks that the tokenizer host and the AI provider APIs can be reached
+/// with the current proxy configuration.
+///
+/// Checks run concurrently; each one is limited to a few seconds.
+pub async fn check_connectivity(settings: &ProxySettings) -> ConnectivityReport {
+    let handles: Vec<_> = diagnostic_targets()
+        .into_iter()
+        .map(|(name, host)| tokio::spawn(check_endpoint(name, host)))
+        .collect();
+
+    let mut checks = Vec::with_capacity(handles.len());
+    for handle in handles {
+        if let Ok(check) = handle.await {
+            checks.push(check);
+        }
+    }
+
+    ConnectivityReport {
+        proxy_source: proxy_source(settings),
+        checks,
+    }
+}
+
+/// Checks one endpoint, directly or through the configured proxy.
+async fn check_endpoint(name: String, host: &'static str) -> ConnectivityCheck {
+    let proxy = https_proxy_for(host);
+    let started = Instant::now();
+
+    let result = tokio::time::timeout(CHECK_TIMEOUT, async {
+        match proxy.as_deref() {
+            Some(proxy) => connect_via_proxy(proxy, host).await,
+            None => TcpStream::connect((host, HTTPS_PORT))
+                .await
+                .map(|_| ())
+                .map_err(|e| format!("Connection failed: {e}")),
+        }
+    })
+    .await
+    .unwrap_or_else(|_| Err(format!("Timed out after {}s", CHECK_TIMEOUT.as_secs())));
+
+    ConnectivityCheck {
+        name,
+        target: format!("{host}:{HTTPS_PORT}"),
+        proxy,
+        reachable: result.is_ok(),
+        elapsed_ms: started.elapsed().as_millis() as u64,
+        error: result.err(),
+    }
+}
+
+/// Opens a `CONNECT` tunnel to `host` through an HTTP proxy.
+///
+/// SOCKS proxies are only checked for reachability of the proxy itself.
+async fn connect_via_proxy(proxy: &str, host: &str) -> Result<(), String> {
+    let (scheme, rest) = proxy.split_once("://").unwrap_or(("http", proxy));
+    let authority = rest.split('/').next().unwrap_or(rest);
+    let (credentials, address) = match authority.rsplit_once('@') {
+        Some((credentials, address)) => (Some(credentials), address),
+        None => (None, authority),
+    };
+    let address = if address.contains(':') {
+        address.to_string()
+    } else {
+        format!("{address}:{}", if scheme == "https" { 443 } else { 80 })
+    };
+
+    let mut stream = TcpStream::connect(&address)
+        .await
+        .map_err(|e| format!("Cannot reach proxy {address}: {e}"))?;
+
+    if scheme.starts_with("socks") {
+        return Ok(());
+    }
+
+    let mut request =
+        format!("CONNECT {host}:{HTTPS_PORT} HTTP/1.1\r\nHost: {host}:{HTTPS_PORT}\r\n");
+    if let Some(credentials) = credentials {
+        let encoded = base64::engine::general_purpose::STANDARD.encode(credentials);
+        request.push_str(&format!("Proxy-Authorization: Basic {encoded}\r\n"));
+    }
+    request.push_str("\r\n");
+
+    stream
+        .write_all(request.as_bytes())
+        .await
+        .map_err(|e| format!("Proxy connection dropped: {e}"))?;
+
+    let mut status_line = String::new();
+    BufReader::new(stream)
+        .read_line(&mut status_line)
+        .await
+        .map_err(|e| format!("Proxy connection dropped: {e}"))?;
+
+    match status_line.split_whitespace().nth(1) {
+        Some(status) if status.starts_with('2') => Ok(()),
+        Some("407") => Err("Proxy requires authentication (407)".to_string()),
+        Some(status) => Err(format!("Proxy refused the tunnel ({status})")),
+        None => Err("Proxy sent an invalid response".to_string()),
+    }
+}
@@ -0,0 +1,116 @@
+//! Resource Folder Scan
+//!
+//! Finds textual inversion embeddings and `LoRA` models in the folders set in
+//! the resource folder settings, and hashes each file so it can be identified
+//! on Civitai.
+//!
+//! Scanning reads every file in full to hash it, which takes a while for
+//! folders of large `LoRA` models; call it from `tokio::task::spawn_blocking`.
+//! Files are only read, never modified.
+
+use std::fs::{self, File};
+use std::io;
+use std::path::Path;
+
+use sha2::{Digest, Sha256};
+
+use crate::domain::resource::{LocalResource, ResourceKind};
+use crate::domain::settings::ResourceFolders;
+use crate::error::AppError;
+
+/// Deepest subfolder level searched below a resource folder.
+const MAX_SCAN_DEPTH: usize = 4;
+
+/// Scans the configured folders for embeddings and `LoRA` models.
+///
+/// Folders that are unset or do not exist are skipped. Hidden files and
+/// folders are ignored.
+///
+/// # Arguments
+///
+/// * `folders` - The embeddings and `LoRA` folders to scan
+///
+/// # Returns
+///
+/// Embeddings, then `LoRA` models, each sorted by name (case-insensitive).
+///
+/// # Errors
+///
+/// Returns `AppError::Io` if a folder or file cannot be read.
+pub fn scan_resources(folders: &ResourceFolders) -> Result<Vec<LocalResource>, AppError> {
+    let mut resources = Vec::new();
+
+    for (folder, kind) in [
+        (&folders.embeddings, ResourceKind::Embedding),
+        (&folders.loras, ResourceKind::Lora),
+    ] {
+        let Some(folder) = folder.as_deref().map(str::trim) else {
+            continue;
+        };
+        let folder = Path::new(folder);
+        if !folder.is_dir() {
+            tracing::warn!(folder = %folder.display(), "Resource folder not found");
+            continue;
+        }
+
+        let mut found = Vec::new();
+        scan_folder(folder, kind, 0, &mut found)?;
+        found.sort_by_key(|resource| resource.name.to_lowercase());
+        resources.extend(found);
+    }
+
+    Ok(resources)
+}
+
+/// Adds the resources of `folder` and its subfolders to `found`.
+fn scan_folder(
+    folder: &Path,
+    kind: ResourceKind,
+    depth: usize,
+    found: &mut Vec<LocalResource>,
+) -> Result<(), AppError> {
+    for entry in fs::read_dir(folder)? {
+        let entry = entry?;
+        let path = entry.path();
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            if depth < MAX_SCAN_DEPTH {
+                scan_folder(&path, kind, depth + 1, found)?;
+            }
+            continue;
+        }
+
+        let is_resource = path
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .is_some_and(|ext| kind.extensions().contains(&ext.as_str()));
+        if !is_resource {
+            continue;
+        }
+        let Some(name) = path.file_stem().map(|s| s.to_string_lossy().into_owned()) else {
+            continue;
+        };
+
+        let (sha256, size) = hash_file(&path)?;
+        found.push(LocalResource::new(
+            kind,
+            name,
+            path.to_string_lossy().into_owned(),
+            size,
+            &sha256,
+        ));
+    }
+
+    Ok(())
+}
+
+/// Computes the SHA-256 of a file, returning it with the number of bytes read.
+fn hash_file(path: &Path) -> Result<(String, u64), AppError> {
+    let mut hasher = Sha256::new();
+    let size = io::copy(&mut File::open(path)?, &mut hasher)?;
+    Ok((format!("{:x}", hasher.finalize()), size))
+}
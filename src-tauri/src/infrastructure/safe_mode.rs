@@ -0,0 +1,48 @@
+//! Safe mode
+//!
+//! A process-wide switch that hides mature content, for streaming or demoing
+//! the app. When enabled, personas rated [`ContentRating::Mature`] are left out
+//! of lists, search results, and smart collection queries, and cannot be
+//! composed. Commands addressing a persona by ID for editing still work.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::domain::persona::{ContentRating, Persona};
+use crate::error::AppError;
+
+/// Whether safe mode is enabled.
+static SAFE_MODE: AtomicBool = AtomicBool::new(false);
+
+/// Enables or disables safe mode.
+///
+/// Applied at startup from the stored settings, and again whenever they change.
+pub fn set_safe_mode(enabled: bool) {
+    SAFE_MODE.store(enabled, Ordering::Relaxed);
+}
+
+/// Returns whether safe mode is enabled.
+#[must_use]
+pub fn is_safe_mode() -> bool {
+    SAFE_MODE.load(Ordering::Relaxed)
+}
+
+/// Returns whether a persona may be shown in the current mode.
+#[must_use]
+pub fn is_visible(persona: &Persona) -> bool {
+    !is_safe_mode() || persona.content_rating != ContentRating::Mature
+}
+
+/// Fails if safe mode hides the persona.
+///
+/// # Errors
+///
+/// Returns `AppError::NotFound` if safe mode is enabled and the persona is rated mature.
+pub fn ensure_visible(persona: &Persona) -> Result<(), AppError> {
+    if !is_visible(persona) {
+        return Err(AppError::NotFound(format!(
+            "Persona with id '{}' is hidden in safe mode",
+            persona.id
+        )));
+    }
+    Ok(())
+}
@@ -0,0 +1,92 @@
+//! Scheduler
+//!
+//! A process-wide switch and timer for scheduled tasks (see
+//! `domain::schedule`). While the app is open, [`start`] checks for due tasks
+//! every few minutes; nothing runs unless the scheduler is enabled in the app
+//! settings. The first check waits a little after startup, so missed runs do
+//! not compete with the app's own loading.
+//!
+//! A task runs at most once at a time: [`begin_run`] guards against the timer
+//! and a manual run overlapping.
+
+use std::collections::HashSet;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use tauri::AppHandle;
+
+/// Whether scheduled tasks run.
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// IDs of the tasks currently running.
+static RUNNING: Mutex<Option<HashSet<String>>> = Mutex::new(None);
+
+/// Delay before the first check after startup.
+const STARTUP_DELAY: Duration = Duration::from_secs(60);
+
+/// Time between two checks for due tasks.
+const CHECK_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// Enables or disables the scheduler.
+///
+/// Applied at startup from the stored settings, and again whenever they change.
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Returns whether the scheduler is enabled.
+#[must_use]
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Marks a task as running until the returned guard is dropped.
+///
+/// Returns `None` if the task is already running.
+#[must_use]
+pub fn begin_run(task_id: &str) -> Option<RunGuard> {
+    let mut running = RUNNING.lock().ok()?;
+    running
+        .get_or_insert_with(HashSet::new)
+        .insert(task_id.to_string())
+        .then(|| RunGuard {
+            task_id: task_id.to_string(),
+        })
+}
+
+/// Marks a task as running while alive (see [`begin_run`]).
+pub struct RunGuard {
+    task_id: String,
+}
+
+impl Drop for RunGuard {
+    fn drop(&mut self) {
+        if let Ok(mut running) = RUNNING.lock() {
+            if let Some(running) = running.as_mut() {
+                running.remove(&self.task_id);
+            }
+        }
+    }
+}
+
+/// Starts the background timer.
+///
+/// `run_due` is called on every check while the scheduler is enabled, and
+/// runs whatever tasks are due.
+pub fn start<F, Fut>(app: AppHandle, run_due: F)
+where
+    F: Fn(AppHandle) -> Fut + Send + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(STARTUP_DELAY).await;
+        loop {
+            if is_enabled() {
+                run_due(app.clone()).await;
+            }
+            tokio::time::sleep(CHECK_INTERVAL).await;
+        }
+    });
+}
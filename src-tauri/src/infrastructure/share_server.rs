@@ -0,0 +1,312 @@
+//! Share Server
+//!
+//! A minimal HTTP/1.1 server for share sessions (see `domain::share`). It
+//! answers `GET` and `HEAD` requests through a handler supplied by the
+//! caller, one short-lived thread per connection up to
+//! [`MAX_CONNECTIONS`] at once (later ones get a 503), and closes every
+//! connection after one response.
+//!
+//! Only one server runs at a time. It stops when the session expires, when
+//! [`stop`] is called, or when another session is started. Binding to
+//! `0.0.0.0` (LAN sharing) may prompt the operating system firewall.
+
+use std::io::{ErrorKind, Read, Write};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, TcpListener, TcpStream, UdpSocket};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use chrono::Utc;
+
+use crate::domain::share::ShareSession;
+use crate::error::AppError;
+
+/// The running server, if any.
+static RUNNING: Mutex<Option<RunningServer>> = Mutex::new(None);
+
+/// Time between checks for new connections, expiry, and stop requests.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Time a client has to send its request.
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Time a client has to take each part of a response.
+const WRITE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Largest accepted request head; share requests have no body.
+const MAX_REQUEST_BYTES: usize = 8 * 1024;
+
+/// Most connections served at once; a browser opens about six per page.
+pub const MAX_CONNECTIONS: usize = 16;
+
+/// Answers a request path with a response.
+pub type ShareHandler = Arc<dyn Fn(&str) -> ShareResponse + Send + Sync>;
+
+/// A response to a share request.
+#[derive(Debug, Clone)]
+pub struct ShareResponse {
+    /// HTTP status code
+    pub status: u16,
+    /// `Content-Type` header value
+    pub content_type: &'static str,
+    /// `Location` header value, for redirects
+    pub location: Option<String>,
+    /// Response body
+    pub body: Vec<u8>,
+}
+
+impl ShareResponse {
+    /// A 200 response.
+    #[must_use]
+    pub const fn ok(content_type: &'static str, body: Vec<u8>) -> Self {
+        Self {
+            status: 200,
+            content_type,
+            location: None,
+            body,
+        }
+    }
+
+    /// A plain-text error response.
+    #[must_use]
+    pub fn error(status: u16, message: &str) -> Self {
+        Self {
+            status,
+            content_type: "text/plain; charset=utf-8",
+            location: None,
+            body: message.as_bytes().to_vec(),
+        }
+    }
+
+    /// A redirect to another path of the server.
+    #[must_use]
+    pub const fn redirect(location: String) -> Self {
+        Self {
+            status: 301,
+            content_type: "text/plain; charset=utf-8",
+            location: Some(location),
+            body: Vec::new(),
+        }
+    }
+}
+
+/// A server running for a session.
+struct RunningServer {
+    session: ShareSession,
+    stop: Arc<AtomicBool>,
+}
+
+/// Starts serving a session, stopping any previous one.
+///
+/// # Arguments
+///
+/// * `session` - The session; its URL is filled in from the bound address
+/// * `handler` - Answers requests while the session has not expired
+///
+/// # Returns
+///
+/// The session with its URL.
+///
+/// # Errors
+///
+/// Returns `AppError::Io` if the server cannot listen, or
+/// `AppError::Validation` if LAN sharing is requested but the computer has
+/// no local network address.
+pub fn start(session: ShareSession, handler: ShareHandler) -> Result<ShareSession, AppError> {
+    stop();
+
+    let (bind, host) = if session.lan {
+        let address = lan_address().ok_or_else(|| {
+            AppError::Validation(
+                "No local network address found; share on this computer only".to_string(),
+            )
+        })?;
+        (IpAddr::V4(Ipv4Addr::UNSPECIFIED), address.to_string())
+    } else {
+        (IpAddr::V4(Ipv4Addr::LOCALHOST), "127.0.0.1".to_string())
+    };
+    let listener = TcpListener::bind(SocketAddr::new(bind, 0))?;
+    listener.set_nonblocking(true)?;
+    let session = session.with_address(&host, listener.local_addr()?.port());
+
+    let stop = Arc::new(AtomicBool::new(false));
+    {
+        let mut running = RUNNING
+            .lock()
+            .map_err(|_| AppError::Internal("Failed to acquire share server lock".to_string()))?;
+        *running = Some(RunningServer {
+            session: session.clone(),
+            stop: Arc::clone(&stop),
+        });
+    }
+
+    let expires_at = session.expires_at;
+    let token = session.token.clone();
+    let active = Arc::new(AtomicUsize::new(0));
+    thread::spawn(move || {
+        while !stop.load(Ordering::Relaxed) && Utc::now() < expires_at {
+            match listener.accept() {
+                Ok((mut stream, _)) => {
+                    if active.fetch_add(1, Ordering::AcqRel) >= MAX_CONNECTIONS {
+                        active.fetch_sub(1, Ordering::AcqRel);
+                        let _ = reject_busy(&mut stream);
+                        continue;
+                    }
+                    let handler = Arc::clone(&handler);
+                    let active = Arc::clone(&active);
+                    thread::spawn(move || {
+                        if let Err(e) = serve(stream, &handler, expires_at) {
+                            tracing::debug!(error = %e, "Share request failed");
+                        }
+                        active.fetch_sub(1, Ordering::AcqRel);
+                    });
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => thread::sleep(POLL_INTERVAL),
+                Err(e) => {
+                    tracing::warn!(error = %e, "Share server stopped accepting connections");
+                    break;
+                }
+            }
+        }
+        clear(&token);
+        tracing::info!("Share server stopped");
+    });
+
+    tracing::info!(lan = session.lan, expires_at = %session.expires_at, "Share server started");
+    Ok(session)
+}
+
+/// Stops the running server, if any.
+///
+/// Returns whether a server was running.
+pub fn stop() -> bool {
+    let Ok(mut running) = RUNNING.lock() else {
+        return false;
+    };
+    running.take().is_some_and(|server| {
+        server.stop.store(true, Ordering::Relaxed);
+        true
+    })
+}
+
+/// Returns the session being served, if it has not expired.
+#[must_use]
+pub fn current() -> Option<ShareSession> {
+    let running = RUNNING.lock().ok()?;
+    running
+        .as_ref()
+        .map(|server| server.session.clone())
+        .filter(|session| !session.is_expired(Utc::now()))
+}
+
+/// Forgets a server once its thread ends, unless another one replaced it
+/// (internal helper).
+fn clear(token: &str) {
+    if let Ok(mut running) = RUNNING.lock() {
+        if running
+            .as_ref()
+            .is_some_and(|server| server.session.token == token)
+        {
+            *running = None;
+        }
+    }
+}
+
+/// Reads one request and writes its response (internal helper).
+fn serve(
+    mut stream: TcpStream,
+    handler: &ShareHandler,
+    expires_at: chrono::DateTime<Utc>,
+) -> std::io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+
+    let mut request = Vec::new();
+    let mut buffer = [0; 1024];
+    while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+        let read = stream.read(&mut buffer)?;
+        if read == 0 {
+            return Ok(());
+        }
+        request.extend_from_slice(&buffer[..read]);
+        if request.len() > MAX_REQUEST_BYTES {
+            return respond(
+                &mut stream,
+                &ShareResponse::error(431, "Request too large"),
+                false,
+            );
+        }
+    }
+
+    let head = String::from_utf8_lossy(&request);
+    let mut parts = head.lines().next().unwrap_or_default().split_whitespace();
+    let (method, path) = (
+        parts.next().unwrap_or_default(),
+        parts.next().unwrap_or("/"),
+    );
+    let response = match method {
+        "GET" | "HEAD" if Utc::now() >= expires_at => {
+            ShareResponse::error(410, "This share link has expired.")
+        }
+        "GET" | "HEAD" => handler(path),
+        _ => ShareResponse::error(405, "Method not allowed"),
+    };
+    respond(&mut stream, &response, method != "HEAD")
+}
+
+/// Answers a connection over the limit with a 503, without reading its
+/// request (internal helper).
+fn reject_busy(stream: &mut TcpStream) -> std::io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+    respond(
+        stream,
+        &ShareResponse::error(503, "Too many connections, try again shortly."),
+        true,
+    )
+}
+
+/// Writes a response (internal helper).
+fn respond(stream: &mut TcpStream, response: &ShareResponse, body: bool) -> std::io::Result<()> {
+    let reason = match response.status {
+        200 => "OK",
+        301 => "Moved Permanently",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        410 => "Gone",
+        431 => "Request Header Fields Too Large",
+        503 => "Service Unavailable",
+        _ => "Internal Server Error",
+    };
+    let mut head = format!(
+        "HTTP/1.1 {} {reason}\r\nContent-Type: {}\r\nContent-Length: {}\r\n\
+         Cache-Control: no-store\r\nX-Content-Type-Options: nosniff\r\n\
+         Referrer-Policy: no-referrer\r\nConnection: close\r\n",
+        response.status,
+        response.content_type,
+        response.body.len()
+    );
+    if let Some(location) = &response.location {
+        head.push_str(&format!("Location: {location}\r\n"));
+    }
+    head.push_str("\r\n");
+
+    stream.write_all(head.as_bytes())?;
+    if body {
+        stream.write_all(&response.body)?;
+    }
+    stream.flush()
+}
+
+/// Returns this computer's address on the local network (internal helper).
+///
+/// Connecting a UDP socket sends nothing; it only selects the interface
+/// that would route to the given address.
+fn lan_address() -> Option<IpAddr> {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).ok()?;
+    socket.connect((Ipv4Addr::new(192, 0, 2, 1), 9)).ok()?;
+    let address = socket.local_addr().ok()?.ip();
+    (!address.is_loopback() && !address.is_unspecified()).then_some(address)
+}
@@ -0,0 +1,117 @@
+//! Tag Dictionaries
+//!
+//! Loads the tag dictionaries set in the app settings (see
+//! `domain::autocomplete`) and answers prefix lookups against them.
+//!
+//! Dictionaries can hold hundreds of thousands of tags, too many to read on
+//! every keystroke: they are loaded once, sorted by completion key, and kept
+//! in memory. They are read again when the list of dictionaries changes or a
+//! file is modified. Files are only read, never modified.
+
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use crate::domain::autocomplete::{DictionaryTag, TokenCompletion};
+use crate::error::AppError;
+
+/// Dictionaries loaded in memory.
+static LOADED: Mutex<Option<LoadedDictionaries>> = Mutex::new(None);
+
+/// Tags of a set of dictionary files.
+struct LoadedDictionaries {
+    /// Path and modification time of each file, as loaded
+    files: Vec<(String, Option<SystemTime>)>,
+    /// Tags of all files, sorted by key, one per key
+    tags: Vec<DictionaryTag>,
+}
+
+/// Looks up dictionary tags starting with a prefix.
+///
+/// Dictionaries that do not exist are skipped. When two dictionaries hold the
+/// same tag, the higher post count is kept.
+///
+/// # Arguments
+///
+/// * `paths` - Absolute paths of the dictionaries
+/// * `prefix_key` - Completion key of the typed prefix
+/// * `limit` - Number of tags to return at most
+///
+/// # Returns
+///
+/// Matching tags, most popular first.
+///
+/// # Errors
+///
+/// Returns `AppError::Io` if a dictionary cannot be read, or
+/// `AppError::Internal` if the dictionary cache is poisoned.
+pub fn complete(
+    paths: &[String],
+    prefix_key: &str,
+    limit: usize,
+) -> Result<Vec<TokenCompletion>, AppError> {
+    if paths.is_empty() || prefix_key.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let files: Vec<(String, Option<SystemTime>)> = paths
+        .iter()
+        .map(|path| {
+            let path = path.trim().to_string();
+            let modified = fs::metadata(&path)
+                .and_then(|metadata| metadata.modified())
+                .ok();
+            (path, modified)
+        })
+        .collect();
+
+    let mut loaded = LOADED
+        .lock()
+        .map_err(|_| AppError::Internal("Failed to acquire tag dictionary lock".to_string()))?;
+    if !loaded.as_ref().is_some_and(|loaded| loaded.files == files) {
+        let tags = load(&files)?;
+        *loaded = Some(LoadedDictionaries { files, tags });
+    }
+    let Some(loaded) = loaded.as_ref() else {
+        return Ok(Vec::new());
+    };
+
+    let start = loaded
+        .tags
+        .partition_point(|tag| tag.key.as_str() < prefix_key);
+    let mut matches: Vec<&DictionaryTag> = loaded.tags[start..]
+        .iter()
+        .take_while(|tag| tag.key.starts_with(prefix_key))
+        .collect();
+    matches.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.key.cmp(&b.key)));
+
+    Ok(matches
+        .into_iter()
+        .take(limit)
+        .map(DictionaryTag::to_completion)
+        .collect())
+}
+
+/// Reads and merges the tags of the given files (internal helper).
+fn load(files: &[(String, Option<SystemTime>)]) -> Result<Vec<DictionaryTag>, AppError> {
+    let mut tags = Vec::new();
+    for (path, modified) in files {
+        if modified.is_none() && !Path::new(path).is_file() {
+            tracing::warn!(path = %path, "Tag dictionary not found");
+            continue;
+        }
+        let bytes = fs::read(path)?;
+        let parsed: Vec<DictionaryTag> = String::from_utf8_lossy(&bytes)
+            .lines()
+            .filter_map(DictionaryTag::parse)
+            .collect();
+        tracing::info!(path = %path, tags = parsed.len(), "Loaded tag dictionary");
+        tags.extend(parsed);
+    }
+
+    // Most popular spelling first within a key, so dedup keeps it
+    tags.sort_by(|a, b| a.key.cmp(&b.key).then_with(|| b.count.cmp(&a.count)));
+    tags.dedup_by(|later, earlier| later.key == earlier.key);
+    Ok(tags)
+}
@@ -0,0 +1,43 @@
+//! Usage telemetry
+//!
+//! A process-wide switch for the opt-in feature usage counters (see
+//! `domain::telemetry`). Commands call [`record`] when a counted feature is
+//! used; nothing is stored unless the user enabled telemetry.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use chrono::Utc;
+use rusqlite::Connection;
+
+use crate::domain::telemetry::Feature;
+
+use super::database::repositories::FeatureUsageRepository;
+
+/// Whether telemetry is enabled.
+static TELEMETRY: AtomicBool = AtomicBool::new(false);
+
+/// Enables or disables telemetry.
+///
+/// Applied at startup from the stored settings, and again whenever they change.
+pub fn set_enabled(enabled: bool) {
+    TELEMETRY.store(enabled, Ordering::Relaxed);
+}
+
+/// Returns whether telemetry is enabled.
+#[must_use]
+pub fn is_enabled() -> bool {
+    TELEMETRY.load(Ordering::Relaxed)
+}
+
+/// Counts one use of a feature, if telemetry is enabled.
+///
+/// Failures are logged and otherwise ignored, so counting never fails the
+/// command that used the feature.
+pub fn record(conn: &Connection, feature: Feature) {
+    if !is_enabled() {
+        return;
+    }
+    if let Err(e) = FeatureUsageRepository::increment(conn, feature, Utc::now().date_naive()) {
+        tracing::warn!(error = %e, feature = feature.as_str(), "Failed to record feature usage");
+    }
+}
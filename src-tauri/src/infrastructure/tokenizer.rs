@@ -2,13 +2,42 @@
 //!
 //! Provides token counting functionality for various image generation models.
 //! Supports dynamic tokenizer loading from `HuggingFace` based on the model being used.
+//!
+//! # Background Loading
+//!
+//! Loading a tokenizer can take seconds on first use. [`load_in_background`]
+//! loads it on a blocking worker thread and emits [`TOKENIZER_LOADED_EVENT`]
+//! when done, so callers can return an estimate immediately and recount once
+//! the real tokenizer is ready.
+//!
+//! # Failed Loads
+//!
+//! A tokenizer that fails to load is not retried for a while: 30 seconds after
+//! the first failure, doubling with each further one up to 30 minutes. Inside
+//! that window, loads fail immediately and [`load_in_background`] does nothing,
+//! so callers that recount on every finished load cannot retry in a loop.
+//!
+//! # Offline Mode
+//!
+//! In offline mode, tokenizers are only loaded from the local `HuggingFace`
+//! cache; anything not downloaded yet falls back to word-based estimates.
+
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, Instant};
 
-use std::collections::HashMap;
-use std::sync::RwLock;
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Emitter};
 use tokenizers::Tokenizer;
 
+use crate::domain::persona::SamplingRange;
 use crate::domain::DEFAULT_IMAGE_MODEL_ID;
 use crate::error::AppError;
+use crate::infrastructure::offline;
+
+/// Event emitted with a [`TokenizerLoaded`] payload when a background load finishes.
+pub const TOKENIZER_LOADED_EVENT: &str = "tokenizer-loaded";
 
 /// Default tokenizer for unknown models (CLIP for Stable Diffusion compatibility)
 const DEFAULT_TOKENIZER_ID: &str = "openai/clip-vit-large-patch14";
@@ -217,6 +246,63 @@ fn get_known_mappings() -> HashMap<&'static str, TokenizerConfig> {
 /// Global tokenizer cache (`model_id` → Tokenizer)
 static TOKENIZER_CACHE: RwLock<Option<HashMap<String, Tokenizer>>> = RwLock::new(None);
 
+/// Wait before retrying a tokenizer after its first failed load
+const RETRY_DELAY_INITIAL: Duration = Duration::from_secs(30);
+/// Longest wait before retrying a tokenizer that keeps failing to load
+const RETRY_DELAY_MAX: Duration = Duration::from_secs(30 * 60);
+
+/// Consecutive load failures of a tokenizer
+#[derive(Debug, Clone, Copy)]
+struct LoadFailure {
+    /// Failed loads since the last successful one
+    failures: u32,
+    /// When the tokenizer may be loaded again
+    retry_at: Instant,
+}
+
+/// Tokenizers whose last load failed (`tokenizer_id` → failure record)
+static FAILED_LOADS: Mutex<Option<HashMap<String, LoadFailure>>> = Mutex::new(None);
+
+/// Returns how long to wait before retrying after `failures` consecutive failures.
+fn retry_delay(failures: u32) -> Duration {
+    let doublings = failures.saturating_sub(1).min(16);
+    RETRY_DELAY_INITIAL
+        .saturating_mul(1 << doublings)
+        .min(RETRY_DELAY_MAX)
+}
+
+/// Returns when a tokenizer that failed to load may be retried, or `None` if
+/// it can be loaded now.
+fn retry_at(tokenizer_id: &str) -> Option<Instant> {
+    let failed = FAILED_LOADS.lock().ok()?;
+    let retry_at = failed.as_ref()?.get(tokenizer_id)?.retry_at;
+    (Instant::now() < retry_at).then_some(retry_at)
+}
+
+/// Records a failed load, pushing back the next retry.
+fn record_load_failure(tokenizer_id: &str) {
+    if let Ok(mut failed) = FAILED_LOADS.lock() {
+        let failure = failed
+            .get_or_insert_with(HashMap::new)
+            .entry(tokenizer_id.to_string())
+            .or_insert_with(|| LoadFailure {
+                failures: 0,
+                retry_at: Instant::now(),
+            });
+        failure.failures = failure.failures.saturating_add(1);
+        failure.retry_at = Instant::now() + retry_delay(failure.failures);
+    }
+}
+
+/// Forgets the failed loads of a tokenizer that has now loaded.
+fn clear_load_failures(tokenizer_id: &str) {
+    if let Ok(mut failed) = FAILED_LOADS.lock() {
+        if let Some(failed) = failed.as_mut() {
+            failed.remove(tokenizer_id);
+        }
+    }
+}
+
 /// Get or load a tokenizer for the specified tokenizer ID
 fn get_or_load_tokenizer(tokenizer_id: &str) -> Result<Tokenizer, AppError> {
     // Check if already cached
@@ -232,10 +318,36 @@ fn get_or_load_tokenizer(tokenizer_id: &str) -> Result<Tokenizer, AppError> {
         }
     }
 
-    // Load the tokenizer
-    let tokenizer = Tokenizer::from_pretrained(tokenizer_id, None).map_err(|e| {
+    // Don't retry a recent failure before its backoff has passed
+    if let Some(retry_at) = retry_at(tokenizer_id) {
+        return Err(AppError::Internal(format!(
+            "Tokenizer '{tokenizer_id}' failed to load; retrying in {}s",
+            retry_at.saturating_duration_since(Instant::now()).as_secs()
+        )));
+    }
+
+    // Load the tokenizer, without downloading it in offline mode
+    let started = Instant::now();
+    let tokenizer = if offline::is_offline() {
+        let path = cached_tokenizer_file(tokenizer_id).ok_or_else(|| {
+            record_load_failure(tokenizer_id);
+            AppError::Offline(format!("Downloading tokenizer '{tokenizer_id}'"))
+        })?;
+        Tokenizer::from_file(path)
+    } else {
+        Tokenizer::from_pretrained(tokenizer_id, None)
+    }
+    .map_err(|e| {
+        tracing::warn!(tokenizer_id, error = %e, "Tokenizer failed to load");
+        record_load_failure(tokenizer_id);
         AppError::Internal(format!("Failed to load tokenizer '{tokenizer_id}': {e}"))
     })?;
+    clear_load_failures(tokenizer_id);
+    tracing::info!(
+        tokenizer_id,
+        elapsed_ms = started.elapsed().as_millis() as u64,
+        "Tokenizer loaded"
+    );
 
     // Cache it
     {
@@ -255,6 +367,140 @@ fn get_or_load_tokenizer(tokenizer_id: &str) -> Result<Tokenizer, AppError> {
     Ok(tokenizer)
 }
 
+/// Locates a previously downloaded `tokenizer.json` in the `HuggingFace` cache.
+///
+/// Follows the hub cache layout used by `Tokenizer::from_pretrained`:
+/// `$HF_HOME/hub` (default `~/.cache/huggingface/hub`), then
+/// `models--{org}--{name}/snapshots/{commit of refs/main}/tokenizer.json`.
+fn cached_tokenizer_file(tokenizer_id: &str) -> Option<PathBuf> {
+    let hub = match std::env::var_os("HF_HOME") {
+        Some(home) => PathBuf::from(home).join("hub"),
+        None => {
+            PathBuf::from(std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE"))?)
+                .join(".cache")
+                .join("huggingface")
+                .join("hub")
+        }
+    };
+    let repo = hub.join(format!("models--{}", tokenizer_id.replace('/', "--")));
+    let commit = std::fs::read_to_string(repo.join("refs").join("main")).ok()?;
+    let path = repo
+        .join("snapshots")
+        .join(commit.trim())
+        .join("tokenizer.json");
+
+    path.exists().then_some(path)
+}
+
+/// Tokenizer IDs currently being loaded in the background
+static PENDING_LOADS: Mutex<Option<HashSet<String>>> = Mutex::new(None);
+
+/// Payload of [`TOKENIZER_LOADED_EVENT`].
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct TokenizerLoaded {
+    /// The `HuggingFace` tokenizer ID that finished loading
+    pub tokenizer_id: String,
+    /// Whether the tokenizer loaded; if false, counts stay estimated
+    pub available: bool,
+    /// Time the load took, in milliseconds
+    pub load_ms: u64,
+}
+
+/// Returns whether a tokenizer is already loaded in memory.
+#[must_use]
+pub fn is_tokenizer_loaded(tokenizer_id: &str) -> bool {
+    TOKENIZER_CACHE.read().is_ok_and(|cache| {
+        cache
+            .as_ref()
+            .is_some_and(|cache_map| cache_map.contains_key(tokenizer_id))
+    })
+}
+
+/// Returns the IDs of the tokenizers loaded in memory, sorted.
+#[must_use]
+pub fn loaded_tokenizer_ids() -> Vec<String> {
+    let mut ids: Vec<String> = TOKENIZER_CACHE
+        .read()
+        .map(|cache| {
+            cache
+                .as_ref()
+                .map(|cache_map| cache_map.keys().cloned().collect())
+                .unwrap_or_default()
+        })
+        .unwrap_or_default();
+    ids.sort();
+    ids
+}
+
+/// Returns the IDs of the tokenizers being loaded in the background, sorted.
+#[must_use]
+pub fn loading_tokenizer_ids() -> Vec<String> {
+    let mut ids: Vec<String> = PENDING_LOADS
+        .lock()
+        .map(|pending| {
+            pending
+                .as_ref()
+                .map(|pending| pending.iter().cloned().collect())
+                .unwrap_or_default()
+        })
+        .unwrap_or_default();
+    ids.sort();
+    ids
+}
+
+/// Loads a tokenizer on a blocking worker thread and emits
+/// [`TOKENIZER_LOADED_EVENT`] when it finishes.
+///
+/// Does nothing if the tokenizer is already loaded, being loaded, or waiting
+/// to be retried after a failed load.
+pub fn load_in_background(app: &AppHandle, tokenizer_id: &str) {
+    if is_tokenizer_loaded(tokenizer_id) || retry_at(tokenizer_id).is_some() {
+        return;
+    }
+
+    {
+        let Ok(mut pending) = PENDING_LOADS.lock() else {
+            return;
+        };
+        if !pending
+            .get_or_insert_with(HashSet::new)
+            .insert(tokenizer_id.to_string())
+        {
+            return;
+        }
+    }
+
+    let app = app.clone();
+    let tokenizer_id = tokenizer_id.to_string();
+    tauri::async_runtime::spawn_blocking(move || {
+        let started = Instant::now();
+        let available = get_or_load_tokenizer(&tokenizer_id).is_ok();
+
+        if let Ok(mut pending) = PENDING_LOADS.lock() {
+            if let Some(pending) = pending.as_mut() {
+                pending.remove(&tokenizer_id);
+            }
+        }
+
+        let _ = app.emit(
+            TOKENIZER_LOADED_EVENT,
+            TokenizerLoaded {
+                tokenizer_id,
+                available,
+                load_ms: started.elapsed().as_millis() as u64,
+            },
+        );
+    });
+}
+
+/// Returns the key under which a text's token count is cached.
+///
+/// Lowercase hexadecimal SHA-256 of the trimmed text.
+#[must_use]
+pub fn text_hash(text: &str) -> String {
+    format!("{:x}", Sha256::digest(text.trim().as_bytes()))
+}
+
 /// Get the tokenizer configuration for a model
 #[must_use]
 pub fn get_config_for_model(model_id: &str) -> TokenizerConfig {
@@ -312,8 +558,12 @@ pub fn get_config_for_model(model_id: &str) -> TokenizerConfig {
     // CLIP-based models (77 tokens)
     // =========================================================================
 
-    // SDXL
-    if model_lower.contains("sdxl") || model_lower.contains("stable-diffusion-xl") {
+    // SDXL (Animagine and Pony are SDXL fine-tunes often named without "sdxl")
+    if model_lower.contains("sdxl")
+        || model_lower.contains("stable-diffusion-xl")
+        || model_lower.contains("animagine")
+        || model_lower.contains("pony")
+    {
         return TokenizerConfig {
             tokenizer_id: "openai/clip-vit-large-patch14".to_string(),
             max_tokens: 77,
@@ -369,9 +619,19 @@ pub struct TokenCount {
     pub model_id: String,
     /// The tokenizer used
     pub tokenizer_id: String,
+    /// Whether the count is a word-based estimate because the tokenizer
+    /// is not (yet) available
+    pub estimated: bool,
 }
 
 impl TokenCount {
+    /// Builds a count for a model from an exact token count.
+    #[must_use]
+    pub fn for_model(count: usize, model_id: Option<&str>) -> Self {
+        let model = model_id.unwrap_or(DEFAULT_IMAGE_MODEL_ID);
+        Self::new(count, &get_config_for_model(model), model)
+    }
+
     fn new(count: usize, config: &TokenizerConfig, model_id: &str) -> Self {
         let exceeds_limit = count > config.usable_tokens;
         let usage_percent = if config.usable_tokens > 0 {
@@ -388,6 +648,7 @@ impl TokenCount {
             usage_percent,
             model_id: model_id.to_string(),
             tokenizer_id: config.tokenizer_id.clone(),
+            estimated: false,
         }
     }
 }
@@ -415,6 +676,85 @@ pub fn count_tokens(text: &str, model_id: Option<&str>) -> TokenCount {
     }
 }
 
+/// A single token produced by the model's tokenizer
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct TokenPiece {
+    /// Vocabulary ID of the token
+    pub id: u32,
+    /// Vocabulary entry (e.g., "red</w>" for CLIP, "▁red" for T5)
+    pub token: String,
+    /// The slice of the input text this token covers
+    pub text: String,
+    /// Byte offset where the token starts in the input text
+    pub start: usize,
+    /// Byte offset where the token ends in the input text (exclusive)
+    pub end: usize,
+}
+
+/// Text split into tokens, for highlighting token boundaries
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct TokenizedText {
+    /// Tokens in order
+    pub pieces: Vec<TokenPiece>,
+    /// Count and limits for the model
+    pub count: TokenCount,
+}
+
+/// Split text into the model's actual tokens with byte offsets
+///
+/// Loads the tokenizer if needed, which can take seconds on first use.
+/// Offsets refer to `text` as given (it is not trimmed).
+///
+/// # Errors
+///
+/// Returns `AppError::Internal` if the tokenizer cannot be loaded or fails to
+/// encode the text; unlike counting, there is no estimate to fall back on.
+/// Returns `AppError::Offline` if the tokenizer would have to be downloaded
+/// while offline mode is enabled.
+pub fn tokenize(text: &str, model_id: Option<&str>) -> Result<TokenizedText, AppError> {
+    let model = model_id.unwrap_or(DEFAULT_IMAGE_MODEL_ID);
+    let config = get_config_for_model(model);
+
+    let tokenizer = get_or_load_tokenizer(&config.tokenizer_id)?;
+    let encoding = tokenizer
+        .encode(text, false)
+        .map_err(|e| AppError::Internal(format!("Failed to tokenize text: {e}")))?;
+
+    let pieces: Vec<TokenPiece> = encoding
+        .get_ids()
+        .iter()
+        .zip(encoding.get_tokens())
+        .zip(encoding.get_offsets())
+        .map(|((&id, token), &(start, end))| TokenPiece {
+            id,
+            token: token.clone(),
+            text: text.get(start..end).unwrap_or_default().to_string(),
+            start,
+            end,
+        })
+        .collect();
+
+    Ok(TokenizedText {
+        count: TokenCount::new(pieces.len(), &config, model),
+        pieces,
+    })
+}
+
+/// Estimate tokens in a text string without loading the tokenizer
+///
+/// Uses the real tokenizer only if it is already loaded; never blocks on a load.
+#[must_use]
+pub fn estimate_tokens(text: &str, model_id: Option<&str>) -> TokenCount {
+    let model = model_id.unwrap_or(DEFAULT_IMAGE_MODEL_ID);
+    let config = get_config_for_model(model);
+
+    if is_tokenizer_loaded(&config.tokenizer_id) {
+        return count_tokens(text, Some(model));
+    }
+
+    simple_token_count(text.trim(), &config, model)
+}
+
 /// Simple token counting fallback (word-based approximation)
 fn simple_token_count(text: &str, config: &TokenizerConfig, model_id: &str) -> TokenCount {
     let mut count = 0;
@@ -434,7 +774,28 @@ fn simple_token_count(text: &str, config: &TokenizerConfig, model_id: &str) -> T
             .count();
     }
 
-    TokenCount::new(count, config, model_id)
+    TokenCount {
+        estimated: true,
+        ..TokenCount::new(count, config, model_id)
+    }
+}
+
+/// A text to count, tagged with a caller-chosen label (e.g., "positive", "hair")
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct LabeledText {
+    /// Label echoed back with the count
+    pub label: String,
+    /// The text to count tokens for
+    pub text: String,
+}
+
+/// Token count for a [`LabeledText`]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct LabeledTokenCount {
+    /// Label of the counted text
+    pub label: String,
+    /// The count result
+    pub count: TokenCount,
 }
 
 /// Count tokens in multiple text strings
@@ -505,8 +866,87 @@ pub fn get_known_models() -> Vec<TokenizerInfo> {
 pub struct ImageModelPromptContext {
     /// Human-readable display name (e.g., "Stable Diffusion XL")
     pub display_name: String,
-    /// Model family identifier (sdxl, pixart, sd2, sd15, kandinsky)
+    /// Model family identifier (sdxl, pixart, sd2, sd15, kandinsky, flux)
     pub family: String,
+    /// Recommended Style-level quality tags, in prompt order
+    ///
+    /// Empty for natural-language models, where tags like "masterpiece" are
+    /// read literally and degrade the result.
+    pub quality_tokens: Vec<String>,
+    /// Whether the model uses a negative prompt at all
+    ///
+    /// False for guidance-distilled models (FLUX, Turbo, Lightning, LCM),
+    /// which sample without classifier-free guidance and ignore it.
+    pub supports_negative_prompt: bool,
+    /// Recommended CFG scale and step count
+    pub sampling: SamplingRange,
+}
+
+impl ImageModelPromptContext {
+    fn new(display_name: &str, family: &str, quality_tokens: &[&str]) -> Self {
+        Self {
+            display_name: display_name.to_string(),
+            family: family.to_string(),
+            quality_tokens: quality_tokens.iter().map(ToString::to_string).collect(),
+            supports_negative_prompt: true,
+            sampling: SamplingRange::default(),
+        }
+    }
+}
+
+/// Checks whether a model ID names an anime-specialized checkpoint
+///
+/// These checkpoints are trained on booru-style tags and respond strongly
+/// to quality tags such as "masterpiece".
+fn is_anime_checkpoint(model_lower: &str) -> bool {
+    [
+        "anime",
+        "anything-v",
+        "waifu",
+        "counterfeit",
+        "animagine",
+        "pony",
+    ]
+    .iter()
+    .any(|marker| model_lower.contains(marker))
+}
+
+/// Checks whether a model ID names a guidance-distilled model
+///
+/// These models sample without classifier-free guidance, so a negative
+/// prompt has no effect (or, forced through CFG, degrades the image).
+fn is_guidance_distilled(model_lower: &str) -> bool {
+    ["flux", "turbo", "lightning", "lcm", "hyper-sd"]
+        .iter()
+        .any(|marker| model_lower.contains(marker))
+}
+
+/// Recommended sampling settings for a lowercased model ID
+///
+/// Step-distilled models (Turbo, Lightning, LCM, Hyper-SD, FLUX schnell)
+/// converge in a few steps at CFG scale 1 or below; FLUX dev takes regular
+/// step counts but is guidance-distilled and overcooks at high CFG scales.
+fn sampling_range(model_lower: &str) -> SamplingRange {
+    let (min_cfg_scale, max_cfg_scale, min_steps, max_steps) =
+        if model_lower.contains("turbo") || model_lower.contains("schnell") {
+            (0.0, 1.0, 1, 4)
+        } else if ["lightning", "lcm", "hyper-sd"]
+            .iter()
+            .any(|marker| model_lower.contains(marker))
+        {
+            (1.0, 2.0, 2, 8)
+        } else if model_lower.contains("flux") {
+            (1.0, 4.0, 20, 50)
+        } else {
+            return SamplingRange::default();
+        };
+
+    SamplingRange {
+        min_cfg_scale,
+        max_cfg_scale,
+        min_steps,
+        max_steps,
+    }
 }
 
 /// Get prompt engineering context for an image generation model
@@ -518,8 +958,18 @@ pub fn get_prompt_context_for_model(model_id: Option<&str>) -> ImageModelPromptC
     let model = model_id.unwrap_or(DEFAULT_IMAGE_MODEL_ID);
     let model_lower = model.to_lowercase();
 
+    let mut context = family_prompt_context(&model_lower);
+    if is_guidance_distilled(&model_lower) {
+        context.supports_negative_prompt = false;
+    }
+    context.sampling = sampling_range(&model_lower);
+    context
+}
+
+/// Prompt engineering context of the model family a lowercased model ID belongs to
+fn family_prompt_context(model_lower: &str) -> ImageModelPromptContext {
     // =========================================================================
-    // T5-based models (natural language prompts)
+    // T5-based models (natural language prompts, no quality tags)
     // =========================================================================
 
     // PixArt models
@@ -529,10 +979,7 @@ pub fn get_prompt_context_for_model(model_id: Option<&str>) -> ImageModelPromptC
         } else {
             "PixArt-Alpha"
         };
-        return ImageModelPromptContext {
-            display_name: display_name.to_string(),
-            family: "pixart".to_string(),
-        };
+        return ImageModelPromptContext::new(display_name, "pixart", &[]);
     }
 
     // Hunyuan models (Tencent)
@@ -542,54 +989,56 @@ pub fn get_prompt_context_for_model(model_id: Option<&str>) -> ImageModelPromptC
         } else {
             "HunyuanDiT"
         };
-        return ImageModelPromptContext {
-            display_name: display_name.to_string(),
-            family: "hunyuan".to_string(),
-        };
+        return ImageModelPromptContext::new(display_name, "hunyuan", &[]);
     }
 
     // Kolors (Kwai)
     if model_lower.contains("kolors") {
-        return ImageModelPromptContext {
-            display_name: "Kolors".to_string(),
-            family: "kolors".to_string(),
-        };
+        return ImageModelPromptContext::new("Kolors", "kolors", &[]);
     }
 
     // DeepFloyd IF
     if model_lower.contains("deepfloyd") || model_lower.contains("if-i-") {
-        return ImageModelPromptContext {
-            display_name: "DeepFloyd IF".to_string(),
-            family: "deepfloyd".to_string(),
-        };
+        return ImageModelPromptContext::new("DeepFloyd IF", "deepfloyd", &[]);
+    }
+
+    // FLUX (Black Forest Labs)
+    if model_lower.contains("flux") {
+        return ImageModelPromptContext::new("FLUX.1", "flux", &[]);
     }
 
     // =========================================================================
     // CLIP-based models (tag-style prompts)
     // =========================================================================
 
-    // SDXL
-    if model_lower.contains("sdxl") || model_lower.contains("stable-diffusion-xl") {
-        return ImageModelPromptContext {
-            display_name: "Stable Diffusion XL".to_string(),
-            family: "sdxl".to_string(),
+    // SDXL (Animagine and Pony are SDXL fine-tunes often named without "sdxl")
+    if model_lower.contains("sdxl")
+        || model_lower.contains("stable-diffusion-xl")
+        || model_lower.contains("animagine")
+        || model_lower.contains("pony")
+    {
+        let quality_tokens: &[&str] = if model_lower.contains("pony") {
+            &["score_9", "score_8_up", "score_7_up"]
+        } else if is_anime_checkpoint(model_lower) {
+            &["masterpiece", "best quality", "very aesthetic"]
+        } else {
+            &["high quality", "detailed"]
         };
+        return ImageModelPromptContext::new("Stable Diffusion XL", "sdxl", quality_tokens);
     }
 
     // Stable Cascade / Würstchen
     if model_lower.contains("cascade") || model_lower.contains("wuerstchen") {
-        return ImageModelPromptContext {
-            display_name: "Stable Cascade".to_string(),
-            family: "cascade".to_string(),
-        };
+        return ImageModelPromptContext::new("Stable Cascade", "cascade", &["high quality"]);
     }
 
     // SD 2.x models
     if model_lower.contains("stable-diffusion-2") {
-        return ImageModelPromptContext {
-            display_name: "Stable Diffusion 2.1".to_string(),
-            family: "sd2".to_string(),
-        };
+        return ImageModelPromptContext::new(
+            "Stable Diffusion 2.1",
+            "sd2",
+            &["highly detailed", "sharp focus"],
+        );
     }
 
     // SD 1.5 and legacy models
@@ -598,10 +1047,11 @@ pub fn get_prompt_context_for_model(model_id: Option<&str>) -> ImageModelPromptC
         || model_lower.contains("stable-diffusion-1")
         || model_lower.contains("compvis")
     {
-        return ImageModelPromptContext {
-            display_name: "Stable Diffusion 1.5".to_string(),
-            family: "sd15".to_string(),
-        };
+        return ImageModelPromptContext::new(
+            "Stable Diffusion 1.5",
+            "sd15",
+            &["best quality", "highly detailed"],
+        );
     }
 
     // Kandinsky models
@@ -611,15 +1061,22 @@ pub fn get_prompt_context_for_model(model_id: Option<&str>) -> ImageModelPromptC
         } else {
             "Kandinsky 2.2"
         };
-        return ImageModelPromptContext {
-            display_name: display_name.to_string(),
-            family: "kandinsky".to_string(),
-        };
+        return ImageModelPromptContext::new(display_name, "kandinsky", &[]);
     }
 
-    // Default fallback (generic Stable Diffusion compatible)
-    ImageModelPromptContext {
-        display_name: "Stable Diffusion".to_string(),
-        family: "stable-diffusion".to_string(),
+    // Anime checkpoints are almost always SD 1.5 fine-tunes unless named otherwise
+    if is_anime_checkpoint(model_lower) {
+        return ImageModelPromptContext::new(
+            "Stable Diffusion 1.5",
+            "sd15",
+            &["masterpiece", "best quality", "highres"],
+        );
     }
+
+    // Default fallback (generic Stable Diffusion compatible)
+    ImageModelPromptContext::new(
+        "Stable Diffusion",
+        "stable-diffusion",
+        &["best quality", "highly detailed"],
+    )
 }
@@ -0,0 +1,215 @@
+//! Application Updates
+//!
+//! Stages the database around an application update, so that a new version
+//! whose migrations fail leaves the library as it was before the update.
+//!
+//! # Update Sequence
+//!
+//! 1. Once the new version is downloaded, [`prepare_update`] snapshots the
+//!    database into the `backups` directory and records the update as pending
+//!    in `update-state.json` next to the database
+//! 2. The updater installs the new version and restarts the app
+//! 3. At startup, the new version finds the pending update
+//!    ([`pending_update`]), opens the database (running its migrations), and
+//!    checks the result with [`verify_database`]
+//! 4. [`complete_update`] records the outcome; if the database could not be
+//!    migrated, it first restores the snapshot
+//!
+//! The record of the last update is kept for the `get_update_report`
+//! diagnostics command.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::Utc;
+use rusqlite::Connection;
+
+use crate::domain::update::{UpdateOutcome, UpdateReport};
+use crate::error::AppError;
+
+use super::database::connection::MIGRATION_BACKUP_DIR;
+use super::database::migrations;
+
+/// File, next to the database, recording the last update.
+pub const UPDATE_STATE_FILE: &str = "update-state.json";
+
+/// Returns the path of the update record for the database at `db_path`.
+fn state_path(db_path: &Path) -> PathBuf {
+    db_path.with_file_name(UPDATE_STATE_FILE)
+}
+
+/// Snapshots the database and records an update to `to_version` as pending.
+///
+/// Call right before installing, after the download, so that nothing written
+/// in the meantime is missing from the snapshot. The snapshot of an encrypted
+/// database is encrypted with the same passphrase.
+///
+/// # Arguments
+///
+/// * `conn` - Connection to the library database
+/// * `db_path` - File system path of the library database
+/// * `to_version` - Version about to be installed
+///
+/// # Errors
+///
+/// Returns `AppError::Io` if the backup directory or the record cannot be
+/// written, or `AppError::Database` if the snapshot fails.
+#[tracing::instrument(level = "debug", skip_all, fields(to_version = %to_version))]
+pub fn prepare_update(
+    conn: &Connection,
+    db_path: &Path,
+    to_version: &str,
+) -> Result<UpdateReport, AppError> {
+    let from_version = env!("CARGO_PKG_VERSION");
+    let dir = db_path
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join(MIGRATION_BACKUP_DIR);
+    fs::create_dir_all(&dir)?;
+
+    let started_at = Utc::now();
+    let snapshot_path = dir.join(format!(
+        "ppm-pre-update-{from_version}-{}.db",
+        started_at.format("%Y%m%dT%H%M%SZ")
+    ));
+    let _ = fs::remove_file(&snapshot_path);
+
+    // VACUUM INTO writes a consistent copy, including changes still in the WAL
+    conn.execute("VACUUM INTO ?1", [snapshot_path.to_string_lossy()])?;
+
+    let report = UpdateReport {
+        from_version: from_version.to_string(),
+        to_version: to_version.to_string(),
+        snapshot_path: snapshot_path.to_string_lossy().to_string(),
+        started_at,
+        outcome: UpdateOutcome::Pending,
+        checked_at: None,
+        error: None,
+    };
+    write_report(db_path, &report)?;
+    tracing::info!(
+        path = %snapshot_path.display(),
+        from = from_version,
+        to = to_version,
+        "Snapshotted database before update"
+    );
+
+    Ok(report)
+}
+
+/// Returns the record of the last update, if any.
+///
+/// # Errors
+///
+/// Returns `AppError::Io` if the record exists but cannot be read, or
+/// `AppError::Serialization` if it is malformed.
+pub fn read_report(db_path: &Path) -> Result<Option<UpdateReport>, AppError> {
+    match fs::read_to_string(state_path(db_path)) {
+        Ok(json) => Ok(Some(serde_json::from_str(&json)?)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Writes the record of the last update.
+fn write_report(db_path: &Path, report: &UpdateReport) -> Result<(), AppError> {
+    fs::write(state_path(db_path), serde_json::to_string_pretty(report)?)?;
+    Ok(())
+}
+
+/// Returns the pending update to this version, if the app is starting for
+/// the first time after installing it.
+///
+/// A pending update to another version (e.g., an install that was aborted)
+/// is ignored. An unreadable record is logged and ignored.
+#[must_use]
+pub fn pending_update(db_path: &Path) -> Option<UpdateReport> {
+    match read_report(db_path) {
+        Ok(report) => report.filter(|report| {
+            report.outcome == UpdateOutcome::Pending
+                && report.to_version == env!("CARGO_PKG_VERSION")
+        }),
+        Err(e) => {
+            tracing::warn!(error = %e, "Failed to read update record");
+            None
+        }
+    }
+}
+
+/// Checks that the database opened after an update is fully migrated and
+/// intact.
+///
+/// # Errors
+///
+/// Returns `AppError::Internal` if the schema version is not the one this
+/// build expects or the integrity check finds a problem, or
+/// `AppError::Database` if the checks cannot run.
+pub fn verify_database(conn: &Connection) -> Result<(), AppError> {
+    let version = migrations::read_schema_version(conn)?;
+    if version != Some(migrations::SCHEMA_VERSION) {
+        return Err(AppError::Internal(format!(
+            "Database schema is at version {}, expected {}",
+            version.map_or_else(|| "none".to_string(), |v| v.to_string()),
+            migrations::SCHEMA_VERSION
+        )));
+    }
+
+    let check: String = conn.query_row("PRAGMA quick_check", [], |row| row.get(0))?;
+    if check != "ok" {
+        return Err(AppError::Internal(format!(
+            "Database integrity check failed: {check}"
+        )));
+    }
+
+    Ok(())
+}
+
+/// Records the outcome of a pending update, restoring the snapshot if the
+/// database could not be migrated.
+///
+/// The database must be closed when `error` is given.
+///
+/// # Arguments
+///
+/// * `db_path` - File system path of the library database
+/// * `report` - The pending update
+/// * `error` - Why opening or verifying the database failed, if it did
+///
+/// # Returns
+///
+/// The updated record, `Succeeded` or `RolledBack`.
+///
+/// # Errors
+///
+/// Returns `AppError::Io` if the snapshot cannot be restored or the record
+/// cannot be written.
+#[tracing::instrument(level = "debug", skip_all, fields(to_version = %report.to_version))]
+pub fn complete_update(
+    db_path: &Path,
+    mut report: UpdateReport,
+    error: Option<&AppError>,
+) -> Result<UpdateReport, AppError> {
+    report.checked_at = Some(Utc::now());
+
+    if let Some(error) = error {
+        tracing::error!(
+            %error,
+            snapshot = %report.snapshot_path,
+            "Database check failed after update, restoring snapshot"
+        );
+
+        // Drop the WAL of the failed migration so it is not replayed onto the snapshot
+        let _ = fs::remove_file(db_path.with_extension("db-wal"));
+        let _ = fs::remove_file(db_path.with_extension("db-shm"));
+        fs::copy(&report.snapshot_path, db_path)?;
+
+        report.outcome = UpdateOutcome::RolledBack;
+        report.error = Some(error.to_string());
+    } else {
+        tracing::info!(from = %report.from_version, "Database verified after update");
+        report.outcome = UpdateOutcome::Succeeded;
+    }
+
+    write_report(db_path, &report)?;
+    Ok(report)
+}
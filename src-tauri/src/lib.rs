@@ -6,10 +6,13 @@
 //!
 //! # Architecture
 //!
-//! The application follows a clean architecture pattern with three primary layers:
+//! The application follows a clean architecture pattern with four primary layers:
 //!
 //! - **Commands Layer** ([`commands`]): Tauri IPC handlers that expose backend functionality
-//!   to the frontend. These are thin wrappers that delegate to domain and infrastructure layers.
+//!   to the frontend. These are thin wrappers that delegate to the layers below.
+//!
+//! - **Services Layer** ([`services`]): Business operations spanning several repositories
+//!   (naming, duplication, imports), reusable outside Tauri commands.
 //!
 //! - **Domain Layer** ([`domain`]): Core business logic and entity definitions. Contains
 //!   the canonical representations of personas, tokens, prompts, and AI configuration.
@@ -31,11 +34,34 @@ pub mod commands;
 pub mod domain;
 pub mod error;
 pub mod infrastructure;
+pub mod services;
 
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
 use tauri::Manager;
+use tauri_plugin_deep_link::DeepLinkExt;
+use tauri_plugin_dialog::{DialogExt, MessageDialogKind};
+
+use domain::update::UpdateOutcome;
+use error::AppError;
+
+use infrastructure::ai::rate_limit::AiRateLimiter;
+use infrastructure::ai::request_log::AiRequestLog;
+use infrastructure::database::repositories::SettingsRepository;
+use infrastructure::database::MetadataCache;
+use infrastructure::deep_link::{self, PendingPersonaImport};
+use infrastructure::event_bus::{self, EventBus};
+use infrastructure::logging::AppLogging;
+use infrastructure::{
+    keyring, locale, offline, proxy, safe_mode, scheduler, telemetry, update, Database,
+};
 
-use infrastructure::Database;
+/// Environment variable overriding the app data directory.
+pub const DATA_DIR_ENV: &str = "PPM_DATA_DIR";
+
+/// Command-line flag overriding the app data directory, as `--data-dir <path>` or
+/// `--data-dir=<path>`. Takes precedence over [`DATA_DIR_ENV`].
+pub const DATA_DIR_ARG: &str = "--data-dir";
 
 /// Thread-safe application state shared across all Tauri command invocations.
 ///
@@ -47,41 +73,170 @@ pub struct AppState {
     pub db: Mutex<Database>,
     /// Path to the database file for import/export operations.
     pub db_path: std::path::PathBuf,
+    /// Cached granularity levels, invalidated when levels change or the database is replaced.
+    pub metadata: MetadataCache,
 }
 
 /// Initializes and runs the Tauri application.
 ///
 /// This function performs the following initialization sequence:
-/// 1. Registers Tauri plugins for process control and OS detection
-/// 2. Creates the app data directory and initializes `SQLite` with WAL mode
-/// 3. Stores the database connection in Tauri's managed state
-/// 4. Registers all IPC command handlers
+/// 1. On desktop, ensures a single running instance: a second launch focuses the
+///    existing window and forwards its `ppm://` link to it, then exits. Skipped when
+///    the data directory is overridden, so isolated instances can run side by side
+/// 2. Registers Tauri plugins for process control, OS detection, and (on desktop)
+///    updates
+/// 3. Creates the app data directory (or the one given by [`DATA_DIR_ARG`] or
+///    [`DATA_DIR_ENV`]), starts file logging, initializes `SQLite`
+///    with WAL mode, and applies the stored network, offline mode, safe mode, and log
+///    level settings. On the first launch after an update, the migrated database is
+///    verified, and restored from the snapshot taken before the update if it fails
+/// 4. Stores the database connection in Tauri's managed state, and the event bus
+///    with its window-forwarding subscriber
+/// 5. Wires `ppm://` deep links to the persona import preview
+/// 6. Registers all IPC command handlers
 ///
 /// # Panics
 ///
-/// Panics if the app data directory cannot be created or the database fails to initialize.
+/// Panics if the app data directory cannot be created, or logging or the database fails to
+/// initialize. A database written by a newer version of the app, an encrypted one whose
+/// passphrase is missing or wrong, or one rolled back after a failed update is reported in
+/// an error dialog instead, and the app exits when it is dismissed.
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    tauri::Builder::default()
+    let data_dir_override = data_dir_override();
+    let builder = tauri::Builder::default();
+
+    // Registered first so a second launch exits before touching the database;
+    // with the `deep-link` feature its `ppm://` URL reaches `on_open_url` here
+    #[cfg(desktop)]
+    let builder = if data_dir_override.is_none() {
+        builder.plugin(tauri_plugin_single_instance::init(|app, _argv, _cwd| {
+            focus_main_window(app);
+        }))
+    } else {
+        builder
+    };
+
+    #[cfg(all(desktop, feature = "updater"))]
+    let builder = builder.plugin(tauri_plugin_updater::Builder::new().build());
+
+    builder
         .plugin(tauri_plugin_process::init())
         .plugin(tauri_plugin_os::init())
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_store::Builder::default().build())
-        .setup(|app| {
-            let app_data_dir = app
-                .path()
-                .app_data_dir()
-                .expect("Failed to get app data directory");
+        .plugin(tauri_plugin_deep_link::init())
+        .setup(move |app| {
+            let app_data_dir = match data_dir_override {
+                Some(dir) => dir,
+                None => app
+                    .path()
+                    .app_data_dir()
+                    .expect("Failed to get app data directory"),
+            };
 
             std::fs::create_dir_all(&app_data_dir).expect("Failed to create app data directory");
 
+            let logging = AppLogging::init(&app_data_dir).expect("Failed to initialize logging");
+            tracing::info!(
+                version = env!("CARGO_PKG_VERSION"),
+                data_dir = %app_data_dir.display(),
+                "Starting application"
+            );
+
             let db_path = app_data_dir.join("ppm.db");
-            let database = Database::new(&db_path).expect("Failed to initialize database");
+            let pending_update = update::pending_update(&db_path);
+            let opened = open_database(&db_path).and_then(|database| {
+                if pending_update.is_some() {
+                    update::verify_database(database.connection())?;
+                }
+                Ok(database)
+            });
+
+            // An encrypted or too new database was not migrated; check again next launch
+            let mut rolled_back = false;
+            if let Some(report) = pending_update {
+                if !matches!(
+                    opened,
+                    Err(AppError::SchemaTooNew { .. } | AppError::Encryption(_))
+                ) {
+                    match update::complete_update(&db_path, report, opened.as_ref().err()) {
+                        Ok(report) => rolled_back = report.outcome == UpdateOutcome::RolledBack,
+                        Err(error) => tracing::error!(%error, "Failed to complete update"),
+                    }
+                }
+            }
+
+            let database = match opened {
+                Ok(database) => database,
+                Err(error @ (AppError::SchemaTooNew { .. } | AppError::Encryption(_))) => {
+                    tracing::error!(%error, "Database cannot be opened");
+                    show_startup_error(app, error.to_string());
+                    return Ok(());
+                }
+                Err(error) if rolled_back => {
+                    show_startup_error(
+                        app,
+                        format!(
+                            "{error}\n\nThis version could not upgrade the library, so it was \
+                             restored as it was before the update. Reinstall the previous \
+                             version to keep using it."
+                        ),
+                    );
+                    return Ok(());
+                }
+                Err(error) => panic!("Failed to initialize database: {error}"),
+            };
+
+            // Apply network settings before any tokenizer download or AI client is created
+            let settings = SettingsRepository::load(database.connection()).unwrap_or_default();
+            proxy::apply_proxy_settings(&settings.proxy);
+            offline::set_offline(settings.offline);
+            safe_mode::set_safe_mode(settings.safe_mode);
+            telemetry::set_enabled(settings.telemetry);
+            scheduler::set_enabled(settings.scheduler);
+            locale::set_locale(settings.locale);
+            let _ = logging.set_level(settings.log_level);
+            app.manage(logging);
 
             app.manage(AppState {
                 db: Mutex::new(database),
                 db_path,
+                metadata: MetadataCache::default(),
+            });
+
+            // Persona and token mutations reach the windows through the event bus
+            let events = EventBus::default();
+            let handle = app.handle().clone();
+            events.subscribe("windows", move |event| {
+                event_bus::forward_to_windows(&handle, event)
+            });
+            app.manage(events);
+
+            // Per-provider throttling and opt-in debug logging shared by all AI commands
+            app.manage(AiRateLimiter::default());
+            app.manage(AiRequestLog::new(&app_data_dir));
+
+            // Scheduled tasks run in the background while the app is open
+            scheduler::start(app.handle().clone(), |app| async move {
+                commands::schedule::run_due_tasks(app).await;
+            });
+
+            // Deep links: keep the launch URL for the frontend, forward later ones live
+            app.manage(PendingPersonaImport::default());
+
+            // Ensure the scheme is registered even for unbundled (e.g. AppImage) installs
+            #[cfg(any(windows, target_os = "linux"))]
+            let _ = app.deep_link().register_all();
+
+            if let Ok(Some(urls)) = app.deep_link().get_current() {
+                deep_link::store_launch_urls(app.handle(), &urls);
+            }
+
+            let handle = app.handle().clone();
+            app.deep_link().on_open_url(move |event| {
+                deep_link::handle_urls(&handle, &event.urls());
             });
 
             Ok(())
@@ -90,42 +245,237 @@ pub fn run() {
             // Persona commands
             commands::persona::create_persona,
             commands::persona::get_persona_by_id,
+            commands::persona::open_persona,
+            commands::persona::list_recent_personas,
+            commands::persona::get_persona_full,
+            commands::persona::compare_personas,
+            commands::persona::find_similar_personas,
+            commands::persona::list_tag_tree,
             commands::persona::list_personas,
             commands::persona::update_persona,
+            commands::persona::set_base_persona,
             commands::persona::delete_persona,
+            commands::persona::bulk_update_personas,
+            commands::persona::bulk_delete_personas,
             commands::persona::get_persona_generation_params,
             commands::persona::update_generation_params,
+            commands::persona::validate_generation_params,
             commands::persona::duplicate_persona,
+            commands::persona::check_persona_name,
+            commands::persona::list_persona_templates,
+            commands::persona::create_persona_from_template,
             // Token commands
             commands::token::create_token,
             commands::token::create_tokens_batch,
+            commands::token::apply_generated_tokens,
             commands::token::get_tokens_by_persona,
+            commands::token::autocomplete_tokens,
             commands::token::update_token,
+            commands::token::get_token_history,
+            commands::token::revert_token,
+            commands::token::find_replace_tokens,
             commands::token::delete_token,
             commands::token::get_all_granularity_levels,
             commands::token::reorder_tokens,
+            commands::token::reorder_within_granularity,
+            commands::token::optimize_token_order,
+            commands::token::list_token_packs,
+            commands::token::apply_token_pack,
             // Prompt commands
             commands::prompt::compose_prompt,
+            commands::prompt::compose_prompt_preview,
+            commands::prompt::generate_prompt_matrix,
+            commands::prompt::get_cached_prompt,
+            commands::prompt::get_composition_defaults,
+            commands::prompt::set_composition_defaults,
+            commands::prompt::lint_prompt,
+            commands::prompt::get_default_regions,
             // Tokenizer commands
             commands::tokenizer::count_tokens_for_model,
+            commands::tokenizer::count_tokens_batch,
+            commands::tokenizer::tokenize_text,
             commands::tokenizer::get_known_image_models,
+            commands::tokenizer::suggest_quality_tokens,
+            commands::tokenizer::list_resolution_presets,
+            commands::tokenizer::check_resolution,
+            commands::tokenizer::warmup_tokenizers,
+            commands::tokenizer::lookup_civitai_model_by_hash,
+            commands::tokenizer::search_civitai_models,
+            commands::tokenizer::search_hf_models,
+            commands::tokenizer::scan_local_resources,
             // AI commands
             commands::ai::generate_ai_token_suggestions,
             commands::ai::generate_persona_with_ai,
+            commands::ai::create_persona_from_ai,
+            commands::ai::transfer_persona_style,
+            commands::ai::verify_image_against_persona,
+            commands::ai::blend_personas,
             commands::ai::get_ai_provider_config,
             commands::ai::get_ai_provider_metadata,
+            commands::ai::set_ai_logging_enabled,
+            commands::ai::get_recent_ai_logs,
+            commands::ai::clear_ai_logs,
             // Export/Import commands
             commands::export::export_database,
             commands::export::import_database,
+            commands::export::export_personas,
+            commands::export::get_export_schema,
+            commands::export::validate_export_json,
+            commands::export::preview_import,
+            commands::export::import_personas,
+            commands::export::export_section_snippet,
+            commands::export::import_section_snippet,
+            commands::export::encode_persona_share_code,
+            commands::export::decode_persona_share_code,
+            commands::export::take_pending_persona_import,
+            commands::export::export_persona_pdf,
+            commands::export::export_library_html,
+            // Share session commands
+            commands::share::start_share_session,
+            commands::share::stop_share_session,
+            commands::share::get_share_session,
             // Settings commands (including keyring)
             commands::settings::store_api_key,
-            commands::settings::get_api_key_for_provider,
+            commands::settings::rotate_api_key,
             commands::settings::delete_api_key,
             commands::settings::get_api_key_status,
+            commands::settings::get_env_credential_status,
             commands::settings::check_credential_store,
+            commands::settings::get_app_settings,
+            commands::settings::update_app_settings,
+            commands::settings::set_locale,
+            commands::settings::get_message_catalog,
+            commands::settings::check_connectivity,
+            commands::settings::get_database_encryption_status,
+            commands::settings::set_database_passphrase,
+            commands::settings::remove_database_passphrase,
+            commands::diagnostics::get_system_diagnostics,
+            commands::diagnostics::get_migration_history,
+            commands::diagnostics::get_update_report,
+            commands::diagnostics::repair_token_order,
+            commands::diagnostics::set_log_level,
+            commands::diagnostics::collect_logs_zip,
+            // Update commands
+            #[cfg(desktop)]
+            commands::update::check_for_update,
+            #[cfg(desktop)]
+            commands::update::install_update,
+            // Window commands
+            commands::window::open_compose_window,
+            // Smart collection commands
+            commands::collection::list_smart_collections,
+            commands::collection::create_smart_collection,
+            commands::collection::update_smart_collection,
+            commands::collection::delete_smart_collection,
+            commands::collection::query_personas,
+            // Banned term commands
+            commands::banned_term::list_banned_terms,
+            commands::banned_term::add_banned_term,
+            commands::banned_term::remove_banned_term,
+            commands::alias::list_token_aliases,
+            commands::alias::add_token_alias,
+            commands::alias::remove_token_alias,
+            // Gallery commands
+            commands::gallery::attach_generated_image,
+            commands::gallery::get_persona_gallery,
+            commands::gallery::set_image_starred,
+            commands::gallery::purge_generated_images,
+            commands::gallery::export_reproduction_bundle,
+            commands::gallery::analyze_gallery_consistency,
+            // Search commands
+            commands::search::quick_search,
+            commands::search::search_tokens,
+            // Statistics commands
+            commands::stats::get_library_stats,
+            commands::telemetry::get_usage_report,
+            commands::telemetry::clear_usage_data,
+            // Scheduled task commands
+            commands::schedule::list_scheduled_tasks,
+            commands::schedule::create_scheduled_task,
+            commands::schedule::update_scheduled_task,
+            commands::schedule::delete_scheduled_task,
+            commands::schedule::run_scheduled_task,
+            // Pending suggestion commands
+            commands::suggestion::list_pending_suggestions,
+            commands::suggestion::accept_pending_suggestions,
+            commands::suggestion::reject_pending_suggestions,
+            // Persona draft commands
+            commands::draft::save_draft,
+            commands::draft::list_drafts,
+            commands::draft::promote_draft,
+            commands::draft::delete_draft,
+            // Persona wizard commands
+            commands::wizard::start_persona_wizard,
+            commands::wizard::list_persona_wizards,
+            commands::wizard::submit_wizard_step,
+            commands::wizard::finish_wizard,
+            commands::wizard::discard_persona_wizard,
             // Configuration commands
             commands::config::get_default_image_model_id,
+            commands::config::get_api_version,
+            commands::config::api_handshake,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+/// Opens the library database, keyed with the passphrase from the OS keyring if
+/// the file is encrypted.
+///
+/// # Errors
+///
+/// Returns `AppError::Encryption` if the file is encrypted and the stored
+/// passphrase is missing or wrong, or any error from [`Database::new`].
+pub(crate) fn open_database(path: &Path) -> Result<Database, AppError> {
+    let passphrase = if Database::is_encrypted_file(path)? {
+        keyring::get_database_passphrase()?
+    } else {
+        None
+    };
+    Database::new(path, passphrase.as_deref())
+}
+
+/// Hides the main window and shows a startup error, exiting the app when it is
+/// dismissed.
+fn show_startup_error(app: &tauri::App, message: String) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.hide();
+    }
+    let handle = app.handle().clone();
+    app.dialog()
+        .message(message)
+        .title("Cannot open the library")
+        .kind(MessageDialogKind::Error)
+        .show(move |_| handle.exit(1));
+}
+
+/// Returns the app data directory requested on the command line or in the environment.
+fn data_dir_override() -> Option<PathBuf> {
+    let mut args = std::env::args_os().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == DATA_DIR_ARG {
+            return args.next().map(PathBuf::from);
+        }
+        if let Some(dir) = arg
+            .to_str()
+            .and_then(|arg| arg.strip_prefix(DATA_DIR_ARG))
+            .and_then(|rest| rest.strip_prefix('='))
+        {
+            return Some(PathBuf::from(dir));
+        }
+    }
+
+    std::env::var_os(DATA_DIR_ENV)
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Brings the main window to the front, restoring it if minimized or hidden.
+#[cfg(desktop)]
+fn focus_main_window(app: &tauri::AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
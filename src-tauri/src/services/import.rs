@@ -0,0 +1,651 @@
+//! Import Service
+//!
+//! Business rules for merging exported data into the library: JSON persona
+//! exports (with granularity level mapping, name conflict resolution, and
+//! duplicate detection) and section snippets.
+//!
+//! # Usage
+//!
+//! ```rust,ignore
+//! ImportService::check_version(&data)?;
+//! let preview = ImportService::preview(&conn, &data, &levels)?;
+//! let result = ImportService::import_personas(&conn, data, &options)?;
+//! ```
+//!
+//! The methods are generic over the repository traits (see
+//! `domain::repository`), so they run on a `SQLite` connection as well as on
+//! an `InMemoryStore`.
+
+use std::collections::HashMap;
+
+use crate::domain::alias::AliasMap;
+use crate::domain::export::{
+    find_unknown_granularities, BulkExport, ExportedToken, GranularityMappingTarget, ImportPreview,
+    PersonaExport, PersonaImportOptions, PersonaImportResult, SectionImportOptions, SectionSnippet,
+    SkippedPersona, PERSONA_EXPORT_VERSION, SECTION_SNIPPET_VERSION,
+};
+use crate::domain::naming::{self, NameSuffix};
+use crate::domain::persona::{
+    compute_content_hash, CreatePersonaRequest, Persona, UpdatePersonaRequest,
+};
+use crate::domain::repository::{AliasRepo, GranularityRepo, PersonaRepo, TokenRepo, UnitOfWork};
+use crate::domain::token::{
+    CreateTokenRequest, GeneratedTokenSelection, Granularity, GranularityLevel, Token,
+};
+use crate::error::AppError;
+
+/// Service for importing exported personas and snippets.
+///
+/// This struct contains no state; all methods take a repository reference.
+pub struct ImportService;
+
+impl ImportService {
+    /// Rejects export documents newer than this version of the application.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Validation` if the export format version is unsupported.
+    pub fn check_version(data: &BulkExport) -> Result<(), AppError> {
+        if data.version > PERSONA_EXPORT_VERSION {
+            return Err(AppError::Validation(format!(
+                "Unsupported export version {} (latest supported is {PERSONA_EXPORT_VERSION})",
+                data.version
+            )));
+        }
+        Ok(())
+    }
+
+    /// Previews a JSON persona import without changing anything.
+    ///
+    /// # Arguments
+    ///
+    /// * `repo` - Persona and token storage, e.g., a database connection
+    /// * `data` - The export document to preview
+    /// * `levels` - The library's granularity levels
+    ///
+    /// # Returns
+    ///
+    /// `ImportPreview` with counts, personas that already exist, and the
+    /// granularity levels the document uses but `levels` lacks.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Validation` if the export format version is unsupported.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub fn preview<R: PersonaRepo>(
+        repo: &R,
+        data: &BulkExport,
+        levels: &[GranularityLevel],
+    ) -> Result<ImportPreview, AppError> {
+        Self::check_version(data)?;
+
+        let mut existing_ids = Vec::new();
+        for entry in &data.personas {
+            if find_persona(repo, &entry.persona.id)?.is_some() {
+                existing_ids.push(entry.persona.id.clone());
+            }
+        }
+
+        Ok(ImportPreview {
+            persona_count: data.personas.len(),
+            token_count: data.personas.iter().map(|entry| entry.tokens.len()).sum(),
+            existing_ids,
+            unknown_granularities: find_unknown_granularities(data, levels),
+        })
+    }
+
+    /// Imports personas from a JSON export in a single unit of work.
+    ///
+    /// Each persona is created under a new ID, with "(Imported)" or
+    /// "(Imported N)" appended on name conflicts. With `skip_duplicates`,
+    /// entries whose content hash matches an existing persona are skipped;
+    /// with `update_existing`, an entry whose UUID matches an existing persona
+    /// replaces that persona's content instead. Tokens of unknown granularity
+    /// levels are moved as set in `granularity_mapping`.
+    ///
+    /// # Arguments
+    ///
+    /// * `repo` - Persona and token storage, e.g., a database connection
+    /// * `data` - The export document to import
+    /// * `options` - Import options
+    ///
+    /// # Returns
+    ///
+    /// `PersonaImportResult` listing created and updated personas and skipped entries.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Validation` if the export format version is
+    /// unsupported, or an unknown granularity level is unmapped or mapped to a
+    /// missing level. Nothing is imported then.
+    #[tracing::instrument(level = "debug", skip_all, fields(count = data.personas.len()))]
+    pub fn import_personas<R: PersonaRepo + TokenRepo + GranularityRepo + UnitOfWork>(
+        repo: &R,
+        mut data: BulkExport,
+        options: &PersonaImportOptions,
+    ) -> Result<PersonaImportResult, AppError> {
+        Self::check_version(&data)?;
+
+        repo.unit_of_work(|repo| {
+            let mut imported = Vec::new();
+            let mut updated = Vec::new();
+            let mut skipped = Vec::new();
+
+            apply_granularity_mapping(repo, &mut data, &options.granularity_mapping)?;
+
+            for entry in data.personas {
+                if options.update_existing {
+                    if let Some(existing) = find_persona(repo, &entry.persona.id)? {
+                        updated.push(update_persona_from_entry(repo, &existing, entry)?);
+                        continue;
+                    }
+                }
+
+                if options.skip_duplicates {
+                    let hash = content_hash_for_export(&entry);
+                    if let Some(existing) = repo.find_by_content_hash(&hash)? {
+                        skipped.push(SkippedPersona {
+                            name: entry.persona.name,
+                            reason: format!("Same content as existing persona '{}'", existing.name),
+                        });
+                        continue;
+                    }
+                }
+
+                imported.push(import_persona_entry(repo, entry)?);
+            }
+
+            Ok(PersonaImportResult {
+                imported,
+                updated,
+                skipped,
+            })
+        })
+    }
+
+    /// Imports a section snippet into a persona.
+    ///
+    /// The snippet's tokens are appended in the snippet's section, keeping
+    /// their relative order; tokens the section already contains, directly or
+    /// under an alias (see `domain::alias`), are skipped.
+    /// With `replace`, the section is emptied first.
+    ///
+    /// # Arguments
+    ///
+    /// * `repo` - Persona and token storage, e.g., a database connection
+    /// * `persona_id` - UUID of the persona to import into
+    /// * `snippet` - The snippet to import
+    /// * `options` - Import options
+    ///
+    /// # Returns
+    ///
+    /// The tokens created by the import.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::NotFound` if the persona does not exist.
+    /// Returns `AppError::Validation` if the snippet version or granularity is unsupported.
+    #[tracing::instrument(level = "debug", skip_all, fields(persona_id = %persona_id))]
+    pub fn import_section<R: PersonaRepo + TokenRepo + AliasRepo + UnitOfWork>(
+        repo: &R,
+        persona_id: &str,
+        snippet: SectionSnippet,
+        options: &SectionImportOptions,
+    ) -> Result<Vec<Token>, AppError> {
+        if snippet.version > SECTION_SNIPPET_VERSION {
+            return Err(AppError::Validation(format!(
+                "Unsupported snippet version {} (latest supported is {SECTION_SNIPPET_VERSION})",
+                snippet.version
+            )));
+        }
+        if Granularity::parse(&snippet.granularity_id).is_none() {
+            return Err(AppError::Validation(format!(
+                "Unknown granularity '{}'",
+                snippet.granularity_id
+            )));
+        }
+
+        repo.unit_of_work(|repo| {
+            PersonaRepo::find_by_id(repo, persona_id)?;
+
+            if options.replace {
+                repo.delete_by_granularity(persona_id, &snippet.granularity_id)?;
+            }
+
+            let mut tokens = snippet.tokens;
+            tokens.sort_by_key(|t| t.display_order);
+            let selections: Vec<_> = tokens
+                .into_iter()
+                .map(|t| GeneratedTokenSelection {
+                    granularity_id: snippet.granularity_id.clone(),
+                    polarity: t.polarity,
+                    content: t.content,
+                    weight: t.weight,
+                })
+                .collect();
+
+            let aliases = AliasMap::new(&AliasRepo::find_all(repo)?);
+            repo.create_missing(persona_id, &selections, &aliases)
+        })
+    }
+}
+
+/// Moves the tokens of unknown granularity levels to their mapped levels.
+///
+/// Levels mapped to `CreateNew` are created as custom levels first.
+fn apply_granularity_mapping<R: GranularityRepo>(
+    repo: &R,
+    data: &mut BulkExport,
+    mapping: &HashMap<String, GranularityMappingTarget>,
+) -> Result<(), AppError> {
+    let levels = GranularityRepo::find_all(repo)?;
+    let unknown = find_unknown_granularities(data, &levels);
+
+    let unmapped: Vec<&str> = unknown
+        .iter()
+        .filter(|u| !mapping.contains_key(&u.granularity_id))
+        .map(|u| u.granularity_id.as_str())
+        .collect();
+    if !unmapped.is_empty() {
+        return Err(AppError::Validation(format!(
+            "Map these unknown granularity levels before importing: {}",
+            unmapped.join(", ")
+        )));
+    }
+
+    let mut targets: HashMap<String, String> = HashMap::new();
+    for item in unknown {
+        let target = match &mapping[&item.granularity_id] {
+            GranularityMappingTarget::Existing { granularity_id } => {
+                if !levels.iter().any(|level| level.id == *granularity_id) {
+                    return Err(AppError::Validation(format!(
+                        "Unknown granularity '{granularity_id}'"
+                    )));
+                }
+                granularity_id.clone()
+            }
+            GranularityMappingTarget::CreateNew { name } => {
+                let name = name.as_deref().unwrap_or(&item.granularity_id);
+                GranularityRepo::create(repo, &item.granularity_id, name)?.id
+            }
+        };
+        targets.insert(item.granularity_id, target);
+    }
+
+    for entry in &mut data.personas {
+        for token in &mut entry.tokens {
+            if let Some(target) = targets.get(&token.granularity_id) {
+                token.granularity_id.clone_from(target);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Computes the content hash an exported persona would have once imported.
+fn content_hash_for_export(entry: &PersonaExport) -> String {
+    let tokens: Vec<Token> = entry
+        .tokens
+        .iter()
+        .map(|t| {
+            Token::new(
+                String::new(),
+                t.granularity_id.clone(),
+                t.polarity,
+                t.content.clone(),
+                t.weight,
+                t.display_order,
+            )
+        })
+        .collect();
+
+    compute_content_hash(entry.persona.description.as_deref(), &tokens)
+}
+
+/// Creates a single persona from an export entry, resolving name conflicts.
+fn import_persona_entry<R: PersonaRepo + TokenRepo>(
+    repo: &R,
+    entry: PersonaExport,
+) -> Result<Persona, AppError> {
+    let source = entry.persona;
+
+    let mut base = naming::normalize_name(&source.name);
+    if base.is_empty() {
+        base = "Untitled".to_string();
+    }
+    let name = repo
+        .available_names(naming::name_candidates(base, NameSuffix::Imported), None, 1)?
+        .pop()
+        .ok_or_else(|| AppError::Internal("No available name for the import".to_string()))?;
+
+    let created = PersonaRepo::create(
+        repo,
+        &CreatePersonaRequest {
+            name,
+            description: source.description,
+            tags: source.tags,
+            content_rating: source.content_rating,
+        },
+    )?;
+
+    let persona = PersonaRepo::update(
+        repo,
+        &created.id,
+        &UpdatePersonaRequest {
+            name: None,
+            description: None,
+            tags: None,
+            ai_provider_id: Some(source.ai_provider_id),
+            ai_model_id: Some(source.ai_model_id),
+            ai_instructions: Some(source.ai_instructions),
+            ai_output_language: Some(source.ai_output_language),
+            archived: None,
+            color: Some(source.color),
+            content_rating: Some(source.content_rating),
+            expected_updated_at: None,
+        },
+    )?;
+
+    if let Some(mut params) = entry.generation_params {
+        params.persona_id = persona.id.clone();
+        repo.update_generation_params(&params)?;
+    }
+    if let Some(defaults) = &entry.composition_defaults {
+        repo.set_composition_defaults(&persona.id, Some(defaults))?;
+    }
+
+    import_tokens(repo, &persona.id, entry.tokens)?;
+
+    Ok(persona)
+}
+
+/// Looks up a persona by ID, returning `None` if it does not exist.
+fn find_persona<R: PersonaRepo>(repo: &R, id: &str) -> Result<Option<Persona>, AppError> {
+    match PersonaRepo::find_by_id(repo, id) {
+        Ok(persona) => Ok(Some(persona)),
+        Err(AppError::NotFound(_)) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Replaces an existing persona's content with an export entry of the same ID.
+///
+/// The persona's archived state is kept. A renamed entry renames the persona
+/// only if the new name is valid and not used by another persona.
+fn update_persona_from_entry<R: PersonaRepo + TokenRepo>(
+    repo: &R,
+    existing: &Persona,
+    entry: PersonaExport,
+) -> Result<Persona, AppError> {
+    let source = entry.persona;
+
+    let name = match naming::validate_name(&source.name) {
+        Ok(name) if !repo.name_exists(&name, Some(existing.id.as_str()))? => Some(name),
+        _ => None,
+    };
+
+    let persona = PersonaRepo::update(
+        repo,
+        &existing.id,
+        &UpdatePersonaRequest {
+            name,
+            description: Some(source.description.unwrap_or_default()),
+            tags: Some(source.tags),
+            ai_provider_id: Some(source.ai_provider_id),
+            ai_model_id: Some(source.ai_model_id),
+            ai_instructions: Some(source.ai_instructions),
+            ai_output_language: Some(source.ai_output_language),
+            archived: None,
+            color: Some(source.color),
+            content_rating: Some(source.content_rating),
+            expected_updated_at: None,
+        },
+    )?;
+
+    if let Some(mut params) = entry.generation_params {
+        params.persona_id = persona.id.clone();
+        repo.update_generation_params(&params)?;
+    }
+    repo.set_composition_defaults(&persona.id, entry.composition_defaults.as_ref())?;
+
+    repo.delete_by_persona(&persona.id)?;
+    import_tokens(repo, &persona.id, entry.tokens)?;
+
+    Ok(persona)
+}
+
+/// Creates exported tokens for a persona, keeping their relative order.
+fn import_tokens<R: TokenRepo>(
+    repo: &R,
+    persona_id: &str,
+    mut tokens: Vec<ExportedToken>,
+) -> Result<(), AppError> {
+    tokens.sort_by_key(|t| t.display_order);
+    let requests: Vec<_> = tokens
+        .into_iter()
+        .map(|token| CreateTokenRequest {
+            persona_id: persona_id.to_string(),
+            granularity_id: token.granularity_id,
+            polarity: token.polarity,
+            content: token.content,
+            weight: token.weight,
+        })
+        .collect();
+    repo.create_all(&requests)?;
+    repo.normalize_token_order(persona_id)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::persona::ContentRating;
+    use crate::infrastructure::memory::InMemoryStore;
+    use crate::services::test_support::exported_token;
+
+    fn entry(name: &str, description: &str, tokens: &[&str]) -> PersonaExport {
+        PersonaExport {
+            persona: Persona::new(name.to_string(), Some(description.to_string()), Vec::new()),
+            generation_params: None,
+            composition_defaults: None,
+            tokens: tokens
+                .iter()
+                .zip(0..)
+                .map(|(content, order)| exported_token("hair", content, order))
+                .collect(),
+        }
+    }
+
+    /// Imports entries into an empty store with default options.
+    fn store_with(entries: Vec<PersonaExport>) -> (InMemoryStore, Vec<Persona>) {
+        let store = InMemoryStore::default();
+        let result = ImportService::import_personas(
+            &store,
+            BulkExport::new(entries),
+            &PersonaImportOptions::default(),
+        )
+        .unwrap();
+        (store, result.imported)
+    }
+
+    fn contents(store: &InMemoryStore, persona_id: &str) -> Vec<String> {
+        store
+            .find_by_persona(persona_id)
+            .unwrap()
+            .into_iter()
+            .map(|t| t.content)
+            .collect()
+    }
+
+    #[test]
+    fn import_names_conflicts_imported_n() {
+        let (_, imported) = store_with(vec![
+            entry("Aria", "first", &[]),
+            entry("aria", "second", &[]),
+            entry("Aria", "third", &[]),
+            entry("   ", "fourth", &[]),
+        ]);
+
+        let names: Vec<_> = imported.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(
+            names,
+            ["Aria", "aria (Imported)", "Aria (Imported 2)", "Untitled"]
+        );
+    }
+
+    #[test]
+    fn import_keeps_token_order_under_a_new_id() {
+        let source = entry("Aria", "red-haired knight", &["red hair", "braid"]);
+        let source_id = source.persona.id.clone();
+        let (store, imported) = store_with(vec![source]);
+
+        assert_ne!(imported[0].id, source_id);
+        assert_eq!(contents(&store, &imported[0].id), ["red hair", "braid"]);
+    }
+
+    #[test]
+    fn skip_duplicates_skips_entries_with_existing_content() {
+        let (store, existing) = store_with(vec![entry("Aria", "knight", &["red hair", "braid"])]);
+        let options = PersonaImportOptions {
+            skip_duplicates: true,
+            ..PersonaImportOptions::default()
+        };
+
+        // Same description and tokens under another name and order
+        let result = ImportService::import_personas(
+            &store,
+            BulkExport::new(vec![
+                entry("Bella", "knight", &["braid", "red hair"]),
+                entry("Cora", "knight", &["red hair"]),
+            ]),
+            &options,
+        )
+        .unwrap();
+
+        assert_eq!(result.skipped.len(), 1);
+        assert_eq!(result.skipped[0].name, "Bella");
+        assert!(result.skipped[0].reason.contains(&existing[0].name));
+        assert_eq!(result.imported.len(), 1);
+        assert_eq!(result.imported[0].name, "Cora");
+    }
+
+    #[test]
+    fn update_existing_replaces_the_persona_with_the_same_id() {
+        let (store, existing) = store_with(vec![
+            entry("Aria", "knight", &["red hair"]),
+            entry("Bella", "mage", &[]),
+        ]);
+        let aria = &existing[0];
+
+        let mut renamed = entry("Aria the Bold", "paladin", &["silver hair", "scar"]);
+        renamed.persona.id.clone_from(&aria.id);
+        let mut name_taken = entry("Bella", "sorceress", &[]);
+        name_taken.persona.id.clone_from(&existing[1].id);
+        name_taken.persona.content_rating = ContentRating::Mature;
+
+        let result = ImportService::import_personas(
+            &store,
+            BulkExport::new(vec![renamed, name_taken, entry("Cora", "thief", &[])]),
+            &PersonaImportOptions {
+                update_existing: true,
+                ..PersonaImportOptions::default()
+            },
+        )
+        .unwrap();
+
+        assert_eq!(result.updated.len(), 2);
+        assert_eq!(result.imported.len(), 1);
+        assert_eq!(result.updated[0].id, aria.id);
+        assert_eq!(result.updated[0].name, "Aria the Bold");
+        assert_eq!(result.updated[0].description.as_deref(), Some("paladin"));
+        assert_eq!(contents(&store, &aria.id), ["silver hair", "scar"]);
+        // Its own name is not a conflict
+        assert_eq!(result.updated[1].name, "Bella");
+        assert_eq!(result.updated[1].content_rating, ContentRating::Mature);
+        assert_eq!(PersonaRepo::find_all(&store).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn unmapped_unknown_granularity_imports_nothing() {
+        let mut source = entry("Aria", "knight", &["red hair"]);
+        source.tokens[0].granularity_id = "accessories".to_string();
+        let store = InMemoryStore::default();
+
+        let result = ImportService::import_personas(
+            &store,
+            BulkExport::new(vec![entry("Bella", "mage", &[]), source]),
+            &PersonaImportOptions::default(),
+        );
+
+        assert!(matches!(result, Err(AppError::Validation(_))));
+        assert!(PersonaRepo::find_all(&store).unwrap().is_empty());
+    }
+
+    #[test]
+    fn import_creates_mapped_levels_and_tokens() {
+        let store = InMemoryStore::default();
+        let data = BulkExport::new(vec![PersonaExport {
+            persona: Persona::new("Aria".to_string(), None, Vec::new()),
+            generation_params: None,
+            composition_defaults: None,
+            tokens: vec![
+                exported_token("accessories", "silver ring", 5),
+                exported_token("hair", "red hair", 2),
+            ],
+        }]);
+        let options = PersonaImportOptions {
+            granularity_mapping: HashMap::from([(
+                "accessories".to_string(),
+                GranularityMappingTarget::CreateNew { name: None },
+            )]),
+            ..PersonaImportOptions::default()
+        };
+
+        let result = ImportService::import_personas(&store, data, &options).unwrap();
+
+        assert_eq!(result.imported.len(), 1);
+        assert!(GranularityRepo::find_all(&store)
+            .unwrap()
+            .iter()
+            .any(|level| level.id == "accessories" && !level.is_default));
+        let tokens = TokenRepo::find_by_persona(&store, &result.imported[0].id).unwrap();
+        let order: Vec<_> = tokens
+            .iter()
+            .map(|t| (t.content.as_str(), t.display_order))
+            .collect();
+        assert_eq!(order, [("red hair", 0), ("silver ring", 1)]);
+    }
+
+    #[test]
+    fn import_section_skips_tokens_present_under_an_alias() {
+        let (store, personas) = store_with(vec![entry("Aria", "", &["Ginger  Hair"])]);
+        AliasRepo::create(&store, "ginger hair", "red hair").unwrap();
+        let snippet = SectionSnippet {
+            version: SECTION_SNIPPET_VERSION,
+            exported_at: chrono::Utc::now(),
+            source_persona: "Bella".to_string(),
+            granularity_id: "hair".to_string(),
+            tokens: vec![
+                exported_token("hair", "red hair", 0),
+                exported_token("hair", "ginger hair", 1),
+                exported_token("hair", "long hair", 2),
+            ],
+        };
+
+        let created = ImportService::import_section(
+            &store,
+            &personas[0].id,
+            snippet,
+            &SectionImportOptions::default(),
+        )
+        .unwrap();
+
+        assert_eq!(created.len(), 1);
+        assert_eq!(
+            contents(&store, &personas[0].id),
+            ["Ginger  Hair", "long hair"]
+        );
+    }
+}
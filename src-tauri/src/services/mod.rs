@@ -0,0 +1,32 @@
+//! Application Services - Business Operations
+//!
+//! This module holds the business rules of operations that span several
+//! repositories, so they can be reused by every entry point (Tauri commands
+//! today, a CLI or HTTP server later) instead of living in command handlers.
+//!
+//! # Design Principles
+//!
+//! - **Stateless**: Service structs contain no state; methods take any
+//!   implementation of the repository traits in `domain::repository`, such as
+//!   a database connection or the `InMemoryStore` fake
+//! - **Atomic**: Methods that write more than once run inside a unit of work, so
+//!   callers need not open one (but may, to compose several calls)
+//! - **No IPC**: Services know nothing about windows, dialogs, or change
+//!   events; commands lock the database, call a service, and emit events
+//!
+//! # Available Services
+//!
+//! - [`PersonaService`]: Creating, duplicating, and naming personas
+//! - [`TokenService`]: Creating and editing tokens under the banned-term rules
+//! - [`ImportService`]: Merging JSON exports and section snippets into the library
+
+pub mod import;
+pub mod persona;
+pub mod token;
+
+#[cfg(test)]
+pub(crate) mod test_support;
+
+pub use import::ImportService;
+pub use persona::PersonaService;
+pub use token::TokenService;
@@ -0,0 +1,309 @@
+//! Persona Service
+//!
+//! Business rules for creating personas: name validation and deduplication,
+//! copying settings into duplicates, and seeding personas from templates.
+//!
+//! # Usage
+//!
+//! ```rust,ignore
+//! let persona = PersonaService::create(&conn, request)?;
+//! let copy = PersonaService::duplicate(&conn, &persona.id, None)?;
+//! ```
+//!
+//! The methods are generic over the repository traits (see
+//! `domain::repository`), so they run on a `SQLite` connection as well as on
+//! an `InMemoryStore`.
+
+use crate::domain::naming::{self, NameCheck, NameSuffix};
+use crate::domain::persona::{ContentRating, CreatePersonaRequest, Persona};
+use crate::domain::repository::{PersonaRepo, TokenRepo, UnitOfWork};
+use crate::domain::template::PersonaTemplate;
+use crate::error::AppError;
+
+/// Number of alternatives returned by [`PersonaService::check_name`].
+const NAME_SUGGESTION_COUNT: usize = 3;
+
+/// Service for persona creation and naming.
+///
+/// This struct contains no state; all methods take a repository reference.
+pub struct PersonaService;
+
+impl PersonaService {
+    /// Creates a persona after validating its name.
+    ///
+    /// # Arguments
+    ///
+    /// * `repo` - Persona storage, e.g., a database connection
+    /// * `request` - Persona creation data; the name is normalized
+    ///
+    /// # Returns
+    ///
+    /// The new persona, with default generation parameters.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Validation` if the name breaks the naming rules or a
+    /// persona with the same name already exists.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub fn create<R: PersonaRepo + UnitOfWork>(
+        repo: &R,
+        mut request: CreatePersonaRequest,
+    ) -> Result<Persona, AppError> {
+        request.name = naming::validate_name(&request.name)?;
+        repo.unit_of_work(|repo| PersonaRepo::create(repo, &request))
+    }
+
+    /// Duplicates a persona's metadata, generation parameters, and composition
+    /// defaults under a free name.
+    ///
+    /// Tokens are not copied. A variant's inherited parameters are copied
+    /// resolved, so the copy does not depend on the base persona.
+    ///
+    /// # Arguments
+    ///
+    /// * `repo` - Persona storage, e.g., a database connection
+    /// * `id` - UUID of the persona to duplicate
+    /// * `new_name` - Name for the copy, used as is when free; by default the
+    ///   original name without any reserved suffix
+    ///
+    /// # Returns
+    ///
+    /// The copy, named with "(Copy)" or "(Copy N)" appended if needed.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::NotFound` if the persona does not exist, or
+    /// `AppError::Validation` if `new_name` breaks the naming rules.
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id))]
+    pub fn duplicate<R: PersonaRepo + UnitOfWork>(
+        repo: &R,
+        id: &str,
+        new_name: Option<&str>,
+    ) -> Result<Persona, AppError> {
+        let new_name = new_name.map(naming::validate_name).transpose()?;
+
+        repo.unit_of_work(|repo| {
+            let original = PersonaRepo::find_by_id(repo, id)?;
+
+            // A custom name is used as is when free; otherwise "(Copy N)" is appended
+            let (base, skip) = match new_name {
+                Some(name) => (name, 0),
+                None => (naming::strip_reserved_suffix(&original.name), 1),
+            };
+            let candidates = naming::name_candidates(base, NameSuffix::Copy).skip(skip);
+            let name = repo
+                .available_names(candidates, None, 1)?
+                .pop()
+                .ok_or_else(|| AppError::Internal("No available name for the copy".to_string()))?;
+
+            let new_persona = PersonaRepo::create(
+                repo,
+                &CreatePersonaRequest {
+                    name,
+                    description: original.description,
+                    tags: original.tags,
+                    content_rating: original.content_rating,
+                },
+            )?;
+
+            let mut params = repo.find_resolved_generation_params(id)?;
+            params.persona_id = new_persona.id.clone();
+            repo.update_generation_params(&params)?;
+            if let Some(defaults) = repo.find_composition_defaults(id)? {
+                repo.set_composition_defaults(&new_persona.id, Some(&defaults))?;
+            }
+
+            Ok(new_persona)
+        })
+    }
+
+    /// Checks a persona name and suggests available alternatives.
+    ///
+    /// # Arguments
+    ///
+    /// * `repo` - Persona storage, e.g., a database connection
+    /// * `name` - The name to check
+    /// * `exclude_id` - UUID of the persona being renamed; its current name is
+    ///   always accepted
+    ///
+    /// # Returns
+    ///
+    /// The normalized name, whether it can be used, the broken rule if any, and
+    /// up to three suggestions ("Alice (2)") when it cannot be used.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::NotFound` if `exclude_id` names no persona.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub fn check_name<R: PersonaRepo>(
+        repo: &R,
+        name: &str,
+        exclude_id: Option<&str>,
+    ) -> Result<NameCheck, AppError> {
+        let normalized = naming::normalize_name(name);
+        if let Some(id) = exclude_id {
+            let current = PersonaRepo::find_by_id(repo, id)?;
+            if naming::name_key(&normalized) == naming::name_key(&current.name) {
+                return Ok(NameCheck {
+                    normalized,
+                    available: true,
+                    error: None,
+                    suggestions: Vec::new(),
+                });
+            }
+        }
+
+        let error = match naming::validate_name(&normalized) {
+            Ok(_) => None,
+            Err(AppError::Validation(message)) => Some(message),
+            Err(e) => return Err(e),
+        };
+        let taken = repo.name_exists(&normalized, exclude_id)?;
+        let available = error.is_none() && !taken;
+
+        let base = naming::strip_reserved_suffix(&normalized);
+        let suggestions = if available || base.is_empty() {
+            Vec::new()
+        } else {
+            let candidates = naming::name_candidates(base, NameSuffix::Counter)
+                .filter(|candidate| naming::validate_name(candidate).is_ok());
+            repo.available_names(candidates, exclude_id, NAME_SUGGESTION_COUNT)?
+        };
+
+        Ok(NameCheck {
+            normalized,
+            available,
+            error,
+            suggestions,
+        })
+    }
+
+    /// Creates a persona with a template's description, tags, and tokens.
+    ///
+    /// # Arguments
+    ///
+    /// * `repo` - Persona storage, e.g., a database connection
+    /// * `template` - The template to copy
+    /// * `name` - Name of the new persona
+    ///
+    /// # Returns
+    ///
+    /// The new persona, rated General.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Validation` if the name breaks the naming rules or is
+    /// already taken.
+    #[tracing::instrument(level = "debug", skip_all, fields(template_id = %template.id))]
+    pub fn create_from_template<R: PersonaRepo + TokenRepo + UnitOfWork>(
+        repo: &R,
+        template: &PersonaTemplate,
+        name: &str,
+    ) -> Result<Persona, AppError> {
+        let name = naming::validate_name(name)?;
+
+        repo.unit_of_work(|repo| {
+            let persona = PersonaRepo::create(
+                repo,
+                &CreatePersonaRequest {
+                    name,
+                    description: Some(template.description.clone()),
+                    tags: template.tags.clone(),
+                    content_rating: ContentRating::General,
+                },
+            )?;
+            repo.create_from_selections(&persona.id, &template.tokens)?;
+
+            Ok(persona)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::memory::InMemoryStore;
+    use crate::services::test_support::persona_request;
+
+    fn create(store: &InMemoryStore, name: &str) -> Persona {
+        PersonaService::create(store, persona_request(name)).unwrap()
+    }
+
+    #[test]
+    fn duplicate_appends_the_next_free_copy_suffix() {
+        let store = InMemoryStore::default();
+        let original = create(&store, "Aria");
+
+        let first = PersonaService::duplicate(&store, &original.id, None).unwrap();
+        let second = PersonaService::duplicate(&store, &original.id, None).unwrap();
+        // Duplicating a copy starts again from the original name
+        let third = PersonaService::duplicate(&store, &first.id, None).unwrap();
+
+        assert_eq!(first.name, "Aria (Copy)");
+        assert_eq!(second.name, "Aria (Copy 2)");
+        assert_eq!(third.name, "Aria (Copy 3)");
+    }
+
+    #[test]
+    fn duplicate_uses_a_free_custom_name_as_is() {
+        let store = InMemoryStore::default();
+        let original = create(&store, "Aria");
+
+        let renamed = PersonaService::duplicate(&store, &original.id, Some("  Bella  ")).unwrap();
+        let taken = PersonaService::duplicate(&store, &original.id, Some("bella")).unwrap();
+
+        assert_eq!(renamed.name, "Bella");
+        assert_eq!(taken.name, "bella (Copy)");
+    }
+
+    #[test]
+    fn duplicate_rejects_a_reserved_custom_name() {
+        let store = InMemoryStore::default();
+        let original = create(&store, "Aria");
+
+        let result = PersonaService::duplicate(&store, &original.id, Some("Bella (Copy)"));
+
+        assert!(matches!(result, Err(AppError::Validation(_))));
+        assert_eq!(PersonaRepo::find_all(&store).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn check_name_suggests_free_names_for_a_taken_one() {
+        let store = InMemoryStore::default();
+        create(&store, "Aria");
+        create(&store, "Aria (2)");
+
+        let check = PersonaService::check_name(&store, " aria ", None).unwrap();
+
+        assert_eq!(check.normalized, "aria");
+        assert!(!check.available);
+        assert_eq!(check.error, None);
+        assert_eq!(check.suggestions, ["aria (3)", "aria (4)", "aria (5)"]);
+    }
+
+    #[test]
+    fn check_name_accepts_the_current_name_of_the_renamed_persona() {
+        let store = InMemoryStore::default();
+        let aria = create(&store, "Aria");
+
+        let check = PersonaService::check_name(&store, "ARIA", Some(&aria.id)).unwrap();
+
+        assert!(check.available);
+        assert!(check.suggestions.is_empty());
+    }
+
+    #[test]
+    fn check_name_reports_broken_rules() {
+        let store = InMemoryStore::default();
+
+        let reserved = PersonaService::check_name(&store, "Bella (Imported 2)", None).unwrap();
+        let empty = PersonaService::check_name(&store, "   ", None).unwrap();
+
+        assert!(!reserved.available);
+        assert!(reserved.error.is_some());
+        assert_eq!(reserved.suggestions, ["Bella", "Bella (2)", "Bella (3)"]);
+        assert!(!empty.available);
+        assert!(empty.error.is_some());
+        assert!(empty.suggestions.is_empty());
+    }
+}
@@ -0,0 +1,48 @@
+//! Request factories shared by the service tests.
+
+use crate::domain::export::ExportedToken;
+use crate::domain::persona::{ContentRating, CreatePersonaRequest};
+use crate::domain::token::{CreateTokenRequest, TokenPolarity, UpdateTokenRequest};
+
+/// A request for a general-rated persona without description or tags.
+pub fn persona_request(name: &str) -> CreatePersonaRequest {
+    CreatePersonaRequest {
+        name: name.to_string(),
+        description: None,
+        tags: Vec::new(),
+        content_rating: ContentRating::General,
+    }
+}
+
+/// A request for a positive Hair token weighted 1.0.
+pub fn token_request(persona_id: &str, content: &str) -> CreateTokenRequest {
+    CreateTokenRequest {
+        persona_id: persona_id.to_string(),
+        granularity_id: "hair".to_string(),
+        polarity: TokenPolarity::Positive,
+        content: content.to_string(),
+        weight: 1.0,
+    }
+}
+
+/// A request changing only a token's content.
+pub fn content_update(content: &str) -> UpdateTokenRequest {
+    UpdateTokenRequest {
+        content: Some(content.to_string()),
+        weight: None,
+        granularity_id: None,
+        polarity: None,
+        expected_updated_at: None,
+    }
+}
+
+/// A positive exported token weighted 1.0.
+pub fn exported_token(granularity_id: &str, content: &str, display_order: i32) -> ExportedToken {
+    ExportedToken {
+        granularity_id: granularity_id.to_string(),
+        polarity: TokenPolarity::Positive,
+        content: content.to_string(),
+        weight: 1.0,
+        display_order,
+    }
+}
@@ -0,0 +1,348 @@
+//! Token Service
+//!
+//! Business rules for creating and editing tokens: every new or changed token
+//! content is checked against the banned terms (see `domain::banned_term`)
+//! before anything is written.
+//!
+//! # Usage
+//!
+//! ```rust,ignore
+//! let token = TokenService::create(&conn, &request)?;
+//! let tokens = TokenService::create_batch(&conn, &batch_request)?;
+//! ```
+//!
+//! The methods are generic over the repository traits (see
+//! `domain::repository`), so they run on a `SQLite` connection as well as on
+//! an `InMemoryStore`.
+
+use crate::domain::alias::AliasMap;
+use crate::domain::banned_term;
+use crate::domain::repository::{AliasRepo, BannedTermRepo, PersonaRepo, TokenRepo, UnitOfWork};
+use crate::domain::token::{
+    BatchCreateTokenRequest, CreateTokenRequest, GeneratedTokenSelection, Token, UpdateTokenRequest,
+};
+use crate::domain::token_pack::TokenPack;
+use crate::error::AppError;
+
+/// Service for token creation and editing.
+///
+/// This struct contains no state; all methods take a repository reference.
+pub struct TokenService;
+
+impl TokenService {
+    /// Creates a token at the end of its persona's token list.
+    ///
+    /// # Arguments
+    ///
+    /// * `repo` - Token storage, e.g., a database connection
+    /// * `request` - Token creation data
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Validation` if the content contains a banned term.
+    #[tracing::instrument(level = "debug", skip_all, fields(persona_id = %request.persona_id))]
+    pub fn create<R: TokenRepo + BannedTermRepo + UnitOfWork>(
+        repo: &R,
+        request: &CreateTokenRequest,
+    ) -> Result<Token, AppError> {
+        repo.unit_of_work(|repo| {
+            banned_term::ensure_allowed(&request.content, &BannedTermRepo::find_all(repo)?)?;
+            TokenRepo::create(repo, request)
+        })
+    }
+
+    /// Creates one token per comma-separated value of a batch request.
+    ///
+    /// # Arguments
+    ///
+    /// * `repo` - Token storage, e.g., a database connection
+    /// * `request` - Batch creation data with comma-separated contents
+    ///
+    /// # Returns
+    ///
+    /// The new tokens, in creation order.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Validation` if any of the contents contains a banned
+    /// term; no token is created.
+    #[tracing::instrument(level = "debug", skip_all, fields(persona_id = %request.persona_id))]
+    pub fn create_batch<R: TokenRepo + BannedTermRepo + UnitOfWork>(
+        repo: &R,
+        request: &BatchCreateTokenRequest,
+    ) -> Result<Vec<Token>, AppError> {
+        let contents = request.parse_contents();
+
+        repo.unit_of_work(|repo| {
+            let banned_terms = BannedTermRepo::find_all(repo)?;
+            for content in &contents {
+                banned_term::ensure_allowed(content, &banned_terms)?;
+            }
+            repo.create_batch(
+                &request.persona_id,
+                &request.granularity_id,
+                request.polarity,
+                &contents,
+                request.weight,
+            )
+        })
+    }
+
+    /// Appends selected tokens (e.g., accepted AI suggestions) to a persona.
+    ///
+    /// # Arguments
+    ///
+    /// * `repo` - Token storage, e.g., a database connection
+    /// * `persona_id` - UUID of the persona receiving the tokens
+    /// * `selections` - Tokens to append, in order
+    ///
+    /// # Returns
+    ///
+    /// The new tokens, in selection order.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::NotFound` if the persona does not exist, or
+    /// `AppError::Validation` if a selection has an unknown granularity or
+    /// contains a banned term.
+    #[tracing::instrument(level = "debug", skip_all, fields(persona_id = %persona_id))]
+    pub fn append_selections<R: PersonaRepo + TokenRepo + BannedTermRepo + UnitOfWork>(
+        repo: &R,
+        persona_id: &str,
+        selections: &[GeneratedTokenSelection],
+    ) -> Result<Vec<Token>, AppError> {
+        repo.unit_of_work(|repo| {
+            PersonaRepo::find_by_id(repo, persona_id)?;
+            let banned_terms = BannedTermRepo::find_all(repo)?;
+            for selection in selections {
+                banned_term::ensure_allowed(&selection.content, &banned_terms)?;
+            }
+            repo.create_from_selections(persona_id, selections)
+        })
+    }
+
+    /// Updates a token, recording its previous content and weight.
+    ///
+    /// # Arguments
+    ///
+    /// * `repo` - Token storage, e.g., a database connection
+    /// * `id` - UUID of the token
+    /// * `request` - Fields to change
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::NotFound` if the token does not exist, or
+    /// `AppError::Validation` if the new content contains a banned term.
+    #[tracing::instrument(level = "debug", skip_all, fields(id = %id))]
+    pub fn update<R: TokenRepo + BannedTermRepo + UnitOfWork>(
+        repo: &R,
+        id: &str,
+        request: &UpdateTokenRequest,
+    ) -> Result<Token, AppError> {
+        repo.unit_of_work(|repo| {
+            if let Some(content) = &request.content {
+                banned_term::ensure_allowed(content, &BannedTermRepo::find_all(repo)?)?;
+            }
+            TokenRepo::update(repo, id, request)
+        })
+    }
+
+    /// Restores a token's content and weight from one of its revisions.
+    ///
+    /// The values replaced are recorded as a new revision.
+    ///
+    /// # Arguments
+    ///
+    /// * `repo` - Token storage, e.g., a database connection
+    /// * `token_id` - UUID of the token
+    /// * `revision` - Revision number to restore
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::NotFound` if the token or revision does not exist, or
+    /// `AppError::Validation` if the restored content contains a banned term.
+    #[tracing::instrument(level = "debug", skip_all, fields(token_id = %token_id, revision))]
+    pub fn revert<R: TokenRepo + BannedTermRepo + UnitOfWork>(
+        repo: &R,
+        token_id: &str,
+        revision: i32,
+    ) -> Result<Token, AppError> {
+        repo.unit_of_work(|repo| {
+            let revision = repo.find_revision(token_id, revision)?;
+            banned_term::ensure_allowed(&revision.content, &BannedTermRepo::find_all(repo)?)?;
+            TokenRepo::update(
+                repo,
+                token_id,
+                &UpdateTokenRequest {
+                    content: Some(revision.content),
+                    weight: Some(revision.weight),
+                    granularity_id: None,
+                    polarity: None,
+                    expected_updated_at: None,
+                },
+            )
+        })
+    }
+
+    /// Adds the pack's tokens a persona does not have yet.
+    ///
+    /// A pack token the persona has under an alias (see `domain::alias`)
+    /// counts as present.
+    ///
+    /// # Arguments
+    ///
+    /// * `repo` - Token storage, e.g., a database connection
+    /// * `persona_id` - UUID of the persona receiving the tokens
+    /// * `pack` - The pack to apply
+    ///
+    /// # Returns
+    ///
+    /// The tokens created; applying a pack twice creates none the second time.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::NotFound` if the persona does not exist.
+    #[tracing::instrument(level = "debug", skip_all, fields(persona_id = %persona_id))]
+    pub fn apply_pack<R: PersonaRepo + TokenRepo + AliasRepo + UnitOfWork>(
+        repo: &R,
+        persona_id: &str,
+        pack: &TokenPack,
+    ) -> Result<Vec<Token>, AppError> {
+        repo.unit_of_work(|repo| {
+            PersonaRepo::find_by_id(repo, persona_id)?;
+            let aliases = AliasMap::new(&AliasRepo::find_all(repo)?);
+            repo.create_missing(persona_id, &pack.tokens, &aliases)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::token::TokenPolarity;
+    use crate::infrastructure::memory::InMemoryStore;
+    use crate::services::test_support::{content_update, persona_request, token_request};
+    use crate::services::PersonaService;
+
+    /// A store with one persona and "gore" banned, and the persona's ID.
+    fn store_with_banned_term() -> (InMemoryStore, String) {
+        let store = InMemoryStore::default();
+        let persona = PersonaRepo::create(&store, &persona_request("Aria")).unwrap();
+        BannedTermRepo::create(&store, "gore").unwrap();
+        (store, persona.id)
+    }
+
+    #[test]
+    fn create_rejects_banned_terms() {
+        let (store, persona_id) = store_with_banned_term();
+
+        let allowed = TokenService::create(&store, &token_request(&persona_id, "gorgeous"));
+        let banned = TokenService::create(&store, &token_request(&persona_id, "gore scene"));
+
+        assert!(allowed.is_ok());
+        assert!(matches!(banned, Err(AppError::Validation(_))));
+        assert_eq!(store.find_by_persona(&persona_id).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn create_batch_creates_nothing_if_one_content_is_banned() {
+        let (store, persona_id) = store_with_banned_term();
+
+        let result = TokenService::create_batch(
+            &store,
+            &BatchCreateTokenRequest {
+                persona_id: persona_id.clone(),
+                granularity_id: "style".to_string(),
+                polarity: TokenPolarity::Positive,
+                contents: "watercolor, gore, soft light".to_string(),
+                weight: 1.0,
+            },
+        );
+
+        assert!(matches!(result, Err(AppError::Validation(_))));
+        assert!(store.find_by_persona(&persona_id).unwrap().is_empty());
+    }
+
+    #[test]
+    fn append_selections_rejects_banned_terms() {
+        let (store, persona_id) = store_with_banned_term();
+        let selection = |content: &str| GeneratedTokenSelection {
+            granularity_id: "style".to_string(),
+            polarity: TokenPolarity::Positive,
+            content: content.to_string(),
+            weight: 1.0,
+        };
+
+        let result = TokenService::append_selections(
+            &store,
+            &persona_id,
+            &[selection("watercolor"), selection("Gore")],
+        );
+
+        assert!(matches!(result, Err(AppError::Validation(_))));
+        assert!(store.find_by_persona(&persona_id).unwrap().is_empty());
+    }
+
+    #[test]
+    fn update_and_revert_reject_banned_terms() {
+        let (store, persona_id) = store_with_banned_term();
+        let token =
+            TokenService::create(&store, &token_request(&persona_id, "watercolor")).unwrap();
+        let banned_edit = TokenService::update(&store, &token.id, &content_update("gore"));
+        assert!(matches!(banned_edit, Err(AppError::Validation(_))));
+
+        // Ban a term after it was saved: reverting to it is refused too
+        TokenService::update(&store, &token.id, &content_update("blood moon")).unwrap();
+        TokenService::update(&store, &token.id, &content_update("soft light")).unwrap();
+        BannedTermRepo::create(&store, "blood").unwrap();
+        let banned_revert = TokenService::revert(&store, &token.id, 2);
+
+        assert!(matches!(banned_revert, Err(AppError::Validation(_))));
+        assert_eq!(
+            TokenRepo::find_by_id(&store, &token.id).unwrap().content,
+            "soft light"
+        );
+    }
+
+    #[test]
+    fn revert_restores_a_recorded_revision() {
+        let store = InMemoryStore::default();
+        let persona = PersonaService::create(&store, persona_request("Aria")).unwrap();
+        let token = TokenService::create(&store, &token_request(&persona.id, "red hair")).unwrap();
+
+        TokenService::update(&store, &token.id, &content_update("blue hair")).unwrap();
+        let reverted = TokenService::revert(&store, &token.id, 1).unwrap();
+
+        assert_eq!(reverted.content, "red hair");
+        assert_eq!(
+            store.find_revision(&token.id, 2).unwrap().content,
+            "blue hair"
+        );
+    }
+
+    #[test]
+    fn apply_pack_skips_tokens_present_under_an_alias() {
+        let store = InMemoryStore::default();
+        let persona = PersonaService::create(&store, persona_request("Aria")).unwrap();
+        TokenService::create(&store, &token_request(&persona.id, "ginger hair")).unwrap();
+        AliasRepo::create(&store, "ginger hair", "red hair").unwrap();
+        let selection = |content: &str| GeneratedTokenSelection {
+            granularity_id: "hair".to_string(),
+            polarity: TokenPolarity::Positive,
+            content: content.to_string(),
+            weight: 1.0,
+        };
+        let pack = TokenPack {
+            id: "hair_colors".to_string(),
+            name: "Hair colors".to_string(),
+            description: String::new(),
+            model_families: Vec::new(),
+            tokens: vec![selection("Red Hair"), selection("blue hair")],
+        };
+
+        let created = TokenService::apply_pack(&store, &persona.id, &pack).unwrap();
+
+        let contents: Vec<_> = created.iter().map(|t| t.content.as_str()).collect();
+        assert_eq!(contents, ["blue hair"]);
+    }
+}
@@ -4,15 +4,28 @@
 
 import { tauriInvoke } from './tauri';
 import type {
+	AiCreatedPersona,
+	AiLogEntry,
 	AiPersonaGenerationRequest,
 	AiPersonaGenerationResponse,
 	AiProvider,
 	AiProviderConfig,
 	AiProviderMetadata,
+	ImageConsistencyReport,
+	PersonaBlendDraft,
+	PersonaBlendRequest,
+	StyleTransferProposal,
+	StyleTransferRequest,
 	TokenGenerationRequest,
 	TokenGenerationResponse
 } from '$lib/types';
 
+/**
+ * Event emitted with an AiQueueStatus while a request waits for the
+ * provider's rate limiter
+ */
+export const AI_QUEUE_STATUS_EVENT = 'ai-queue-status';
+
 // ============================================================================
 // Persona Generation
 // ============================================================================
@@ -27,6 +40,71 @@ export async function generatePersonaWithAi(
 	return tauriInvoke<AiPersonaGenerationResponse>('generate_persona_with_ai', { config, request });
 }
 
+/** Generate a persona using AI and save it with its tokens in a single transaction */
+export async function createPersonaFromAi(
+	config: AiProviderConfig,
+	request: AiPersonaGenerationRequest
+): Promise<AiCreatedPersona> {
+	return tauriInvoke<AiCreatedPersona>('create_persona_from_ai', { config, request });
+}
+
+// ============================================================================
+// Style Transfer
+// ============================================================================
+//
+// Adapts one persona's tokens to another persona's visual style.
+
+/** Propose changes adapting a persona's tokens to another persona's style (nothing is saved) */
+export async function transferPersonaStyle(
+	config: AiProviderConfig,
+	request: StyleTransferRequest
+): Promise<StyleTransferProposal> {
+	return tauriInvoke<StyleTransferProposal>('transfer_persona_style', { config, request });
+}
+
+// ============================================================================
+// Image Consistency
+// ============================================================================
+//
+// Checks a generated image against the persona it was made from.
+
+/**
+ * Ask a vision model which of a persona's tokens a generated image shows
+ *
+ * @param config - Provider configuration; the model must accept images
+ * @param imagePath - Absolute path of a PNG, JPEG, WebP, or GIF image
+ * @param personaId - Persona to check against
+ * @returns Consistency score overall and per granularity, with per-token results
+ */
+export async function verifyImageAgainstPersona(
+	config: AiProviderConfig,
+	imagePath: string,
+	personaId: string
+): Promise<ImageConsistencyReport> {
+	return tauriInvoke<ImageConsistencyReport>('verify_image_against_persona', {
+		config,
+		imagePath,
+		personaId
+	});
+}
+
+// ============================================================================
+// Persona Blending
+// ============================================================================
+//
+// Combines several parent personas into a new persona draft.
+
+/**
+ * Blend personas into an unsaved draft.
+ * The config is only needed for 'ai' mode; 'interleave' mode makes no AI request.
+ */
+export async function blendPersonas(
+	request: PersonaBlendRequest,
+	config?: AiProviderConfig
+): Promise<PersonaBlendDraft> {
+	return tauriInvoke<PersonaBlendDraft>('blend_personas', { config: config ?? null, request });
+}
+
 // ============================================================================
 // Token Generation
 // ============================================================================
@@ -56,3 +134,24 @@ export async function getAiProviderConfig(provider: AiProvider): Promise<AiProvi
 export async function getAiProviderMetadata(): Promise<AiProviderMetadata[]> {
 	return tauriInvoke<AiProviderMetadata[]>('get_ai_provider_metadata');
 }
+
+// ============================================================================
+// Debug Logging
+// ============================================================================
+//
+// Opt-in recording of AI exchanges for diagnosing failed requests.
+
+/** Enable or disable the AI request/response debug log */
+export async function setAiLoggingEnabled(enabled: boolean): Promise<void> {
+	return tauriInvoke('set_ai_logging_enabled', { enabled });
+}
+
+/** Get the most recent AI debug log entries, newest first */
+export async function getRecentAiLogs(limit?: number): Promise<AiLogEntry[]> {
+	return tauriInvoke<AiLogEntry[]>('get_recent_ai_logs', { limit });
+}
+
+/** Delete all AI debug log files */
+export async function clearAiLogs(): Promise<void> {
+	return tauriInvoke('clear_ai_logs');
+}
@@ -0,0 +1,62 @@
+/**
+ * Smart collection service - Tauri IPC wrapper for saved persona queries
+ *
+ * Only collection definitions are stored; members are computed by
+ * queryPersonas each time a collection is opened.
+ */
+
+import { tauriInvoke } from './tauri';
+import type {
+	CreateSmartCollectionRequest,
+	Persona,
+	PersonaQuery,
+	ShareSession,
+	SmartCollection,
+	StartShareRequest,
+	UpdateSmartCollectionRequest
+} from '$lib/types';
+
+/** List all smart collections, ordered by name */
+export async function listSmartCollections(): Promise<SmartCollection[]> {
+	return tauriInvoke<SmartCollection[]>('list_smart_collections');
+}
+
+/** Save a new smart collection */
+export async function createSmartCollection(
+	request: CreateSmartCollectionRequest
+): Promise<SmartCollection> {
+	return tauriInvoke<SmartCollection>('create_smart_collection', { request });
+}
+
+/** Rename a smart collection or replace its query */
+export async function updateSmartCollection(
+	id: string,
+	request: UpdateSmartCollectionRequest
+): Promise<SmartCollection> {
+	return tauriInvoke<SmartCollection>('update_smart_collection', { id, request });
+}
+
+/** Delete a smart collection (personas are not affected) */
+export async function deleteSmartCollection(id: string): Promise<void> {
+	return tauriInvoke<void>('delete_smart_collection', { id });
+}
+
+/** Evaluate a persona query, e.g. a smart collection's saved query */
+export async function queryPersonas(query: PersonaQuery): Promise<Persona[]> {
+	return tauriInvoke<Persona[]>('query_personas', { query });
+}
+
+/** Serve a smart collection read-only over HTTP, replacing any running share session */
+export async function startShareSession(request: StartShareRequest): Promise<ShareSession> {
+	return tauriInvoke<ShareSession>('start_share_session', { request });
+}
+
+/** Stop the running share session; returns whether one was running */
+export async function stopShareSession(): Promise<boolean> {
+	return tauriInvoke<boolean>('stop_share_session');
+}
+
+/** The running share session, or null if none is running or it expired */
+export async function getShareSession(): Promise<ShareSession | null> {
+	return tauriInvoke<ShareSession | null>('get_share_session');
+}
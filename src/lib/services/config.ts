@@ -8,6 +8,15 @@
  */
 
 import { tauriInvoke } from './tauri';
+import type { ApiHandshake, ApiVersionInfo } from '$lib/types';
+
+/**
+ * Version of the IPC command interface this frontend was built against.
+ *
+ * Must match `API_VERSION` in src-tauri/src/domain/api_version.rs; bump both
+ * together when a command changes incompatibly.
+ */
+export const FRONTEND_API_VERSION = 1;
 
 /**
  * Retrieves the default image generation model identifier from the backend.
@@ -29,3 +38,35 @@ import { tauriInvoke } from './tauri';
 export async function getDefaultImageModelId(): Promise<string> {
 	return tauriInvoke<string>('get_default_image_model_id');
 }
+
+/**
+ * Retrieves the IPC API version served by the backend.
+ *
+ * @returns Promise resolving to the backend's API and application versions
+ */
+export async function getApiVersion(): Promise<ApiVersionInfo> {
+	return tauriInvoke<ApiVersionInfo>('get_api_version');
+}
+
+/**
+ * Checks whether this frontend can use the running backend.
+ *
+ * Call once at startup, before any other command.
+ *
+ * @returns Promise resolving to the handshake result
+ *
+ * @example
+ * ```typescript
+ * const handshake = await apiHandshake();
+ * if (!handshake.usable) {
+ *   // Block the UI and show handshake.message
+ * }
+ * ```
+ *
+ * @see src-tauri/src/domain/api_version.rs - Compatibility policy
+ */
+export async function apiHandshake(): Promise<ApiHandshake> {
+	return tauriInvoke<ApiHandshake>('api_handshake', {
+		frontendApiVersion: FRONTEND_API_VERSION
+	});
+}
@@ -0,0 +1,29 @@
+/**
+ * Draft service - Tauri IPC wrapper for autosaved persona drafts
+ *
+ * The create form saves a draft every few seconds; names are only checked
+ * when the draft is promoted to a persona.
+ */
+
+import { tauriInvoke } from './tauri';
+import type { Persona, PersonaDraft, SaveDraftRequest } from '$lib/types';
+
+/** Save the create form as a draft; pass the returned ID on later saves */
+export async function saveDraft(request: SaveDraftRequest): Promise<PersonaDraft> {
+	return tauriInvoke<PersonaDraft>('save_draft', { request });
+}
+
+/** List all drafts, most recently saved first */
+export async function listDrafts(): Promise<PersonaDraft[]> {
+	return tauriInvoke<PersonaDraft[]>('list_drafts');
+}
+
+/** Create a persona from a draft and delete the draft */
+export async function promoteDraft(id: string): Promise<Persona> {
+	return tauriInvoke<Persona>('promote_draft', { id });
+}
+
+/** Delete a draft without creating its persona */
+export async function deleteDraft(id: string): Promise<void> {
+	return tauriInvoke<void>('delete_draft', { id });
+}
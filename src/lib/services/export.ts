@@ -1,9 +1,23 @@
 /**
- * Export service - Tauri IPC wrapper for database import/export operations
+ * Export service - Tauri IPC wrapper for database and persona import/export operations
  */
 
 import { tauriInvoke } from './tauri';
-import type { ExportResult, ImportResult } from '$lib/types';
+import type {
+	BulkExport,
+	ExportResult,
+	ExportValidationError,
+	ImportPreview,
+	ImportResult,
+	PersonaExport,
+	PersonaImportOptions,
+	PersonaImportResult,
+	PersonaQuery,
+	SectionImportOptions,
+	SectionSnippet,
+	SheetTemplate,
+	Token
+} from '$lib/types';
 
 /**
  * Export the database to a user-selected location.
@@ -21,3 +35,113 @@ export async function exportDatabase(): Promise<ExportResult> {
 export async function importDatabase(): Promise<ImportResult> {
 	return tauriInvoke<ImportResult>('import_database');
 }
+
+/**
+ * Export personas as a JSON document.
+ * An empty list exports the whole library.
+ */
+export async function exportPersonas(personaIds: string[] = []): Promise<BulkExport> {
+	return tauriInvoke<BulkExport>('export_personas', { personaIds });
+}
+
+/** Get the JSON Schema of the JSON persona export format */
+export async function getExportSchema(): Promise<Record<string, unknown>> {
+	return tauriInvoke<Record<string, unknown>>('get_export_schema');
+}
+
+/** Validate a JSON persona export without importing it; empty if valid */
+export async function validateExportJson(json: string): Promise<ExportValidationError[]> {
+	return tauriInvoke<ExportValidationError[]>('validate_export_json', { json });
+}
+
+/**
+ * Preview a JSON persona import: counts, existing personas, and granularity
+ * levels that must be mapped before importing.
+ */
+export async function previewImport(data: BulkExport): Promise<ImportPreview> {
+	return tauriInvoke<ImportPreview>('preview_import', { data });
+}
+
+/**
+ * Import personas from a JSON document, merging them into the library.
+ * Name conflicts are resolved with an "(Imported)" suffix.
+ */
+export async function importPersonas(
+	data: BulkExport,
+	options?: PersonaImportOptions
+): Promise<PersonaImportResult> {
+	return tauriInvoke<PersonaImportResult>('import_personas', { data, options });
+}
+
+/** Export the tokens of one granularity section of a persona as a snippet */
+export async function exportSectionSnippet(
+	personaId: string,
+	granularityId: string
+): Promise<SectionSnippet> {
+	return tauriInvoke<SectionSnippet>('export_section_snippet', { personaId, granularityId });
+}
+
+/**
+ * Import a section snippet into a persona.
+ * Tokens already in the section are skipped; `replace` empties the section first.
+ */
+export async function importSectionSnippet(
+	personaId: string,
+	snippet: SectionSnippet,
+	options?: SectionImportOptions
+): Promise<Token[]> {
+	return tauriInvoke<Token[]>('import_section_snippet', { personaId, snippet, options });
+}
+
+/** Encode a single persona as a compact share-code (e.g., for pasting into chat) */
+export async function encodePersonaShareCode(personaId: string): Promise<string> {
+	return tauriInvoke<string>('encode_persona_share_code', { personaId });
+}
+
+/**
+ * Decode a share-code into a persona export for preview.
+ * Nothing is saved; pass the result to importPersonas to add it to the library.
+ */
+export async function decodePersonaShareCode(code: string): Promise<PersonaExport> {
+	return tauriInvoke<PersonaExport>('decode_persona_share_code', { code });
+}
+
+/** Event emitted with a `PersonaExport` when a `ppm://import?code=…` link is opened */
+export const PERSONA_IMPORT_REQUESTED_EVENT = 'persona-import-requested';
+
+/** Event emitted with an error message when a deep link cannot be handled */
+export const DEEP_LINK_ERROR_EVENT = 'deep-link-error';
+
+/**
+ * Take the persona import requested by the link that launched the app, if any.
+ * Links opened while the app is running arrive via PERSONA_IMPORT_REQUESTED_EVENT.
+ */
+export async function takePendingPersonaImport(): Promise<PersonaExport | null> {
+	return tauriInvoke<PersonaExport | null>('take_pending_persona_import');
+}
+
+/**
+ * Export a persona as a printable PDF character sheet.
+ * Opens a native save dialog.
+ */
+export async function exportPersonaPdf(
+	personaId: string,
+	template?: SheetTemplate
+): Promise<ExportResult> {
+	return tauriInvoke<ExportResult>('export_persona_pdf', {
+		personaId,
+		template: template ?? null
+	});
+}
+
+/**
+ * Export personas as a static HTML site with client-side search.
+ * The folder must be empty or hold an earlier site export, which is replaced.
+ * Without a filter, all unarchived personas are exported.
+ */
+export async function exportLibraryHtml(
+	path: string,
+	filter?: PersonaQuery
+): Promise<ExportResult> {
+	return tauriInvoke<ExportResult>('export_library_html', { path, filter: filter ?? null });
+}
@@ -0,0 +1,68 @@
+/**
+ * Result gallery service - Tauri IPC wrapper for images generated from personas
+ *
+ * Only gallery entries are managed; image files are never modified or deleted.
+ */
+
+import { tauriInvoke } from './tauri';
+import type {
+	AttachImageRequest,
+	GalleryConsistencyReport,
+	GeneratedImage,
+	ReproductionBundle
+} from '$lib/types';
+
+/** Add an existing image file to a persona's gallery */
+export async function attachGeneratedImage(request: AttachImageRequest): Promise<GeneratedImage> {
+	return tauriInvoke<GeneratedImage>('attach_generated_image', { request });
+}
+
+/** List a persona's gallery, newest first */
+export async function getPersonaGallery(
+	personaId: string,
+	starredOnly = false
+): Promise<GeneratedImage[]> {
+	return tauriInvoke<GeneratedImage[]>('get_persona_gallery', { personaId, starredOnly });
+}
+
+/** Mark a gallery image as a favorite, or unmark it */
+export async function setImageStarred(id: string, starred: boolean): Promise<GeneratedImage> {
+	return tauriInvoke<GeneratedImage>('set_image_starred', { id, starred });
+}
+
+/**
+ * Remove gallery entries older than a number of days, across all personas
+ *
+ * @returns The number of entries removed
+ */
+export async function purgeGeneratedImages(
+	olderThanDays: number,
+	includeStarred = false
+): Promise<number> {
+	return tauriInvoke<number>('purge_generated_images', { olderThanDays, includeStarred });
+}
+
+/** Export the prompts, seed, parameters, and persona snapshot behind a gallery image */
+export async function exportReproductionBundle(imageId: string): Promise<ReproductionBundle> {
+	return tauriInvoke<ReproductionBundle>('export_reproduction_bundle', { imageId });
+}
+
+/**
+ * Group a persona's gallery images by face to detect visual drift
+ *
+ * New images are embedded with the face embedder set in the app settings.
+ *
+ * @param threshold - Similarity at which faces are grouped together (default: 0.5)
+ * @param recompute - Embed every image again, e.g. after changing the embedder
+ */
+export async function analyzeGalleryConsistency(
+	personaId: string,
+	threshold?: number,
+	recompute = false
+): Promise<GalleryConsistencyReport> {
+	return tauriInvoke<GalleryConsistencyReport>('analyze_gallery_consistency', {
+		personaId,
+		threshold: threshold ?? null,
+		recompute
+	});
+}
@@ -2,12 +2,27 @@
  * Persona service - Tauri IPC wrapper for persona operations
  */
 
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
+import { getCurrentWindow } from '@tauri-apps/api/window';
 import { tauriInvoke } from './tauri';
 import type {
+	ActivityKind,
+	BulkPersonaPatch,
+	CompositionOptions,
 	Persona,
+	PersonaChanged,
+	PersonaFull,
+	PersonaComparison,
 	CreatePersonaRequest,
 	UpdatePersonaRequest,
-	GenerationParams
+	GenerationParams,
+	ParamWarning,
+	NameCheck,
+	PersonaTemplate,
+	TagNode,
+	RecentPersona,
+	LibraryStats,
+	SimilarPersona
 } from '$lib/types';
 
 /** Create a new persona */
@@ -20,6 +35,53 @@ export async function getPersona(id: string): Promise<Persona> {
 	return tauriInvoke<Persona>('get_persona_by_id', { id });
 }
 
+/** Get a persona by ID and record it as recently opened */
+export async function openPersona(id: string): Promise<Persona> {
+	return tauriInvoke<Persona>('open_persona', { id });
+}
+
+/** List personas most recently opened, modified, or composed (newest first) */
+export async function listRecentPersonas(
+	kind: ActivityKind,
+	limit?: number
+): Promise<RecentPersona[]> {
+	return tauriInvoke<RecentPersona[]>('list_recent_personas', { kind, limit });
+}
+
+/**
+ * Get a persona with its parameters, tokens, and composed prompt in one call
+ *
+ * Powers the read-only presentation view; nothing is saved.
+ *
+ * @param options - Composition settings; defaults to all levels with weights
+ */
+export async function getPersonaFull(
+	id: string,
+	options?: CompositionOptions
+): Promise<PersonaFull> {
+	return tauriInvoke<PersonaFull>('get_persona_full', { personaId: id, options: options ?? null });
+}
+
+/** Compare two personas: differing fields, tag overlap, and token overlap */
+export async function comparePersonas(a: string, b: string): Promise<PersonaComparison> {
+	return tauriInvoke<PersonaComparison>('compare_personas', { a, b });
+}
+
+/** Rank other personas by shared tokens and tags to find near-duplicates (default limit 10) */
+export async function findSimilarPersonas(
+	personaId: string,
+	limit?: number
+): Promise<SimilarPersona[]> {
+	return tauriInvoke<SimilarPersona[]>('find_similar_personas', { personaId, limit });
+}
+
+/** List persona tags as a tree of "/"-separated namespaces */
+export async function listTagTree(includeArchived?: boolean): Promise<TagNode[]> {
+	return tauriInvoke<TagNode[]>('list_tag_tree', {
+		includeArchived: includeArchived ?? null
+	});
+}
+
 /** Get all personas */
 export async function listPersonas(): Promise<Persona[]> {
 	return tauriInvoke<Persona[]>('list_personas');
@@ -30,11 +92,43 @@ export async function updatePersona(id: string, request: UpdatePersonaRequest):
 	return tauriInvoke<Persona>('update_persona', { id, request });
 }
 
+/**
+ * Link a variant to a base persona, or unlink it with null
+ *
+ * The variant inherits the base's generation parameters (except those it overrides) and the
+ * tokens of the given levels (except levels it has tokens of). Unlinking copies what was
+ * inherited onto the variant.
+ */
+export async function setBasePersona(
+	personaId: string,
+	basePersonaId: string | null,
+	inheritedGranularities: string[] = []
+): Promise<Persona> {
+	return tauriInvoke<Persona>('set_base_persona', {
+		personaId,
+		basePersonaId,
+		inheritedGranularities
+	});
+}
+
 /** Delete a persona */
 export async function deletePersona(id: string): Promise<void> {
 	return tauriInvoke<void>('delete_persona', { id });
 }
 
+/** Apply the same tag, AI provider, and archive changes to several personas atomically */
+export async function bulkUpdatePersonas(
+	ids: string[],
+	patch: BulkPersonaPatch
+): Promise<Persona[]> {
+	return tauriInvoke<Persona[]>('bulk_update_personas', { ids, patch });
+}
+
+/** Delete several personas atomically; returns the number deleted */
+export async function bulkDeletePersonas(ids: string[]): Promise<number> {
+	return tauriInvoke<number>('bulk_delete_personas', { ids });
+}
+
 /** Get generation parameters for a persona */
 export async function getGenerationParams(personaId: string): Promise<GenerationParams> {
 	return tauriInvoke<GenerationParams>('get_persona_generation_params', { personaId });
@@ -45,7 +139,56 @@ export async function updateGenerationParams(params: GenerationParams): Promise<
 	return tauriInvoke<void>('update_generation_params', { params });
 }
 
+/** Check generation parameters against their model (CFG scale, steps, resolution) */
+export async function validateGenerationParams(
+	params: GenerationParams
+): Promise<ParamWarning[]> {
+	return tauriInvoke<ParamWarning[]>('validate_generation_params', { params });
+}
+
 /** Duplicate a persona */
 export async function duplicatePersona(id: string, newName?: string): Promise<Persona> {
 	return tauriInvoke<Persona>('duplicate_persona', { id, newName });
 }
+
+/**
+ * Check a persona name and get available alternatives.
+ * Names are compared ignoring case and accents; pass excludeId when renaming.
+ */
+export async function checkPersonaName(name: string, excludeId?: string): Promise<NameCheck> {
+	return tauriInvoke<NameCheck>('check_persona_name', { name, excludeId });
+}
+
+/** List the built-in persona archetype templates */
+export async function listPersonaTemplates(): Promise<PersonaTemplate[]> {
+	return tauriInvoke<PersonaTemplate[]>('list_persona_templates');
+}
+
+/** Create a persona with the description, tags, and tokens of a built-in template */
+export async function createPersonaFromTemplate(templateId: string, name: string): Promise<Persona> {
+	return tauriInvoke<Persona>('create_persona_from_template', { templateId, name });
+}
+
+/** Get library-wide counts for the dashboard (mature personas excluded in safe mode) */
+export async function getLibraryStats(): Promise<LibraryStats> {
+	return tauriInvoke<LibraryStats>('get_library_stats');
+}
+
+/** Open the compose window for a persona, or focus it if already open */
+export async function openComposeWindow(personaId: string): Promise<void> {
+	return tauriInvoke<void>('open_compose_window', { personaId });
+}
+
+/**
+ * Subscribe to personas being created, updated, or deleted in other windows
+ *
+ * @returns Function that removes the listener
+ */
+export async function onPersonaChanged(
+	callback: (payload: PersonaChanged) => void
+): Promise<UnlistenFn> {
+	const label = getCurrentWindow().label;
+	return listen<PersonaChanged>('persona-changed', (event) => {
+		if (event.payload.source_window !== label) callback(event.payload);
+	});
+}
@@ -3,9 +3,19 @@
  */
 
 import { tauriInvoke } from './tauri';
-import type { ComposedPrompt, CompositionOptions } from '$lib/types';
+import type {
+	CachedPrompt,
+	ComposedPrompt,
+	CompositionDefaults,
+	CompositionOptions,
+	LintIssue,
+	MatrixAxis,
+	PromptMatrix,
+	PromptPreview,
+	RegionalOptions
+} from '$lib/types';
 
-/** Compose a prompt from a persona's tokens */
+/** Compose a prompt from a persona's tokens (the persona's defaults apply without options) */
 export async function composePrompt(
 	personaId: string,
 	options?: CompositionOptions
@@ -13,6 +23,57 @@ export async function composePrompt(
 	return tauriInvoke<ComposedPrompt>('compose_prompt', { personaId, options });
 }
 
+/** Compose a prompt and render it in every copy format (plain, A1111, ComfyUI, JSON) */
+export async function composePromptPreview(
+	personaId: string,
+	options?: CompositionOptions
+): Promise<PromptPreview> {
+	return tauriInvoke<PromptPreview>('compose_prompt_preview', { personaId, options });
+}
+
+/** Compose one prompt per combination of alternative tokens (at most 1000) */
+export async function generatePromptMatrix(
+	personaId: string,
+	axes: MatrixAxis[],
+	options?: CompositionOptions
+): Promise<PromptMatrix> {
+	return tauriInvoke<PromptMatrix>('generate_prompt_matrix', { personaId, axes, options });
+}
+
+/** Get the default face/body/background layout for regional composition */
+export async function getDefaultRegions(): Promise<RegionalOptions> {
+	return tauriInvoke<RegionalOptions>('get_default_regions');
+}
+
+/** Get a persona's cached prompt (composed with the persona's defaults on a cache miss) */
+export async function getCachedPrompt(personaId: string): Promise<CachedPrompt> {
+	return tauriInvoke<CachedPrompt>('get_cached_prompt', { personaId });
+}
+
+/** Get a persona's composition defaults (null if it uses the global defaults) */
+export async function getCompositionDefaults(
+	personaId: string
+): Promise<CompositionDefaults | null> {
+	return tauriInvoke<CompositionDefaults | null>('get_composition_defaults', { personaId });
+}
+
+/** Set a persona's composition defaults, or pass null to use the global defaults */
+export async function setCompositionDefaults(
+	personaId: string,
+	defaults: CompositionDefaults | null
+): Promise<void> {
+	return tauriInvoke<void>('set_composition_defaults', { personaId, defaults });
+}
+
+/** Check a prompt pair for common quality issues (no AI required) */
+export async function lintPrompt(
+	positivePrompt: string,
+	negativePrompt?: string,
+	modelId?: string
+): Promise<LintIssue[]> {
+	return tauriInvoke<LintIssue[]>('lint_prompt', { positivePrompt, negativePrompt, modelId });
+}
+
 /** Copy text to clipboard */
 export async function copyToClipboard(text: string): Promise<void> {
 	try {
@@ -0,0 +1,43 @@
+/**
+ * Schedule service - Tauri IPC wrapper for scheduled tasks
+ *
+ * Tasks only run on schedule while the scheduler is enabled in the app settings.
+ * Their suggestions land in the pending suggestions inbox (see ./suggestion).
+ */
+
+import { tauriInvoke } from './tauri';
+import type {
+	CreateScheduledTaskRequest,
+	ScheduledTask,
+	UpdateScheduledTaskRequest
+} from '$lib/types';
+
+/** List all scheduled tasks, oldest first */
+export async function listScheduledTasks(): Promise<ScheduledTask[]> {
+	return tauriInvoke<ScheduledTask[]>('list_scheduled_tasks');
+}
+
+/** Create a scheduled task, first due one interval from now */
+export async function createScheduledTask(
+	request: CreateScheduledTaskRequest
+): Promise<ScheduledTask> {
+	return tauriInvoke<ScheduledTask>('create_scheduled_task', { request });
+}
+
+/** Change a scheduled task's settings or turn its schedule on or off */
+export async function updateScheduledTask(
+	id: string,
+	request: UpdateScheduledTaskRequest
+): Promise<ScheduledTask> {
+	return tauriInvoke<ScheduledTask>('update_scheduled_task', { id, request });
+}
+
+/** Delete a scheduled task; the suggestions it staged stay pending */
+export async function deleteScheduledTask(id: string): Promise<void> {
+	return tauriInvoke<void>('delete_scheduled_task', { id });
+}
+
+/** Run a scheduled task now, whether or not it is due or enabled */
+export async function runScheduledTask(id: string): Promise<ScheduledTask> {
+	return tauriInvoke<ScheduledTask>('run_scheduled_task', { id });
+}
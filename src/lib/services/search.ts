@@ -0,0 +1,36 @@
+/**
+ * Search service - Tauri IPC wrapper for the quick switcher and token search
+ *
+ * Quick search covers personas, tokens, smart collections, and persona templates
+ * at once. Every word must prefix-match; case and accents are ignored.
+ */
+
+import { tauriInvoke } from './tauri';
+import type { SearchResult, TokenPolarity, TokenSearchMatch } from '$lib/types';
+
+/** Search the library and templates (best matches first, default limit 20) */
+export async function quickSearch(query: string, limit?: number): Promise<SearchResult[]> {
+	return tauriInvoke<SearchResult[]>('quick_search', { query, limit });
+}
+
+/** Filters for searchTokens; unset filters match everything */
+export interface TokenSearchFilters {
+	/** Only search these personas */
+	personaIds?: string[];
+	/** Only match tokens of this granularity level ID */
+	granularity?: string;
+	/** Only match tokens of this polarity */
+	polarity?: TokenPolarity;
+}
+
+/**
+ * Find tokens whose content contains the query (ignoring case and accents), or
+ * matches it as a regular expression; ordered by persona name
+ */
+export async function searchTokens(
+	query: string,
+	regex: boolean,
+	filters: TokenSearchFilters = {}
+): Promise<TokenSearchMatch[]> {
+	return tauriInvoke<TokenSearchMatch[]>('search_tokens', { query, regex, ...filters });
+}
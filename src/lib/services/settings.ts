@@ -1,16 +1,115 @@
 /**
- * Settings service - Tauri IPC wrapper for API key management
+ * Settings service - Tauri IPC wrapper for API key management and app settings
  *
- * Provides secure API key storage and retrieval via OS keyring.
+ * Provides secure API key storage via OS keyring. Keys are write-only from the
+ * frontend's perspective: the backend reads them when making AI requests, so
+ * plaintext keys never travel back over IPC.
+ *
+ * Also manages backend settings such as the network proxy used for tokenizer
+ * downloads and AI requests, and offline mode, which disables both, and the
+ * optional passphrase encryption of the library database.
  */
 
 import { tauriInvoke } from './tauri';
-import type { AiProvider } from '$lib/types';
+import type { AiProvider, ExportResult } from '$lib/types';
 
 /** Status of an API key for a provider */
 export interface ApiKeyStatus {
 	provider: AiProvider;
 	has_key: boolean;
+	/** ISO timestamp of when the key was stored, if recorded */
+	stored_at: string | null;
+}
+
+/** Environment variable status for a provider (credential passthrough mode) */
+export interface EnvCredentialStatus {
+	provider: AiProvider;
+	/** Environment variable read for this provider (null if no key is needed) */
+	env_var: string | null;
+	found: boolean;
+}
+
+/** Explicit proxy configuration */
+export interface ProxySettings {
+	/** Whether the explicit proxy overrides the environment */
+	enabled: boolean;
+	/** Proxy URL for plain HTTP requests (e.g., http://proxy.corp:8080) */
+	http_proxy: string | null;
+	/** Proxy URL for HTTPS requests */
+	https_proxy: string | null;
+	/** Comma-separated hosts that bypass the proxy */
+	no_proxy: string | null;
+}
+
+/** Folders of image generator add-ons scanned for prompt triggers (absolute paths) */
+export interface ResourceFolders {
+	/** Folder of textual inversion embeddings (e.g., A1111's embeddings) */
+	embeddings: string | null;
+	/** Folder of LoRA models (e.g., A1111's models/Lora) */
+	loras: string | null;
+}
+
+/** Settings stored by the backend */
+export interface AppSettings {
+	proxy: ProxySettings;
+	/** Blocks tokenizer downloads and remote AI requests (Ollama still works) */
+	offline: boolean;
+	/** Minimum level of events written to the log files */
+	log_level: LogLevel;
+	/** Hides mature-rated personas from lists, search, and composition */
+	safe_mode: boolean;
+	/** Counts feature usage locally; turning it off deletes the counters */
+	telemetry: boolean;
+	/** Language of error messages and other backend display text */
+	locale: Locale;
+	/** Language of AI-written descriptions and rationales (e.g., 'German'); null means English */
+	ai_output_language: string | null;
+	/** Folders scanned by scanLocalResources */
+	resource_folders: ResourceFolders;
+	/** Absolute paths of tag dictionaries (e.g., A1111 tag autocomplete CSVs) used by autocompleteTokens */
+	tag_dictionaries: string[];
+	/**
+	 * Absolute path of a local program printing an image's face embedding as a JSON array
+	 * (or null without a face); used by analyzeGalleryConsistency
+	 */
+	face_embedder: string | null;
+	/** Runs enabled scheduled tasks in the background while the app is open */
+	scheduler: boolean;
+}
+
+/** Language of backend display text */
+export type Locale = 'en' | 'de' | 'fr' | 'es';
+
+/** All messages of a locale, keyed by stable code */
+export interface MessageCatalog {
+	locale: Locale;
+	/** Templates with {name} placeholders, English where the locale has none */
+	messages: Record<string, string>;
+}
+
+/** Verbosity of the application log */
+export type LogLevel = 'error' | 'warn' | 'info' | 'debug' | 'trace';
+
+/** Where the proxy in effect comes from */
+export type ProxySource = 'settings' | 'environment' | 'none';
+
+/** Result of checking that one endpoint can be reached */
+export interface ConnectivityCheck {
+	name: string;
+	/** Host and port that was checked */
+	target: string;
+	/** Proxy the connection went through, if any */
+	proxy: string | null;
+	reachable: boolean;
+	elapsed_ms: number;
+	/** Failure reason, if unreachable */
+	error: string | null;
+}
+
+/** Outcome of the connectivity diagnostic */
+export interface ConnectivityReport {
+	proxy_source: ProxySource;
+	checks: ConnectivityCheck[];
 }
 
 /**
@@ -24,13 +123,15 @@ export async function storeApiKey(provider: AiProvider, apiKey: string): Promise
 }
 
 /**
- * Get an API key from the OS keyring
+ * Replace an API key after verifying it with a test request to the provider
+ * The stored key is left untouched if verification fails.
  *
  * @param provider - The AI provider ID
- * @returns The API key or null if not set
+ * @param newKey - The replacement API key
+ * @returns The updated key status, including the new storage timestamp
  */
-export async function getApiKey(provider: AiProvider): Promise<string | null> {
-	return tauriInvoke<string | null>('get_api_key_for_provider', { provider });
+export async function rotateApiKey(provider: AiProvider, newKey: string): Promise<ApiKeyStatus> {
+	return tauriInvoke<ApiKeyStatus>('rotate_api_key', { provider, newKey });
 }
 
 /**
@@ -51,6 +152,16 @@ export async function getApiKeyStatus(): Promise<ApiKeyStatus[]> {
 	return tauriInvoke<ApiKeyStatus[]>('get_api_key_status');
 }
 
+/**
+ * Get which provider API key environment variables are set
+ * Values are never returned, only whether each variable was found.
+ *
+ * @returns Array of environment credential statuses for all providers
+ */
+export async function getEnvCredentialStatus(): Promise<EnvCredentialStatus[]> {
+	return tauriInvoke<EnvCredentialStatus[]>('get_env_credential_status');
+}
+
 /**
  * Check if the system credential store is available
  * On Linux, returns false if no Secret Service daemon is running
@@ -60,3 +171,327 @@ export async function getApiKeyStatus(): Promise<ApiKeyStatus[]> {
 export async function checkCredentialStore(): Promise<boolean> {
 	return tauriInvoke<boolean>('check_credential_store');
 }
+
+/**
+ * Get the backend application settings
+ *
+ * @returns The stored settings, or defaults if none were saved
+ */
+export async function getAppSettings(): Promise<AppSettings> {
+	return tauriInvoke<AppSettings>('get_app_settings');
+}
+
+/**
+ * Save the backend application settings
+ * Proxy and offline mode changes apply to new AI requests and tokenizer downloads immediately.
+ *
+ * @param settings - The complete settings to store
+ * @returns The saved settings
+ */
+export async function updateAppSettings(settings: AppSettings): Promise<AppSettings> {
+	return tauriInvoke<AppSettings>('update_app_settings', { settings });
+}
+
+/**
+ * Change the language of backend display text; the locale is kept for future launches
+ *
+ * @param locale - The new display locale
+ * @returns The message catalog of the new locale
+ */
+export async function setLocale(locale: Locale): Promise<MessageCatalog> {
+	return tauriInvoke<MessageCatalog>('set_locale', { locale });
+}
+
+/**
+ * Get the message catalog of a locale
+ * Error codes map to `error.<code>`; built-in granularity names and parameter
+ * warnings have their own codes.
+ *
+ * @param locale - Locale to return (default: the current display locale)
+ */
+export async function getMessageCatalog(locale?: Locale): Promise<MessageCatalog> {
+	return tauriInvoke<MessageCatalog>('get_message_catalog', { locale });
+}
+
+/**
+ * Check that HuggingFace and each AI provider API can be reached
+ * Uses the proxy currently in effect.
+ *
+ * @returns Proxy source and one check per endpoint
+ */
+export async function checkConnectivity(): Promise<ConnectivityReport> {
+	return tauriInvoke<ConnectivityReport>('check_connectivity');
+}
+
+/** Encryption state of the library database */
+export interface DatabaseEncryptionStatus {
+	/** Whether this build supports encrypted databases */
+	available: boolean;
+	encrypted: boolean;
+}
+
+/**
+ * Get whether the library database is encrypted
+ *
+ * @returns Encryption support and state
+ */
+export async function getDatabaseEncryptionStatus(): Promise<DatabaseEncryptionStatus> {
+	return tauriInvoke<DatabaseEncryptionStatus>('get_database_encryption_status');
+}
+
+/**
+ * Encrypt the library database, or change its passphrase
+ * The passphrase is kept in the OS keyring so the library opens at startup.
+ *
+ * @param currentPassphrase - The passphrase in use, required if already encrypted
+ * @param newPassphrase - The passphrase to encrypt with (at least 8 characters)
+ * @returns The new encryption state
+ */
+export async function setDatabasePassphrase(
+	currentPassphrase: string | null,
+	newPassphrase: string
+): Promise<DatabaseEncryptionStatus> {
+	return tauriInvoke<DatabaseEncryptionStatus>('set_database_passphrase', {
+		currentPassphrase,
+		newPassphrase
+	});
+}
+
+/**
+ * Decrypt the library database back to a plain SQLite file
+ *
+ * @param currentPassphrase - The passphrase in use
+ * @returns The new encryption state
+ */
+export async function removeDatabasePassphrase(
+	currentPassphrase: string
+): Promise<DatabaseEncryptionStatus> {
+	return tauriInvoke<DatabaseEncryptionStatus>('remove_database_passphrase', {
+		currentPassphrase
+	});
+}
+
+/** Database file and schema state */
+export interface DatabaseDiagnostics {
+	path: string;
+	/** Size on disk in bytes, including the write-ahead log */
+	size_bytes: number;
+	/** Schema version stored in the database (null if unreadable) */
+	schema_version: number | null;
+	/** Schema version this build of the app expects */
+	expected_schema_version: number;
+	/** Personas whose token order has gaps or duplicates (see repairTokenOrder) */
+	denormalized_token_orders: number;
+}
+
+/** Tokenizer loading and count cache state */
+export interface TokenizerDiagnostics {
+	/** Tokenizer IDs loaded in memory */
+	loaded: string[];
+	/** Tokenizer IDs currently loading in the background */
+	loading: string[];
+	/** Number of token counts in the persistent cache */
+	cached_counts: number;
+}
+
+/** API key status for one AI provider */
+export interface ProviderDiagnostics {
+	provider: AiProvider;
+	requires_api_key: boolean;
+	has_stored_key: boolean;
+	has_env_key: boolean;
+}
+
+/** Aggregated status of all subsystems */
+export interface SystemDiagnostics {
+	app_version: string;
+	database: DatabaseDiagnostics;
+	credential_store_available: boolean;
+	tokenizers: TokenizerDiagnostics;
+	ai_providers: ProviderDiagnostics[];
+	offline: boolean;
+	proxy_source: ProxySource;
+	/** ISO timestamp of the last database export, if any */
+	last_backup_at: string | null;
+}
+
+/**
+ * Get the status of every subsystem in one report, for support requests
+ *
+ * @returns Database, credential store, tokenizer, provider key, network and backup status
+ */
+export async function getSystemDiagnostics(): Promise<SystemDiagnostics> {
+	return tauriInvoke<SystemDiagnostics>('get_system_diagnostics');
+}
+
+/** A schema migration run recorded in the migration history */
+export interface MigrationRecord {
+	/** Schema version the migration upgrades to */
+	version: number;
+	description: string;
+	/** ISO timestamp of the run */
+	applied_at: string;
+	duration_ms: number;
+	/** Backup of the database taken before the upgrade, if any */
+	backup_path: string | null;
+	/** Why the migration failed; null if it succeeded */
+	error: string | null;
+}
+
+/**
+ * Get the schema migrations that ran on this database, most recent first
+ *
+ * @returns Migration runs, including failed ones, with their pre-upgrade backup
+ */
+export async function getMigrationHistory(): Promise<MigrationRecord[]> {
+	return tauriInvoke<MigrationRecord[]>('get_migration_history');
+}
+
+/** Where the last application update stands */
+export type UpdateOutcome = 'pending' | 'succeeded' | 'rolled_back';
+
+/** Record of the last application update */
+export interface UpdateReport {
+	from_version: string;
+	to_version: string;
+	/** Copy of the database taken right before installing */
+	snapshot_path: string;
+	/** ISO timestamp of the snapshot */
+	started_at: string;
+	outcome: UpdateOutcome;
+	/** ISO timestamp of the database check by the new version, if it has started */
+	checked_at: string | null;
+	/** Why the database check failed; null unless rolled back */
+	error: string | null;
+}
+
+/**
+ * Get the record of the last application update
+ *
+ * @returns The update record, or null if the app was never updated
+ */
+export async function getUpdateReport(): Promise<UpdateReport | null> {
+	return tauriInvoke<UpdateReport | null>('get_update_report');
+}
+
+/** An update offered by the release server */
+export interface AvailableUpdate {
+	version: string;
+	current_version: string;
+	/** Release date, as published */
+	date: string | null;
+	/** Release notes, as published */
+	notes: string | null;
+}
+
+/**
+ * Ask the release server for a newer version
+ *
+ * Fails in builds made without the updater and its signing key.
+ *
+ * @returns The available update, or null if this version is current
+ */
+export async function checkForUpdate(): Promise<AvailableUpdate | null> {
+	return tauriInvoke<AvailableUpdate | null>('check_for_update');
+}
+
+/**
+ * Download and install the available update, then restart the app
+ *
+ * The database is snapshotted before installing and restored by the new
+ * version if it cannot be upgraded (see {@link getUpdateReport}).
+ */
+export async function installUpdate(): Promise<void> {
+	return tauriInvoke('install_update');
+}
+
+/**
+ * Change the log level; the level is kept for future launches
+ *
+ * @param level - Minimum level of events to record
+ */
+export async function setLogLevel(level: LogLevel): Promise<void> {
+	return tauriInvoke('set_log_level', { level });
+}
+
+/**
+ * Renumber the tokens of every persona whose display orders have gaps or duplicates
+ *
+ * @returns Number of personas whose tokens were renumbered
+ */
+export async function repairTokenOrder(): Promise<number> {
+	return tauriInvoke<number>('repair_token_order');
+}
+
+/**
+ * Save all log files as a zip archive for a bug report
+ * Opens a native save dialog for choosing the destination.
+ *
+ * @returns Result with the archive path, or cancelled if the dialog was dismissed
+ */
+export async function collectLogsZip(): Promise<ExportResult> {
+	return tauriInvoke<ExportResult>('collect_logs_zip');
+}
+
+/** A feature whose use is counted by telemetry */
+export type Feature =
+	| 'create_persona'
+	| 'create_from_template'
+	| 'persona_wizard'
+	| 'duplicate_persona'
+	| 'compare_personas'
+	| 'compose_prompt'
+	| 'prompt_matrix'
+	| 'lint_prompt'
+	| 'ai_token_suggestions'
+	| 'ai_generate_persona'
+	| 'ai_style_transfer'
+	| 'ai_blend_personas'
+	| 'ai_verify_image'
+	| 'ai_scheduled_refresh'
+	| 'apply_token_pack'
+	| 'find_replace'
+	| 'revert_token'
+	| 'export_personas'
+	| 'import_personas'
+	| 'share_code'
+	| 'persona_sheet'
+	| 'library_site'
+	| 'share_session'
+	| 'quick_search'
+	| 'smart_collection';
+
+/** How often one feature was used */
+export interface FeatureUsage {
+	feature: Feature;
+	count: number;
+	/** Day of the first counted use (YYYY-MM-DD) */
+	first_used_on: string;
+	/** Day of the last counted use (YYYY-MM-DD) */
+	last_used_on: string;
+}
+
+/** Everything telemetry has collected, exactly as it would be shared */
+export interface UsageReport {
+	enabled: boolean;
+	app_version: string;
+	/** Counters, most used first */
+	features: FeatureUsage[];
+}
+
+/**
+ * Get the feature usage counters collected so far
+ * Nothing is sent anywhere; this shows exactly what telemetry holds.
+ *
+ * @returns Whether telemetry is enabled and the counters
+ */
+export async function getUsageReport(): Promise<UsageReport> {
+	return tauriInvoke<UsageReport>('get_usage_report');
+}
+
+/**
+ * Delete all feature usage counters
+ */
+export async function clearUsageData(): Promise<void> {
+	return tauriInvoke('clear_usage_data');
+}
@@ -0,0 +1,37 @@
+/**
+ * Suggestion service - Tauri IPC wrapper for the inbox of AI-suggested tokens
+ *
+ * Token suggestions requested for a persona and scheduled refreshes land here
+ * and stay until accepted or rejected, across window reloads.
+ */
+
+import { tauriInvoke } from './tauri';
+import type { PendingSuggestion, Token } from '$lib/types';
+
+/** Event emitted with a SuggestionsStaged payload when new suggestions were staged */
+export const SUGGESTIONS_STAGED_EVENT = 'suggestions-staged';
+
+/** List pending suggestions, newest first, optionally for one persona */
+export async function listPendingSuggestions(personaId?: string): Promise<PendingSuggestion[]> {
+	return tauriInvoke<PendingSuggestion[]>('list_pending_suggestions', {
+		personaId: personaId ?? null
+	});
+}
+
+/**
+ * Append pending suggestions to their personas as tokens
+ *
+ * @returns The created tokens, in acceptance order
+ */
+export async function acceptPendingSuggestions(ids: string[]): Promise<Token[]> {
+	return tauriInvoke<Token[]>('accept_pending_suggestions', { ids });
+}
+
+/**
+ * Discard pending suggestions
+ *
+ * @returns The number of suggestions rejected
+ */
+export async function rejectPendingSuggestions(ids: string[]): Promise<number> {
+	return tauriInvoke<number>('reject_pending_suggestions', { ids });
+}
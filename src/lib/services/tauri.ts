@@ -6,6 +6,7 @@
  */
 
 import { invoke } from '@tauri-apps/api/core';
+import type { AppError, ConflictError } from '$lib/types';
 
 /**
  * Invoke a Tauri command with typed parameters and return value
@@ -24,3 +25,22 @@ export async function tauriInvoke<T>(command: string, args?: Record<string, unkn
 		throw error;
 	}
 }
+
+/**
+ * Check whether a command error is an AppError from the backend
+ *
+ * @param error - The error thrown by tauriInvoke
+ */
+export function isAppError(error: unknown): error is AppError {
+	return typeof error === 'object' && error !== null && 'code' in error && 'error' in error;
+}
+
+/**
+ * Check whether a command error is a ConflictError
+ * Update commands fail this way when `expected_updated_at` is stale.
+ *
+ * @param error - The error thrown by tauriInvoke
+ */
+export function isConflictError<T>(error: unknown): error is ConflictError<T> {
+	return typeof error === 'object' && error !== null && 'error' in error && 'current' in error;
+}
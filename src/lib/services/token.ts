@@ -2,13 +2,24 @@
  * Token service - Tauri IPC wrapper for token operations
  */
 
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
+import { getCurrentWindow } from '@tauri-apps/api/window';
 import { tauriInvoke } from './tauri';
 import type {
+	BannedTerm,
+	FindReplaceResult,
+	ReplaceScope,
 	Token,
+	TokenAlias,
+	TokenChanged,
+	TokenCompletion,
+	TokenPack,
+	TokenRevision,
 	CreateTokenRequest,
 	UpdateTokenRequest,
 	GranularityLevel,
-	ReorderTokensRequest
+	ReorderTokensRequest,
+	GeneratedTokenSelection
 } from '$lib/types';
 
 /** Create a new token */
@@ -16,16 +27,70 @@ export async function createToken(request: CreateTokenRequest): Promise<Token> {
 	return tauriInvoke<Token>('create_token', { request });
 }
 
+/** Save accepted AI suggestions as tokens in one transactional call */
+export async function applyGeneratedTokens(
+	personaId: string,
+	selections: GeneratedTokenSelection[]
+): Promise<Token[]> {
+	return tauriInvoke<Token[]>('apply_generated_tokens', { personaId, selections });
+}
+
 /** Get all tokens for a persona */
 export async function getTokensByPersona(personaId: string): Promise<Token[]> {
 	return tauriInvoke<Token[]>('get_tokens_by_persona', { personaId });
 }
 
+/**
+ * Complete the token being typed: library contents ranked by use, then tags from the
+ * tag dictionaries in the app settings. Optionally only library tokens of one granularity.
+ */
+export async function autocompleteTokens(
+	prefix: string,
+	granularityId?: string,
+	limit?: number
+): Promise<TokenCompletion[]> {
+	return tauriInvoke<TokenCompletion[]>('autocomplete_tokens', {
+		prefix,
+		granularityId: granularityId ?? null,
+		limit: limit ?? null
+	});
+}
+
 /** Update a token */
 export async function updateToken(id: string, request: UpdateTokenRequest): Promise<Token> {
 	return tauriInvoke<Token>('update_token', { id, request });
 }
 
+/** Get a token's previous content and weight values, most recent first */
+export async function getTokenHistory(tokenId: string): Promise<TokenRevision[]> {
+	return tauriInvoke<TokenRevision[]>('get_token_history', { tokenId });
+}
+
+/** Restore a token's content and weight from a revision (recorded as a new edit) */
+export async function revertToken(tokenId: string, revision: number): Promise<Token> {
+	return tauriInvoke<Token>('revert_token', { tokenId, revision });
+}
+
+/**
+ * Replace text in token content across a persona, collection, or the library.
+ * With dryRun, only previews; otherwise applies all replacements in one transaction.
+ */
+export async function findReplaceTokens(
+	scope: ReplaceScope,
+	find: string,
+	replace: string,
+	regex: boolean,
+	dryRun: boolean
+): Promise<FindReplaceResult> {
+	return tauriInvoke<FindReplaceResult>('find_replace_tokens', {
+		scope,
+		find,
+		replace,
+		regex,
+		dryRun
+	});
+}
+
 /** Delete a token */
 export async function deleteToken(id: string): Promise<void> {
 	return tauriInvoke<void>('delete_token', { id });
@@ -40,3 +105,81 @@ export async function getGranularityLevels(): Promise<GranularityLevel[]> {
 export async function reorderTokens(request: ReorderTokensRequest): Promise<void> {
 	return tauriInvoke<void>('reorder_tokens', { request });
 }
+
+/**
+ * Reorder the tokens of one granularity level; the backend recomputes the
+ * global order and leaves other levels in place. Returns all tokens in order.
+ */
+export async function reorderWithinGranularity(
+	personaId: string,
+	granularityId: string,
+	orderedTokenIds: string[]
+): Promise<Token[]> {
+	return tauriInvoke<Token[]>('reorder_within_granularity', {
+		personaId,
+		granularityId,
+		orderedTokenIds
+	});
+}
+
+/**
+ * Propose a heuristic token order for a persona (subject first, style early,
+ * detail modifiers later). Nothing is saved; apply the result with reorderTokens.
+ */
+export async function optimizeTokenOrder(personaId: string): Promise<ReorderTokensRequest> {
+	return tauriInvoke<ReorderTokensRequest>('optimize_token_order', { personaId });
+}
+
+/** List the built-in starter token packs (e.g., negatives per model family) */
+export async function listTokenPacks(): Promise<TokenPack[]> {
+	return tauriInvoke<TokenPack[]>('list_token_packs');
+}
+
+/** Add a pack's tokens to a persona, skipping tokens it already has */
+export async function applyTokenPack(personaId: string, packId: string): Promise<Token[]> {
+	return tauriInvoke<Token[]>('apply_token_pack', { personaId, packId });
+}
+
+/** List banned terms, ordered alphabetically */
+export async function listBannedTerms(): Promise<BannedTerm[]> {
+	return tauriInvoke<BannedTerm[]>('list_banned_terms');
+}
+
+/** Ban a word or phrase; tokens containing it can no longer be created */
+export async function addBannedTerm(term: string): Promise<BannedTerm> {
+	return tauriInvoke<BannedTerm>('add_banned_term', { term });
+}
+
+/** Remove a banned term */
+export async function removeBannedTerm(id: string): Promise<void> {
+	return tauriInvoke<void>('remove_banned_term', { id });
+}
+
+/** List token aliases, alphabetically by alias */
+export async function listTokenAliases(): Promise<TokenAlias[]> {
+	return tauriInvoke<TokenAlias[]>('list_token_aliases');
+}
+
+/** Map a token variant to its preferred phrasing (applied with the apply_aliases option) */
+export async function addTokenAlias(alias: string, canonical: string): Promise<TokenAlias> {
+	return tauriInvoke<TokenAlias>('add_token_alias', { alias, canonical });
+}
+
+/** Remove a token alias */
+export async function removeTokenAlias(id: string): Promise<void> {
+	return tauriInvoke<void>('remove_token_alias', { id });
+}
+
+/**
+ * Subscribe to token changes made in other windows
+ *
+ * @returns Function that removes the listener
+ */
+export async function onTokenChanged(
+	callback: (payload: TokenChanged) => void
+): Promise<UnlistenFn> {
+	const label = getCurrentWindow().label;
+	return listen<TokenChanged>('token-changed', (event) => {
+		if (event.payload.source_window !== label) callback(event.payload);
+	});
+}
@@ -4,8 +4,22 @@
  * Provides model-aware token counting for various image generation models.
  */
 
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
 import { tauriInvoke } from './tauri';
-import type { TokenCount, TokenizerInfo } from '$lib/types';
+import type {
+	CivitaiModel,
+	GeneratedTokenSelection,
+	HubModel,
+	LabeledText,
+	LabeledTokenCount,
+	LocalResource,
+	ResolutionCheck,
+	ResolutionPresets,
+	TokenCount,
+	TokenizedText,
+	TokenizerInfo,
+	TokenizerLoaded
+} from '$lib/types';
 
 /**
  * Count tokens in a text string for a specific model
@@ -13,6 +27,9 @@ import type { TokenCount, TokenizerInfo } from '$lib/types';
  * @param text - The text to count tokens for
  * @param modelId - Optional model ID. If not provided, uses the default model
  *                  from the backend (see `getDefaultImageModelId()` in config service).
+ *
+ * If the tokenizer is still loading, the result is an estimate (`estimated: true`);
+ * recount after `onTokenizerLoaded` fires with `available: true`.
  */
 export async function countTokens(text: string, modelId?: string): Promise<TokenCount> {
 	return tauriInvoke<TokenCount>('count_tokens_for_model', {
@@ -21,6 +38,36 @@ export async function countTokens(text: string, modelId?: string): Promise<Token
 	});
 }
 
+/**
+ * Count tokens in several labeled texts in one IPC round trip
+ *
+ * @param texts - Texts to count, each with a label echoed back in the result
+ * @param modelId - Optional model ID. If not provided, uses the default model.
+ * @returns Counts in the same order as `texts`
+ */
+export async function countTokensBatch(
+	texts: LabeledText[],
+	modelId?: string
+): Promise<LabeledTokenCount[]> {
+	return tauriInvoke<LabeledTokenCount[]>('count_tokens_batch', {
+		texts,
+		modelId: modelId ?? null
+	});
+}
+
+/**
+ * Split text into the model's actual tokens, for highlighting token boundaries
+ *
+ * Offsets are UTF-8 byte offsets; render pieces in order using their `text`
+ * rather than slicing the JS string. May take seconds if the tokenizer is not loaded.
+ *
+ * @param text - The text to tokenize
+ * @param modelId - Optional model ID. If not provided, uses the default model.
+ */
+export async function tokenizeText(text: string, modelId?: string): Promise<TokenizedText> {
+	return tauriInvoke<TokenizedText>('tokenize_text', { text, modelId: modelId ?? null });
+}
+
 /**
  * Get list of all known model mappings
  *
@@ -29,3 +76,106 @@ export async function countTokens(text: string, modelId?: string): Promise<Token
 export async function getKnownModels(): Promise<TokenizerInfo[]> {
 	return tauriInvoke<TokenizerInfo[]>('get_known_image_models');
 }
+
+/**
+ * Get recommended Style-level quality tokens for a model
+ *
+ * Empty for natural-language models (e.g., FLUX, PixArt), where quality tags hurt.
+ *
+ * @param modelId - Optional model ID. If not provided, uses the default model.
+ */
+export async function suggestQualityTokens(modelId?: string): Promise<GeneratedTokenSelection[]> {
+	return tauriInvoke<GeneratedTokenSelection[]>('suggest_quality_tokens', {
+		modelId: modelId ?? null
+	});
+}
+
+/**
+ * List the native output resolutions for a model
+ *
+ * @param modelId - Optional model ID. If not provided, uses the default model.
+ */
+export async function listResolutionPresets(modelId?: string): Promise<ResolutionPresets> {
+	return tauriInvoke<ResolutionPresets>('list_resolution_presets', { modelId: modelId ?? null });
+}
+
+/**
+ * Check whether an output resolution is native for a model
+ *
+ * @param modelId - Optional model ID. If not provided, uses the default model.
+ * @param width - Output width in pixels
+ * @param height - Output height in pixels
+ */
+export async function checkResolution(
+	modelId: string | undefined,
+	width: number,
+	height: number
+): Promise<ResolutionCheck> {
+	return tauriInvoke<ResolutionCheck>('check_resolution', {
+		modelId: modelId ?? null,
+		width,
+		height
+	});
+}
+
+/**
+ * Start loading tokenizers in the background so the first count is fast
+ *
+ * @param modelIds - Models whose tokenizers to load. Defaults to the default model.
+ */
+export async function warmupTokenizers(modelIds?: string[]): Promise<void> {
+	return tauriInvoke('warmup_tokenizers', { modelIds: modelIds ?? null });
+}
+
+/**
+ * Subscribe to background tokenizer loads finishing
+ *
+ * @returns Function that removes the listener
+ */
+export async function onTokenizerLoaded(
+	callback: (payload: TokenizerLoaded) => void
+): Promise<UnlistenFn> {
+	return listen<TokenizerLoaded>('tokenizer-loaded', (event) => callback(event.payload));
+}
+
+/**
+ * Look up a checkpoint or LoRA on Civitai by the hash of its file
+ *
+ * @param hash - SHA-256, BLAKE3, AutoV1, or AutoV2 hash of the model file
+ * @returns The matching model version, or null if Civitai doesn't know the hash
+ */
+export async function lookupCivitaiModelByHash(hash: string): Promise<CivitaiModel | null> {
+	return tauriInvoke<CivitaiModel | null>('lookup_civitai_model_by_hash', { hash });
+}
+
+/**
+ * Search Civitai checkpoints and LoRAs by name
+ *
+ * @param query - Name or part of a name
+ * @param limit - Maximum number of results (default: 10, at most 50)
+ */
+export async function searchCivitaiModels(query: string, limit?: number): Promise<CivitaiModel[]> {
+	return tauriInvoke<CivitaiModel[]>('search_civitai_models', { query, limit: limit ?? null });
+}
+
+/**
+ * Search the HuggingFace Hub for model repositories, most downloaded first
+ *
+ * @param query - Repo ID or part of one
+ * @param pipelineTag - Task to filter on (default: "text-to-image")
+ * @returns Up to 20 repositories, each with the tokenizer the app would use
+ */
+export async function searchHfModels(query: string, pipelineTag?: string): Promise<HubModel[]> {
+	return tauriInvoke<HubModel[]>('search_hf_models', { query, pipelineTag: pipelineTag ?? null });
+}
+
+/**
+ * List the embeddings and LoRA models in the folders set in the app settings
+ *
+ * Every file is hashed, so this can take a while for large LoRA folders.
+ *
+ * @returns Embeddings, then LoRA models, each with its trigger and file hashes
+ */
+export async function scanLocalResources(): Promise<LocalResource[]> {
+	return tauriInvoke<LocalResource[]>('scan_local_resources');
+}
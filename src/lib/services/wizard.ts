@@ -0,0 +1,50 @@
+/**
+ * Wizard service - Tauri IPC wrapper for step-by-step persona creation
+ *
+ * Drafts are saved after every step, so an unfinished wizard can be resumed
+ * after the app was closed.
+ */
+
+import { tauriInvoke } from './tauri';
+import type {
+	AiProviderConfig,
+	Persona,
+	PersonaWizard,
+	StartPersonaWizardRequest,
+	WizardStepRequest
+} from '$lib/types';
+
+/** Start the wizard for a new persona */
+export async function startPersonaWizard(
+	request: StartPersonaWizardRequest
+): Promise<PersonaWizard> {
+	return tauriInvoke<PersonaWizard>('start_persona_wizard', { request });
+}
+
+/** List unfinished wizard drafts, most recently changed first */
+export async function listPersonaWizards(): Promise<PersonaWizard[]> {
+	return tauriInvoke<PersonaWizard[]>('list_persona_wizards');
+}
+
+/**
+ * Submit a wizard step; with request.ai_fill_count, the AI adds tokens to it
+ *
+ * @param config - AI provider configuration, required for AI fill-in
+ */
+export async function submitWizardStep(
+	id: string,
+	request: WizardStepRequest,
+	config?: AiProviderConfig
+): Promise<PersonaWizard> {
+	return tauriInvoke<PersonaWizard>('submit_wizard_step', { id, request, config: config ?? null });
+}
+
+/** Create the persona of a completed wizard and delete the draft */
+export async function finishWizard(id: string): Promise<Persona> {
+	return tauriInvoke<Persona>('finish_wizard', { id });
+}
+
+/** Delete a wizard draft without creating its persona */
+export async function discardPersonaWizard(id: string): Promise<void> {
+	return tauriInvoke<void>('discard_persona_wizard', { id });
+}
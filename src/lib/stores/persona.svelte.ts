@@ -5,7 +5,14 @@
  */
 
 import * as personaService from '$lib/services/persona';
-import type { Persona, CreatePersonaRequest, UpdatePersonaRequest } from '$lib/types';
+import { isConflictError } from '$lib/services/tauri';
+import type {
+	BulkPersonaPatch,
+	Persona,
+	PersonaChanged,
+	CreatePersonaRequest,
+	UpdatePersonaRequest
+} from '$lib/types';
 
 /** Create a reactive persona store */
 function createPersonaStore() {
@@ -36,7 +43,7 @@ function createPersonaStore() {
 		isLoading = true;
 		error = null;
 		try {
-			const persona = await personaService.getPersona(id);
+			const persona = await personaService.openPersona(id);
 			selectedPersona = persona;
 			return persona;
 		} catch (err) {
@@ -69,13 +76,21 @@ function createPersonaStore() {
 		isLoading = true;
 		error = null;
 		try {
-			const updatedPersona = await personaService.updatePersona(id, request);
-			personas = personas.map((p) => (p.id === id ? updatedPersona : p));
-			if (selectedPersona?.id === id) {
-				selectedPersona = updatedPersona;
-			}
+			// Reject the edit if the persona changed since this window loaded it
+			const expected = personas.find((p) => p.id === id)?.updated_at;
+			const updatedPersona = await personaService.updatePersona(id, {
+				expected_updated_at: expected,
+				...request
+			});
+			replacePersona(updatedPersona);
 			return updatedPersona;
 		} catch (err) {
+			if (isConflictError<Persona>(err)) {
+				// Show the current data so the user can reapply their edit
+				replacePersona(err.current);
+				error = err.error;
+				return null;
+			}
 			error = err instanceof Error ? err.message : 'Failed to update persona';
 			console.error('Failed to update persona:', err);
 			return null;
@@ -84,6 +99,13 @@ function createPersonaStore() {
 		}
 	}
 
+	function replacePersona(persona: Persona): void {
+		personas = personas.map((p) => (p.id === persona.id ? persona : p));
+		if (selectedPersona?.id === persona.id) {
+			selectedPersona = persona;
+		}
+	}
+
 	async function remove(id: string): Promise<boolean> {
 		isLoading = true;
 		error = null;
@@ -103,6 +125,43 @@ function createPersonaStore() {
 		}
 	}
 
+	async function bulkUpdate(ids: string[], patch: BulkPersonaPatch): Promise<boolean> {
+		isLoading = true;
+		error = null;
+		try {
+			const updated = await personaService.bulkUpdatePersonas(ids, patch);
+			for (const persona of updated) {
+				replacePersona(persona);
+			}
+			return true;
+		} catch (err) {
+			error = err instanceof Error ? err.message : 'Failed to update personas';
+			console.error('Failed to update personas:', err);
+			return false;
+		} finally {
+			isLoading = false;
+		}
+	}
+
+	async function bulkRemove(ids: string[]): Promise<boolean> {
+		isLoading = true;
+		error = null;
+		try {
+			await personaService.bulkDeletePersonas(ids);
+			personas = personas.filter((p) => !ids.includes(p.id));
+			if (selectedPersona && ids.includes(selectedPersona.id)) {
+				selectedPersona = null;
+			}
+			return true;
+		} catch (err) {
+			error = err instanceof Error ? err.message : 'Failed to delete personas';
+			console.error('Failed to delete personas:', err);
+			return false;
+		} finally {
+			isLoading = false;
+		}
+	}
+
 	async function duplicate(id: string, newName?: string): Promise<Persona | null> {
 		isLoading = true;
 		error = null;
@@ -119,6 +178,28 @@ function createPersonaStore() {
 		}
 	}
 
+	/** Apply a change made in another window (see onPersonaChanged) */
+	async function applyExternalChange(change: PersonaChanged): Promise<void> {
+		if (change.kind === 'deleted') {
+			personas = personas.filter((p) => p.id !== change.persona_id);
+			if (selectedPersona?.id === change.persona_id) {
+				selectedPersona = null;
+			}
+			return;
+		}
+
+		try {
+			const persona = await personaService.getPersona(change.persona_id);
+			if (personas.some((p) => p.id === persona.id)) {
+				replacePersona(persona);
+			} else {
+				personas = [...personas, persona];
+			}
+		} catch (err) {
+			console.error('Failed to refresh persona:', err);
+		}
+	}
+
 	function clearError(): void {
 		error = null;
 	}
@@ -147,7 +228,10 @@ function createPersonaStore() {
 		create,
 		update,
 		remove,
+		bulkUpdate,
+		bulkRemove,
 		duplicate,
+		applyExternalChange,
 		clearError
 	};
 }
@@ -7,6 +7,7 @@
 import * as tokenService from '$lib/services/token';
 import type {
 	Token,
+	TokenChanged,
 	GranularityLevel,
 	BatchCreateTokenRequest,
 	UpdateTokenRequest,
@@ -69,6 +70,21 @@ function createTokenStore() {
 		}
 	}
 
+	/**
+	 * Reload tokens changed in another window (see onTokenChanged)
+	 *
+	 * Ignored while a draft is open; committing it reports conflicting edits.
+	 */
+	async function applyExternalChange(change: TokenChanged): Promise<void> {
+		if (change.persona_id !== currentPersonaId || draftState) return;
+
+		try {
+			tokens = await tokenService.getTokensByPersona(change.persona_id);
+		} catch (err) {
+			console.error('Failed to reload tokens:', err);
+		}
+	}
+
 	// ==================== Draft Mode Methods ====================
 
 	/**
@@ -272,7 +288,10 @@ function createTokenStore() {
 			const updateOps = operations.filter((op) => op.type === 'update');
 			for (const op of updateOps) {
 				if (op.type === 'update') {
-					await tokenService.updateToken(op.id, op.updates);
+					await tokenService.updateToken(op.id, {
+						expected_updated_at: op.originalData.updated_at,
+						...op.updates
+					});
 				}
 			}
 
@@ -349,6 +368,7 @@ function createTokenStore() {
 		// Actions
 		loadGranularityLevels,
 		loadTokensForPersona,
+		applyExternalChange,
 
 		// Draft mode actions
 		startDraft,
@@ -5,20 +5,29 @@
  * persistent preferences (stored in JSON file via tauri-plugin-store).
  *
  * Session preferences: personaListTags (filter state, survives navigation)
- * Persistent preferences: personaListSort (database-backed, survives restart)
+ * Persistent preferences: personaListSort, useEnvCredentials, aiDebugLogging,
+ * tokenizerWarmup (file-backed, survives restart)
  */
 
 import { LazyStore } from '@tauri-apps/plugin-store';
+import { setAiLoggingEnabled } from '$lib/services/ai';
+import { warmupTokenizers } from '$lib/services/tokenizer';
 
 /** Preference keys for file storage */
 const Keys = {
-	PERSONA_LIST_SORT: 'personaListSort'
+	PERSONA_LIST_SORT: 'personaListSort',
+	USE_ENV_CREDENTIALS: 'useEnvCredentials',
+	AI_DEBUG_LOGGING: 'aiDebugLogging',
+	TOKENIZER_WARMUP: 'tokenizerWarmup'
 } as const;
 
 /** Default values for preferences */
 const DEFAULTS = {
 	personaListSort: 'updated_at-desc' as string,
-	personaListTags: [] as string[]
+	personaListTags: [] as string[],
+	useEnvCredentials: false,
+	aiDebugLogging: false,
+	tokenizerWarmup: true
 };
 
 /** Create the UI preferences store */
@@ -27,7 +36,10 @@ function createUiPreferencesStore() {
 	const fileStore = new LazyStore('preferences.json', {
 		autoSave: 100,
 		defaults: {
-			[Keys.PERSONA_LIST_SORT]: DEFAULTS.personaListSort
+			[Keys.PERSONA_LIST_SORT]: DEFAULTS.personaListSort,
+			[Keys.USE_ENV_CREDENTIALS]: DEFAULTS.useEnvCredentials,
+			[Keys.AI_DEBUG_LOGGING]: DEFAULTS.aiDebugLogging,
+			[Keys.TOKENIZER_WARMUP]: DEFAULTS.tokenizerWarmup
 		}
 	});
 
@@ -36,6 +48,9 @@ function createUiPreferencesStore() {
 
 	// === Persistent state (file-backed) ===
 	let personaListSort = $state(DEFAULTS.personaListSort);
+	let useEnvCredentials = $state(DEFAULTS.useEnvCredentials);
+	let aiDebugLogging = $state(DEFAULTS.aiDebugLogging);
+	let tokenizerWarmup = $state(DEFAULTS.tokenizerWarmup);
 
 	// Loading state
 	let isInitialized = $state(false);
@@ -52,6 +67,24 @@ function createUiPreferencesStore() {
 			if (storedSort) {
 				personaListSort = storedSort;
 			}
+			const storedUseEnv = await fileStore.get<boolean>(Keys.USE_ENV_CREDENTIALS);
+			if (storedUseEnv !== undefined) {
+				useEnvCredentials = storedUseEnv;
+			}
+			const storedAiLogging = await fileStore.get<boolean>(Keys.AI_DEBUG_LOGGING);
+			if (storedAiLogging !== undefined) {
+				aiDebugLogging = storedAiLogging;
+			}
+			const storedWarmup = await fileStore.get<boolean>(Keys.TOKENIZER_WARMUP);
+			if (storedWarmup !== undefined) {
+				tokenizerWarmup = storedWarmup;
+			}
+			// The backend keeps this flag in memory only; re-apply it on every launch
+			await setAiLoggingEnabled(aiDebugLogging);
+			// Load the default tokenizer in the background so the first count is fast
+			if (tokenizerWarmup) {
+				await warmupTokenizers();
+			}
 			isInitialized = true;
 		} catch (err) {
 			console.error('Failed to load UI preferences:', err);
@@ -84,6 +117,47 @@ function createUiPreferencesStore() {
 		}
 	}
 
+	/**
+	 * Enable or disable environment-variable credential passthrough.
+	 * When enabled, AI requests read API keys from the environment instead of the keyring.
+	 * Persisted to file - survives app restart.
+	 */
+	async function setUseEnvCredentials(value: boolean): Promise<void> {
+		useEnvCredentials = value;
+		try {
+			await fileStore.set(Keys.USE_ENV_CREDENTIALS, value);
+		} catch (err) {
+			console.error('Failed to save credential preference:', err);
+		}
+	}
+
+	/**
+	 * Enable or disable the AI request/response debug log.
+	 * Persisted to file - survives app restart.
+	 */
+	async function setAiDebugLogging(value: boolean): Promise<void> {
+		aiDebugLogging = value;
+		try {
+			await setAiLoggingEnabled(value);
+			await fileStore.set(Keys.AI_DEBUG_LOGGING, value);
+		} catch (err) {
+			console.error('Failed to save AI logging preference:', err);
+		}
+	}
+
+	/**
+	 * Enable or disable loading the tokenizer in the background at startup.
+	 * Takes effect on next launch. Persisted to file - survives app restart.
+	 */
+	async function setTokenizerWarmup(value: boolean): Promise<void> {
+		tokenizerWarmup = value;
+		try {
+			await fileStore.set(Keys.TOKENIZER_WARMUP, value);
+		} catch (err) {
+			console.error('Failed to save tokenizer warmup preference:', err);
+		}
+	}
+
 	return {
 		// State getters
 		get personaListTags() {
@@ -92,6 +166,15 @@ function createUiPreferencesStore() {
 		get personaListSort() {
 			return personaListSort;
 		},
+		get useEnvCredentials() {
+			return useEnvCredentials;
+		},
+		get aiDebugLogging() {
+			return aiDebugLogging;
+		},
+		get tokenizerWarmup() {
+			return tokenizerWarmup;
+		},
 		get isInitialized() {
 			return isInitialized;
 		},
@@ -99,7 +182,10 @@ function createUiPreferencesStore() {
 		// Actions
 		initialize,
 		setPersonaListTags,
-		setPersonaListSort
+		setPersonaListSort,
+		setUseEnvCredentials,
+		setAiDebugLogging,
+		setTokenizerWarmup
 	};
 }
 
@@ -2,6 +2,9 @@
  * AI-related types - TypeScript equivalents of Rust AI types
  */
 
+import type { Persona } from './persona';
+import type { FilteredToken, Token, TokenPolarity } from './token';
+
 /**
  * AI provider identifier string.
  *
@@ -29,8 +32,32 @@ export interface AiProviderMetadata {
 export interface AiProviderConfig {
 	provider: AiProvider;
 	model: string;
-	api_key?: string | null;
 	base_url?: string | null;
+	/** Read the API key from the provider's environment variable instead of the keyring */
+	use_env_credentials?: boolean;
+}
+
+/** Queue position of a rate-limited AI request (payload of the ai-queue-status event) */
+export interface AiQueueStatus {
+	provider: AiProvider;
+	/** Requests still ahead in the queue (0 once the request starts) */
+	position: number;
+}
+
+/** A recorded AI exchange from the opt-in debug log (API keys redacted) */
+export interface AiLogEntry {
+	/** ISO timestamp of when the request was sent */
+	timestamp: string;
+	provider: AiProvider;
+	model: string;
+	/** Operation that issued the request (e.g., persona_generation) */
+	operation: string;
+	system_prompt: string;
+	user_prompt: string;
+	/** Raw response text, if any was received */
+	response: string | null;
+	/** Request or parse error, if the exchange failed */
+	error: string | null;
 }
 
 /** A single generated token */
@@ -43,6 +70,12 @@ export interface GeneratedToken {
 	rationale?: string | null;
 	/** Granularity category (only set for persona generation, not ad-hoc token generation) */
 	granularity_id?: string | null;
+	/** Encoder tokens this token costs in the target model's prompt (weight syntax included) */
+	token_cost?: number | null;
+	/** Prompt size once this and all preceding suggestions are added */
+	cumulative_tokens?: number | null;
+	/** Whether accepting this token (with those before it) would exceed the model's budget */
+	exceeds_budget?: boolean;
 }
 
 /** Request to generate tokens for a persona */
@@ -77,6 +110,12 @@ export interface TokenGenerationRequest {
 	negative_token_count?: number | null;
 	/** Maximum usable tokens */
 	max_usable_tokens?: number | null;
+	/** Language of the rationales (the persona's setting); the global setting applies when null */
+	output_language?: string | null;
+	/** Persona whose pending suggestions keep the result (requires granularity_id) */
+	persona_id?: string | null;
+	/** Granularity level ID the result is kept under (requires persona_id) */
+	granularity_id?: string | null;
 }
 
 /** Response from token generation */
@@ -89,6 +128,8 @@ export interface TokenGenerationResponse {
 	provider: AiProvider;
 	/** Model used */
 	model: string;
+	/** Suggestions left out because they contain a banned term */
+	filtered: FilteredToken[];
 }
 
 // ============================================================================
@@ -237,6 +278,8 @@ export interface AiPersonaGenerationRequest {
 	improveInstructionsViaAi?: boolean;
 	/** Whether to skip AI description generation when no description provided (default: false) */
 	skipAiDescription?: boolean;
+	/** Language of the description and rationales; also saved on the persona by createPersonaFromAi */
+	outputLanguage?: string | null;
 }
 
 /** Response from AI persona generation */
@@ -249,8 +292,122 @@ export interface AiPersonaGenerationResponse {
 	tags: string[];
 	/** Generated tokens in AI-recommended optimal order. Each token includes granularity_id for categorization. */
 	tokens: GeneratedToken[];
+	/** Planned and tokenizer-verified budget for each granularity */
+	granularityBudgets: GranularityBudget[];
 	/** Provider that handled the request */
 	provider: AiProvider;
 	/** Model used for generation */
 	model: string;
+	/** Tokens left out because they contain a banned term */
+	filtered: FilteredToken[];
+}
+
+/** A persona created end-to-end from AI generation, as saved to the database */
+export interface AiCreatedPersona {
+	/** The saved persona, including its AI configuration */
+	persona: Persona;
+	/** The saved tokens, in AI-recommended order */
+	tokens: Token[];
+	/** Planned and tokenizer-verified budget for each granularity */
+	granularityBudgets: GranularityBudget[];
+	/** Generated tokens left out because they contain a banned term */
+	filtered: FilteredToken[];
+}
+
+/** Token budget planned for a granularity and the verified usage after trimming */
+export interface GranularityBudget {
+	/** Granularity level ID (e.g., "hair", "face") */
+	granularityId: string;
+	/** Encoder tokens allocated by the planning pass */
+	allocatedTokens: number;
+	/** Encoder tokens used by the kept tokens */
+	usedTokens: number;
+}
+
+// ============================================================================
+// Style Transfer Types
+// ============================================================================
+
+/** Request to adapt one persona's tokens to another persona's style */
+export interface StyleTransferRequest {
+	/** Persona whose Style tokens define the style to apply */
+	sourcePersonaId: string;
+	/** Persona whose tokens are adapted */
+	targetPersonaId: string;
+	/** Whether to also use the source persona's description as style context */
+	includeSourceDescription?: boolean;
+}
+
+/** A proposed change to one of the target persona's tokens */
+export interface TokenStyleChange {
+	/** UUID of the token to change */
+	tokenId: string;
+	/** Granularity level ID of the token */
+	granularityId: string;
+	/** Polarity of the token */
+	polarity: TokenPolarity;
+	/** Current token text */
+	originalContent: string;
+	/** Current token weight */
+	originalWeight: number;
+	/** Proposed token text */
+	proposedContent: string;
+	/** Proposed token weight */
+	proposedWeight: number;
+	/** AI's explanation for the change */
+	rationale?: string | null;
+}
+
+/** Style transfer proposal to review before applying; unchanged tokens are omitted */
+export interface StyleTransferProposal {
+	/** Persona the style was taken from */
+	sourcePersonaId: string;
+	/** Persona whose tokens would change */
+	targetPersonaId: string;
+	/** Proposed per-token changes, in the target's token order */
+	changes: TokenStyleChange[];
+	/** Provider that handled the request */
+	provider: AiProvider;
+	/** Model used for generation */
+	model: string;
+}
+
+/** What a vision model saw of one persona token in an image */
+export interface TokenPresence {
+	/** UUID of the token */
+	tokenId: string;
+	/** Token text */
+	content: string;
+	polarity: TokenPolarity;
+	/** Whether the token's content is visible in the image */
+	present: boolean;
+	/** Whether the image agrees with the token: positive present, negative absent */
+	consistent: boolean;
+	/** AI's remark on what it saw */
+	note: string | null;
+}
+
+/** Consistency of one granularity level's tokens with an image */
+export interface GranularityConsistency {
+	granularityId: string;
+	/** Share of the level's tokens the image agrees with (0-1) */
+	score: number;
+	/** Per-token results, in the persona's token order */
+	tokens: TokenPresence[];
+}
+
+/** Result of checking a generated image against a persona; tokens without a verdict are left out */
+export interface ImageConsistencyReport {
+	personaId: string;
+	imagePath: string;
+	/** Share of all checked tokens the image agrees with (0-1) */
+	score: number;
+	/** Results per granularity level */
+	granularities: GranularityConsistency[];
+	/** AI's overall impression of the image */
+	summary: string | null;
+	/** Provider that handled the request */
+	provider: AiProvider;
+	/** Model used for the check */
+	model: string;
 }
@@ -0,0 +1,35 @@
+/**
+ * Persona blending types - TypeScript equivalents of Rust blend types
+ */
+
+import type { UUID } from './common';
+import type { GeneratedTokenSelection } from './token';
+
+/** How parent personas are combined */
+export type BlendMode = 'interleave' | 'ai';
+
+/** Request to blend several personas into a new draft */
+export interface PersonaBlendRequest {
+	/** Parent persona IDs (at least two, no duplicates) */
+	persona_ids: UUID[];
+	/** Relative weight per parent, in the same order; omit for equal weights */
+	weights?: number[];
+	/** How the parents are combined (default: 'interleave') */
+	mode?: BlendMode;
+	/** Name for the draft; derived from the parents' names if omitted */
+	name?: string | null;
+	/** Guidance on which traits to inherit from which parent ('ai' mode only) */
+	inherit_traits?: string | null;
+}
+
+/** Unsaved persona produced by blending; save with createPersona then applyGeneratedTokens */
+export interface PersonaBlendDraft {
+	name: string;
+	description: string | null;
+	/** Tags inherited from the parents, heaviest parent first */
+	tags: string[];
+	/** Tokens in the order they should be saved */
+	tokens: GeneratedTokenSelection[];
+	parent_ids: UUID[];
+	mode: BlendMode;
+}
@@ -0,0 +1,67 @@
+/**
+ * Smart collection types - TypeScript equivalents of Rust collection types
+ */
+
+import type { ISODateString, UUID } from './common';
+
+/** Filter selecting personas; unset criteria match everything */
+export interface PersonaQuery {
+	/** Terms that must each be contained in one of the persona's tags (case-insensitive) */
+	tags?: string[];
+	/** Namespaces that must each hold one of the persona's tags ("project" holds "project/a") */
+	namespaces?: string[];
+	/** Model family of the persona's image model (e.g., "sdxl", "sd15") */
+	model_family?: string | null;
+	/** Only personas edited or composed within this many days */
+	used_within_days?: number | null;
+	/** Whether archived personas can match */
+	include_archived?: boolean;
+}
+
+/** A named, saved persona query whose members are computed on demand */
+export interface SmartCollection {
+	id: UUID;
+	name: string;
+	query: PersonaQuery;
+	/** Label color as #rrggbb */
+	color: string | null;
+	created_at: ISODateString;
+	updated_at: ISODateString;
+}
+
+export interface CreateSmartCollectionRequest {
+	name: string;
+	query: PersonaQuery;
+	/** Label color as #rrggbb */
+	color?: string | null;
+}
+
+/** Omitted fields are kept */
+export interface UpdateSmartCollectionRequest {
+	name?: string;
+	query?: PersonaQuery;
+	/** Label color as #rrggbb; null clears it */
+	color?: string | null;
+}
+
+/** Request to share a smart collection read-only in a web browser */
+export interface StartShareRequest {
+	collection_id: UUID;
+	/** Open the server to the local network instead of this computer only */
+	lan?: boolean;
+	/** Minutes until the link stops working (default 60, at most 1440) */
+	duration_minutes?: number | null;
+}
+
+/** A running share session; only one runs at a time */
+export interface ShareSession {
+	/** Secret token contained in the URL */
+	token: string;
+	collection_id: UUID;
+	collection_name: string;
+	/** Address to send to the collaborator */
+	url: string;
+	lan: boolean;
+	started_at: ISODateString;
+	expires_at: ISODateString;
+}
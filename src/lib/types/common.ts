@@ -7,3 +7,61 @@ export type ISODateString = string;
 
 /** UUID string type */
 export type UUID = string;
+
+/** Stable category of a command error */
+export type AppErrorCode =
+	| 'database'
+	| 'not_found'
+	| 'validation'
+	| 'io'
+	| 'serialization'
+	| 'offline'
+	| 'conflict'
+	| 'schema_too_new'
+	| 'encryption'
+	| 'internal';
+
+/** Error returned by a failed command */
+export interface AppError {
+	/** Stable category; its message template is `error.<code>` in the message catalog */
+	code: AppErrorCode;
+	/** Human-readable message in the backend's display locale */
+	error: string;
+}
+
+/**
+ * Error returned when an update was based on stale data
+ * (the entity changed elsewhere since it was read)
+ */
+export interface ConflictError<T> extends AppError {
+	code: 'conflict';
+	/** The entity as currently stored, for merging */
+	current: T;
+}
+
+/** Relation of the frontend's IPC API version to the backend's */
+export type ApiCompatibility =
+	| 'compatible'
+	| 'deprecated'
+	| 'frontend_too_old'
+	| 'frontend_too_new';
+
+/** Version information about the running backend */
+export interface ApiVersionInfo {
+	/** Version of the IPC command interface */
+	api_version: number;
+	/** Oldest frontend API version still served */
+	min_supported_api_version: number;
+	/** Application version of the backend build */
+	app_version: string;
+}
+
+/** Result of the startup handshake between frontend and backend */
+export interface ApiHandshake {
+	compatibility: ApiCompatibility;
+	/** Whether the frontend can use the backend */
+	usable: boolean;
+	backend: ApiVersionInfo;
+	/** Explanation for the user when the versions differ */
+	message: string | null;
+}
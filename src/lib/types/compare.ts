@@ -0,0 +1,73 @@
+/**
+ * Persona comparison types - TypeScript equivalents of Rust compare types
+ */
+
+import type { UUID } from './common';
+import type { Persona } from './persona';
+import type { Token, TokenPolarity } from './token';
+
+/** Structured diff between persona A and persona B (see comparePersonas) */
+export interface PersonaComparison {
+	persona_a_id: UUID;
+	persona_b_id: UUID;
+	/** Metadata fields that differ (name, description, AI settings) */
+	metadata: FieldDifference[];
+	/** Generation parameters that differ */
+	params: FieldDifference[];
+	tags: TagComparison;
+	tokens: TokenComparison;
+}
+
+/** A field whose value differs between the two personas */
+export interface FieldDifference {
+	/** Field name (e.g., "description", "cfg_scale") */
+	field: string;
+	/** Value in persona A (null if unset) */
+	a: string | number | null;
+	/** Value in persona B (null if unset) */
+	b: string | number | null;
+}
+
+/** Tags split by which persona has them (compared case-insensitively) */
+export interface TagComparison {
+	/** Tags on both personas, as spelled on A */
+	shared: string[];
+	only_a: string[];
+	only_b: string[];
+}
+
+/** Tokens split by which persona has them */
+export interface TokenComparison {
+	/** Tokens on both personas with the same weight */
+	shared: TokenMatch[];
+	/** Tokens only on persona A, in A's display order */
+	only_a: Token[];
+	/** Tokens only on persona B, in B's display order */
+	only_b: Token[];
+	/** Tokens on both personas with different weights */
+	weight_differences: TokenMatch[];
+}
+
+/** A token present on both personas (same granularity, polarity, and content) */
+export interface TokenMatch {
+	granularity_id: string;
+	polarity: TokenPolarity;
+	/** Token content, as written on A */
+	content: string;
+	token_a_id: UUID;
+	token_b_id: UUID;
+	weight_a: number;
+	weight_b: number;
+}
+
+/** A persona ranked by similarity to another (see findSimilarPersonas) */
+export interface SimilarPersona {
+	persona: Persona;
+	/** 0 (nothing shared) to 1; weighs token overlap at 0.8 and tag overlap at 0.2 */
+	score: number;
+	/** Jaccard index of the token sets (case, accents, and whitespace ignored) */
+	token_similarity: number;
+	shared_token_count: number;
+	/** Shared tags, as spelled on the similar persona */
+	shared_tags: string[];
+}
@@ -0,0 +1,32 @@
+/**
+ * Persona draft types - TypeScript equivalents of Rust draft types
+ */
+
+import type { ISODateString, UUID } from './common';
+import type { ContentRating } from './persona';
+import type { GeneratedTokenSelection } from './token';
+
+/** An autosaved, possibly incomplete persona; never listed with personas */
+export interface PersonaDraft {
+	id: UUID;
+	/** Name as typed so far; may be empty or taken */
+	name: string;
+	description: string | null;
+	tags: string[];
+	content_rating: ContentRating;
+	/** Tokens added so far, in order */
+	tokens: GeneratedTokenSelection[];
+	created_at: ISODateString;
+	updated_at: ISODateString;
+}
+
+/** The create form's current contents, saved as a draft */
+export interface SaveDraftRequest {
+	/** Draft to update; omit on the first save */
+	id?: UUID | null;
+	name?: string;
+	description?: string | null;
+	tags?: string[];
+	content_rating?: ContentRating;
+	tokens?: GeneratedTokenSelection[];
+}
@@ -1,7 +1,12 @@
 /**
- * Export/Import types for SQLite database operations
+ * Export/Import types for SQLite database and JSON persona operations
  */
 
+import type { ISODateString } from './common';
+import type { GenerationParams, Persona } from './persona';
+import type { CompositionDefaults } from './prompt';
+import type { TokenPolarity } from './token';
+
 /** Result of a database export operation */
 export interface ExportResult {
 	/** Whether the export completed successfully */
@@ -21,3 +26,111 @@ export interface ImportResult {
 	/** Error message (if failed) */
 	error?: string | null;
 }
+
+/** A token as stored in a JSON persona export */
+export interface ExportedToken {
+	granularity_id: string;
+	polarity: TokenPolarity;
+	content: string;
+	weight: number;
+	display_order: number;
+}
+
+/** A single persona with its generation parameters, composition defaults, and tokens */
+export interface PersonaExport {
+	persona: Persona;
+	generation_params?: GenerationParams | null;
+	composition_defaults?: CompositionDefaults | null;
+	tokens: ExportedToken[];
+}
+
+/** JSON export document containing one or more personas */
+export interface BulkExport {
+	/** Format version */
+	version: number;
+	exported_at: ISODateString;
+	personas: PersonaExport[];
+}
+
+/** A problem found while validating a JSON persona export */
+export interface ExportValidationError {
+	/** JSON Pointer to the offending value (e.g., "/personas/0/weight"); empty for the document */
+	path: string;
+	message: string;
+}
+
+/** Options controlling how a JSON persona import is merged */
+export interface PersonaImportOptions {
+	/** Skip personas whose content matches an existing persona, regardless of name */
+	skip_duplicates?: boolean;
+	/** Update the existing persona with the same ID in place instead of importing a copy */
+	update_existing?: boolean;
+	/** Target level for each granularity ID the library does not have (see previewImport) */
+	granularity_mapping?: Record<string, GranularityMappingTarget>;
+}
+
+/**
+ * Where tokens of an unknown granularity level go on import: an existing level,
+ * or a new custom level with the unknown ID (name defaults to the ID)
+ */
+export type GranularityMappingTarget =
+	| { action: 'existing'; granularity_id: string }
+	| { action: 'create_new'; name?: string | null };
+
+/** A granularity level used in an import file but missing from the library */
+export interface UnknownGranularity {
+	granularity_id: string;
+	token_count: number;
+	/** Names of the personas whose tokens use it */
+	persona_names: string[];
+}
+
+/** What a JSON persona import would do, computed without changing anything */
+export interface ImportPreview {
+	persona_count: number;
+	token_count: number;
+	/** IDs of personas that already exist (updated in place with update_existing) */
+	existing_ids: string[];
+	/** Granularity levels that must be mapped before importing */
+	unknown_granularities: UnknownGranularity[];
+}
+
+/** A persona left out of an import */
+export interface SkippedPersona {
+	name: string;
+	reason: string;
+}
+
+/** Result of a JSON persona import */
+export interface PersonaImportResult {
+	imported: Persona[];
+	/** Existing personas updated in place (see update_existing) */
+	updated: Persona[];
+	skipped: SkippedPersona[];
+}
+
+/** The tokens of one granularity section of a persona (e.g., all Hair tokens) */
+export interface SectionSnippet {
+	/** Format version */
+	version: number;
+	exported_at: ISODateString;
+	/** Name of the persona the section was copied from */
+	source_persona: string;
+	/** Granularity level ID of the section */
+	granularity_id: string;
+	tokens: ExportedToken[];
+}
+
+/** Options controlling how a section snippet is imported */
+export interface SectionImportOptions {
+	/** Delete the persona's existing tokens in the section first */
+	replace?: boolean;
+}
+
+/**
+ * Which parts of a persona a printed sheet shows:
+ * - full: everything, including negative tokens and generation parameters
+ * - client: for commission clients, without the technical setup
+ * - compact: tokens and prompts with a single reference image
+ */
+export type SheetTemplate = 'full' | 'client' | 'compact';
@@ -0,0 +1,98 @@
+/**
+ * Result gallery types - TypeScript equivalents of Rust gallery types
+ */
+
+import type { ISODateString, UUID } from './common';
+import type { PersonaExport } from './export';
+import type { GenerationParams } from './persona';
+
+/** An image generated for a persona, with what produced it */
+export interface GeneratedImage {
+	id: UUID;
+	persona_id: UUID;
+	/** Absolute path of the image file */
+	path: string;
+	/** SHA-256 of the trimmed positive and negative prompts */
+	prompt_hash: string;
+	/** Positive prompt as sent to the image generator, if recorded */
+	positive_prompt: string | null;
+	/** Negative prompt as sent to the image generator, if recorded */
+	negative_prompt: string | null;
+	/** Seed the image was generated with, if known */
+	seed: number | null;
+	/** Generation parameters at the time, if known */
+	params: GenerationParams | null;
+	/** Marked as a favorite; kept by purges unless includeStarred is set */
+	starred: boolean;
+	created_at: ISODateString;
+}
+
+/** Request to add an existing image file to a persona's gallery */
+export interface AttachImageRequest {
+	persona_id: UUID;
+	/** Absolute path of the image file */
+	path: string;
+	positive_prompt: string;
+	negative_prompt?: string;
+	/** Seed actually used; defaults to the parameters' seed unless it is random */
+	seed?: number | null;
+	/** Parameters used; defaults to the persona's current ones */
+	params?: GenerationParams | null;
+}
+
+/** Everything needed to reproduce a gallery image (see exportReproductionBundle) */
+export interface ReproductionBundle {
+	/** Format version */
+	version: number;
+	exported_at: ISODateString;
+	image_id: UUID;
+	/** Path of the image file when it was attached */
+	image_path: string;
+	generated_at: ISODateString;
+	positive_prompt: string | null;
+	negative_prompt: string | null;
+	prompt_hash: string;
+	/** Image model the prompt was written for, if known */
+	model_id: string | null;
+	seed: number | null;
+	params: GenerationParams | null;
+	/** The persona as it was when the image was attached */
+	persona: PersonaExport | null;
+}
+
+/** A group of gallery images showing the same face */
+export interface FaceCluster {
+	/** Images in the cluster, oldest first */
+	image_ids: UUID[];
+	/** Mean similarity of the cluster's faces to its mean face (0-1) */
+	cohesion: number;
+}
+
+/** How one image's face compares to the persona's main face */
+export interface ImageFaceSimilarity {
+	image_id: UUID;
+	created_at: ISODateString;
+	/** Index of the image's cluster in the report's clusters */
+	cluster: number;
+	/** Cosine similarity to the mean face of the largest cluster */
+	similarity: number;
+}
+
+/** Gallery images grouped by face (see analyzeGalleryConsistency) */
+export interface GalleryConsistencyReport {
+	persona_id: UUID;
+	/** Similarity at which faces were grouped together */
+	threshold: number;
+	/** Clusters, largest first; the first is taken as the persona's face */
+	clusters: FaceCluster[];
+	/** Analyzed images, oldest first */
+	images: ImageFaceSimilarity[];
+	/** Share of analyzed images in the largest cluster */
+	main_share: number;
+	/** Whether the largest cluster holds less than 80% of the analyzed images */
+	drifting: boolean;
+	/** Images in which no face was found */
+	without_face: UUID[];
+	/** Images without an embedding (no embedder set, embedder failed, or another dimension) */
+	not_analyzed: UUID[];
+}
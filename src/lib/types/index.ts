@@ -1,8 +1,18 @@
 // Re-export all types
 export * from './ai';
+export * from './blend';
+export * from './collection';
 export * from './common';
+export * from './compare';
+export * from './draft';
 export * from './export';
+export * from './gallery';
 export * from './persona';
 export * from './prompt';
+export * from './schedule';
+export * from './search';
+export * from './stats';
+export * from './suggestion';
 export * from './token';
 export * from './tokenizer';
+export * from './wizard';
@@ -3,6 +3,8 @@
  */
 
 import type { ISODateString, UUID } from './common';
+import type { PromptPreview } from './prompt';
+import type { GeneratedTokenSelection, GranularityLevel, Token } from './token';
 
 /** A Persona represents a complete fictional character profile */
 export interface Persona {
@@ -16,10 +18,38 @@ export interface Persona {
 	ai_model_id: string | null;
 	/** Custom instructions for AI token generation */
 	ai_instructions: string | null;
+	/** Language of AI-written descriptions and rationales; null follows the global setting */
+	ai_output_language: string | null;
 	created_at: ISODateString;
 	updated_at: ISODateString;
+	/** Kept, but hidden from everyday lists */
+	archived: boolean;
+	/** Label color as #rrggbb */
+	color: string | null;
+	/** Mature personas are hidden in safe mode */
+	content_rating: ContentRating;
+	/** Base persona this variant inherits from (see setBasePersona) */
+	base_persona_id: UUID | null;
+	/** Granularity level IDs whose tokens come from the base */
+	inherited_granularities: string[];
+	/** Generation parameters set on this variant instead of inherited */
+	param_overrides: ParamField[];
 }
 
+/** Audience rating of a persona */
+export type ContentRating = 'general' | 'mature';
+
+/** A generation parameter a variant can override; resolution covers width and height */
+export type ParamField =
+	| 'model_id'
+	| 'seed'
+	| 'steps'
+	| 'cfg_scale'
+	| 'sampler'
+	| 'scheduler'
+	| 'resolution'
+	| 'conditioning';
+
 /** Generation parameters for image generation */
 export interface GenerationParams {
 	persona_id: UUID;
@@ -30,6 +60,38 @@ export interface GenerationParams {
 	sampler: string | null;
 	/** Scheduler algorithm (e.g., "karras", "exponential", "normal") */
 	scheduler: string | null;
+	/** Output width in pixels (null for the model's default) */
+	width?: number | null;
+	/** Output height in pixels (null for the model's default) */
+	height?: number | null;
+	/** ControlNet and IP-Adapter units applied on top of the prompt, in order */
+	conditioning?: ConditioningEntry[];
+}
+
+/** Kind of image conditioning */
+export type ConditioningKind = 'controlnet' | 'ip_adapter';
+
+/** A reference image applied through ControlNet or IP-Adapter */
+export interface ConditioningEntry {
+	kind: ConditioningKind;
+	/** Model file name (e.g., "control_v11p_sd15_openpose.pth") */
+	model: string;
+	/** Preprocessor run on the reference image (e.g., "openpose"), if any */
+	preprocessor?: string | null;
+	/** Absolute path of the reference image */
+	image_path: string;
+	/** Strength of the conditioning (0–2) */
+	weight: number;
+}
+
+/** A generation setting that is valid but likely to give poor results */
+export interface ParamWarning {
+	/** Setting the warning is about: 'cfg_scale', 'steps', or 'resolution' */
+	field: string;
+	/** Stable message code (e.g., 'param.steps_out_of_range') */
+	code: string;
+	/** Explanation in the backend's display locale */
+	message: string;
 }
 
 /** Request to create a new persona */
@@ -37,6 +99,29 @@ export interface CreatePersonaRequest {
 	name: string;
 	description?: string | null;
 	tags?: string[];
+	/** Defaults to 'general' */
+	content_rating?: ContentRating;
+}
+
+/** Activity recorded for a persona (see listRecentPersonas) */
+export type ActivityKind = 'opened' | 'modified' | 'composed';
+
+/** A persona with the time of its latest activity of the requested kind */
+export interface RecentPersona {
+	persona: Persona;
+	at: ISODateString;
+}
+
+/** Result of checking a persona name before saving it (see checkPersonaName) */
+export interface NameCheck {
+	/** The name as it would be stored (trimmed, whitespace collapsed) */
+	normalized: string;
+	/** Whether the name is valid and unused */
+	available: boolean;
+	/** Why the name cannot be used, if it breaks a naming rule */
+	error: string | null;
+	/** Available alternatives, empty if the name is available */
+	suggestions: string[];
 }
 
 /** Request to update an existing persona */
@@ -47,4 +132,73 @@ export interface UpdatePersonaRequest {
 	ai_provider_id?: string | null;
 	ai_model_id?: string | null;
 	ai_instructions?: string | null;
+	/** Language name such as 'German'; null clears it */
+	ai_output_language?: string | null;
+	archived?: boolean;
+	/** Label color as #rrggbb; null clears it */
+	color?: string | null;
+	content_rating?: ContentRating;
+	/** updated_at the edit is based on; a stale value fails with a ConflictError */
+	expected_updated_at?: ISODateString | null;
+}
+
+/** Changes applied to every persona of a bulk update (see bulkUpdatePersonas) */
+export interface BulkPersonaPatch {
+	/** Tags to add (skipped where already present, ignoring case) */
+	add_tags?: string[];
+	/** Tags to remove (matched ignoring case) */
+	remove_tags?: string[];
+	/** Omit to keep, null to clear */
+	ai_provider_id?: string | null;
+	/** Omit to keep, null to clear */
+	ai_model_id?: string | null;
+	archived?: boolean;
+	content_rating?: ContentRating;
+}
+
+/** Everything a read-only presentation of a persona displays (see getPersonaFull) */
+export interface PersonaFull {
+	persona: Persona;
+	/** Null if the persona has no saved parameters */
+	generation_params: GenerationParams | null;
+	/** All tokens in display order */
+	tokens: Token[];
+	/** Granularity levels in display order */
+	granularity_levels: GranularityLevel[];
+	/** The composed prompt and its copy formats */
+	prompt: PromptPreview;
+}
+
+/** A built-in persona archetype (see createPersonaFromTemplate) */
+export interface PersonaTemplate {
+	/** Stable identifier (e.g., "fantasy_knight") */
+	id: string;
+	name: string;
+	description: string;
+	tags: string[];
+	/** Tokens created for the persona, in display order */
+	tokens: GeneratedTokenSelection[];
+}
+
+/** What happened to a persona in a PersonaChanged event */
+export type ChangeKind = 'created' | 'updated' | 'deleted';
+
+/** Payload of the persona-changed event (see onPersonaChanged) */
+export interface PersonaChanged {
+	persona_id: string;
+	kind: ChangeKind;
+	/** Label of the window that made the change */
+	source_window: string;
+}
+
+/** A node of the tag tree: one segment of a namespaced tag like "project/clientA" */
+export interface TagNode {
+	/** Last segment of the path (e.g., "clientA") */
+	name: string;
+	/** Full tag path (e.g., "project/clientA") */
+	path: string;
+	/** Number of personas tagged with this path or a tag below it */
+	persona_count: number;
+	/** Tags directly below this one, sorted by name */
+	children: TagNode[];
 }
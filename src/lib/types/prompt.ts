@@ -2,16 +2,99 @@
  * Prompt composition types - TypeScript equivalents of Rust domain types
  */
 
+import type { FilteredToken } from './token';
+
 /** Position for ad-hoc tokens in the composed prompt */
 export type AdhocPosition = 'beginning' | 'end';
 
+/**
+ * Token ordering strategy for composition:
+ * - 'global': drag-and-drop display order
+ * - 'granularity_then_order': grouped by granularity level (Style first), then display order
+ * - { custom: [...] }: listed granularity IDs first, in that order; the rest in display order
+ */
+export type TokenOrder = 'global' | 'granularity_then_order' | { custom: string[] };
+
+/**
+ * Handling of negative tokens when the image model ignores negative prompts:
+ * - 'warn': compose the negative prompt anyway and flag it
+ * - 'suppress': leave the negative prompt empty
+ */
+export type UnsupportedNegativeMode = 'warn' | 'suppress';
+
+/**
+ * How token weights are written:
+ * - 'a1111': (token:1.2), read by A1111, Forge, and ComfyUI
+ * - 'compel': (token)1.2, read by Compel (diffusers, InvokeAI)
+ */
+export type WeightSyntax = 'a1111' | 'compel';
+
+/**
+ * Prompt a granularity's positive tokens go to when composing dual prompts:
+ * - 'primary': prompt only (CLIP ViT-L)
+ * - 'secondary': prompt_2 only (OpenCLIP bigG for SDXL, T5 for FLUX)
+ * - 'both': both prompts
+ */
+export type PromptTarget = 'primary' | 'secondary' | 'both';
+
+/** Settings for composing a separate prompt_2 for the second text encoder */
+export interface DualPromptOptions {
+	/** Target prompt by granularity ID; unlisted granularities go to both (default: {}) */
+	routing?: Record<string, PromptTarget>;
+	/** Separator between tokens in prompt_2 (default: ". ") */
+	separator?: string;
+	/** Whether to include weight modifiers in prompt_2 (default: false) */
+	include_weights?: boolean;
+}
+
+/**
+ * Syntax used to split a prompt into regions:
+ * - 'regional_prompter': common prompt + ADDCOMM, regions separated by BREAK (A1111, Forge)
+ * - 'attention_couple': base prompt, then one AND subprompt per region
+ */
+export type RegionalSyntax = 'regional_prompter' | 'attention_couple';
+
+/** An area of the image and the granularities described in it */
+export interface PromptRegion {
+	/** Region name, for display (e.g., "Face") */
+	name: string;
+	/** Granularity IDs whose tokens go to this region */
+	granularity_ids: string[];
+}
+
+/** Settings for splitting the positive prompt into regions */
+export interface RegionalOptions {
+	/** Regions in area order; granularities in no region are common to all */
+	regions: PromptRegion[];
+	/** Syntax to emit (default: 'regional_prompter') */
+	syntax?: RegionalSyntax;
+}
+
+/** A persona's own composition settings, used when no options are given */
+export interface CompositionDefaults {
+	/** Separator between tokens (e.g., ", " for tags, ". " for sentences) */
+	separator: string;
+	/** Whether to include weight modifiers in the output */
+	include_weights: boolean;
+	/** How weights are written (default: 'a1111') */
+	weight_syntax?: WeightSyntax;
+	/** Dual prompt settings, if the persona composes a separate prompt_2 */
+	dual_prompt?: DualPromptOptions | null;
+}
+
 /** A composed prompt ready for use in image generation */
 export interface ComposedPrompt {
 	positive_prompt: string;
+	/** Prompt for the second text encoder (prompt_2), if dual prompts were requested */
+	positive_prompt_2: string | null;
 	negative_prompt: string;
 	positive_token_count: number;
 	negative_token_count: number;
 	breakdown: PromptBreakdown;
+	/** Whether the persona's image model ignores the negative prompt */
+	negative_prompt_unsupported: boolean;
+	/** Stored tokens left out because they contain a banned term */
+	filtered_tokens: FilteredToken[];
 }
 
 /** Breakdown of the prompt by granularity level */
@@ -28,6 +111,69 @@ export interface GranularitySection {
 	negative_tokens: string[];
 }
 
+/** Copy-ready encodings of a composed prompt */
+export interface PromptFormats {
+	/** "Positive:" and "Negative:" blocks, with a "Positive (prompt_2):" block for dual prompts */
+	plain: string;
+	/** AUTOMATIC1111 infotext (prompt, "Negative prompt:" line, settings line) */
+	a1111: string;
+	/** ComfyUI API-format JSON with a text encoding node per prompt */
+	comfyui: string;
+	/** Pretty-printed JSON of the individual parts and breakdown */
+	json: string;
+}
+
+/** A composed prompt together with every copy format */
+export interface PromptPreview {
+	composed: ComposedPrompt;
+	formats: PromptFormats;
+}
+
+/** One varying aspect of a prompt matrix, with its alternatives */
+export interface MatrixAxis {
+	/** Name used in the labels (e.g., "hairstyle") */
+	name: string;
+	/** Granularity level the alternatives belong to; unset adds them as ad-hoc tokens */
+	granularity_id?: string | null;
+	alternatives: string[];
+}
+
+/** One combination of alternatives and its composed prompt */
+export interface PromptMatrixEntry {
+	/** The chosen alternatives (e.g., "hairstyle: ponytail / outfit: sundress") */
+	label: string;
+	/** Index of the chosen alternative on each axis */
+	choices: number[];
+	prompt: ComposedPrompt;
+}
+
+/** Every combination of a persona's matrix axes, composed */
+export interface PromptMatrix {
+	persona_id: string;
+	/** The axes, with blank alternatives removed */
+	axes: MatrixAxis[];
+	/** One entry per combination, last axis varying fastest */
+	entries: PromptMatrixEntry[];
+	formats: {
+		/** One positive prompt per line */
+		lines: string;
+		/** One line per prompt for the A1111 "Prompts from file or textbox" script */
+		a1111_script: string;
+	};
+}
+
+/** The last composed prompt stored for a persona */
+export interface CachedPrompt {
+	persona_id: string;
+	/** Hash of the composition options that produced the prompt */
+	options_hash: string;
+	positive_prompt: string;
+	negative_prompt: string;
+	positive_token_count: number;
+	negative_token_count: number;
+	created_at: string;
+}
+
 /** Options for prompt composition */
 export interface CompositionOptions {
 	/** Whether to include weight modifiers in the output */
@@ -42,4 +188,41 @@ export interface CompositionOptions {
 	adhoc_negative?: string | null;
 	/** Where to place ad-hoc tokens */
 	adhoc_position?: AdhocPosition;
+	/** How tokens are ordered (default: 'global') */
+	order_by?: TokenOrder;
+	/** Negative prompt handling for models that ignore it (default: 'warn') */
+	unsupported_negative?: UnsupportedNegativeMode;
+	/** How weights are written (default: 'a1111') */
+	weight_syntax?: WeightSyntax;
+	/** Also compose prompt_2 for the second text encoder (default: off) */
+	dual_prompt?: DualPromptOptions | null;
+	/** Split the positive prompt into image regions (default: off) */
+	regional?: RegionalOptions | null;
+	/** Replace aliased tokens with their preferred phrasing (default: off) */
+	apply_aliases?: boolean;
+}
+
+/** Kind of problem reported by the prompt linter */
+export type LintCode =
+	| 'duplicate_concept'
+	| 'conflicting_weights'
+	| 'prompt_too_long'
+	| 'negative_in_positive'
+	| 'trailing_separator'
+	| 'empty_segment'
+	| 'malformed_weight'
+	| 'unsupported_negative_prompt';
+
+/** How serious a lint issue is */
+export type LintSeverity = 'info' | 'warning' | 'error';
+
+/** A single problem found by the prompt linter */
+export interface LintIssue {
+	code: LintCode;
+	severity: LintSeverity;
+	/** Prompt the problem was found in */
+	target: 'positive' | 'negative';
+	message: string;
+	/** The offending prompt segment, if the issue concerns one */
+	segment: string | null;
 }
@@ -0,0 +1,58 @@
+/**
+ * Scheduled task types - TypeScript equivalents of Rust schedule types
+ */
+
+import type { AiProviderConfig } from './ai';
+import type { ISODateString, UUID } from './common';
+
+/** What a scheduled task does */
+export type ScheduledTaskKind = 'style_refresh';
+
+/** How often a scheduled task runs */
+export type ScheduleFrequency = 'daily' | 'weekly' | 'monthly';
+
+/** A recurring background task */
+export interface ScheduledTask {
+	id: UUID;
+	name: string;
+	kind: ScheduledTaskKind;
+	/** Personas the task refreshes */
+	persona_ids: UUID[];
+	frequency: ScheduleFrequency;
+	/** AI provider and model used; the API key is read from the keyring */
+	config: AiProviderConfig;
+	/** Suggestions requested per persona and run */
+	token_count: number;
+	/** Runs on schedule; disabled tasks only run when started manually */
+	enabled: boolean;
+	last_run_at: ISODateString | null;
+	next_run_at: ISODateString;
+	/** Personas whose refresh failed in the last run, with the reason */
+	last_error: string | null;
+	created_at: ISODateString;
+	updated_at: ISODateString;
+}
+
+/** Request to create a scheduled task */
+export interface CreateScheduledTaskRequest {
+	name: string;
+	kind?: ScheduledTaskKind;
+	persona_ids: UUID[];
+	/** Defaults to 'weekly' */
+	frequency?: ScheduleFrequency;
+	config: AiProviderConfig;
+	/** Defaults to 5 (at most 20) */
+	token_count?: number;
+	/** Defaults to true */
+	enabled?: boolean;
+}
+
+/** Request to update a scheduled task; omitted fields are kept */
+export interface UpdateScheduledTaskRequest {
+	name?: string;
+	persona_ids?: UUID[];
+	frequency?: ScheduleFrequency;
+	config?: AiProviderConfig;
+	token_count?: number;
+	enabled?: boolean;
+}
@@ -0,0 +1,33 @@
+/**
+ * Quick search types - TypeScript equivalents of Rust search types
+ */
+
+import type { UUID } from './common';
+import type { Token } from './token';
+
+/** Type of entity a search result points to */
+export type SearchResultKind = 'persona' | 'token' | 'collection' | 'template';
+
+/** A single quick search result (see quickSearch) */
+export interface SearchResult {
+	kind: SearchResultKind;
+	/** ID of the persona, token, collection, or template */
+	id: string;
+	/** Persona the entity belongs to (personas and tokens only) */
+	persona_id: UUID | null;
+	/** Name or token content */
+	title: string;
+	/** Persona description, or the token's persona name */
+	subtitle: string | null;
+	/** Relevance; higher is better. Template matches score 0 and come last */
+	rank: number;
+}
+
+/** A token found by advanced token search (see searchTokens) */
+export interface TokenSearchMatch {
+	token: Token;
+	/** Name of the persona the token belongs to */
+	persona_name: string;
+	/** Whether the persona is archived */
+	persona_archived: boolean;
+}
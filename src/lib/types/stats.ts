@@ -0,0 +1,41 @@
+/**
+ * Library statistics types - TypeScript equivalents of Rust stats types
+ */
+
+/** Token counts of one granularity level */
+export interface GranularityTokenCount {
+	granularity_id: string;
+	/** Level display name (the ID for levels that no longer exist) */
+	name: string;
+	positive: number;
+	negative: number;
+}
+
+/** Number of personas with a tag */
+export interface TagCount {
+	tag: string;
+	count: number;
+}
+
+/** Number of personas targeting a model family */
+export interface ModelFamilyCount {
+	/** Model family identifier (sdxl, pixart, flux, ...) */
+	family: string;
+	count: number;
+}
+
+/** Aggregate counts over the library, for the dashboard (see getLibraryStats) */
+export interface LibraryStats {
+	/** Number of personas, including archived ones */
+	persona_count: number;
+	archived_persona_count: number;
+	token_count: number;
+	/** 0 without personas */
+	avg_tokens_per_persona: number;
+	/** In granularity level order */
+	tokens_per_granularity: GranularityTokenCount[];
+	/** Most used first */
+	tag_histogram: TagCount[];
+	/** Most used first */
+	personas_per_model_family: ModelFamilyCount[];
+}
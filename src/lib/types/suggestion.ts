@@ -0,0 +1,34 @@
+/**
+ * Pending suggestion types - TypeScript equivalents of Rust suggestion types
+ */
+
+import type { ISODateString, UUID } from './common';
+import type { TokenPolarity } from './token';
+
+/** What produced a pending suggestion */
+export type SuggestionSource = 'token_suggestions' | 'scheduled_refresh';
+
+/** An AI-suggested token awaiting review */
+export interface PendingSuggestion {
+	id: UUID;
+	persona_id: UUID;
+	source: SuggestionSource;
+	/** Scheduled task that staged it, if any; null once the task is deleted */
+	task_id: UUID | null;
+	granularity_id: string;
+	polarity: TokenPolarity;
+	content: string;
+	weight: number;
+	rationale: string | null;
+	created_at: ISODateString;
+}
+
+/** Payload of SUGGESTIONS_STAGED_EVENT */
+export interface SuggestionsStaged {
+	source: SuggestionSource;
+	/** Scheduled task whose run staged the suggestions, if any */
+	task_id: UUID | null;
+	/** Personas that received new suggestions */
+	persona_ids: UUID[];
+	count: number;
+}
@@ -50,12 +50,48 @@ export interface BatchCreateTokenRequest {
 	weight?: number;
 }
 
+/** An accepted AI suggestion to save as a token (see applyGeneratedTokens) */
+export interface GeneratedTokenSelection {
+	granularity_id: string;
+	polarity: TokenPolarity;
+	content: string;
+	weight?: number;
+}
+
 /** Request to update an existing token */
 export interface UpdateTokenRequest {
 	content?: string;
 	weight?: number;
 	granularity_id?: string;
 	polarity?: TokenPolarity;
+	/** updated_at the edit is based on; a stale value fails with a ConflictError */
+	expected_updated_at?: ISODateString | null;
+}
+
+/** Content and weight a token had before one of its edits */
+export interface TokenRevision {
+	/** Token UUID */
+	token_id: string;
+	/** Revision number, increasing with each edit of the token */
+	revision: number;
+	/** Content before the edit */
+	content: string;
+	/** Weight before the edit */
+	weight: number;
+	/** When the edit was made */
+	recorded_at: ISODateString;
+}
+
+/** A built-in set of tokens for a model family (see applyTokenPack) */
+export interface TokenPack {
+	/** Stable identifier (e.g., "sdxl_negatives") */
+	id: string;
+	name: string;
+	description: string;
+	/** Model families the pack is tuned for (e.g., "sd15", "sdxl") */
+	model_families: string[];
+	/** Tokens added to the persona, in display order */
+	tokens: GeneratedTokenSelection[];
 }
 
 /** Single token ordering update within a reorder request */
@@ -123,3 +159,75 @@ export interface TokenDraftState {
 	/** Current view state - computed from original + pending */
 	draftTokens: Token[];
 }
+
+/** Payload of the token-changed event (see onTokenChanged) */
+export interface TokenChanged {
+	/** Persona whose tokens changed */
+	persona_id: string;
+	/** Label of the window that made the change */
+	source_window: string;
+}
+
+/** A word or phrase that tokens must not contain (whole words, case-insensitive) */
+export interface BannedTerm {
+	id: string;
+	/** The banned word or phrase, unique ignoring case */
+	term: string;
+	created_at: string;
+}
+
+/** A token variant and the phrasing it stands for */
+export interface TokenAlias {
+	id: string;
+	/** The variant to replace (e.g., 'ginger hair'), unique ignoring case */
+	alias: string;
+	/** The phrasing to use instead (e.g., 'red hair') */
+	canonical: string;
+	created_at: string;
+}
+
+/** A token left out because it contains a banned term */
+export interface FilteredToken {
+	/** The token text */
+	content: string;
+	/** The banned term it contains */
+	term: string;
+}
+
+/** Tokens a find-and-replace operates on */
+export type ReplaceScope =
+	| { kind: 'persona'; persona_id: string }
+	| { kind: 'collection'; collection_id: string }
+	| { kind: 'library' };
+
+/** A token whose content a find-and-replace changes */
+export interface TokenReplacement {
+	token_id: string;
+	persona_id: string;
+	/** Persona name, for display */
+	persona_name: string;
+	before: string;
+	after: string;
+	/** Why the replacement cannot be applied (empty, duplicate, or banned term) */
+	rejected: string | null;
+}
+
+/** Result of findReplaceTokens */
+export interface FindReplaceResult {
+	/** Every changed token, in persona then display order */
+	replacements: TokenReplacement[];
+	/** Whether the replacements were written (false for dry runs or if any is rejected) */
+	applied: boolean;
+}
+
+/** Where a token completion comes from */
+export type CompletionSource = 'library' | 'dictionary';
+
+/** A completion for the token being typed, from autocompleteTokens */
+export interface TokenCompletion {
+	/** Token content to insert */
+	content: string;
+	source: CompletionSource;
+	/** Number of tokens with this content (library), or the tag's post count (dictionary) */
+	count: number;
+}
@@ -5,6 +5,8 @@
  * Rust is the single source of truth for model/tokenizer mappings.
  */
 
+import type { GeneratedTokenSelection } from './token';
+
 /** Token count result with detailed breakdown */
 export interface TokenCount {
 	/** Number of tokens in the text */
@@ -21,6 +23,50 @@ export interface TokenCount {
 	model_id: string;
 	/** The tokenizer used */
 	tokenizer_id: string;
+	/** Whether the count is a word-based estimate (tokenizer still loading or unavailable) */
+	estimated: boolean;
+}
+
+/** A single token produced by the model's tokenizer */
+export interface TokenPiece {
+	/** Vocabulary ID of the token */
+	id: number;
+	/** Vocabulary entry (e.g., "red</w>" for CLIP) */
+	token: string;
+	/** The slice of the input text this token covers */
+	text: string;
+	/** UTF-8 byte offset where the token starts (not a JS string index) */
+	start: number;
+	/** UTF-8 byte offset where the token ends, exclusive */
+	end: number;
+}
+
+/** Text split into tokens, for highlighting token boundaries */
+export interface TokenizedText {
+	pieces: TokenPiece[];
+	count: TokenCount;
+}
+
+/** A text to count, tagged with a caller-chosen label (e.g., "positive", "hair") */
+export interface LabeledText {
+	label: string;
+	text: string;
+}
+
+/** Token count for a labeled text */
+export interface LabeledTokenCount {
+	label: string;
+	count: TokenCount;
+}
+
+/** Payload of the `tokenizer-loaded` event, emitted when a background load finishes */
+export interface TokenizerLoaded {
+	/** Tokenizer ID from HuggingFace */
+	tokenizer_id: string;
+	/** Whether the tokenizer loaded; if false, counts stay estimated */
+	available: boolean;
+	/** Time the load took, in milliseconds */
+	load_ms: number;
 }
 
 /** Information about the tokenizer for a model (fetched from Rust backend) */
@@ -36,3 +82,107 @@ export interface TokenizerInfo {
 	/** Usable tokens (excluding special tokens like BOS/EOS) */
 	usable_tokens: number;
 }
+
+/** A native output resolution */
+export interface ResolutionPreset {
+	width: number;
+	height: number;
+	/** Aspect ratio in lowest terms (e.g., "3:2") */
+	aspect_ratio: string;
+}
+
+/** Resolution options for an image model family */
+export interface ResolutionPresets {
+	/** Model family identifier (e.g., "sdxl", "flux") */
+	family: string;
+	/** Whether any size within the pixel range works, not only the presets */
+	flexible: boolean;
+	/** Width and height must be multiples of this */
+	step: number;
+	/** Smallest recommended pixel count (flexible families) */
+	min_pixels: number;
+	/** Largest recommended pixel count (flexible families) */
+	max_pixels: number;
+	/** Recommended sizes, default first */
+	presets: ResolutionPreset[];
+}
+
+/** Result of checking a resolution against a model */
+export interface ResolutionCheck {
+	/** Whether the resolution is native for the model */
+	supported: boolean;
+	/** Stable code of the warning (e.g., 'resolution.not_native'), if any */
+	warning_code: string | null;
+	/** Why the resolution is off-bucket, if it is, in the backend's display locale */
+	warning: string | null;
+	/** Preset with the closest aspect ratio, if the resolution is off-bucket */
+	suggestion: ResolutionPreset | null;
+}
+
+/** A checkpoint or LoRA version published on Civitai */
+export interface CivitaiModel {
+	/** Civitai model ID */
+	model_id: number;
+	/** Civitai ID of the matched version */
+	version_id: number;
+	/** Model name (e.g., "Juggernaut XL") */
+	name: string;
+	/** Version name (e.g., "v9") */
+	version_name: string;
+	/** Model type as reported by Civitai (e.g., "Checkpoint", "LORA") */
+	model_type: string;
+	/** Base model as reported by Civitai (e.g., "SDXL 1.0", "Pony") */
+	base_model: string;
+	/** Model family the base model belongs to (sd15, sd2, sdxl, flux), if known */
+	family: string | null;
+	/** Words the version was trained with, deduplicated, in published order */
+	trigger_words: string[];
+	/** Trigger words as positive Style-level tokens, ready to add to a persona */
+	suggested_tokens: GeneratedTokenSelection[];
+}
+
+/** A model repository found on the HuggingFace Hub */
+export interface HubModel {
+	/** Repository ID (e.g., "stabilityai/stable-diffusion-xl-base-1.0") */
+	repo_id: string;
+	/** Task the model performs (e.g., "text-to-image"), if declared */
+	pipeline_tag: string | null;
+	/** Library the weights are for (e.g., "diffusers"), if declared */
+	library_name: string | null;
+	/** Model the repository was fine-tuned from or adapts, if declared */
+	base_model: string | null;
+	/** Downloads over the last 30 days */
+	downloads: number;
+	/** Number of likes */
+	likes: number;
+	/** Tokenizer the app uses to count tokens for this model */
+	tokenizer_id: string;
+	/** Maximum prompt tokens for this model */
+	max_tokens: number;
+	/** Model family (e.g., "sdxl", "flux") */
+	family: string;
+	/** Whether the model (or its base model) was recognized; if not, it gets CLIP defaults */
+	recognized: boolean;
+}
+
+/** Kind of a local model resource */
+export type ResourceKind = 'embedding' | 'lora';
+
+/** An embedding or LoRA file found in a resource folder */
+export interface LocalResource {
+	kind: ResourceKind;
+	/** File name without extension, as image generators refer to it */
+	name: string;
+	/** Absolute path of the file */
+	path: string;
+	/** File size in bytes */
+	size: number;
+	/** SHA-256 of the file, uppercase hexadecimal */
+	sha256: string;
+	/** Short hash shown by A1111 and Civitai (first 10 characters of the SHA-256) */
+	auto_v2: string;
+	/** Text that activates the resource in A1111 prompt syntax (e.g., "<lora:name:1>") */
+	trigger: string;
+	/** The trigger as a Style-level token; negative for negative embeddings */
+	suggested_token: GeneratedTokenSelection;
+}
@@ -0,0 +1,46 @@
+/**
+ * Persona wizard types - TypeScript equivalents of Rust wizard types
+ */
+
+import type { ISODateString, UUID } from './common';
+import type { ContentRating } from './persona';
+import type { GeneratedTokenSelection } from './token';
+
+/** A step of the persona creation wizard, in order */
+export type WizardStep = 'style' | 'physique' | 'face' | 'hair';
+
+/** A persona being created with the wizard, stored as a draft */
+export interface PersonaWizard {
+	id: UUID;
+	name: string;
+	description: string | null;
+	tags: string[];
+	content_rating: ContentRating;
+	/** Image model for the generation parameters; null means the default model */
+	image_model_id: string | null;
+	/** Tokens of all submitted steps, in step order */
+	tokens: GeneratedTokenSelection[];
+	/** Steps submitted so far; the first missing one is the current step */
+	completed_steps: WizardStep[];
+	created_at: ISODateString;
+	updated_at: ISODateString;
+}
+
+/** Request to start the persona creation wizard */
+export interface StartPersonaWizardRequest {
+	/** Must be free */
+	name: string;
+	description?: string | null;
+	tags?: string[];
+	content_rating?: ContentRating;
+	image_model_id?: string | null;
+}
+
+/** Request to submit a wizard step */
+export interface WizardStepRequest {
+	step: WizardStep;
+	/** Tokens for the step's granularity levels; at least one positive, unless the AI fills in */
+	tokens?: GeneratedTokenSelection[];
+	/** Tokens the AI adds per granularity level (1-10); requires an AI provider config */
+	ai_fill_count?: number | null;
+}
@@ -0,0 +1,22 @@
+/**
+ * Text matching and sorting that ignore case and accents, so "Élise" is found
+ * by "elise" and sorts next to "Elise" (mirrors the backend's collation)
+ */
+
+/** Collator for display order: the user's locale, ignoring case and accents */
+const collator = new Intl.Collator(undefined, { sensitivity: 'base', numeric: true });
+
+/** Fold text for matching: decompose it, drop accents, and lowercase it */
+export function foldText(text: string): string {
+	return text.normalize('NFKD').replace(/\p{M}/gu, '').toLowerCase();
+}
+
+/** Whether `needle` occurs in `haystack`, ignoring case and accents */
+export function includesFolded(haystack: string, needle: string): boolean {
+	return foldText(haystack).includes(foldText(needle));
+}
+
+/** Compare two names for sorting; names equal under the collator keep a stable order */
+export function compareNames(a: string, b: string): number {
+	return collator.compare(a, b) || (a < b ? -1 : a > b ? 1 : 0);
+}